@@ -18,6 +18,7 @@ use state_processing::per_epoch_processing::base::{
     process_justification_and_finalization as process_justification_and_finalization_base,
     TotalBalances, ValidatorStatus, ValidatorStatuses,
 };
+use state_processing::per_epoch_processing::JustificationAndFinalizationState;
 use state_processing::{
     common::altair::BaseRewardPerIncrement,
     common::update_progressive_balances_cache::initialize_progressive_balances_cache,
@@ -47,42 +48,69 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             .state_root_at_slot(state_slot)?
             .ok_or(BeaconChainError::NoStateForSlot(state_slot))?;
 
-        let state = self
+        let mut state = self
             .get_state(&state_root, Some(state_slot))?
             .ok_or(BeaconChainError::MissingBeaconState(state_root))?;
 
-        match state {
+        self.compute_attestation_rewards_for_state(&mut state, validators)
+    }
+
+    /// Compute the attestation rewards for `state.previous_epoch()`.
+    ///
+    /// The `state` must be from the last slot of the epoch following the one for which rewards
+    /// are being computed, prior to the epoch transition being applied.
+    ///
+    /// The justification and inactivity score changes which are applied to compute the rewards
+    /// are reverted before returning, so that the caller may continue to process the `state`.
+    /// Only caches are left modified.
+    pub fn compute_attestation_rewards_for_state(
+        &self,
+        state: &mut BeaconState<T::EthSpec>,
+        validators: Vec<ValidatorId>,
+    ) -> Result<StandardAttestationRewards, BeaconChainError> {
+        let justification_and_finalization = JustificationAndFinalizationState::new(state);
+        // Cloning the list is cheap since it shares its tree with the original.
+        let inactivity_scores = state.inactivity_scores().ok().cloned();
+
+        let result = match state {
             BeaconState::Base(_) => self.compute_attestation_rewards_base(state, validators),
             BeaconState::Altair(_)
             | BeaconState::Bellatrix(_)
             | BeaconState::Capella(_)
             | BeaconState::Deneb(_)
             | BeaconState::Electra(_) => self.compute_attestation_rewards_altair(state, validators),
+        };
+
+        justification_and_finalization.apply_changes_to_state(state);
+        if let Some(inactivity_scores) = inactivity_scores {
+            *state.inactivity_scores_mut()? = inactivity_scores;
         }
+
+        result
     }
 
     fn compute_attestation_rewards_base(
         &self,
-        mut state: BeaconState<T::EthSpec>,
+        state: &mut BeaconState<T::EthSpec>,
         validators: Vec<ValidatorId>,
     ) -> Result<StandardAttestationRewards, BeaconChainError> {
         let spec = &self.spec;
-        let mut validator_statuses = ValidatorStatuses::new(&state, spec)?;
-        validator_statuses.process_attestations(&state)?;
+        let mut validator_statuses = ValidatorStatuses::new(state, spec)?;
+        validator_statuses.process_attestations(state)?;
 
         process_justification_and_finalization_base(
-            &state,
+            state,
             &validator_statuses.total_balances,
             spec,
         )?
-        .apply_changes_to_state(&mut state);
+        .apply_changes_to_state(state);
 
         let ideal_rewards =
-            self.compute_ideal_rewards_base(&state, &validator_statuses.total_balances)?;
+            self.compute_ideal_rewards_base(state, &validator_statuses.total_balances)?;
 
         let indices_to_attestation_delta = if validators.is_empty() {
             get_attestation_deltas_all(
-                &state,
+                state,
                 &validator_statuses,
                 ProposerRewardCalculation::Exclude,
                 spec,
@@ -91,9 +119,9 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             .enumerate()
             .collect()
         } else {
-            let validator_indices = Self::validators_ids_to_indices(&mut state, validators)?;
+            let validator_indices = Self::validators_ids_to_indices(state, validators)?;
             get_attestation_deltas_subset(
-                &state,
+                state,
                 &validator_statuses,
                 ProposerRewardCalculation::Exclude,
                 &validator_indices,
@@ -139,21 +167,21 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
 
     fn compute_attestation_rewards_altair(
         &self,
-        mut state: BeaconState<T::EthSpec>,
+        state: &mut BeaconState<T::EthSpec>,
         validators: Vec<ValidatorId>,
     ) -> Result<StandardAttestationRewards, BeaconChainError> {
         let spec = &self.spec;
 
         // Build required caches.
-        initialize_epoch_cache(&mut state, spec)?;
-        initialize_progressive_balances_cache(&mut state, spec)?;
+        initialize_epoch_cache(state, spec)?;
+        initialize_progressive_balances_cache(state, spec)?;
         state.build_exit_cache(spec)?;
         state.build_committee_cache(RelativeEpoch::Previous, spec)?;
         state.build_committee_cache(RelativeEpoch::Current, spec)?;
 
         // Calculate ideal_rewards
-        process_justification_and_finalization(&state)?.apply_changes_to_state(&mut state);
-        process_inactivity_updates_slow(&mut state, spec)?;
+        process_justification_and_finalization(state)?.apply_changes_to_state(state);
+        process_inactivity_updates_slow(state, spec)?;
 
         let previous_epoch = state.previous_epoch();
 
@@ -206,9 +234,9 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         let mut total_rewards: Vec<TotalAttestationRewards> = Vec::new();
 
         let validators = if validators.is_empty() {
-            Self::all_eligible_validator_indices(&state, previous_epoch)?
+            Self::all_eligible_validator_indices(state, previous_epoch)?
         } else {
-            Self::validators_ids_to_indices(&mut state, validators)?
+            Self::validators_ids_to_indices(state, validators)?
         };
 
         for &validator_index in &validators {
@@ -72,6 +72,7 @@ use crate::{
     kzg_utils, metrics, AvailabilityPendingExecutedBlock, BeaconChainError, BeaconForkChoiceStore,
     BeaconSnapshot, CachedHead,
 };
+use eth2::lighthouse::SseDataColumnSidecar;
 use eth2::types::{EventKind, SseBlobSidecar, SseBlock, SseExtendedPayloadAttributes};
 use execution_layer::{
     BlockProposalContents, BlockProposalContentsType, BuilderParams, ChainHealth, ExecutionLayer,
@@ -2090,7 +2091,16 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         metrics::inc_counter(&metrics::SYNC_MESSAGE_PROCESSING_REQUESTS);
         let _timer = metrics::start_timer(&metrics::SYNC_MESSAGE_GOSSIP_VERIFICATION_TIMES);
 
-        VerifiedSyncCommitteeMessage::verify(sync_message, subnet_id, self).inspect(|_| {
+        VerifiedSyncCommitteeMessage::verify(sync_message, subnet_id, self).inspect(|v| {
+            // This method is called for API and gossip messages, so this covers all sync committee
+            // message events
+            if let Some(event_handler) = self.event_handler.as_ref() {
+                if event_handler.has_sync_committee_message_subscribers() {
+                    event_handler.register(EventKind::LighthouseSyncCommitteeMessage(Box::new(
+                        v.sync_message().clone(),
+                    )));
+                }
+            }
             metrics::inc_counter(&metrics::SYNC_MESSAGE_PROCESSING_SUCCESSES);
         })
     }
@@ -3022,6 +3032,11 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             return Err(BlockError::DuplicateFullyImported(block_root));
         }
 
+        self.emit_sse_data_column_sidecar_events(
+            &block_root,
+            data_columns.iter().map(|column| column.as_data_column()),
+        );
+
         let r = self
             .check_gossip_data_columns_availability_and_import(
                 slot,
@@ -3127,6 +3142,31 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         }
     }
 
+    fn emit_sse_data_column_sidecar_events<'a, I>(
+        self: &Arc<Self>,
+        block_root: &Hash256,
+        data_columns_iter: I,
+    ) where
+        I: Iterator<Item = &'a DataColumnSidecar<T::EthSpec>>,
+    {
+        if let Some(event_handler) = self.event_handler.as_ref() {
+            if event_handler.has_data_column_sidecar_subscribers() {
+                let imported_data_columns = self
+                    .data_availability_checker
+                    .cached_data_column_indexes(block_root)
+                    .unwrap_or_default();
+                let new_data_columns =
+                    data_columns_iter.filter(|c| !imported_data_columns.contains(&c.index));
+
+                for data_column in new_data_columns {
+                    event_handler.register(EventKind::LighthouseDataColumnSidecar(
+                        SseDataColumnSidecar::from_data_column_sidecar(data_column),
+                    ));
+                }
+            }
+        }
+    }
+
     /// Cache the columns in the processing cache, process it, then evict it from the cache if it was
     /// imported or errors.
     pub async fn process_rpc_custody_columns(
@@ -3167,6 +3207,11 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             }
         }
 
+        self.emit_sse_data_column_sidecar_events(
+            &block_root,
+            custody_columns.iter().map(|column| column.as_ref()),
+        );
+
         let r = self
             .check_rpc_custody_columns_availability_and_import(slot, block_root, custody_columns)
             .await;
@@ -5313,6 +5358,10 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             chain_health: self
                 .is_healthy(&parent_root)
                 .map_err(BlockProductionError::BeaconChain)?,
+            parent_gas_limit: state
+                .latest_execution_payload_header()
+                .ok()
+                .map(|header| header.gas_limit()),
         };
 
         // If required, start the process of loading an execution payload from the EL early. This
@@ -5391,6 +5440,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                 &state,
                 prev_attestation_filter,
                 curr_attestation_filter,
+                self.config.attestation_packing_search_timeout,
                 &self.spec,
             )
             .map_err(BlockProductionError::OpPoolError)?;
@@ -6483,6 +6533,47 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         }
     }
 
+    /// Returns `true` if a validator connected to this node is due to propose at `slot`.
+    ///
+    /// Only the proposer cache is consulted, so this returns `false` if the proposers of the epoch
+    /// of `slot` have not been computed yet.
+    pub async fn has_local_proposer_at_slot(&self, slot: Slot) -> bool {
+        let Some(execution_layer) = self.execution_layer.as_ref() else {
+            return false;
+        };
+
+        let proposer_index = {
+            let cached_head = self.canonical_head.cached_head();
+            let head_block_root = cached_head.head_block_root();
+            let head_epoch = cached_head.head_slot().epoch(T::EthSpec::slots_per_epoch());
+            let shuffling_decision_root =
+                if head_epoch == slot.epoch(T::EthSpec::slots_per_epoch()) {
+                    match cached_head
+                        .snapshot
+                        .beacon_state
+                        .proposer_shuffling_decision_root(head_block_root)
+                    {
+                        Ok(root) => root,
+                        Err(_) => return false,
+                    }
+                } else {
+                    head_block_root
+                };
+            match self
+                .beacon_proposer_cache
+                .lock()
+                .get_slot::<T::EthSpec>(shuffling_decision_root, slot)
+            {
+                Some(proposer) => proposer.index as u64,
+                None => return false,
+            }
+        };
+
+        execution_layer
+            .has_proposer_preparation_data(proposer_index)
+            .await
+    }
+
     /// Returns the value of `execution_optimistic` for the current head block.
     /// You can optionally provide `head_info` if it was computed previously.
     ///
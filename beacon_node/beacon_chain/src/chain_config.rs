@@ -9,6 +9,9 @@ pub const DEFAULT_RE_ORG_MAX_EPOCHS_SINCE_FINALIZATION: Epoch = Epoch::new(2);
 /// Default to 1/12th of the slot, which is 1 second on mainnet.
 pub const DEFAULT_RE_ORG_CUTOFF_DENOMINATOR: u32 = 12;
 pub const DEFAULT_FORK_CHOICE_BEFORE_PROPOSAL_TIMEOUT: u64 = 250;
+/// The maximum time in milliseconds which may be spent searching for attestations to pack into a
+/// block, as the search delays the proposal.
+pub const MAX_ATTESTATION_PACKING_SEARCH_TIMEOUT: u64 = 300;
 
 /// Default fraction of a slot lookahead for payload preparation (12/3 = 4 seconds on mainnet).
 pub const DEFAULT_PREPARE_PAYLOAD_LOOKAHEAD_FACTOR: u32 = 3;
@@ -47,6 +50,11 @@ pub struct ChainConfig {
     ///
     /// If set to 0 then block proposal will not wait for fork choice at all.
     pub fork_choice_before_proposal_timeout_ms: u64,
+    /// The maximum time to spend searching for a more rewarding set of attestations than the
+    /// greedy solution when producing a post-Electra block.
+    ///
+    /// If set to `None` then no search is performed.
+    pub attestation_packing_search_timeout: Option<Duration>,
     /// Number of skip slots in a row before the BN refuses to use connected builders during payload construction.
     pub builder_fallback_skips: usize,
     /// Number of skip slots in the past `SLOTS_PER_EPOCH` before the BN refuses to use connected
@@ -76,6 +84,14 @@ pub struct ChainConfig {
     /// If using a weak-subjectivity sync, whether we should download blocks all the way back to
     /// genesis.
     pub genesis_backfill: bool,
+    /// Only run backfill sync between these hours of the day in UTC, as `(start, end)` with the
+    /// end exclusive. The window may wrap around midnight.
+    pub backfill_window_utc_hours: Option<(u8, u8)>,
+    /// Pause backfill sync this many slots before a proposal by a local validator, until the end
+    /// of the proposal slot.
+    pub backfill_proposal_pause_slots: Option<u64>,
+    /// Limit backfill sync to processing batches for at most this percentage of the time.
+    pub backfill_processing_budget: Option<u8>,
     /// Whether to send payload attributes every slot, regardless of connected proposers.
     ///
     /// This is useful for block builders and testing.
@@ -109,6 +125,7 @@ impl Default for ChainConfig {
             re_org_cutoff_millis: None,
             re_org_disallowed_offsets: DisallowedReOrgOffsets::default(),
             fork_choice_before_proposal_timeout_ms: DEFAULT_FORK_CHOICE_BEFORE_PROPOSAL_TIMEOUT,
+            attestation_packing_search_timeout: None,
             // Builder fallback configs that are set in `clap` will override these.
             builder_fallback_skips: 3,
             builder_fallback_skips_per_epoch: 8,
@@ -122,6 +139,9 @@ impl Default for ChainConfig {
             optimistic_finalized_sync: true,
             shuffling_cache_size: crate::shuffling_cache::DEFAULT_CACHE_SIZE,
             genesis_backfill: false,
+            backfill_window_utc_hours: None,
+            backfill_proposal_pause_slots: None,
+            backfill_processing_budget: None,
             always_prepare_payload: false,
             epochs_per_migration: crate::migrate::DEFAULT_EPOCHS_PER_MIGRATION,
             enable_light_client_server: false,
@@ -0,0 +1,44 @@
+use crate::{BeaconChain, BeaconChainTypes};
+use eth2::lighthouse::SseEngineState;
+use eth2::types::EventKind;
+use futures::StreamExt;
+use std::sync::Arc;
+use task_executor::TaskExecutor;
+
+/// Spawns a routine which publishes changes to the state of the execution engine to the
+/// `lighthouse_engine_state` event topic.
+///
+/// The service will not be started if there is no `execution_layer` or `event_handler` on the
+/// `chain`.
+pub fn start_engine_state_event_service<T: BeaconChainTypes>(
+    executor: TaskExecutor,
+    chain: Arc<BeaconChain<T>>,
+) {
+    if chain.execution_layer.is_some() && chain.event_handler.is_some() {
+        executor.spawn(
+            async move { engine_state_event_service(chain).await },
+            "engine_state_event_service",
+        );
+    }
+}
+
+/// Loop indefinitely, registering an event each time the engine state changes.
+async fn engine_state_event_service<T: BeaconChainTypes>(chain: Arc<BeaconChain<T>>) {
+    let (Some(execution_layer), Some(event_handler)) =
+        (chain.execution_layer.as_ref(), chain.event_handler.as_ref())
+    else {
+        return;
+    };
+
+    let mut status_watch = execution_layer.get_status_watch().await;
+    let mut previous = None;
+    while let Some(current) = status_watch.next().await {
+        if event_handler.has_engine_state_subscribers() {
+            event_handler.register(EventKind::LighthouseEngineState(SseEngineState {
+                previous,
+                current,
+            }));
+        }
+        previous = Some(current);
+    }
+}
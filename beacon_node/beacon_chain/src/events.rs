@@ -1,58 +1,153 @@
+use eth2::types::EventTopic;
 pub use eth2::types::{EventKind, SseBlock, SseFinalizedCheckpoint, SseHead};
+use parking_lot::Mutex;
 use slog::{trace, Logger};
+use std::collections::VecDeque;
+use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::sync::broadcast;
 use tokio::sync::broadcast::{error::SendError, Receiver, Sender};
 use types::EthSpec;
 
 const DEFAULT_CHANNEL_CAPACITY: usize = 16;
 
+/// An event, along with the identifier that was assigned to it when it was registered.
+///
+/// Identifiers are unique across all topics and increase in the order in which events are
+/// registered, so a client may resume a stream of several topics from a single identifier.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdentifiedEvent<E: EthSpec> {
+    pub id: u64,
+    pub kind: EventKind<E>,
+}
+
+/// The broadcast channel for a single topic, along with the most recent events which may be
+/// replayed to a client that reconnects.
+struct EventChannel<E: EthSpec> {
+    tx: Sender<IdentifiedEvent<E>>,
+    replay_buffer: Mutex<VecDeque<IdentifiedEvent<E>>>,
+    replay_buffer_size: usize,
+}
+
+impl<E: EthSpec> EventChannel<E> {
+    fn new(capacity: usize, replay_buffer_size: usize) -> Self {
+        let (tx, _) = broadcast::channel(capacity);
+        Self {
+            tx,
+            replay_buffer: Mutex::new(VecDeque::with_capacity(replay_buffer_size)),
+            replay_buffer_size,
+        }
+    }
+
+    fn send(&self, event: IdentifiedEvent<E>) -> Result<usize, SendError<IdentifiedEvent<E>>> {
+        // The lock is held whilst sending so that `subscribe_from` never observes an event in
+        // both the replay buffer and the receiver, or in neither.
+        let mut replay_buffer = self.replay_buffer.lock();
+        if self.replay_buffer_size > 0 {
+            if replay_buffer.len() >= self.replay_buffer_size {
+                replay_buffer.pop_front();
+            }
+            replay_buffer.push_back(event.clone());
+        }
+        self.tx.send(event)
+    }
+
+    fn subscribe(&self) -> Receiver<IdentifiedEvent<E>> {
+        self.tx.subscribe()
+    }
+
+    /// Subscribe to the channel, returning any buffered events with an identifier greater than
+    /// `last_event_id`.
+    fn subscribe_from(
+        &self,
+        last_event_id: Option<u64>,
+    ) -> (Vec<IdentifiedEvent<E>>, Receiver<IdentifiedEvent<E>>) {
+        let replay_buffer = self.replay_buffer.lock();
+        let missed = last_event_id
+            .map(|last_event_id| {
+                replay_buffer
+                    .iter()
+                    .filter(|event| event.id > last_event_id)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+        (missed, self.tx.subscribe())
+    }
+
+    /// Returns `true` if events sent to this channel will be observed, either by a current
+    /// subscriber or by a future subscriber via the replay buffer.
+    fn has_subscribers(&self) -> bool {
+        self.tx.receiver_count() > 0 || self.replay_buffer_size > 0
+    }
+}
+
 pub struct ServerSentEventHandler<E: EthSpec> {
-    attestation_tx: Sender<EventKind<E>>,
-    block_tx: Sender<EventKind<E>>,
-    blob_sidecar_tx: Sender<EventKind<E>>,
-    finalized_tx: Sender<EventKind<E>>,
-    head_tx: Sender<EventKind<E>>,
-    exit_tx: Sender<EventKind<E>>,
-    chain_reorg_tx: Sender<EventKind<E>>,
-    contribution_tx: Sender<EventKind<E>>,
-    payload_attributes_tx: Sender<EventKind<E>>,
-    late_head: Sender<EventKind<E>>,
-    light_client_finality_update_tx: Sender<EventKind<E>>,
-    light_client_optimistic_update_tx: Sender<EventKind<E>>,
-    block_reward_tx: Sender<EventKind<E>>,
-    proposer_slashing_tx: Sender<EventKind<E>>,
-    attester_slashing_tx: Sender<EventKind<E>>,
-    bls_to_execution_change_tx: Sender<EventKind<E>>,
-    block_gossip_tx: Sender<EventKind<E>>,
+    attestation_tx: EventChannel<E>,
+    block_tx: EventChannel<E>,
+    blob_sidecar_tx: EventChannel<E>,
+    finalized_tx: EventChannel<E>,
+    head_tx: EventChannel<E>,
+    exit_tx: EventChannel<E>,
+    chain_reorg_tx: EventChannel<E>,
+    contribution_tx: EventChannel<E>,
+    payload_attributes_tx: EventChannel<E>,
+    late_head: EventChannel<E>,
+    light_client_finality_update_tx: EventChannel<E>,
+    light_client_optimistic_update_tx: EventChannel<E>,
+    block_reward_tx: EventChannel<E>,
+    proposer_slashing_tx: EventChannel<E>,
+    attester_slashing_tx: EventChannel<E>,
+    bls_to_execution_change_tx: EventChannel<E>,
+    block_gossip_tx: EventChannel<E>,
+    data_column_sidecar_tx: EventChannel<E>,
+    sync_committee_message_tx: EventChannel<E>,
+    sync_state_tx: EventChannel<E>,
+    engine_state_tx: EventChannel<E>,
+    peer_connection_tx: EventChannel<E>,
+    /// The identifier to assign to the next event.
+    ///
+    /// The lock is held for the duration of `register`, so that events are sent to their
+    /// channels in the order of their identifiers.
+    next_event_id: Mutex<u64>,
     log: Logger,
 }
 
 impl<E: EthSpec> ServerSentEventHandler<E> {
-    pub fn new(log: Logger, capacity_multiplier: usize) -> Self {
+    pub fn new(log: Logger, capacity_multiplier: usize, replay_buffer_size: usize) -> Self {
         Self::new_with_capacity(
             log,
             capacity_multiplier.saturating_mul(DEFAULT_CHANNEL_CAPACITY),
+            replay_buffer_size,
         )
     }
 
-    pub fn new_with_capacity(log: Logger, capacity: usize) -> Self {
-        let (attestation_tx, _) = broadcast::channel(capacity);
-        let (block_tx, _) = broadcast::channel(capacity);
-        let (blob_sidecar_tx, _) = broadcast::channel(capacity);
-        let (finalized_tx, _) = broadcast::channel(capacity);
-        let (head_tx, _) = broadcast::channel(capacity);
-        let (exit_tx, _) = broadcast::channel(capacity);
-        let (chain_reorg_tx, _) = broadcast::channel(capacity);
-        let (contribution_tx, _) = broadcast::channel(capacity);
-        let (payload_attributes_tx, _) = broadcast::channel(capacity);
-        let (late_head, _) = broadcast::channel(capacity);
-        let (light_client_finality_update_tx, _) = broadcast::channel(capacity);
-        let (light_client_optimistic_update_tx, _) = broadcast::channel(capacity);
-        let (block_reward_tx, _) = broadcast::channel(capacity);
-        let (proposer_slashing_tx, _) = broadcast::channel(capacity);
-        let (attester_slashing_tx, _) = broadcast::channel(capacity);
-        let (bls_to_execution_change_tx, _) = broadcast::channel(capacity);
-        let (block_gossip_tx, _) = broadcast::channel(capacity);
+    /// Create a handler with broadcast channels of length `capacity`.
+    ///
+    /// The `replay_buffer_size` most recent events of each topic are retained so that they may
+    /// be replayed to clients which reconnect. A size of zero disables replay.
+    pub fn new_with_capacity(log: Logger, capacity: usize, replay_buffer_size: usize) -> Self {
+        let attestation_tx = EventChannel::new(capacity, replay_buffer_size);
+        let block_tx = EventChannel::new(capacity, replay_buffer_size);
+        let blob_sidecar_tx = EventChannel::new(capacity, replay_buffer_size);
+        let finalized_tx = EventChannel::new(capacity, replay_buffer_size);
+        let head_tx = EventChannel::new(capacity, replay_buffer_size);
+        let exit_tx = EventChannel::new(capacity, replay_buffer_size);
+        let chain_reorg_tx = EventChannel::new(capacity, replay_buffer_size);
+        let contribution_tx = EventChannel::new(capacity, replay_buffer_size);
+        let payload_attributes_tx = EventChannel::new(capacity, replay_buffer_size);
+        let late_head = EventChannel::new(capacity, replay_buffer_size);
+        let light_client_finality_update_tx = EventChannel::new(capacity, replay_buffer_size);
+        let light_client_optimistic_update_tx = EventChannel::new(capacity, replay_buffer_size);
+        let block_reward_tx = EventChannel::new(capacity, replay_buffer_size);
+        let proposer_slashing_tx = EventChannel::new(capacity, replay_buffer_size);
+        let attester_slashing_tx = EventChannel::new(capacity, replay_buffer_size);
+        let bls_to_execution_change_tx = EventChannel::new(capacity, replay_buffer_size);
+        let block_gossip_tx = EventChannel::new(capacity, replay_buffer_size);
+        let data_column_sidecar_tx = EventChannel::new(capacity, replay_buffer_size);
+        let sync_committee_message_tx = EventChannel::new(capacity, replay_buffer_size);
+        let sync_state_tx = EventChannel::new(capacity, replay_buffer_size);
+        let engine_state_tx = EventChannel::new(capacity, replay_buffer_size);
+        let peer_connection_tx = EventChannel::new(capacity, replay_buffer_size);
 
         Self {
             attestation_tx,
@@ -72,6 +167,19 @@ impl<E: EthSpec> ServerSentEventHandler<E> {
             attester_slashing_tx,
             bls_to_execution_change_tx,
             block_gossip_tx,
+            data_column_sidecar_tx,
+            sync_committee_message_tx,
+            sync_state_tx,
+            engine_state_tx,
+            peer_connection_tx,
+            // Seeding identifiers from the clock keeps them increasing across restarts, so that a
+            // client resuming with an identifier from a previous run is not sent stale events.
+            next_event_id: Mutex::new(
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|duration| duration.as_micros() as u64)
+                    .unwrap_or(0),
+            ),
             log,
         }
     }
@@ -85,206 +193,310 @@ impl<E: EthSpec> ServerSentEventHandler<E> {
                 "receiver_count" => count
             );
         };
-        let result = match &kind {
+        let mut next_event_id = self.next_event_id.lock();
+        let id = *next_event_id;
+        *next_event_id = next_event_id.saturating_add(1);
+        let event = IdentifiedEvent { id, kind };
+
+        let result = match &event.kind {
             EventKind::Attestation(_) => self
                 .attestation_tx
-                .send(kind)
+                .send(event)
                 .map(|count| log_count("attestation", count)),
             EventKind::Block(_) => self
                 .block_tx
-                .send(kind)
+                .send(event)
                 .map(|count| log_count("block", count)),
             EventKind::BlobSidecar(_) => self
                 .blob_sidecar_tx
-                .send(kind)
+                .send(event)
                 .map(|count| log_count("blob sidecar", count)),
             EventKind::FinalizedCheckpoint(_) => self
                 .finalized_tx
-                .send(kind)
+                .send(event)
                 .map(|count| log_count("finalized checkpoint", count)),
             EventKind::Head(_) => self
                 .head_tx
-                .send(kind)
+                .send(event)
                 .map(|count| log_count("head", count)),
             EventKind::VoluntaryExit(_) => self
                 .exit_tx
-                .send(kind)
+                .send(event)
                 .map(|count| log_count("exit", count)),
             EventKind::ChainReorg(_) => self
                 .chain_reorg_tx
-                .send(kind)
+                .send(event)
                 .map(|count| log_count("chain reorg", count)),
             EventKind::ContributionAndProof(_) => self
                 .contribution_tx
-                .send(kind)
+                .send(event)
                 .map(|count| log_count("contribution and proof", count)),
             EventKind::PayloadAttributes(_) => self
                 .payload_attributes_tx
-                .send(kind)
+                .send(event)
                 .map(|count| log_count("payload attributes", count)),
             EventKind::LateHead(_) => self
                 .late_head
-                .send(kind)
+                .send(event)
                 .map(|count| log_count("late head", count)),
             EventKind::LightClientFinalityUpdate(_) => self
                 .light_client_finality_update_tx
-                .send(kind)
+                .send(event)
                 .map(|count| log_count("light client finality update", count)),
             EventKind::LightClientOptimisticUpdate(_) => self
                 .light_client_optimistic_update_tx
-                .send(kind)
+                .send(event)
                 .map(|count| log_count("light client optimistic update", count)),
             EventKind::BlockReward(_) => self
                 .block_reward_tx
-                .send(kind)
+                .send(event)
                 .map(|count| log_count("block reward", count)),
             EventKind::ProposerSlashing(_) => self
                 .proposer_slashing_tx
-                .send(kind)
+                .send(event)
                 .map(|count| log_count("proposer slashing", count)),
             EventKind::AttesterSlashing(_) => self
                 .attester_slashing_tx
-                .send(kind)
+                .send(event)
                 .map(|count| log_count("attester slashing", count)),
             EventKind::BlsToExecutionChange(_) => self
                 .bls_to_execution_change_tx
-                .send(kind)
+                .send(event)
                 .map(|count| log_count("bls to execution change", count)),
             EventKind::BlockGossip(_) => self
                 .block_gossip_tx
-                .send(kind)
+                .send(event)
                 .map(|count| log_count("block gossip", count)),
+            EventKind::LighthouseDataColumnSidecar(_) => self
+                .data_column_sidecar_tx
+                .send(event)
+                .map(|count| log_count("data column sidecar", count)),
+            EventKind::LighthouseSyncCommitteeMessage(_) => self
+                .sync_committee_message_tx
+                .send(event)
+                .map(|count| log_count("sync committee message", count)),
+            EventKind::LighthouseSyncState(_) => self
+                .sync_state_tx
+                .send(event)
+                .map(|count| log_count("sync state", count)),
+            EventKind::LighthouseEngineState(_) => self
+                .engine_state_tx
+                .send(event)
+                .map(|count| log_count("engine state", count)),
+            EventKind::LighthousePeerConnection(_) => self
+                .peer_connection_tx
+                .send(event)
+                .map(|count| log_count("peer connection", count)),
         };
+        drop(next_event_id);
         if let Err(SendError(event)) = result {
-            trace!(self.log, "No receivers registered to listen for event"; "event" => ?event);
+            trace!(self.log, "No receivers registered to listen for event"; "event" => ?event.kind);
         }
     }
 
-    pub fn subscribe_attestation(&self) -> Receiver<EventKind<E>> {
+    pub fn subscribe_attestation(&self) -> Receiver<IdentifiedEvent<E>> {
         self.attestation_tx.subscribe()
     }
 
-    pub fn subscribe_block(&self) -> Receiver<EventKind<E>> {
+    pub fn subscribe_block(&self) -> Receiver<IdentifiedEvent<E>> {
         self.block_tx.subscribe()
     }
 
-    pub fn subscribe_blob_sidecar(&self) -> Receiver<EventKind<E>> {
+    pub fn subscribe_blob_sidecar(&self) -> Receiver<IdentifiedEvent<E>> {
         self.blob_sidecar_tx.subscribe()
     }
 
-    pub fn subscribe_finalized(&self) -> Receiver<EventKind<E>> {
+    pub fn subscribe_finalized(&self) -> Receiver<IdentifiedEvent<E>> {
         self.finalized_tx.subscribe()
     }
 
-    pub fn subscribe_head(&self) -> Receiver<EventKind<E>> {
+    pub fn subscribe_head(&self) -> Receiver<IdentifiedEvent<E>> {
         self.head_tx.subscribe()
     }
 
-    pub fn subscribe_exit(&self) -> Receiver<EventKind<E>> {
+    pub fn subscribe_exit(&self) -> Receiver<IdentifiedEvent<E>> {
         self.exit_tx.subscribe()
     }
 
-    pub fn subscribe_reorgs(&self) -> Receiver<EventKind<E>> {
+    pub fn subscribe_reorgs(&self) -> Receiver<IdentifiedEvent<E>> {
         self.chain_reorg_tx.subscribe()
     }
 
-    pub fn subscribe_contributions(&self) -> Receiver<EventKind<E>> {
+    pub fn subscribe_contributions(&self) -> Receiver<IdentifiedEvent<E>> {
         self.contribution_tx.subscribe()
     }
 
-    pub fn subscribe_payload_attributes(&self) -> Receiver<EventKind<E>> {
+    pub fn subscribe_payload_attributes(&self) -> Receiver<IdentifiedEvent<E>> {
         self.payload_attributes_tx.subscribe()
     }
 
-    pub fn subscribe_late_head(&self) -> Receiver<EventKind<E>> {
+    pub fn subscribe_late_head(&self) -> Receiver<IdentifiedEvent<E>> {
         self.late_head.subscribe()
     }
 
-    pub fn subscribe_light_client_finality_update(&self) -> Receiver<EventKind<E>> {
+    pub fn subscribe_light_client_finality_update(&self) -> Receiver<IdentifiedEvent<E>> {
         self.light_client_finality_update_tx.subscribe()
     }
 
-    pub fn subscribe_light_client_optimistic_update(&self) -> Receiver<EventKind<E>> {
+    pub fn subscribe_light_client_optimistic_update(&self) -> Receiver<IdentifiedEvent<E>> {
         self.light_client_optimistic_update_tx.subscribe()
     }
 
-    pub fn subscribe_block_reward(&self) -> Receiver<EventKind<E>> {
+    pub fn subscribe_block_reward(&self) -> Receiver<IdentifiedEvent<E>> {
         self.block_reward_tx.subscribe()
     }
 
-    pub fn subscribe_attester_slashing(&self) -> Receiver<EventKind<E>> {
+    pub fn subscribe_attester_slashing(&self) -> Receiver<IdentifiedEvent<E>> {
         self.attester_slashing_tx.subscribe()
     }
 
-    pub fn subscribe_proposer_slashing(&self) -> Receiver<EventKind<E>> {
+    pub fn subscribe_proposer_slashing(&self) -> Receiver<IdentifiedEvent<E>> {
         self.proposer_slashing_tx.subscribe()
     }
 
-    pub fn subscribe_bls_to_execution_change(&self) -> Receiver<EventKind<E>> {
+    pub fn subscribe_bls_to_execution_change(&self) -> Receiver<IdentifiedEvent<E>> {
         self.bls_to_execution_change_tx.subscribe()
     }
 
-    pub fn subscribe_block_gossip(&self) -> Receiver<EventKind<E>> {
+    pub fn subscribe_block_gossip(&self) -> Receiver<IdentifiedEvent<E>> {
         self.block_gossip_tx.subscribe()
     }
 
+    pub fn subscribe_data_column_sidecar(&self) -> Receiver<IdentifiedEvent<E>> {
+        self.data_column_sidecar_tx.subscribe()
+    }
+
+    pub fn subscribe_sync_committee_message(&self) -> Receiver<IdentifiedEvent<E>> {
+        self.sync_committee_message_tx.subscribe()
+    }
+
+    pub fn subscribe_sync_state(&self) -> Receiver<IdentifiedEvent<E>> {
+        self.sync_state_tx.subscribe()
+    }
+
+    pub fn subscribe_engine_state(&self) -> Receiver<IdentifiedEvent<E>> {
+        self.engine_state_tx.subscribe()
+    }
+
+    pub fn subscribe_peer_connection(&self) -> Receiver<IdentifiedEvent<E>> {
+        self.peer_connection_tx.subscribe()
+    }
+
+    /// Subscribe to `topic`, returning any retained events registered after the event with
+    /// identifier `last_event_id`.
+    ///
+    /// No events are returned if `last_event_id` is `None`. If the event with identifier
+    /// `last_event_id` has already been evicted from the replay buffer then some events will have
+    /// been missed, and all retained events are returned.
+    pub fn subscribe_from(
+        &self,
+        topic: &EventTopic,
+        last_event_id: Option<u64>,
+    ) -> (Vec<IdentifiedEvent<E>>, Receiver<IdentifiedEvent<E>>) {
+        let channel = match topic {
+            EventTopic::Head => &self.head_tx,
+            EventTopic::Block => &self.block_tx,
+            EventTopic::BlobSidecar => &self.blob_sidecar_tx,
+            EventTopic::Attestation => &self.attestation_tx,
+            EventTopic::VoluntaryExit => &self.exit_tx,
+            EventTopic::FinalizedCheckpoint => &self.finalized_tx,
+            EventTopic::ChainReorg => &self.chain_reorg_tx,
+            EventTopic::ContributionAndProof => &self.contribution_tx,
+            EventTopic::PayloadAttributes => &self.payload_attributes_tx,
+            EventTopic::LateHead => &self.late_head,
+            EventTopic::LightClientFinalityUpdate => &self.light_client_finality_update_tx,
+            EventTopic::LightClientOptimisticUpdate => &self.light_client_optimistic_update_tx,
+            EventTopic::BlockReward => &self.block_reward_tx,
+            EventTopic::AttesterSlashing => &self.attester_slashing_tx,
+            EventTopic::ProposerSlashing => &self.proposer_slashing_tx,
+            EventTopic::BlsToExecutionChange => &self.bls_to_execution_change_tx,
+            EventTopic::BlockGossip => &self.block_gossip_tx,
+            EventTopic::LighthouseDataColumnSidecar => &self.data_column_sidecar_tx,
+            EventTopic::LighthouseSyncCommitteeMessage => &self.sync_committee_message_tx,
+            EventTopic::LighthouseSyncState => &self.sync_state_tx,
+            EventTopic::LighthouseEngineState => &self.engine_state_tx,
+            EventTopic::LighthousePeerConnection => &self.peer_connection_tx,
+        };
+        channel.subscribe_from(last_event_id)
+    }
+
     pub fn has_attestation_subscribers(&self) -> bool {
-        self.attestation_tx.receiver_count() > 0
+        self.attestation_tx.has_subscribers()
     }
 
     pub fn has_block_subscribers(&self) -> bool {
-        self.block_tx.receiver_count() > 0
+        self.block_tx.has_subscribers()
     }
 
     pub fn has_blob_sidecar_subscribers(&self) -> bool {
-        self.blob_sidecar_tx.receiver_count() > 0
+        self.blob_sidecar_tx.has_subscribers()
     }
 
     pub fn has_finalized_subscribers(&self) -> bool {
-        self.finalized_tx.receiver_count() > 0
+        self.finalized_tx.has_subscribers()
     }
 
     pub fn has_head_subscribers(&self) -> bool {
-        self.head_tx.receiver_count() > 0
+        self.head_tx.has_subscribers()
     }
 
     pub fn has_exit_subscribers(&self) -> bool {
-        self.exit_tx.receiver_count() > 0
+        self.exit_tx.has_subscribers()
     }
 
     pub fn has_reorg_subscribers(&self) -> bool {
-        self.chain_reorg_tx.receiver_count() > 0
+        self.chain_reorg_tx.has_subscribers()
     }
 
     pub fn has_contribution_subscribers(&self) -> bool {
-        self.contribution_tx.receiver_count() > 0
+        self.contribution_tx.has_subscribers()
     }
 
     pub fn has_payload_attributes_subscribers(&self) -> bool {
-        self.payload_attributes_tx.receiver_count() > 0
+        self.payload_attributes_tx.has_subscribers()
     }
 
     pub fn has_late_head_subscribers(&self) -> bool {
-        self.late_head.receiver_count() > 0
+        self.late_head.has_subscribers()
     }
 
     pub fn has_block_reward_subscribers(&self) -> bool {
-        self.block_reward_tx.receiver_count() > 0
+        self.block_reward_tx.has_subscribers()
     }
 
     pub fn has_proposer_slashing_subscribers(&self) -> bool {
-        self.proposer_slashing_tx.receiver_count() > 0
+        self.proposer_slashing_tx.has_subscribers()
     }
 
     pub fn has_attester_slashing_subscribers(&self) -> bool {
-        self.attester_slashing_tx.receiver_count() > 0
+        self.attester_slashing_tx.has_subscribers()
     }
 
     pub fn has_bls_to_execution_change_subscribers(&self) -> bool {
-        self.bls_to_execution_change_tx.receiver_count() > 0
+        self.bls_to_execution_change_tx.has_subscribers()
     }
 
     pub fn has_block_gossip_subscribers(&self) -> bool {
-        self.block_gossip_tx.receiver_count() > 0
+        self.block_gossip_tx.has_subscribers()
+    }
+
+    pub fn has_data_column_sidecar_subscribers(&self) -> bool {
+        self.data_column_sidecar_tx.has_subscribers()
+    }
+
+    pub fn has_sync_committee_message_subscribers(&self) -> bool {
+        self.sync_committee_message_tx.has_subscribers()
+    }
+
+    pub fn has_sync_state_subscribers(&self) -> bool {
+        self.sync_state_tx.has_subscribers()
+    }
+
+    pub fn has_engine_state_subscribers(&self) -> bool {
+        self.engine_state_tx.has_subscribers()
+    }
+
+    pub fn has_peer_connection_subscribers(&self) -> bool {
+        self.peer_connection_tx.has_subscribers()
     }
 }
@@ -1,5 +1,6 @@
 use kzg::{
     Blob as KzgBlob, Bytes48, CellRef as KzgCellRef, CellsAndKzgProofs, Error as KzgError, Kzg,
+    CELLS_PER_EXT_BLOB,
 };
 use rayon::prelude::*;
 use ssz_types::FixedVector;
@@ -7,8 +8,9 @@ use std::sync::Arc;
 use types::beacon_block_body::KzgCommitments;
 use types::data_column_sidecar::{Cell, DataColumn, DataColumnSidecarError};
 use types::{
-    Blob, ChainSpec, ColumnIndex, DataColumnSidecar, DataColumnSidecarList, EthSpec, Hash256,
-    KzgCommitment, KzgProof, KzgProofs, SignedBeaconBlock, SignedBeaconBlockHeader,
+    AbstractExecPayload, Blob, BlobSidecar, BlobSidecarList, ChainSpec, ColumnIndex,
+    DataColumnSidecar, DataColumnSidecarList, EthSpec, Hash256, KzgCommitment, KzgProof, KzgProofs,
+    SignedBeaconBlock, SignedBeaconBlockHeader,
 };
 
 /// Converts a blob ssz List object to an array to be used with the kzg
@@ -287,11 +289,108 @@ pub fn reconstruct_data_columns<E: EthSpec>(
     .map_err(KzgError::ReconstructFailed)
 }
 
+/// Reconstruct the blobs of `block` from a subset of its data column sidecars (requires at least
+/// 50%).
+///
+/// Only the blobs at `blob_indices` are reconstructed, or all blobs if `None`. The data columns
+/// are assumed to have been verified prior to storage, so the recovered blobs are not checked
+/// against their commitments.
+pub fn reconstruct_blobs<E: EthSpec, Payload: AbstractExecPayload<E>>(
+    kzg: &Kzg,
+    data_columns: &[Arc<DataColumnSidecar<E>>],
+    blob_indices: Option<&[u64]>,
+    block: &SignedBeaconBlock<E, Payload>,
+) -> Result<BlobSidecarList<E>, String> {
+    // Cells must be ordered by column index when the blob is read directly from the columns.
+    let mut data_columns = data_columns.to_vec();
+    data_columns.sort_by_key(|data_column| data_column.index);
+    data_columns.dedup_by_key(|data_column| data_column.index);
+
+    let first_data_column = data_columns
+        .first()
+        .ok_or("data_columns should have at least one element")?;
+    let num_of_blobs = first_data_column.kzg_commitments.len();
+    let blob_indices = match blob_indices {
+        Some(indices) => indices
+            .iter()
+            .map(|index| *index as usize)
+            .filter(|index| *index < num_of_blobs)
+            .collect::<Vec<_>>(),
+        None => (0..num_of_blobs).collect(),
+    };
+
+    // The cells of the first half of the extended blob are the cells of the original blob.
+    let num_of_original_cells = CELLS_PER_EXT_BLOB / 2;
+    let blob_sidecars = blob_indices
+        .into_par_iter()
+        .map(|row_index| {
+            let mut cells: Vec<KzgCellRef> = vec![];
+            let mut cell_ids: Vec<u64> = vec![];
+            for data_column in &data_columns {
+                let cell = data_column
+                    .column
+                    .get(row_index)
+                    .ok_or(format!("Missing data column at index {row_index}"))?;
+
+                cells.push(ssz_cell_to_crypto_cell::<E>(cell).map_err(|e| format!("{e:?}"))?);
+                cell_ids.push(data_column.index);
+            }
+
+            let blob_bytes = if data_columns.len() < CELLS_PER_EXT_BLOB {
+                let (recovered_cells, _kzg_proofs) = kzg
+                    .recover_cells_and_compute_kzg_proofs(&cell_ids, &cells)
+                    .map_err(|e| format!("Failed to recover cells: {e:?}"))?;
+                recovered_cells
+                    .iter()
+                    .take(num_of_original_cells)
+                    .flat_map(|cell| cell.iter().copied())
+                    .collect::<Vec<_>>()
+            } else {
+                cells
+                    .iter()
+                    .take(num_of_original_cells)
+                    .flat_map(|cell| cell.iter().copied())
+                    .collect::<Vec<_>>()
+            };
+            let blob = Blob::<E>::new(blob_bytes).map_err(|e| format!("{e:?}"))?;
+
+            let kzg_commitment = *first_data_column
+                .kzg_commitments
+                .get(row_index)
+                .ok_or(format!("Missing KZG commitment for blob {row_index}"))?;
+            let kzg_proof = compute_blob_kzg_proof::<E>(kzg, &blob, kzg_commitment)
+                .map_err(|e| format!("Failed to compute blob KZG proof: {e:?}"))?;
+            let kzg_commitment_inclusion_proof = block
+                .message()
+                .body()
+                .complete_kzg_commitment_merkle_proof(
+                    row_index,
+                    &first_data_column.kzg_commitments_inclusion_proof,
+                )
+                .map_err(|e| format!("{e:?}"))?;
+
+            Ok(Arc::new(BlobSidecar {
+                index: row_index as u64,
+                blob,
+                kzg_commitment,
+                kzg_proof,
+                signed_block_header: first_data_column.signed_block_header.clone(),
+                kzg_commitment_inclusion_proof,
+            }))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    BlobSidecarList::new(blob_sidecars).map_err(|e| format!("{e:?}"))
+}
+
 #[cfg(test)]
 mod test {
-    use crate::kzg_utils::{blobs_to_data_column_sidecars, reconstruct_data_columns};
+    use crate::kzg_utils::{
+        blob_to_kzg_commitment, blobs_to_data_column_sidecars, reconstruct_blobs,
+        reconstruct_data_columns,
+    };
     use bls::Signature;
-    use kzg::{trusted_setup::get_trusted_setup, Kzg, KzgCommitment, TrustedSetup};
+    use kzg::{trusted_setup::get_trusted_setup, Kzg, TrustedSetup, BYTES_PER_BLOB};
     use types::{
         beacon_block_body::KzgCommitments, BeaconBlock, BeaconBlockDeneb, Blob, BlobsList,
         ChainSpec, EmptyBlock, EthSpec, MainnetEthSpec, SignedBeaconBlock,
@@ -308,12 +407,13 @@ mod test {
         test_build_data_columns_empty(&kzg, &spec);
         test_build_data_columns(&kzg, &spec);
         test_reconstruct_data_columns(&kzg, &spec);
+        test_reconstruct_blobs(&kzg, &spec);
     }
 
     #[track_caller]
     fn test_build_data_columns_empty(kzg: &Kzg, spec: &ChainSpec) {
         let num_of_blobs = 0;
-        let (signed_block, blobs) = create_test_block_and_blobs::<E>(num_of_blobs, spec);
+        let (signed_block, blobs) = create_test_block_and_blobs::<E>(num_of_blobs, kzg, spec);
         let blob_refs = blobs.iter().collect::<Vec<_>>();
         let column_sidecars =
             blobs_to_data_column_sidecars(&blob_refs, &signed_block, kzg, spec).unwrap();
@@ -323,7 +423,7 @@ mod test {
     #[track_caller]
     fn test_build_data_columns(kzg: &Kzg, spec: &ChainSpec) {
         let num_of_blobs = 6;
-        let (signed_block, blobs) = create_test_block_and_blobs::<E>(num_of_blobs, spec);
+        let (signed_block, blobs) = create_test_block_and_blobs::<E>(num_of_blobs, kzg, spec);
 
         let blob_refs = blobs.iter().collect::<Vec<_>>();
         let column_sidecars =
@@ -361,7 +461,7 @@ mod test {
     #[track_caller]
     fn test_reconstruct_data_columns(kzg: &Kzg, spec: &ChainSpec) {
         let num_of_blobs = 6;
-        let (signed_block, blobs) = create_test_block_and_blobs::<E>(num_of_blobs, spec);
+        let (signed_block, blobs) = create_test_block_and_blobs::<E>(num_of_blobs, kzg, spec);
         let blob_refs = blobs.iter().collect::<Vec<_>>();
         let column_sidecars =
             blobs_to_data_column_sidecars(&blob_refs, &signed_block, kzg, spec).unwrap();
@@ -386,24 +486,66 @@ mod test {
         Kzg::new_from_trusted_setup_das_enabled(trusted_setup).expect("should create kzg")
     }
 
+    #[track_caller]
+    fn test_reconstruct_blobs(kzg: &Kzg, spec: &ChainSpec) {
+        let num_of_blobs = 6;
+        let (signed_block, blobs) = create_test_block_and_blobs::<E>(num_of_blobs, kzg, spec);
+        let blob_refs = blobs.iter().collect::<Vec<_>>();
+        let column_sidecars =
+            blobs_to_data_column_sidecars(&blob_refs, &signed_block, kzg, spec).unwrap();
+
+        // Reconstruct from the second half of the columns, in reverse order.
+        let half_of_columns = column_sidecars[column_sidecars.len() / 2..]
+            .iter()
+            .rev()
+            .cloned()
+            .collect::<Vec<_>>();
+        let blob_sidecars = reconstruct_blobs(kzg, &half_of_columns, None, &signed_block).unwrap();
+
+        assert_eq!(blob_sidecars.len(), num_of_blobs);
+        for (i, blob_sidecar) in blob_sidecars.iter().enumerate() {
+            assert_eq!(blob_sidecar.index, i as u64);
+            assert_eq!(blob_sidecar.blob, blobs[i]);
+            assert!(blob_sidecar.verify_blob_sidecar_inclusion_proof());
+        }
+
+        // Reconstruct a subset of the blobs from all of the columns, without recovery.
+        let blob_sidecars =
+            reconstruct_blobs(kzg, &column_sidecars, Some(&[1, 4]), &signed_block).unwrap();
+
+        assert_eq!(blob_sidecars.len(), 2);
+        assert_eq!(blob_sidecars[0].blob, blobs[1]);
+        assert_eq!(blob_sidecars[1].blob, blobs[4]);
+    }
+
     fn create_test_block_and_blobs<E: EthSpec>(
         num_of_blobs: usize,
+        kzg: &Kzg,
         spec: &ChainSpec,
     ) -> (SignedBeaconBlock<E>, BlobsList<E>) {
+        // Each field element must be less than the BLS modulus, so leave the top byte empty.
+        let blobs = (0..num_of_blobs)
+            .map(|i| {
+                let bytes = (0..BYTES_PER_BLOB)
+                    .map(|j| if j % 32 == 0 { 0 } else { (i * 31 + j) as u8 })
+                    .collect::<Vec<_>>();
+                Blob::<E>::new(bytes).unwrap()
+            })
+            .collect::<Vec<_>>();
+
         let mut block = BeaconBlock::Deneb(BeaconBlockDeneb::empty(spec));
         let mut body = block.body_mut();
         let blob_kzg_commitments = body.blob_kzg_commitments_mut().unwrap();
-        *blob_kzg_commitments =
-            KzgCommitments::<E>::new(vec![KzgCommitment::empty_for_testing(); num_of_blobs])
-                .unwrap();
+        *blob_kzg_commitments = KzgCommitments::<E>::new(
+            blobs
+                .iter()
+                .map(|blob| blob_to_kzg_commitment::<E>(kzg, blob).unwrap())
+                .collect(),
+        )
+        .unwrap();
 
         let signed_block = SignedBeaconBlock::from_block(block, Signature::empty());
 
-        let blobs = (0..num_of_blobs)
-            .map(|_| Blob::<E>::default())
-            .collect::<Vec<_>>()
-            .into();
-
-        (signed_block, blobs)
+        (signed_block, blobs.into())
     }
 }
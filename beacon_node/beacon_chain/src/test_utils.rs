@@ -568,6 +568,7 @@ where
             .event_handler(Some(ServerSentEventHandler::new_with_capacity(
                 log.clone(),
                 5,
+                0,
             )))
             .validator_monitor_config(validator_monitor_config);
 
@@ -1,11 +1,13 @@
 use beacon_chain::blob_verification::GossipVerifiedBlob;
-use beacon_chain::test_utils::BeaconChainHarness;
-use eth2::types::{EventKind, SseBlobSidecar};
+use beacon_chain::events::ServerSentEventHandler;
+use beacon_chain::test_utils::{BeaconChainHarness, RelativeSyncCommittee};
+use eth2::types::{EventKind, EventTopic, SseBlobSidecar, SseBlock, SseFinalizedCheckpoint};
+use logging::test_logger;
 use rand::rngs::StdRng;
 use rand::SeedableRng;
 use std::sync::Arc;
 use types::blob_sidecar::FixedBlobSidecarList;
-use types::{BlobSidecar, EthSpec, ForkName, MinimalEthSpec};
+use types::{BlobSidecar, Epoch, EthSpec, ForkName, Hash256, MinimalEthSpec, Slot, SyncSubnetId};
 
 type E = MinimalEthSpec;
 
@@ -40,7 +42,10 @@ async fn blob_sidecar_event_on_process_gossip_blob() {
         .unwrap();
 
     let sidecar_event = blob_event_receiver.try_recv().unwrap();
-    assert_eq!(sidecar_event, EventKind::BlobSidecar(expected_sse_blobs));
+    assert_eq!(
+        sidecar_event.kind,
+        EventKind::BlobSidecar(expected_sse_blobs)
+    );
 }
 
 /// Verifies that a blob event is emitted when blobs are received via RPC.
@@ -87,7 +92,7 @@ async fn blob_sidecar_event_on_process_rpc_blobs() {
 
     let mut sse_blobs: Vec<SseBlobSidecar> = vec![];
     while let Ok(sidecar_event) = blob_event_receiver.try_recv() {
-        if let EventKind::BlobSidecar(sse_blob_sidecar) = sidecar_event {
+        if let EventKind::BlobSidecar(sse_blob_sidecar) = sidecar_event.kind {
             sse_blobs.push(sse_blob_sidecar);
         } else {
             panic!("`BlobSidecar` event kind expected.");
@@ -95,3 +100,137 @@ async fn blob_sidecar_event_on_process_rpc_blobs() {
     }
     assert_eq!(sse_blobs, expected_sse_blobs);
 }
+
+/// Verifies that a sync committee message event is emitted when a sync committee message is
+/// verified.
+#[tokio::test]
+async fn sync_committee_message_event_on_gossip_verification() {
+    let spec = Arc::new(ForkName::Altair.make_genesis_spec(E::default_spec()));
+    let harness = BeaconChainHarness::builder(E::default())
+        .spec(spec)
+        .deterministic_keypairs(8)
+        .fresh_ephemeral_store()
+        .mock_execution_layer()
+        .build();
+    harness.advance_slot();
+
+    // subscribe to sync committee message events
+    let event_handler = harness.chain.event_handler.as_ref().unwrap();
+    let mut sync_message_receiver = event_handler.subscribe_sync_committee_message();
+
+    let head_state = harness.chain.head_beacon_state_cloned();
+    let head_block_root = harness.chain.head_snapshot().beacon_block_root;
+    let (sync_message, _) = harness.make_sync_committee_messages(
+        &head_state,
+        head_block_root,
+        harness.get_current_slot(),
+        RelativeSyncCommittee::Current,
+    )[0][0]
+        .clone();
+
+    harness
+        .chain
+        .verify_sync_committee_message_for_gossip(sync_message.clone(), SyncSubnetId::new(0))
+        .unwrap();
+
+    let sync_message_event = sync_message_receiver.try_recv().unwrap();
+    assert_eq!(
+        sync_message_event.kind,
+        EventKind::LighthouseSyncCommitteeMessage(Box::new(sync_message))
+    );
+}
+
+fn finalized_event(epoch: u64) -> EventKind<E> {
+    EventKind::FinalizedCheckpoint(SseFinalizedCheckpoint {
+        block: Hash256::repeat_byte(epoch as u8),
+        state: Hash256::repeat_byte(epoch as u8),
+        epoch: Epoch::new(epoch),
+        execution_optimistic: false,
+    })
+}
+
+/// Verifies that events registered after `Last-Event-ID` are replayed, and that only the most
+/// recent events are retained.
+#[test]
+fn replay_events_after_last_event_id() {
+    let event_handler = ServerSentEventHandler::<E>::new_with_capacity(test_logger(), 16, 3);
+
+    // Events are retained without subscribers.
+    assert!(event_handler.has_finalized_subscribers());
+    for epoch in 0..4 {
+        event_handler.register(finalized_event(epoch));
+    }
+    // Events of other topics are not replayed.
+    event_handler.register(EventKind::Block(SseBlock {
+        slot: Slot::new(0),
+        block: Hash256::zero(),
+        execution_optimistic: false,
+    }));
+
+    let (retained, _) = event_handler.subscribe_from(&EventTopic::FinalizedCheckpoint, Some(0));
+    assert_eq!(retained.len(), 3);
+    assert!(retained.windows(2).all(|pair| pair[0].id < pair[1].id));
+    assert_eq!(
+        retained.iter().map(|event| &event.kind).collect::<Vec<_>>(),
+        vec![
+            &finalized_event(1),
+            &finalized_event(2),
+            &finalized_event(3)
+        ]
+    );
+
+    let (missed, mut receiver) =
+        event_handler.subscribe_from(&EventTopic::FinalizedCheckpoint, Some(retained[1].id));
+    assert_eq!(missed, vec![retained[2].clone()]);
+
+    // Events are not replayed if the client is not resuming a stream.
+    let (missed, _) = event_handler.subscribe_from(&EventTopic::FinalizedCheckpoint, None);
+    assert!(missed.is_empty());
+
+    event_handler.register(finalized_event(4));
+    let live = receiver.try_recv().unwrap();
+    assert!(live.id > retained[2].id);
+    assert_eq!(live.kind, finalized_event(4));
+}
+
+/// Verifies that a lone subscriber which disconnects is replayed the events it missed when it
+/// reconnects.
+#[test]
+fn replay_events_to_reconnected_subscriber() {
+    let event_handler = ServerSentEventHandler::<E>::new_with_capacity(test_logger(), 16, 8);
+
+    let (_, mut receiver) = event_handler.subscribe_from(&EventTopic::FinalizedCheckpoint, None);
+    event_handler.register(finalized_event(0));
+    let last_seen = receiver.try_recv().unwrap();
+    assert_eq!(last_seen.kind, finalized_event(0));
+
+    // The only subscriber disconnects, and events continue to be produced.
+    drop(receiver);
+    assert!(event_handler.has_finalized_subscribers());
+    for epoch in 1..3 {
+        event_handler.register(finalized_event(epoch));
+    }
+
+    let (missed, _) =
+        event_handler.subscribe_from(&EventTopic::FinalizedCheckpoint, Some(last_seen.id));
+    assert_eq!(
+        missed
+            .into_iter()
+            .map(|event| event.kind)
+            .collect::<Vec<_>>(),
+        vec![finalized_event(1), finalized_event(2)]
+    );
+}
+
+/// Verifies that no events are retained when the replay buffer is disabled.
+#[test]
+fn replay_disabled() {
+    let event_handler = ServerSentEventHandler::<E>::new_with_capacity(test_logger(), 16, 0);
+
+    assert!(!event_handler.has_finalized_subscribers());
+    let _subscriber = event_handler.subscribe_finalized();
+    event_handler.register(finalized_event(0));
+
+    let (missed, _) = event_handler.subscribe_from(&EventTopic::FinalizedCheckpoint, Some(0));
+    assert!(missed.is_empty());
+}
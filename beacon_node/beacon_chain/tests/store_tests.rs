@@ -28,7 +28,7 @@ use std::time::Duration;
 use store::metadata::{SchemaVersion, CURRENT_SCHEMA_VERSION, STATE_UPPER_LIMIT_NO_RETAIN};
 use store::{
     iter::{BlockRootsIterator, StateRootsIterator},
-    BlobInfo, DBColumn, HotColdDB, LevelDB, StoreConfig,
+    BlobInfo, CompactEpochInclusion, DBColumn, HotColdDB, LevelDB, StoreConfig,
 };
 use tempfile::{tempdir, TempDir};
 use tokio::time::sleep;
@@ -250,6 +250,155 @@ async fn full_participation_no_skips() {
     check_iterators(&harness);
 }
 
+#[tokio::test]
+async fn compact_epoch_shufflings_stored_on_migration() {
+    let num_blocks_produced = E::slots_per_epoch() * 6;
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+
+    // Skip the first slot of epoch 2 so that a shuffling is stored for a skipped slot.
+    let skipped_slot = Epoch::new(2).start_slot(E::slots_per_epoch());
+    harness
+        .extend_chain(
+            skipped_slot.as_usize() - 1,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        )
+        .await;
+    harness.advance_slot();
+    harness
+        .extend_chain(
+            (num_blocks_produced - skipped_slot.as_u64()) as usize,
+            BlockStrategy::ForkCanonicalChainAt {
+                previous_slot: skipped_slot - 1,
+                first_slot: skipped_slot + 1,
+            },
+            AttestationStrategy::AllValidators,
+        )
+        .await;
+    check_split_slot(&harness, store.clone());
+
+    let head_state = harness.get_current_state();
+    let split_epoch = store.get_split_slot().epoch(E::slots_per_epoch());
+    for epoch in (0..split_epoch.as_u64()).map(Epoch::new) {
+        let shuffling = store
+            .get_cold_epoch_shuffling(epoch)
+            .unwrap()
+            .unwrap_or_else(|| panic!("no shuffling for epoch {epoch}"));
+        assert_eq!(shuffling.epoch, epoch);
+
+        // The committees match those computed from the head state.
+        assert_eq!(
+            shuffling.committee_cache::<E>(&harness.spec).unwrap(),
+            CommitteeCache::initialized(&head_state, epoch, &harness.spec).unwrap()
+        );
+
+        // The proposers match the proposers of the canonical blocks.
+        for (slot, proposer_index) in epoch
+            .slot_iter(E::slots_per_epoch())
+            .zip(&shuffling.proposer_indices)
+        {
+            if let Some(block_root) = harness
+                .chain
+                .block_root_at_slot(slot, WhenSlotSkipped::None)
+                .unwrap()
+            {
+                let block = store.get_blinded_block(&block_root).unwrap().unwrap();
+                assert_eq!(block.message().proposer_index(), *proposer_index);
+            } else {
+                assert_eq!(slot, skipped_slot);
+            }
+        }
+
+        // The decision roots match those of the canonical chain.
+        let decision_root = |decision_epoch: Epoch| {
+            let decision_slot = decision_epoch
+                .start_slot(E::slots_per_epoch())
+                .saturating_sub(1_u64);
+            harness
+                .chain
+                .block_root_at_slot(decision_slot, WhenSlotSkipped::Prev)
+                .unwrap()
+                .unwrap()
+        };
+        assert_eq!(
+            shuffling.proposer_shuffling_decision_root,
+            decision_root(epoch)
+        );
+        assert_eq!(
+            shuffling.attester_shuffling_decision_root,
+            decision_root(epoch.saturating_sub(1_u64))
+        );
+    }
+
+    // The shuffling for the unfinalized epochs has not been stored yet.
+    assert!(store
+        .get_cold_epoch_shuffling(split_epoch)
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn compact_epoch_inclusions_stored_on_migration() {
+    let num_blocks_produced = E::slots_per_epoch() * 6;
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+
+    harness
+        .extend_chain(
+            num_blocks_produced as usize,
+            BlockStrategy::OnCanonicalHead,
+            AttestationStrategy::AllValidators,
+        )
+        .await;
+    check_split_slot(&harness, store.clone());
+
+    let split_slot = store.get_split_slot();
+    let split_epoch = split_slot.epoch(E::slots_per_epoch());
+    for epoch in (0..split_epoch.as_u64()).map(Epoch::new) {
+        let inclusion = store
+            .get_cold_epoch_inclusion(epoch)
+            .unwrap()
+            .unwrap_or_else(|| panic!("no inclusion for epoch {epoch}"));
+
+        // The inclusion matches that computed from the end of epoch state.
+        let end_slot = epoch.end_slot(E::slots_per_epoch());
+        let state_root = harness.chain.state_root_at_slot(end_slot).unwrap().unwrap();
+        let state = store
+            .get_state(&state_root, Some(end_slot))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            inclusion,
+            CompactEpochInclusion::from_state(&state, &harness.spec).unwrap()
+        );
+        assert_eq!(inclusion.effective_balances.len(), LOW_VALIDATOR_COUNT);
+
+        // Every validator attests, so all of them are included once the previous epoch has a
+        // full set of attestations.
+        if epoch >= 2 {
+            assert_eq!(
+                inclusion.previous_epoch_target_attesting_gwei,
+                inclusion.current_epoch_active_gwei
+            );
+            for index in 0..LOW_VALIDATOR_COUNT {
+                let (flags, _) = inclusion.validator(index).unwrap();
+                assert!(flags.is_previous_epoch_target_attester());
+                assert!(!flags.is_slashed());
+            }
+        }
+        assert!(inclusion.validator(LOW_VALIDATOR_COUNT).is_none());
+    }
+
+    // The inclusion for the unfinalized epochs has not been stored yet.
+    assert!(store
+        .get_cold_epoch_inclusion(split_epoch)
+        .unwrap()
+        .is_none());
+}
+
 #[tokio::test]
 async fn randomised_skips() {
     let num_slots = E::slots_per_epoch() * 5;
@@ -2366,6 +2515,7 @@ async fn weak_subjectivity_sync_test(slots: Vec<Slot>, checkpoint_slot: Slot) {
         .event_handler(Some(ServerSentEventHandler::new_with_capacity(
             log.clone(),
             1,
+            0,
         )))
         .execution_layer(Some(mock.el))
         .build()
@@ -67,7 +67,7 @@ use types::{
 use types::{EthSpec, Slot};
 use work_reprocessing_queue::{
     spawn_reprocess_scheduler, QueuedAggregate, QueuedLightClientUpdate, QueuedRpcBlock,
-    QueuedUnaggregate, ReadyWork,
+    QueuedUnaggregate, ReadyWork, BACKFILL_SCHEDULE_IN_SLOT,
 };
 use work_reprocessing_queue::{IgnoredRpcBlock, QueuedSamplingRequest};
 
@@ -136,6 +136,7 @@ pub struct BeaconProcessorQueueLengths {
     lc_optimistic_update_queue: usize,
     lc_finality_update_queue: usize,
     lc_update_range_queue: usize,
+    checkpoint_state_queue: usize,
     api_request_p0_queue: usize,
     api_request_p1_queue: usize,
 }
@@ -204,6 +205,7 @@ impl BeaconProcessorQueueLengths {
             lc_optimistic_update_queue: 512,
             lc_finality_update_queue: 512,
             lc_update_range_queue: 512,
+            checkpoint_state_queue: 64,
             api_request_p0_queue: 1024,
             api_request_p1_queue: 1024,
         })
@@ -242,6 +244,9 @@ pub struct BeaconProcessorConfig {
     pub max_gossip_attestation_batch_size: usize,
     pub max_gossip_aggregate_batch_size: usize,
     pub enable_backfill_rate_limiting: bool,
+    /// Fractions of each slot at which a backfill batch is processed when backfill rate limiting
+    /// is enabled. The number of entries bounds the backfill batches processed per slot.
+    pub backfill_schedule_in_slot: Vec<(u32, u32)>,
 }
 
 impl Default for BeaconProcessorConfig {
@@ -253,6 +258,7 @@ impl Default for BeaconProcessorConfig {
             max_gossip_attestation_batch_size: DEFAULT_MAX_GOSSIP_ATTESTATION_BATCH_SIZE,
             max_gossip_aggregate_batch_size: DEFAULT_MAX_GOSSIP_AGGREGATE_BATCH_SIZE,
             enable_backfill_rate_limiting: true,
+            backfill_schedule_in_slot: BACKFILL_SCHEDULE_IN_SLOT.to_vec(),
         }
     }
 }
@@ -625,6 +631,7 @@ pub enum Work<E: EthSpec> {
     LightClientOptimisticUpdateRequest(BlockingFn),
     LightClientFinalityUpdateRequest(BlockingFn),
     LightClientUpdatesByRangeRequest(BlockingFn),
+    CheckpointStateChunksRequest(BlockingFn),
     ApiRequestP0(BlockingOrAsync),
     ApiRequestP1(BlockingOrAsync),
 }
@@ -677,6 +684,7 @@ pub enum WorkType {
     LightClientOptimisticUpdateRequest,
     LightClientFinalityUpdateRequest,
     LightClientUpdatesByRangeRequest,
+    CheckpointStateChunksRequest,
     ApiRequestP0,
     ApiRequestP1,
 }
@@ -728,6 +736,7 @@ impl<E: EthSpec> Work<E> {
             }
             Work::LightClientFinalityUpdateRequest(_) => WorkType::LightClientFinalityUpdateRequest,
             Work::LightClientUpdatesByRangeRequest(_) => WorkType::LightClientUpdatesByRangeRequest,
+            Work::CheckpointStateChunksRequest(_) => WorkType::CheckpointStateChunksRequest,
             Work::UnknownBlockAttestation { .. } => WorkType::UnknownBlockAttestation,
             Work::UnknownBlockAggregate { .. } => WorkType::UnknownBlockAggregate,
             Work::UnknownBlockSamplingRequest { .. } => WorkType::UnknownBlockSamplingRequest,
@@ -909,6 +918,8 @@ impl<E: EthSpec> BeaconProcessor<E> {
         let mut lc_finality_update_queue = FifoQueue::new(queue_lengths.lc_finality_update_queue);
         let mut lc_update_range_queue = FifoQueue::new(queue_lengths.lc_update_range_queue);
 
+        let mut checkpoint_state_queue = FifoQueue::new(queue_lengths.checkpoint_state_queue);
+
         let mut api_request_p0_queue = FifoQueue::new(queue_lengths.api_request_p0_queue);
         let mut api_request_p1_queue = FifoQueue::new(queue_lengths.api_request_p1_queue);
 
@@ -923,6 +934,7 @@ impl<E: EthSpec> BeaconProcessor<E> {
             Arc::new(slot_clock),
             self.log.clone(),
             maximum_gossip_clock_disparity,
+            self.config.backfill_schedule_in_slot.clone(),
         )?;
 
         let executor = self.executor.clone();
@@ -1236,6 +1248,9 @@ impl<E: EthSpec> BeaconProcessor<E> {
                         // Handle backfill sync chain segments.
                         } else if let Some(item) = backfill_chain_segment.pop() {
                             Some(item)
+                        // Serve states to peers checkpoint syncing from us.
+                        } else if let Some(item) = checkpoint_state_queue.pop() {
+                            Some(item)
                         // Handle light client requests.
                         } else if let Some(item) = lc_bootstrap_queue.pop() {
                             Some(item)
@@ -1388,6 +1403,9 @@ impl<E: EthSpec> BeaconProcessor<E> {
                             Work::LightClientUpdatesByRangeRequest { .. } => {
                                 lc_update_range_queue.push(work, work_id, &self.log)
                             }
+                            Work::CheckpointStateChunksRequest { .. } => {
+                                checkpoint_state_queue.push(work, work_id, &self.log)
+                            }
                             Work::UnknownBlockAttestation { .. } => {
                                 unknown_block_attestation_queue.push(work)
                             }
@@ -1480,6 +1498,7 @@ impl<E: EthSpec> BeaconProcessor<E> {
                             lc_finality_update_queue.len()
                         }
                         WorkType::LightClientUpdatesByRangeRequest => lc_update_range_queue.len(),
+                        WorkType::CheckpointStateChunksRequest => checkpoint_state_queue.len(),
                         WorkType::ApiRequestP0 => api_request_p0_queue.len(),
                         WorkType::ApiRequestP1 => api_request_p1_queue.len(),
                     };
@@ -1633,7 +1652,8 @@ impl<E: EthSpec> BeaconProcessor<E> {
             | Work::LightClientBootstrapRequest(process_fn)
             | Work::LightClientOptimisticUpdateRequest(process_fn)
             | Work::LightClientFinalityUpdateRequest(process_fn)
-            | Work::LightClientUpdatesByRangeRequest(process_fn) => {
+            | Work::LightClientUpdatesByRangeRequest(process_fn)
+            | Work::CheckpointStateChunksRequest(process_fn) => {
                 task_spawner.spawn_blocking(process_fn)
             }
         };
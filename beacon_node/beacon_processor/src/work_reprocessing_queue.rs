@@ -70,6 +70,8 @@ const MAXIMUM_QUEUED_SAMPLING_REQUESTS: usize = 16_384;
 
 // Process backfill batch 50%, 60%, 80% through each slot.
 //
+// This is the default schedule, see `BeaconProcessorConfig::backfill_schedule_in_slot`.
+//
 // Note: use caution to set these fractions in a way that won't cause panic-y
 // arithmetic.
 pub const BACKFILL_SCHEDULE_IN_SLOT: [(u32, u32); 3] = [
@@ -265,6 +267,8 @@ struct ReprocessQueue<S> {
     lc_update_delay_debounce: TimeLatch,
     sampling_request_delay_debounce: TimeLatch,
     next_backfill_batch_event: Option<Pin<Box<tokio::time::Sleep>>>,
+    /// Fractions of the slot at which a queued backfill batch is processed, in ascending order.
+    backfill_schedule_in_slot: Vec<(u32, u32)>,
     slot_clock: Arc<S>,
 }
 
@@ -376,12 +380,25 @@ pub fn spawn_reprocess_scheduler<S: SlotClock + 'static>(
     slot_clock: Arc<S>,
     log: Logger,
     maximum_gossip_clock_disparity: Duration,
+    backfill_schedule_in_slot: Vec<(u32, u32)>,
 ) -> Result<(), String> {
     // Sanity check
     if ADDITIONAL_QUEUED_BLOCK_DELAY >= maximum_gossip_clock_disparity {
         return Err("The block delay and gossip disparity don't match.".to_string());
     }
-    let mut queue = ReprocessQueue::new(ready_work_tx, work_reprocessing_rx, slot_clock);
+    if backfill_schedule_in_slot.is_empty()
+        || backfill_schedule_in_slot
+            .iter()
+            .any(|&(multiplier, divisor)| divisor == 0 || multiplier >= divisor)
+    {
+        return Err("Backfill schedule must contain fractions of a slot.".to_string());
+    }
+    let mut queue = ReprocessQueue::new(
+        ready_work_tx,
+        work_reprocessing_rx,
+        slot_clock,
+        backfill_schedule_in_slot,
+    );
 
     executor.spawn(
         async move {
@@ -405,7 +422,15 @@ impl<S: SlotClock> ReprocessQueue<S> {
         ready_work_tx: Sender<ReadyWork>,
         work_reprocessing_rx: Receiver<ReprocessQueueMessage>,
         slot_clock: Arc<S>,
+        mut backfill_schedule_in_slot: Vec<(u32, u32)>,
     ) -> Self {
+        // Compare the fractions without losing precision.
+        backfill_schedule_in_slot.sort_by(
+            |(a_multiplier, a_divisor), (b_multiplier, b_divisor)| {
+                (*a_multiplier as u64 * *b_divisor as u64)
+                    .cmp(&(*b_multiplier as u64 * *a_divisor as u64))
+            },
+        );
         ReprocessQueue {
             work_reprocessing_rx,
             ready_work_tx,
@@ -432,6 +457,7 @@ impl<S: SlotClock> ReprocessQueue<S> {
             lc_update_delay_debounce: TimeLatch::default(),
             sampling_request_delay_debounce: <_>::default(),
             next_backfill_batch_event: None,
+            backfill_schedule_in_slot,
             slot_clock,
         }
     }
@@ -1018,7 +1044,10 @@ impl<S: SlotClock> ReprocessQueue<S> {
         // only recompute the `next_backfill_batch_event` if there are backfill batches in the queue
         if !self.queued_backfill_batches.is_empty() {
             self.next_backfill_batch_event = Some(Box::pin(tokio::time::sleep(
-                ReprocessQueue::<S>::duration_until_next_backfill_batch_event(&self.slot_clock),
+                ReprocessQueue::<S>::duration_until_next_backfill_batch_event(
+                    &self.slot_clock,
+                    &self.backfill_schedule_in_slot,
+                ),
             )));
         } else {
             self.next_backfill_batch_event = None
@@ -1027,13 +1056,17 @@ impl<S: SlotClock> ReprocessQueue<S> {
 
     /// Returns duration until the next scheduled processing time. The schedule ensure that backfill
     /// processing is done in windows of time that aren't critical
-    fn duration_until_next_backfill_batch_event(slot_clock: &S) -> Duration {
+    fn duration_until_next_backfill_batch_event(
+        slot_clock: &S,
+        backfill_schedule_in_slot: &[(u32, u32)],
+    ) -> Duration {
         let slot_duration = slot_clock.slot_duration();
         slot_clock
             .millis_from_current_slot_start()
             .and_then(|duration_from_slot_start| {
-                BACKFILL_SCHEDULE_IN_SLOT
-                    .into_iter()
+                backfill_schedule_in_slot
+                    .iter()
+                    .copied()
                     // Convert fractions to seconds from slot start.
                     .map(|(multiplier, divisor)| (slot_duration / divisor) * multiplier)
                     .find_or_first(|&event_duration_from_slot_start| {
@@ -1077,6 +1110,7 @@ mod tests {
             let duration_to_next_event =
                 ReprocessQueue::<TestingSlotClock>::duration_until_next_backfill_batch_event(
                     &slot_clock,
+                    &BACKFILL_SCHEDULE_IN_SLOT,
                 );
 
             let current_time = slot_clock.millis_from_current_slot_start().unwrap();
@@ -1094,6 +1128,7 @@ mod tests {
         let duration_to_next_event =
             ReprocessQueue::<TestingSlotClock>::duration_until_next_backfill_batch_event(
                 &slot_clock,
+                &BACKFILL_SCHEDULE_IN_SLOT,
             );
         assert_eq!(
             duration_to_next_event,
@@ -1101,6 +1136,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn custom_backfill_processing_schedule_calculation() {
+        let slot_duration = Duration::from_secs(12);
+        let slot_clock = TestingSlotClock::new(Slot::new(0), Duration::from_secs(0), slot_duration);
+        let current_slot_start = slot_clock.start_of(Slot::new(100)).unwrap();
+        slot_clock.set_current_time(current_slot_start);
+
+        // A single batch per slot, processed three quarters through the slot.
+        let schedule = [(3, 4)];
+        let duration_to_next_event =
+            ReprocessQueue::<TestingSlotClock>::duration_until_next_backfill_batch_event(
+                &slot_clock,
+                &schedule,
+            );
+        assert_eq!(duration_to_next_event, Duration::from_secs(9));
+
+        slot_clock.set_current_time(current_slot_start + Duration::from_secs(10));
+        let duration_to_next_event =
+            ReprocessQueue::<TestingSlotClock>::duration_until_next_backfill_batch_event(
+                &slot_clock,
+                &schedule,
+            );
+        assert_eq!(duration_to_next_event, Duration::from_secs(11));
+    }
+
     // Regression test for issue #5504.
     // See: https://github.com/sigp/lighthouse/issues/5504#issuecomment-2050930045
     #[tokio::test]
@@ -1119,6 +1179,7 @@ mod tests {
             slot_clock.clone(),
             log,
             Duration::from_millis(500),
+            BACKFILL_SCHEDULE_IN_SLOT.to_vec(),
         )
         .unwrap();
 
@@ -1142,8 +1203,10 @@ mod tests {
 
         // Advance the time by more than 1/2 the slot to trigger a scheduled backfill batch to be sent.
         // This should fail as the `ready_work` channel is at capacity, and it should be rescheduled.
-        let duration_to_next_event =
-            ReprocessQueue::duration_until_next_backfill_batch_event(slot_clock.as_ref());
+        let duration_to_next_event = ReprocessQueue::duration_until_next_backfill_batch_event(
+            slot_clock.as_ref(),
+            &BACKFILL_SCHEDULE_IN_SLOT,
+        );
         let one_ms = Duration::from_millis(1);
         advance_time(&slot_clock, duration_to_next_event.add(one_ms)).await;
 
@@ -1155,8 +1218,10 @@ mod tests {
         assert!(ready_work_rx.try_recv().is_err());
 
         // Advance time again, and assert that the re-scheduled batch is successfully sent.
-        let duration_to_next_event =
-            ReprocessQueue::duration_until_next_backfill_batch_event(slot_clock.as_ref());
+        let duration_to_next_event = ReprocessQueue::duration_until_next_backfill_batch_event(
+            slot_clock.as_ref(),
+            &BACKFILL_SCHEDULE_IN_SLOT,
+        );
         advance_time(&slot_clock, duration_to_next_event.add(one_ms)).await;
         assert!(matches!(
             ready_work_rx.try_recv(),
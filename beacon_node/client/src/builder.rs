@@ -6,6 +6,7 @@ use crate::notifier::spawn_notifier;
 use crate::Client;
 use beacon_chain::attestation_simulator::start_attestation_simulator_service;
 use beacon_chain::data_availability_checker::start_availability_cache_maintenance_service;
+use beacon_chain::engine_state_events::start_engine_state_event_service;
 use beacon_chain::graffiti_calculator::start_engine_version_cache_refresh_service;
 use beacon_chain::otb_verification_service::start_otb_verification_service;
 use beacon_chain::proposer_prep_service::start_proposer_prep_service;
@@ -177,6 +178,7 @@ where
             Some(ServerSentEventHandler::new(
                 context.log().clone(),
                 self.http_api_config.sse_capacity_multiplier,
+                self.http_api_config.sse_replay_buffer_size,
             ))
         } else {
             None
@@ -252,6 +254,7 @@ where
         } else if chain_exists {
             if matches!(client_genesis, ClientGenesis::WeakSubjSszBytes { .. })
                 || matches!(client_genesis, ClientGenesis::CheckpointSyncUrl { .. })
+                || matches!(client_genesis, ClientGenesis::CheckpointSyncP2p)
             {
                 info!(
                     context.log(),
@@ -539,6 +542,46 @@ where
                     .weak_subjectivity_state(state, block, blobs, genesis_state)
                     .map(|v| (v, service))?
             }
+            ClientGenesis::CheckpointSyncP2p => {
+                let checkpoint = config.chain.weak_subjectivity_checkpoint.ok_or(
+                    "A weak subjectivity checkpoint is required to checkpoint sync from peers",
+                )?;
+                info!(
+                    context.log(),
+                    "Starting checkpoint sync from peers";
+                    "block_root" => ?checkpoint.root,
+                    "epoch" => checkpoint.epoch,
+                );
+                if config.chain.genesis_backfill {
+                    info!(
+                        context.log(),
+                        "Blocks will be downloaded all the way back to genesis"
+                    );
+                }
+
+                let genesis_state = genesis_state(&runtime_context, &config, log).await?;
+                let (state, block, blobs) = network::checkpoint_sync::checkpoint_sync_from_peers(
+                    context.executor.clone(),
+                    &config.network,
+                    checkpoint,
+                    &genesis_state,
+                    spec.clone(),
+                    context.log(),
+                )
+                .await?;
+
+                info!(
+                    context.log(),
+                    "Loaded checkpoint block and state";
+                    "block_slot" => block.slot(),
+                    "state_slot" => state.slot(),
+                    "block_root" => ?checkpoint.root,
+                );
+
+                builder
+                    .weak_subjectivity_state(state, block, blobs, genesis_state)
+                    .map(|v| (v, None))?
+            }
             ClientGenesis::DepositContract => {
                 info!(
                     context.log(),
@@ -970,6 +1013,10 @@ where
             }
 
             start_proposer_prep_service(runtime_context.executor.clone(), beacon_chain.clone());
+            start_engine_state_event_service(
+                runtime_context.executor.clone(),
+                beacon_chain.clone(),
+            );
             start_otb_verification_service(runtime_context.executor.clone(), beacon_chain.clone());
             start_availability_cache_maintenance_service(
                 runtime_context.executor.clone(),
@@ -46,6 +46,8 @@ pub enum ClientGenesis {
     CheckpointSyncUrl {
         url: SensitiveUrl,
     },
+    /// Downloads the block and state of the weak subjectivity checkpoint from peers.
+    CheckpointSyncP2p,
 }
 
 /// The core configuration of a Lighthouse beacon node.
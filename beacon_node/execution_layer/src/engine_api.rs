@@ -30,12 +30,14 @@ use types::{Graffiti, GRAFFITI_BYTES_LEN};
 
 pub mod auth;
 pub mod http;
+#[cfg(unix)]
+pub mod ipc;
 pub mod json_structures;
 mod new_payload_request;
 
 pub use new_payload_request::{
     NewPayloadRequest, NewPayloadRequestBellatrix, NewPayloadRequestCapella,
-    NewPayloadRequestDeneb, NewPayloadRequestElectra,
+    NewPayloadRequestDeneb, NewPayloadRequestElectra, OwnedNewPayloadRequest,
 };
 
 pub const LATEST_TAG: &str = "latest";
@@ -48,6 +50,7 @@ pub enum Error {
     Auth(auth::Error),
     BadResponse(String),
     RequestFailed(String),
+    Ipc(String),
     InvalidExecutePayloadResponse(&'static str),
     JsonRpc(RpcError),
     Json(serde_json::Error),
@@ -1,18 +1,21 @@
-//! Contains an implementation of `EngineAPI` using the JSON-RPC API via HTTP.
+//! Contains an implementation of `EngineAPI` using the JSON-RPC API via HTTP, or via IPC for
+//! `ipc://` endpoints.
 
 use super::*;
 use crate::auth::Auth;
 use crate::json_structures::*;
+use crate::recorder::EngineApiRecorder;
 use lighthouse_version::{COMMIT_PREFIX, VERSION};
 use reqwest::header::CONTENT_TYPE;
 use sensitive_url::SensitiveUrl;
 use serde::de::DeserializeOwned;
 use serde_json::json;
 use std::collections::HashSet;
+use std::path::PathBuf;
 use std::sync::LazyLock;
 use tokio::sync::Mutex;
 
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 
 pub use deposit_log::{DepositLog, Log};
 pub use reqwest::Client;
@@ -596,6 +599,31 @@ impl<T: Clone> CachedResponse<T> {
     }
 }
 
+/// The URL scheme of IPC endpoints, e.g. `ipc:///var/run/geth/engine.ipc`.
+pub const IPC_SCHEME: &str = "ipc";
+
+/// Returns the socket path of an IPC endpoint, or `None` if `url` is not an IPC endpoint.
+///
+/// IPC endpoints are Unix domain sockets, so they are an error on other platforms.
+pub fn ipc_socket_path(url: &SensitiveUrl) -> Result<Option<PathBuf>, Error> {
+    if url.full.scheme() != IPC_SCHEME {
+        return Ok(None);
+    }
+
+    #[cfg(unix)]
+    {
+        ipc::socket_path(url)
+            .map(Some)
+            .ok_or_else(|| Error::Ipc(format!("invalid socket path in {}", url)))
+    }
+    #[cfg(not(unix))]
+    {
+        Err(Error::Ipc(
+            "IPC endpoints are only supported on Unix platforms".to_string(),
+        ))
+    }
+}
+
 pub struct HttpJsonRpc {
     pub client: Client,
     pub url: SensitiveUrl,
@@ -603,6 +631,9 @@ pub struct HttpJsonRpc {
     pub engine_capabilities_cache: Mutex<Option<CachedResponse<EngineCapabilities>>>,
     pub engine_version_cache: Mutex<Option<CachedResponse<Vec<ClientVersionV1>>>>,
     auth: Option<Auth>,
+    /// The socket path of an `ipc://` endpoint, which is used instead of HTTP.
+    ipc_path: Option<PathBuf>,
+    recorder: Option<EngineApiRecorder>,
 }
 
 impl HttpJsonRpc {
@@ -612,6 +643,8 @@ impl HttpJsonRpc {
     ) -> Result<Self, Error> {
         Ok(Self {
             client: Client::builder().build()?,
+            ipc_path: ipc_socket_path(&url)?,
+            recorder: None,
             url,
             execution_timeout_multiplier: execution_timeout_multiplier.unwrap_or(1),
             engine_capabilities_cache: Mutex::new(None),
@@ -627,6 +660,8 @@ impl HttpJsonRpc {
     ) -> Result<Self, Error> {
         Ok(Self {
             client: Client::builder().build()?,
+            ipc_path: ipc_socket_path(&url)?,
+            recorder: None,
             url,
             execution_timeout_multiplier: execution_timeout_multiplier.unwrap_or(1),
             engine_capabilities_cache: Mutex::new(None),
@@ -635,6 +670,20 @@ impl HttpJsonRpc {
         })
     }
 
+    /// Records every request and its response with `recorder`.
+    pub fn with_recorder(mut self, recorder: EngineApiRecorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// The endpoint of the engine, with any credentials redacted.
+    pub fn endpoint(&self) -> String {
+        match &self.ipc_path {
+            Some(path) => format!("{}://{}", IPC_SCHEME, path.display()),
+            None => self.url.to_string(),
+        }
+    }
+
     pub async fn rpc_request<D: DeserializeOwned>(
         &self,
         method: &str,
@@ -648,20 +697,52 @@ impl HttpJsonRpc {
             id: json!(STATIC_ID),
         };
 
+        let sent_at = SystemTime::now();
+        let start = Instant::now();
+        let response = self.send_rpc_request(&body, timeout).await;
+
+        if let Some(recorder) = &self.recorder {
+            recorder.record(
+                self.endpoint(),
+                method,
+                body.params,
+                sent_at,
+                start.elapsed(),
+                &response,
+            );
+        }
+
+        Self::response_result(response?)
+    }
+
+    /// Sends a JSON-RPC request and returns the response without interpreting it.
+    pub async fn send_rpc_request(
+        &self,
+        body: &JsonRequestBody<'_>,
+        timeout: Duration,
+    ) -> Result<JsonResponseBody, Error> {
+        // The socket is protected by its file permissions, so no token is sent over IPC.
+        #[cfg(unix)]
+        if let Some(path) = &self.ipc_path {
+            return ipc::request(path, body, timeout).await;
+        }
+
         let mut request = self
             .client
             .post(self.url.full.clone())
             .timeout(timeout)
             .header(CONTENT_TYPE, "application/json")
-            .json(&body);
+            .json(body);
 
         // Generate and add a jwt token to the header if auth is defined.
         if let Some(auth) = &self.auth {
             request = request.bearer_auth(auth.generate_token()?);
         };
 
-        let body: JsonResponseBody = request.send().await?.error_for_status()?.json().await?;
+        Ok(request.send().await?.error_for_status()?.json().await?)
+    }
 
+    fn response_result<D: DeserializeOwned>(body: JsonResponseBody) -> Result<D, Error> {
         match (body.result, body.error) {
             (result, None) => serde_json::from_value(result).map_err(Into::into),
             (_, Some(error)) => {
@@ -680,7 +761,10 @@ impl HttpJsonRpc {
 
 impl std::fmt::Display for HttpJsonRpc {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}, auth={}", self.url, self.auth.is_some())
+        match &self.ipc_path {
+            Some(_) => write!(f, "{}", self.endpoint()),
+            None => write!(f, "{}, auth={}", self.url, self.auth.is_some()),
+        }
     }
 }
 
@@ -2090,4 +2174,93 @@ mod test {
             )
             .await;
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn ipc_requests() {
+        let server = MockServer::<MainnetEthSpec>::unit_testing();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("engine.ipc");
+        server.listen_ipc(&path).unwrap();
+
+        // No JWT is required over IPC.
+        let url = SensitiveUrl::parse(&format!("ipc://{}", path.display())).unwrap();
+        let client = HttpJsonRpc::new(url, None).unwrap();
+        assert_eq!(client.to_string(), format!("ipc://{}", path.display()));
+
+        client.upcheck().await.unwrap();
+        let block = client
+            .get_block_by_number(BlockByNumberQuery::Tag(LATEST_TAG))
+            .await
+            .unwrap();
+        assert!(block.is_some());
+
+        *server.ctx.syncing_response.lock() = Ok(true);
+        assert!(matches!(client.upcheck().await, Err(Error::IsSyncing)));
+
+        server.push_preloaded_response(json!({
+            "jsonrpc": JSONRPC_VERSION,
+            "id": STATIC_ID,
+            "error": { "code": -32000, "message": "meow" }
+        }));
+        assert!(matches!(
+            client.upcheck().await,
+            Err(Error::ServerMessage { code: -32000, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn records_requests() {
+        use crate::recorder::{RecorderConfig, RecordingReader};
+        use task_executor::test_utils::TestRuntime;
+
+        let runtime = TestRuntime::default();
+        let dir = tempfile::tempdir().unwrap();
+        let recorder = EngineApiRecorder::start(
+            RecorderConfig::new(dir.path().to_path_buf()),
+            &runtime.task_executor,
+            &runtime.log,
+        )
+        .unwrap();
+
+        let server = MockServer::<MainnetEthSpec>::unit_testing();
+        let url = SensitiveUrl::parse(&server.url()).unwrap();
+        let auth = Auth::new(JwtKey::from_slice(&DEFAULT_JWT_SECRET).unwrap(), None, None);
+        let client = HttpJsonRpc::new_with_auth(url.clone(), auth, None)
+            .unwrap()
+            .with_recorder(recorder);
+
+        client.upcheck().await.unwrap();
+        *server.ctx.syncing_response.lock() = Err("meow".to_string());
+        assert!(client.upcheck().await.is_err());
+
+        // The records are written by a separate task.
+        let mut records = vec![];
+        for _ in 0..100 {
+            records = RecordingReader::open(dir.path())
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            if records.len() == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        assert_eq!(records.len(), 2);
+        for record in &records {
+            assert_eq!(record.endpoint, url.to_string());
+            assert_eq!(record.engine, 0);
+            assert_eq!(record.method, ETH_SYNCING);
+            assert_eq!(record.params, json!([]));
+            assert_eq!(record.error, None);
+        }
+        let responses = records
+            .iter()
+            .map(|record| record.response.as_ref().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(responses[0].result, json!(false));
+        assert_eq!(responses[0].error, None);
+        assert_eq!(responses[1].error.as_ref().unwrap().message, "meow");
+    }
 }
@@ -0,0 +1,184 @@
+//! Contains the IPC transport of the JSON-RPC API, used by `HttpJsonRpc` for `ipc://` endpoints.
+//!
+//! Requests and responses are written to a Unix domain socket as plain JSON objects without any
+//! HTTP framing, in the same way as the IPC endpoints of the execution clients. Access to the
+//! socket is controlled by its file permissions, so no JWT token is sent.
+
+use super::{http::IPC_SCHEME, Error};
+use sensitive_url::SensitiveUrl;
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixStream;
+
+/// The minimum buffer space available to each read from the socket.
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Returns the socket path of an IPC endpoint (e.g. `ipc:///var/run/geth/engine.ipc`), or `None`
+/// if `url` is not an IPC endpoint.
+pub fn socket_path(url: &SensitiveUrl) -> Option<PathBuf> {
+    if url.full.scheme() != IPC_SCHEME {
+        return None;
+    }
+    url.full.to_file_path().ok()
+}
+
+/// Sends `body` over a new connection to the socket at `path` and returns the response.
+pub async fn request<T: Serialize, D: DeserializeOwned>(
+    path: &Path,
+    body: &T,
+    timeout: Duration,
+) -> Result<D, Error> {
+    let request = async {
+        let mut stream = UnixStream::connect(path).await.map_err(io_error)?;
+        stream
+            .write_all(&serde_json::to_vec(body)?)
+            .await
+            .map_err(io_error)?;
+        JsonReader::new(stream)
+            .read_value()
+            .await?
+            .ok_or_else(|| Error::Ipc("connection closed without a response".to_string()))
+    };
+
+    tokio::time::timeout(timeout, request)
+        .await
+        .map_err(|_| Error::Ipc(format!("request timed out after {:?}", timeout)))?
+}
+
+fn io_error(e: std::io::Error) -> Error {
+    Error::Ipc(e.to_string())
+}
+
+/// Reads consecutive JSON objects (or arrays) from a stream.
+///
+/// The end of each value is found by scanning the bytes as they arrive, so that large responses
+/// are only deserialized once.
+pub struct JsonReader<R> {
+    reader: R,
+    buf: Vec<u8>,
+    /// The number of bytes of `buf` scanned so far.
+    scanned: usize,
+    /// The nesting depth of objects and arrays at the end of the scanned bytes.
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+}
+
+impl<R: AsyncRead + Unpin> JsonReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: vec![],
+            scanned: 0,
+            depth: 0,
+            in_string: false,
+            escaped: false,
+        }
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Returns the next value, or `None` if the stream ended in between two values.
+    pub async fn read_value<D: DeserializeOwned>(&mut self) -> Result<Option<D>, Error> {
+        loop {
+            if let Some(end) = self.scan() {
+                let value = serde_json::from_slice(&self.buf[..end]);
+                self.buf.drain(..end);
+                self.scanned = 0;
+                return value.map(Some).map_err(Into::into);
+            }
+
+            self.buf.reserve(READ_CHUNK_SIZE);
+            if self
+                .reader
+                .read_buf(&mut self.buf)
+                .await
+                .map_err(io_error)?
+                == 0
+            {
+                return if self.buf.iter().all(u8::is_ascii_whitespace) {
+                    Ok(None)
+                } else {
+                    Err(Error::Ipc("connection closed mid-message".to_string()))
+                };
+            }
+        }
+    }
+
+    /// Scans the unscanned bytes, returning the length of the first complete value in `buf`.
+    fn scan(&mut self) -> Option<usize> {
+        while self.scanned < self.buf.len() {
+            let byte = self.buf[self.scanned];
+            self.scanned += 1;
+
+            if self.in_string {
+                if self.escaped {
+                    self.escaped = false;
+                } else if byte == b'\\' {
+                    self.escaped = true;
+                } else if byte == b'"' {
+                    self.in_string = false;
+                }
+                continue;
+            }
+
+            match byte {
+                b'"' => self.in_string = true,
+                b'{' | b'[' => self.depth += 1,
+                b'}' | b']' => {
+                    self.depth = self.depth.saturating_sub(1);
+                    if self.depth == 0 {
+                        return Some(self.scanned);
+                    }
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn socket_path_of_ipc_endpoints() {
+        let url = SensitiveUrl::parse("ipc:///var/run/geth/engine.ipc").unwrap();
+        assert_eq!(
+            socket_path(&url),
+            Some(PathBuf::from("/var/run/geth/engine.ipc"))
+        );
+
+        let url = SensitiveUrl::parse("http://localhost:8551").unwrap();
+        assert_eq!(socket_path(&url), None);
+    }
+
+    #[tokio::test]
+    async fn reads_consecutive_values() {
+        let stream = br#" {"a":"}\"{"} [1,{"b":[]}]{"c":"#;
+        let mut reader = JsonReader::new(&stream[..]);
+
+        assert_eq!(
+            reader.read_value::<Value>().await.unwrap(),
+            Some(json!({"a": "}\"{"}))
+        );
+        assert_eq!(
+            reader.read_value::<Value>().await.unwrap(),
+            Some(json!([1, {"b": []}]))
+        );
+        assert!(matches!(
+            reader.read_value::<Value>().await,
+            Err(Error::Ipc(_))
+        ));
+
+        let mut reader = JsonReader::new(&b"{} \n"[..]);
+        assert_eq!(reader.read_value::<Value>().await.unwrap(), Some(json!({})));
+        assert_eq!(reader.read_value::<Value>().await.unwrap(), None);
+    }
+}
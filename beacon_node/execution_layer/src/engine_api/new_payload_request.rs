@@ -150,6 +150,85 @@ impl<'block, E: EthSpec> NewPayloadRequest<'block, E> {
     }
 }
 
+/// An owned copy of a `NewPayloadRequest`, so that the request can be sent from another task.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OwnedNewPayloadRequest<E: EthSpec> {
+    Bellatrix {
+        execution_payload: ExecutionPayloadBellatrix<E>,
+    },
+    Capella {
+        execution_payload: ExecutionPayloadCapella<E>,
+    },
+    Deneb {
+        execution_payload: ExecutionPayloadDeneb<E>,
+        versioned_hashes: Vec<VersionedHash>,
+        parent_beacon_block_root: Hash256,
+    },
+    Electra {
+        execution_payload: ExecutionPayloadElectra<E>,
+        versioned_hashes: Vec<VersionedHash>,
+        parent_beacon_block_root: Hash256,
+        execution_requests_list: ExecutionRequests<E>,
+    },
+}
+
+impl<E: EthSpec> OwnedNewPayloadRequest<E> {
+    pub fn as_request(&self) -> NewPayloadRequest<'_, E> {
+        match self {
+            Self::Bellatrix { execution_payload } => {
+                NewPayloadRequest::Bellatrix(NewPayloadRequestBellatrix { execution_payload })
+            }
+            Self::Capella { execution_payload } => {
+                NewPayloadRequest::Capella(NewPayloadRequestCapella { execution_payload })
+            }
+            Self::Deneb {
+                execution_payload,
+                versioned_hashes,
+                parent_beacon_block_root,
+            } => NewPayloadRequest::Deneb(NewPayloadRequestDeneb {
+                execution_payload,
+                versioned_hashes: versioned_hashes.clone(),
+                parent_beacon_block_root: *parent_beacon_block_root,
+            }),
+            Self::Electra {
+                execution_payload,
+                versioned_hashes,
+                parent_beacon_block_root,
+                execution_requests_list,
+            } => NewPayloadRequest::Electra(NewPayloadRequestElectra {
+                execution_payload,
+                versioned_hashes: versioned_hashes.clone(),
+                parent_beacon_block_root: *parent_beacon_block_root,
+                execution_requests_list,
+            }),
+        }
+    }
+}
+
+impl<'block, E: EthSpec> From<&NewPayloadRequest<'block, E>> for OwnedNewPayloadRequest<E> {
+    fn from(request: &NewPayloadRequest<'block, E>) -> Self {
+        match request {
+            NewPayloadRequest::Bellatrix(request) => Self::Bellatrix {
+                execution_payload: request.execution_payload.clone(),
+            },
+            NewPayloadRequest::Capella(request) => Self::Capella {
+                execution_payload: request.execution_payload.clone(),
+            },
+            NewPayloadRequest::Deneb(request) => Self::Deneb {
+                execution_payload: request.execution_payload.clone(),
+                versioned_hashes: request.versioned_hashes.clone(),
+                parent_beacon_block_root: request.parent_beacon_block_root,
+            },
+            NewPayloadRequest::Electra(request) => Self::Electra {
+                execution_payload: request.execution_payload.clone(),
+                versioned_hashes: request.versioned_hashes.clone(),
+                parent_beacon_block_root: request.parent_beacon_block_root,
+                execution_requests_list: request.execution_requests_list.clone(),
+            },
+        }
+    }
+}
+
 impl<'a, E: EthSpec> TryFrom<BeaconBlockRef<'a, E>> for NewPayloadRequest<'a, E> {
     type Error = BeaconStateError;
 
@@ -213,7 +292,7 @@ impl<'a, E: EthSpec> TryFrom<ExecutionPayloadRef<'a, E>> for NewPayloadRequest<'
 #[cfg(test)]
 mod test {
     use crate::versioned_hashes::Error as VersionedHashError;
-    use crate::{Error, NewPayloadRequest};
+    use crate::{Error, NewPayloadRequest, OwnedNewPayloadRequest};
     use state_processing::per_block_processing::deneb::kzg_commitment_to_versioned_hash;
     use types::{BeaconBlock, ExecPayload, ExecutionBlockHash, Hash256, MainnetEthSpec};
 
@@ -231,6 +310,16 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_owned_new_payload_request() {
+        let beacon_block = get_valid_beacon_block();
+        let new_payload_request = NewPayloadRequest::try_from(beacon_block.to_ref())
+            .expect("should create new payload request");
+
+        let owned_request = OwnedNewPayloadRequest::from(&new_payload_request);
+        assert_eq!(owned_request.as_request(), new_payload_request);
+    }
+
     #[test]
     fn test_optimistic_sync_verifications_bad_block_hash() {
         let mut beacon_block = get_valid_beacon_block();
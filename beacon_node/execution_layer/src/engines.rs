@@ -4,7 +4,10 @@ use crate::engine_api::{
     EngineCapabilities, Error as EngineApiError, ForkchoiceUpdatedResponse, PayloadAttributes,
     PayloadId,
 };
-use crate::{ClientVersionV1, HttpJsonRpc};
+use crate::{metrics, ClientVersionV1, HttpJsonRpc};
+use eth2::lighthouse::EngineStatus;
+use futures::future::join_all;
+use futures::stream::{select_all, StreamExt};
 use lru::LruCache;
 use slog::{debug, error, info, warn, Logger};
 use std::future::Future;
@@ -57,12 +60,28 @@ impl From<EngineStateInternal> for EngineState {
     }
 }
 
+impl From<EngineStateInternal> for EngineStatus {
+    fn from(state: EngineStateInternal) -> Self {
+        match state {
+            EngineStateInternal::Synced => EngineStatus::Synced,
+            EngineStateInternal::Syncing => EngineStatus::Syncing,
+            EngineStateInternal::Offline => EngineStatus::Offline,
+            EngineStateInternal::AuthFailed => EngineStatus::AuthFailed,
+        }
+    }
+}
+
 /// Wrapper structure that ensures changes to the engine state are correctly reported to watchers.
 struct State {
     /// The actual engine state.
     state: EngineStateInternal,
     /// Notifier to watch the engine state.
     notifier: watch::Sender<EngineState>,
+    /// Notifier to watch the detailed engine state.
+    status_notifier: watch::Sender<EngineStatus>,
+    /// Whether the state has been updated. The initial state is offline only because the engine
+    /// has not been checked yet.
+    updated: bool,
 }
 
 impl std::ops::Deref for State {
@@ -77,7 +96,13 @@ impl Default for State {
     fn default() -> Self {
         let state = EngineStateInternal::default();
         let (notifier, _receiver) = watch::channel(state.into());
-        State { state, notifier }
+        let (status_notifier, _receiver) = watch::channel(state.into());
+        State {
+            state,
+            notifier,
+            status_notifier,
+            updated: false,
+        }
     }
 }
 
@@ -85,11 +110,17 @@ impl State {
     // Updates the state and notifies all watchers if the state has changed.
     pub fn update(&mut self, new_state: EngineStateInternal) {
         self.state = new_state;
+        self.updated = true;
         self.notifier.send_if_modified(|last_state| {
             let changed = *last_state != new_state.into(); // notify conditionally
             *last_state = new_state.into(); // update the state unconditionally
             changed
         });
+        self.status_notifier.send_if_modified(|last_status| {
+            let changed = *last_status != new_state.into();
+            *last_status = new_state.into();
+            changed
+        });
     }
 
     /// Gives access to a channel containing whether the last state is online.
@@ -98,6 +129,14 @@ impl State {
     pub fn watch(&self) -> WatchStream<EngineState> {
         self.notifier.subscribe().into()
     }
+
+    /// Gives access to a channel containing the last detailed state, e.g. distinguishing an
+    /// engine which is syncing from one which is synced.
+    ///
+    /// This can be called several times.
+    pub fn watch_status(&self) -> WatchStream<EngineStatus> {
+        self.status_notifier.subscribe().into()
+    }
 }
 
 #[derive(Copy, Clone, PartialEq, Debug)]
@@ -151,6 +190,13 @@ impl Engine {
         self.state.read().await.watch()
     }
 
+    /// Gives access to a channel containing the last detailed engine state.
+    ///
+    /// This can be called several times.
+    pub async fn watch_status(&self) -> WatchStream<EngineStatus> {
+        self.state.read().await.watch_status()
+    }
+
     pub async fn get_payload_id(
         &self,
         head_block_hash: &ExecutionBlockHash,
@@ -235,6 +281,11 @@ impl Engine {
         }
     }
 
+    /// Returns the detailed state of the engine.
+    pub async fn status(&self) -> EngineStatus {
+        (**self.state.read().await).into()
+    }
+
     /// Returns `true` if the engine has a "synced" status.
     pub async fn is_synced(&self) -> bool {
         **self.state.read().await == EngineStateInternal::Synced
@@ -365,7 +416,8 @@ impl Engine {
         self.api.get_engine_version(age_limit).await
     }
 
-    /// Run `func` on the node regardless of the node's current state.
+    /// Run `func` on the node, unless the node was found to be offline. The watchdog upcheck
+    /// brings such nodes back online.
     ///
     /// ## Note
     ///
@@ -376,12 +428,25 @@ impl Engine {
         F: FnOnce(&'a Engine) -> G,
         G: Future<Output = Result<H, EngineApiError>>,
     {
+        // Take a clone *without* holding the read-lock since the `upcheck` function will take a
+        // write-lock.
+        let (state, updated) = {
+            let state = self.state.read().await;
+            (**state, state.updated)
+        };
+
+        // Skip the node if it was offline when last checked. The initial state is also offline,
+        // but the node has not been checked yet.
+        if updated {
+            match state {
+                EngineStateInternal::Offline => return Err(EngineError::Offline),
+                EngineStateInternal::AuthFailed => return Err(EngineError::Auth),
+                EngineStateInternal::Synced | EngineStateInternal::Syncing => {}
+            }
+        }
+
         match func(self).await {
             Ok(result) => {
-                // Take a clone *without* holding the read-lock since the `upcheck` function will
-                // take a write-lock.
-                let state: EngineStateInternal = **self.state.read().await;
-
                 // Keep an up to date engine state.
                 if state != EngineStateInternal::Synced {
                     // Spawn the upcheck in another task to avoid slowing down this request.
@@ -395,11 +460,21 @@ impl Engine {
                 Ok(result)
             }
             Err(error) => {
-                warn!(
-                    self.log,
-                    "Execution engine call failed";
-                    "error" => ?error,
-                );
+                // Only warn when the node fails after being online, the upcheck logs any
+                // further failures.
+                if EngineState::from(state) == EngineState::Online {
+                    warn!(
+                        self.log,
+                        "Execution engine call failed";
+                        "error" => ?error,
+                    );
+                } else {
+                    debug!(
+                        self.log,
+                        "Execution engine call failed";
+                        "error" => ?error,
+                    );
+                }
 
                 // The node just returned an error, run an upcheck so we can update the endpoint
                 // state.
@@ -417,6 +492,163 @@ impl Engine {
     }
 }
 
+/// An ordered set of execution engines.
+///
+/// The first engine is the primary engine and any others are secondary engines. Requests are sent
+/// to the first synced engine, so that the secondary engines take over while the engines before
+/// them are offline or syncing.
+pub struct Engines {
+    engines: Vec<Arc<Engine>>,
+    /// The best state of any of the engines.
+    state: RwLock<State>,
+}
+
+impl Engines {
+    pub fn new(primary: Engine, secondaries: Vec<Engine>) -> Self {
+        Self {
+            engines: std::iter::once(primary)
+                .chain(secondaries)
+                .map(Arc::new)
+                .collect(),
+            state: Default::default(),
+        }
+    }
+
+    /// Returns the engines, primary engine first.
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<Engine>> {
+        self.engines.iter()
+    }
+
+    /// Returns the index of the engine requests should be sent to: the first synced engine, or
+    /// the first syncing engine if none are synced, or the primary engine if all are offline.
+    pub async fn preferred_index(&self) -> usize {
+        let statuses = self.statuses().await;
+        statuses
+            .iter()
+            .position(|status| *status == EngineStatus::Synced)
+            .or_else(|| {
+                statuses
+                    .iter()
+                    .position(|status| *status == EngineStatus::Syncing)
+            })
+            .unwrap_or(0)
+    }
+
+    /// Returns the engine requests should be sent to, see `Self::preferred_index`.
+    pub async fn preferred(&self) -> &Arc<Engine> {
+        self.preferred_with_index().await.1
+    }
+
+    /// Returns the engine requests should be sent to along with its index, see
+    /// `Self::preferred_index`.
+    pub async fn preferred_with_index(&self) -> (usize, &Arc<Engine>) {
+        let index = self.preferred_index().await;
+        (index, &self.engines[index])
+    }
+
+    /// Returns the detailed state of each engine, primary engine first.
+    pub async fn statuses(&self) -> Vec<EngineStatus> {
+        join_all(self.engines.iter().map(|engine| engine.status())).await
+    }
+
+    /// Runs `Engine::upcheck` on all engines.
+    pub async fn upcheck(&self) {
+        join_all(self.engines.iter().map(|engine| engine.upcheck())).await;
+        self.update_state().await;
+    }
+
+    /// Stores the latest forkchoice state on all engines, so that it can be sent to engines that
+    /// come back online.
+    pub async fn set_latest_forkchoice_state(&self, state: ForkchoiceState) {
+        join_all(
+            self.engines
+                .iter()
+                .map(|engine| engine.set_latest_forkchoice_state(state)),
+        )
+        .await;
+    }
+
+    /// Returns `true` if any engine has a "synced" status.
+    pub async fn is_synced(&self) -> bool {
+        **self.state.read().await == EngineStateInternal::Synced
+    }
+
+    /// Returns `true` if all engines have a status other than synced or syncing.
+    pub async fn is_offline(&self) -> bool {
+        EngineState::from(**self.state.read().await) == EngineState::Offline
+    }
+
+    /// Gives access to a channel containing whether any engine is online.
+    ///
+    /// This can be called several times.
+    pub async fn watch_state(&self) -> WatchStream<EngineState> {
+        self.state.read().await.watch()
+    }
+
+    /// Gives access to a channel containing the best detailed state of any engine.
+    ///
+    /// This can be called several times.
+    pub async fn watch_status(&self) -> WatchStream<EngineStatus> {
+        self.state.read().await.watch_status()
+    }
+
+    /// Keeps the combined state of the engines up to date as the state of each engine changes.
+    ///
+    /// This future never completes.
+    pub async fn track_state(&self) {
+        let mut statuses =
+            select_all(join_all(self.engines.iter().map(|engine| engine.watch_status())).await);
+        while statuses.next().await.is_some() {
+            self.update_state().await;
+        }
+    }
+
+    /// Updates the combined state of the engines and the per-engine metrics.
+    async fn update_state(&self) {
+        let mut best_state = EngineStateInternal::Offline;
+        for (index, engine) in self.engines.iter().enumerate() {
+            let state = **engine.state.read().await;
+            for other in [
+                EngineStateInternal::Synced,
+                EngineStateInternal::Syncing,
+                EngineStateInternal::Offline,
+                EngineStateInternal::AuthFailed,
+            ] {
+                metrics::set_gauge_vec(
+                    &metrics::EXECUTION_LAYER_ENGINE_STATUS,
+                    &[&index.to_string(), other.label()],
+                    (other == state) as i64,
+                );
+            }
+            if state.rank() > best_state.rank() {
+                best_state = state;
+            }
+        }
+        self.state.write().await.update(best_state);
+    }
+}
+
+impl EngineStateInternal {
+    /// Orders states by how useful the engine is, for the combined state of several engines.
+    fn rank(self) -> u8 {
+        match self {
+            EngineStateInternal::Synced => 3,
+            EngineStateInternal::Syncing => 2,
+            EngineStateInternal::AuthFailed => 1,
+            EngineStateInternal::Offline => 0,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            EngineStateInternal::Synced => "synced",
+            EngineStateInternal::Syncing => "syncing",
+            EngineStateInternal::AuthFailed => "auth_failed",
+            EngineStateInternal::Offline => "offline",
+        }
+    }
+}
+
 impl PayloadIdCacheKey {
     fn new(head_block_hash: &ExecutionBlockHash, attributes: &PayloadAttributes) -> Self {
         Self {
@@ -9,13 +9,13 @@ use crate::payload_cache::PayloadCache;
 use arc_swap::ArcSwapOption;
 use auth::{strip_prefix, Auth, JwtKey};
 pub use block_hash::calculate_execution_block_hash;
-use builder_client::BuilderHttpClient;
 pub use engine_api::EngineCapabilities;
 use engine_api::Error as ApiError;
 pub use engine_api::*;
 pub use engine_api::{http, http::deposit_methods, http::HttpJsonRpc};
-use engines::{Engine, EngineError};
+use engines::{Engine, EngineError, Engines};
 pub use engines::{EngineState, ForkchoiceState};
+use eth2::lighthouse::{BuilderRelayStatus, EngineStatus, ExecutionEngineStatus};
 use eth2::types::FullPayloadContents;
 use eth2::types::{builder_bid::SignedBuilderBid, BlobsBundle, ForkVersionedResponse};
 use ethers_core::types::Transaction as EthersTransaction;
@@ -24,22 +24,26 @@ use fork_choice::ForkchoiceUpdateParameters;
 use lru::LruCache;
 use payload_status::process_payload_status;
 pub use payload_status::PayloadStatus;
+use recorder::{EngineApiRecorder, RecorderConfig};
+use relays::RelayBid;
+pub use relays::{BuilderRelays, RelayStats};
 use sensitive_url::SensitiveUrl;
 use serde::{Deserialize, Serialize};
-use slog::{crit, debug, error, info, warn, Logger};
+use slog::{crit, debug, error, info, o, warn, Logger};
 use slot_clock::SlotClock;
 use std::collections::HashMap;
 use std::fmt;
 use std::future::Future;
 use std::io::Write;
 use std::num::NonZeroUsize;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use strum::AsRefStr;
 use task_executor::TaskExecutor;
 use tokio::{
     sync::{Mutex, MutexGuard, RwLock},
+    task::JoinHandle,
     time::sleep,
 };
 use tokio_stream::wrappers::WatchStream;
@@ -55,7 +59,7 @@ use types::{
 use types::{
     BeaconStateError, BlindedPayload, ChainSpec, Epoch, ExecPayload, ExecutionPayloadBellatrix,
     ExecutionPayloadCapella, ExecutionPayloadElectra, FullPayload, ProposerPreparationData,
-    PublicKeyBytes, Signature, Slot,
+    PublicKeyBytes, Signature, Slot, ValidatorRegistrationData,
 };
 
 mod block_hash;
@@ -65,6 +69,8 @@ mod keccak;
 mod metrics;
 pub mod payload_cache;
 mod payload_status;
+pub mod recorder;
+mod relays;
 pub mod test_utils;
 pub mod versioned_hashes;
 
@@ -88,6 +94,14 @@ const EXECUTION_BLOCKS_LRU_CACHE_SIZE: NonZeroUsize = new_non_zero_usize(128);
 const DEFAULT_SUGGESTED_FEE_RECIPIENT: [u8; 20] =
     [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
 
+/// The gas limit of a payload may differ from that of its parent by less than `1 / 1024` of it.
+const GAS_LIMIT_ADJUSTMENT_FACTOR: u64 = 1024;
+
+/// When engines are required to agree on `newPayload`, this is how long to wait for the secondary
+/// engines after the preferred engine has responded. Engines that respond later are treated as not
+/// agreeing with a `VALID` verdict.
+const ENGINE_AGREEMENT_TIMEOUT: Duration = Duration::from_secs(1);
+
 /// A payload alongside some information about where it came from.
 pub enum ProvenancedPayload<P> {
     /// A good old fashioned farm-to-table payload from your local EE.
@@ -158,6 +172,9 @@ pub enum Error {
     BeaconStateError(BeaconStateError),
     PayloadTypeMismatch,
     VerifyingVersionedHashes(versioned_hashes::Error),
+    Recorder(String),
+    /// A JWT secret was configured for an IPC endpoint, which cannot send it.
+    JwtSecretForIpcEndpoint(String),
 }
 
 impl From<BeaconStateError> for Error {
@@ -342,6 +359,8 @@ pub struct BuilderParams {
     pub pubkey: PublicKeyBytes,
     pub slot: Slot,
     pub chain_health: ChainHealth,
+    /// The gas limit of the parent payload, used to determine the gas limit builders should target.
+    pub parent_gas_limit: Option<u64>,
 }
 
 #[derive(PartialEq)]
@@ -361,9 +380,14 @@ pub enum FailedCondition {
 
 type PayloadContentsRefTuple<'a, E> = (ExecutionPayloadRef<'a, E>, Option<&'a BlobsBundle<E>>);
 
+/// The index of a secondary engine and the task sending it a request.
+type SecondaryRequest<T> = (usize, JoinHandle<Option<Result<T, EngineError>>>);
+
 struct Inner<E: EthSpec> {
-    engine: Arc<Engine>,
-    builder: ArcSwapOption<BuilderHttpClient>,
+    engines: Arc<Engines>,
+    /// Only consider payloads valid if all engines that respond agree they are valid.
+    require_engine_agreement: bool,
+    builder: ArcSwapOption<BuilderRelays>,
     execution_engine_forkchoice_lock: Mutex<()>,
     suggested_fee_recipient: Option<Address>,
     proposer_preparation_data: Mutex<HashMap<u64, ProposerPreparationDataEntry>>,
@@ -383,8 +407,18 @@ struct Inner<E: EthSpec> {
 pub struct Config {
     /// Endpoint url for EL nodes that are running the engine api.
     pub execution_endpoint: Option<SensitiveUrl>,
-    /// Endpoint urls for services providing the builder api.
-    pub builder_url: Option<SensitiveUrl>,
+    /// Endpoint urls for secondary EL nodes, used in order when the engines before them are
+    /// offline or syncing.
+    pub secondary_execution_endpoints: Vec<SensitiveUrl>,
+    /// JWT secrets for the secondary endpoints, in the same order. Secondary endpoints without a
+    /// secret file use the secret of the primary endpoint.
+    pub secondary_secret_files: Vec<PathBuf>,
+    /// Only consider payloads valid if all engines respond to `newPayload` in time and agree
+    /// they are valid, see `ENGINE_AGREEMENT_TIMEOUT`.
+    pub require_engine_agreement: bool,
+    /// Endpoint urls for services providing the builder api. Headers are requested from all of
+    /// them and the most valuable valid bid is used.
+    pub builder_urls: Vec<SensitiveUrl>,
     /// The timeout value used when making a request to fetch a block header
     /// from the builder api.
     pub builder_header_timeout: Option<Duration>,
@@ -402,6 +436,8 @@ pub struct Config {
     /// Default directory for the jwt secret if not provided through cli.
     pub default_datadir: PathBuf,
     pub execution_timeout_multiplier: Option<u32>,
+    /// Record all engine API requests and responses to disk, if set.
+    pub engine_api_recorder: Option<RecorderConfig>,
 }
 
 /// Provides access to one execution engine and provides a neat interface for consumption by the
@@ -412,11 +448,15 @@ pub struct ExecutionLayer<E: EthSpec> {
 }
 
 impl<E: EthSpec> ExecutionLayer<E> {
-    /// Instantiate `Self` with an Execution engine specified in `Config`, using JSON-RPC via HTTP.
+    /// Instantiate `Self` with an Execution engine specified in `Config`, using JSON-RPC via HTTP
+    /// or IPC.
     pub fn from_config(config: Config, executor: TaskExecutor, log: Logger) -> Result<Self, Error> {
         let Config {
             execution_endpoint: url,
-            builder_url,
+            secondary_execution_endpoints,
+            secondary_secret_files,
+            require_engine_agreement,
+            builder_urls,
             builder_user_agent,
             builder_header_timeout,
             secret_file,
@@ -425,52 +465,77 @@ impl<E: EthSpec> ExecutionLayer<E> {
             jwt_version,
             default_datadir,
             execution_timeout_multiplier,
+            engine_api_recorder,
         } = config;
 
         let execution_url = url.ok_or(Error::NoEngine)?;
 
         // Use the default jwt secret path if not provided via cli.
-        let secret_file = secret_file.unwrap_or_else(|| default_datadir.join(DEFAULT_JWT_FILE));
-
-        let jwt_key = if secret_file.exists() {
-            // Read secret from file if it already exists
-            std::fs::read_to_string(&secret_file)
-                .map_err(|e| format!("Failed to read JWT secret file. Error: {:?}", e))
-                .and_then(|ref s| {
-                    let secret = JwtKey::from_slice(
-                        &hex::decode(strip_prefix(s.trim_end()))
-                            .map_err(|e| format!("Invalid hex string: {:?}", e))?,
-                    )?;
-                    Ok(secret)
-                })
-                .map_err(Error::InvalidJWTSecret)
-        } else {
-            // Create a new file and write a randomly generated secret to it if file does not exist
-            warn!(log, "No JWT found on disk. Generating"; "path" => %secret_file.display());
-            std::fs::File::options()
-                .write(true)
-                .create_new(true)
-                .open(&secret_file)
-                .map_err(|e| format!("Failed to open JWT secret file. Error: {:?}", e))
-                .and_then(|mut f| {
-                    let secret = auth::JwtKey::random();
-                    f.write_all(secret.hex_string().as_bytes())
-                        .map_err(|e| format!("Failed to write to JWT secret file: {:?}", e))?;
-                    Ok(secret)
-                })
-                .map_err(Error::InvalidJWTSecret)
-        }?;
-
-        let engine: Engine = {
-            let auth = Auth::new(jwt_key, jwt_id, jwt_version);
-            debug!(log, "Loaded execution endpoint"; "endpoint" => %execution_url, "jwt_path" => ?secret_file.as_path());
-            let api = HttpJsonRpc::new_with_auth(execution_url, auth, execution_timeout_multiplier)
-                .map_err(Error::ApiError)?;
-            Engine::new(api, executor.clone(), &log)
+        let default_secret_file = default_datadir.join(DEFAULT_JWT_FILE);
+        let primary_secret_file = secret_file.as_deref().unwrap_or(&default_secret_file);
+
+        let recorder = engine_api_recorder
+            .map(|config| {
+                info!(log, "Recording engine API requests"; "dir" => ?config.dir);
+                EngineApiRecorder::start(config, &executor, &log)
+            })
+            .transpose()
+            .map_err(Error::Recorder)?;
+
+        let new_api = |engine: usize,
+                       url,
+                       secret_file: Option<&Path>,
+                       default_secret_file: &Path,
+                       log: &Logger| {
+            let api = new_engine_api(
+                url,
+                secret_file,
+                default_secret_file,
+                jwt_id.clone(),
+                jwt_version.clone(),
+                execution_timeout_multiplier,
+                log,
+            )?;
+            Ok::<_, Error>(match &recorder {
+                Some(recorder) => api.with_recorder(recorder.for_engine(engine)),
+                None => api,
+            })
         };
 
+        let engine = Engine::new(
+            new_api(
+                0,
+                execution_url,
+                secret_file.as_deref(),
+                &default_secret_file,
+                &log,
+            )?,
+            executor.clone(),
+            &log,
+        );
+
+        let secondary_engines = secondary_execution_endpoints
+            .into_iter()
+            .enumerate()
+            .map(|(index, url)| {
+                let log = log.new(o!("engine" => index + 1));
+                let secret_file = secondary_secret_files.get(index).map(PathBuf::as_path);
+                let api = new_api(index + 1, url, secret_file, primary_secret_file, &log)?;
+                Ok(Engine::new(api, executor.clone(), &log))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        let engines = Arc::new(Engines::new(engine, secondary_engines));
+
+        // Keep the combined state of the engines up to date.
+        let engines_to_track = engines.clone();
+        executor.spawn(
+            async move { engines_to_track.track_state().await },
+            "exec_engines_state",
+        );
+
         let inner = Inner {
-            engine: Arc::new(engine),
+            engines,
+            require_engine_agreement,
             builder: ArcSwapOption::empty(),
             execution_engine_forkchoice_lock: <_>::default(),
             suggested_fee_recipient,
@@ -487,18 +552,85 @@ impl<E: EthSpec> ExecutionLayer<E> {
             inner: Arc::new(inner),
         };
 
-        if let Some(builder_url) = builder_url {
-            el.set_builder_url(builder_url, builder_user_agent, builder_header_timeout)?;
+        if !builder_urls.is_empty() {
+            el.set_builder_urls(builder_urls, builder_user_agent, builder_header_timeout)?;
         }
 
         Ok(el)
     }
 
-    fn engine(&self) -> &Arc<Engine> {
-        &self.inner.engine
+    fn engines(&self) -> &Arc<Engines> {
+        &self.inner.engines
+    }
+
+    /// Returns the engine requests are sent to, the first synced engine if there is one.
+    async fn engine(&self) -> &Arc<Engine> {
+        self.engines().preferred().await
     }
 
-    pub fn builder(&self) -> Option<Arc<BuilderHttpClient>> {
+    /// Sends a request to each engine other than the `preferred` engine in a separate task, so
+    /// that the secondary engines are kept up to date without delaying the preferred engine.
+    fn spawn_secondary_requests<F, G, T>(
+        &self,
+        preferred: usize,
+        func: F,
+        name: &'static str,
+    ) -> Vec<SecondaryRequest<T>>
+    where
+        F: Fn(Arc<Engine>) -> G,
+        G: Future<Output = Result<T, EngineError>> + Send + 'static,
+        T: Send + 'static,
+    {
+        self.engines()
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| *index != preferred)
+            .filter_map(|(index, engine)| {
+                let handle = self.executor().spawn_handle(func(engine.clone()), name)?;
+                Some((index, handle))
+            })
+            .collect()
+    }
+
+    /// Returns the endpoint and detailed state of each execution engine, primary engine first.
+    pub async fn engine_statuses(&self) -> Vec<ExecutionEngineStatus> {
+        let preferred = self.engines().preferred_index().await;
+        let statuses = self.engines().statuses().await;
+        self.engines()
+            .iter()
+            .zip(statuses)
+            .enumerate()
+            .map(|(index, (engine, status))| ExecutionEngineStatus {
+                index,
+                endpoint: engine.api.endpoint(),
+                status,
+                preferred: index == preferred,
+            })
+            .collect()
+    }
+
+    /// Returns the endpoint and request statistics of each builder relay.
+    pub fn relay_statuses(&self) -> Vec<BuilderRelayStatus> {
+        let Some(builder) = self.builder() else {
+            return vec![];
+        };
+        builder
+            .stats()
+            .into_iter()
+            .enumerate()
+            .map(|(index, (endpoint, stats))| BuilderRelayStatus {
+                index,
+                endpoint,
+                requests: stats.requests,
+                failures: stats.failures,
+                bids: stats.bids,
+                bids_selected: stats.bids_selected,
+                latency_ms: stats.latency.map(|latency| latency.as_millis() as u64),
+            })
+            .collect()
+    }
+
+    pub fn builder(&self) -> Option<Arc<BuilderRelays>> {
         self.inner.builder.load_full()
     }
 
@@ -512,19 +644,33 @@ impl<E: EthSpec> ExecutionLayer<E> {
         builder_user_agent: Option<String>,
         builder_header_timeout: Option<Duration>,
     ) -> Result<(), Error> {
-        let builder_client = BuilderHttpClient::new(
-            builder_url.clone(),
+        self.set_builder_urls(
+            vec![builder_url],
             builder_user_agent,
             builder_header_timeout,
         )
-        .map_err(Error::Builder)?;
+    }
+
+    /// Set the builder relay URLs after initialization, replacing any previous relays.
+    pub fn set_builder_urls(
+        &self,
+        builder_urls: Vec<SensitiveUrl>,
+        builder_user_agent: Option<String>,
+        builder_header_timeout: Option<Duration>,
+    ) -> Result<(), Error> {
+        let builder_urls_str = builder_urls
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        let relays = BuilderRelays::new(builder_urls, builder_user_agent, builder_header_timeout)
+            .map_err(Error::Builder)?;
         info!(
             self.log(),
             "Using external block builder";
-            "builder_url" => ?builder_url,
-            "local_user_agent" => builder_client.get_user_agent(),
+            "builder_urls" => ?builder_urls_str,
+            "local_user_agent" => relays.get_user_agent(),
         );
-        self.inner.builder.swap(Some(Arc::new(builder_client)));
+        self.inner.builder.swap(Some(Arc::new(relays)));
         Ok(())
     }
 
@@ -562,6 +708,7 @@ impl<E: EthSpec> ExecutionLayer<E> {
     pub async fn get_current_difficulty(&self) -> Result<Uint256, ApiError> {
         let block = self
             .engine()
+            .await
             .api
             .get_block_by_number(BlockByNumberQuery::Tag(LATEST_TAG))
             .await?
@@ -579,7 +726,14 @@ impl<E: EthSpec> ExecutionLayer<E> {
     ///
     /// This can be called several times.
     pub async fn get_responsiveness_watch(&self) -> WatchStream<EngineState> {
-        self.engine().watch_state().await
+        self.engines().watch_state().await
+    }
+
+    /// Gives access to a channel containing the last detailed engine state.
+    ///
+    /// This can be called several times.
+    pub async fn get_status_watch(&self) -> WatchStream<EngineStatus> {
+        self.engines().watch_status().await
     }
 
     /// Note: this function returns a mutex guard, be careful to avoid deadlocks.
@@ -631,7 +785,7 @@ impl<E: EthSpec> ExecutionLayer<E> {
 
     /// Performs a single execution of the watchdog routine.
     pub async fn watchdog_task(&self) {
-        self.engine().upcheck().await;
+        self.engines().upcheck().await;
     }
 
     /// Spawns a routine which cleans the cached proposer data periodically.
@@ -673,9 +827,9 @@ impl<E: EthSpec> ExecutionLayer<E> {
         self.spawn(preparation_cleaner, "exec_preparation_cleanup");
     }
 
-    /// Returns `true` if the execution engine is synced and reachable.
+    /// Returns `true` if any execution engine is synced and reachable.
     pub async fn is_synced(&self) -> bool {
-        self.engine().is_synced().await
+        self.engines().is_synced().await
     }
 
     /// Execution nodes return a "SYNCED" response when they do not have any peers.
@@ -689,6 +843,7 @@ impl<E: EthSpec> ExecutionLayer<E> {
         if synced {
             if let Ok(Some(block)) = self
                 .engine()
+                .await
                 .api
                 .get_block_by_number(BlockByNumberQuery::Tag(LATEST_TAG))
                 .await
@@ -707,7 +862,7 @@ impl<E: EthSpec> ExecutionLayer<E> {
     /// be used to give an indication on the HTTP API that the node's execution layer is struggling,
     /// which can in turn be used by the VC.
     pub async fn is_offline_or_erroring(&self) -> bool {
-        self.engine().is_offline().await || *self.inner.last_new_payload_errored.read().await
+        self.engines().is_offline().await || *self.inner.last_new_payload_errored.read().await
     }
 
     /// Updates the proposer preparation data provided by validators
@@ -921,16 +1076,13 @@ impl<E: EthSpec> ExecutionLayer<E> {
     /// Fetches local and builder paylaods concurrently, Logs and returns results.
     async fn fetch_builder_and_local_payloads(
         &self,
-        builder: &BuilderHttpClient,
+        builder: &BuilderRelays,
         parent_hash: ExecutionBlockHash,
         builder_params: &BuilderParams,
         payload_attributes: &PayloadAttributes,
         forkchoice_update_params: ForkchoiceUpdateParameters,
         current_fork: ForkName,
-    ) -> (
-        Result<Option<ForkVersionedResponse<SignedBuilderBid<E>>>, builder_client::Error>,
-        Result<GetPayloadResponse<E>, Error>,
-    ) {
+    ) -> (Vec<RelayBid<E>>, Result<GetPayloadResponse<E>, Error>) {
         let slot = builder_params.slot;
         let pubkey = &builder_params.pubkey;
 
@@ -942,8 +1094,8 @@ impl<E: EthSpec> ExecutionLayer<E> {
             "parent_hash" => ?parent_hash,
         );
 
-        // Wait for the builder *and* local EL to produce a payload (or return an error).
-        let ((relay_result, relay_duration), (local_result, local_duration)) = tokio::join!(
+        // Wait for the builders *and* local EL to produce a payload (or return an error).
+        let ((relay_bids, relay_duration), (local_result, local_duration)) = tokio::join!(
             timed_future(metrics::GET_BLINDED_PAYLOAD_BUILDER, async {
                 builder
                     .get_builder_header::<E>(slot, parent_hash, pubkey)
@@ -964,14 +1116,32 @@ impl<E: EthSpec> ExecutionLayer<E> {
             })
         );
 
+        for bid in &relay_bids {
+            debug!(
+                self.log(),
+                "Received relay response";
+                "relay" => builder.endpoint(bid.relay),
+                "fee_recipient" => match &bid.result {
+                    Ok(Some(r)) => format!("{:?}", r.data.message.header().fee_recipient()),
+                    Ok(None) => "empty response".to_string(),
+                    Err(_) => "request failed".to_string(),
+                },
+                "value" => match &bid.result {
+                    Ok(Some(r)) => r.data.message.value().to_string(),
+                    _ => "none".to_string(),
+                },
+                "response_ms" => bid.duration.as_millis(),
+            );
+        }
+
         info!(
             self.log(),
             "Requested blinded execution payload";
-            "relay_fee_recipient" => match &relay_result {
-                Ok(Some(r)) => format!("{:?}", r.data.message.header().fee_recipient()),
-                Ok(None) => "empty response".to_string(),
-                Err(_) => "request failed".to_string(),
-            },
+            "relay_bids" => relay_bids
+                .iter()
+                .filter(|bid| matches!(bid.result, Ok(Some(_))))
+                .count(),
+            "relays" => relay_bids.len(),
             "relay_response_ms" => relay_duration.as_millis(),
             "local_fee_recipient" => match &local_result {
                 Ok(get_payload_response) => format!("{:?}", get_payload_response.fee_recipient()),
@@ -981,7 +1151,134 @@ impl<E: EthSpec> ExecutionLayer<E> {
             "parent_hash" => ?parent_hash,
         );
 
-        (relay_result, local_result)
+        (relay_bids, local_result)
+    }
+
+    /// Selects the bid to propose with from the responses of the relays.
+    ///
+    /// Invalid bids are discarded. Of the valid bids, those with the fee recipient of the
+    /// proposer's registration and the gas limit expected from its target are preferred, and the
+    /// most valuable one is returned. Returns the first relay error if none of the relays
+    /// responded.
+    #[allow(clippy::too_many_arguments)]
+    fn select_builder_bid(
+        &self,
+        builder: &BuilderRelays,
+        relay_bids: Vec<RelayBid<E>>,
+        parent_hash: ExecutionBlockHash,
+        builder_params: &BuilderParams,
+        payload_attributes: &PayloadAttributes,
+        block_number: Option<u64>,
+        current_fork: ForkName,
+        spec: &ChainSpec,
+    ) -> Result<Option<ForkVersionedResponse<SignedBuilderBid<E>>>, builder_client::Error> {
+        let registration = builder.registration(&builder_params.pubkey);
+        let parent_gas_limit = builder_params.parent_gas_limit;
+        let mut first_error = None;
+        let mut responded = false;
+        let mut valid_bids = vec![];
+
+        for RelayBid { relay, result, .. } in relay_bids {
+            let bid = match result {
+                Ok(Some(bid)) => bid,
+                Ok(None) => {
+                    responded = true;
+                    continue;
+                }
+                Err(e) => {
+                    warn!(
+                        self.log(),
+                        "Relay error when requesting payload header";
+                        "relay" => builder.endpoint(relay),
+                        "relay_error" => ?e,
+                        "parent_hash" => ?parent_hash,
+                    );
+                    first_error.get_or_insert(e);
+                    continue;
+                }
+            };
+            responded = true;
+
+            if let Err(reason) = verify_builder_bid(
+                &bid,
+                parent_hash,
+                payload_attributes,
+                block_number,
+                current_fork,
+                spec,
+            ) {
+                metrics::inc_counter_vec(
+                    &metrics::EXECUTION_LAYER_GET_PAYLOAD_BUILDER_REJECTIONS,
+                    &[reason.as_ref().as_ref()],
+                );
+                warn!(
+                    self.log(),
+                    "Builder returned invalid payload";
+                    "info" => "ignoring the bid",
+                    "relay" => builder.endpoint(relay),
+                    "reason" => %reason,
+                    "relay_block_hash" => ?bid.data.message.header().block_hash(),
+                    "parent_hash" => ?parent_hash,
+                );
+                continue;
+            }
+
+            let matches_registration = registration.as_ref().map_or(true, |registration| {
+                bid_matches_registration(
+                    &bid,
+                    registration,
+                    parent_gas_limit,
+                    builder.endpoint(relay),
+                    self.log(),
+                )
+            });
+            valid_bids.push((relay, matches_registration, bid));
+        }
+
+        // Prefer the first relay if bids are equally valuable.
+        let Some(best_index) = (0..valid_bids.len()).rev().max_by_key(|&index| {
+            let (_, matches_registration, bid) = &valid_bids[index];
+            (*matches_registration, *bid.data.message.value())
+        }) else {
+            return match first_error {
+                Some(e) if !responded => Err(e),
+                _ => Ok(None),
+            };
+        };
+        let (best_relay, best_matches_registration, best) = valid_bids.swap_remove(best_index);
+        let block_hash = best.data.message.header().block_hash();
+
+        if !best_matches_registration {
+            warn!(
+                self.log(),
+                "Selected builder bid that does not match registration";
+                "info" => "no relay offered a bid matching the validator registration",
+                "relay_block_hash" => ?block_hash,
+            );
+        }
+
+        // Reveal the block to every relay that offered the same payload.
+        let offering_relays = std::iter::once(best_relay)
+            .chain(
+                valid_bids
+                    .iter()
+                    .filter(|(_, _, bid)| bid.data.message.header().block_hash() == block_hash)
+                    .map(|(relay, _, _)| *relay),
+            )
+            .collect::<Vec<_>>();
+        info!(
+            self.log(),
+            "Selected builder bid";
+            "relays" => ?offering_relays
+                .iter()
+                .filter_map(|relay| builder.endpoint(*relay))
+                .collect::<Vec<_>>(),
+            "value" => %best.data.message.value(),
+            "relay_block_hash" => ?block_hash,
+        );
+        builder.on_bid_selected(block_hash, offering_relays);
+
+        Ok(Some(best))
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -1045,7 +1342,7 @@ impl<E: EthSpec> ExecutionLayer<E> {
                 .map(ProvenancedPayload::Local);
         }
 
-        let (relay_result, local_result) = self
+        let (relay_bids, local_result) = self
             .fetch_builder_and_local_payloads(
                 builder.as_ref(),
                 parent_hash,
@@ -1055,6 +1352,16 @@ impl<E: EthSpec> ExecutionLayer<E> {
                 current_fork,
             )
             .await;
+        let relay_result = self.select_builder_bid(
+            builder.as_ref(),
+            relay_bids,
+            parent_hash,
+            &builder_params,
+            payload_attributes,
+            local_result.as_ref().ok().map(|local| local.block_number()),
+            current_fork,
+            spec,
+        );
 
         match (relay_result, local_result) {
             (Err(e), Ok(local)) => {
@@ -1117,33 +1424,6 @@ impl<E: EthSpec> ExecutionLayer<E> {
                     "parent_hash" => ?parent_hash,
                 );
 
-                // check relay payload validity
-                if let Err(reason) = verify_builder_bid(
-                    &relay,
-                    parent_hash,
-                    payload_attributes,
-                    Some(local.block_number()),
-                    current_fork,
-                    spec,
-                ) {
-                    // relay payload invalid -> return local
-                    metrics::inc_counter_vec(
-                        &metrics::EXECUTION_LAYER_GET_PAYLOAD_BUILDER_REJECTIONS,
-                        &[reason.as_ref().as_ref()],
-                    );
-                    warn!(
-                        self.log(),
-                        "Builder returned invalid payload";
-                        "info" => "using local payload",
-                        "reason" => %reason,
-                        "relay_block_hash" => ?header.block_hash(),
-                        "parent_hash" => ?parent_hash,
-                    );
-                    return Ok(ProvenancedPayload::Local(BlockProposalContentsType::Full(
-                        local.try_into()?,
-                    )));
-                }
-
                 let relay_value = *relay.data.message.value();
 
                 let boosted_relay_value = match builder_boost_factor {
@@ -1189,6 +1469,7 @@ impl<E: EthSpec> ExecutionLayer<E> {
                     "builder_boost_factor" => ?builder_boost_factor
                 );
 
+                builder.on_bid_used(header.block_hash());
                 Ok(ProvenancedPayload::try_from(relay.data.message)?)
             }
             (Ok(Some(relay)), Err(local_error)) => {
@@ -1202,31 +1483,8 @@ impl<E: EthSpec> ExecutionLayer<E> {
                     "parent_hash" => ?parent_hash,
                 );
 
-                match verify_builder_bid(
-                    &relay,
-                    parent_hash,
-                    payload_attributes,
-                    None,
-                    current_fork,
-                    spec,
-                ) {
-                    Ok(()) => Ok(ProvenancedPayload::try_from(relay.data.message)?),
-                    Err(reason) => {
-                        metrics::inc_counter_vec(
-                            &metrics::EXECUTION_LAYER_GET_PAYLOAD_BUILDER_REJECTIONS,
-                            &[reason.as_ref().as_ref()],
-                        );
-                        crit!(
-                            self.log(),
-                            "Builder returned invalid payload";
-                            "info" => "no local payload either - unable to propose block",
-                            "reason" => %reason,
-                            "relay_block_hash" => ?header.block_hash(),
-                            "parent_hash" => ?parent_hash,
-                        );
-                        Err(Error::CannotProduceHeader)
-                    }
-                }
+                builder.on_bid_used(header.block_hash());
+                Ok(ProvenancedPayload::try_from(relay.data.message)?)
             }
         }
     }
@@ -1261,6 +1519,7 @@ impl<E: EthSpec> ExecutionLayer<E> {
         ) -> Option<FullPayloadContents<E>>,
     ) -> Result<GetPayloadResponseType<E>, Error> {
         self.engine()
+            .await
             .request(move |engine| async move {
                 let payload_id = if let Some(id) = engine
                     .get_payload_id(&parent_hash, payload_attributes)
@@ -1371,11 +1630,43 @@ impl<E: EthSpec> ExecutionLayer<E> {
         let block_hash = new_payload_request.block_hash();
         let parent_hash = new_payload_request.parent_hash();
 
-        let result = self
-            .engine()
+        // Send the payload to the secondary engines in the background so that they stay in sync,
+        // and take the verdict of the preferred engine.
+        let (preferred, engine) = self.engines().preferred_with_index().await;
+        let owned_request = OwnedNewPayloadRequest::from(&new_payload_request);
+        let secondary_requests = self.spawn_secondary_requests(
+            preferred,
+            |engine| {
+                let owned_request = owned_request.clone();
+                async move {
+                    engine
+                        .request(|engine| engine.api.new_payload(owned_request.as_request()))
+                        .await
+                }
+            },
+            "exec_secondary_new_payload",
+        );
+        let preferred_result = engine
             .request(|engine| engine.api.new_payload(new_payload_request))
             .await;
 
+        // Only wait for the secondary engines if their verdicts are required.
+        let secondary_results = if self.inner.require_engine_agreement {
+            secondary_results(secondary_requests, Some(ENGINE_AGREEMENT_TIMEOUT)).await
+        } else if preferred_result.is_err() {
+            secondary_results(secondary_requests, None).await
+        } else {
+            vec![]
+        };
+        let result = new_payload_verdict(
+            (preferred, preferred_result),
+            secondary_results,
+            self.inner
+                .require_engine_agreement
+                .then(|| self.engines().iter().count()),
+            self.log(),
+        );
+
         if let Ok(status) = &result {
             let status_str = <&'static str>::from(status.status);
             metrics::inc_counter_vec(
@@ -1401,7 +1692,7 @@ impl<E: EthSpec> ExecutionLayer<E> {
 
     /// Update engine sync status.
     pub async fn upcheck(&self) {
-        self.engine().upcheck().await;
+        self.engines().upcheck().await;
     }
 
     /// Register that the given `validator_index` is going to produce a block at `slot`.
@@ -1515,18 +1806,49 @@ impl<E: EthSpec> ExecutionLayer<E> {
             finalized_block_hash,
         };
 
-        self.engine()
+        self.engines()
             .set_latest_forkchoice_state(forkchoice_state)
             .await;
 
-        let result = self
-            .engine()
+        // Update the head of the secondary engines in the background, and take the response of
+        // the preferred engine or, if it fails, of the first secondary engine that responds.
+        let (preferred, engine) = self.engines().preferred_with_index().await;
+        let secondary_requests = self.spawn_secondary_requests(
+            preferred,
+            |engine| {
+                let payload_attributes = payload_attributes.clone();
+                let log = self.log().clone();
+                async move {
+                    engine
+                        .request(|engine| async move {
+                            engine
+                                .notify_forkchoice_updated(
+                                    forkchoice_state,
+                                    payload_attributes,
+                                    &log,
+                                )
+                                .await
+                        })
+                        .await
+                }
+            },
+            "exec_secondary_forkchoice_updated",
+        );
+        let preferred_result = engine
             .request(|engine| async move {
                 engine
                     .notify_forkchoice_updated(forkchoice_state, payload_attributes, self.log())
                     .await
             })
             .await;
+        let result = match preferred_result {
+            Ok(response) => Ok(response),
+            Err(error) => secondary_results(secondary_requests, None)
+                .await
+                .into_iter()
+                .find_map(|(_, result)| result.ok())
+                .ok_or(error),
+        };
 
         if let Ok(status) = &result {
             metrics::inc_counter_vec(
@@ -1558,6 +1880,7 @@ impl<E: EthSpec> ExecutionLayer<E> {
         age_limit: Option<Duration>,
     ) -> Result<EngineCapabilities, Error> {
         self.engine()
+            .await
             .request(|engine| engine.get_engine_capabilities(age_limit))
             .await
             .map_err(Into::into)
@@ -1577,6 +1900,7 @@ impl<E: EthSpec> ExecutionLayer<E> {
         age_limit: Option<Duration>,
     ) -> Result<Vec<ClientVersionV1>, Error> {
         self.engine()
+            .await
             .request(|engine| engine.get_engine_version(age_limit))
             .await
             .map_err(Into::into)
@@ -1601,6 +1925,7 @@ impl<E: EthSpec> ExecutionLayer<E> {
 
         let hash_opt = self
             .engine()
+            .await
             .request(|engine| async move {
                 let terminal_block_hash = spec.terminal_block_hash;
                 if terminal_block_hash != ExecutionBlockHash::zero() {
@@ -1728,6 +2053,7 @@ impl<E: EthSpec> ExecutionLayer<E> {
         );
 
         self.engine()
+            .await
             .request(|engine| async move {
                 if let Some(pow_block) = self.get_pow_block(engine, block_hash).await? {
                     if let Some(pow_parent) =
@@ -1787,6 +2113,7 @@ impl<E: EthSpec> ExecutionLayer<E> {
         hashes: Vec<ExecutionBlockHash>,
     ) -> Result<Vec<Option<ExecutionPayloadBodyV1<E>>>, Error> {
         self.engine()
+            .await
             .request(|engine: &Engine| async move {
                 engine.api.get_payload_bodies_by_hash_v1(hashes).await
             })
@@ -1802,6 +2129,7 @@ impl<E: EthSpec> ExecutionLayer<E> {
     ) -> Result<Vec<Option<ExecutionPayloadBodyV1<E>>>, Error> {
         let _timer = metrics::start_timer(&metrics::EXECUTION_LAYER_GET_PAYLOAD_BODIES_BY_RANGE);
         self.engine()
+            .await
             .request(|engine: &Engine| async move {
                 engine
                     .api
@@ -1866,6 +2194,7 @@ impl<E: EthSpec> ExecutionLayer<E> {
 
         if capabilities.get_blobs_v1 {
             self.engine()
+                .await
                 .request(|engine| async move { engine.api.get_blobs(query).await })
                 .await
                 .map_err(Box::new)
@@ -1880,6 +2209,7 @@ impl<E: EthSpec> ExecutionLayer<E> {
         query: BlockByNumberQuery<'_>,
     ) -> Result<Option<ExecutionBlock>, Error> {
         self.engine()
+            .await
             .request(|engine| async move { engine.api.get_block_by_number(query).await })
             .await
             .map_err(Box::new)
@@ -1953,6 +2283,70 @@ impl<E: EthSpec> ExecutionLayer<E> {
     }
 }
 
+/// The gas limit of a payload building on a payload with `parent_gas_limit`, moving as close to
+/// `target_gas_limit` as allowed. Returns `None` on overflow or if the parent gas limit is too low
+/// to be adjusted.
+pub fn expected_gas_limit(parent_gas_limit: u64, target_gas_limit: u64) -> Option<u64> {
+    let max_gas_limit_difference =
+        (parent_gas_limit / GAS_LIMIT_ADJUSTMENT_FACTOR).checked_sub(1)?;
+    if target_gas_limit > parent_gas_limit {
+        let gas_diff = target_gas_limit.saturating_sub(parent_gas_limit);
+        parent_gas_limit.checked_add(std::cmp::min(gas_diff, max_gas_limit_difference))
+    } else {
+        let gas_diff = parent_gas_limit.saturating_sub(target_gas_limit);
+        parent_gas_limit.checked_sub(std::cmp::min(gas_diff, max_gas_limit_difference))
+    }
+}
+
+/// Returns `true` if the bid pays the fee recipient of the proposer's registration, and moves the
+/// gas limit from `parent_gas_limit` towards the registered target as far as allowed. Without a
+/// parent gas limit, the bid must have the registered gas limit. Mismatches are logged, but the bid
+/// may still be used if no relay offers a matching bid, as some builders pay the proposer with a
+/// transaction instead.
+fn bid_matches_registration<E: EthSpec>(
+    bid: &ForkVersionedResponse<SignedBuilderBid<E>>,
+    registration: &ValidatorRegistrationData,
+    parent_gas_limit: Option<u64>,
+    relay: Option<&str>,
+    log: &Logger,
+) -> bool {
+    let header = bid.data.message.header();
+    let expected_gas_limit = parent_gas_limit
+        .and_then(|parent_gas_limit| expected_gas_limit(parent_gas_limit, registration.gas_limit))
+        .unwrap_or(registration.gas_limit);
+    let mut matches = true;
+    if header.fee_recipient() != registration.fee_recipient {
+        metrics::inc_counter_vec(
+            &metrics::EXECUTION_LAYER_BUILDER_BID_MISMATCHES,
+            &["fee_recipient"],
+        );
+        debug!(
+            log,
+            "Builder bid fee recipient does not match registration";
+            "relay" => relay,
+            "payload" => ?header.fee_recipient(),
+            "expected" => ?registration.fee_recipient,
+        );
+        matches = false;
+    }
+    if header.gas_limit() != expected_gas_limit {
+        metrics::inc_counter_vec(
+            &metrics::EXECUTION_LAYER_BUILDER_BID_MISMATCHES,
+            &["gas_limit"],
+        );
+        debug!(
+            log,
+            "Builder bid gas limit does not match registration";
+            "relay" => relay,
+            "payload" => header.gas_limit(),
+            "expected" => expected_gas_limit,
+            "target" => registration.gas_limit,
+        );
+        matches = false;
+    }
+    matches
+}
+
 #[derive(AsRefStr)]
 #[strum(serialize_all = "snake_case")]
 enum InvalidBuilderPayload {
@@ -2091,6 +2485,156 @@ fn verify_builder_bid<E: EthSpec>(
     }
 }
 
+/// Waits for the results of the `requests` sent to the secondary engines, for at most `timeout`
+/// if given.
+///
+/// Returns the results of the engines that responded in time, in the order of the engines.
+async fn secondary_results<T>(
+    requests: Vec<SecondaryRequest<T>>,
+    timeout: Option<Duration>,
+) -> Vec<(usize, Result<T, EngineError>)> {
+    let deadline = timeout.map(|timeout| tokio::time::Instant::now() + timeout);
+    futures::future::join_all(requests.into_iter().map(|(index, handle)| async move {
+        let result = match deadline {
+            Some(deadline) => tokio::time::timeout_at(deadline, handle).await.ok()?,
+            None => handle.await,
+        };
+        Some((index, result.ok()??))
+    }))
+    .await
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Determines the `newPayload` verdict from the result of the `preferred` engine and the
+/// `secondary` results, each alongside the index of the engine.
+///
+/// The verdict is that of the preferred engine, or of the first secondary engine that responded if
+/// the preferred engine failed. If agreement is required between `require_agreement_of` engines,
+/// a `VALID` verdict is downgraded to `SYNCING` unless all of them responded and found the payload
+/// valid, so that the block is imported optimistically until the engines agree.
+fn new_payload_verdict(
+    preferred: (usize, Result<PayloadStatusV1, EngineError>),
+    secondary: Vec<(usize, Result<PayloadStatusV1, EngineError>)>,
+    require_agreement_of: Option<usize>,
+    log: &Logger,
+) -> Result<PayloadStatusV1, EngineError> {
+    let statuses = std::iter::once(&preferred)
+        .chain(&secondary)
+        .filter_map(|(_, result)| result.as_ref().ok())
+        .collect::<Vec<_>>();
+    let disagreement = require_agreement_of.and_then(|engine_count| {
+        if statuses.len() < engine_count {
+            Some("not all engines responded")
+        } else if statuses
+            .iter()
+            .any(|status| status.status != PayloadStatusV1Status::Valid)
+        {
+            Some("engines disagree")
+        } else {
+            None
+        }
+    });
+
+    let (verdict_index, verdict) = if preferred.1.is_ok() {
+        preferred
+    } else {
+        secondary
+            .into_iter()
+            .find(|(_, result)| result.is_ok())
+            .unwrap_or(preferred)
+    };
+
+    match verdict {
+        Ok(status) if disagreement.is_some() && status.status == PayloadStatusV1Status::Valid => {
+            warn!(
+                log,
+                "Execution engines do not agree on payload";
+                "info" => "the payload will be treated as optimistic until the engines agree",
+                "reason" => disagreement,
+                "engine" => verdict_index,
+            );
+            metrics::inc_counter(&metrics::EXECUTION_LAYER_ENGINE_DISAGREEMENTS);
+            Ok(PayloadStatusV1 {
+                status: PayloadStatusV1Status::Syncing,
+                latest_valid_hash: None,
+                validation_error: None,
+            })
+        }
+        verdict => verdict,
+    }
+}
+
+/// Creates the API of an execution engine, authenticated with the JWT secret in `secret_file`, or
+/// in `default_secret_file` if no secret was configured for the engine.
+///
+/// IPC endpoints are protected by the permissions of the socket file rather than by a JWT. As the
+/// secret cannot be sent over IPC, configuring one for an IPC endpoint is an error.
+fn new_engine_api(
+    url: SensitiveUrl,
+    secret_file: Option<&Path>,
+    default_secret_file: &Path,
+    jwt_id: Option<String>,
+    jwt_version: Option<String>,
+    execution_timeout_multiplier: Option<u32>,
+    log: &Logger,
+) -> Result<HttpJsonRpc, Error> {
+    let api = if http::ipc_socket_path(&url)
+        .map_err(Error::ApiError)?
+        .is_some()
+    {
+        if secret_file.is_some() {
+            return Err(Error::JwtSecretForIpcEndpoint(url.to_string()));
+        }
+        debug!(log, "Loaded execution endpoint"; "endpoint" => %url, "transport" => "ipc");
+        HttpJsonRpc::new(url, execution_timeout_multiplier)
+    } else {
+        let secret_file = secret_file.unwrap_or(default_secret_file);
+        let auth = Auth::new(
+            load_or_create_jwt_key(secret_file, log)?,
+            jwt_id,
+            jwt_version,
+        );
+        debug!(log, "Loaded execution endpoint"; "endpoint" => %url, "jwt_path" => ?secret_file);
+        HttpJsonRpc::new_with_auth(url, auth, execution_timeout_multiplier)
+    };
+    api.map_err(Error::ApiError)
+}
+
+/// Reads the JWT secret from `secret_file`, or generates a new secret and writes it to
+/// `secret_file` if it does not exist.
+fn load_or_create_jwt_key(secret_file: &Path, log: &Logger) -> Result<JwtKey, Error> {
+    if secret_file.exists() {
+        // Read secret from file if it already exists
+        std::fs::read_to_string(secret_file)
+            .map_err(|e| format!("Failed to read JWT secret file. Error: {:?}", e))
+            .and_then(|ref s| {
+                let secret = JwtKey::from_slice(
+                    &hex::decode(strip_prefix(s.trim_end()))
+                        .map_err(|e| format!("Invalid hex string: {:?}", e))?,
+                )?;
+                Ok(secret)
+            })
+            .map_err(Error::InvalidJWTSecret)
+    } else {
+        // Create a new file and write a randomly generated secret to it if file does not exist
+        warn!(log, "No JWT found on disk. Generating"; "path" => %secret_file.display());
+        std::fs::File::options()
+            .write(true)
+            .create_new(true)
+            .open(secret_file)
+            .map_err(|e| format!("Failed to open JWT secret file. Error: {:?}", e))
+            .and_then(|mut f| {
+                let secret = auth::JwtKey::random();
+                f.write_all(secret.hex_string().as_bytes())
+                    .map_err(|e| format!("Failed to write to JWT secret file: {:?}", e))?;
+                Ok(secret)
+            })
+            .map_err(Error::InvalidJWTSecret)
+    }
+}
+
 /// A helper function to record the time it takes to execute a future.
 async fn timed_future<F: Future<Output = T>, T>(metric: &str, future: F) -> (T, Duration) {
     let start = Instant::now();
@@ -2158,7 +2702,7 @@ mod test {
         MockExecutionLayer::default_params(runtime.task_executor.clone())
             .move_to_block_prior_to_terminal_block()
             .with_terminal_block(|spec, el, _| async move {
-                el.engine().upcheck().await;
+                el.upcheck().await;
                 assert_eq!(
                     el.get_terminal_pow_block_hash(&spec, timestamp_now())
                         .await
@@ -2185,7 +2729,7 @@ mod test {
         MockExecutionLayer::default_params(runtime.task_executor.clone())
             .move_to_block_prior_to_terminal_block()
             .with_terminal_block(|spec, el, _| async move {
-                el.engine().upcheck().await;
+                el.upcheck().await;
                 assert_eq!(
                     el.get_terminal_pow_block_hash(&spec, timestamp_now())
                         .await
@@ -2213,7 +2757,7 @@ mod test {
         MockExecutionLayer::default_params(runtime.task_executor.clone())
             .move_to_terminal_block()
             .with_terminal_block(|spec, el, terminal_block| async move {
-                el.engine().upcheck().await;
+                el.upcheck().await;
                 assert_eq!(
                     el.is_valid_terminal_pow_block_hash(terminal_block.unwrap().block_hash, &spec)
                         .await
@@ -2230,7 +2774,7 @@ mod test {
         MockExecutionLayer::default_params(runtime.task_executor.clone())
             .move_to_terminal_block()
             .with_terminal_block(|spec, el, terminal_block| async move {
-                el.engine().upcheck().await;
+                el.upcheck().await;
                 let invalid_terminal_block = terminal_block.unwrap().parent_hash;
 
                 assert_eq!(
@@ -2249,7 +2793,7 @@ mod test {
         MockExecutionLayer::default_params(runtime.task_executor.clone())
             .move_to_terminal_block()
             .with_terminal_block(|spec, el, _| async move {
-                el.engine().upcheck().await;
+                el.upcheck().await;
                 let missing_terminal_block = ExecutionBlockHash::repeat_byte(42);
 
                 assert_eq!(
@@ -2261,4 +2805,307 @@ mod test {
             })
             .await;
     }
+
+    #[tokio::test]
+    async fn fails_over_to_synced_secondary_engine() {
+        let runtime = TestRuntime::default();
+        let mock =
+            MockExecutionLayer::with_secondary_engines(runtime.task_executor.clone(), 1, false);
+
+        mock.el.upcheck().await;
+        let statuses = mock.el.engine_statuses().await;
+        assert!(statuses[0].preferred);
+        assert!(!statuses[1].preferred);
+
+        mock.server.set_syncing_response(Ok(true));
+        mock.el.upcheck().await;
+        let statuses = mock.el.engine_statuses().await;
+        assert_eq!(statuses[0].status, EngineStatus::Syncing);
+        assert_eq!(statuses[1].status, EngineStatus::Synced);
+        assert!(!statuses[0].preferred);
+        assert!(statuses[1].preferred);
+        assert!(mock.el.is_synced().await);
+
+        mock.server.set_syncing_response(Ok(false));
+        mock.el.upcheck().await;
+        assert!(mock.el.engine_statuses().await[0].preferred);
+    }
+
+    #[tokio::test]
+    async fn skips_offline_engines() {
+        let runtime = TestRuntime::default();
+        let mock =
+            MockExecutionLayer::with_secondary_engines(runtime.task_executor.clone(), 1, false);
+        let primary = mock.el.engines().iter().next().unwrap().clone();
+        let secondary = mock.el.engines().iter().nth(1).unwrap().clone();
+        let get_latest_block = |engine: &Arc<Engine>| {
+            let engine = engine.clone();
+            async move {
+                engine
+                    .request(|engine| {
+                        engine
+                            .api
+                            .get_block_by_number(BlockByNumberQuery::Tag(LATEST_TAG))
+                    })
+                    .await
+            }
+        };
+
+        // Engines which have not been checked yet are not skipped.
+        assert!(secondary.is_offline().await);
+        assert!(get_latest_block(&secondary).await.is_ok());
+
+        mock.server
+            .set_syncing_response(Err("connection refused".to_string()));
+        mock.el.upcheck().await;
+        assert!(primary.is_offline().await);
+        assert!(matches!(
+            get_latest_block(&primary).await,
+            Err(EngineError::Offline)
+        ));
+        assert!(!mock.el.engine_statuses().await[0].preferred);
+
+        mock.server.set_syncing_response(Ok(false));
+        mock.el.upcheck().await;
+        assert!(get_latest_block(&primary).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn new_payload_requires_all_engines_to_agree() {
+        let runtime = TestRuntime::default();
+        let mock =
+            MockExecutionLayer::with_secondary_engines(runtime.task_executor.clone(), 1, true);
+        mock.el.upcheck().await;
+
+        let payload: ExecutionPayload<MainnetEthSpec> = ExecutionPayloadBellatrix {
+            block_hash: ExecutionBlockHash::repeat_byte(1),
+            ..Default::default()
+        }
+        .into();
+        let block_hash = payload.block_hash();
+        let valid = PayloadStatusV1 {
+            status: PayloadStatusV1Status::Valid,
+            latest_valid_hash: Some(block_hash),
+            validation_error: None,
+        };
+        mock.server
+            .set_new_payload_status(block_hash, valid.clone());
+
+        // The payload is optimistic while the secondary engine does not answer.
+        mock.secondary_servers[0].set_new_payload_error(block_hash, "timeout".to_string());
+        let status = mock
+            .el
+            .notify_new_payload(payload.to_ref().try_into().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(status, PayloadStatus::Syncing);
+
+        mock.secondary_servers[0].set_new_payload_status(block_hash, valid);
+        mock.el.upcheck().await;
+        let status = mock
+            .el
+            .notify_new_payload(payload.to_ref().try_into().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(status, PayloadStatus::Valid);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn rejects_jwt_secret_for_ipc_endpoint() {
+        let runtime = TestRuntime::default();
+        let executor = runtime.task_executor.clone();
+        let ipc_url = SensitiveUrl::parse("ipc:///tmp/engine.ipc").unwrap();
+        let secret_file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(&secret_file, hex::encode(test_utils::DEFAULT_JWT_SECRET)).unwrap();
+        let secret_path = secret_file.path().to_path_buf();
+        let from_config = |config| {
+            ExecutionLayer::<MainnetEthSpec>::from_config(
+                config,
+                executor.clone(),
+                executor.log().clone(),
+            )
+        };
+
+        let result = from_config(Config {
+            execution_endpoint: Some(ipc_url.clone()),
+            secret_file: Some(secret_path.clone()),
+            ..Default::default()
+        });
+        assert!(matches!(result, Err(Error::JwtSecretForIpcEndpoint(_))));
+
+        let result = from_config(Config {
+            execution_endpoint: Some(SensitiveUrl::parse("http://localhost:8551").unwrap()),
+            secondary_execution_endpoints: vec![ipc_url.clone()],
+            secondary_secret_files: vec![secret_path.clone()],
+            secret_file: Some(secret_path.clone()),
+            ..Default::default()
+        });
+        assert!(matches!(result, Err(Error::JwtSecretForIpcEndpoint(_))));
+
+        // IPC endpoints do not inherit the JWT secret of the primary endpoint.
+        assert!(from_config(Config {
+            execution_endpoint: Some(SensitiveUrl::parse("http://localhost:8551").unwrap()),
+            secondary_execution_endpoints: vec![ipc_url],
+            secret_file: Some(secret_path),
+            ..Default::default()
+        })
+        .is_ok());
+    }
+
+    #[test]
+    fn expected_gas_limit_moves_towards_target() {
+        assert_eq!(expected_gas_limit(30_000_000, 30_000_000), Some(30_000_000));
+        assert_eq!(expected_gas_limit(30_000_000, 30_010_000), Some(30_010_000));
+        assert_eq!(expected_gas_limit(30_000_000, 36_000_000), Some(30_029_295));
+        assert_eq!(expected_gas_limit(30_000_000, 20_000_000), Some(29_970_705));
+        assert_eq!(expected_gas_limit(1023, 36_000_000), None);
+    }
+
+    #[test]
+    fn bids_match_registration_from_parent_gas_limit() {
+        use types::builder_bid::BuilderBidBellatrix;
+        use types::ExecutionPayloadHeaderBellatrix;
+
+        let log = logging::test_logger();
+        let registration = ValidatorRegistrationData {
+            fee_recipient: Address::repeat_byte(42),
+            gas_limit: 36_000_000,
+            timestamp: 0,
+            pubkey: PublicKeyBytes::empty(),
+        };
+        let bid = |gas_limit| ForkVersionedResponse {
+            version: Some(ForkName::Bellatrix),
+            metadata: Default::default(),
+            data: SignedBuilderBid::<MainnetEthSpec> {
+                message: BuilderBid::Bellatrix(BuilderBidBellatrix {
+                    header: ExecutionPayloadHeaderBellatrix {
+                        fee_recipient: registration.fee_recipient,
+                        gas_limit,
+                        ..Default::default()
+                    },
+                    value: Uint256::from(1),
+                    pubkey: PublicKeyBytes::empty(),
+                }),
+                signature: Signature::empty(),
+            },
+        };
+
+        // The parent is too far from the target to reach it in a single block.
+        let parent_gas_limit = Some(30_000_000);
+        assert!(bid_matches_registration(
+            &bid(30_029_295),
+            &registration,
+            parent_gas_limit,
+            None,
+            &log
+        ));
+        assert!(!bid_matches_registration(
+            &bid(36_000_000),
+            &registration,
+            parent_gas_limit,
+            None,
+            &log
+        ));
+
+        // Without a parent gas limit, the bid must target the registered gas limit.
+        assert!(bid_matches_registration(
+            &bid(36_000_000),
+            &registration,
+            None,
+            None,
+            &log
+        ));
+    }
+
+    fn payload_status(status: PayloadStatusV1Status) -> Result<PayloadStatusV1, EngineError> {
+        Ok(PayloadStatusV1 {
+            status,
+            latest_valid_hash: Some(ExecutionBlockHash::zero()),
+            validation_error: None,
+        })
+    }
+
+    #[test]
+    fn new_payload_verdict_prefers_preferred_engine() {
+        let log = logging::test_logger();
+        let verdict = new_payload_verdict(
+            (1, payload_status(PayloadStatusV1Status::Valid)),
+            vec![(0, payload_status(PayloadStatusV1Status::Syncing))],
+            None,
+            &log,
+        );
+        assert_eq!(verdict.unwrap().status, PayloadStatusV1Status::Valid);
+
+        // Falls back to the first secondary engine that responded.
+        let verdict = new_payload_verdict(
+            (1, Err(EngineError::Offline)),
+            vec![
+                (0, Err(EngineError::Offline)),
+                (2, payload_status(PayloadStatusV1Status::Accepted)),
+            ],
+            None,
+            &log,
+        );
+        assert_eq!(verdict.unwrap().status, PayloadStatusV1Status::Accepted);
+
+        // Returns the error of the preferred engine if no engine responded.
+        let verdict = new_payload_verdict(
+            (0, Err(EngineError::Auth)),
+            vec![(1, Err(EngineError::Offline))],
+            None,
+            &log,
+        );
+        assert!(matches!(verdict, Err(EngineError::Auth)));
+    }
+
+    #[test]
+    fn new_payload_verdict_requires_agreement() {
+        let log = logging::test_logger();
+        let preferred = || (0, payload_status(PayloadStatusV1Status::Valid));
+        let secondary = || {
+            vec![
+                (1, payload_status(PayloadStatusV1Status::Syncing)),
+                (2, Err(EngineError::Offline)),
+            ]
+        };
+        assert_eq!(
+            new_payload_verdict(preferred(), secondary(), None, &log)
+                .unwrap()
+                .status,
+            PayloadStatusV1Status::Valid
+        );
+        let verdict = new_payload_verdict(preferred(), secondary(), Some(3), &log).unwrap();
+        assert_eq!(verdict.status, PayloadStatusV1Status::Syncing);
+        assert_eq!(verdict.latest_valid_hash, None);
+
+        // Engines that failed, or did not respond in time, do not agree with a `VALID` verdict.
+        let verdict = new_payload_verdict(
+            preferred(),
+            vec![(1, Err(EngineError::Offline))],
+            Some(2),
+            &log,
+        );
+        assert_eq!(verdict.unwrap().status, PayloadStatusV1Status::Syncing);
+        let verdict = new_payload_verdict(preferred(), vec![], Some(2), &log);
+        assert_eq!(verdict.unwrap().status, PayloadStatusV1Status::Syncing);
+
+        // Other verdicts are kept as is.
+        let verdict = new_payload_verdict(
+            (0, payload_status(PayloadStatusV1Status::Invalid)),
+            vec![],
+            Some(2),
+            &log,
+        );
+        assert_eq!(verdict.unwrap().status, PayloadStatusV1Status::Invalid);
+
+        // Payloads are valid once all engines agree.
+        let verdict = new_payload_verdict(
+            preferred(),
+            vec![(1, payload_status(PayloadStatusV1Status::Valid))],
+            Some(2),
+            &log,
+        );
+        assert_eq!(verdict.unwrap().status, PayloadStatusV1Status::Valid);
+    }
 }
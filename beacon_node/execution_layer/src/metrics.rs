@@ -116,3 +116,49 @@ pub static EXECUTION_LAYER_PAYLOAD_BIDS: LazyLock<Result<IntGaugeVec>> = LazyLoc
         &["source"]
     )
 });
+pub static EXECUTION_LAYER_ENGINE_STATUS: LazyLock<Result<IntGaugeVec>> = LazyLock::new(|| {
+    try_create_int_gauge_vec(
+        "execution_layer_engine_status",
+        "Set to 1 for the current status of each execution engine, 0 otherwise. The primary \
+        engine has index 0.",
+        &["engine", "status"],
+    )
+});
+pub static EXECUTION_LAYER_ENGINE_DISAGREEMENTS: LazyLock<Result<IntCounter>> =
+    LazyLock::new(|| {
+        try_create_int_counter(
+            "execution_layer_engine_disagreements_total",
+            "Count of payloads not considered valid because the execution engines disagreed",
+        )
+    });
+pub static EXECUTION_LAYER_RELAY_REQUESTS: LazyLock<Result<IntCounterVec>> = LazyLock::new(|| {
+    try_create_int_counter_vec(
+        "execution_layer_relay_requests_total",
+        "Count of requests sent to each builder relay, by method and outcome",
+        &["relay", "method", "outcome"],
+    )
+});
+pub static EXECUTION_LAYER_RELAY_REQUEST_TIMES: LazyLock<Result<HistogramVec>> =
+    LazyLock::new(|| {
+        try_create_histogram_vec_with_buckets(
+            "execution_layer_relay_request_times",
+            "Duration of requests sent to each builder relay, by method",
+            decimal_buckets(-2, 1),
+            &["relay", "method"],
+        )
+    });
+pub static EXECUTION_LAYER_BUILDER_BID_MISMATCHES: LazyLock<Result<IntCounterVec>> =
+    LazyLock::new(|| {
+        try_create_int_counter_vec(
+            "execution_layer_builder_bid_mismatches_total",
+            "Count of builder bids that did not match the proposer's registration, by field",
+            &["field"],
+        )
+    });
+pub static EXECUTION_LAYER_RECORDER_DROPPED_RECORDS: LazyLock<Result<IntCounter>> =
+    LazyLock::new(|| {
+        try_create_int_counter(
+            "execution_layer_recorder_dropped_records_total",
+            "Count of engine API requests not recorded because the recorder could not keep up",
+        )
+    });
@@ -0,0 +1,387 @@
+//! Records the Engine API requests sent to the execution engines, and their responses, to disk so
+//! that they can be replayed against another execution engine at a later time.
+//!
+//! Records are written by a blocking task to a directory of files, each of which contains one
+//! JSON `EngineApiRecord` per line. A new file is started once the current file reaches the
+//! configured size, and the oldest files are deleted once the configured number of files is
+//! exceeded.
+
+use crate::engine_api::{json_structures::JsonResponseBody, Error};
+use crate::metrics;
+use serde::{Deserialize, Serialize};
+use slog::{debug, warn, Logger};
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use task_executor::TaskExecutor;
+use tokio::sync::mpsc;
+
+/// The prefix of the name of each recording file.
+pub const RECORDING_FILE_PREFIX: &str = "engine-api-";
+/// The extension of each recording file.
+pub const RECORDING_FILE_EXTENSION: &str = "jsonl";
+/// The default maximum size of each recording file, in bytes.
+pub const DEFAULT_RECORDING_MAX_FILE_SIZE: u64 = 256 * 1024 * 1024;
+/// The default maximum number of recording files to retain.
+pub const DEFAULT_RECORDING_MAX_FILES: usize = 8;
+
+/// The number of records which may be queued before new records are dropped.
+const RECORDER_CHANNEL_CAPACITY: usize = 1_024;
+
+/// A request sent to an execution engine and its outcome.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct EngineApiRecord {
+    /// The time at which the request was sent, in milliseconds since the UNIX epoch.
+    pub sent_ms: u64,
+    /// The endpoint of the engine, with any credentials redacted.
+    pub endpoint: String,
+    /// The position of the engine, `0` for the primary engine.
+    #[serde(default)]
+    pub engine: usize,
+    pub method: String,
+    pub params: serde_json::Value,
+    /// The time taken for the engine to respond, in microseconds.
+    pub latency_us: u64,
+    /// The JSON-RPC response, if one was received.
+    pub response: Option<JsonResponseBody>,
+    /// The reason no response was received (e.g. a timeout), if applicable.
+    pub error: Option<String>,
+}
+
+/// Configuration for recording Engine API traffic to disk.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecorderConfig {
+    /// The directory in which recording files are written.
+    pub dir: PathBuf,
+    /// The size in bytes at which a new recording file is started.
+    pub max_file_size: u64,
+    /// The maximum number of recording files to retain.
+    pub max_files: usize,
+}
+
+impl RecorderConfig {
+    /// Record to `dir` with the default limits.
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            max_file_size: DEFAULT_RECORDING_MAX_FILE_SIZE,
+            max_files: DEFAULT_RECORDING_MAX_FILES,
+        }
+    }
+}
+
+/// Sends Engine API requests and responses to a task which writes them to disk.
+#[derive(Clone)]
+pub struct EngineApiRecorder {
+    sender: mpsc::Sender<EngineApiRecord>,
+    /// The position of the engine whose requests are recorded, `0` for the primary engine.
+    engine: usize,
+}
+
+impl EngineApiRecorder {
+    /// Start a task which writes records to the directory given by `config`.
+    pub fn start(
+        config: RecorderConfig,
+        executor: &TaskExecutor,
+        log: &Logger,
+    ) -> Result<Self, String> {
+        let writer = RecordWriter::new(config, log.clone())
+            .map_err(|e| format!("Unable to open engine API recording directory: {e}"))?;
+        let (sender, receiver) = mpsc::channel(RECORDER_CHANNEL_CAPACITY);
+        executor.spawn_blocking(move || writer.run(receiver), "engine_api_recorder");
+        Ok(Self { sender, engine: 0 })
+    }
+
+    /// Returns a recorder for the requests sent to the engine at position `engine`, writing to
+    /// the same directory.
+    pub fn for_engine(&self, engine: usize) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            engine,
+        }
+    }
+
+    /// Record a request sent at `sent_at` and the response received after `latency`, or the
+    /// error which prevented a response from being received.
+    pub fn record(
+        &self,
+        endpoint: String,
+        method: &str,
+        params: serde_json::Value,
+        sent_at: SystemTime,
+        latency: Duration,
+        response: &Result<JsonResponseBody, Error>,
+    ) {
+        let (response, error) = match response {
+            Ok(response) => (Some(response.clone()), None),
+            Err(e) => (None, Some(format!("{e:?}"))),
+        };
+        let record = EngineApiRecord {
+            sent_ms: sent_at
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            endpoint,
+            engine: self.engine,
+            method: method.to_string(),
+            params,
+            latency_us: latency.as_micros() as u64,
+            response,
+            error,
+        };
+        if self.sender.try_send(record).is_err() {
+            metrics::inc_counter(&metrics::EXECUTION_LAYER_RECORDER_DROPPED_RECORDS);
+        }
+    }
+}
+
+/// Writes records to a rotating set of recording files.
+struct RecordWriter {
+    config: RecorderConfig,
+    /// Recording files in the order they were created, including the current file.
+    files: VecDeque<PathBuf>,
+    file: Option<BufWriter<File>>,
+    file_size: u64,
+    /// Distinguishes files created within the same millisecond.
+    sequence: u64,
+    log: Logger,
+}
+
+impl RecordWriter {
+    fn new(config: RecorderConfig, log: Logger) -> io::Result<Self> {
+        fs::create_dir_all(&config.dir)?;
+        // Files from previous runs count towards the limit.
+        let files = recording_files(&config.dir)?.into();
+        Ok(Self {
+            config,
+            files,
+            file: None,
+            file_size: 0,
+            sequence: 0,
+            log,
+        })
+    }
+
+    fn run(mut self, mut receiver: mpsc::Receiver<EngineApiRecord>) {
+        while let Some(record) = receiver.blocking_recv() {
+            // Flush whenever the queue is drained so that the files are readable whilst the node
+            // is running.
+            let result = self.write(&record).and_then(|()| {
+                if receiver.is_empty() {
+                    self.flush()
+                } else {
+                    Ok(())
+                }
+            });
+            if let Err(e) = result {
+                warn!(
+                    self.log,
+                    "Failed to write engine API recording";
+                    "error" => %e,
+                    "dir" => ?self.config.dir,
+                );
+            }
+        }
+        if let Err(e) = self.flush() {
+            warn!(self.log, "Failed to flush engine API recording"; "error" => %e);
+        }
+    }
+
+    fn write(&mut self, record: &EngineApiRecord) -> io::Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        let length = line.len() as u64;
+        if self.file.is_none() || self.file_size + length > self.config.max_file_size {
+            self.rotate()?;
+        }
+        if let Some(file) = self.file.as_mut() {
+            file.write_all(&line)?;
+            self.file_size += length;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.file.as_mut() {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// Start a new recording file, deleting the oldest files if there are too many.
+    fn rotate(&mut self) -> io::Result<()> {
+        self.flush()?;
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let name = format!(
+            "{}{:013}-{:06}.{}",
+            RECORDING_FILE_PREFIX, now_ms, self.sequence, RECORDING_FILE_EXTENSION
+        );
+        self.sequence += 1;
+        let path = self.config.dir.join(name);
+        self.file = Some(BufWriter::new(File::create(&path)?));
+        self.file_size = 0;
+        debug!(self.log, "Started engine API recording file"; "path" => ?path);
+        self.files.push_back(path);
+
+        while self.files.len() > self.config.max_files.max(1) {
+            if let Some(oldest) = self.files.pop_front() {
+                fs::remove_file(&oldest)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the recording files in `dir`, oldest first.
+pub fn recording_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?
+        .into_iter()
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == RECORDING_FILE_EXTENSION)
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with(RECORDING_FILE_PREFIX))
+        })
+        .collect::<Vec<_>>();
+    // The timestamp and sequence number are zero-padded, so file names sort chronologically.
+    files.sort();
+    Ok(files)
+}
+
+/// Reads the records of a directory of recording files, oldest first.
+pub struct RecordingReader {
+    files: VecDeque<PathBuf>,
+    current: Option<(PathBuf, BufReader<File>)>,
+    line: String,
+}
+
+impl RecordingReader {
+    pub fn open(dir: &Path) -> Result<Self, String> {
+        let files = recording_files(dir)
+            .map_err(|e| format!("Unable to read recording directory {dir:?}: {e}"))?;
+        Ok(Self {
+            files: files.into(),
+            current: None,
+            line: String::new(),
+        })
+    }
+
+    fn next_record(&mut self) -> Result<Option<EngineApiRecord>, String> {
+        loop {
+            if self.current.is_none() {
+                let Some(path) = self.files.pop_front() else {
+                    return Ok(None);
+                };
+                let file =
+                    File::open(&path).map_err(|e| format!("Unable to open {path:?}: {e}"))?;
+                self.current = Some((path, BufReader::new(file)));
+            }
+            let Some((path, reader)) = self.current.as_mut() else {
+                continue;
+            };
+
+            self.line.clear();
+            reader
+                .read_line(&mut self.line)
+                .map_err(|e| format!("Unable to read {path:?}: {e}"))?;
+            // The final record may be incomplete if the node was still running.
+            if !self.line.ends_with('\n') {
+                self.current = None;
+                continue;
+            }
+
+            return serde_json::from_str(&self.line)
+                .map(Some)
+                .map_err(|e| format!("Invalid record in {path:?}: {e}"));
+        }
+    }
+}
+
+impl Iterator for RecordingReader {
+    type Item = Result<EngineApiRecord, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_record().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn record(index: u64) -> EngineApiRecord {
+        EngineApiRecord {
+            sent_ms: index,
+            endpoint: "http://localhost:8551/".to_string(),
+            engine: 0,
+            method: "engine_newPayloadV3".to_string(),
+            params: json!([{ "blockNumber": format!("{index:#x}") }, [], "0x00"]),
+            latency_us: index * 1_000,
+            response: (index % 2 == 0).then(|| JsonResponseBody {
+                jsonrpc: "2.0".to_string(),
+                error: None,
+                result: json!({ "status": "VALID" }),
+                id: json!(1),
+            }),
+            error: (index % 2 == 1).then(|| "timeout".to_string()),
+        }
+    }
+
+    #[test]
+    fn write_rotate_and_read() {
+        let dir = tempdir().unwrap();
+        let config = RecorderConfig {
+            dir: dir.path().to_path_buf(),
+            max_file_size: 100,
+            max_files: 3,
+        };
+        let log = logging::test_logger();
+        let mut writer = RecordWriter::new(config, log).unwrap();
+
+        let records = (0..8).map(record).collect::<Vec<_>>();
+        for record in &records {
+            writer.write(record).unwrap();
+        }
+        writer.flush().unwrap();
+
+        // Each file holds a single record, and only the last three files are retained.
+        assert_eq!(recording_files(dir.path()).unwrap().len(), 3);
+        let read = RecordingReader::open(dir.path())
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(read, records[5..]);
+    }
+
+    #[test]
+    fn skips_incomplete_final_record() {
+        let dir = tempdir().unwrap();
+        let mut line = serde_json::to_string(&record(0)).unwrap();
+        line.push('\n');
+        line.push_str(r#"{"sent_ms":1,"endpoint""#);
+        fs::write(
+            dir.path().join(format!(
+                "{RECORDING_FILE_PREFIX}0.{RECORDING_FILE_EXTENSION}"
+            )),
+            line,
+        )
+        .unwrap();
+
+        let read = RecordingReader::open(dir.path())
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(read, vec![record(0)]);
+    }
+}
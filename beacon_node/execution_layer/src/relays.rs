@@ -0,0 +1,377 @@
+//! Provides access to a set of builder relays.
+//!
+//! Payload headers are requested from every relay at the same time, and the bid to propose with is
+//! selected by the `ExecutionLayer`. The blinded block is only revealed to the relays that offered
+//! the selected payload, whereas validator registrations are sent to all relays.
+use crate::metrics;
+use builder_client::{BuilderHttpClient, Error};
+use eth2::types::builder_bid::SignedBuilderBid;
+use eth2::types::{ForkVersionedResponse, FullPayloadContents};
+use eth2::StatusCode;
+use futures::future::join_all;
+use lru::LruCache;
+use parking_lot::{Mutex, RwLock};
+use sensitive_url::SensitiveUrl;
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::time::{Duration, Instant};
+use types::non_zero_usize::new_non_zero_usize;
+use types::{
+    EthSpec, ExecPayload, ExecutionBlockHash, PublicKeyBytes, SignedBlindedBeaconBlock,
+    SignedValidatorRegistrationData, Slot, ValidatorRegistrationData,
+};
+
+/// The number of selected payloads for which the offering relays are remembered.
+const BID_RELAYS_CACHE_SIZE: NonZeroUsize = new_non_zero_usize(32);
+
+/// Weight of the latest request in the moving average of a relay's latency.
+const LATENCY_WEIGHT: f64 = 0.2;
+
+const GET_HEADER: &str = "get_header";
+const POST_BLINDED_BLOCKS: &str = "post_blinded_blocks";
+const POST_VALIDATORS: &str = "post_validators";
+
+/// Request statistics of a single relay.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RelayStats {
+    /// Number of requests sent to the relay.
+    pub requests: u64,
+    /// Number of requests that failed or timed out.
+    pub failures: u64,
+    /// Number of payload headers offered by the relay.
+    pub bids: u64,
+    /// Number of payload headers offered by the relay that were proposed with, in favour of the
+    /// bids of the other relays and of the local payload.
+    pub bids_selected: u64,
+    /// Moving average of the response time of successful requests.
+    pub latency: Option<Duration>,
+}
+
+impl RelayStats {
+    fn on_response(&mut self, success: bool, duration: Duration) {
+        self.requests += 1;
+        if !success {
+            self.failures += 1;
+            return;
+        }
+        self.latency = Some(match self.latency {
+            Some(latency) => {
+                latency.mul_f64(1.0 - LATENCY_WEIGHT) + duration.mul_f64(LATENCY_WEIGHT)
+            }
+            None => duration,
+        });
+    }
+}
+
+struct Relay {
+    client: BuilderHttpClient,
+    endpoint: String,
+    stats: Mutex<RelayStats>,
+}
+
+impl Relay {
+    /// Updates the statistics and metrics of the relay with the outcome of a request.
+    fn on_response<T>(&self, method: &str, result: &Result<T, Error>, duration: Duration) {
+        self.stats.lock().on_response(result.is_ok(), duration);
+        metrics::observe_timer_vec(
+            &metrics::EXECUTION_LAYER_RELAY_REQUEST_TIMES,
+            &[&self.endpoint, method],
+            duration,
+        );
+        metrics::inc_counter_vec(
+            &metrics::EXECUTION_LAYER_RELAY_REQUESTS,
+            &[
+                &self.endpoint,
+                method,
+                if result.is_ok() {
+                    metrics::SUCCESS
+                } else {
+                    metrics::FAILURE
+                },
+            ],
+        );
+    }
+}
+
+/// The response of a single relay to a payload header request.
+pub struct RelayBid<E: EthSpec> {
+    /// The index of the relay in `BuilderRelays`.
+    pub relay: usize,
+    pub result: Result<Option<ForkVersionedResponse<SignedBuilderBid<E>>>, Error>,
+    pub duration: Duration,
+}
+
+pub struct BuilderRelays {
+    relays: Vec<Relay>,
+    /// The latest registration of each validator, as sent to the relays.
+    registrations: RwLock<HashMap<PublicKeyBytes, ValidatorRegistrationData>>,
+    /// The relays that offered each selected payload, keyed by block hash.
+    bid_relays: Mutex<LruCache<ExecutionBlockHash, Vec<usize>>>,
+}
+
+impl BuilderRelays {
+    pub fn new(
+        urls: Vec<SensitiveUrl>,
+        user_agent: Option<String>,
+        header_timeout: Option<Duration>,
+    ) -> Result<Self, Error> {
+        let relays = urls
+            .into_iter()
+            .map(|url| {
+                Ok(Relay {
+                    endpoint: url.to_string(),
+                    client: BuilderHttpClient::new(url, user_agent.clone(), header_timeout)?,
+                    stats: Mutex::new(RelayStats::default()),
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(Self {
+            relays,
+            registrations: RwLock::new(HashMap::new()),
+            bid_relays: Mutex::new(LruCache::new(BID_RELAYS_CACHE_SIZE)),
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.relays.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.relays.is_empty()
+    }
+
+    /// The endpoint of the relay at `index`, with any credentials redacted.
+    pub fn endpoint(&self, index: usize) -> Option<&str> {
+        self.relays.get(index).map(|relay| relay.endpoint.as_str())
+    }
+
+    pub fn get_user_agent(&self) -> Option<&str> {
+        self.relays
+            .first()
+            .map(|relay| relay.client.get_user_agent())
+    }
+
+    /// Returns the endpoint and request statistics of each relay.
+    pub fn stats(&self) -> Vec<(String, RelayStats)> {
+        self.relays
+            .iter()
+            .map(|relay| (relay.endpoint.clone(), relay.stats.lock().clone()))
+            .collect()
+    }
+
+    /// The latest registration of the validator with `pubkey`, if any.
+    pub fn registration(&self, pubkey: &PublicKeyBytes) -> Option<ValidatorRegistrationData> {
+        self.registrations.read().get(pubkey).cloned()
+    }
+
+    /// Sends the registrations to all relays. Succeeds if at least one relay accepted them.
+    pub async fn post_builder_validators(
+        &self,
+        registrations: &[SignedValidatorRegistrationData],
+    ) -> Result<(), Error> {
+        {
+            let mut cache = self.registrations.write();
+            for registration in registrations {
+                cache.insert(registration.message.pubkey, registration.message.clone());
+            }
+        }
+
+        let results = join_all(self.relays.iter().map(|relay| async move {
+            let start = Instant::now();
+            let result = relay.client.post_builder_validators(registrations).await;
+            relay.on_response(POST_VALIDATORS, &result, start.elapsed());
+            result
+        }))
+        .await;
+
+        first_success(results)
+    }
+
+    /// Requests a payload header from all relays at the same time.
+    pub async fn get_builder_header<E: EthSpec>(
+        &self,
+        slot: Slot,
+        parent_hash: ExecutionBlockHash,
+        pubkey: &PublicKeyBytes,
+    ) -> Vec<RelayBid<E>> {
+        join_all(
+            self.relays
+                .iter()
+                .enumerate()
+                .map(|(index, relay)| async move {
+                    let start = Instant::now();
+                    let result = relay
+                        .client
+                        .get_builder_header::<E>(slot, parent_hash, pubkey)
+                        .await;
+                    let duration = start.elapsed();
+                    relay.on_response(GET_HEADER, &result, duration);
+                    if let Ok(Some(_)) = result {
+                        relay.stats.lock().bids += 1;
+                    }
+                    RelayBid {
+                        relay: index,
+                        result,
+                        duration,
+                    }
+                }),
+        )
+        .await
+    }
+
+    /// Remembers that the payload with `block_hash` was the best bid, and was offered by `relays`.
+    pub fn on_bid_selected(&self, block_hash: ExecutionBlockHash, relays: Vec<usize>) {
+        self.bid_relays.lock().put(block_hash, relays);
+    }
+
+    /// Counts the payload with `block_hash` as proposed with for the relays that offered it.
+    ///
+    /// Not called if the local payload is used instead of the best bid.
+    pub fn on_bid_used(&self, block_hash: ExecutionBlockHash) {
+        let Some(relays) = self.bid_relays.lock().peek(&block_hash).cloned() else {
+            return;
+        };
+        for index in relays {
+            if let Some(relay) = self.relays.get(index) {
+                relay.stats.lock().bids_selected += 1;
+            }
+        }
+    }
+
+    /// Reveals the blinded block to the relays that offered its payload, or to all relays if
+    /// those are unknown. Returns the first payload revealed.
+    pub async fn post_builder_blinded_blocks<E: EthSpec>(
+        &self,
+        blinded_block: &SignedBlindedBeaconBlock<E>,
+    ) -> Result<ForkVersionedResponse<FullPayloadContents<E>>, Error> {
+        let offering_relays = blinded_block
+            .message()
+            .body()
+            .execution_payload()
+            .ok()
+            .and_then(|payload| self.bid_relays.lock().get(&payload.block_hash()).cloned())
+            .unwrap_or_else(|| (0..self.relays.len()).collect());
+
+        let results = join_all(
+            offering_relays
+                .into_iter()
+                .filter_map(|index| self.relays.get(index))
+                .map(|relay| async move {
+                    let start = Instant::now();
+                    let result = relay
+                        .client
+                        .post_builder_blinded_blocks(blinded_block)
+                        .await;
+                    relay.on_response(POST_BLINDED_BLOCKS, &result, start.elapsed());
+                    result
+                }),
+        )
+        .await;
+
+        first_success(results)
+    }
+}
+
+/// Returns the first successful result, or the first error if none succeeded.
+fn first_success<T>(results: Vec<Result<T, Error>>) -> Result<T, Error> {
+    results
+        .into_iter()
+        .reduce(|first, result| {
+            if first.is_err() && result.is_ok() {
+                result
+            } else {
+                first
+            }
+        })
+        // Only reachable without any relays.
+        .unwrap_or(Err(Error::StatusCode(StatusCode::SERVICE_UNAVAILABLE)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use types::{BeaconBlock, BlindedPayload, ForkName, MainnetEthSpec, Signature};
+
+    /// Starts a relay that counts the blinded blocks revealed to it and rejects them.
+    fn counting_relay() -> (SensitiveUrl, Arc<AtomicUsize>) {
+        let count = Arc::new(AtomicUsize::new(0));
+        let route_count = count.clone();
+        let route = warp::path!("eth" / "v1" / "builder" / "blinded_blocks")
+            .and(warp::post())
+            .map(move || {
+                route_count.fetch_add(1, Ordering::Relaxed);
+                warp::reply::with_status(warp::reply(), warp::http::StatusCode::BAD_GATEWAY)
+            });
+        let (address, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+        let url = SensitiveUrl::parse(&format!("http://{address}")).unwrap();
+        (url, count)
+    }
+
+    #[test]
+    fn relay_stats_track_failures_and_latency() {
+        let mut stats = RelayStats::default();
+        stats.on_response(false, Duration::from_secs(1));
+        assert_eq!(stats.failures, 1);
+        assert_eq!(stats.latency, None);
+
+        stats.on_response(true, Duration::from_millis(100));
+        assert_eq!(stats.latency, Some(Duration::from_millis(100)));
+        stats.on_response(true, Duration::from_millis(600));
+        assert_eq!(stats.requests, 3);
+        assert_eq!(stats.failures, 1);
+        assert!((stats.latency.unwrap().as_secs_f64() - 0.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn first_success_prefers_success() {
+        let error = || Error::StatusCode(StatusCode::BAD_GATEWAY);
+        assert_eq!(first_success(vec![Err(error()), Ok(1), Ok(2)]).unwrap(), 1);
+        assert!(matches!(
+            first_success::<()>(vec![Err(error()), Err(Error::NoToken)]),
+            Err(Error::StatusCode(StatusCode::BAD_GATEWAY))
+        ));
+        assert!(first_success::<()>(vec![]).is_err());
+    }
+
+    #[tokio::test]
+    async fn blinded_blocks_are_revealed_to_offering_relays() {
+        let (url_a, count_a) = counting_relay();
+        let (url_b, count_b) = counting_relay();
+        let relays = BuilderRelays::new(vec![url_a, url_b], None, None).unwrap();
+
+        let spec = ForkName::Bellatrix.make_genesis_spec(MainnetEthSpec::default_spec());
+        let block = SignedBlindedBeaconBlock::<MainnetEthSpec>::from_block(
+            BeaconBlock::<_, BlindedPayload<_>>::empty(&spec),
+            Signature::empty(),
+        );
+        let block_hash = block
+            .message()
+            .body()
+            .execution_payload()
+            .unwrap()
+            .block_hash();
+
+        // Unknown payloads are revealed to all relays.
+        assert!(relays.post_builder_blinded_blocks(&block).await.is_err());
+        assert_eq!(count_a.load(Ordering::Relaxed), 1);
+        assert_eq!(count_b.load(Ordering::Relaxed), 1);
+
+        relays.on_bid_selected(block_hash, vec![1]);
+        assert!(relays.post_builder_blinded_blocks(&block).await.is_err());
+        assert_eq!(count_a.load(Ordering::Relaxed), 1);
+        assert_eq!(count_b.load(Ordering::Relaxed), 2);
+
+        // Bids are only counted as selected once proposed with.
+        assert!(relays
+            .stats()
+            .iter()
+            .all(|(_, stats)| stats.bids_selected == 0));
+        relays.on_bid_used(block_hash);
+        let stats = relays.stats();
+        assert_eq!(stats[0].1.bids_selected, 0);
+        assert_eq!(stats[1].1.bids_selected, 1);
+    }
+}
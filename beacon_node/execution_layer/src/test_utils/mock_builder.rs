@@ -1,5 +1,5 @@
 use crate::test_utils::{DEFAULT_BUILDER_PAYLOAD_VALUE_WEI, DEFAULT_JWT_SECRET};
-use crate::{Config, ExecutionLayer, PayloadAttributes};
+use crate::{expected_gas_limit, Config, ExecutionLayer, PayloadAttributes};
 use eth2::types::{BlobsBundle, BlockId, StateId, ValidatorId};
 use eth2::{BeaconNodeHttpClient, Timeouts, CONSENSUS_VERSION_HEADER};
 use fork_choice::ForkchoiceUpdateParameters;
@@ -413,11 +413,12 @@ pub fn serve<E: EthSpec>(
 
                 let block = head.data.message();
                 let head_block_root = block.tree_hash_root();
-                let head_execution_hash = block
+                let head_execution_payload = block
                     .body()
                     .execution_payload()
-                    .map_err(|_| reject("pre-merge block"))?
-                    .block_hash();
+                    .map_err(|_| reject("pre-merge block"))?;
+                let head_execution_hash = head_execution_payload.block_hash();
+                let head_gas_limit = head_execution_payload.gas_limit();
                 if head_execution_hash != parent_hash {
                     return Err(reject("head mismatch"));
                 }
@@ -648,7 +649,10 @@ pub fn serve<E: EthSpec>(
                     }
                 };
 
-                message.set_gas_limit(cached_data.gas_limit);
+                message.set_gas_limit(
+                    expected_gas_limit(head_gas_limit, cached_data.gas_limit)
+                        .unwrap_or(cached_data.gas_limit),
+                );
 
                 builder.apply_operations(&mut message);
 
@@ -11,6 +11,8 @@ use types::{FixedBytesExtended, MainnetEthSpec};
 
 pub struct MockExecutionLayer<E: EthSpec> {
     pub server: MockServer<E>,
+    /// Servers behind the secondary engines of `el`, in order.
+    pub secondary_servers: Vec<MockServer<E>>,
     pub el: ExecutionLayer<E>,
     pub executor: TaskExecutor,
     pub spec: ChainSpec,
@@ -77,6 +79,65 @@ impl<E: EthSpec> MockExecutionLayer<E> {
 
         Self {
             server,
+            secondary_servers: vec![],
+            el,
+            executor,
+            spec,
+        }
+    }
+
+    /// Returns a mock execution layer with default parameters that sends requests to the primary
+    /// engine and `secondary_engines` secondary engines.
+    pub fn with_secondary_engines(
+        executor: TaskExecutor,
+        secondary_engines: usize,
+        require_engine_agreement: bool,
+    ) -> Self {
+        let mut spec = MainnetEthSpec::default_spec();
+        spec.terminal_total_difficulty = Uint256::from(DEFAULT_TERMINAL_DIFFICULTY);
+        spec.terminal_block_hash = ExecutionBlockHash::zero();
+        spec.terminal_block_hash_activation_epoch = Epoch::new(0);
+        let handle = executor.handle().unwrap();
+
+        let new_server = || {
+            MockServer::new(
+                &handle,
+                JwtKey::from_slice(&DEFAULT_JWT_SECRET).unwrap(),
+                spec.terminal_total_difficulty,
+                DEFAULT_TERMINAL_BLOCK,
+                spec.terminal_block_hash,
+                None,
+                None,
+                None,
+                None,
+            )
+        };
+        let server = new_server();
+        let secondary_servers = (0..secondary_engines)
+            .map(|_| new_server())
+            .collect::<Vec<_>>();
+
+        let file = NamedTempFile::new().unwrap();
+        let path = file.path().into();
+        std::fs::write(&path, hex::encode(DEFAULT_JWT_SECRET)).unwrap();
+
+        let config = Config {
+            execution_endpoint: Some(SensitiveUrl::parse(&server.url()).unwrap()),
+            secondary_execution_endpoints: secondary_servers
+                .iter()
+                .map(|server| SensitiveUrl::parse(&server.url()).unwrap())
+                .collect(),
+            require_engine_agreement,
+            secret_file: Some(path),
+            suggested_fee_recipient: Some(Address::repeat_byte(42)),
+            ..Default::default()
+        };
+        let el =
+            ExecutionLayer::from_config(config, executor.clone(), executor.log().clone()).unwrap();
+
+        Self {
+            server,
+            secondary_servers,
             el,
             executor,
             spec,
@@ -126,6 +187,7 @@ impl<E: EthSpec> MockExecutionLayer<E> {
             pubkey: PublicKeyBytes::empty(),
             slot,
             chain_health: ChainHealth::Healthy,
+            parent_gas_limit: None,
         };
         let suggested_fee_recipient = self.el.get_suggested_fee_recipient(validator_index).await;
         let payload_attributes =
@@ -166,6 +228,7 @@ impl<E: EthSpec> MockExecutionLayer<E> {
             pubkey: PublicKeyBytes::empty(),
             slot,
             chain_health: ChainHealth::Healthy,
+            parent_gas_limit: None,
         };
         let suggested_fee_recipient = self.el.get_suggested_fee_recipient(validator_index).await;
         let payload_attributes =
@@ -1,4 +1,4 @@
-//! Provides a mock execution engine HTTP JSON-RPC API for use in testing.
+//! Provides a mock execution engine HTTP (and IPC) JSON-RPC API for use in testing.
 
 use crate::engine_api::auth::JwtKey;
 use crate::engine_api::{
@@ -19,6 +19,7 @@ use std::convert::Infallible;
 use std::future::Future;
 use std::marker::PhantomData;
 use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::path::Path;
 use std::sync::{Arc, LazyLock};
 use tokio::{runtime, sync::oneshot};
 use types::{EthSpec, ExecutionBlockHash, Uint256};
@@ -193,6 +194,13 @@ impl<E: EthSpec> MockServer<E> {
         }
     }
 
+    /// Also serves the JSON-RPC API on the Unix socket at `path`, for as long as the runtime runs.
+    #[cfg(unix)]
+    pub fn listen_ipc(&self, path: &Path) -> Result<(), Error> {
+        tokio::spawn(serve_ipc(self.ctx.clone(), path)?);
+        Ok(())
+    }
+
     pub fn set_engine_capabilities(&self, engine_capabilities: EngineCapabilities) {
         *self.ctx.engine_capabilities.write() = engine_capabilities;
     }
@@ -636,6 +644,43 @@ async fn handle_rejection(err: Rejection) -> Result<impl warp::Reply, Infallible
     Ok(warp::reply::with_status(json, code))
 }
 
+/// Returns the response to a JSON-RPC request, or `None` if the request has no `id`.
+async fn rpc_response<E: EthSpec>(
+    body: serde_json::Value,
+    ctx: Arc<Context<E>>,
+) -> Option<serde_json::Value> {
+    let id = body.get("id").and_then(serde_json::Value::as_u64)?;
+    let preloaded_response = {
+        let mut preloaded_responses = ctx.preloaded_responses.lock();
+        if !preloaded_responses.is_empty() {
+            Some(preloaded_responses.remove(0))
+        } else {
+            None
+        }
+    };
+
+    let response = if let Some(preloaded_response) = preloaded_response {
+        preloaded_response
+    } else {
+        match handle_rpc(body, ctx).await {
+            Ok(result) => json!({
+                "id": id,
+                "jsonrpc": JSONRPC_VERSION,
+                "result": result
+            }),
+            Err((message, code)) => json!({
+                "id": id,
+                "jsonrpc": JSONRPC_VERSION,
+                "error": {
+                    "code": code,
+                    "message": message
+                }
+            }),
+        }
+    };
+    Some(response)
+}
+
 /// Creates a server that will serve requests using information from `ctx`.
 ///
 /// The server will shut down gracefully when the `shutdown` future resolves.
@@ -668,38 +713,9 @@ pub fn serve<E: EthSpec>(
         .and(warp::body::json())
         .and(ctx_filter.clone())
         .and_then(|body: serde_json::Value, ctx: Arc<Context<E>>| async move {
-            let id = body
-                .get("id")
-                .and_then(serde_json::Value::as_u64)
+            let response = rpc_response(body, ctx)
+                .await
                 .ok_or_else(|| warp::reject::custom(MissingIdField))?;
-            let preloaded_response = {
-                let mut preloaded_responses = ctx.preloaded_responses.lock();
-                if !preloaded_responses.is_empty() {
-                    Some(preloaded_responses.remove(0))
-                } else {
-                    None
-                }
-            };
-
-            let response = if let Some(preloaded_response) = preloaded_response {
-                preloaded_response
-            } else {
-                match handle_rpc(body, ctx).await {
-                    Ok(result) => json!({
-                        "id": id,
-                        "jsonrpc": JSONRPC_VERSION,
-                        "result": result
-                    }),
-                    Err((message, code)) => json!({
-                        "id": id,
-                        "jsonrpc": JSONRPC_VERSION,
-                        "error": {
-                            "code": code,
-                            "message": message
-                        }
-                    }),
-                }
-            };
 
             Ok::<_, warp::reject::Rejection>(
                 warp::http::Response::builder()
@@ -743,3 +759,45 @@ pub fn serve<E: EthSpec>(
 
     Ok((listening_socket, server))
 }
+
+/// Creates a server that responds to JSON-RPC requests on the Unix socket at `path`, in the same
+/// way as the HTTP server but without JWT authentication.
+///
+/// Must be called from within a tokio runtime.
+#[cfg(unix)]
+pub fn serve_ipc<E: EthSpec>(
+    ctx: Arc<Context<E>>,
+    path: &Path,
+) -> Result<impl Future<Output = ()>, Error> {
+    use crate::engine_api::ipc::JsonReader;
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::UnixListener;
+
+    let listener = UnixListener::bind(path)
+        .map_err(|e| format!("Unable to bind IPC socket {}: {:?}", path.display(), e))?;
+
+    info!(
+        ctx.log,
+        "IPC server started";
+        "path" => %path.display(),
+    );
+
+    Ok(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let ctx = ctx.clone();
+            tokio::spawn(async move {
+                let mut reader = JsonReader::new(stream);
+                while let Ok(Some(body)) = reader.read_value::<serde_json::Value>().await {
+                    let Some(response) = rpc_response(body, ctx.clone()).await else {
+                        break;
+                    };
+                    let response =
+                        serde_json::to_vec(&response).expect("response must be valid JSON");
+                    if reader.get_mut().write_all(&response).await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    })
+}
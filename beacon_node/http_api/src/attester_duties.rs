@@ -1,5 +1,6 @@
 //! Contains the handler for the `GET validator/duties/attester/{epoch}` endpoint.
 
+use crate::committees::finalized_epoch_shuffling;
 use crate::state_id::StateId;
 use beacon_chain::{BeaconChain, BeaconChainError, BeaconChainTypes};
 use eth2::types::{self as api_types};
@@ -68,13 +69,34 @@ fn cached_attestation_duties<T: BeaconChainTypes>(
     )
 }
 
-/// Compute some attester duties by reading a `BeaconState` from disk, completely ignoring the
-/// shuffling cache.
+/// Compute some attester duties from the freezer or by reading a `BeaconState` from disk,
+/// completely ignoring the shuffling cache.
 fn compute_historic_attester_duties<T: BeaconChainTypes>(
     request_epoch: Epoch,
     request_indices: &[u64],
     chain: &BeaconChain<T>,
 ) -> Result<ApiDuties, warp::reject::Rejection> {
+    // Finalized epochs can be served from the freezer without loading a state.
+    if let Some(shuffling) = finalized_epoch_shuffling(chain, request_epoch)? {
+        let committee_cache = shuffling
+            .committee_cache::<T::EthSpec>(&chain.spec)
+            .map_err(BeaconChainError::from)
+            .map_err(warp_utils::reject::beacon_chain_error)?;
+        let duties = request_indices
+            .iter()
+            .map(|&validator_index| {
+                committee_cache.get_attestation_duties(validator_index as usize)
+            })
+            .collect();
+        return convert_to_api_response(
+            duties,
+            request_indices,
+            shuffling.attester_shuffling_decision_root,
+            false,
+            chain,
+        );
+    }
+
     // If the head is quite old then it might still be relevant for a historical request.
     //
     // Avoid holding the `cached_head` longer than necessary.
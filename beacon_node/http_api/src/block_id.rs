@@ -1,13 +1,13 @@
 use crate::{state_id::checkpoint_slot_and_execution_optimistic, ExecutionOptimistic};
-use beacon_chain::{BeaconChain, BeaconChainError, BeaconChainTypes, WhenSlotSkipped};
+use beacon_chain::{kzg_utils, BeaconChain, BeaconChainError, BeaconChainTypes, WhenSlotSkipped};
 use eth2::types::BlobIndicesQuery;
 use eth2::types::BlockId as CoreBlockId;
 use std::fmt;
 use std::str::FromStr;
 use std::sync::Arc;
 use types::{
-    BlobSidecarList, EthSpec, FixedBytesExtended, Hash256, SignedBeaconBlock,
-    SignedBlindedBeaconBlock, Slot,
+    AbstractExecPayload, BlobSidecarList, DataColumnSidecarList, EthSpec, FixedBytesExtended,
+    Hash256, SignedBeaconBlock, SignedBlindedBeaconBlock, Slot,
 };
 
 /// Wraps `eth2::types::BlockId` and provides a simple way to obtain a block or root for a given
@@ -277,27 +277,8 @@ impl BlockId {
             warp_utils::reject::custom_not_found(format!("beacon block with root {}", root))
         })?;
 
-        // Error if the block is pre-Deneb and lacks blobs.
-        let blob_kzg_commitments = block.message().body().blob_kzg_commitments().map_err(|_| {
-            warp_utils::reject::custom_bad_request(
-                "block is pre-Deneb and has no blobs".to_string(),
-            )
-        })?;
-
-        // Return the `BlobSidecarList` identified by `self`.
-        let blob_sidecar_list = if !blob_kzg_commitments.is_empty() {
-            chain
-                .store
-                .get_blobs(&root)
-                .map_err(|e| warp_utils::reject::beacon_chain_error(e.into()))?
-                .ok_or_else(|| {
-                    warp_utils::reject::custom_not_found(format!(
-                        "no blobs stored for block {root}"
-                    ))
-                })?
-        } else {
-            BlobSidecarList::default()
-        };
+        let blob_sidecar_list =
+            Self::blob_sidecar_list(root, &block, indices.indices.as_deref(), chain)?;
 
         let blob_sidecar_list_filtered = match indices.indices {
             Some(vec) => {
@@ -317,6 +298,92 @@ impl BlockId {
             finalized,
         ))
     }
+
+    /// Return the blobs stored for the block with `root`, reconstructing them from data columns
+    /// after PeerDAS.
+    ///
+    /// Only the blobs at `indices` are guaranteed to be included when blobs are reconstructed.
+    pub fn blob_sidecar_list<T: BeaconChainTypes, Payload: AbstractExecPayload<T::EthSpec>>(
+        root: Hash256,
+        block: &SignedBeaconBlock<T::EthSpec, Payload>,
+        indices: Option<&[u64]>,
+        chain: &BeaconChain<T>,
+    ) -> Result<BlobSidecarList<T::EthSpec>, warp::Rejection> {
+        // Error if the block is pre-Deneb and lacks blobs.
+        let blob_kzg_commitments = block.message().body().blob_kzg_commitments().map_err(|_| {
+            warp_utils::reject::custom_bad_request(
+                "block is pre-Deneb and has no blobs".to_string(),
+            )
+        })?;
+
+        if blob_kzg_commitments.is_empty() {
+            return Ok(BlobSidecarList::default());
+        }
+
+        match chain
+            .store
+            .get_blobs(&root)
+            .map_err(|e| warp_utils::reject::beacon_chain_error(e.into()))?
+        {
+            Some(blob_sidecar_list) => Ok(blob_sidecar_list),
+            // After PeerDAS blobs are only stored as data columns.
+            None if chain.spec.is_peer_das_enabled_for_epoch(block.epoch()) => {
+                Self::reconstruct_blobs(root, block, indices, chain)
+            }
+            None => Err(warp_utils::reject::custom_not_found(format!(
+                "no blobs stored for block {root}"
+            ))),
+        }
+    }
+
+    /// Reconstruct the blobs at `indices` of the block with `root` from its stored data columns.
+    ///
+    /// This requires the node to custody at least half of all columns.
+    fn reconstruct_blobs<T: BeaconChainTypes, Payload: AbstractExecPayload<T::EthSpec>>(
+        root: Hash256,
+        block: &SignedBeaconBlock<T::EthSpec, Payload>,
+        indices: Option<&[u64]>,
+        chain: &BeaconChain<T>,
+    ) -> Result<BlobSidecarList<T::EthSpec>, warp::Rejection> {
+        let data_columns = Self::stored_data_columns(root, chain)?;
+        let required_columns = chain.spec.number_of_columns / 2;
+        if data_columns.len() < required_columns {
+            return Err(warp_utils::reject::custom_not_found(format!(
+                "unable to reconstruct blobs for block {root}: {} of the {required_columns} \
+                 required data columns are stored",
+                data_columns.len()
+            )));
+        }
+
+        kzg_utils::reconstruct_blobs(&chain.kzg, &data_columns, indices, block).map_err(|e| {
+            warp_utils::reject::custom_server_error(format!(
+                "unable to reconstruct blobs for block {root}: {e}"
+            ))
+        })
+    }
+
+    /// Return the data columns stored for the block with `root`, ordered by column index.
+    pub fn stored_data_columns<T: BeaconChainTypes>(
+        root: Hash256,
+        chain: &BeaconChain<T>,
+    ) -> Result<DataColumnSidecarList<T::EthSpec>, warp::Rejection> {
+        let mut column_indices = chain
+            .store
+            .get_data_column_keys(root)
+            .map_err(|e| warp_utils::reject::beacon_chain_error(e.into()))?;
+        column_indices.sort_unstable();
+
+        let mut data_columns = Vec::with_capacity(column_indices.len());
+        for index in column_indices {
+            if let Some(data_column) = chain
+                .get_data_column(&root, &index)
+                .map_err(warp_utils::reject::beacon_chain_error)?
+            {
+                data_columns.push(data_column);
+            }
+        }
+        Ok(data_columns)
+    }
 }
 
 impl FromStr for BlockId {
@@ -0,0 +1,291 @@
+use crate::block_id::BlockId;
+use beacon_chain::{BeaconChain, BeaconChainError, BeaconChainTypes, WhenSlotSkipped};
+use bytes::Bytes;
+use eth2::lighthouse::{block_range::encode_frame, BlockRangeQuery};
+use futures::StreamExt;
+use slog::{debug, warn};
+use ssz::Encode;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use types::{AbstractExecPayload, EthSpec, Hash256, SignedBeaconBlock, Slot};
+use warp::http::Response;
+use warp::hyper::Body;
+use warp_utils::reject::{beacon_chain_error, custom_bad_request, custom_server_error};
+
+/// The maximum number of slots which may be included in a single request.
+const MAX_REQUEST_SLOTS: u64 = 8_192;
+/// The number of blocks which are loaded from the database at a time.
+const BLOCK_ROOT_CHUNK_SIZE: usize = 64;
+/// The number of blocks which may be buffered before loading waits for the client.
+const BLOCK_CHANNEL_CAPACITY: usize = 64;
+
+#[derive(Debug)]
+// We don't use the inner values directly, but they're used in the Debug impl.
+enum BlockRangeError {
+    BeaconChain(#[allow(dead_code)] BeaconChainError),
+    Rejection(#[allow(dead_code)] warp::Rejection),
+    MissingBlock(#[allow(dead_code)] Hash256),
+    ReceiverDropped,
+}
+
+impl From<BeaconChainError> for BlockRangeError {
+    fn from(e: BeaconChainError) -> Self {
+        Self::BeaconChain(e)
+    }
+}
+
+impl From<warp::Rejection> for BlockRangeError {
+    fn from(e: warp::Rejection) -> Self {
+        Self::Rejection(e)
+    }
+}
+
+type BlockSender = mpsc::Sender<Result<Bytes, std::io::Error>>;
+
+/// Stream the canonical blocks in `start_slot..=end_slot` as length-prefixed SSZ.
+///
+/// Each block is sent as a single frame, followed by a frame containing its blob sidecars if
+/// `blobs` is set. The block roots are resolved before the response is returned so that invalid
+/// requests are rejected with an appropriate status code. The blocks themselves are loaded in
+/// chunks by a separate task which feeds the response body.
+pub fn get_block_range<T: BeaconChainTypes>(
+    query: BlockRangeQuery,
+    chain: Arc<BeaconChain<T>>,
+) -> Result<Response<Body>, warp::Rejection> {
+    let BlockRangeQuery {
+        start_slot,
+        end_slot,
+        blobs,
+        ..
+    } = query;
+
+    if start_slot > end_slot {
+        return Err(custom_bad_request(format!(
+            "start_slot must not be larger than end_slot. start: {}, end: {}",
+            start_slot, end_slot
+        )));
+    }
+
+    if end_slot - start_slot >= MAX_REQUEST_SLOTS {
+        return Err(custom_bad_request(format!(
+            "at most {} slots may be requested",
+            MAX_REQUEST_SLOTS
+        )));
+    }
+
+    let head_slot = chain.canonical_head.cached_head().head_slot();
+    if end_slot > head_slot {
+        return Err(custom_bad_request(format!(
+            "end_slot must not be later than the head. head: {}, end: {}",
+            head_slot, end_slot
+        )));
+    }
+
+    if blobs {
+        check_blobs_available(start_slot, end_slot, &chain)?;
+    }
+
+    let block_roots = canonical_block_roots(start_slot, end_slot, &chain)?;
+
+    let (sender, receiver) = mpsc::channel(BLOCK_CHANNEL_CAPACITY);
+    let task_chain = chain.clone();
+    chain.task_executor.spawn(
+        async move {
+            let chain = task_chain;
+            if let Err(e) = stream_blocks(&chain, block_roots, query, &sender).await {
+                match e {
+                    BlockRangeError::ReceiverDropped => {
+                        debug!(chain.log, "Block range request cancelled");
+                    }
+                    e => {
+                        warn!(
+                            chain.log,
+                            "Failed to stream block range";
+                            "error" => ?e
+                        );
+                        // Abort the response body so the client does not mistake a partial
+                        // response for a complete one.
+                        let _ = sender
+                            .send(Err(std::io::Error::other(format!("{:?}", e))))
+                            .await;
+                    }
+                }
+            }
+        },
+        "block_range",
+    );
+
+    Response::builder()
+        .status(200)
+        .header("Content-Type", "application/octet-stream")
+        .body(Body::wrap_stream(ReceiverStream::new(receiver)))
+        .map_err(|e| custom_server_error(format!("failed to create response: {}", e)))
+}
+
+/// Reject requests for blobs which have already been pruned.
+fn check_blobs_available<T: BeaconChainTypes>(
+    start_slot: Slot,
+    end_slot: Slot,
+    chain: &BeaconChain<T>,
+) -> Result<(), warp::Rejection> {
+    let Some(deneb_fork_epoch) = chain.spec.deneb_fork_epoch else {
+        return Ok(());
+    };
+    // Blocks prior to Deneb have no blobs.
+    let first_blob_slot = std::cmp::max(
+        start_slot,
+        deneb_fork_epoch.start_slot(T::EthSpec::slots_per_epoch()),
+    );
+    if first_blob_slot > end_slot {
+        return Ok(());
+    }
+
+    match chain.store.get_blob_info().oldest_blob_slot {
+        Some(oldest_blob_slot) if first_blob_slot < oldest_blob_slot => Err(custom_bad_request(
+            format!("blobs are only available from slot {}", oldest_blob_slot),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Return the roots of the canonical blocks in `start_slot..=end_slot`, in slot order.
+fn canonical_block_roots<T: BeaconChainTypes>(
+    start_slot: Slot,
+    end_slot: Slot,
+    chain: &BeaconChain<T>,
+) -> Result<Vec<Hash256>, warp::Rejection> {
+    // Skipped slots repeat the root of the prior block, which may be prior to `start_slot`.
+    let mut last_block_root = match start_slot.as_u64().checked_sub(1) {
+        Some(prior_slot) => chain
+            .block_root_at_slot(Slot::new(prior_slot), WhenSlotSkipped::Prev)
+            .map_err(beacon_chain_error)?,
+        None => None,
+    };
+
+    let mut block_roots = vec![];
+    for result in chain
+        .forwards_iter_block_roots_until(start_slot, end_slot)
+        .map_err(|e| match e {
+            BeaconChainError::HistoricalBlockOutOfRange {
+                oldest_block_slot, ..
+            } => custom_bad_request(format!(
+                "blocks are only available from slot {}",
+                oldest_block_slot
+            )),
+            e => beacon_chain_error(e),
+        })?
+    {
+        let (block_root, _) = result.map_err(beacon_chain_error)?;
+        if Some(block_root) != last_block_root {
+            block_roots.push(block_root);
+            last_block_root = Some(block_root);
+        }
+    }
+    Ok(block_roots)
+}
+
+async fn stream_blocks<T: BeaconChainTypes>(
+    chain: &Arc<BeaconChain<T>>,
+    block_roots: Vec<Hash256>,
+    query: BlockRangeQuery,
+    sender: &BlockSender,
+) -> Result<(), BlockRangeError> {
+    for chunk in block_roots.chunks(BLOCK_ROOT_CHUNK_SIZE) {
+        let chunk = chunk.to_vec();
+        let task_chain = chain.clone();
+        let frames = if query.blinded {
+            spawn_blocking(
+                chain,
+                move || {
+                    let blocks = chunk
+                        .into_iter()
+                        .map(|root| {
+                            let block = task_chain
+                                .get_blinded_block(&root)?
+                                .ok_or(BlockRangeError::MissingBlock(root))?;
+                            Ok((root, Arc::new(block)))
+                        })
+                        .collect::<Result<Vec<_>, BlockRangeError>>()?;
+                    encode_blocks(&task_chain, blocks, query.blobs)
+                },
+                "block_range_blinded",
+            )
+            .await??
+        } else {
+            // Full blocks are loaded via the block streamer, which fetches payloads from the
+            // execution layer by range.
+            let mut blocks = Vec::with_capacity(chunk.len());
+            let mut stream = chain.get_blocks(chunk)?;
+            while let Some((root, result)) = stream.next().await {
+                match result.as_ref() {
+                    Ok(Some(block)) => blocks.push((root, block.clone())),
+                    Ok(None) => return Err(BlockRangeError::MissingBlock(root)),
+                    Err(e) => {
+                        return Err(BlockRangeError::Rejection(custom_server_error(format!(
+                            "unable to load block {}: {:?}",
+                            root, e
+                        ))))
+                    }
+                }
+            }
+            spawn_blocking(
+                chain,
+                move || encode_blocks(&task_chain, blocks, query.blobs),
+                "block_range_full",
+            )
+            .await??
+        };
+
+        for frame in frames {
+            sender
+                .send(Ok(frame))
+                .await
+                .map_err(|_| BlockRangeError::ReceiverDropped)?;
+        }
+    }
+    Ok(())
+}
+
+/// Encode each block, and its blobs if requested, into the bytes of its frames.
+fn encode_blocks<T: BeaconChainTypes, Payload: AbstractExecPayload<T::EthSpec>>(
+    chain: &BeaconChain<T>,
+    blocks: Vec<(Hash256, Arc<SignedBeaconBlock<T::EthSpec, Payload>>)>,
+    blobs: bool,
+) -> Result<Vec<Bytes>, BlockRangeError> {
+    blocks
+        .into_iter()
+        .map(|(root, block)| {
+            let mut bytes = vec![];
+            encode_frame(&block.as_ssz_bytes(), &mut bytes);
+            if blobs {
+                // Blocks prior to Deneb are followed by an empty frame.
+                let blob_sidecar_list = if block.message().body().blob_kzg_commitments().is_ok() {
+                    BlockId::blob_sidecar_list(root, &*block, None, chain)?.as_ssz_bytes()
+                } else {
+                    vec![]
+                };
+                encode_frame(&blob_sidecar_list, &mut bytes);
+            }
+            Ok(Bytes::from(bytes))
+        })
+        .collect()
+}
+
+async fn spawn_blocking<T, F, R>(
+    chain: &BeaconChain<T>,
+    task: F,
+    name: &'static str,
+) -> Result<R, BeaconChainError>
+where
+    T: BeaconChainTypes,
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    chain
+        .task_executor
+        .spawn_blocking_handle(task, name)
+        .ok_or(BeaconChainError::RuntimeShutdown)?
+        .await
+        .map_err(BeaconChainError::TokioJoin)
+}
@@ -0,0 +1,268 @@
+use beacon_chain::{BeaconChain, BeaconChainError, BeaconChainTypes};
+use eth2::lighthouse::{BlockSimulation, SimulatedPayloadStatus};
+use eth2::SSZ_CONTENT_TYPE_HEADER;
+use execution_layer::{NewPayloadRequest, PayloadStatus};
+use slog::debug;
+use state_processing::state_advance::complete_state_advance;
+use state_processing::{
+    per_block_processing, BlockSignatureStrategy, ConsensusContext, VerifyBlockRoot,
+};
+use std::sync::Arc;
+use types::{
+    AbstractExecPayload, BeaconBlock, BeaconState, BlindedPayload, ChainSpec, EthSpec, ExecPayload,
+    ExecutionBlockHash, ForkVersionDeserialize, FullPayload, Signature, SignedBeaconBlock, Slot,
+};
+use warp_utils::reject::{beacon_chain_error, custom_bad_request, custom_not_found};
+
+/// An unsigned block submitted for simulation.
+pub enum UnsignedBlock<E: EthSpec> {
+    Full(BeaconBlock<E, FullPayload<E>>),
+    Blinded(BeaconBlock<E, BlindedPayload<E>>),
+}
+
+impl<E: EthSpec> UnsignedBlock<E> {
+    /// Decode a block from either a JSON or SSZ request body.
+    ///
+    /// The fork is determined by the slot of the block.
+    pub fn from_request_body(
+        body: &[u8],
+        content_type: Option<&str>,
+        blinded: bool,
+        spec: &ChainSpec,
+    ) -> Result<Self, warp::Rejection> {
+        if content_type == Some(SSZ_CONTENT_TYPE_HEADER) {
+            let ssz_error = |e| custom_bad_request(format!("invalid SSZ: {e:?}"));
+            if blinded {
+                BeaconBlock::from_ssz_bytes(body, spec)
+                    .map(Self::Blinded)
+                    .map_err(ssz_error)
+            } else {
+                BeaconBlock::from_ssz_bytes(body, spec)
+                    .map(Self::Full)
+                    .map_err(ssz_error)
+            }
+        } else {
+            let value: serde_json::Value = serde_json::from_slice(body)
+                .map_err(|e| custom_bad_request(format!("invalid JSON: {e:?}")))?;
+            let slot: Slot = value
+                .get("slot")
+                .cloned()
+                .ok_or_else(|| custom_bad_request("block is missing slot".to_string()))
+                .and_then(|slot| {
+                    serde_json::from_value(slot)
+                        .map_err(|e| custom_bad_request(format!("invalid slot: {e:?}")))
+                })?;
+            let fork_name = spec.fork_name_at_slot::<E>(slot);
+            let json_error = |e| custom_bad_request(format!("invalid JSON: {e:?}"));
+            if blinded {
+                BeaconBlock::deserialize_by_fork::<serde_json::Value>(value, fork_name)
+                    .map(Self::Blinded)
+                    .map_err(json_error)
+            } else {
+                BeaconBlock::deserialize_by_fork::<serde_json::Value>(value, fork_name)
+                    .map(Self::Full)
+                    .map_err(json_error)
+            }
+        }
+    }
+}
+
+/// Apply `block` to its parent state without importing it, then verify its execution payload.
+///
+/// Signatures are not checked, so that candidate blocks from builders or relays may be checked
+/// before they are signed.
+pub async fn simulate_block<T: BeaconChainTypes>(
+    block: UnsignedBlock<T::EthSpec>,
+    chain: Arc<BeaconChain<T>>,
+) -> Result<BlockSimulation, warp::Rejection> {
+    match block {
+        UnsignedBlock::Full(block) => {
+            let task_chain = chain.clone();
+            let (mut simulation, block) = warp_utils::task::blocking_task(move || {
+                let simulation = simulate_state_transition(&task_chain, &block)?;
+                Ok((simulation, block))
+            })
+            .await?;
+
+            if simulation.valid {
+                simulation.execution_payload = verify_execution_payload(&chain, &block).await;
+                simulation.valid = !matches!(
+                    simulation.execution_payload,
+                    SimulatedPayloadStatus::Invalid { .. }
+                        | SimulatedPayloadStatus::InvalidBlockHash { .. }
+                );
+            }
+            Ok(simulation)
+        }
+        UnsignedBlock::Blinded(block) => {
+            warp_utils::task::blocking_task(move || {
+                let mut simulation = simulate_state_transition(&chain, &block)?;
+                if block.body().execution_payload().is_ok() {
+                    simulation.execution_payload = SimulatedPayloadStatus::Unavailable;
+                }
+                Ok(simulation)
+            })
+            .await
+        }
+    }
+}
+
+/// Load the state of the block's parent, advanced to the slot of the block.
+fn load_pre_state<T: BeaconChainTypes, Payload: AbstractExecPayload<T::EthSpec>>(
+    chain: &BeaconChain<T>,
+    block: &BeaconBlock<T::EthSpec, Payload>,
+) -> Result<BeaconState<T::EthSpec>, warp::Rejection> {
+    let parent_root = block.parent_root();
+    let parent_block = chain
+        .get_blinded_block(&parent_root)
+        .and_then(|maybe_block| {
+            maybe_block.ok_or(BeaconChainError::MissingBeaconBlock(parent_root))
+        })
+        .map_err(|e| custom_not_found(format!("parent block is not available: {:?}", e)))?;
+
+    if block.slot() <= parent_block.slot() {
+        return Err(custom_bad_request(format!(
+            "block slot {} must be greater than parent slot {}",
+            block.slot(),
+            parent_block.slot()
+        )));
+    }
+
+    let (state_root, mut state) = chain
+        .store
+        .get_advanced_hot_state(parent_root, block.slot(), parent_block.state_root())
+        .map_err(BeaconChainError::from)
+        .and_then(|maybe_state| match maybe_state {
+            Some(state) => Ok(state),
+            None => chain
+                .get_state(&parent_block.state_root(), Some(parent_block.slot()))?
+                .map(|state| (parent_block.state_root(), state))
+                .ok_or(BeaconChainError::MissingBeaconState(
+                    parent_block.state_root(),
+                )),
+        })
+        .map_err(|e| custom_not_found(format!("parent state is not available: {:?}", e)))?;
+
+    complete_state_advance(&mut state, Some(state_root), block.slot(), &chain.spec)
+        .map_err(|e| beacon_chain_error(e.into()))?;
+
+    Ok(state)
+}
+
+fn simulate_state_transition<T: BeaconChainTypes, Payload: AbstractExecPayload<T::EthSpec>>(
+    chain: &BeaconChain<T>,
+    block: &BeaconBlock<T::EthSpec, Payload>,
+) -> Result<BlockSimulation, warp::Rejection> {
+    let mut state = load_pre_state(chain, block)?;
+
+    let mut simulation = BlockSimulation {
+        slot: block.slot(),
+        parent_root: block.parent_root(),
+        proposer_index: block.proposer_index(),
+        valid: false,
+        errors: vec![],
+        post_state_root: None,
+        proposer_reward: None,
+        execution_payload: SimulatedPayloadStatus::NotApplicable,
+    };
+
+    // Rewards are computed from the pre-state, so this must happen prior to block processing.
+    match chain.compute_beacon_block_reward(block.to_ref(), &mut state.clone()) {
+        Ok(reward) => simulation.proposer_reward = Some(reward),
+        Err(e) => debug!(
+            chain.log,
+            "Unable to compute simulated block reward";
+            "error" => ?e,
+            "slot" => block.slot(),
+        ),
+    }
+
+    let signed_block = SignedBeaconBlock::from_block(block.clone(), Signature::empty());
+    let mut ctxt = ConsensusContext::new(block.slot());
+    if let Err(e) = per_block_processing(
+        &mut state,
+        &signed_block,
+        BlockSignatureStrategy::NoVerification,
+        VerifyBlockRoot::True,
+        &mut ctxt,
+        &chain.spec,
+    ) {
+        simulation.errors.push(format!("{:?}", e));
+        return Ok(simulation);
+    }
+
+    let post_state_root = state
+        .update_tree_hash_cache()
+        .map_err(|e| beacon_chain_error(e.into()))?;
+    simulation.post_state_root = Some(post_state_root);
+    if post_state_root != block.state_root() {
+        simulation.errors.push(format!(
+            "StateRootMismatch {{ block: {:?}, local: {:?} }}",
+            block.state_root(),
+            post_state_root
+        ));
+    }
+
+    simulation.valid = simulation.errors.is_empty();
+    if simulation.valid && block.body().execution_payload().is_ok() {
+        simulation.execution_payload = SimulatedPayloadStatus::NotChecked;
+    }
+
+    Ok(simulation)
+}
+
+/// Send the payload to the execution engine with `engine_newPayload`.
+///
+/// Since the beacon node never calls `engine_forkchoiceUpdated` for the simulated block, the
+/// payload is not made canonical on the execution layer.
+async fn verify_execution_payload<T: BeaconChainTypes>(
+    chain: &BeaconChain<T>,
+    block: &BeaconBlock<T::EthSpec, FullPayload<T::EthSpec>>,
+) -> SimulatedPayloadStatus {
+    let Ok(payload) = block.body().execution_payload() else {
+        return SimulatedPayloadStatus::NotApplicable;
+    };
+    // Blocks prior to the merge transition contain a default payload.
+    if payload.block_hash() == ExecutionBlockHash::zero() {
+        return SimulatedPayloadStatus::NotApplicable;
+    }
+    let Some(execution_layer) = chain.execution_layer.as_ref() else {
+        return SimulatedPayloadStatus::NotChecked;
+    };
+
+    let new_payload_request = match NewPayloadRequest::try_from(block.to_ref()) {
+        Ok(request) => request,
+        Err(e) => {
+            return SimulatedPayloadStatus::Error {
+                error: format!("{:?}", e),
+            }
+        }
+    };
+    if let Err(e) = new_payload_request.perform_optimistic_sync_verifications() {
+        return SimulatedPayloadStatus::InvalidBlockHash {
+            validation_error: Some(format!("{:?}", e)),
+        };
+    }
+
+    match execution_layer
+        .notify_new_payload(new_payload_request)
+        .await
+    {
+        Ok(PayloadStatus::Valid) => SimulatedPayloadStatus::Valid,
+        Ok(PayloadStatus::Invalid {
+            latest_valid_hash,
+            validation_error,
+        }) => SimulatedPayloadStatus::Invalid {
+            latest_valid_hash,
+            validation_error,
+        },
+        Ok(PayloadStatus::Syncing) => SimulatedPayloadStatus::Syncing,
+        Ok(PayloadStatus::Accepted) => SimulatedPayloadStatus::Accepted,
+        Ok(PayloadStatus::InvalidBlockHash { validation_error }) => {
+            SimulatedPayloadStatus::InvalidBlockHash { validation_error }
+        }
+        Err(e) => SimulatedPayloadStatus::Error {
+            error: format!("{:?}", e),
+        },
+    }
+}
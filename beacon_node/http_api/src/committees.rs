@@ -0,0 +1,149 @@
+//! Helpers for the `GET beacon/states/{state_id}/committees` endpoint, and for serving the
+//! committees and duties of finalized epochs from the compact shufflings stored in the freezer.
+
+use crate::state_id::StateId;
+use beacon_chain::{BeaconChain, BeaconChainError, BeaconChainTypes};
+use eth2::types::{self as api_types, StateId as CoreStateId};
+use store::CompactEpochShuffling;
+use types::{CommitteeCache, Epoch, EthSpec, Unsigned};
+
+/// Load the compact shuffling for `epoch` from the freezer, if `epoch` is finalized and the
+/// shuffling was stored during migration.
+///
+/// This allows committees and duties of historic epochs to be computed without loading a
+/// `BeaconState`, which is slow and may not be possible if historic states have been pruned.
+pub fn finalized_epoch_shuffling<T: BeaconChainTypes>(
+    chain: &BeaconChain<T>,
+    epoch: Epoch,
+) -> Result<Option<CompactEpochShuffling>, warp::Rejection> {
+    if epoch.start_slot(T::EthSpec::slots_per_epoch()) >= chain.store.get_split_slot() {
+        return Ok(None);
+    }
+    chain
+        .store
+        .get_cold_epoch_shuffling(epoch)
+        .map_err(BeaconChainError::DBError)
+        .map_err(warp_utils::reject::beacon_chain_error)
+}
+
+/// Attempt to serve a committees request for a state in the freezer using the compact shuffling
+/// of the requested epoch, returning `None` if the request must be served by loading the state.
+///
+/// This works even if historic states have been pruned.
+pub fn committees_from_finalized_shuffling<T: BeaconChainTypes>(
+    state_id: &StateId,
+    query: &api_types::CommitteesQuery,
+    chain: &BeaconChain<T>,
+) -> Result<Option<Vec<api_types::CommitteeData>>, warp::Rejection> {
+    // Recent states, including the finalized state, are cheap to load from the hot database.
+    let state_slot = match &state_id.0 {
+        CoreStateId::Genesis => chain.spec.genesis_slot,
+        CoreStateId::Slot(slot) => *slot,
+        CoreStateId::Root(root) => {
+            let Some(slot) = chain
+                .store
+                .load_cold_state_slot(root)
+                .map_err(BeaconChainError::DBError)
+                .map_err(warp_utils::reject::beacon_chain_error)?
+            else {
+                return Ok(None);
+            };
+            slot
+        }
+        CoreStateId::Head | CoreStateId::Finalized | CoreStateId::Justified => return Ok(None),
+    };
+    if state_slot >= chain.store.get_split_slot() {
+        return Ok(None);
+    }
+
+    let state_epoch = state_slot.epoch(T::EthSpec::slots_per_epoch());
+    let epoch = query.epoch.unwrap_or(state_epoch);
+
+    // Reject epochs for which the state could not compute a shuffling, exactly as if the state
+    // had been loaded.
+    let min_randao_epoch = (state_epoch + 1)
+        .saturating_sub(<T::EthSpec as EthSpec>::EpochsPerHistoricalVector::to_u64());
+    let reqd_randao_epoch = epoch
+        .saturating_sub(chain.spec.min_seed_lookahead)
+        .saturating_sub(1u64);
+    if reqd_randao_epoch < min_randao_epoch || epoch > state_epoch + 1 {
+        return Err(epoch_out_of_bounds_error::<T::EthSpec>(epoch, state_epoch));
+    }
+
+    let Some(shuffling) = finalized_epoch_shuffling(chain, epoch)? else {
+        return Ok(None);
+    };
+    let committee_cache = shuffling
+        .committee_cache::<T::EthSpec>(&chain.spec)
+        .map_err(BeaconChainError::from)
+        .map_err(warp_utils::reject::beacon_chain_error)?;
+
+    committee_data::<T::EthSpec>(&committee_cache, epoch, query).map(Some)
+}
+
+/// The error returned when the committees for `epoch` can't be computed from a state in
+/// `state_epoch`.
+pub fn epoch_out_of_bounds_error<E: EthSpec>(epoch: Epoch, state_epoch: Epoch) -> warp::Rejection {
+    if epoch < state_epoch {
+        let max_sprp = E::slots_per_historical_root() as u64;
+        let first_subsequent_restore_point_slot =
+            ((epoch.start_slot(E::slots_per_epoch()) / max_sprp) + 1) * max_sprp;
+        warp_utils::reject::custom_bad_request(format!(
+            "epoch out of bounds, try state at slot {}",
+            first_subsequent_restore_point_slot,
+        ))
+    } else {
+        warp_utils::reject::custom_bad_request("epoch out of bounds, too far in future".into())
+    }
+}
+
+/// Read the committees selected by `query` from the `committee_cache` for `epoch`.
+pub fn committee_data<E: EthSpec>(
+    committee_cache: &CommitteeCache,
+    epoch: Epoch,
+    query: &api_types::CommitteesQuery,
+) -> Result<Vec<api_types::CommitteeData>, warp::Rejection> {
+    // Use either the supplied slot or all slots in the epoch.
+    let slots = query
+        .slot
+        .map(|slot| vec![slot])
+        .unwrap_or_else(|| epoch.slot_iter(E::slots_per_epoch()).collect());
+
+    // Use either the supplied committee index or all available indices.
+    let indices = query
+        .index
+        .map(|index| vec![index])
+        .unwrap_or_else(|| (0..committee_cache.committees_per_slot()).collect());
+
+    let mut response = Vec::with_capacity(slots.len() * indices.len());
+
+    for slot in slots {
+        // It is not acceptable to query with a slot that is not within the
+        // specified epoch.
+        if slot.epoch(E::slots_per_epoch()) != epoch {
+            return Err(warp_utils::reject::custom_bad_request(format!(
+                "{} is not in epoch {}",
+                slot, epoch
+            )));
+        }
+
+        for &index in &indices {
+            let committee = committee_cache
+                .get_beacon_committee(slot, index)
+                .ok_or_else(|| {
+                    warp_utils::reject::custom_bad_request(format!(
+                        "committee index {} does not exist in epoch {}",
+                        index, epoch
+                    ))
+                })?;
+
+            response.push(api_types::CommitteeData {
+                index,
+                slot,
+                validators: committee.committee.iter().map(|i| *i as u64).collect(),
+            });
+        }
+    }
+
+    Ok(response)
+}
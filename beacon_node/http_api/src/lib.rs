@@ -9,17 +9,22 @@ mod attestation_performance;
 mod attester_duties;
 mod block_id;
 mod block_packing_efficiency;
+mod block_range;
 mod block_rewards;
+mod block_simulation;
 mod build_block_contents;
 mod builder_states;
+mod committees;
 mod database;
 mod light_client;
 mod metrics;
+mod packing_reports;
 mod produce_block;
 mod proposer_duties;
 mod publish_attestations;
 mod publish_blocks;
 mod standard_block_rewards;
+mod state_fields;
 mod state_id;
 mod sync_committee_rewards;
 mod sync_committees;
@@ -28,6 +33,7 @@ pub mod test_utils;
 mod ui;
 mod validator;
 mod validator_inclusion;
+mod validator_income;
 mod validators;
 mod version;
 
@@ -35,22 +41,29 @@ use crate::light_client::{get_light_client_bootstrap, get_light_client_updates};
 use crate::produce_block::{produce_blinded_block_v2, produce_block_v2, produce_block_v3};
 use crate::version::fork_versioned_response;
 use beacon_chain::{
-    attestation_verification::VerifiedAttestation, observed_operations::ObservationOutcome,
-    validator_monitor::timestamp_now, AttestationError as AttnError, BeaconChain, BeaconChainError,
-    BeaconChainTypes, WhenSlotSkipped,
+    attestation_verification::VerifiedAttestation, events::IdentifiedEvent,
+    observed_operations::ObservationOutcome, validator_monitor::timestamp_now,
+    AttestationError as AttnError, BeaconChain, BeaconChainError, BeaconChainTypes,
+    WhenSlotSkipped,
 };
 use beacon_processor::{work_reprocessing_queue::ReprocessQueueMessage, BeaconProcessorSend};
 pub use block_id::BlockId;
 use builder_states::get_next_withdrawals;
 use bytes::Bytes;
+use committees::epoch_out_of_bounds_error;
 use directory::DEFAULT_ROOT_DIR;
 use eth2::types::{
     self as api_types, BroadcastValidation, EndpointVersion, ForkChoice, ForkChoiceNode,
     LightClientUpdatesQuery, PublishBlockRequest, ValidatorBalancesRequestBody, ValidatorId,
     ValidatorStatus, ValidatorsRequestBody,
 };
-use eth2::{CONSENSUS_VERSION_HEADER, CONTENT_TYPE_HEADER, SSZ_CONTENT_TYPE_HEADER};
-use lighthouse_network::{types::SyncState, EnrExt, NetworkGlobals, PeerId, PubsubMessage};
+use eth2::{
+    CONSENSUS_VERSION_HEADER, CONTENT_TYPE_HEADER, EXECUTION_PAYLOAD_BLINDED_HEADER,
+    LAST_EVENT_ID_HEADER, SSZ_CONTENT_TYPE_HEADER,
+};
+use lighthouse_network::{
+    types::SyncState, EnrExt, NetworkGlobals, PeerId, PeerStats, PubsubMessage,
+};
 use lighthouse_version::version_with_platform;
 use logging::SSELoggingComponents;
 use network::{NetworkMessage, NetworkSenders, ValidatorSubscriptionMessage};
@@ -99,7 +112,9 @@ use warp::hyper::Body;
 use warp::sse::Event;
 use warp::Reply;
 use warp::{http::Response, Filter, Rejection};
-use warp_utils::{query::multi_key_query, reject::convert_rejection, uor::UnifyingOrFilter};
+use warp_utils::{
+    auth::AccessControl, query::multi_key_query, reject::convert_rejection, uor::UnifyingOrFilter,
+};
 
 const API_PREFIX: &str = "eth";
 
@@ -148,6 +163,8 @@ pub struct Config {
     pub tls_config: Option<TlsConfig>,
     pub data_dir: PathBuf,
     pub sse_capacity_multiplier: usize,
+    pub sse_replay_buffer_size: usize,
+    pub access_control_file: Option<PathBuf>,
     pub enable_beacon_processor: bool,
     #[serde(with = "eth2::types::serde_status_code")]
     pub duplicate_block_status_code: StatusCode,
@@ -165,6 +182,8 @@ impl Default for Config {
             tls_config: None,
             data_dir: PathBuf::from(DEFAULT_ROOT_DIR),
             sse_capacity_multiplier: 1,
+            sse_replay_buffer_size: 0,
+            access_control_file: None,
             enable_beacon_processor: true,
             duplicate_block_status_code: StatusCode::ACCEPTED,
             enable_light_client_server: false,
@@ -330,7 +349,7 @@ pub fn serve<T: BeaconChainTypes>(
     let cors_builder = {
         let builder = warp::cors()
             .allow_methods(vec!["GET", "POST"])
-            .allow_headers(vec!["Content-Type"]);
+            .allow_headers(vec!["Content-Type", "Authorization"]);
 
         warp_utils::cors::set_builder_origins(
             builder,
@@ -347,6 +366,22 @@ pub fn serve<T: BeaconChainTypes>(
         ));
     }
 
+    // Load the optional access control list, which restricts routes to authenticated clients.
+    let access_control = config
+        .access_control_file
+        .as_ref()
+        .map(|path| AccessControl::from_file(path).map(Arc::new))
+        .transpose()
+        .map_err(Error::Other)?;
+    if let Some(path) = &config.access_control_file {
+        info!(
+            log,
+            "HTTP API access control enabled";
+            "file" => ?path,
+        );
+    }
+    let access_control_filter = warp_utils::auth::access_control_filter(access_control);
+
     // Create a filter that extracts the endpoint version.
     let any_version = warp::path(API_PREFIX).and(warp::path::param::<EndpointVersion>().or_else(
         |_| async move {
@@ -853,6 +888,17 @@ pub fn serve<T: BeaconChainTypes>(
              chain: Arc<BeaconChain<T>>,
              query: api_types::CommitteesQuery| {
                 task_spawner.blocking_json_task(Priority::P1, move || {
+                    // Avoid loading historic states if the freezer has the shuffling.
+                    if let Some(data) =
+                        committees::committees_from_finalized_shuffling(&state_id, &query, &chain)?
+                    {
+                        return Ok(api_types::ExecutionOptimisticFinalizedResponse {
+                            data,
+                            execution_optimistic: Some(false),
+                            finalized: Some(true),
+                        });
+                    }
+
                     let (data, execution_optimistic, finalized) = state_id
                         .map_state_and_execution_optimistic_and_finalized(
                             &chain,
@@ -913,30 +959,10 @@ pub fn serve<T: BeaconChainTypes>(
                                         .map_err(|e| {
                                             match e {
                                                 BeaconStateError::EpochOutOfBounds => {
-                                                    let max_sprp =
-                                                        T::EthSpec::slots_per_historical_root()
-                                                            as u64;
-                                                    let first_subsequent_restore_point_slot =
-                                                        ((epoch.start_slot(
-                                                            T::EthSpec::slots_per_epoch(),
-                                                        ) / max_sprp)
-                                                            + 1)
-                                                            * max_sprp;
-                                                    if epoch < current_epoch {
-                                                        warp_utils::reject::custom_bad_request(
-                                                            format!(
-                                                                "epoch out of bounds, \
-                                                                 try state at slot {}",
-                                                                first_subsequent_restore_point_slot,
-                                                            ),
-                                                        )
-                                                    } else {
-                                                        warp_utils::reject::custom_bad_request(
-                                                            "epoch out of bounds, \
-                                                             too far in future"
-                                                                .into(),
-                                                        )
-                                                    }
+                                                    epoch_out_of_bounds_error::<T::EthSpec>(
+                                                        epoch,
+                                                        current_epoch,
+                                                    )
                                                 }
                                                 _ => {
                                                     warp_utils::reject::beacon_chain_error(e.into())
@@ -964,50 +990,11 @@ pub fn serve<T: BeaconChainTypes>(
                                     possibly_built_cache
                                 };
 
-                                // Use either the supplied slot or all slots in the epoch.
-                                let slots =
-                                    query.slot.map(|slot| vec![slot]).unwrap_or_else(|| {
-                                        epoch.slot_iter(T::EthSpec::slots_per_epoch()).collect()
-                                    });
-
-                                // Use either the supplied committee index or all available indices.
-                                let indices =
-                                    query.index.map(|index| vec![index]).unwrap_or_else(|| {
-                                        (0..committee_cache.committees_per_slot()).collect()
-                                    });
-
-                                let mut response = Vec::with_capacity(slots.len() * indices.len());
-
-                                for slot in slots {
-                                    // It is not acceptable to query with a slot that is not within the
-                                    // specified epoch.
-                                    if slot.epoch(T::EthSpec::slots_per_epoch()) != epoch {
-                                        return Err(warp_utils::reject::custom_bad_request(
-                                            format!("{} is not in epoch {}", slot, epoch),
-                                        ));
-                                    }
-
-                                    for &index in &indices {
-                                        let committee = committee_cache
-                                            .get_beacon_committee(slot, index)
-                                            .ok_or_else(|| {
-                                                warp_utils::reject::custom_bad_request(format!(
-                                                    "committee index {} does not exist in epoch {}",
-                                                    index, epoch
-                                                ))
-                                            })?;
-
-                                        response.push(api_types::CommitteeData {
-                                            index,
-                                            slot,
-                                            validators: committee
-                                                .committee
-                                                .iter()
-                                                .map(|i| *i as u64)
-                                                .collect(),
-                                        });
-                                    }
-                                }
+                                let response = committees::committee_data::<T::EthSpec>(
+                                    &committee_cache,
+                                    epoch,
+                                    &query,
+                                )?;
 
                                 Ok((response, execution_optimistic, finalized))
                             },
@@ -4185,7 +4172,7 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path("connected"))
         .and(warp::path::end())
         .and(task_spawner_filter.clone())
-        .and(network_globals)
+        .and(network_globals.clone())
         .then(
             |task_spawner: TaskSpawner<T::EthSpec>,
              network_globals: Arc<NetworkGlobals<T::EthSpec>>| {
@@ -4203,6 +4190,99 @@ pub fn serve<T: BeaconChainTypes>(
             },
         );
 
+    // GET lighthouse/peers/{peer_id}/stats
+    let get_lighthouse_peer_stats = warp::path("lighthouse")
+        .and(warp::path("peers"))
+        .and(warp::path::param::<String>())
+        .and(warp::path("stats"))
+        .and(warp::path::end())
+        .and(task_spawner_filter.clone())
+        .and(network_globals.clone())
+        .then(
+            |requested_peer_id: String,
+             task_spawner: TaskSpawner<T::EthSpec>,
+             network_globals: Arc<NetworkGlobals<T::EthSpec>>| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
+                    let peer_id = PeerId::from_bytes(
+                        &bs58::decode(requested_peer_id.as_str())
+                            .into_vec()
+                            .map_err(|e| {
+                                warp_utils::reject::custom_bad_request(format!(
+                                    "invalid peer id: {}",
+                                    e
+                                ))
+                            })?,
+                    )
+                    .map_err(|_| {
+                        warp_utils::reject::custom_bad_request("invalid peer id.".to_string())
+                    })?;
+
+                    let peers = network_globals.peers.read();
+                    let peer_info = peers.peer_info(&peer_id).ok_or_else(|| {
+                        warp_utils::reject::custom_not_found("peer not found.".to_string())
+                    })?;
+                    let stats = PeerStats {
+                        rpc: network_globals.rpc_stats.peer_stats(&peer_id),
+                        gossip: peer_info.gossip_stats().clone(),
+                    };
+                    Ok(api_types::GenericResponse::from(
+                        eth2::lighthouse::PeerStatsResponse {
+                            peer_id: peer_id.to_string(),
+                            client: peer_info.client().kind.as_ref().to_string(),
+                            useful_gossip_ratio: stats.useful_gossip_ratio(),
+                            stats,
+                        },
+                    ))
+                })
+            },
+        );
+
+    // GET lighthouse/peers/stats
+    let get_lighthouse_peers_stats = warp::path("lighthouse")
+        .and(warp::path("peers"))
+        .and(warp::path("stats"))
+        .and(warp::path::end())
+        .and(warp::query::<eth2::lighthouse::PeerStatsQuery>())
+        .and(task_spawner_filter.clone())
+        .and(network_globals)
+        .then(
+            |query: eth2::lighthouse::PeerStatsQuery,
+             task_spawner: TaskSpawner<T::EthSpec>,
+             network_globals: Arc<NetworkGlobals<T::EthSpec>>| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
+                    let mut summaries = network_globals
+                        .peers
+                        .read()
+                        .peers()
+                        .map(|(peer_id, peer_info)| {
+                            let stats = PeerStats {
+                                rpc: network_globals.rpc_stats.peer_stats(peer_id),
+                                gossip: peer_info.gossip_stats().clone(),
+                            };
+                            eth2::lighthouse::PeerStatsSummary {
+                                peer_id: peer_id.to_string(),
+                                client: peer_info.client().kind.as_ref().to_string(),
+                                bytes_in: stats.bytes_in(),
+                                bytes_out: stats.bytes_out(),
+                                rpc_errors: stats.rpc_errors(),
+                                duplicates_in: stats.duplicates_in(),
+                                useful_gossip_ratio: stats.useful_gossip_ratio(),
+                            }
+                        })
+                        .collect::<Vec<_>>();
+                    summaries.sort_unstable_by_key(|summary| {
+                        std::cmp::Reverse(query.sort_by.key(summary))
+                    });
+                    summaries.truncate(
+                        query
+                            .limit
+                            .unwrap_or(eth2::lighthouse::DEFAULT_PEER_STATS_LIMIT),
+                    );
+                    Ok(api_types::GenericResponse::from(summaries))
+                })
+            },
+        );
+
     // GET lighthouse/proto_array
     let get_lighthouse_proto_array = warp::path("lighthouse")
         .and(warp::path("proto_array"))
@@ -4295,6 +4375,50 @@ pub fn serve<T: BeaconChainTypes>(
             },
         );
 
+    // GET lighthouse/eth1/engines
+    let get_lighthouse_eth1_engines = warp::path("lighthouse")
+        .and(warp::path("eth1"))
+        .and(warp::path("engines"))
+        .and(warp::path::end())
+        .and(task_spawner_filter.clone())
+        .and(chain_filter.clone())
+        .then(
+            |task_spawner: TaskSpawner<T::EthSpec>, chain: Arc<BeaconChain<T>>| {
+                task_spawner.spawn_async_with_rejection(Priority::P1, async move {
+                    let execution_layer = chain.execution_layer.as_ref().ok_or_else(|| {
+                        warp_utils::reject::custom_not_found(
+                            "No execution layer is configured".to_string(),
+                        )
+                    })?;
+                    Ok::<_, warp::Rejection>(warp::reply::json(&api_types::GenericResponse::from(
+                        execution_layer.engine_statuses().await,
+                    )))
+                })
+            },
+        );
+
+    // GET lighthouse/eth1/relays
+    let get_lighthouse_eth1_relays = warp::path("lighthouse")
+        .and(warp::path("eth1"))
+        .and(warp::path("relays"))
+        .and(warp::path::end())
+        .and(task_spawner_filter.clone())
+        .and(chain_filter.clone())
+        .then(
+            |task_spawner: TaskSpawner<T::EthSpec>, chain: Arc<BeaconChain<T>>| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
+                    let execution_layer = chain.execution_layer.as_ref().ok_or_else(|| {
+                        warp_utils::reject::custom_not_found(
+                            "No execution layer is configured".to_string(),
+                        )
+                    })?;
+                    Ok(api_types::GenericResponse::from(
+                        execution_layer.relay_statuses(),
+                    ))
+                })
+            },
+        );
+
     // GET lighthouse/eth1/block_cache
     let get_lighthouse_eth1_block_cache = warp::path("lighthouse")
         .and(warp::path("eth1"))
@@ -4376,6 +4500,176 @@ pub fn serve<T: BeaconChainTypes>(
             },
         );
 
+    let packing_reports_path = warp::path("lighthouse")
+        .and(warp::path("operation_pool"))
+        .and(warp::path("packing_reports"));
+
+    // GET lighthouse/operation_pool/packing_reports
+    let get_lighthouse_operation_pool_packing_reports = packing_reports_path
+        .and(warp::path::end())
+        .and(task_spawner_filter.clone())
+        .and(chain_filter.clone())
+        .then(
+            |task_spawner: TaskSpawner<T::EthSpec>, chain: Arc<BeaconChain<T>>| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
+                    Ok(api_types::GenericResponse::from(
+                        packing_reports::summaries(chain),
+                    ))
+                })
+            },
+        );
+
+    // GET lighthouse/operation_pool/packing_reports/{slot}
+    let get_lighthouse_operation_pool_packing_report = packing_reports_path
+        .and(warp::path::param::<Slot>())
+        .and(warp::path::end())
+        .and(task_spawner_filter.clone())
+        .and(chain_filter.clone())
+        .then(
+            |slot: Slot, task_spawner: TaskSpawner<T::EthSpec>, chain: Arc<BeaconChain<T>>| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
+                    packing_reports::report(slot, chain).map(api_types::GenericResponse::from)
+                })
+            },
+        );
+
+    // POST lighthouse/beacon/blocks/simulate
+    let post_lighthouse_beacon_blocks_simulate = warp::path("lighthouse")
+        .and(warp::path("beacon"))
+        .and(warp::path("blocks"))
+        .and(warp::path("simulate"))
+        .and(warp::path::end())
+        .and(warp::header::optional::<String>(CONTENT_TYPE_HEADER))
+        .and(warp::header::optional::<bool>(
+            EXECUTION_PAYLOAD_BLINDED_HEADER,
+        ))
+        .and(warp::body::bytes())
+        .and(task_spawner_filter.clone())
+        .and(chain_filter.clone())
+        .then(
+            |content_type: Option<String>,
+             blinded: Option<bool>,
+             block_bytes: Bytes,
+             task_spawner: TaskSpawner<T::EthSpec>,
+             chain: Arc<BeaconChain<T>>| {
+                task_spawner.spawn_async_with_rejection(Priority::P1, async move {
+                    let block = block_simulation::UnsignedBlock::from_request_body(
+                        &block_bytes,
+                        content_type.as_deref(),
+                        blinded.unwrap_or(false),
+                        &chain.spec,
+                    )?;
+                    let simulation = block_simulation::simulate_block(block, chain).await?;
+                    Ok(warp::reply::json(&simulation).into_response())
+                })
+            },
+        );
+
+    // GET lighthouse/beacon/blocks/range
+    let get_lighthouse_beacon_blocks_range = warp::path("lighthouse")
+        .and(warp::path("beacon"))
+        .and(warp::path("blocks"))
+        .and(warp::path("range"))
+        .and(warp::path::end())
+        .and(warp::query::<eth2::lighthouse::BlockRangeQuery>())
+        .and(task_spawner_filter.clone())
+        .and(chain_filter.clone())
+        .then(
+            |query, task_spawner: TaskSpawner<T::EthSpec>, chain: Arc<BeaconChain<T>>| {
+                task_spawner.blocking_response_task(Priority::P1, move || {
+                    block_range::get_block_range(query, chain)
+                })
+            },
+        );
+
+    // GET lighthouse/beacon/data_columns/{block_id}
+    let get_lighthouse_beacon_data_columns = warp::path("lighthouse")
+        .and(warp::path("beacon"))
+        .and(warp::path("data_columns"))
+        .and(block_id_or_err)
+        .and(warp::path::end())
+        .and(task_spawner_filter.clone())
+        .and(chain_filter.clone())
+        .and(network_globals.clone())
+        .then(
+            |block_id: BlockId,
+             task_spawner: TaskSpawner<T::EthSpec>,
+             chain: Arc<BeaconChain<T>>,
+             network_globals: Arc<NetworkGlobals<T::EthSpec>>| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
+                    let (block_root, execution_optimistic, finalized) = block_id.root(&chain)?;
+                    let block =
+                        BlockId::blinded_block_by_root(&block_root, &chain)?.ok_or_else(|| {
+                            warp_utils::reject::custom_not_found(format!(
+                                "beacon block with root {}",
+                                block_root
+                            ))
+                        })?;
+
+                    let data_columns = BlockId::stored_data_columns(block_root, &chain)?;
+                    let number_of_columns = chain.spec.number_of_columns;
+                    let custody = eth2::lighthouse::DataColumnCustody {
+                        number_of_columns: number_of_columns as u64,
+                        custody_columns: network_globals.sampling_columns.clone(),
+                        stored_columns: data_columns.iter().map(|column| column.index).collect(),
+                        can_reconstruct_blobs: data_columns.len() >= number_of_columns / 2,
+                    };
+
+                    Ok(api_types::ExecutionOptimisticFinalizedResponse {
+                        data: eth2::lighthouse::StoredDataColumns {
+                            block_root,
+                            slot: block.slot(),
+                            custody,
+                            data_columns,
+                        },
+                        execution_optimistic: Some(execution_optimistic),
+                        finalized: Some(finalized),
+                    })
+                })
+            },
+        );
+
+    // POST lighthouse/beacon/states/{state_id}/fields
+    let post_lighthouse_beacon_states_fields = warp::path("lighthouse")
+        .and(warp::path("beacon"))
+        .and(warp::path("states"))
+        .and(warp::path::param::<StateId>().or_else(|_| async {
+            Err(warp_utils::reject::custom_bad_request(
+                "Invalid state ID".to_string(),
+            ))
+        }))
+        .and(warp::path("fields"))
+        .and(warp::path::end())
+        .and(warp_utils::json::json())
+        .and(task_spawner_filter.clone())
+        .and(chain_filter.clone())
+        .then(
+            |state_id: StateId,
+             request: eth2::lighthouse::StateFieldsRequest,
+             task_spawner: TaskSpawner<T::EthSpec>,
+             chain: Arc<BeaconChain<T>>| {
+                task_spawner.blocking_json_task(Priority::P1, move || {
+                    let (data, execution_optimistic, finalized) = state_id
+                        .map_state_and_execution_optimistic_and_finalized(
+                            &chain,
+                            |state, execution_optimistic, finalized| {
+                                Ok((
+                                    state_fields::query_state_fields(state, &request.paths)?,
+                                    execution_optimistic,
+                                    finalized,
+                                ))
+                            },
+                        )?;
+
+                    Ok(api_types::ExecutionOptimisticFinalizedResponse {
+                        data,
+                        execution_optimistic: Some(execution_optimistic),
+                        finalized: Some(finalized),
+                    })
+                })
+            },
+        );
+
     // POST lighthouse/database/reconstruct
     let post_lighthouse_database_reconstruct = database_path
         .and(warp::path("reconstruct"))
@@ -4444,6 +4738,22 @@ pub fn serve<T: BeaconChainTypes>(
             },
         );
 
+    // POST lighthouse/analysis/validator_income
+    let post_lighthouse_validator_income = warp::path("lighthouse")
+        .and(warp::path("analysis"))
+        .and(warp::path("validator_income"))
+        .and(warp_utils::json::json())
+        .and(warp::path::end())
+        .and(task_spawner_filter.clone())
+        .and(chain_filter.clone())
+        .then(
+            |request, task_spawner: TaskSpawner<T::EthSpec>, chain: Arc<BeaconChain<T>>| {
+                task_spawner.blocking_response_task(Priority::P1, move || {
+                    validator_income::get_validator_income(request, chain)
+                })
+            },
+        );
+
     // GET lighthouse/analysis/block_packing_efficiency
     let get_lighthouse_block_packing_efficiency = warp::path("lighthouse")
         .and(warp::path("analysis"))
@@ -4483,80 +4793,31 @@ pub fn serve<T: BeaconChainTypes>(
         .and(warp::path("events"))
         .and(warp::path::end())
         .and(multi_key_query::<api_types::EventQuery>())
+        .and(warp::header::optional::<u64>(LAST_EVENT_ID_HEADER))
         .and(task_spawner_filter.clone())
         .and(chain_filter)
         .then(
             |topics_res: Result<api_types::EventQuery, warp::Rejection>,
+             last_event_id: Option<u64>,
              task_spawner: TaskSpawner<T::EthSpec>,
              chain: Arc<BeaconChain<T>>| {
                 task_spawner.blocking_response_task(Priority::P0, move || {
                     let topics = topics_res?;
                     // for each topic subscribed spawn a new subscription
                     let mut receivers = Vec::with_capacity(topics.topics.len());
+                    let mut missed_events = vec![];
 
                     if let Some(event_handler) = chain.event_handler.as_ref() {
                         for topic in topics.topics {
-                            let receiver = match topic {
-                                api_types::EventTopic::Head => event_handler.subscribe_head(),
-                                api_types::EventTopic::Block => event_handler.subscribe_block(),
-                                api_types::EventTopic::BlobSidecar => {
-                                    event_handler.subscribe_blob_sidecar()
-                                }
-                                api_types::EventTopic::Attestation => {
-                                    event_handler.subscribe_attestation()
-                                }
-                                api_types::EventTopic::VoluntaryExit => {
-                                    event_handler.subscribe_exit()
-                                }
-                                api_types::EventTopic::FinalizedCheckpoint => {
-                                    event_handler.subscribe_finalized()
-                                }
-                                api_types::EventTopic::ChainReorg => {
-                                    event_handler.subscribe_reorgs()
-                                }
-                                api_types::EventTopic::ContributionAndProof => {
-                                    event_handler.subscribe_contributions()
-                                }
-                                api_types::EventTopic::PayloadAttributes => {
-                                    event_handler.subscribe_payload_attributes()
-                                }
-                                api_types::EventTopic::LateHead => {
-                                    event_handler.subscribe_late_head()
-                                }
-                                api_types::EventTopic::LightClientFinalityUpdate => {
-                                    event_handler.subscribe_light_client_finality_update()
-                                }
-                                api_types::EventTopic::LightClientOptimisticUpdate => {
-                                    event_handler.subscribe_light_client_optimistic_update()
-                                }
-                                api_types::EventTopic::BlockReward => {
-                                    event_handler.subscribe_block_reward()
-                                }
-                                api_types::EventTopic::AttesterSlashing => {
-                                    event_handler.subscribe_attester_slashing()
-                                }
-                                api_types::EventTopic::ProposerSlashing => {
-                                    event_handler.subscribe_proposer_slashing()
-                                }
-                                api_types::EventTopic::BlsToExecutionChange => {
-                                    event_handler.subscribe_bls_to_execution_change()
-                                }
-                                api_types::EventTopic::BlockGossip => {
-                                    event_handler.subscribe_block_gossip()
-                                }
-                            };
+                            let (missed, receiver) =
+                                event_handler.subscribe_from(&topic, last_event_id);
+                            missed_events.extend(missed);
 
                             receivers.push(
                                 BroadcastStream::new(receiver)
                                     .map(|msg| {
                                         match msg {
-                                            Ok(data) => Event::default()
-                                                .event(data.topic_name())
-                                                .json_data(data)
-                                                .unwrap_or_else(|e| {
-                                                    Event::default()
-                                                        .comment(format!("error - bad json: {e:?}"))
-                                                }),
+                                            Ok(event) => sse_event(event),
                                             // Do not terminate the stream if the channel fills
                                             // up. Just drop some messages and send a comment to
                                             // the client.
@@ -4576,7 +4837,16 @@ pub fn serve<T: BeaconChainTypes>(
                         ));
                     }
 
-                    let s = futures::stream::select_all(receivers);
+                    // Replay missed events across all topics in the order they were registered,
+                    // before any live events.
+                    missed_events.sort_by_key(|event| event.id);
+                    let replay = futures::stream::iter(
+                        missed_events
+                            .into_iter()
+                            .map(|event| Ok::<_, std::convert::Infallible>(sse_event(event))),
+                    );
+
+                    let s = replay.chain(futures::stream::select_all(receivers));
 
                     Ok(warp::sse::reply(warp::sse::keep_alive().stream(s)))
                 })
@@ -4631,7 +4901,7 @@ pub fn serve<T: BeaconChainTypes>(
 
     // Define the ultimate set of routes that will be provided to the server.
     // Use `uor` rather than `or` in order to simplify types (see `UnifyingOrFilter`).
-    let routes = warp::get()
+    let api_routes = warp::get()
         .and(
             get_beacon_genesis
                 .uor(get_beacon_state_root)
@@ -4683,14 +4953,22 @@ pub fn serve<T: BeaconChainTypes>(
                 .uor(get_lighthouse_nat)
                 .uor(get_lighthouse_peers)
                 .uor(get_lighthouse_peers_connected)
+                .uor(get_lighthouse_peer_stats)
+                .uor(get_lighthouse_peers_stats)
                 .uor(get_lighthouse_proto_array)
                 .uor(get_lighthouse_validator_inclusion_global)
                 .uor(get_lighthouse_validator_inclusion)
                 .uor(get_lighthouse_eth1_syncing)
+                .uor(get_lighthouse_eth1_engines)
+                .uor(get_lighthouse_eth1_relays)
                 .uor(get_lighthouse_eth1_block_cache)
                 .uor(get_lighthouse_eth1_deposit_cache)
                 .uor(get_lighthouse_staking)
                 .uor(get_lighthouse_database_info)
+                .uor(get_lighthouse_operation_pool_packing_reports)
+                .uor(get_lighthouse_operation_pool_packing_report)
+                .uor(get_lighthouse_beacon_blocks_range)
+                .uor(get_lighthouse_beacon_data_columns)
                 .uor(get_lighthouse_block_rewards)
                 .uor(get_lighthouse_attestation_performance)
                 .uor(
@@ -4752,12 +5030,18 @@ pub fn serve<T: BeaconChainTypes>(
                     .uor(post_validator_liveness_epoch)
                     .uor(post_lighthouse_liveness)
                     .uor(post_lighthouse_database_reconstruct)
+                    .uor(post_lighthouse_beacon_blocks_simulate)
+                    .uor(post_lighthouse_beacon_states_fields)
                     .uor(post_lighthouse_block_rewards)
+                    .uor(post_lighthouse_validator_income)
                     .uor(post_lighthouse_ui_validator_metrics)
                     .uor(post_lighthouse_ui_validator_info)
                     .recover(warp_utils::reject::handle_rejection),
             ),
-        )
+        );
+
+    let routes = access_control_filter
+        .and(api_routes)
         .recover(warp_utils::reject::handle_rejection)
         .with(slog_logging(log.clone()))
         .with(prometheus_metrics())
@@ -4799,6 +5083,16 @@ pub fn serve<T: BeaconChainTypes>(
     Ok(http_server)
 }
 
+/// Convert an event into a server-sent event, including its identifier so that the client may
+/// resume the stream with `Last-Event-ID`.
+fn sse_event<E: EthSpec>(event: IdentifiedEvent<E>) -> Event {
+    Event::default()
+        .id(event.id.to_string())
+        .event(event.kind.topic_name())
+        .json_data(event.kind)
+        .unwrap_or_else(|e| Event::default().comment(format!("error - bad json: {e:?}")))
+}
+
 /// Publish a message to the libp2p pubsub network.
 fn publish_pubsub_message<E: EthSpec>(
     network_tx: &UnboundedSender<NetworkMessage<E>>,
@@ -0,0 +1,70 @@
+use beacon_chain::{BeaconChain, BeaconChainTypes};
+use eth2::lighthouse::{
+    ExcludedAttestation, ExcludedOperation, ExclusionReason, PackingReport, PackingReportSummary,
+};
+use std::sync::Arc;
+use types::Slot;
+
+/// Summarise the operations excluded from each recently packed block.
+pub fn summaries<T: BeaconChainTypes>(chain: Arc<BeaconChain<T>>) -> Vec<PackingReportSummary> {
+    chain
+        .op_pool
+        .get_packing_reports()
+        .into_iter()
+        .map(|report| PackingReportSummary::from(&convert_report(report)))
+        .collect()
+}
+
+/// Return the operations excluded from the block packed for `slot`.
+pub fn report<T: BeaconChainTypes>(
+    slot: Slot,
+    chain: Arc<BeaconChain<T>>,
+) -> Result<PackingReport, warp::Rejection> {
+    chain
+        .op_pool
+        .get_packing_report(slot)
+        .map(convert_report)
+        .ok_or_else(|| {
+            warp_utils::reject::custom_not_found(format!("no block packed for slot {}", slot))
+        })
+}
+
+fn convert_report(report: operation_pool::PackingReport) -> PackingReport {
+    let convert_operations = |operations: Vec<operation_pool::ExcludedOperation>| {
+        operations
+            .into_iter()
+            .map(|operation| ExcludedOperation {
+                validator_indices: operation.validator_indices,
+                reason: convert_reason(operation.reason),
+            })
+            .collect()
+    };
+
+    PackingReport {
+        slot: report.slot,
+        parent_root: report.parent_root,
+        attestations: report
+            .attestations
+            .into_iter()
+            .map(|att| ExcludedAttestation {
+                data: att.data,
+                num_attesting_indices: att.num_attesting_indices,
+                fresh_validator_indices: att.fresh_validator_indices,
+                reason: convert_reason(att.reason),
+            })
+            .collect(),
+        proposer_slashings: convert_operations(report.proposer_slashings),
+        attester_slashings: convert_operations(report.attester_slashings),
+        voluntary_exits: convert_operations(report.voluntary_exits),
+        bls_to_execution_changes: convert_operations(report.bls_to_execution_changes),
+    }
+}
+
+fn convert_reason(reason: operation_pool::ExclusionReason) -> ExclusionReason {
+    match reason {
+        operation_pool::ExclusionReason::InvalidAgainstHead => ExclusionReason::InvalidAgainstHead,
+        operation_pool::ExclusionReason::Superseded => ExclusionReason::Superseded,
+        operation_pool::ExclusionReason::MaxCoverLost => ExclusionReason::MaxCoverLost,
+        operation_pool::ExclusionReason::LimitReached => ExclusionReason::LimitReached,
+    }
+}
@@ -1,5 +1,6 @@
 //! Contains the handler for the `GET validator/duties/proposer/{epoch}` endpoint.
 
+use crate::committees::finalized_epoch_shuffling;
 use crate::state_id::StateId;
 use beacon_chain::{
     beacon_proposer_cache::{compute_proposer_duties_from_head, ensure_state_is_in_epoch},
@@ -188,6 +189,22 @@ fn compute_historic_proposer_duties<T: BeaconChainTypes>(
     epoch: Epoch,
     chain: &BeaconChain<T>,
 ) -> Result<ApiDuties, warp::reject::Rejection> {
+    // Finalized epochs can be served from the freezer without loading a state.
+    if let Some(shuffling) = finalized_epoch_shuffling(chain, epoch)? {
+        let indices = shuffling
+            .proposer_indices
+            .iter()
+            .map(|&index| index as usize)
+            .collect();
+        return convert_to_api_response(
+            chain,
+            epoch,
+            shuffling.proposer_shuffling_decision_root,
+            false,
+            indices,
+        );
+    }
+
     // If the head is quite old then it might still be relevant for a historical request.
     //
     // Avoid holding the `cached_head` longer than necessary.
@@ -0,0 +1,707 @@
+//! Selective queries of `BeaconState` fields for `POST lighthouse/beacon/states/{state_id}/fields`.
+//!
+//! Each selected value is returned with a Merkle proof against the state root, built from the
+//! tree hash roots of the state's fields and the elements of its lists. Proofs into a list are
+//! generated in a single pass over its elements without building its tree, and the total hashing
+//! work of a request is bounded by `MAX_HASHED_NODES`.
+
+use eth2::lighthouse::{StateFieldProof, StateFields};
+use merkle_proof::{generate_proofs, merkle_root_from_branch, MerkleTree};
+use serde::Serialize;
+use serde_json::Value;
+use std::ops::Range;
+use tree_hash::{TreeHash, TreeHashType, BYTES_PER_CHUNK};
+use types::{
+    milhouse, BeaconBlockHeader, BeaconState, BeaconStateError, BitVector, Checkpoint, Epoch,
+    Eth1Data, EthSpec, ExecutionPayloadHeaderBellatrix, ExecutionPayloadHeaderCapella,
+    ExecutionPayloadHeaderDeneb, ExecutionPayloadHeaderElectra, FixedBytesExtended, Fork, Hash256,
+    HistoricalSummary, List, ParticipationFlags, PendingAttestation, PendingBalanceDeposit,
+    PendingConsolidation, PendingPartialWithdrawal, Slot, SyncCommittee, Unsigned, Validator,
+    Vector,
+};
+
+/// The maximum number of values which may be selected by a single request.
+pub const MAX_SELECTED_VALUES: usize = 8_192;
+
+/// The maximum number of element roots and tree leaves which may be hashed by a single request.
+///
+/// This permits a proof into the validator registry of mainnet, along with a few smaller lists.
+pub const MAX_HASHED_NODES: usize = 1 << 22;
+
+/// The fields of the phase 0 `BeaconState`, in tree hash order.
+const PHASE0_FIELDS: &[&str] = &[
+    "genesis_time",
+    "genesis_validators_root",
+    "slot",
+    "fork",
+    "latest_block_header",
+    "block_roots",
+    "state_roots",
+    "historical_roots",
+    "eth1_data",
+    "eth1_data_votes",
+    "eth1_deposit_index",
+    "validators",
+    "balances",
+    "randao_mixes",
+    "slashings",
+    "previous_epoch_attestations",
+    "current_epoch_attestations",
+    "justification_bits",
+    "previous_justified_checkpoint",
+    "current_justified_checkpoint",
+    "finalized_checkpoint",
+];
+
+/// The fields of the Altair `BeaconState`, in tree hash order.
+const ALTAIR_FIELDS: &[&str] = &[
+    "genesis_time",
+    "genesis_validators_root",
+    "slot",
+    "fork",
+    "latest_block_header",
+    "block_roots",
+    "state_roots",
+    "historical_roots",
+    "eth1_data",
+    "eth1_data_votes",
+    "eth1_deposit_index",
+    "validators",
+    "balances",
+    "randao_mixes",
+    "slashings",
+    "previous_epoch_participation",
+    "current_epoch_participation",
+    "justification_bits",
+    "previous_justified_checkpoint",
+    "current_justified_checkpoint",
+    "finalized_checkpoint",
+    "inactivity_scores",
+    "current_sync_committee",
+    "next_sync_committee",
+];
+
+/// The fields appended to the `BeaconState` at each fork after Altair.
+const BELLATRIX_FIELDS: &[&str] = &["latest_execution_payload_header"];
+const CAPELLA_FIELDS: &[&str] = &[
+    "next_withdrawal_index",
+    "next_withdrawal_validator_index",
+    "historical_summaries",
+];
+const ELECTRA_FIELDS: &[&str] = &[
+    "deposit_requests_start_index",
+    "deposit_balance_to_consume",
+    "exit_balance_to_consume",
+    "earliest_exit_epoch",
+    "consolidation_balance_to_consume",
+    "earliest_consolidation_epoch",
+    "pending_balance_deposits",
+    "pending_partial_withdrawals",
+    "pending_consolidations",
+];
+
+/// Return the names of the fields of `state`, in tree hash order.
+fn field_names<E: EthSpec>(state: &BeaconState<E>) -> Vec<&'static str> {
+    let forks: &[&[&'static str]] = match state {
+        BeaconState::Base(_) => &[PHASE0_FIELDS],
+        BeaconState::Altair(_) => &[ALTAIR_FIELDS],
+        BeaconState::Bellatrix(_) => &[ALTAIR_FIELDS, BELLATRIX_FIELDS],
+        BeaconState::Capella(_) | BeaconState::Deneb(_) => {
+            &[ALTAIR_FIELDS, BELLATRIX_FIELDS, CAPELLA_FIELDS]
+        }
+        BeaconState::Electra(_) => &[
+            ALTAIR_FIELDS,
+            BELLATRIX_FIELDS,
+            CAPELLA_FIELDS,
+            ELECTRA_FIELDS,
+        ],
+    };
+    forks
+        .iter()
+        .flat_map(|fields| fields.iter().copied())
+        .collect()
+}
+
+/// Select the values named by `paths` from `state`, along with proofs against the state root.
+pub fn query_state_fields<E: EthSpec>(
+    state: &BeaconState<E>,
+    paths: &[String],
+) -> Result<StateFields, warp::Rejection> {
+    let field_names = field_names(state);
+    let field_proofs = state
+        .compute_field_proofs()
+        .map_err(warp_utils::reject::beacon_state_error)?;
+    if field_names.len() != field_proofs.len() {
+        return Err(warp_utils::reject::custom_server_error(format!(
+            "expected {} state fields, found {}",
+            field_names.len(),
+            field_proofs.len()
+        )));
+    }
+
+    let state_root = {
+        let (leaf, proof) = &field_proofs[0];
+        merkle_root_from_branch(*leaf, proof, proof.len(), 0)
+    };
+
+    let mut budget = HashBudget::default();
+    let mut fields = vec![];
+    for path in paths {
+        let field_path = FieldPath::parse(path).map_err(|e| {
+            warp_utils::reject::custom_bad_request(format!("invalid path {path}: {e}"))
+        })?;
+        let field_index = field_names
+            .iter()
+            .position(|name| *name == field_path.field)
+            .ok_or_else(|| {
+                warp_utils::reject::custom_bad_request(format!(
+                    "{} is not a field of the state at slot {}",
+                    field_path.field,
+                    state.slot()
+                ))
+            })?;
+
+        let selected = field_path.selected_len();
+        if fields.len() + selected > MAX_SELECTED_VALUES {
+            return Err(warp_utils::reject::custom_bad_request(format!(
+                "request selects more than {MAX_SELECTED_VALUES} values"
+            )));
+        }
+
+        let (field_root, field_proof) = &field_proofs[field_index];
+        let branches = select(state, &field_path, &mut budget).map_err(|e| {
+            warp_utils::reject::custom_bad_request(format!("unable to select {path}: {e}"))
+        })?;
+
+        for (path, mut branch) in branches {
+            // A branch of depth zero selects the field itself.
+            if branch.depth == 0 {
+                branch.leaf = *field_root;
+            }
+            let branch = branch.extend(field_index, field_proof.len(), field_proof);
+            fields.push(StateFieldProof {
+                path,
+                value: branch.value,
+                leaf: branch.leaf,
+                gindex: (1 << branch.depth) | branch.index,
+                proof: branch.proof,
+            });
+        }
+    }
+
+    Ok(StateFields {
+        slot: state.slot(),
+        state_root,
+        fields,
+    })
+}
+
+/// A parsed path such as `finalized_checkpoint`, `balances[0..1000]` or
+/// `validators[123].effective_balance`.
+#[derive(Debug, PartialEq)]
+struct FieldPath<'a> {
+    field: &'a str,
+    elements: Option<Range<usize>>,
+    subfield: Option<&'a str>,
+}
+
+impl<'a> FieldPath<'a> {
+    fn parse(path: &'a str) -> Result<Self, String> {
+        let (field, rest) = path.split_at(
+            path.find(|c: char| c == '[' || c == '.')
+                .unwrap_or(path.len()),
+        );
+        if !is_identifier(field) {
+            return Err("expected a field name".to_string());
+        }
+
+        let (elements, rest) = match rest.strip_prefix('[') {
+            Some(rest) => {
+                let (selector, rest) = rest.split_once(']').ok_or("missing ']'")?;
+                let parse_index = |index: &str| {
+                    index
+                        .parse::<usize>()
+                        .map_err(|e| format!("invalid index {index}: {e}"))
+                };
+                let elements = match selector.split_once("..") {
+                    Some((start, end)) => parse_index(start)?..parse_index(end)?,
+                    None => {
+                        let index = parse_index(selector)?;
+                        index..index.saturating_add(1)
+                    }
+                };
+                if elements.is_empty() {
+                    return Err(format!("empty range {selector}"));
+                }
+                (Some(elements), rest)
+            }
+            None => (None, rest),
+        };
+
+        let subfield = match rest {
+            "" => None,
+            rest => Some(
+                rest.strip_prefix('.')
+                    .filter(|subfield| is_identifier(subfield))
+                    .ok_or_else(|| format!("unexpected {rest}"))?,
+            ),
+        };
+
+        Ok(Self {
+            field,
+            elements,
+            subfield,
+        })
+    }
+
+    /// The number of values selected by this path.
+    fn selected_len(&self) -> usize {
+        self.elements.as_ref().map_or(1, |elements| elements.len())
+    }
+}
+
+/// Returns `true` if `s` is a lowercase field name such as `eth1_data`.
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_lowercase() || c == '_')
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// A value selected from a subtree, with a proof of its inclusion in that subtree.
+struct Branch {
+    value: Value,
+    leaf: Hash256,
+    /// The position of `leaf` among the `2^depth` nodes at the bottom of the subtree.
+    index: u64,
+    depth: usize,
+    proof: Vec<Hash256>,
+}
+
+impl Branch {
+    fn new(value: Value, leaf: Hash256) -> Self {
+        Self {
+            value,
+            leaf,
+            index: 0,
+            depth: 0,
+            proof: vec![],
+        }
+    }
+
+    /// Extend the branch to the root of a parent tree, in which the root of the current subtree
+    /// is found at position `index` at `depth`, with the given proof.
+    fn extend(mut self, index: usize, depth: usize, proof: &[Hash256]) -> Self {
+        self.index |= (index as u64) << self.depth;
+        self.depth += depth;
+        self.proof.extend_from_slice(proof);
+        self
+    }
+}
+
+/// A value which may be selected from a `BeaconState`.
+trait QueryValue: TreeHash + Serialize {
+    fn to_json(&self) -> Result<Value, String> {
+        serde_json::to_value(self).map_err(|e| format!("unable to serialize value: {e}"))
+    }
+
+    /// The names and tree hash roots of the fields of a container, in tree hash order.
+    ///
+    /// Returns `None` for values which don't support field access.
+    fn field_roots(&self) -> Option<Vec<(&'static str, Hash256)>> {
+        None
+    }
+}
+
+impl QueryValue for u64 {
+    fn to_json(&self) -> Result<Value, String> {
+        Ok(Value::String(self.to_string()))
+    }
+}
+
+macro_rules! impl_query_value {
+    ($type: ty) => {
+        impl QueryValue for $type {}
+    };
+    ($type: ty, [$($field: ident),+]) => {
+        impl QueryValue for $type {
+            fn field_roots(&self) -> Option<Vec<(&'static str, Hash256)>> {
+                Some(vec![$((stringify!($field), self.$field.tree_hash_root())),+])
+            }
+        }
+    };
+}
+
+impl_query_value!(Hash256);
+impl_query_value!(Slot);
+impl_query_value!(Epoch);
+impl_query_value!(ParticipationFlags);
+impl_query_value!(HistoricalSummary);
+impl_query_value!(Fork, [previous_version, current_version, epoch]);
+impl_query_value!(Checkpoint, [epoch, root]);
+impl_query_value!(Eth1Data, [deposit_root, deposit_count, block_hash]);
+impl_query_value!(
+    BeaconBlockHeader,
+    [slot, proposer_index, parent_root, state_root, body_root]
+);
+impl_query_value!(
+    Validator,
+    [
+        pubkey,
+        withdrawal_credentials,
+        effective_balance,
+        slashed,
+        activation_eligibility_epoch,
+        activation_epoch,
+        exit_epoch,
+        withdrawable_epoch
+    ]
+);
+impl_query_value!(PendingBalanceDeposit, [index, amount]);
+impl_query_value!(
+    PendingPartialWithdrawal,
+    [index, amount, withdrawable_epoch]
+);
+impl_query_value!(PendingConsolidation, [source_index, target_index]);
+
+impl<N: Unsigned + Clone> QueryValue for BitVector<N> {}
+impl<E: EthSpec> QueryValue for PendingAttestation<E> {}
+impl<E: EthSpec> QueryValue for SyncCommittee<E> {}
+impl<E: EthSpec> QueryValue for ExecutionPayloadHeaderBellatrix<E> {}
+impl<E: EthSpec> QueryValue for ExecutionPayloadHeaderCapella<E> {}
+impl<E: EthSpec> QueryValue for ExecutionPayloadHeaderDeneb<E> {}
+impl<E: EthSpec> QueryValue for ExecutionPayloadHeaderElectra<E> {}
+
+/// Select the values named by `path` from `state`, with proofs against the root of the field.
+fn select<E: EthSpec>(
+    state: &BeaconState<E>,
+    path: &FieldPath,
+    budget: &mut HashBudget,
+) -> Result<Vec<(String, Branch)>, String> {
+    let not_present = || format!("{} is not a field of this state", path.field);
+    let missing = |_: BeaconStateError| not_present();
+
+    match path.field {
+        "genesis_time" => single(&state.genesis_time(), path),
+        "genesis_validators_root" => single(&state.genesis_validators_root(), path),
+        "slot" => single(&state.slot(), path),
+        "fork" => single(&state.fork(), path),
+        "latest_block_header" => single(state.latest_block_header(), path),
+        "block_roots" => sequence(state.block_roots(), path, budget),
+        "state_roots" => sequence(state.state_roots(), path, budget),
+        "historical_roots" => sequence(state.historical_roots(), path, budget),
+        "eth1_data" => single(state.eth1_data(), path),
+        "eth1_data_votes" => sequence(state.eth1_data_votes(), path, budget),
+        "eth1_deposit_index" => single(&state.eth1_deposit_index(), path),
+        "validators" => sequence(state.validators(), path, budget),
+        "balances" => sequence(state.balances(), path, budget),
+        "randao_mixes" => sequence(state.randao_mixes(), path, budget),
+        "slashings" => sequence(state.slashings(), path, budget),
+        "previous_epoch_attestations" => sequence(
+            state.previous_epoch_attestations().map_err(missing)?,
+            path,
+            budget,
+        ),
+        "current_epoch_attestations" => sequence(
+            state.current_epoch_attestations().map_err(missing)?,
+            path,
+            budget,
+        ),
+        "previous_epoch_participation" => sequence(
+            state.previous_epoch_participation().map_err(missing)?,
+            path,
+            budget,
+        ),
+        "current_epoch_participation" => sequence(
+            state.current_epoch_participation().map_err(missing)?,
+            path,
+            budget,
+        ),
+        "justification_bits" => single(state.justification_bits(), path),
+        "previous_justified_checkpoint" => single(&state.previous_justified_checkpoint(), path),
+        "current_justified_checkpoint" => single(&state.current_justified_checkpoint(), path),
+        "finalized_checkpoint" => single(&state.finalized_checkpoint(), path),
+        "inactivity_scores" => sequence(state.inactivity_scores().map_err(missing)?, path, budget),
+        "current_sync_committee" => {
+            single(&**state.current_sync_committee().map_err(missing)?, path)
+        }
+        "next_sync_committee" => single(&**state.next_sync_committee().map_err(missing)?, path),
+        "latest_execution_payload_header" => match state {
+            BeaconState::Base(_) | BeaconState::Altair(_) => Err(not_present()),
+            BeaconState::Bellatrix(inner) => single(&inner.latest_execution_payload_header, path),
+            BeaconState::Capella(inner) => single(&inner.latest_execution_payload_header, path),
+            BeaconState::Deneb(inner) => single(&inner.latest_execution_payload_header, path),
+            BeaconState::Electra(inner) => single(&inner.latest_execution_payload_header, path),
+        },
+        "next_withdrawal_index" => single(&state.next_withdrawal_index().map_err(missing)?, path),
+        "next_withdrawal_validator_index" => single(
+            &state.next_withdrawal_validator_index().map_err(missing)?,
+            path,
+        ),
+        "historical_summaries" => {
+            sequence(state.historical_summaries().map_err(missing)?, path, budget)
+        }
+        "deposit_requests_start_index" => single(
+            &state.deposit_requests_start_index().map_err(missing)?,
+            path,
+        ),
+        "deposit_balance_to_consume" => {
+            single(&state.deposit_balance_to_consume().map_err(missing)?, path)
+        }
+        "exit_balance_to_consume" => {
+            single(&state.exit_balance_to_consume().map_err(missing)?, path)
+        }
+        "earliest_exit_epoch" => single(&state.earliest_exit_epoch().map_err(missing)?, path),
+        "consolidation_balance_to_consume" => single(
+            &state.consolidation_balance_to_consume().map_err(missing)?,
+            path,
+        ),
+        "earliest_consolidation_epoch" => single(
+            &state.earliest_consolidation_epoch().map_err(missing)?,
+            path,
+        ),
+        "pending_balance_deposits" => sequence(
+            state.pending_balance_deposits().map_err(missing)?,
+            path,
+            budget,
+        ),
+        "pending_partial_withdrawals" => sequence(
+            state.pending_partial_withdrawals().map_err(missing)?,
+            path,
+            budget,
+        ),
+        "pending_consolidations" => sequence(
+            state.pending_consolidations().map_err(missing)?,
+            path,
+            budget,
+        ),
+        _ => Err(not_present()),
+    }
+}
+
+/// Select a field which is not a list or vector, or one of its subfields.
+fn single<T: QueryValue>(value: &T, path: &FieldPath) -> Result<Vec<(String, Branch)>, String> {
+    if path.elements.is_some() {
+        return Err(format!("{} is not a list", path.field));
+    }
+    let branch = subfield_branch(value, path.subfield)?;
+    let path = match path.subfield {
+        Some(subfield) => format!("{}.{subfield}", path.field),
+        None => path.field.to_string(),
+    };
+    Ok(vec![(path, branch)])
+}
+
+/// A list or vector of the state.
+trait Sequence {
+    type Item: QueryValue;
+    /// The maximum length of a list, or the length of a vector.
+    const MAX_LEN: usize;
+    /// The length of a list is mixed in to its root, whereas that of a vector is not.
+    const IS_LIST: bool;
+
+    fn length(&self) -> usize;
+    fn element(&self, index: usize) -> Option<&Self::Item>;
+    fn elements(&self) -> impl Iterator<Item = &Self::Item>;
+}
+
+impl<T: milhouse::Value + QueryValue, N: Unsigned> Sequence for List<T, N> {
+    type Item = T;
+    const MAX_LEN: usize = N::USIZE;
+    const IS_LIST: bool = true;
+
+    fn length(&self) -> usize {
+        self.len()
+    }
+
+    fn element(&self, index: usize) -> Option<&T> {
+        self.get(index)
+    }
+
+    fn elements(&self) -> impl Iterator<Item = &T> {
+        self.iter()
+    }
+}
+
+impl<T: milhouse::Value + QueryValue, N: Unsigned> Sequence for Vector<T, N> {
+    type Item = T;
+    const MAX_LEN: usize = N::USIZE;
+    const IS_LIST: bool = false;
+
+    fn length(&self) -> usize {
+        self.len()
+    }
+
+    fn element(&self, index: usize) -> Option<&T> {
+        self.get(index)
+    }
+
+    fn elements(&self) -> impl Iterator<Item = &T> {
+        self.iter()
+    }
+}
+
+/// The hashing work done to generate the proofs of a single request.
+#[derive(Default)]
+struct HashBudget {
+    /// The number of element roots and tree leaves hashed so far.
+    hashed_nodes: usize,
+}
+
+impl HashBudget {
+    fn spend(&mut self, nodes: usize) -> Result<(), String> {
+        self.hashed_nodes = self.hashed_nodes.saturating_add(nodes);
+        if self.hashed_nodes > MAX_HASHED_NODES {
+            return Err(format!(
+                "request requires hashing more than {MAX_HASHED_NODES} nodes"
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The tree hash leaves of `items`, packing basic types into chunks.
+fn leaves<'a, T: QueryValue + 'a>(
+    items: impl Iterator<Item = &'a T> + 'a,
+) -> Box<dyn Iterator<Item = Hash256> + 'a> {
+    if !matches!(T::tree_hash_type(), TreeHashType::Basic) {
+        return Box::new(items.map(|item| item.tree_hash_root()));
+    }
+    let per_chunk = T::tree_hash_packing_factor();
+    let mut items = items.peekable();
+    Box::new(std::iter::from_fn(move || {
+        items.peek()?;
+        let mut leaf = [0; BYTES_PER_CHUNK];
+        let mut offset = 0;
+        for item in items.by_ref().take(per_chunk) {
+            let bytes = item.tree_hash_packed_encoding();
+            leaf[offset..offset + bytes.len()].copy_from_slice(&bytes);
+            offset += bytes.len();
+        }
+        Some(Hash256::from(leaf))
+    }))
+}
+
+/// Select the elements of a list or vector, or one of the subfields of each element.
+///
+/// Elements of basic types are packed into chunks, in which case the leaf of each branch is the
+/// chunk containing the element.
+fn sequence<S: Sequence>(
+    items: &S,
+    path: &FieldPath,
+    budget: &mut HashBudget,
+) -> Result<Vec<(String, Branch)>, String> {
+    let len = items.length();
+    let Some(elements) = path.elements.clone() else {
+        if path.subfield.is_some() {
+            return Err(format!(
+                "an element of {} must be selected before its fields",
+                path.field
+            ));
+        }
+        if len > MAX_SELECTED_VALUES {
+            return Err(format!(
+                "{} has more than {MAX_SELECTED_VALUES} elements, select a range instead",
+                path.field
+            ));
+        }
+        let value = items
+            .elements()
+            .map(|item| item.to_json())
+            .collect::<Result<_, _>>()?;
+        // The root of the field is filled in by the caller.
+        return Ok(vec![(
+            path.field.to_string(),
+            Branch::new(Value::Array(value), Hash256::zero()),
+        )]);
+    };
+    if elements.end > len {
+        return Err(format!(
+            "{} has {} elements, cannot select {}..{}",
+            path.field, len, elements.start, elements.end
+        ));
+    }
+    if elements.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let is_basic = matches!(S::Item::tree_hash_type(), TreeHashType::Basic);
+    let per_chunk = if is_basic {
+        S::Item::tree_hash_packing_factor()
+    } else {
+        1
+    };
+    let depth = S::MAX_LEN.div_ceil(per_chunk).next_power_of_two().ilog2() as usize;
+
+    // Each element of a composite type is hashed before the tree of their roots.
+    let num_leaves = len.div_ceil(per_chunk);
+    budget.spend(if is_basic {
+        num_leaves
+    } else {
+        len + num_leaves
+    })?;
+
+    let first_chunk = elements.start / per_chunk;
+    let chunks = first_chunk..(elements.end - 1) / per_chunk + 1;
+    let proofs = generate_proofs(leaves(items.elements()), depth, chunks)
+        .map_err(|e| format!("unable to generate proof: {e:?}"))?;
+
+    // The length of a list is mixed in to the right of the root of its elements.
+    let length_proof = S::IS_LIST.then(|| Hash256::from_low_u64_le(len as u64));
+
+    elements
+        .map(|index| {
+            let item = items
+                .element(index)
+                .ok_or_else(|| format!("missing element {index} of {}", path.field))?;
+            let chunk_index = index / per_chunk;
+            let (chunk, mut proof) = proofs[chunk_index - first_chunk].clone();
+            proof.extend(length_proof);
+
+            let element = if is_basic {
+                if path.subfield.is_some() {
+                    return Err(format!("elements of {} have no fields", path.field));
+                }
+                Branch::new(item.to_json()?, chunk)
+            } else {
+                subfield_branch(item, path.subfield)?
+            };
+            let branch = element.extend(chunk_index, proof.len(), &proof);
+
+            let path = match path.subfield {
+                Some(subfield) => format!("{}[{index}].{subfield}", path.field),
+                None => format!("{}[{index}]", path.field),
+            };
+            Ok((path, branch))
+        })
+        .collect()
+}
+
+/// Select `value`, or one of its fields, with a proof against the root of `value`.
+fn subfield_branch<T: QueryValue>(value: &T, subfield: Option<&str>) -> Result<Branch, String> {
+    let Some(subfield) = subfield else {
+        return Ok(Branch::new(value.to_json()?, value.tree_hash_root()));
+    };
+
+    let fields = value
+        .field_roots()
+        .ok_or_else(|| format!("unable to select field {subfield} of this value"))?;
+    let field_index = fields
+        .iter()
+        .position(|(name, _)| *name == subfield)
+        .ok_or_else(|| format!("unknown field {subfield}"))?;
+    let field_value = value
+        .to_json()?
+        .get(subfield)
+        .cloned()
+        .ok_or_else(|| format!("unable to serialize field {subfield}"))?;
+
+    let leaves = fields.iter().map(|(_, root)| *root).collect::<Vec<_>>();
+    let depth = leaves.len().next_power_of_two().ilog2() as usize;
+    let (leaf, proof) = MerkleTree::create(&leaves, depth)
+        .generate_proof(field_index, depth)
+        .map_err(|e| format!("unable to generate proof: {e:?}"))?;
+
+    Ok(Branch::new(field_value, leaf).extend(field_index, depth, &proof))
+}
@@ -1,10 +1,11 @@
 use crate::state_id::StateId;
-use beacon_chain::{BeaconChain, BeaconChainTypes};
+use beacon_chain::{BeaconChain, BeaconChainError, BeaconChainTypes};
 use eth2::{
     lighthouse::{GlobalValidatorInclusionData, ValidatorInclusionData},
     types::ValidatorId,
 };
 use state_processing::per_epoch_processing::{process_epoch, EpochProcessingSummary};
+use store::CompactEpochInclusion;
 use types::{BeaconState, BeaconStateError, ChainSpec, Epoch, EthSpec};
 
 /// Returns the state in the last slot of `epoch`.
@@ -19,6 +20,25 @@ fn end_of_epoch_state<T: BeaconChainTypes>(
     Ok(state)
 }
 
+/// Load the validator inclusion for `epoch` from the freezer, if `epoch` is finalized and the
+/// inclusion was stored during migration.
+///
+/// This avoids loading and processing a historic state, which may not be possible if historic
+/// states have been pruned.
+fn finalized_epoch_inclusion<T: BeaconChainTypes>(
+    epoch: Epoch,
+    chain: &BeaconChain<T>,
+) -> Result<Option<CompactEpochInclusion>, warp::reject::Rejection> {
+    if epoch.end_slot(T::EthSpec::slots_per_epoch()) >= chain.store.get_split_slot() {
+        return Ok(None);
+    }
+    chain
+        .store
+        .get_cold_epoch_inclusion(epoch)
+        .map_err(BeaconChainError::DBError)
+        .map_err(warp_utils::reject::beacon_chain_error)
+}
+
 /// Generate an `EpochProcessingSummary` for `state`.
 ///
 /// ## Notes
@@ -42,6 +62,15 @@ pub fn global_validator_inclusion_data<T: BeaconChainTypes>(
     epoch: Epoch,
     chain: &BeaconChain<T>,
 ) -> Result<GlobalValidatorInclusionData, warp::Rejection> {
+    if let Some(inclusion) = finalized_epoch_inclusion(epoch, chain)? {
+        return Ok(GlobalValidatorInclusionData {
+            current_epoch_active_gwei: inclusion.current_epoch_active_gwei,
+            current_epoch_target_attesting_gwei: inclusion.current_epoch_target_attesting_gwei,
+            previous_epoch_target_attesting_gwei: inclusion.previous_epoch_target_attesting_gwei,
+            previous_epoch_head_attesting_gwei: inclusion.previous_epoch_head_attesting_gwei,
+        });
+    }
+
     let mut state = end_of_epoch_state(epoch, chain)?;
     let summary = get_epoch_processing_summary(&mut state, &chain.spec)?;
 
@@ -65,6 +94,38 @@ pub fn validator_inclusion_data<T: BeaconChainTypes>(
     validator_id: &ValidatorId,
     chain: &BeaconChain<T>,
 ) -> Result<Option<ValidatorInclusionData>, warp::Rejection> {
+    if let Some(inclusion) = finalized_epoch_inclusion(epoch, chain)? {
+        let validator_index = match validator_id {
+            ValidatorId::Index(index) => *index as usize,
+            ValidatorId::PublicKey(pubkey) => {
+                if let Some(index) = chain
+                    .validator_index(pubkey)
+                    .map_err(warp_utils::reject::beacon_chain_error)?
+                {
+                    index
+                } else {
+                    return Ok(None);
+                }
+            }
+        };
+
+        // Validators which joined the registry after `epoch` are unknown, as for a state.
+        let Some((flags, effective_balance)) = inclusion.validator(validator_index) else {
+            return Ok(None);
+        };
+
+        return Ok(Some(ValidatorInclusionData {
+            is_slashed: flags.is_slashed(),
+            is_withdrawable_in_current_epoch: flags.is_withdrawable_in_current_epoch(),
+            is_active_unslashed_in_current_epoch: flags.is_active_unslashed_in_current_epoch(),
+            is_active_unslashed_in_previous_epoch: flags.is_active_unslashed_in_previous_epoch(),
+            current_epoch_effective_balance_gwei: effective_balance,
+            is_current_epoch_target_attester: flags.is_current_epoch_target_attester(),
+            is_previous_epoch_target_attester: flags.is_previous_epoch_target_attester(),
+            is_previous_epoch_head_attester: flags.is_previous_epoch_head_attester(),
+        }));
+    }
+
     let mut state = end_of_epoch_state(epoch, chain)?;
 
     state
@@ -0,0 +1,575 @@
+use beacon_chain::{BeaconChain, BeaconChainError, BeaconChainTypes, WhenSlotSkipped};
+use bytes::Bytes;
+use eth2::lighthouse::{
+    attestation_rewards::TotalAttestationRewards, ValidatorIncome, ValidatorIncomeEpoch,
+    ValidatorIncomeRequest,
+};
+use eth2::types::ValidatorId;
+use slog::{debug, warn};
+use state_processing::per_block_processing::{
+    altair::sync_committee::compute_sync_aggregate_rewards, get_expected_withdrawals,
+};
+use state_processing::{
+    per_epoch_processing::EpochProcessingSummary, BlockProcessingError, BlockReplayError,
+    BlockReplayer,
+};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use types::{
+    BeaconState, BeaconStateError, Epoch, EthSpec, Hash256, SignedBlindedBeaconBlock, Slot,
+};
+use warp::http::Response;
+use warp::hyper::Body;
+use warp_utils::reject::{beacon_chain_error, custom_bad_request, custom_server_error};
+
+/// The maximum number of validators which may be included in a single request.
+const MAX_REQUEST_VALIDATORS: usize = 1024;
+const BLOCK_ROOT_CHUNK_SIZE: usize = 100;
+/// The number of epochs which may be buffered before the replay waits for the client.
+const EPOCH_CHANNEL_CAPACITY: usize = 16;
+
+#[derive(Debug)]
+// We don't use the inner values directly, but they're used in the Debug impl.
+enum ValidatorIncomeError {
+    BlockReplay(#[allow(dead_code)] BlockReplayError),
+    BeaconState(#[allow(dead_code)] BeaconStateError),
+    BlockProcessing(#[allow(dead_code)] BlockProcessingError),
+    BeaconChain(#[allow(dead_code)] BeaconChainError),
+    Json(#[allow(dead_code)] serde_json::Error),
+    ReceiverDropped,
+}
+
+impl From<BlockReplayError> for ValidatorIncomeError {
+    fn from(e: BlockReplayError) -> Self {
+        Self::BlockReplay(e)
+    }
+}
+
+impl From<BeaconStateError> for ValidatorIncomeError {
+    fn from(e: BeaconStateError) -> Self {
+        Self::BeaconState(e)
+    }
+}
+
+impl From<BlockProcessingError> for ValidatorIncomeError {
+    fn from(e: BlockProcessingError) -> Self {
+        Self::BlockProcessing(e)
+    }
+}
+
+impl From<BeaconChainError> for ValidatorIncomeError {
+    fn from(e: BeaconChainError) -> Self {
+        Self::BeaconChain(e)
+    }
+}
+
+type EpochSender = mpsc::Sender<Result<Bytes, std::io::Error>>;
+
+/// Accumulates the income of a set of validators as blocks and slots are replayed.
+///
+/// Each epoch is sent to the client as soon as its epoch transition has been applied so that
+/// memory usage does not grow with the size of the requested range.
+struct IncomeTracker<'a, T: BeaconChainTypes> {
+    chain: &'a BeaconChain<T>,
+    indices: Vec<usize>,
+    /// Map from validator index to its position in `indices`.
+    positions: HashMap<usize, usize>,
+    start_epoch: Epoch,
+    end_epoch: Epoch,
+    /// The epoch currently being accumulated, if any.
+    epoch: Option<Epoch>,
+    /// Balances at the start of `self.epoch`.
+    epoch_start_balances: Vec<u64>,
+    incomes: Vec<ValidatorIncome>,
+    /// Balances prior to the block or epoch transition currently being applied.
+    pre_balances: Vec<u64>,
+    pre_slashed: Vec<bool>,
+    /// The sync committee reward for each tracked validator in the block being applied.
+    pending_sync_committee: Vec<i64>,
+    /// Attestation rewards which will be applied by the next epoch transition.
+    pending_attestation: Option<Vec<TotalAttestationRewards>>,
+    /// Whether the next epoch transition will be processed according to phase0 rules.
+    pending_transition_is_base: bool,
+    /// Validator indices of the sync committee for the given sync committee period.
+    sync_committee_cache: Option<(u64, Vec<usize>)>,
+    sender: EpochSender,
+}
+
+impl<T: BeaconChainTypes> IncomeTracker<'_, T> {
+    fn is_tracked(&self, epoch: Epoch) -> bool {
+        epoch >= self.start_epoch && epoch <= self.end_epoch
+    }
+
+    fn balances(&self, state: &BeaconState<T::EthSpec>) -> Vec<u64> {
+        self.indices
+            .iter()
+            .map(|&index| state.balances().get(index).copied().unwrap_or(0))
+            .collect()
+    }
+
+    fn slashed(&self, state: &BeaconState<T::EthSpec>) -> Vec<bool> {
+        self.indices
+            .iter()
+            .map(|&index| {
+                state
+                    .validators()
+                    .get(index)
+                    .is_some_and(|validator| validator.slashed)
+            })
+            .collect()
+    }
+
+    fn start_epoch_tracking(&mut self, state: &BeaconState<T::EthSpec>) {
+        self.epoch = Some(state.current_epoch());
+        self.epoch_start_balances = self.balances(state);
+        self.incomes = self
+            .indices
+            .iter()
+            .map(|&index| ValidatorIncome {
+                validator_index: index as u64,
+                ..ValidatorIncome::default()
+            })
+            .collect();
+    }
+
+    fn sync_committee_indices(
+        &mut self,
+        state: &mut BeaconState<T::EthSpec>,
+    ) -> Result<Vec<usize>, ValidatorIncomeError> {
+        let period = state
+            .current_epoch()
+            .sync_committee_period(&self.chain.spec)
+            .map_err(BeaconStateError::from)?;
+        match &self.sync_committee_cache {
+            Some((cached_period, indices)) if *cached_period == period => Ok(indices.clone()),
+            _ => {
+                let sync_committee = state.current_sync_committee()?.clone();
+                let indices = state.get_sync_committee_indices(&sync_committee)?;
+                self.sync_committee_cache = Some((period, indices.clone()));
+                Ok(indices)
+            }
+        }
+    }
+
+    fn pre_block(
+        &mut self,
+        state: &mut BeaconState<T::EthSpec>,
+        block: &SignedBlindedBeaconBlock<T::EthSpec>,
+    ) -> Result<(), ValidatorIncomeError> {
+        if self.epoch.is_none() {
+            return Ok(());
+        }
+        self.pre_balances = self.balances(state);
+        self.pre_slashed = self.slashed(state);
+        self.pending_sync_committee = vec![0; self.indices.len()];
+
+        if let Ok(sync_aggregate) = block.message().body().sync_aggregate() {
+            let (participant_reward, _) = compute_sync_aggregate_rewards(state, &self.chain.spec)?;
+            let committee_indices = self.sync_committee_indices(state)?;
+            for (index, participated) in committee_indices
+                .into_iter()
+                .zip(sync_aggregate.sync_committee_bits.iter())
+            {
+                if let Some(&position) = self.positions.get(&index) {
+                    if participated {
+                        self.pending_sync_committee[position] += participant_reward as i64;
+                    } else {
+                        self.pending_sync_committee[position] -= participant_reward as i64;
+                    }
+                }
+            }
+        }
+
+        if state.fork_name_unchecked().capella_enabled() {
+            let (withdrawals, _) = get_expected_withdrawals(state, &self.chain.spec)?;
+            for withdrawal in withdrawals.iter() {
+                if let Some(&position) = self.positions.get(&(withdrawal.validator_index as usize))
+                {
+                    self.incomes[position].withdrawals += withdrawal.amount;
+                    // Treat the withdrawal as if it happened prior to the block so that it is not
+                    // attributed to another component.
+                    self.pre_balances[position] =
+                        self.pre_balances[position].saturating_sub(withdrawal.amount);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn post_block(
+        &mut self,
+        state: &mut BeaconState<T::EthSpec>,
+        block: &SignedBlindedBeaconBlock<T::EthSpec>,
+    ) -> Result<(), ValidatorIncomeError> {
+        if self.epoch.is_none() {
+            return Ok(());
+        }
+        let proposer_index = block.message().proposer_index() as usize;
+        let post_balances = self.balances(state);
+        let post_slashed = self.slashed(state);
+
+        for (position, income) in self.incomes.iter_mut().enumerate() {
+            let sync_committee = self.pending_sync_committee[position];
+            income.sync_committee += sync_committee;
+
+            let remainder = post_balances[position] as i64
+                - self.pre_balances[position] as i64
+                - sync_committee;
+            if post_slashed[position] && !self.pre_slashed[position] {
+                income.slashing += remainder;
+            } else if self.indices[position] == proposer_index {
+                income.proposal += remainder;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn pre_slot(
+        &mut self,
+        state: &mut BeaconState<T::EthSpec>,
+    ) -> Result<(), ValidatorIncomeError> {
+        let slots_per_epoch = T::EthSpec::slots_per_epoch();
+        if self.epoch.is_none() || state.slot() != state.current_epoch().end_slot(slots_per_epoch) {
+            return Ok(());
+        }
+
+        // The next slot will trigger an epoch transition.
+        self.pre_balances = self.balances(state);
+        self.pre_slashed = self.slashed(state);
+        self.pending_transition_is_base = !state.fork_name_unchecked().altair_enabled();
+
+        if state.current_epoch() > self.chain.spec.genesis_slot.epoch(slots_per_epoch) {
+            let validators = self
+                .indices
+                .iter()
+                .filter(|&&index| index < state.validators().len())
+                .map(|&index| ValidatorId::Index(index as u64))
+                .collect();
+            let rewards = self
+                .chain
+                .compute_attestation_rewards_for_state(state, validators)?;
+            self.pending_attestation = Some(rewards.total_rewards);
+        }
+
+        Ok(())
+    }
+
+    fn post_slot(
+        &mut self,
+        state: &mut BeaconState<T::EthSpec>,
+        summary: Option<EpochProcessingSummary<T::EthSpec>>,
+    ) -> Result<(), ValidatorIncomeError> {
+        // Only epoch transitions are of interest.
+        if summary.is_none() {
+            return Ok(());
+        }
+
+        if let Some(epoch) = self.epoch.take() {
+            self.apply_epoch_transition(state)?;
+            self.send_epoch(epoch, state)?;
+        }
+
+        if self.is_tracked(state.current_epoch()) {
+            self.start_epoch_tracking(state);
+        }
+
+        Ok(())
+    }
+
+    fn apply_epoch_transition(
+        &mut self,
+        state: &BeaconState<T::EthSpec>,
+    ) -> Result<(), ValidatorIncomeError> {
+        let post_balances = self.balances(state);
+        let post_slashed = self.slashed(state);
+        let attestation_rewards = self
+            .pending_attestation
+            .take()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|rewards| (rewards.validator_index as usize, rewards))
+            .collect::<HashMap<_, _>>();
+
+        for (position, income) in self.incomes.iter_mut().enumerate() {
+            let mut attestation = 0;
+            if let Some(rewards) = attestation_rewards.get(&self.indices[position]) {
+                let inclusion_delay = rewards
+                    .inclusion_delay
+                    .as_ref()
+                    .map_or(0, |quoted| quoted.value as i64);
+                income.head += rewards.head;
+                income.target += rewards.target;
+                income.source += rewards.source;
+                income.inclusion_delay += inclusion_delay;
+                income.inactivity += rewards.inactivity;
+                attestation = rewards.head
+                    + rewards.target
+                    + rewards.source
+                    + inclusion_delay
+                    + rewards.inactivity;
+            }
+
+            let remainder =
+                post_balances[position] as i64 - self.pre_balances[position] as i64 - attestation;
+            if post_slashed[position] {
+                income.slashing += remainder;
+            } else if self.pending_transition_is_base {
+                // Prior to Altair, proposers are rewarded for attestation inclusion during
+                // epoch processing.
+                income.proposal += remainder;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn send_epoch(
+        &mut self,
+        epoch: Epoch,
+        state: &BeaconState<T::EthSpec>,
+    ) -> Result<(), ValidatorIncomeError> {
+        let balances = self.balances(state);
+        let mut validators = std::mem::take(&mut self.incomes);
+        for (position, income) in validators.iter_mut().enumerate() {
+            income.balance = balances[position];
+            income.delta = balances[position] as i64 - self.epoch_start_balances[position] as i64;
+            income.other = income.delta + income.withdrawals as i64
+                - income.head
+                - income.target
+                - income.source
+                - income.inclusion_delay
+                - income.sync_committee
+                - income.proposal
+                - income.inactivity
+                - income.slashing;
+        }
+
+        let mut line = serde_json::to_vec(&ValidatorIncomeEpoch { epoch, validators })
+            .map_err(ValidatorIncomeError::Json)?;
+        line.push(b'\n');
+        self.sender
+            .blocking_send(Ok(Bytes::from(line)))
+            .map_err(|_| ValidatorIncomeError::ReceiverDropped)
+    }
+}
+
+/// Stream the per-epoch income of the requested validators as newline-delimited JSON.
+///
+/// The starting state is loaded before the response is returned so that invalid requests are
+/// rejected with an appropriate status code. The remainder of the work happens on a blocking
+/// task which feeds the response body.
+pub fn get_validator_income<T: BeaconChainTypes>(
+    request: ValidatorIncomeRequest,
+    chain: Arc<BeaconChain<T>>,
+) -> Result<Response<Body>, warp::Rejection> {
+    let slots_per_epoch = T::EthSpec::slots_per_epoch();
+
+    if request.start_epoch == 0 {
+        return Err(custom_bad_request(
+            "start_epoch must be greater than 0".to_string(),
+        ));
+    }
+
+    if request.start_epoch > request.end_epoch {
+        return Err(custom_bad_request(format!(
+            "start_epoch must not be larger than end_epoch. start: {}, end: {}",
+            request.start_epoch, request.end_epoch
+        )));
+    }
+
+    // The epoch transition at the end of `end_epoch` must already have occurred.
+    let current_epoch = chain.epoch().map_err(beacon_chain_error)?;
+    if request.end_epoch >= current_epoch {
+        return Err(custom_bad_request(format!(
+            "end_epoch must be less than the current epoch. current: {}, end: {}",
+            current_epoch, request.end_epoch
+        )));
+    }
+
+    let mut indices = request
+        .validators
+        .iter()
+        .map(|&index| index as usize)
+        .collect::<Vec<_>>();
+    indices.sort_unstable();
+    indices.dedup();
+    if indices.is_empty() || indices.len() > MAX_REQUEST_VALIDATORS {
+        return Err(custom_bad_request(format!(
+            "between 1 and {} validators must be requested",
+            MAX_REQUEST_VALIDATORS
+        )));
+    }
+
+    // Load the state at the last slot of the epoch prior to `start_epoch`.
+    let start_slot = request.start_epoch.start_slot(slots_per_epoch);
+    let prior_slot = start_slot - 1;
+    let end_slot = request.end_epoch.end_slot(slots_per_epoch);
+    let target_slot = end_slot + 1;
+
+    let state_root = chain
+        .state_root_at_slot(prior_slot)
+        .and_then(|maybe_root| maybe_root.ok_or(BeaconChainError::NoStateForSlot(prior_slot)))
+        .map_err(beacon_chain_error)?;
+    let state = chain
+        .get_state(&state_root, Some(prior_slot))
+        .and_then(|maybe_state| maybe_state.ok_or(BeaconChainError::MissingBeaconState(state_root)))
+        .map_err(beacon_chain_error)?;
+    let prior_block_root = chain
+        .block_root_at_slot(prior_slot, WhenSlotSkipped::Prev)
+        .map_err(beacon_chain_error)?
+        .ok_or_else(|| {
+            custom_server_error(format!("unable to find block root for slot {}", prior_slot))
+        })?;
+
+    let (sender, receiver) = mpsc::channel(EPOCH_CHANNEL_CAPACITY);
+    let task_chain = chain.clone();
+    chain.task_executor.spawn_blocking(
+        move || {
+            let chain = task_chain;
+            if let Err(e) = replay_validator_income(
+                &chain,
+                state,
+                prior_block_root,
+                indices,
+                request,
+                target_slot,
+                sender.clone(),
+            ) {
+                match e {
+                    ValidatorIncomeError::ReceiverDropped => {
+                        debug!(chain.log, "Validator income request cancelled");
+                    }
+                    e => {
+                        warn!(
+                            chain.log,
+                            "Failed to compute validator income";
+                            "error" => ?e
+                        );
+                        // Abort the response body so the client does not mistake a partial
+                        // response for a complete one.
+                        let _ =
+                            sender.blocking_send(Err(std::io::Error::other(format!("{:?}", e))));
+                    }
+                }
+            }
+        },
+        "validator_income",
+    );
+
+    Response::builder()
+        .status(200)
+        .header("Content-Type", "application/x-ndjson")
+        .body(Body::wrap_stream(ReceiverStream::new(receiver)))
+        .map_err(|e| custom_server_error(format!("failed to create response: {}", e)))
+}
+
+fn replay_validator_income<T: BeaconChainTypes>(
+    chain: &BeaconChain<T>,
+    state: BeaconState<T::EthSpec>,
+    prior_block_root: Hash256,
+    indices: Vec<usize>,
+    request: ValidatorIncomeRequest,
+    target_slot: Slot,
+    sender: EpochSender,
+) -> Result<(), ValidatorIncomeError> {
+    let positions = indices
+        .iter()
+        .enumerate()
+        .map(|(position, &index)| (index, position))
+        .collect();
+    let tracker = RefCell::new(IncomeTracker {
+        chain,
+        indices,
+        positions,
+        start_epoch: request.start_epoch,
+        end_epoch: request.end_epoch,
+        epoch: None,
+        epoch_start_balances: vec![],
+        incomes: vec![],
+        pre_balances: vec![],
+        pre_slashed: vec![],
+        pending_sync_committee: vec![],
+        pending_attestation: None,
+        pending_transition_is_base: false,
+        sync_committee_cache: None,
+        sender,
+    });
+
+    let mut replayer: BlockReplayer<T::EthSpec, ValidatorIncomeError> =
+        BlockReplayer::new(state, &chain.spec)
+            .no_state_root_iter()
+            .no_signature_verification()
+            .minimal_block_root_verification()
+            .pre_block_hook(Box::new(
+                |state: &mut BeaconState<T::EthSpec>,
+                 block: &SignedBlindedBeaconBlock<T::EthSpec>| {
+                    tracker.borrow_mut().pre_block(state, block)
+                },
+            ))
+            .post_block_hook(Box::new(
+                |state: &mut BeaconState<T::EthSpec>,
+                 block: &SignedBlindedBeaconBlock<T::EthSpec>| {
+                    tracker.borrow_mut().post_block(state, block)
+                },
+            ))
+            .pre_slot_hook(Box::new(
+                |_: Hash256, state: &mut BeaconState<T::EthSpec>| {
+                    tracker.borrow_mut().pre_slot(state)
+                },
+            ))
+            .post_slot_hook(Box::new(
+                |state: &mut BeaconState<T::EthSpec>,
+                 summary: Option<EpochProcessingSummary<T::EthSpec>>,
+                 _is_skip_slot: bool| {
+                    tracker.borrow_mut().post_slot(state, summary)
+                },
+            ));
+
+    // Load blocks lazily in chunks so that memory usage is bounded regardless of the range.
+    let start_slot = request
+        .start_epoch
+        .start_slot(T::EthSpec::slots_per_epoch());
+    let end_slot = request.end_epoch.end_slot(T::EthSpec::slots_per_epoch());
+    let mut previous_root = prior_block_root;
+    let mut chunk = Vec::with_capacity(BLOCK_ROOT_CHUNK_SIZE);
+    for result in chain.forwards_iter_block_roots_until(start_slot, end_slot)? {
+        let (block_root, _) = result?;
+        if block_root == previous_root {
+            continue;
+        }
+        previous_root = block_root;
+        chunk.push(block_root);
+
+        if chunk.len() == BLOCK_ROOT_CHUNK_SIZE {
+            let blocks = load_blocks(chain, &chunk)?;
+            replayer = replayer.apply_blocks(blocks, None)?;
+            chunk.clear();
+        }
+    }
+    let blocks = load_blocks(chain, &chunk)?;
+    replayer.apply_blocks(blocks, Some(target_slot))?;
+
+    Ok(())
+}
+
+fn load_blocks<T: BeaconChainTypes>(
+    chain: &BeaconChain<T>,
+    block_roots: &[Hash256],
+) -> Result<Vec<SignedBlindedBeaconBlock<T::EthSpec>>, ValidatorIncomeError> {
+    block_roots
+        .iter()
+        .map(|root| {
+            chain
+                .get_blinded_block(root)
+                .and_then(|maybe_block| {
+                    maybe_block.ok_or(BeaconChainError::MissingBeaconBlock(*root))
+                })
+                .map_err(Into::into)
+        })
+        .collect()
+}
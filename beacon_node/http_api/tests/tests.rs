@@ -1,9 +1,19 @@
-use beacon_chain::test_utils::RelativeSyncCommittee;
+use beacon_chain::engine_state_events::start_engine_state_event_service;
+use beacon_chain::kzg_utils::{blobs_to_data_column_sidecars, validate_blob};
+use beacon_chain::test_utils::{
+    generate_rand_block_and_blobs, generate_rand_block_and_data_columns, NumBlobs,
+    RelativeSyncCommittee,
+};
 use beacon_chain::{
     test_utils::{AttestationStrategy, BeaconChainHarness, BlockStrategy, EphemeralHarnessType},
     BeaconChain, ChainConfig, StateSkipConfig, WhenSlotSkipped,
 };
 use eth2::{
+    lighthouse::{
+        EngineStatus, ExcludedOperation, ExclusionCounts, ExclusionReason, PeerConnectionEvent,
+        PeerStatsSortKey, SimulatedPayloadStatus, SseDataColumnSidecar, SseEngineState,
+        SsePeerConnection, SseSyncState, ValidatorIncomeRequest,
+    },
     mixin::{RequestAccept, ResponseForkName, ResponseOptional},
     reqwest::RequestBuilder,
     types::{
@@ -13,6 +23,7 @@ use eth2::{
     Error::ServerMessage,
     StatusCode, Timeouts,
 };
+use execution_layer::expected_gas_limit;
 use execution_layer::test_utils::{
     MockBuilder, Operation, DEFAULT_BUILDER_PAYLOAD_VALUE_WEI, DEFAULT_MOCK_EL_PAYLOAD_VALUE_WEI,
 };
@@ -22,7 +33,7 @@ use http_api::{
     test_utils::{create_api_server, ApiServer},
     BlockId, StateId,
 };
-use lighthouse_network::{types::SyncState, Enr, EnrExt, PeerId};
+use lighthouse_network::{rpc::Protocol, types::SyncState, Enr, EnrExt, PeerId, RpcEvent};
 use logging::test_logger;
 use network::NetworkReceivers;
 use proto_array::ExecutionStatus;
@@ -33,9 +44,11 @@ use state_processing::per_slot_processing;
 use state_processing::state_advance::partial_state_advance;
 use std::convert::TryInto;
 use std::sync::Arc;
+use store::StoreOp;
 use tokio::time::Duration;
 use tree_hash::TreeHash;
 use types::application_domain::ApplicationDomain;
+use types::test_utils::{SeedableRng, XorShiftRng};
 use types::{
     attestation::AttestationBase, AggregateSignature, BitList, Domain, EthSpec, ExecutionBlockHash,
     Hash256, Keypair, MainnetEthSpec, RelativeEpoch, SelectionProof, SignedRoot, Slot,
@@ -1754,6 +1767,99 @@ impl ApiTester {
         self
     }
 
+    /// Test that blobs are reconstructed from the data columns stored after PeerDAS.
+    pub async fn test_get_blob_sidecars_from_data_columns(self) -> Self {
+        let mut rng = XorShiftRng::from_seed([42; 16]);
+        let (mut block, expected_blobs) =
+            generate_rand_block_and_blobs::<E>(ForkName::Deneb, NumBlobs::Number(3), &mut rng);
+
+        // Move the block into the PeerDAS fork, after which only data columns are stored.
+        let peer_das_slot = self
+            .chain
+            .spec
+            .eip7594_fork_epoch
+            .unwrap()
+            .start_slot(E::slots_per_epoch());
+        *block.message_mut().slot_mut() = peer_das_slot;
+        let block_root = block.canonical_root();
+
+        let blob_refs = expected_blobs
+            .iter()
+            .map(|blob_sidecar| &blob_sidecar.blob)
+            .collect::<Vec<_>>();
+        let data_columns =
+            blobs_to_data_column_sidecars(&blob_refs, &block, &self.chain.kzg, &self.chain.spec)
+                .unwrap();
+        assert_eq!(data_columns.len(), self.chain.spec.number_of_columns);
+
+        // Store the block along with only half of its data columns.
+        let stored_columns = &data_columns[..data_columns.len() / 2];
+        self.chain.store.put_block(&block_root, block).unwrap();
+        self.chain
+            .store
+            .do_atomically_with_block_and_blobs_cache(vec![StoreOp::PutDataColumns(
+                block_root,
+                stored_columns.to_vec(),
+            )])
+            .unwrap();
+        assert_eq!(self.chain.store.get_blobs(&block_root).unwrap(), None);
+
+        let blob_sidecars = self
+            .client
+            .get_blobs::<E>(CoreBlockId::Root(block_root), None)
+            .await
+            .unwrap()
+            .unwrap()
+            .data;
+
+        assert_eq!(blob_sidecars.len(), expected_blobs.len());
+        for (blob_sidecar, expected) in blob_sidecars.iter().zip(&expected_blobs) {
+            assert_eq!(blob_sidecar.index, expected.index);
+            assert_eq!(blob_sidecar.blob, expected.blob);
+            assert_eq!(blob_sidecar.kzg_commitment, expected.kzg_commitment);
+            assert_eq!(blob_sidecar.kzg_proof, expected.kzg_proof);
+            assert_eq!(blob_sidecar.block_root(), block_root);
+            assert!(blob_sidecar.verify_blob_sidecar_inclusion_proof());
+            validate_blob::<E>(
+                &self.chain.kzg,
+                &blob_sidecar.blob,
+                blob_sidecar.kzg_commitment,
+                blob_sidecar.kzg_proof,
+            )
+            .unwrap();
+        }
+
+        // Only the requested blobs are returned.
+        let blob_sidecars = self
+            .client
+            .get_blobs::<E>(CoreBlockId::Root(block_root), Some(&[2]))
+            .await
+            .unwrap()
+            .unwrap()
+            .data;
+        assert_eq!(blob_sidecars.len(), 1);
+        assert_eq!(blob_sidecars[0].blob, expected_blobs[2].blob);
+
+        // Blobs cannot be reconstructed from fewer than half of the data columns.
+        let deleted_column = stored_columns[0].index;
+        self.chain
+            .store
+            .do_atomically_with_block_and_blobs_cache(vec![StoreOp::DeleteDataColumns(
+                block_root,
+                vec![deleted_column],
+            )])
+            .unwrap();
+        assert_eq!(
+            self.client
+                .get_blobs::<E>(CoreBlockId::Root(block_root), None)
+                .await
+                .unwrap(),
+            None
+        );
+
+        self
+    }
+
     pub async fn test_beacon_blocks_attestations(self) -> Self {
         for block_id in self.interesting_block_ids() {
             let result = self
@@ -3755,6 +3861,39 @@ impl ApiTester {
         self
     }
 
+    /// Starts another mock builder and adds it to the relays of the execution layer. Validators
+    /// need to be registered again afterwards, as the relays are replaced.
+    pub fn add_mock_relay(&self) -> Arc<MockBuilder<E>> {
+        let mock_el = self.harness.mock_execution_layer.as_ref().unwrap();
+        let mock_el_url = SensitiveUrl::parse(mock_el.server.url().as_str()).unwrap();
+        let beacon_url = SensitiveUrl::parse(self.client.as_ref()).unwrap();
+        let (mock_builder, (addr, mock_builder_server)) = MockBuilder::new_for_testing(
+            mock_el_url,
+            beacon_url,
+            self.harness.spec.clone(),
+            self.harness.runtime.task_executor.clone(),
+        );
+        self.harness.runtime.task_executor.spawn(
+            async move { mock_builder_server.await },
+            "mock_relay_server",
+        );
+
+        let execution_layer = self.chain.execution_layer.as_ref().unwrap();
+        let relays = execution_layer.builder().unwrap();
+        let mut relay_urls = (0..relays.len())
+            .filter_map(|index| relays.endpoint(index))
+            .map(|endpoint| SensitiveUrl::parse(endpoint).unwrap())
+            .collect::<Vec<_>>();
+        relay_urls.push(
+            SensitiveUrl::parse(format!("http://127.0.0.1:{}", addr.port()).as_str()).unwrap(),
+        );
+        execution_layer
+            .set_builder_urls(relay_urls, None, None)
+            .unwrap();
+
+        Arc::new(mock_builder)
+    }
+
     pub async fn test_post_validator_register_validator(self) -> Self {
         let mut registrations = vec![];
         let mut fee_recipients = vec![];
@@ -4031,7 +4170,10 @@ impl ApiTester {
 
         let expected_fee_recipient = Address::from_low_u64_be(proposer_index as u64);
         assert_eq!(payload.fee_recipient(), expected_fee_recipient);
-        assert_eq!(payload.gas_limit(), 11_111_111);
+        assert_eq!(
+            payload.gas_limit(),
+            expected_gas_limit(16_384, 11_111_111).unwrap()
+        );
 
         self
     }
@@ -4085,7 +4227,10 @@ impl ApiTester {
 
         let expected_fee_recipient = Address::from_low_u64_be(proposer_index as u64);
         assert_eq!(payload.fee_recipient(), expected_fee_recipient);
-        assert_eq!(payload.gas_limit(), 11_111_111);
+        assert_eq!(
+            payload.gas_limit(),
+            expected_gas_limit(16_384, 11_111_111).unwrap()
+        );
 
         self
     }
@@ -4109,7 +4254,10 @@ impl ApiTester {
 
         let expected_fee_recipient = Address::from_low_u64_be(proposer_index as u64);
         assert_eq!(payload.fee_recipient(), expected_fee_recipient);
-        assert_eq!(payload.gas_limit(), 11_111_111);
+        assert_eq!(
+            payload.gas_limit(),
+            expected_gas_limit(16_384, 11_111_111).unwrap()
+        );
 
         // If this cache is empty, it indicates fallback was not used, so the payload came from the
         // mock builder.
@@ -5181,6 +5329,85 @@ impl ApiTester {
         self
     }
 
+    pub async fn test_payload_v3_chooses_most_valuable_relay(self) -> Self {
+        let relay = self.add_mock_relay();
+        let tester = self.test_post_validator_register_validator().await;
+
+        let value = Uint256::from(DEFAULT_BUILDER_PAYLOAD_VALUE_WEI + 1);
+        relay.add_operation(Operation::Value(value));
+
+        let slot = tester.chain.slot().unwrap();
+        let epoch = tester.chain.epoch().unwrap();
+
+        let (_, randao_reveal) = tester.get_test_randao(slot, epoch).await;
+
+        let (payload_type, metadata) = tester
+            .client
+            .get_validator_blocks_v3::<E>(slot, &randao_reveal, None, None)
+            .await
+            .unwrap();
+        Self::check_block_v3_metadata(&metadata, &payload_type);
+
+        match payload_type.data {
+            ProduceBlockV3Response::Blinded(_) => (),
+            ProduceBlockV3Response::Full(_) => panic!("Expecting a blinded payload"),
+        };
+        assert_eq!(metadata.execution_payload_value, value);
+
+        let relays = tester
+            .client
+            .get_lighthouse_eth1_relays()
+            .await
+            .unwrap()
+            .data;
+        assert_eq!(relays.len(), 2);
+        assert!(relays.iter().all(|relay| relay.bids == 1));
+        assert_eq!(relays[0].bids_selected, 0);
+        assert_eq!(relays[1].bids_selected, 1);
+
+        tester
+    }
+
+    pub async fn test_payload_v3_prefers_relay_matching_registration(self) -> Self {
+        let relay = self.add_mock_relay();
+        let tester = self.test_post_validator_register_validator().await;
+
+        // The more valuable bid does not pay the registered fee recipient.
+        relay.add_operation(Operation::Value(Uint256::from(
+            DEFAULT_BUILDER_PAYLOAD_VALUE_WEI + 1,
+        )));
+        relay.add_operation(Operation::FeeRecipient(Address::repeat_byte(42)));
+
+        let slot = tester.chain.slot().unwrap();
+        let epoch = tester.chain.epoch().unwrap();
+
+        let (proposer_index, randao_reveal) = tester.get_test_randao(slot, epoch).await;
+
+        let (payload_type, metadata) = tester
+            .client
+            .get_validator_blocks_v3::<E>(slot, &randao_reveal, None, None)
+            .await
+            .unwrap();
+        Self::check_block_v3_metadata(&metadata, &payload_type);
+
+        let payload: BlindedPayload<E> = match payload_type.data {
+            ProduceBlockV3Response::Blinded(payload) => {
+                payload.body().execution_payload().unwrap().into()
+            }
+            ProduceBlockV3Response::Full(_) => panic!("Expecting a blinded payload"),
+        };
+        assert_eq!(
+            payload.fee_recipient(),
+            Address::from_low_u64_be(proposer_index as u64)
+        );
+        assert_eq!(
+            metadata.execution_payload_value,
+            Uint256::from(DEFAULT_BUILDER_PAYLOAD_VALUE_WEI)
+        );
+
+        tester
+    }
+
     pub async fn test_local_payload_chosen_when_equally_profitable(self) -> Self {
         // Mutate value.
         self.mock_builder
@@ -5540,6 +5767,262 @@ impl ApiTester {
         self
     }
 
+    pub async fn test_get_lighthouse_operation_pool_packing_reports(mut self) -> Self {
+        // Pack the head slot with an exit from a validator which is also being slashed, so that
+        // the exit is superseded by the slashing.
+        let exit = self
+            .harness
+            .make_voluntary_exit(2, self.chain.epoch().unwrap());
+        self = self.test_post_beacon_pool_proposer_slashings_valid().await;
+        self.client
+            .post_beacon_pool_voluntary_exits(&exit)
+            .await
+            .unwrap();
+
+        let state = self.chain.head_beacon_state_cloned();
+        let head_slot = state.slot();
+        let (proposer_slashings, _, voluntary_exits) = self
+            .chain
+            .op_pool
+            .get_slashings_and_exits(&state, &self.chain.spec);
+        assert_eq!(proposer_slashings, vec![self.proposer_slashing.clone()]);
+        assert!(voluntary_exits.is_empty());
+
+        let report = self
+            .client
+            .get_lighthouse_operation_pool_packing_report(head_slot)
+            .await
+            .unwrap()
+            .expect("a report should exist for the packed slot")
+            .data;
+        assert_eq!(report.slot, head_slot);
+        assert_eq!(
+            report.parent_root,
+            *state.get_block_root(head_slot - 1).unwrap()
+        );
+        assert!(report.proposer_slashings.is_empty());
+        assert!(report.attester_slashings.is_empty());
+        assert_eq!(
+            report.voluntary_exits,
+            vec![ExcludedOperation {
+                validator_indices: vec![2],
+                reason: ExclusionReason::Superseded,
+            }]
+        );
+
+        let summaries = self
+            .client
+            .get_lighthouse_operation_pool_packing_reports()
+            .await
+            .unwrap()
+            .data;
+        let summary = summaries
+            .iter()
+            .find(|summary| summary.slot == head_slot)
+            .expect("the packed slot should be summarised");
+        assert_eq!(summary.parent_root, report.parent_root);
+        assert_eq!(
+            summary.voluntary_exits,
+            ExclusionCounts {
+                superseded: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(summary.proposer_slashings, ExclusionCounts::default());
+
+        // No block has been packed for a future slot.
+        let report = self
+            .client
+            .get_lighthouse_operation_pool_packing_report(head_slot + 1024)
+            .await
+            .unwrap();
+        assert!(report.is_none());
+
+        self
+    }
+
+    pub async fn test_get_lighthouse_peer_stats(self) -> Self {
+        self.ctx.network_globals.as_ref().unwrap().rpc_stats.record(
+            &self.external_peer_id,
+            Protocol::Status,
+            RpcEvent::RequestReceived,
+            42,
+        );
+
+        let result = self
+            .client
+            .get_lighthouse_peer_stats(&self.external_peer_id.to_string())
+            .await
+            .unwrap()
+            .unwrap()
+            .data;
+
+        assert_eq!(result.peer_id, self.external_peer_id.to_string());
+        assert_eq!(result.useful_gossip_ratio, None);
+        let status = result.stats.rpc[&Protocol::Status.to_string()];
+        assert_eq!((status.requests_in, status.bytes_in), (1, 42));
+
+        // An unknown peer is not found.
+        let unknown_peer_id = PeerId::random();
+        assert!(self
+            .client
+            .get_lighthouse_peer_stats(&unknown_peer_id.to_string())
+            .await
+            .unwrap()
+            .is_none());
+
+        self
+    }
+
+    pub async fn test_get_lighthouse_peers_stats(self) -> Self {
+        for sort_by in [
+            PeerStatsSortKey::BytesIn,
+            PeerStatsSortKey::BytesOut,
+            PeerStatsSortKey::RpcErrors,
+            PeerStatsSortKey::Duplicates,
+        ] {
+            let result = self
+                .client
+                .get_lighthouse_peers_stats(sort_by, None)
+                .await
+                .unwrap()
+                .data;
+            assert!(result
+                .iter()
+                .any(|summary| summary.peer_id == self.external_peer_id.to_string()));
+            assert!(result
+                .windows(2)
+                .all(|pair| sort_by.key(&pair[0]) >= sort_by.key(&pair[1])));
+        }
+
+        let result = self
+            .client
+            .get_lighthouse_peers_stats(PeerStatsSortKey::BytesIn, Some(0))
+            .await
+            .unwrap()
+            .data;
+        assert!(result.is_empty());
+
+        self
+    }
+
+    pub async fn test_get_lighthouse_beacon_data_columns(self) -> Self {
+        let head = self.chain.canonical_head.cached_head();
+        let response = self
+            .client
+            .get_lighthouse_beacon_data_columns::<E>(CoreBlockId::Head)
+            .await
+            .unwrap()
+            .unwrap()
+            .data;
+
+        assert_eq!(response.block_root, head.head_block_root());
+        assert_eq!(response.slot, head.head_slot());
+        assert_eq!(
+            response.custody.number_of_columns,
+            self.chain.spec.number_of_columns as u64
+        );
+        assert_eq!(
+            response.custody.custody_columns,
+            self.ctx.network_globals.as_ref().unwrap().sampling_columns
+        );
+
+        // Data columns are not stored prior to PeerDAS.
+        assert!(response.data_columns.is_empty());
+        assert!(response.custody.stored_columns.is_empty());
+        assert!(!response.custody.can_reconstruct_blobs);
+
+        // Unknown blocks are not found.
+        assert!(self
+            .client
+            .get_lighthouse_beacon_data_columns::<E>(CoreBlockId::Root(Hash256::repeat_byte(1)))
+            .await
+            .unwrap()
+            .is_none());
+
+        self
+    }
+
+    pub async fn test_get_lighthouse_beacon_blocks_range(self) -> Self {
+        let head_slot = self.chain.canonical_head.cached_head().head_slot();
+        let start_slot = Slot::new(0);
+        let expected_roots = (start_slot.as_u64()..=head_slot.as_u64())
+            .filter_map(|slot| {
+                self.chain
+                    .block_root_at_slot(Slot::new(slot), WhenSlotSkipped::None)
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let full_blocks = self
+            .client
+            .get_lighthouse_beacon_blocks_range::<E, FullPayload<E>>(
+                start_slot,
+                head_slot,
+                false,
+                &self.chain.spec,
+            )
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(
+            full_blocks
+                .iter()
+                .map(|item| item.block.canonical_root())
+                .collect::<Vec<_>>(),
+            expected_roots
+        );
+        for item in &full_blocks {
+            assert!(item.blobs.is_none());
+            let expected = self
+                .chain
+                .get_block(&item.block.canonical_root())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(*item.block, expected);
+        }
+
+        let blinded_blocks = self
+            .client
+            .get_lighthouse_beacon_blocks_range::<E, BlindedPayload<E>>(
+                start_slot,
+                head_slot,
+                true,
+                &self.chain.spec,
+            )
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(blinded_blocks.len(), full_blocks.len());
+        for (blinded, full) in blinded_blocks.iter().zip(&full_blocks) {
+            assert_eq!(*blinded.block, full.block.clone_as_blinded());
+            let expected_blobs = self.chain.get_blobs(&full.block.canonical_root()).unwrap();
+            assert_eq!(blinded.blobs.as_ref(), Some(&expected_blobs));
+        }
+
+        // Ranges which end after the head are rejected.
+        let result = self
+            .client
+            .get_lighthouse_beacon_blocks_range::<E, FullPayload<E>>(
+                start_slot,
+                head_slot + 1,
+                false,
+                &self.chain.spec,
+            )
+            .await;
+        assert_eq!(
+            result.err().unwrap().status(),
+            Some(StatusCode::BAD_REQUEST)
+        );
+
+        self
+    }
+
     pub async fn test_post_lighthouse_database_reconstruct(self) -> Self {
         let response = self
             .client
@@ -5550,6 +6033,283 @@ impl ApiTester {
         self
     }
 
+    pub async fn test_post_lighthouse_beacon_blocks_simulate(self) -> Self {
+        let block = self.next_block.signed_block().message().clone();
+
+        let simulation = self
+            .client
+            .post_lighthouse_beacon_blocks_simulate(&block)
+            .await
+            .unwrap();
+
+        assert!(simulation.valid, "{:?}", simulation.errors);
+        assert!(simulation.errors.is_empty());
+        assert_eq!(simulation.slot, block.slot());
+        assert_eq!(simulation.post_state_root, Some(block.state_root()));
+        assert_eq!(
+            simulation.proposer_reward.unwrap().proposer_index,
+            block.proposer_index()
+        );
+
+        // The block must not have been imported.
+        assert!(self
+            .chain
+            .get_blinded_block(&block.canonical_root())
+            .unwrap()
+            .is_none());
+
+        // A block with an incorrect state root is invalid.
+        let mut invalid_block = block.clone();
+        *invalid_block.state_root_mut() = Hash256::repeat_byte(42);
+        let simulation = self
+            .client
+            .post_lighthouse_beacon_blocks_simulate(&invalid_block)
+            .await
+            .unwrap();
+
+        assert!(!simulation.valid);
+        assert_eq!(simulation.errors.len(), 1);
+        assert_eq!(simulation.post_state_root, Some(block.state_root()));
+
+        self
+    }
+
+    pub async fn test_post_lighthouse_beacon_blocks_simulate_ssz(self) -> Self {
+        let block = self.next_block.signed_block().message().clone();
+
+        let json_simulation = self
+            .client
+            .post_lighthouse_beacon_blocks_simulate(&block)
+            .await
+            .unwrap();
+        let simulation = self
+            .client
+            .post_lighthouse_beacon_blocks_simulate_ssz(&block)
+            .await
+            .unwrap();
+
+        assert!(simulation.valid, "{:?}", simulation.errors);
+        assert_eq!(simulation, json_simulation);
+
+        // A block with an incorrect state root is invalid.
+        let mut invalid_block = block.clone();
+        *invalid_block.state_root_mut() = Hash256::repeat_byte(42);
+        let simulation = self
+            .client
+            .post_lighthouse_beacon_blocks_simulate_ssz(&invalid_block)
+            .await
+            .unwrap();
+
+        assert!(!simulation.valid);
+        assert_eq!(simulation.errors.len(), 1);
+        assert_eq!(simulation.post_state_root, Some(block.state_root()));
+
+        self
+    }
+
+    pub async fn test_post_lighthouse_beacon_blocks_simulate_blinded(self) -> Self {
+        let block = self.next_block.signed_block().message().clone_as_blinded();
+        let expected_payload_status = if block.body().execution_payload().is_ok() {
+            SimulatedPayloadStatus::Unavailable
+        } else {
+            SimulatedPayloadStatus::NotApplicable
+        };
+
+        for simulation in [
+            self.client
+                .post_lighthouse_beacon_blocks_simulate(&block)
+                .await
+                .unwrap(),
+            self.client
+                .post_lighthouse_beacon_blocks_simulate_ssz(&block)
+                .await
+                .unwrap(),
+        ] {
+            assert!(simulation.valid, "{:?}", simulation.errors);
+            assert_eq!(simulation.slot, block.slot());
+            assert_eq!(simulation.post_state_root, Some(block.state_root()));
+            assert_eq!(simulation.execution_payload, expected_payload_status);
+        }
+
+        // The block must not have been imported.
+        assert!(self
+            .chain
+            .get_blinded_block(&block.canonical_root())
+            .unwrap()
+            .is_none());
+
+        self
+    }
+
+    pub async fn test_post_lighthouse_beacon_states_fields(self) -> Self {
+        let head_state = self.chain.head_beacon_state_cloned();
+        let head_state_root = self.chain.canonical_head.cached_head().head_state_root();
+        let paths = [
+            "slot",
+            "finalized_checkpoint",
+            "finalized_checkpoint.epoch",
+            "validators[1].effective_balance",
+            "validators[2..4].slashed",
+            "balances[0..5]",
+            "block_roots[3]",
+            "eth1_data.deposit_count",
+        ]
+        .map(String::from);
+
+        let response = self
+            .client
+            .post_lighthouse_beacon_states_fields(CoreStateId::Head, &paths)
+            .await
+            .unwrap()
+            .unwrap()
+            .data;
+
+        assert_eq!(response.slot, head_state.slot());
+        assert_eq!(response.state_root, head_state_root);
+        assert_eq!(response.fields.len(), 13);
+
+        for field in &response.fields {
+            let depth = field.proof.len();
+            assert!(
+                merkle_proof::verify_merkle_proof(
+                    field.leaf,
+                    &field.proof,
+                    depth,
+                    (field.gindex - (1 << depth)) as usize,
+                    head_state_root,
+                ),
+                "invalid proof for {}",
+                field.path
+            );
+        }
+
+        let value = |path: &str| {
+            response
+                .fields
+                .iter()
+                .find(|field| field.path == path)
+                .unwrap()
+                .value
+                .clone()
+        };
+        assert_eq!(
+            value("finalized_checkpoint"),
+            serde_json::to_value(head_state.finalized_checkpoint()).unwrap()
+        );
+        assert_eq!(
+            value("validators[1].effective_balance"),
+            head_state
+                .validators()
+                .get(1)
+                .unwrap()
+                .effective_balance
+                .to_string()
+        );
+        assert_eq!(
+            value("balances[4]"),
+            head_state.balances().get(4).unwrap().to_string()
+        );
+        assert_eq!(
+            value("block_roots[3]"),
+            serde_json::to_value(head_state.block_roots().get(3).unwrap()).unwrap()
+        );
+        assert_eq!(
+            value("validators[3].slashed"),
+            serde_json::to_value(head_state.validators().get(3).unwrap().slashed).unwrap()
+        );
+        assert_eq!(
+            value("eth1_data.deposit_count"),
+            head_state.eth1_data().deposit_count.to_string()
+        );
+
+        for invalid_path in [
+            "unknown_field",
+            "slot[0]",
+            "balances[1].amount",
+            "balances[5..2]",
+        ] {
+            let result = self
+                .client
+                .post_lighthouse_beacon_states_fields(
+                    CoreStateId::Head,
+                    &[invalid_path.to_string()],
+                )
+                .await;
+            assert_eq!(result.unwrap_err().status().unwrap(), 400);
+        }
+
+        self
+    }
+
+    pub async fn test_post_lighthouse_analysis_validator_income(self) -> Self {
+        let validators = vec![0, 1, 5, VALIDATOR_COUNT as u64 - 1];
+        let request = ValidatorIncomeRequest {
+            start_epoch: Epoch::new(1),
+            end_epoch: Epoch::new(3),
+            validators: validators.clone(),
+        };
+
+        let epochs = self
+            .client
+            .post_lighthouse_analysis_validator_income(&request)
+            .await
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            epochs.iter().map(|epoch| epoch.epoch).collect::<Vec<_>>(),
+            vec![Epoch::new(1), Epoch::new(2), Epoch::new(3)]
+        );
+
+        for income in &epochs {
+            // The rewards for attestations made in the previous epoch are applied by the epoch
+            // transition at the end of `income.epoch`.
+            let rewards = self
+                .chain
+                .compute_attestation_rewards(
+                    income.epoch - 1,
+                    validators.iter().map(|&i| ValidatorId::Index(i)).collect(),
+                )
+                .unwrap()
+                .total_rewards;
+            let end_state = self
+                .chain
+                .state_at_slot(
+                    (income.epoch + 1).start_slot(E::slots_per_epoch()),
+                    StateSkipConfig::WithoutStateRoots,
+                )
+                .unwrap();
+
+            assert_eq!(income.validators.len(), validators.len());
+            for (validator, rewards) in income.validators.iter().zip(&rewards) {
+                assert_eq!(validator.validator_index, rewards.validator_index);
+                assert_eq!(validator.head, rewards.head);
+                assert_eq!(validator.target, rewards.target);
+                assert_eq!(validator.source, rewards.source);
+                assert_eq!(
+                    validator.inclusion_delay,
+                    rewards
+                        .inclusion_delay
+                        .as_ref()
+                        .map_or(0, |quoted| quoted.value as i64)
+                );
+                assert_eq!(validator.inactivity, rewards.inactivity);
+                assert_eq!(
+                    validator.balance,
+                    *end_state
+                        .balances()
+                        .get(validator.validator_index as usize)
+                        .unwrap()
+                );
+            }
+        }
+
+        self
+    }
+
     pub async fn test_post_lighthouse_liveness(self) -> Self {
         let epoch = self.chain.epoch().unwrap();
         let head_state = self.chain.head_beacon_state_cloned();
@@ -5929,6 +6689,169 @@ impl ApiTester {
         self
     }
 
+    pub async fn test_get_events_lighthouse_data_column_sidecar(self) -> Self {
+        let topics = vec![EventTopic::LighthouseDataColumnSidecar];
+        let mut events_future = self
+            .client
+            .get_events::<E>(topics.as_slice())
+            .await
+            .unwrap();
+
+        let mut rng = XorShiftRng::from_seed([42; 16]);
+        let (_, data_columns) = generate_rand_block_and_data_columns::<E>(
+            ForkName::Deneb,
+            NumBlobs::Number(1),
+            &mut rng,
+            &self.chain.spec,
+        );
+        // Build the columns atop the head so that they are not rejected for an unknown parent.
+        let head_root = self.chain.head_beacon_block_root();
+        let data_columns = data_columns
+            .iter()
+            .take(4)
+            .map(|data_column| {
+                let mut data_column = (**data_column).clone();
+                data_column.signed_block_header.message.parent_root = head_root;
+                Arc::new(data_column)
+            })
+            .collect::<Vec<_>>();
+        let expected = data_columns
+            .iter()
+            .map(|data_column| {
+                EventKind::LighthouseDataColumnSidecar(
+                    SseDataColumnSidecar::from_data_column_sidecar(data_column),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // The random columns are not available for import, but events are emitted for them when
+        // they are received.
+        let _ = self.chain.process_rpc_custody_columns(data_columns).await;
+
+        let events = poll_events(
+            &mut events_future,
+            expected.len(),
+            Duration::from_millis(10000),
+        )
+        .await;
+        assert_eq!(events, expected);
+
+        self
+    }
+
+    pub async fn test_get_events_lighthouse_sync_state(self) -> Self {
+        let topics = vec![EventTopic::LighthouseSyncState];
+        let mut events_future = self
+            .client
+            .get_events::<E>(topics.as_slice())
+            .await
+            .unwrap();
+
+        // The sync manager is not running in the tester, so register the event as it would.
+        let event_handler = self.chain.event_handler.as_ref().unwrap();
+        assert!(event_handler.has_sync_state_subscribers());
+        let expected = EventKind::LighthouseSyncState(SseSyncState {
+            previous: SyncState::Synced,
+            current: SyncState::SyncingHead {
+                start_slot: Slot::new(1),
+                target_slot: Slot::new(64),
+            },
+            execution_optimistic: false,
+        });
+        event_handler.register(expected.clone());
+
+        let events = poll_events(&mut events_future, 1, Duration::from_millis(10000)).await;
+        assert_eq!(events.as_slice(), &[expected]);
+
+        self
+    }
+
+    pub async fn test_get_events_lighthouse_engine_state(self) -> Self {
+        let topics = vec![EventTopic::LighthouseEngineState];
+        let mut events_future = self
+            .client
+            .get_events::<E>(topics.as_slice())
+            .await
+            .unwrap();
+
+        let execution_layer = self.chain.execution_layer.clone().unwrap();
+        let mock_el = self.harness.mock_execution_layer.as_ref().unwrap();
+        execution_layer.upcheck().await;
+        start_engine_state_event_service(
+            self.harness.runtime.task_executor.clone(),
+            self.chain.clone(),
+        );
+
+        // The current state is published when the service starts.
+        let events = poll_events(&mut events_future, 1, Duration::from_millis(10000)).await;
+        assert_eq!(
+            events.as_slice(),
+            &[EventKind::LighthouseEngineState(SseEngineState {
+                previous: None,
+                current: EngineStatus::Synced,
+            })]
+        );
+
+        mock_el.server.set_syncing_response(Ok(true));
+        execution_layer.upcheck().await;
+        let events = poll_events(&mut events_future, 1, Duration::from_millis(10000)).await;
+        assert_eq!(
+            events.as_slice(),
+            &[EventKind::LighthouseEngineState(SseEngineState {
+                previous: Some(EngineStatus::Synced),
+                current: EngineStatus::Syncing,
+            })]
+        );
+
+        mock_el.server.set_syncing_response(Ok(false));
+        execution_layer.upcheck().await;
+        let events = poll_events(&mut events_future, 1, Duration::from_millis(10000)).await;
+        assert_eq!(
+            events.as_slice(),
+            &[EventKind::LighthouseEngineState(SseEngineState {
+                previous: Some(EngineStatus::Syncing),
+                current: EngineStatus::Synced,
+            })]
+        );
+
+        self
+    }
+
+    pub async fn test_get_events_lighthouse_peer_connection(self) -> Self {
+        let topics = vec![EventTopic::LighthousePeerConnection];
+        let mut events_future = self
+            .client
+            .get_events::<E>(topics.as_slice())
+            .await
+            .unwrap();
+
+        // The network service is not running in the tester, so register the events as it would.
+        let event_handler = self.chain.event_handler.as_ref().unwrap();
+        assert!(event_handler.has_peer_connection_subscribers());
+        let expected = vec![
+            EventKind::LighthousePeerConnection(SsePeerConnection {
+                peer_id: self.external_peer_id.to_string(),
+                event: PeerConnectionEvent::Connected,
+                direction: Some(PeerDirection::Inbound),
+                client: Some("Lighthouse".to_string()),
+            }),
+            EventKind::LighthousePeerConnection(SsePeerConnection {
+                peer_id: self.external_peer_id.to_string(),
+                event: PeerConnectionEvent::Disconnected,
+                direction: None,
+                client: None,
+            }),
+        ];
+        for event in &expected {
+            event_handler.register(event.clone());
+        }
+
+        let events = poll_events(&mut events_future, 2, Duration::from_millis(10000)).await;
+        assert_eq!(events, expected);
+
+        self
+    }
+
     pub async fn test_get_events_from_genesis(self) -> Self {
         let topics = vec![EventTopic::Block, EventTopic::Head];
         let mut events_future = self
@@ -6041,6 +6964,20 @@ async fn get_events_altair() {
         .await;
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn get_events_lighthouse() {
+    ApiTester::new()
+        .await
+        .test_get_events_lighthouse_data_column_sidecar()
+        .await
+        .test_get_events_lighthouse_sync_state()
+        .await
+        .test_get_events_lighthouse_engine_state()
+        .await
+        .test_get_events_lighthouse_peer_connection()
+        .await;
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn get_events_from_genesis() {
     ApiTester::new_from_genesis()
@@ -6711,6 +7648,22 @@ async fn post_validator_register_fee_recipient_mutation_v3() {
         .await;
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn get_blinded_block_v3_most_valuable_relay() {
+    ApiTester::new_mev_tester()
+        .await
+        .test_payload_v3_chooses_most_valuable_relay()
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn get_blinded_block_v3_relay_matching_registration() {
+    ApiTester::new_mev_tester()
+        .await
+        .test_payload_v3_prefers_relay_matching_registration()
+        .await;
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn get_blinded_block_invalid_parent_hash() {
     ApiTester::new_mev_tester()
@@ -6971,6 +7924,23 @@ async fn get_blob_sidecars_pre_deneb() {
         .await;
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn get_blob_sidecars_from_data_columns() {
+    let mut config = ApiTesterConfig::default();
+    config.spec.altair_fork_epoch = Some(Epoch::new(0));
+    config.spec.bellatrix_fork_epoch = Some(Epoch::new(0));
+    config.spec.capella_fork_epoch = Some(Epoch::new(0));
+    config.spec.deneb_fork_epoch = Some(Epoch::new(0));
+    // Schedule PeerDAS beyond the end of the test chain, so that its blocks are imported with
+    // blobs.
+    config.spec.eip7594_fork_epoch = Some(Epoch::new(1000));
+
+    ApiTester::new_from_config(config)
+        .await
+        .test_get_blob_sidecars_from_data_columns()
+        .await;
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn post_validator_liveness_epoch() {
     ApiTester::new()
@@ -6979,6 +7949,14 @@ async fn post_validator_liveness_epoch() {
         .await;
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn lighthouse_operation_pool_packing_reports() {
+    ApiTester::new()
+        .await
+        .test_get_lighthouse_operation_pool_packing_reports()
+        .await;
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn lighthouse_endpoints() {
     ApiTester::new()
@@ -7003,12 +7981,46 @@ async fn lighthouse_endpoints() {
         .await
         .test_get_lighthouse_database_info()
         .await
+        .test_get_lighthouse_peer_stats()
+        .await
+        .test_get_lighthouse_peers_stats()
+        .await
+        .test_get_lighthouse_beacon_data_columns()
+        .await
+        .test_get_lighthouse_beacon_blocks_range()
+        .await
         .test_post_lighthouse_database_reconstruct()
         .await
+        .test_post_lighthouse_beacon_blocks_simulate()
+        .await
+        .test_post_lighthouse_beacon_blocks_simulate_ssz()
+        .await
+        .test_post_lighthouse_beacon_blocks_simulate_blinded()
+        .await
+        .test_post_lighthouse_beacon_states_fields()
+        .await
         .test_post_lighthouse_liveness()
         .await;
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn lighthouse_validator_income() {
+    ApiTester::new_from_config(ApiTesterConfig::default().retain_historic_states())
+        .await
+        .test_post_lighthouse_analysis_validator_income()
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn lighthouse_validator_income_altair() {
+    let mut config = ApiTesterConfig::default().retain_historic_states();
+    config.spec.altair_fork_epoch = Some(Epoch::new(0));
+    ApiTester::new_from_config(config)
+        .await
+        .test_post_lighthouse_analysis_validator_income()
+        .await;
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn optimistic_responses() {
     ApiTester::new_with_hard_forks(true, true)
@@ -67,13 +67,15 @@ use super::types::{
     ControlAction, FailedMessages, Message, MessageAcceptance, MessageId, PeerInfo, RawMessage,
     Subscription, SubscriptionAction,
 };
-use super::types::{Graft, IHave, IWant, PeerConnections, PeerKind, Prune};
+use super::types::{Graft, IHave, IWant, PeerConnections, PeerKind, Prune, TopicTraffic};
 use super::{backoff::BackoffStorage, types::RpcSender};
 use super::{
     config::{Config, ValidationMode},
     types::RpcOut,
 };
-use super::{PublishError, SubscriptionError, TopicScoreParams, ValidationError};
+use super::{
+    ConfigBuilderError, PublishError, SubscriptionError, TopicScoreParams, ValidationError,
+};
 use quick_protobuf::{MessageWrite, Writer};
 use std::{cmp::Ordering::Equal, fmt::Debug};
 
@@ -522,6 +524,38 @@ where
         self.connected_peers.iter().map(|(k, v)| (k, &v.kind))
     }
 
+    /// Returns the messages exchanged with a peer on each topic over its current connection, if
+    /// it is connected.
+    pub fn peer_traffic(&self, peer_id: &PeerId) -> Option<&HashMap<TopicHash, TopicTraffic>> {
+        self.connected_peers.get(peer_id).map(|peer| &peer.traffic)
+    }
+
+    /// Updates the mesh parameters. The meshes are grown or pruned to the new parameters at the
+    /// next heartbeat.
+    ///
+    /// The parameters are subject to the same constraints as when building a [`Config`].
+    pub fn set_mesh_params(
+        &mut self,
+        mesh_n_low: usize,
+        mesh_n: usize,
+        mesh_n_high: usize,
+        mesh_outbound_min: usize,
+        gossip_lazy: usize,
+    ) -> Result<(), ConfigBuilderError> {
+        self.config.set_mesh_params(
+            mesh_n_low,
+            mesh_n,
+            mesh_n_high,
+            mesh_outbound_min,
+            gossip_lazy,
+        )
+    }
+
+    /// Updates the size above which we send IDONTWANT messages for received messages.
+    pub fn set_idontwant_message_size_threshold(&mut self, size: usize) {
+        self.config.set_idontwant_message_size_threshold(size);
+    }
+
     /// Returns the gossipsub score for a given peer, if one exists.
     pub fn peer_score(&self, peer_id: &PeerId) -> Option<f64> {
         self.peer_score
@@ -743,15 +777,20 @@ where
 
         // Send to peers we know are subscribed to the topic.
         let mut publish_failed = true;
+        let priority = self.config.message_priority(&raw_message.topic);
         for peer_id in recipient_peers.iter() {
             if let Some(peer) = self.connected_peers.get_mut(peer_id) {
                 tracing::trace!(peer=%peer_id, "Sending message to peer");
                 match peer.sender.publish(
                     raw_message.clone(),
+                    priority,
                     self.config.publish_queue_duration(),
                     self.metrics.as_mut(),
                 ) {
-                    Ok(_) => publish_failed = false,
+                    Ok(()) => {
+                        peer.record_sent(&raw_message.topic, raw_message.raw_protobuf_len());
+                        publish_failed = false;
+                    }
                     Err(_) => {
                         self.failed_messages.entry(*peer_id).or_default().priority += 1;
 
@@ -1391,15 +1430,19 @@ where
                     }
 
                     tracing::debug!(peer=%peer_id, "IWANT: Sending cached messages to peer");
-                    if peer
-                        .sender
-                        .forward(
-                            msg,
-                            self.config.forward_queue_duration(),
-                            self.metrics.as_mut(),
-                        )
-                        .is_err()
-                    {
+                    let (topic, bytes) = (msg.topic.clone(), msg.raw_protobuf_len());
+                    let queued = peer.sender.forward(
+                        msg,
+                        self.config.message_priority(&topic),
+                        self.config.forward_queue_duration(),
+                        self.metrics.as_mut(),
+                    );
+                    if queued.is_ok() {
+                        peer.record_sent(&topic, bytes);
+                    }
+                    // The queue was full, either this message or the oldest one in the queue was
+                    // dropped.
+                    if !matches!(queued, Ok(None)) {
                         // Downscore the peer
                         if let Some((peer_score, ..)) = &mut self.peer_score {
                             peer_score.failed_message_slow_peer(peer_id);
@@ -1833,7 +1876,12 @@ where
             return;
         }
 
-        if !self.duplicate_cache.insert(msg_id.clone()) {
+        let duplicate = !self.duplicate_cache.insert(msg_id.clone());
+        if let Some(peer) = self.connected_peers.get_mut(propagation_source) {
+            peer.record_received(&raw_message, duplicate);
+        }
+
+        if duplicate {
             tracing::debug!(message=%msg_id, "Message already received, ignoring");
             if let Some((peer_score, ..)) = &mut self.peer_score {
                 peer_score.duplicated_message(propagation_source, &msg_id, &message.topic);
@@ -2131,7 +2179,7 @@ where
         // steady-state size of the queues.
         if let Some(m) = &mut self.metrics {
             for sender_queue in self.connected_peers.values_mut().map(|v| &v.sender) {
-                m.observe_priority_queue_size(sender_queue.priority_len());
+                m.observe_priority_queue_size(sender_queue.messages_len());
                 m.observe_non_priority_queue_size(sender_queue.non_priority_len());
             }
         }
@@ -2833,6 +2881,7 @@ where
 
         // forward the message to peers
         if !recipient_peers.is_empty() {
+            let priority = self.config.message_priority(&message.topic);
             for peer_id in recipient_peers.iter() {
                 if let Some(peer) = self.connected_peers.get_mut(peer_id) {
                     if peer.dont_send_received.get(msg_id).is_some() {
@@ -2841,15 +2890,18 @@ where
                     }
 
                     tracing::debug!(%peer_id, message=%msg_id, "Sending message to peer");
-                    if peer
-                        .sender
-                        .forward(
-                            message.clone(),
-                            self.config.forward_queue_duration(),
-                            self.metrics.as_mut(),
-                        )
-                        .is_err()
-                    {
+                    let queued = peer.sender.forward(
+                        message.clone(),
+                        priority,
+                        self.config.forward_queue_duration(),
+                        self.metrics.as_mut(),
+                    );
+                    if queued.is_ok() {
+                        peer.record_sent(&message.topic, message.raw_protobuf_len());
+                    }
+                    // The queue was full, either this message or the oldest one in the queue was
+                    // dropped.
+                    if !matches!(queued, Ok(None)) {
                         // Downscore the peer
                         if let Some((peer_score, ..)) = &mut self.peer_score {
                             peer_score.failed_message_slow_peer(peer_id);
@@ -3187,10 +3239,14 @@ where
             .or_insert(PeerConnections {
                 kind: PeerKind::Floodsub,
                 connections: vec![],
-                sender: RpcSender::new(self.config.connection_handler_queue_len()),
+                sender: RpcSender::new(
+                    &self.config,
+                    self.metrics.as_ref().map(|m| m.message_queue_duration()),
+                ),
                 topics: Default::default(),
                 dont_send_received: LinkedHashMap::new(),
                 dont_send_sent: LinkedHashMap::new(),
+                traffic: HashMap::new(),
             });
         // Add the new connection
         connected_peer.connections.push(connection_id);
@@ -3220,10 +3276,14 @@ where
             .or_insert(PeerConnections {
                 kind: PeerKind::Floodsub,
                 connections: vec![],
-                sender: RpcSender::new(self.config.connection_handler_queue_len()),
+                sender: RpcSender::new(
+                    &self.config,
+                    self.metrics.as_ref().map(|m| m.message_queue_duration()),
+                ),
                 topics: Default::default(),
                 dont_send_received: LinkedHashMap::new(),
                 dont_send_sent: LinkedHashMap::new(),
+                traffic: HashMap::new(),
             });
         // Add the new connection
         connected_peer.connections.push(connection_id);
@@ -22,9 +22,10 @@
 
 use super::*;
 use crate::subscription_filter::WhitelistSubscriptionFilter;
-use crate::types::RpcReceiver;
+use crate::types::{DropPolicy, MessagePriority, RpcReceiver};
 use crate::{config::ConfigBuilder, types::Rpc, IdentTopic as Topic};
 use byteorder::{BigEndian, ByteOrder};
+use futures::FutureExt;
 use libp2p::core::ConnectedPoint;
 use rand::Rng;
 use std::net::Ipv4Addr;
@@ -229,7 +230,7 @@ where
         }
     };
 
-    let sender = RpcSender::new(gs.config.connection_handler_queue_len());
+    let sender = RpcSender::new(&gs.config, None);
     let receiver = sender.new_receiver();
     let connection_id = ConnectionId::new_unchecked(0);
     gs.connected_peers.insert(
@@ -240,6 +241,7 @@ where
             topics: Default::default(),
             dont_send_received: LinkedHashMap::new(),
             dont_send_sent: LinkedHashMap::new(),
+            traffic: HashMap::new(),
             sender,
         },
     );
@@ -584,9 +586,8 @@ fn test_join() {
             new_receivers.insert(
                 peer_id,
                 RpcReceiver {
-                    priority_len: c.priority_len,
                     priority: priority.peekable(),
-                    non_priority: c.non_priority,
+                    ..c
                 },
             );
         }
@@ -618,7 +619,7 @@ fn test_join() {
             &address,
         )
         .unwrap();
-        let sender = RpcSender::new(gs.config.connection_handler_queue_len());
+        let sender = RpcSender::new(&gs.config, None);
         let receiver = sender.new_receiver();
         let connection_id = ConnectionId::new_unchecked(0);
         gs.connected_peers.insert(
@@ -629,6 +630,7 @@ fn test_join() {
                 topics: Default::default(),
                 dont_send_received: LinkedHashMap::new(),
                 dont_send_sent: LinkedHashMap::new(),
+                traffic: HashMap::new(),
                 sender,
             },
         );
@@ -721,9 +723,9 @@ fn test_publish_without_flood_publishing() {
     let publishes = receivers
         .into_values()
         .fold(vec![], |mut collected_publish, c| {
-            let priority = c.priority.into_inner();
-            while !priority.is_empty() {
-                if let Ok(RpcOut::Publish { message, .. }) = priority.try_recv() {
+            let messages = default_published_queue(c);
+            while !messages.is_empty() {
+                if let Ok(RpcOut::Publish { message, .. }) = messages.try_recv() {
                     collected_publish.push(message);
                 }
             }
@@ -806,9 +808,9 @@ fn test_fanout() {
     let publishes = receivers
         .into_values()
         .fold(vec![], |mut collected_publish, c| {
-            let priority = c.priority.into_inner();
-            while !priority.is_empty() {
-                if let Ok(RpcOut::Publish { message, .. }) = priority.try_recv() {
+            let messages = default_published_queue(c);
+            while !messages.is_empty() {
+                if let Ok(RpcOut::Publish { message, .. }) = messages.try_recv() {
                     collected_publish.push(message);
                 }
             }
@@ -1024,8 +1026,9 @@ fn test_get_random_peers() {
                 kind: PeerKind::Gossipsubv1_1,
                 connections: vec![ConnectionId::new_unchecked(0)],
                 topics: topics.clone(),
-                sender: RpcSender::new(gs.config.connection_handler_queue_len()),
+                sender: RpcSender::new(&gs.config, None),
                 dont_send_sent: LinkedHashMap::new(),
+                traffic: HashMap::new(),
                 dont_send_received: LinkedHashMap::new(),
             },
         );
@@ -1090,9 +1093,9 @@ fn test_handle_iwant_msg_cached() {
     let sent_messages = receivers
         .into_values()
         .fold(vec![], |mut collected_messages, c| {
-            let non_priority = c.non_priority.into_inner();
-            while !non_priority.is_empty() {
-                if let Ok(RpcOut::Forward { message, .. }) = non_priority.try_recv() {
+            let messages = default_message_queue(c);
+            while !messages.is_empty() {
+                if let Ok(RpcOut::Forward { message, .. }) = messages.try_recv() {
                     collected_messages.push(message)
                 }
             }
@@ -1145,28 +1148,32 @@ fn test_handle_iwant_msg_cached_shifted() {
 
         // is the message is being sent?
         let mut message_exists = false;
-        receivers = receivers.into_iter().map(|(peer_id, c)| {
-            let non_priority = c.non_priority.into_inner();
-            while !non_priority.is_empty() {
-                if matches!(non_priority.try_recv(), Ok(RpcOut::Forward{message, timeout: _ }) if
+        receivers = receivers
+            .into_iter()
+            .map(|(peer_id, c)| {
+                // All topics have the default `MessagePriority::Medium`.
+                let [critical, high, medium, low] = c.messages;
+                let messages = medium.into_inner();
+                while !messages.is_empty() {
+                    if matches!(messages.try_recv(), Ok(RpcOut::Forward{message, .. }) if
                         gs.config.message_id(
                             &gs.data_transform
                                 .inbound_transform(message.clone())
                                 .unwrap(),
                         ) == msg_id)
-                {
-                    message_exists = true;
+                    {
+                        message_exists = true;
+                    }
                 }
-            }
-            (
-                peer_id,
-                RpcReceiver {
-                    priority_len: c.priority_len,
-                    priority: c.priority,
-                    non_priority: non_priority.peekable(),
-                },
-            )
-        }).collect();
+                (
+                    peer_id,
+                    RpcReceiver {
+                        messages: [critical, high, messages.peekable(), low],
+                        ..c
+                    },
+                )
+            })
+            .collect();
         // default history_length is 5, expect no messages after shift > 5
         if shift < 5 {
             assert!(
@@ -1413,9 +1420,9 @@ fn count_control_msgs(
         new_receivers.insert(
             peer_id,
             RpcReceiver {
-                priority_len: c.priority_len,
                 priority: priority.peekable(),
                 non_priority: non_priority.peekable(),
+                ..c
             },
         );
     }
@@ -1430,23 +1437,51 @@ fn flush_events<D: DataTransform, F: TopicSubscriptionFilter>(
     let mut new_receivers = HashMap::new();
     for (peer_id, c) in receivers.into_iter() {
         let priority = c.priority.into_inner();
+        let published = c.published.map(|queue| queue.into_inner());
+        let messages = c.messages.map(|queue| queue.into_inner());
         let non_priority = c.non_priority.into_inner();
         while !priority.is_empty() || !non_priority.is_empty() {
             let _ = priority.try_recv();
             let _ = non_priority.try_recv();
         }
+        for queue in published.iter().chain(messages.iter()) {
+            while queue.try_recv().is_ok() {}
+        }
         new_receivers.insert(
             peer_id,
             RpcReceiver {
-                priority_len: c.priority_len,
                 priority: priority.peekable(),
+                published: published.map(|queue| queue.peekable()),
+                messages: messages.map(|queue| queue.peekable()),
                 non_priority: non_priority.peekable(),
+                queue_duration: c.queue_duration,
             },
         );
     }
     new_receivers
 }
 
+/// Returns the queue of forwarded messages of the default priority class, which all topics have
+/// unless configured otherwise.
+fn default_message_queue(receiver: RpcReceiver) -> async_channel::Receiver<RpcOut> {
+    receiver
+        .messages
+        .into_iter()
+        .nth(MessagePriority::default().index())
+        .expect("There is a queue per priority class")
+        .into_inner()
+}
+
+/// Returns the queue of published messages of the default priority class.
+fn default_published_queue(receiver: RpcReceiver) -> async_channel::Receiver<RpcOut> {
+    receiver
+        .published
+        .into_iter()
+        .nth(MessagePriority::default().index())
+        .expect("There is a queue per priority class")
+        .into_inner()
+}
+
 /// tests that a peer added as explicit peer gets connected to
 #[test]
 fn test_explicit_peer_gets_connected() {
@@ -1649,9 +1684,9 @@ fn do_forward_messages_to_explicit_peers() {
     gs.handle_received_message(message.clone(), &local_id);
     assert_eq!(
         receivers.into_iter().fold(0, |mut fwds, (peer_id, c)| {
-            let non_priority = c.non_priority.into_inner();
-            while !non_priority.is_empty() {
-                if matches!(non_priority.try_recv(), Ok(RpcOut::Forward{message: m, timeout: _}) if peer_id == peers[0] && m.data == message.data) {
+            let messages = default_message_queue(c);
+            while !messages.is_empty() {
+                if matches!(messages.try_recv(), Ok(RpcOut::Forward{message: m, ..}) if peer_id == peers[0] && m.data == message.data) {
         fwds +=1;
         }
                 }
@@ -2198,9 +2233,9 @@ fn test_flood_publish() {
     let publishes = receivers
         .into_values()
         .fold(vec![], |mut collected_publish, c| {
-            let priority = c.priority.into_inner();
-            while !priority.is_empty() {
-                if let Ok(RpcOut::Publish { message, .. }) = priority.try_recv() {
+            let messages = default_published_queue(c);
+            while !messages.is_empty() {
+                if let Ok(RpcOut::Publish { message, .. }) = messages.try_recv() {
                     collected_publish.push(message);
                 }
             }
@@ -2760,9 +2795,9 @@ fn test_iwant_msg_from_peer_below_gossip_threshold_gets_ignored() {
         receivers
             .into_iter()
             .fold(vec![], |mut collected_messages, (peer_id, c)| {
-                let non_priority = c.non_priority.into_inner();
-                while !non_priority.is_empty() {
-                    if let Ok(RpcOut::Forward { message, .. }) = non_priority.try_recv() {
+                let messages = default_message_queue(c);
+                while !messages.is_empty() {
+                    if let Ok(RpcOut::Forward { message, .. }) = messages.try_recv() {
                         collected_messages.push((peer_id, message));
                     }
                 }
@@ -2907,9 +2942,9 @@ fn test_do_not_publish_to_peer_below_publish_threshold() {
     let publishes = receivers
         .into_iter()
         .fold(vec![], |mut collected_publish, (peer_id, c)| {
-            let priority = c.priority.into_inner();
-            while !priority.is_empty() {
-                if let Ok(RpcOut::Publish { message, .. }) = priority.try_recv() {
+            let messages = default_published_queue(c);
+            while !messages.is_empty() {
+                if let Ok(RpcOut::Publish { message, .. }) = messages.try_recv() {
                     collected_publish.push((peer_id, message));
                 }
             }
@@ -2962,9 +2997,9 @@ fn test_do_not_flood_publish_to_peer_below_publish_threshold() {
     let publishes = receivers
         .into_iter()
         .fold(vec![], |mut collected_publish, (peer_id, c)| {
-            let priority = c.priority.into_inner();
-            while !priority.is_empty() {
-                if let Ok(RpcOut::Publish { message, .. }) = priority.try_recv() {
+            let messages = default_published_queue(c);
+            while !messages.is_empty() {
+                if let Ok(RpcOut::Publish { message, .. }) = messages.try_recv() {
                     collected_publish.push((peer_id, message))
                 }
             }
@@ -4486,9 +4521,9 @@ fn test_ignore_too_many_iwants_from_same_peer_for_same_message() {
 
     assert_eq!(
         receivers.into_values().fold(0, |mut fwds, c| {
-            let non_priority = c.non_priority.into_inner();
-            while !non_priority.is_empty() {
-                if let Ok(RpcOut::Forward { .. }) = non_priority.try_recv() {
+            let messages = default_message_queue(c);
+            while !messages.is_empty() {
+                if let Ok(RpcOut::Forward { .. }) = messages.try_recv() {
                     fwds += 1;
                 }
             }
@@ -4903,9 +4938,9 @@ fn test_publish_to_floodsub_peers_without_flood_publish() {
     let publishes = receivers
         .into_iter()
         .fold(0, |mut collected_publish, (peer_id, c)| {
-            let priority = c.priority.into_inner();
-            while !priority.is_empty() {
-                if matches!(priority.try_recv(),
+            let messages = default_published_queue(c);
+            while !messages.is_empty() {
+                if matches!(messages.try_recv(),
             Ok(RpcOut::Publish{..}) if peer_id == p1 || peer_id == p2)
                 {
                     collected_publish += 1;
@@ -4959,9 +4994,9 @@ fn test_do_not_use_floodsub_in_fanout() {
     let publishes = receivers
         .into_iter()
         .fold(0, |mut collected_publish, (peer_id, c)| {
-            let priority = c.priority.into_inner();
-            while !priority.is_empty() {
-                if matches!(priority.try_recv(),
+            let messages = default_published_queue(c);
+            while !messages.is_empty() {
+                if matches!(messages.try_recv(),
             Ok(RpcOut::Publish{..}) if peer_id == p1 || peer_id == p2)
                 {
                     collected_publish += 1;
@@ -5416,9 +5451,9 @@ fn doesnt_forward_idontwant() {
     gs.handle_received_message(raw_message.clone(), &local_id);
     assert_eq!(
         receivers.into_iter().fold(0, |mut fwds, (peer_id, c)| {
-            let non_priority = c.non_priority.into_inner();
-            while !non_priority.is_empty() {
-                if let Ok(RpcOut::Forward { .. }) = non_priority.try_recv() {
+            let messages = default_message_queue(c);
+            while !messages.is_empty() {
+                if let Ok(RpcOut::Forward { .. }) = messages.try_recv() {
                     assert_ne!(peer_id, peers[2]);
                     fwds += 1;
                 }
@@ -5483,3 +5518,128 @@ fn clear_stale_idontwant() {
     let peer = gs.connected_peers.get_mut(&peers[2]).unwrap();
     assert!(peer.dont_send_received.is_empty());
 }
+
+/// Test that published and forwarded messages are sent in order of priority, after control
+/// messages and before gossip, and that full queues apply their drop policy.
+#[test]
+fn test_message_priority_queues() {
+    let config = ConfigBuilder::default()
+        .message_queue(MessagePriority::Medium, 2, DropPolicy::DropOldest)
+        .message_queue(MessagePriority::Low, 2, DropPolicy::DropNewest)
+        .build()
+        .unwrap();
+    let mut sender = RpcSender::new(&config, None);
+    let mut receiver = sender.new_receiver();
+    let message = |data: u8| RawMessage {
+        source: None,
+        data: vec![data],
+        sequence_number: None,
+        topic: TopicHash::from_raw("test"),
+        signature: None,
+        key: None,
+        validated: true,
+    };
+    let timeout = Duration::from_secs(5);
+
+    // The newest message is dropped from a full low priority queue.
+    for data in [1, 2] {
+        assert!(matches!(
+            sender.forward(message(data), MessagePriority::Low, timeout, None),
+            Ok(None)
+        ));
+    }
+    assert!(sender
+        .forward(message(3), MessagePriority::Low, timeout, None)
+        .is_err());
+
+    // The oldest message is dropped from a full medium priority queue.
+    for data in [4, 5] {
+        assert!(matches!(
+            sender.forward(message(data), MessagePriority::Medium, timeout, None),
+            Ok(None)
+        ));
+    }
+    assert!(matches!(
+        sender.forward(message(6), MessagePriority::Medium, timeout, None),
+        Ok(Some(RpcOut::Forward { message, .. })) if message.data == [4]
+    ));
+
+    sender
+        .iwant(IWant {
+            message_ids: vec![MessageId::new(&[1])],
+        })
+        .unwrap();
+    sender
+        .forward(message(7), MessagePriority::High, timeout, None)
+        .unwrap();
+    sender
+        .publish(message(8), MessagePriority::Critical, timeout, None)
+        .unwrap();
+    sender.graft(Graft {
+        topic_hash: TopicHash::from_raw("test"),
+    });
+    assert_eq!(sender.messages_len(), 6);
+
+    let mut sent = vec![];
+    while let Some(Some(rpc)) = receiver.next().now_or_never() {
+        sent.push(match rpc {
+            RpcOut::Publish { message, .. } | RpcOut::Forward { message, .. } => {
+                format!("{}", message.data[0])
+            }
+            RpcOut::Graft(_) => "graft".into(),
+            RpcOut::IWant(_) => "iwant".into(),
+            _ => panic!("Unexpected message"),
+        });
+    }
+    assert_eq!(sent, vec!["graft", "8", "7", "5", "6", "1", "2", "iwant"]);
+    assert_eq!(sender.messages_len(), 0);
+}
+
+/// Test that messages published by the local node are not dropped when the queue of forwarded
+/// messages of their priority class is full, and are sent before the forwarded messages.
+#[test]
+fn test_published_messages_not_dropped() {
+    let config = ConfigBuilder::default()
+        .message_queue(MessagePriority::Low, 2, DropPolicy::DropOldest)
+        .build()
+        .unwrap();
+    let mut sender = RpcSender::new(&config, None);
+    let mut receiver = sender.new_receiver();
+    let message = |data: u8| RawMessage {
+        source: None,
+        data: vec![data],
+        sequence_number: None,
+        topic: TopicHash::from_raw("data_column_sidecar"),
+        signature: None,
+        key: None,
+        validated: true,
+    };
+    let timeout = Duration::from_secs(5);
+
+    // Fill the queue of forwarded messages, then publish a message.
+    for data in [1, 2] {
+        sender
+            .forward(message(data), MessagePriority::Low, timeout, None)
+            .unwrap();
+    }
+    sender
+        .publish(message(3), MessagePriority::Low, timeout, None)
+        .unwrap();
+
+    // Forwarded messages keep evicting each other, but never the published message.
+    for data in [4, 5, 6] {
+        assert!(matches!(
+            sender.forward(message(data), MessagePriority::Low, timeout, None),
+            Ok(Some(RpcOut::Forward { .. }))
+        ));
+    }
+    assert_eq!(sender.messages_len(), 3);
+
+    let mut sent = vec![];
+    while let Some(Some(RpcOut::Publish { message, .. } | RpcOut::Forward { message, .. })) =
+        receiver.next().now_or_never()
+    {
+        sent.push(message.data[0]);
+    }
+    assert_eq!(sent, vec![3, 5, 6]);
+}
@@ -24,7 +24,8 @@ use std::time::Duration;
 
 use super::error::ConfigBuilderError;
 use super::protocol::{ProtocolConfig, ProtocolId, FLOODSUB_PROTOCOL};
-use super::types::{Message, MessageId, PeerKind};
+use super::types::{DropPolicy, Message, MessageId, MessagePriority, PeerKind};
+use super::TopicHash;
 
 use libp2p::identity::PeerId;
 use libp2p::swarm::StreamProtocol;
@@ -99,6 +100,9 @@ pub struct Config {
     connection_handler_publish_duration: Duration,
     connection_handler_forward_duration: Duration,
     idontwant_message_size_threshold: usize,
+    message_priority_fn: Arc<dyn Fn(&TopicHash) -> MessagePriority + Send + Sync + 'static>,
+    message_queue_len: [usize; MessagePriority::COUNT],
+    message_drop_policy: [DropPolicy; MessagePriority::COUNT],
 }
 
 impl Config {
@@ -355,7 +359,9 @@ impl Config {
         self.published_message_ids_cache_time
     }
 
-    /// The max number of messages a `ConnectionHandler` can buffer. The default is 5000.
+    /// The max number of messages a `ConnectionHandler` can buffer. Half of it bounds the IHAVE,
+    /// IWANT and IDONTWANT messages, published and forwarded messages are bounded per priority
+    /// class by [`Config::message_queue_len`]. The default is 5000.
     pub fn connection_handler_queue_len(&self) -> usize {
         self.connection_handler_queue_len
     }
@@ -381,6 +387,54 @@ impl Config {
     pub fn idontwant_message_size_threshold(&self) -> usize {
         self.idontwant_message_size_threshold
     }
+
+    /// The priority class of the messages published or forwarded on a topic. The default is
+    /// [`MessagePriority::Medium`] for all topics.
+    pub fn message_priority(&self, topic: &TopicHash) -> MessagePriority {
+        (self.message_priority_fn)(topic)
+    }
+
+    /// The max number of forwarded messages of a priority class a `ConnectionHandler` can
+    /// buffer, and separately of published messages. The default is 2500.
+    pub fn message_queue_len(&self, priority: MessagePriority) -> usize {
+        self.message_queue_len[priority.index()]
+    }
+
+    /// What to drop when the queue of forwarded messages of a priority class is full. Published
+    /// messages are never dropped to make room for others. The default is
+    /// [`DropPolicy::DropNewest`].
+    pub fn message_drop_policy(&self, priority: MessagePriority) -> DropPolicy {
+        self.message_drop_policy[priority.index()]
+    }
+
+    /// Updates the mesh parameters of a running behaviour, validating them as
+    /// [`ConfigBuilder::build`] does.
+    pub(crate) fn set_mesh_params(
+        &mut self,
+        mesh_n_low: usize,
+        mesh_n: usize,
+        mesh_n_high: usize,
+        mesh_outbound_min: usize,
+        gossip_lazy: usize,
+    ) -> Result<(), ConfigBuilderError> {
+        if !(mesh_outbound_min <= mesh_n_low && mesh_n_low <= mesh_n && mesh_n <= mesh_n_high) {
+            return Err(ConfigBuilderError::MeshParametersInvalid);
+        }
+        if mesh_outbound_min * 2 > mesh_n {
+            return Err(ConfigBuilderError::MeshOutboundInvalid);
+        }
+        self.mesh_n_low = mesh_n_low;
+        self.mesh_n = mesh_n;
+        self.mesh_n_high = mesh_n_high;
+        self.mesh_outbound_min = mesh_outbound_min;
+        self.gossip_lazy = gossip_lazy;
+        Ok(())
+    }
+
+    /// Updates the IDONTWANT message size threshold of a running behaviour.
+    pub(crate) fn set_idontwant_message_size_threshold(&mut self, size: usize) {
+        self.idontwant_message_size_threshold = size;
+    }
 }
 
 impl Default for Config {
@@ -452,6 +506,9 @@ impl Default for ConfigBuilder {
                 connection_handler_publish_duration: Duration::from_secs(5),
                 connection_handler_forward_duration: Duration::from_millis(1000),
                 idontwant_message_size_threshold: 1000,
+                message_priority_fn: Arc::new(|_| MessagePriority::default()),
+                message_queue_len: [2500; MessagePriority::COUNT],
+                message_drop_policy: [DropPolicy::default(); MessagePriority::COUNT],
             },
             invalid_protocol: false,
         }
@@ -848,6 +905,32 @@ impl ConfigBuilder {
         self
     }
 
+    /// A user-defined function to assign the messages published or forwarded on a topic to a
+    /// priority class. Each class has its own send queue per peer, drained in order of priority.
+    /// The default assigns all topics to [`MessagePriority::Medium`].
+    pub fn message_priority_fn<F>(&mut self, priority_fn: F) -> &mut Self
+    where
+        F: Fn(&TopicHash) -> MessagePriority + Send + Sync + 'static,
+    {
+        self.config.message_priority_fn = Arc::new(priority_fn);
+        self
+    }
+
+    /// The max number of forwarded messages of a priority class a `ConnectionHandler` can buffer,
+    /// and what to drop when the queue is full. Published messages have a queue of the same
+    /// length which is exempt from the drop policy. The default is 2500 messages, dropping the
+    /// newest.
+    pub fn message_queue(
+        &mut self,
+        priority: MessagePriority,
+        len: usize,
+        drop_policy: DropPolicy,
+    ) -> &mut Self {
+        self.config.message_queue_len[priority.index()] = len;
+        self.config.message_drop_policy[priority.index()] = drop_policy;
+        self
+    }
+
     /// Constructs a [`Config`] from the given configuration and validates the settings.
     pub fn build(&self) -> Result<Config, ConfigBuilderError> {
         // check all constraints on config
@@ -875,6 +958,10 @@ impl ConfigBuilder {
             return Err(ConfigBuilderError::UnsubscribeBackoffIsZero);
         }
 
+        if self.config.message_queue_len.contains(&0) {
+            return Err(ConfigBuilderError::MessageQueueLenIsZero);
+        }
+
         if self.invalid_protocol {
             return Err(ConfigBuilderError::InvalidProtocol);
         }
@@ -922,6 +1009,8 @@ impl std::fmt::Debug for Config {
             "idontwant_message_size_threhold",
             &self.idontwant_message_size_threshold,
         );
+        let _ = builder.field("message_queue_len", &self.message_queue_len);
+        let _ = builder.field("message_drop_policy", &self.message_drop_policy);
         builder.finish()
     }
 }
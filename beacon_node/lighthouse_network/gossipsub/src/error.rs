@@ -137,6 +137,8 @@ pub enum ConfigBuilderError {
     MeshOutboundInvalid,
     /// unsubscribe_backoff is zero
     UnsubscribeBackoffIsZero,
+    /// The queue length of a message priority class is zero
+    MessageQueueLenIsZero,
     /// Invalid protocol
     InvalidProtocol,
 }
@@ -153,6 +155,9 @@ impl std::fmt::Display for ConfigBuilderError {
             Self::MeshParametersInvalid => write!(f, "The ineauality doesn't hold mesh_outbound_min <= mesh_n_low <= mesh_n <= mesh_n_high"),
             Self::MeshOutboundInvalid => write!(f, "The inequality doesn't hold mesh_outbound_min <= self.config.mesh_n / 2"),
             Self::UnsubscribeBackoffIsZero => write!(f, "unsubscribe_backoff is zero"),
+            Self::MessageQueueLenIsZero => {
+                write!(f, "The queue length of a message priority class is zero")
+            }
             Self::InvalidProtocol => write!(f, "Invalid protocol"),
         }
     }
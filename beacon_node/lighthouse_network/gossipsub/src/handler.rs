@@ -252,12 +252,10 @@ impl EnabledHandler {
                     if let Poll::Ready(Some(mut message)) = self.send_queue.poll_next_unpin(cx) {
                         match message {
                             RpcOut::Publish {
-                                message: _,
-                                ref mut timeout,
+                                ref mut timeout, ..
                             }
                             | RpcOut::Forward {
-                                message: _,
-                                ref mut timeout,
+                                ref mut timeout, ..
                             } => {
                                 if Pin::new(timeout).poll(cx).is_ready() {
                                     // Inform the behaviour and end the poll.
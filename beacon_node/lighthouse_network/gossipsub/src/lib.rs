@@ -125,7 +125,10 @@ pub use self::subscription_filter::{
 };
 pub use self::topic::{Hasher, Topic, TopicHash};
 pub use self::transform::{DataTransform, IdentityTransform};
-pub use self::types::{FailedMessages, Message, MessageAcceptance, MessageId, RawMessage};
+pub use self::types::{
+    DropPolicy, FailedMessages, Message, MessageAcceptance, MessageId, MessagePriority, RawMessage,
+    TopicTraffic,
+};
 
 #[deprecated(note = "Will be removed from the public API.")]
 pub type Rpc = self::types::Rpc;
@@ -27,7 +27,7 @@ use prometheus_client::encoding::{EncodeLabelSet, EncodeLabelValue};
 use prometheus_client::metrics::counter::Counter;
 use prometheus_client::metrics::family::{Family, MetricConstructor};
 use prometheus_client::metrics::gauge::Gauge;
-use prometheus_client::metrics::histogram::{linear_buckets, Histogram};
+use prometheus_client::metrics::histogram::{exponential_buckets, linear_buckets, Histogram};
 use prometheus_client::registry::Registry;
 
 use super::topic::TopicHash;
@@ -194,10 +194,12 @@ pub(crate) struct Metrics {
     /// Number of full messages we received that we previously sent a IDONTWANT for.
     idontwant_messages_ignored_per_topic: Family<TopicHash, Counter>,
 
-    /// The size of the priority queue.
+    /// The number of published and forwarded messages queued for a peer.
     priority_queue_size: Histogram,
     /// The size of the non-priority queue.
     non_priority_queue_size: Histogram,
+    /// The time published and forwarded messages spend queued for a peer, per topic.
+    message_queue_duration: QueueDuration,
 }
 
 impl Metrics {
@@ -382,7 +384,7 @@ impl Metrics {
         let priority_queue_size = Histogram::new(linear_buckets(0.0, 25.0, 100));
         registry.register(
             "priority_queue_size",
-            "Histogram of observed priority queue sizes",
+            "Histogram of observed published and forwarded message queue sizes",
             priority_queue_size.clone(),
         );
 
@@ -393,6 +395,15 @@ impl Metrics {
             non_priority_queue_size.clone(),
         );
 
+        let message_queue_duration = Family::new_with_constructor(HistBuilder {
+            buckets: exponential_buckets(0.001, 2.0, 14).collect(),
+        });
+        registry.register(
+            "message_queue_duration_per_topic",
+            "Histogram of the seconds published and forwarded messages spend queued for a peer per topic",
+            message_queue_duration.clone(),
+        );
+
         Self {
             max_topics,
             max_never_subscribed_topics,
@@ -427,6 +438,7 @@ impl Metrics {
             idontwant_messages_ignored_per_topic,
             priority_queue_size,
             non_priority_queue_size,
+            message_queue_duration,
         }
     }
 
@@ -653,7 +665,7 @@ impl Metrics {
         self.heartbeat_duration.observe(millis as f64);
     }
 
-    /// Observes a priority queue size.
+    /// Observes the number of published and forwarded messages queued for a peer.
     pub(crate) fn observe_priority_queue_size(&mut self, len: usize) {
         self.priority_queue_size.observe(len as f64);
     }
@@ -663,6 +675,12 @@ impl Metrics {
         self.non_priority_queue_size.observe(len as f64);
     }
 
+    /// The histograms of the time messages spend queued for a peer, observed as the connection
+    /// handlers dequeue them.
+    pub(crate) fn message_queue_duration(&self) -> QueueDuration {
+        self.message_queue_duration.clone()
+    }
+
     /// Observe a score of a mesh peer.
     pub(crate) fn observe_mesh_peers_score(&mut self, topic: &TopicHash, score: f64) {
         if self.register_topic(topic).is_ok() {
@@ -756,8 +774,11 @@ struct PenaltyLabel {
     penalty: Penalty,
 }
 
-#[derive(Clone)]
-struct HistBuilder {
+/// Histograms of the time messages spend queued for a peer, per topic.
+pub(crate) type QueueDuration = Family<TopicHash, Histogram, HistBuilder>;
+
+#[derive(Clone, Debug)]
+pub(crate) struct HistBuilder {
     buckets: Vec<f64>,
 }
 
@@ -19,9 +19,10 @@
 // DEALINGS IN THE SOFTWARE.
 
 //! A collection of types using the Gossipsub system.
-use crate::metrics::Metrics;
+use crate::config::Config;
+use crate::metrics::{Metrics, QueueDuration};
 use crate::TopicHash;
-use async_channel::{Receiver, Sender};
+use async_channel::{Receiver, Sender, TrySendError};
 use futures::stream::Peekable;
 use futures::{Future, Stream, StreamExt};
 use futures_timer::Delay;
@@ -30,10 +31,8 @@ use libp2p::identity::PeerId;
 use libp2p::swarm::ConnectionId;
 use prometheus_client::encoding::EncodeLabelValue;
 use quick_protobuf::MessageWrite;
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashMap};
 use std::fmt::Debug;
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::time::Instant;
 use std::{fmt, pin::Pin};
@@ -73,7 +72,22 @@ impl FailedMessages {
     }
 }
 
-#[derive(Debug)]
+/// The messages exchanged with a peer on a single topic over its current connection.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TopicTraffic {
+    /// The number of messages received from the peer which we had not already seen.
+    pub first_deliveries: u64,
+    /// The number of messages received from the peer which we had already seen.
+    pub duplicates: u64,
+    /// The number of bytes of messages received from the peer, including duplicates.
+    pub bytes_received: u64,
+    /// The number of messages sent to the peer.
+    pub messages_sent: u64,
+    /// The number of bytes of messages sent to the peer.
+    pub bytes_sent: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 /// Validation kinds from the application for received messages.
 pub enum MessageAcceptance {
     /// The message is considered valid, and it should be delivered and forwarded to the network.
@@ -127,6 +141,44 @@ pub(crate) struct PeerConnections {
     pub(crate) dont_send_received: LinkedHashMap<MessageId, Instant>,
     /// IDONTWANT messages we sent to the peer.
     pub(crate) dont_send_sent: LinkedHashMap<MessageId, Instant>,
+    /// The messages exchanged with the peer on each topic.
+    pub(crate) traffic: HashMap<TopicHash, TopicTraffic>,
+}
+
+impl TopicTraffic {
+    /// Records a received message.
+    pub(crate) fn record_received(&mut self, message: &RawMessage, duplicate: bool) {
+        if duplicate {
+            self.duplicates += 1;
+        } else {
+            self.first_deliveries += 1;
+        }
+        self.bytes_received += message.raw_protobuf_len() as u64;
+    }
+
+    /// Records a sent message of `bytes` length.
+    pub(crate) fn record_sent(&mut self, bytes: usize) {
+        self.messages_sent += 1;
+        self.bytes_sent += bytes as u64;
+    }
+}
+
+impl PeerConnections {
+    /// Records a message received from the peer.
+    pub(crate) fn record_received(&mut self, message: &RawMessage, duplicate: bool) {
+        self.traffic
+            .entry(message.topic.clone())
+            .or_default()
+            .record_received(message, duplicate);
+    }
+
+    /// Records a message of `bytes` length sent to the peer on `topic`.
+    pub(crate) fn record_sent(&mut self, topic: &TopicHash, bytes: usize) {
+        self.traffic
+            .entry(topic.clone())
+            .or_default()
+            .record_sent(bytes);
+    }
 }
 
 /// Describes the types of peers that can exist in the gossipsub context.
@@ -325,11 +377,19 @@ pub struct IDontWant {
 #[derive(Debug)]
 pub enum RpcOut {
     /// Publish a Gossipsub message on network. The [`Delay`] tags the time we attempted to
-    /// send it.
-    Publish { message: RawMessage, timeout: Delay },
+    /// send it, `queued_at` is the time it was queued.
+    Publish {
+        message: RawMessage,
+        timeout: Delay,
+        queued_at: Instant,
+    },
     /// Forward a Gossipsub message to the network. The [`Delay`] tags the time we attempted to
-    /// send it.
-    Forward { message: RawMessage, timeout: Delay },
+    /// send it, `queued_at` is the time it was queued.
+    Forward {
+        message: RawMessage,
+        timeout: Delay,
+        queued_at: Instant,
+    },
     /// Subscribe a topic.
     Subscribe(TopicHash),
     /// Unsubscribe a topic.
@@ -358,18 +418,12 @@ impl From<RpcOut> for proto::RPC {
     /// Converts the RPC into protobuf format.
     fn from(rpc: RpcOut) -> Self {
         match rpc {
-            RpcOut::Publish {
-                message,
-                timeout: _,
-            } => proto::RPC {
+            RpcOut::Publish { message, .. } => proto::RPC {
                 subscriptions: Vec::new(),
                 publish: vec![message.into()],
                 control: None,
             },
-            RpcOut::Forward {
-                message,
-                timeout: _,
-            } => proto::RPC {
+            RpcOut::Forward { message, .. } => proto::RPC {
                 publish: vec![message.into()],
                 subscriptions: Vec::new(),
                 control: None,
@@ -642,39 +696,118 @@ impl fmt::Display for PeerKind {
     }
 }
 
+/// The priority class of a published or forwarded message, given by its topic through
+/// [`crate::ConfigBuilder::message_priority_fn`].
+///
+/// Each class has its own bounded send queues per peer, one for the messages published by the
+/// local node and one for forwarded messages. The queues are drained in order of priority, after
+/// GRAFT, PRUNE and subscription messages and before IHAVE, IWANT and IDONTWANT messages. Within a
+/// class, published messages are sent first and are never dropped to make room for others.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum MessagePriority {
+    Critical,
+    High,
+    #[default]
+    Medium,
+    Low,
+}
+
+impl MessagePriority {
+    /// The number of priority classes.
+    pub const COUNT: usize = 4;
+
+    /// All the priority classes, from the highest to the lowest.
+    pub const ALL: [MessagePriority; Self::COUNT] = [
+        MessagePriority::Critical,
+        MessagePriority::High,
+        MessagePriority::Medium,
+        MessagePriority::Low,
+    ];
+
+    pub(crate) fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// What to drop when a message is queued for a peer whose queue for the priority class of the
+/// message is full.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DropPolicy {
+    /// Drop the message being queued.
+    #[default]
+    DropNewest,
+    /// Drop the oldest message in the queue to make room for the message being queued. Suits
+    /// classes whose messages lose their value quickly.
+    DropOldest,
+}
+
+/// The bounded queues of published and forwarded messages of a priority class.
+#[derive(Debug, Clone)]
+struct MessageQueue {
+    /// Messages published by the local node, which are exempt from the drop policy.
+    published_sender: Sender<RpcOut>,
+    published_receiver: Receiver<RpcOut>,
+    sender: Sender<RpcOut>,
+    /// Held to evict the oldest message under [`DropPolicy::DropOldest`].
+    receiver: Receiver<RpcOut>,
+    drop_policy: DropPolicy,
+}
+
 /// `RpcOut` sender that is priority aware.
 #[derive(Debug, Clone)]
 pub(crate) struct RpcSender {
-    cap: usize,
-    len: Arc<AtomicUsize>,
     pub(crate) priority_sender: Sender<RpcOut>,
     pub(crate) non_priority_sender: Sender<RpcOut>,
     priority_receiver: Receiver<RpcOut>,
     non_priority_receiver: Receiver<RpcOut>,
+    /// The published and forwarded message queues, in order of priority.
+    message_queues: [MessageQueue; MessagePriority::COUNT],
+    /// The time messages spend in the message queues per topic, observed by the receivers.
+    queue_duration: Option<QueueDuration>,
 }
 
 impl RpcSender {
     /// Create a RpcSender.
-    pub(crate) fn new(cap: usize) -> RpcSender {
+    pub(crate) fn new(config: &Config, queue_duration: Option<QueueDuration>) -> RpcSender {
         let (priority_sender, priority_receiver) = async_channel::unbounded();
-        let (non_priority_sender, non_priority_receiver) = async_channel::bounded(cap / 2);
-        let len = Arc::new(AtomicUsize::new(0));
+        let (non_priority_sender, non_priority_receiver) =
+            async_channel::bounded(config.connection_handler_queue_len() / 2);
+        let message_queues = MessagePriority::ALL.map(|priority| {
+            let (published_sender, published_receiver) =
+                async_channel::bounded(config.message_queue_len(priority));
+            let (sender, receiver) = async_channel::bounded(config.message_queue_len(priority));
+            MessageQueue {
+                published_sender,
+                published_receiver,
+                sender,
+                receiver,
+                drop_policy: config.message_drop_policy(priority),
+            }
+        });
         RpcSender {
-            cap: cap / 2,
-            len,
             priority_sender,
             non_priority_sender,
             priority_receiver,
             non_priority_receiver,
+            message_queues,
+            queue_duration,
         }
     }
 
     /// Create a new Receiver to the sender.
     pub(crate) fn new_receiver(&self) -> RpcReceiver {
         RpcReceiver {
-            priority_len: self.len.clone(),
             priority: self.priority_receiver.clone().peekable(),
+            published: self
+                .message_queues
+                .each_ref()
+                .map(|queue| queue.published_receiver.clone().peekable()),
+            messages: self
+                .message_queues
+                .each_ref()
+                .map(|queue| queue.receiver.clone().peekable()),
             non_priority: self.non_priority_receiver.clone().peekable(),
+            queue_duration: self.queue_duration.clone(),
         }
     }
 
@@ -737,24 +870,24 @@ impl RpcSender {
             .expect("Channel is unbounded and should always be open");
     }
 
-    /// Send a `RpcOut::Publish` message to the `RpcReceiver`
-    /// this is high priority. If message sending fails, an `Err` is returned.
+    /// Send a `RpcOut::Publish` message to the `RpcReceiver` in the published queue of its
+    /// priority class. Published messages are never evicted, an `Err` is returned only if the
+    /// published queue itself is full.
     pub(crate) fn publish(
         &mut self,
         message: RawMessage,
+        priority: MessagePriority,
         timeout: Duration,
         metrics: Option<&mut Metrics>,
     ) -> Result<(), ()> {
-        if self.len.load(Ordering::Relaxed) >= self.cap {
-            return Err(());
-        }
-        self.priority_sender
+        self.message_queues[priority.index()]
+            .published_sender
             .try_send(RpcOut::Publish {
                 message: message.clone(),
                 timeout: Delay::new(timeout),
+                queued_at: Instant::now(),
             })
-            .expect("Channel is unbounded and should always be open");
-        self.len.fetch_add(1, Ordering::Relaxed);
+            .map_err(|_| ())?;
 
         if let Some(m) = metrics {
             m.msg_sent(&message.topic, message.raw_protobuf_len());
@@ -763,31 +896,66 @@ impl RpcSender {
         Ok(())
     }
 
-    /// Send a `RpcOut::Forward` message to the `RpcReceiver`
-    /// this is high priority. If the queue is full the message is discarded.
+    /// Send a `RpcOut::Forward` message to the `RpcReceiver` in the queue of its priority class.
+    /// Returns the message dropped to make room for it, or an `Err` if the queue is full and the
+    /// message is dropped.
     pub(crate) fn forward(
         &mut self,
         message: RawMessage,
+        priority: MessagePriority,
         timeout: Duration,
-        metrics: Option<&mut Metrics>,
-    ) -> Result<(), ()> {
-        self.non_priority_sender
-            .try_send(RpcOut::Forward {
+        mut metrics: Option<&mut Metrics>,
+    ) -> Result<Option<RpcOut>, ()> {
+        let evicted = self.send_message(
+            priority,
+            RpcOut::Forward {
                 message: message.clone(),
                 timeout: Delay::new(timeout),
-            })
-            .map_err(|_| ())?;
+                queued_at: Instant::now(),
+            },
+            metrics.as_deref_mut(),
+        )?;
 
         if let Some(m) = metrics {
             m.msg_sent(&message.topic, message.raw_protobuf_len());
         }
 
-        Ok(())
+        Ok(evicted)
+    }
+
+    /// Queue a forwarded message, applying the drop policy of its priority class if the queue is
+    /// full.
+    fn send_message(
+        &mut self,
+        priority: MessagePriority,
+        rpc: RpcOut,
+        metrics: Option<&mut Metrics>,
+    ) -> Result<Option<RpcOut>, ()> {
+        let queue = &self.message_queues[priority.index()];
+        let rpc = match queue.sender.try_send(rpc) {
+            Ok(()) => return Ok(None),
+            Err(TrySendError::Full(rpc)) if queue.drop_policy == DropPolicy::DropOldest => rpc,
+            Err(_) => return Err(()),
+        };
+
+        // The connection handler may have taken a message in the meantime, leaving room for
+        // this one without evicting anything.
+        let evicted = queue.receiver.try_recv().ok();
+        queue.sender.try_send(rpc).map_err(|_| ())?;
+
+        if let (Some(m), Some(RpcOut::Forward { message, .. })) = (metrics, &evicted) {
+            m.forward_msg_dropped(&message.topic);
+        }
+
+        Ok(evicted)
     }
 
-    /// Returns the current size of the priority queue.
-    pub(crate) fn priority_len(&self) -> usize {
-        self.len.load(Ordering::Relaxed)
+    /// Returns the current number of published and forwarded messages queued.
+    pub(crate) fn messages_len(&self) -> usize {
+        self.message_queues
+            .iter()
+            .map(|queue| queue.published_sender.len() + queue.sender.len())
+            .sum()
     }
 
     /// Returns the current size of the non-priority queue.
@@ -799,66 +967,56 @@ impl RpcSender {
 /// `RpcOut` sender that is priority aware.
 #[derive(Debug)]
 pub struct RpcReceiver {
-    /// The maximum length of the priority queue.
-    pub(crate) priority_len: Arc<AtomicUsize>,
     /// The priority queue receiver.
     pub(crate) priority: Peekable<Receiver<RpcOut>>,
+    /// The published message queue receivers, in order of priority.
+    pub(crate) published: [Peekable<Receiver<RpcOut>>; MessagePriority::COUNT],
+    /// The forwarded message queue receivers, in order of priority.
+    pub(crate) messages: [Peekable<Receiver<RpcOut>>; MessagePriority::COUNT],
     /// The non priority queue receiver.
     pub(crate) non_priority: Peekable<Receiver<RpcOut>>,
+    /// The time messages spend in the message queues per topic.
+    pub(crate) queue_duration: Option<QueueDuration>,
 }
 
 impl RpcReceiver {
-    // Peek the next message in the queues and return it if its timeout has elapsed.
-    // Returns `None` if there aren't any more messages on the stream or none is stale.
+    // Peek the next message in the message queues and return it if its timeout has elapsed.
+    // Returns `None` if none is stale.
     pub(crate) fn poll_stale(&mut self, cx: &mut Context<'_>) -> Poll<Option<RpcOut>> {
-        // Peek priority queue.
-        let priority = match Pin::new(&mut self.priority).poll_peek_mut(cx) {
-            Poll::Ready(Some(RpcOut::Publish {
-                message: _,
-                ref mut timeout,
-            })) => {
-                if Pin::new(timeout).poll(cx).is_ready() {
-                    // Return the message.
-                    let dropped = futures::ready!(self.priority.poll_next_unpin(cx))
-                        .expect("There should be a message");
-                    return Poll::Ready(Some(dropped));
+        for queue in self.published.iter_mut().chain(self.messages.iter_mut()) {
+            if let Poll::Ready(Some(
+                RpcOut::Publish {
+                    ref mut timeout, ..
                 }
-                Poll::Ready(None)
-            }
-            poll => poll,
-        };
-
-        let non_priority = match Pin::new(&mut self.non_priority).poll_peek_mut(cx) {
-            Poll::Ready(Some(RpcOut::Forward {
-                message: _,
-                ref mut timeout,
-            })) => {
+                | RpcOut::Forward {
+                    ref mut timeout, ..
+                },
+            )) = Pin::new(&mut *queue).poll_peek_mut(cx)
+            {
                 if Pin::new(timeout).poll(cx).is_ready() {
                     // Return the message.
-                    let dropped = futures::ready!(self.non_priority.poll_next_unpin(cx))
+                    let dropped = futures::ready!(queue.poll_next_unpin(cx))
                         .expect("There should be a message");
                     return Poll::Ready(Some(dropped));
                 }
-                Poll::Ready(None)
             }
-            poll => poll,
-        };
-
-        match (priority, non_priority) {
-            (Poll::Ready(None), Poll::Ready(None)) => Poll::Ready(None),
-            _ => Poll::Pending,
         }
+        Poll::Ready(None)
     }
 
-    /// Poll queues and return true if both are empty.
+    /// Poll queues and return true if all are empty.
     pub(crate) fn poll_is_empty(&mut self, cx: &mut Context<'_>) -> bool {
         matches!(
-            (
-                Pin::new(&mut self.priority).poll_peek(cx),
-                Pin::new(&mut self.non_priority).poll_peek(cx),
-            ),
-            (Poll::Ready(None), Poll::Ready(None))
-        )
+            Pin::new(&mut self.priority).poll_peek(cx),
+            Poll::Ready(None)
+        ) && matches!(
+            Pin::new(&mut self.non_priority).poll_peek(cx),
+            Poll::Ready(None)
+        ) && self
+            .published
+            .iter_mut()
+            .chain(self.messages.iter_mut())
+            .all(|queue| matches!(Pin::new(queue).poll_peek(cx), Poll::Ready(None)))
     }
 }
 
@@ -866,17 +1024,43 @@ impl Stream for RpcReceiver {
     type Item = RpcOut;
 
     fn poll_next(
-        mut self: std::pin::Pin<&mut Self>,
+        self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Option<Self::Item>> {
+        let this = self.get_mut();
         // The priority queue is first polled.
-        if let Poll::Ready(rpc) = Pin::new(&mut self.priority).poll_next(cx) {
-            if let Some(RpcOut::Publish { .. }) = rpc {
-                self.priority_len.fetch_sub(1, Ordering::Relaxed);
-            }
+        if let Poll::Ready(rpc) = Pin::new(&mut this.priority).poll_next(cx) {
             return Poll::Ready(rpc);
         }
+        // Then the message queues, in order of priority and with the published messages of each
+        // class first.
+        let queues = this
+            .published
+            .iter_mut()
+            .zip(this.messages.iter_mut())
+            .flat_map(|(published, forwarded)| [published, forwarded]);
+        for queue in queues {
+            if let Poll::Ready(rpc) = Pin::new(queue).poll_next(cx) {
+                if let (
+                    Some(
+                        RpcOut::Publish {
+                            message, queued_at, ..
+                        }
+                        | RpcOut::Forward {
+                            message, queued_at, ..
+                        },
+                    ),
+                    Some(queue_duration),
+                ) = (&rpc, &this.queue_duration)
+                {
+                    queue_duration
+                        .get_or_create(&message.topic)
+                        .observe(queued_at.elapsed().as_secs_f64());
+                }
+                return Poll::Ready(rpc);
+            }
+        }
         // Then we poll the non priority.
-        Pin::new(&mut self.non_priority).poll_next(cx)
+        Pin::new(&mut this.non_priority).poll_next(cx)
     }
 }
@@ -0,0 +1,793 @@
+//! Records inbound gossip messages, the results of their validation and inbound RPC responses to
+//! disk so that they can be replayed against a beacon node database at a later time.
+//!
+//! Records are written by a blocking task to a directory of files, each of which is a sequence of
+//! SSZ `CaptureRecord`s prefixed by their length as a little-endian `u32`. A new file is started
+//! once the current file reaches the configured size, and the oldest files are deleted once the
+//! configured number of files is exceeded.
+
+use crate::rpc::methods::RpcSuccessResponse;
+use crate::{metrics, MessageAcceptance, MessageId, PeerId, TopicHash};
+use serde::{Deserialize, Serialize};
+use slog::{debug, warn, Logger};
+use snap::raw::{Decoder, Encoder};
+use ssz::{Decode, Encode};
+use ssz_derive::{Decode, Encode};
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use task_executor::TaskExecutor;
+use tokio::sync::mpsc;
+use types::EthSpec;
+
+/// The prefix of the name of each capture file.
+pub const CAPTURE_FILE_PREFIX: &str = "capture-";
+/// The extension of each capture file.
+pub const CAPTURE_FILE_EXTENSION: &str = "ssz";
+/// The default maximum size of each capture file, in bytes.
+pub const DEFAULT_CAPTURE_MAX_FILE_SIZE: u64 = 256 * 1024 * 1024;
+/// The default maximum number of capture files to retain.
+pub const DEFAULT_CAPTURE_MAX_FILES: usize = 16;
+
+/// The number of events which may be queued before new events are dropped.
+const CAPTURE_CHANNEL_CAPACITY: usize = 16_384;
+/// The number of bytes used to encode the length of each record.
+const RECORD_LENGTH_BYTES: usize = 4;
+/// The maximum length of a record.
+///
+/// This exceeds the compressed size of the largest gossip message or RPC response (10 MiB) along
+/// with its metadata, so that a corrupt length cannot cause an arbitrarily large allocation.
+pub const MAX_RECORD_LENGTH: usize = 16 * 1024 * 1024;
+
+/// A gossip message received from a peer.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct GossipRecord {
+    /// The time at which the message arrived, in milliseconds since the UNIX epoch.
+    pub arrival_ms: u64,
+    /// The peer which propagated the message to us.
+    pub peer_id: Vec<u8>,
+    pub message_id: Vec<u8>,
+    pub topic: Vec<u8>,
+    /// The snappy compressed message data.
+    ///
+    /// The data is recompressed after it has been decoded by gossipsub, so whilst it decompresses
+    /// to the same bytes it may not be identical to the bytes sent by the peer.
+    pub data: Vec<u8>,
+}
+
+/// The result of the validation of a gossip message, as reported to gossipsub.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct ValidationRecord {
+    /// The time at which the result was reported, in milliseconds since the UNIX epoch.
+    pub reported_ms: u64,
+    pub peer_id: Vec<u8>,
+    pub message_id: Vec<u8>,
+    /// The `MessageAcceptance`, encoded by `acceptance_to_u8`.
+    pub acceptance: u8,
+}
+
+/// A successful response to an RPC request made by us.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct RpcResponseRecord {
+    /// The time at which the response arrived, in milliseconds since the UNIX epoch.
+    pub arrival_ms: u64,
+    pub peer_id: Vec<u8>,
+    /// The name of the RPC protocol, e.g. `beacon_blocks_by_root`.
+    pub protocol: Vec<u8>,
+    /// The snappy compressed SSZ bytes of the response.
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+#[ssz(enum_behaviour = "union")]
+pub enum CaptureRecord {
+    Gossip(GossipRecord),
+    Validation(ValidationRecord),
+    RpcResponse(RpcResponseRecord),
+}
+
+impl CaptureRecord {
+    /// The time at which the record was captured.
+    pub fn timestamp(&self) -> Duration {
+        let millis = match self {
+            CaptureRecord::Gossip(record) => record.arrival_ms,
+            CaptureRecord::Validation(record) => record.reported_ms,
+            CaptureRecord::RpcResponse(record) => record.arrival_ms,
+        };
+        Duration::from_millis(millis)
+    }
+}
+
+impl GossipRecord {
+    /// Create a record of a message, where `data` is the decompressed message data.
+    pub fn new(
+        arrival_ms: u64,
+        peer_id: PeerId,
+        message_id: MessageId,
+        topic: TopicHash,
+        data: &[u8],
+    ) -> Result<Self, String> {
+        Ok(Self {
+            arrival_ms,
+            peer_id: peer_id.to_bytes(),
+            message_id: message_id.0,
+            topic: topic.into_string().into_bytes(),
+            data: compress(data)?,
+        })
+    }
+
+    pub fn peer_id(&self) -> Result<PeerId, String> {
+        PeerId::from_bytes(&self.peer_id).map_err(|e| format!("invalid peer id: {e}"))
+    }
+
+    pub fn message_id(&self) -> MessageId {
+        MessageId::new(&self.message_id)
+    }
+
+    pub fn topic(&self) -> Result<TopicHash, String> {
+        String::from_utf8(self.topic.clone())
+            .map(TopicHash::from_raw)
+            .map_err(|e| format!("invalid topic: {e}"))
+    }
+
+    /// Returns the decompressed message data, as passed to `PubsubMessage::decode`.
+    pub fn decompressed_data(&self) -> Result<Vec<u8>, String> {
+        decompress(&self.data)
+    }
+}
+
+impl ValidationRecord {
+    pub fn message_id(&self) -> MessageId {
+        MessageId::new(&self.message_id)
+    }
+
+    pub fn acceptance(&self) -> Option<MessageAcceptance> {
+        acceptance_from_u8(self.acceptance)
+    }
+}
+
+impl RpcResponseRecord {
+    /// Create a record of a response, where `data` is the SSZ bytes of the response.
+    pub fn new(
+        arrival_ms: u64,
+        peer_id: PeerId,
+        protocol: &str,
+        data: &[u8],
+    ) -> Result<Self, String> {
+        Ok(Self {
+            arrival_ms,
+            peer_id: peer_id.to_bytes(),
+            protocol: protocol.as_bytes().to_vec(),
+            data: compress(data)?,
+        })
+    }
+
+    pub fn peer_id(&self) -> Result<PeerId, String> {
+        PeerId::from_bytes(&self.peer_id).map_err(|e| format!("invalid peer id: {e}"))
+    }
+
+    pub fn protocol(&self) -> Result<String, String> {
+        String::from_utf8(self.protocol.clone()).map_err(|e| format!("invalid protocol: {e}"))
+    }
+
+    /// Returns the SSZ bytes of the response.
+    pub fn decompressed_data(&self) -> Result<Vec<u8>, String> {
+        decompress(&self.data)
+    }
+}
+
+pub fn acceptance_to_u8(acceptance: MessageAcceptance) -> u8 {
+    match acceptance {
+        MessageAcceptance::Accept => 0,
+        MessageAcceptance::Reject => 1,
+        MessageAcceptance::Ignore => 2,
+    }
+}
+
+pub fn acceptance_from_u8(byte: u8) -> Option<MessageAcceptance> {
+    match byte {
+        0 => Some(MessageAcceptance::Accept),
+        1 => Some(MessageAcceptance::Reject),
+        2 => Some(MessageAcceptance::Ignore),
+        _ => None,
+    }
+}
+
+fn compress(data: &[u8]) -> Result<Vec<u8>, String> {
+    Encoder::new()
+        .compress_vec(data)
+        .map_err(|e| format!("unable to compress data: {e}"))
+}
+
+fn decompress(data: &[u8]) -> Result<Vec<u8>, String> {
+    Decoder::new()
+        .decompress_vec(data)
+        .map_err(|e| format!("invalid snappy data: {e}"))
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Configuration for capturing network messages to disk.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CaptureConfig {
+    /// The directory in which capture files are written.
+    pub dir: PathBuf,
+    /// The size in bytes at which a new capture file is started.
+    pub max_file_size: u64,
+    /// The maximum number of capture files to retain.
+    pub max_files: usize,
+}
+
+impl CaptureConfig {
+    /// Capture to `dir` with the default limits.
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            max_file_size: DEFAULT_CAPTURE_MAX_FILE_SIZE,
+            max_files: DEFAULT_CAPTURE_MAX_FILES,
+        }
+    }
+}
+
+/// An event sent from the network thread to the capture task.
+///
+/// Encoding and compression are left to the capture task to keep the network thread responsive.
+enum CaptureEvent<E: EthSpec> {
+    Gossip {
+        arrival_ms: u64,
+        peer_id: PeerId,
+        message_id: MessageId,
+        topic: TopicHash,
+        data: Vec<u8>,
+    },
+    Validation {
+        reported_ms: u64,
+        peer_id: PeerId,
+        message_id: MessageId,
+        acceptance: MessageAcceptance,
+    },
+    RpcResponse {
+        arrival_ms: u64,
+        peer_id: PeerId,
+        response: RpcSuccessResponse<E>,
+    },
+}
+
+impl<E: EthSpec> CaptureEvent<E> {
+    /// Convert the event into a record, or `None` if the event should not be recorded.
+    fn into_record(self, encoder: &mut Encoder) -> Result<Option<CaptureRecord>, snap::Error> {
+        let record = match self {
+            CaptureEvent::Gossip {
+                arrival_ms,
+                peer_id,
+                message_id,
+                topic,
+                data,
+            } => CaptureRecord::Gossip(GossipRecord {
+                arrival_ms,
+                peer_id: peer_id.to_bytes(),
+                message_id: message_id.0,
+                topic: topic.into_string().into_bytes(),
+                data: encoder.compress_vec(&data)?,
+            }),
+            CaptureEvent::Validation {
+                reported_ms,
+                peer_id,
+                message_id,
+                acceptance,
+            } => CaptureRecord::Validation(ValidationRecord {
+                reported_ms,
+                peer_id: peer_id.to_bytes(),
+                message_id: message_id.0,
+                acceptance: acceptance_to_u8(acceptance),
+            }),
+            CaptureEvent::RpcResponse {
+                arrival_ms,
+                peer_id,
+                response,
+            } => {
+                let ssz_bytes = match &response {
+                    RpcSuccessResponse::BlocksByRange(block)
+                    | RpcSuccessResponse::BlocksByRoot(block) => block.as_ssz_bytes(),
+                    RpcSuccessResponse::BlobsByRange(blob)
+                    | RpcSuccessResponse::BlobsByRoot(blob) => blob.as_ssz_bytes(),
+                    RpcSuccessResponse::DataColumnsByRange(column)
+                    | RpcSuccessResponse::DataColumnsByRoot(column) => column.as_ssz_bytes(),
+                    RpcSuccessResponse::LightClientBootstrap(bootstrap) => bootstrap.as_ssz_bytes(),
+                    RpcSuccessResponse::LightClientOptimisticUpdate(update) => {
+                        update.as_ssz_bytes()
+                    }
+                    RpcSuccessResponse::LightClientFinalityUpdate(update) => update.as_ssz_bytes(),
+                    RpcSuccessResponse::LightClientUpdatesByRange(update) => update.as_ssz_bytes(),
+                    RpcSuccessResponse::CheckpointStateChunks(chunk) => chunk.as_ssz_bytes(),
+                    // These carry no chain data.
+                    RpcSuccessResponse::Status(_)
+                    | RpcSuccessResponse::Pong(_)
+                    | RpcSuccessResponse::MetaData(_) => return Ok(None),
+                };
+                CaptureRecord::RpcResponse(RpcResponseRecord {
+                    arrival_ms,
+                    peer_id: peer_id.to_bytes(),
+                    protocol: response.protocol().as_ref().as_bytes().to_vec(),
+                    data: encoder.compress_vec(&ssz_bytes)?,
+                })
+            }
+        };
+        Ok(Some(record))
+    }
+}
+
+/// Sends network messages to a task which writes them to disk.
+pub struct NetworkCapture<E: EthSpec> {
+    sender: mpsc::Sender<CaptureEvent<E>>,
+}
+
+impl<E: EthSpec> NetworkCapture<E> {
+    /// Start a task which writes captured messages to the directory given by `config`.
+    pub fn start(
+        config: CaptureConfig,
+        executor: &TaskExecutor,
+        log: &Logger,
+    ) -> Result<Self, String> {
+        let writer = CaptureWriter::new(config, log.clone())
+            .map_err(|e| format!("Unable to open network capture directory: {e}"))?;
+        let (sender, receiver) = mpsc::channel(CAPTURE_CHANNEL_CAPACITY);
+        executor.spawn_blocking(move || writer.run(receiver), "network_capture");
+        Ok(Self { sender })
+    }
+
+    fn send(&self, event: CaptureEvent<E>) {
+        if self.sender.try_send(event).is_err() {
+            metrics::inc_counter(&metrics::NETWORK_CAPTURE_DROPPED_EVENTS);
+        }
+    }
+
+    /// Record a gossip message, where `data` is the decompressed message data.
+    pub fn record_gossip(
+        &self,
+        peer_id: PeerId,
+        message_id: MessageId,
+        topic: TopicHash,
+        data: Vec<u8>,
+    ) {
+        self.send(CaptureEvent::Gossip {
+            arrival_ms: now_ms(),
+            peer_id,
+            message_id,
+            topic,
+            data,
+        });
+    }
+
+    /// Record the result of validating a gossip message.
+    pub fn record_validation_result(
+        &self,
+        peer_id: PeerId,
+        message_id: MessageId,
+        acceptance: MessageAcceptance,
+    ) {
+        self.send(CaptureEvent::Validation {
+            reported_ms: now_ms(),
+            peer_id,
+            message_id,
+            acceptance,
+        });
+    }
+
+    /// Record a response to one of our RPC requests.
+    pub fn record_rpc_response(&self, peer_id: PeerId, response: RpcSuccessResponse<E>) {
+        self.send(CaptureEvent::RpcResponse {
+            arrival_ms: now_ms(),
+            peer_id,
+            response,
+        });
+    }
+}
+
+/// Writes records to a rotating set of capture files.
+struct CaptureWriter {
+    config: CaptureConfig,
+    /// Capture files in the order they were created, including the current file.
+    files: VecDeque<PathBuf>,
+    file: Option<BufWriter<File>>,
+    file_size: u64,
+    /// Distinguishes files created within the same millisecond.
+    sequence: u64,
+    log: Logger,
+}
+
+impl CaptureWriter {
+    fn new(config: CaptureConfig, log: Logger) -> io::Result<Self> {
+        fs::create_dir_all(&config.dir)?;
+        // Files from previous runs count towards the limit.
+        let files = capture_files(&config.dir)?.into();
+        Ok(Self {
+            config,
+            files,
+            file: None,
+            file_size: 0,
+            sequence: 0,
+            log,
+        })
+    }
+
+    fn run<E: EthSpec>(mut self, mut receiver: mpsc::Receiver<CaptureEvent<E>>) {
+        let mut encoder = Encoder::new();
+        while let Some(event) = receiver.blocking_recv() {
+            let result = match event.into_record(&mut encoder) {
+                Ok(Some(record)) => self.write(&record),
+                Ok(None) => Ok(()),
+                Err(e) => Err(io::Error::other(e)),
+            };
+            // Flush whenever the queue is drained so that the files are readable whilst the node
+            // is running.
+            let result = result.and_then(|()| {
+                if receiver.is_empty() {
+                    self.flush()
+                } else {
+                    Ok(())
+                }
+            });
+            if let Err(e) = result {
+                warn!(
+                    self.log,
+                    "Failed to write network capture";
+                    "error" => %e,
+                    "dir" => ?self.config.dir,
+                );
+            }
+        }
+        if let Err(e) = self.flush() {
+            warn!(self.log, "Failed to flush network capture"; "error" => %e);
+        }
+    }
+
+    fn write(&mut self, record: &CaptureRecord) -> io::Result<()> {
+        let bytes = record.as_ssz_bytes();
+        if bytes.len() > MAX_RECORD_LENGTH {
+            return Err(io::Error::other(format!(
+                "record of {} bytes exceeds the maximum of {MAX_RECORD_LENGTH}",
+                bytes.len()
+            )));
+        }
+        let length = (RECORD_LENGTH_BYTES + bytes.len()) as u64;
+        if self.file.is_none() || self.file_size + length > self.config.max_file_size {
+            self.rotate()?;
+        }
+        if let Some(file) = self.file.as_mut() {
+            file.write_all(&(bytes.len() as u32).to_le_bytes())?;
+            file.write_all(&bytes)?;
+            self.file_size += length;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.file.as_mut() {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// Start a new capture file, deleting the oldest files if there are too many.
+    fn rotate(&mut self) -> io::Result<()> {
+        self.flush()?;
+        let name = format!(
+            "{}{:013}-{:06}.{}",
+            CAPTURE_FILE_PREFIX,
+            now_ms(),
+            self.sequence,
+            CAPTURE_FILE_EXTENSION
+        );
+        self.sequence += 1;
+        let path = self.config.dir.join(name);
+        self.file = Some(BufWriter::new(File::create(&path)?));
+        self.file_size = 0;
+        debug!(self.log, "Started network capture file"; "path" => ?path);
+        self.files.push_back(path);
+
+        while self.files.len() > self.config.max_files.max(1) {
+            if let Some(oldest) = self.files.pop_front() {
+                fs::remove_file(&oldest)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the capture files in `dir`, oldest first.
+pub fn capture_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?
+        .into_iter()
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == CAPTURE_FILE_EXTENSION)
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with(CAPTURE_FILE_PREFIX))
+        })
+        .collect::<Vec<_>>();
+    // The timestamp and sequence number are zero-padded, so file names sort chronologically.
+    files.sort();
+    Ok(files)
+}
+
+/// Reads the records of a directory of capture files, oldest first.
+pub struct CaptureReader {
+    files: VecDeque<PathBuf>,
+    current: Option<(PathBuf, BufReader<File>)>,
+}
+
+impl CaptureReader {
+    pub fn open(dir: &Path) -> Result<Self, String> {
+        let files = capture_files(dir)
+            .map_err(|e| format!("Unable to read capture directory {dir:?}: {e}"))?;
+        Ok(Self {
+            files: files.into(),
+            current: None,
+        })
+    }
+
+    fn next_record(&mut self) -> Result<Option<CaptureRecord>, String> {
+        loop {
+            if self.current.is_none() {
+                let Some(path) = self.files.pop_front() else {
+                    return Ok(None);
+                };
+                let file =
+                    File::open(&path).map_err(|e| format!("Unable to open {path:?}: {e}"))?;
+                self.current = Some((path, BufReader::new(file)));
+            }
+            let Some((path, reader)) = self.current.as_mut() else {
+                continue;
+            };
+
+            let mut length = [0; RECORD_LENGTH_BYTES];
+            match reader.read_exact(&mut length) {
+                Ok(()) => {}
+                // A clean end of file.
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    self.current = None;
+                    continue;
+                }
+                Err(e) => return Err(format!("Unable to read {path:?}: {e}")),
+            }
+
+            let length = u32::from_le_bytes(length) as usize;
+            if length > MAX_RECORD_LENGTH {
+                // The remainder of the file cannot be read without a valid length.
+                let path = path.clone();
+                self.current = None;
+                return Err(format!(
+                    "Record of {length} bytes in {path:?} exceeds the maximum of \
+                     {MAX_RECORD_LENGTH}"
+                ));
+            }
+
+            let mut bytes = vec![0; length];
+            match reader.read_exact(&mut bytes) {
+                Ok(()) => {}
+                // The final record may be incomplete if the node was still running.
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    self.current = None;
+                    continue;
+                }
+                Err(e) => return Err(format!("Unable to read {path:?}: {e}")),
+            }
+
+            return CaptureRecord::from_ssz_bytes(&bytes)
+                .map(Some)
+                .map_err(|e| format!("Invalid record in {path:?}: {e:?}"));
+        }
+    }
+}
+
+impl Iterator for CaptureReader {
+    type Item = Result<CaptureRecord, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_record().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::methods::Ping;
+    use crate::rpc::Protocol;
+    use std::sync::Arc;
+    use tempfile::tempdir;
+    use types::{BeaconBlock, MainnetEthSpec, Signature, SignedBeaconBlock};
+
+    type E = MainnetEthSpec;
+
+    fn gossip_event(index: u8) -> CaptureEvent<E> {
+        CaptureEvent::Gossip {
+            arrival_ms: index as u64,
+            peer_id: PeerId::random(),
+            message_id: MessageId::new(&[index]),
+            topic: TopicHash::from_raw("/eth2/00000000/beacon_block/ssz_snappy"),
+            data: vec![index; 1_000],
+        }
+    }
+
+    #[test]
+    fn write_rotate_and_read() {
+        let dir = tempdir().unwrap();
+        let config = CaptureConfig {
+            dir: dir.path().to_path_buf(),
+            max_file_size: 100,
+            max_files: 3,
+        };
+        let log = logging::test_logger();
+        let mut writer = CaptureWriter::new(config, log).unwrap();
+        let mut encoder = Encoder::new();
+
+        let records = (0..8)
+            .map(|index| {
+                gossip_event(index)
+                    .into_record(&mut encoder)
+                    .unwrap()
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        for record in &records {
+            writer.write(record).unwrap();
+        }
+        writer.flush().unwrap();
+
+        // Each file holds a single record, and only the last three files are retained.
+        assert_eq!(capture_files(dir.path()).unwrap().len(), 3);
+        let read = CaptureReader::open(dir.path())
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(read, records[5..]);
+
+        let CaptureRecord::Gossip(gossip) = &read[0] else {
+            panic!("expected a gossip record");
+        };
+        assert_eq!(gossip.decompressed_data().unwrap(), vec![5; 1_000]);
+        assert_eq!(gossip.message_id(), MessageId::new(&[5]));
+    }
+
+    #[test]
+    fn capture_and_read_each_record_type() {
+        let dir = tempdir().unwrap();
+        let log = logging::test_logger();
+        let writer = CaptureWriter::new(CaptureConfig::new(dir.path().to_path_buf()), log).unwrap();
+        let (sender, receiver) = mpsc::channel(CAPTURE_CHANNEL_CAPACITY);
+
+        let spec = E::default_spec();
+        let block = Arc::new(SignedBeaconBlock::from_block(
+            BeaconBlock::empty(&spec),
+            Signature::empty(),
+        ));
+        let peer_id = PeerId::random();
+        let events = [
+            gossip_event(1),
+            CaptureEvent::Validation {
+                reported_ms: 2,
+                peer_id,
+                message_id: MessageId::new(&[1]),
+                acceptance: MessageAcceptance::Reject,
+            },
+            CaptureEvent::RpcResponse {
+                arrival_ms: 3,
+                peer_id,
+                response: RpcSuccessResponse::BlocksByRoot(block.clone()),
+            },
+            // Responses without chain data are not recorded.
+            CaptureEvent::RpcResponse {
+                arrival_ms: 4,
+                peer_id,
+                response: RpcSuccessResponse::Pong(Ping { data: 1 }),
+            },
+        ];
+        for event in events {
+            assert!(sender.try_send(event).is_ok());
+        }
+        drop(sender);
+        writer.run(receiver);
+
+        let read = CaptureReader::open(dir.path())
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(read.len(), 3);
+        assert_eq!(
+            read.iter()
+                .map(CaptureRecord::timestamp)
+                .collect::<Vec<_>>(),
+            [1, 2, 3].map(Duration::from_millis)
+        );
+
+        let CaptureRecord::Validation(validation) = &read[1] else {
+            panic!("expected a validation record");
+        };
+        assert_eq!(validation.peer_id, peer_id.to_bytes());
+        assert_eq!(validation.message_id(), MessageId::new(&[1]));
+        assert_eq!(validation.acceptance(), Some(MessageAcceptance::Reject));
+
+        let CaptureRecord::RpcResponse(response) = &read[2] else {
+            panic!("expected an RPC response record");
+        };
+        assert_eq!(response.peer_id().unwrap(), peer_id);
+        assert_eq!(
+            response.protocol().unwrap(),
+            Protocol::BlocksByRoot.as_ref()
+        );
+        assert_eq!(response.decompressed_data().unwrap(), block.as_ssz_bytes());
+    }
+
+    #[test]
+    fn oversized_records_are_rejected() {
+        let dir = tempdir().unwrap();
+        let log = logging::test_logger();
+        let mut writer =
+            CaptureWriter::new(CaptureConfig::new(dir.path().to_path_buf()), log).unwrap();
+
+        let oversized = CaptureRecord::Gossip(GossipRecord {
+            arrival_ms: 0,
+            peer_id: vec![],
+            message_id: vec![],
+            topic: vec![],
+            data: vec![0; MAX_RECORD_LENGTH],
+        });
+        assert!(writer.write(&oversized).is_err());
+
+        // A file with a corrupt length, which sorts before the files of the writer.
+        let mut corrupt = u32::MAX.to_le_bytes().to_vec();
+        corrupt.extend_from_slice(&[0; 64]);
+        fs::write(
+            dir.path().join(format!(
+                "{CAPTURE_FILE_PREFIX}0000000000000-000000.{CAPTURE_FILE_EXTENSION}"
+            )),
+            corrupt,
+        )
+        .unwrap();
+
+        let record = GossipRecord::new(
+            1,
+            PeerId::random(),
+            MessageId::new(&[1]),
+            TopicHash::from_raw("/eth2/00000000/beacon_block/ssz_snappy"),
+            &[1; 100],
+        )
+        .map(CaptureRecord::Gossip)
+        .unwrap();
+        writer.write(&record).unwrap();
+        writer.flush().unwrap();
+
+        // The remainder of the corrupt file is skipped, and the next file is read.
+        let mut reader = CaptureReader::open(dir.path()).unwrap();
+        assert!(reader.next().unwrap().is_err());
+        assert_eq!(reader.next().unwrap().unwrap(), record);
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn validation_round_trip() {
+        for acceptance in [
+            MessageAcceptance::Accept,
+            MessageAcceptance::Reject,
+            MessageAcceptance::Ignore,
+        ] {
+            assert_eq!(
+                acceptance_from_u8(acceptance_to_u8(acceptance)),
+                Some(acceptance)
+            );
+        }
+        assert!(acceptance_from_u8(3).is_none());
+    }
+}
@@ -1,6 +1,8 @@
+use crate::capture::CaptureConfig;
 use crate::listen_addr::{ListenAddr, ListenAddress};
+use crate::peer_manager::peer_groups::PeerGroupConfig;
 use crate::rpc::config::{InboundRateLimiterConfig, OutboundRateLimiterConfig};
-use crate::types::GossipKind;
+use crate::types::{GossipKind, GossipTopic};
 use crate::{Enr, PeerIdSerialized};
 use directory::{
     DEFAULT_BEACON_NODE_DIR, DEFAULT_HARDCODED_NETWORK, DEFAULT_NETWORK_DIR, DEFAULT_ROOT_DIR,
@@ -86,6 +88,9 @@ pub struct Config {
     /// List of trusted libp2p nodes which are not scored and marked as explicit.
     pub trusted_peers: Vec<PeerIdSerialized>,
 
+    /// Static peering groups which have reserved connection slots and are always re-dialed.
+    pub peer_groups: Vec<PeerGroupConfig>,
+
     /// Disables peer scoring altogether.
     pub disable_peer_scoring: bool,
 
@@ -140,12 +145,20 @@ pub struct Config {
     /// Configures if/where invalid blocks should be stored.
     pub invalid_block_storage: Option<PathBuf>,
 
+    /// Configures if/where inbound gossip messages and RPC responses should be captured.
+    pub network_capture: Option<CaptureConfig>,
+
     /// Configuration for the inbound rate limiter (requests received by this node).
     pub inbound_rate_limiter_config: Option<InboundRateLimiterConfig>,
 
     /// Configuration for the minimum message size for which IDONTWANT messages are send in the mesh.
     /// Lower the value reduces the optimization effect of the IDONTWANT messages.
     pub idontwant_message_size_threshold: usize,
+
+    /// The bandwidth in Mbps which the libp2p traffic of the node should stay under, if
+    /// any. The network parameters are adapted dynamically to stay within the budget, with
+    /// `network_load` as the upper bound.
+    pub bandwidth_budget_mbps: Option<f64>,
 }
 
 impl Config {
@@ -339,6 +352,7 @@ impl Default for Config {
             boot_nodes_multiaddr: vec![],
             libp2p_nodes: vec![],
             trusted_peers: vec![],
+            peer_groups: vec![],
             disable_peer_scoring: false,
             client_version: lighthouse_version::version_with_platform(),
             disable_discovery: false,
@@ -356,8 +370,10 @@ impl Default for Config {
             enable_light_client_server: false,
             outbound_rate_limiter_config: None,
             invalid_block_storage: None,
+            network_capture: None,
             inbound_rate_limiter_config: None,
             idontwant_message_size_threshold: DEFAULT_IDONTWANT_MESSAGE_SIZE_THRESHOLD,
+            bandwidth_budget_mbps: None,
         }
     }
 }
@@ -506,10 +522,48 @@ pub fn gossipsub_config(
         .message_id_fn(gossip_message_id)
         .allow_self_origin(true)
         .idontwant_message_size_threshold(idontwant_message_size_threshold)
+        .message_priority_fn(gossip_message_priority)
+        // Attestations lose their value quickly, prefer sending the latest ones.
+        .message_queue(
+            gossipsub::MessagePriority::Medium,
+            2500,
+            gossipsub::DropPolicy::DropOldest,
+        )
+        // Blobs and data columns are large, bound the memory they can take per peer.
+        .message_queue(
+            gossipsub::MessagePriority::Low,
+            512,
+            gossipsub::DropPolicy::DropOldest,
+        )
         .build()
         .expect("valid gossipsub configuration")
 }
 
+/// The priority with which messages on a gossipsub topic are sent to peers, such that large blob
+/// and data column messages do not delay blocks and attestations.
+fn gossip_message_priority(topic: &gossipsub::TopicHash) -> gossipsub::MessagePriority {
+    let Ok(topic) = GossipTopic::decode(topic.as_str()) else {
+        return gossipsub::MessagePriority::Medium;
+    };
+    match topic.kind() {
+        GossipKind::BeaconBlock => gossipsub::MessagePriority::Critical,
+        GossipKind::BeaconAggregateAndProof | GossipKind::SignedContributionAndProof => {
+            gossipsub::MessagePriority::High
+        }
+        GossipKind::BlobSidecar(_) | GossipKind::DataColumnSidecar(_) => {
+            gossipsub::MessagePriority::Low
+        }
+        GossipKind::Attestation(_)
+        | GossipKind::SyncCommitteeMessage(_)
+        | GossipKind::VoluntaryExit
+        | GossipKind::ProposerSlashing
+        | GossipKind::AttesterSlashing
+        | GossipKind::BlsToExecutionChange
+        | GossipKind::LightClientFinalityUpdate
+        | GossipKind::LightClientOptimisticUpdate => gossipsub::MessagePriority::Medium,
+    }
+}
+
 /// Helper function to determine if the IpAddr is a global address or not. The `is_global()`
 /// function is not yet stable on IpAddr.
 #[allow(clippy::nonminimal_bool)]
@@ -48,7 +48,7 @@ use tokio::sync::mpsc;
 use types::{ChainSpec, EnrForkId, EthSpec};
 
 mod subnet_predicate;
-pub use subnet_predicate::subnet_predicate;
+pub use subnet_predicate::{next_fork_predicate, subnet_predicate};
 use types::non_zero_usize::new_non_zero_usize;
 
 /// Local ENR storage filename.
@@ -4,7 +4,7 @@ use crate::types::{EnrAttestationBitfield, EnrSyncCommitteeBitfield};
 use itertools::Itertools;
 use slog::trace;
 use std::ops::Deref;
-use types::{ChainSpec, DataColumnSubnetId};
+use types::{ChainSpec, DataColumnSubnetId, EnrForkId};
 
 /// Returns the predicate for a given subnet.
 pub fn subnet_predicate<E>(
@@ -29,6 +29,28 @@ where
         let sync_committee_bitfield: Result<EnrSyncCommitteeBitfield<E>, _> =
             enr.sync_committee_bitfield::<E>();
 
+        // The custody subnets are only computed if a data column subnet is searched for, and at
+        // most once per ENR.
+        let mut custody_subnets = None;
+        let mut custodies = |subnet: &DataColumnSubnetId| {
+            custody_subnets
+                .get_or_insert_with(|| {
+                    enr.custody_subnet_count::<E>(&spec)
+                        .ok()
+                        .and_then(|custody_subnet_count| {
+                            DataColumnSubnetId::compute_custody_subnets::<E>(
+                                enr.node_id().raw(),
+                                custody_subnet_count,
+                                &spec,
+                            )
+                            .ok()
+                        })
+                        .map(|subnets| subnets.collect::<Vec<_>>())
+                        .unwrap_or_default()
+                })
+                .contains(subnet)
+        };
+
         let predicate = subnets.iter().any(|subnet| match subnet {
             Subnet::Attestation(s) => attestation_bitfield
                 .get(*s.deref() as usize)
@@ -36,18 +58,7 @@ where
             Subnet::SyncCommittee(s) => sync_committee_bitfield
                 .as_ref()
                 .map_or(false, |b| b.get(*s.deref() as usize).unwrap_or(false)),
-            Subnet::DataColumn(s) => {
-                if let Ok(custody_subnet_count) = enr.custody_subnet_count::<E>(&spec) {
-                    DataColumnSubnetId::compute_custody_subnets::<E>(
-                        enr.node_id().raw(),
-                        custody_subnet_count,
-                        &spec,
-                    )
-                    .map_or(false, |mut subnets| subnets.contains(s))
-                } else {
-                    false
-                }
-            }
+            Subnet::DataColumn(s) => custodies(s),
         });
 
         if !predicate {
@@ -60,3 +71,84 @@ where
         predicate
     }
 }
+
+/// Returns the predicate for peers that are ready for the next fork scheduled in
+/// `local_enr_fork_id`.
+///
+/// Peers that advertise a different next fork will be disconnected at that fork, so they are less
+/// useful than peers that are ready for it. All peers are ready if no fork is scheduled.
+pub fn next_fork_predicate(
+    local_enr_fork_id: EnrForkId,
+    spec: &ChainSpec,
+) -> impl Fn(&Enr) -> bool + Send {
+    let no_fork_scheduled = local_enr_fork_id.next_fork_epoch == spec.far_future_epoch;
+    move |enr: &Enr| {
+        no_fork_scheduled
+            || enr.eth2().map_or(false, |enr_fork_id| {
+                enr_fork_id.next_fork_version == local_enr_fork_id.next_fork_version
+                    && enr_fork_id.next_fork_epoch == local_enr_fork_id.next_fork_epoch
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery::enr::ETH2_ENR_KEY;
+    use alloy_rlp::bytes::Bytes;
+    use ssz::Encode;
+    use types::{Epoch, MainnetEthSpec};
+
+    fn enr(enr_fork_id: Option<&EnrForkId>) -> Enr {
+        let enr_key = CombinedKey::generate_secp256k1();
+        let mut builder = Enr::builder();
+        if let Some(enr_fork_id) = enr_fork_id {
+            builder.add_value::<Bytes>(ETH2_ENR_KEY, &enr_fork_id.as_ssz_bytes().into());
+        }
+        builder.build(&enr_key).unwrap()
+    }
+
+    #[test]
+    fn next_fork_predicate_without_scheduled_fork() {
+        let spec = MainnetEthSpec::default_spec();
+        let local_fork_id = EnrForkId {
+            fork_digest: [0; 4],
+            next_fork_version: [0; 4],
+            next_fork_epoch: spec.far_future_epoch,
+        };
+        let predicate = next_fork_predicate(local_fork_id.clone(), &spec);
+        assert!(predicate(&enr(Some(&local_fork_id))));
+        assert!(predicate(&enr(None)));
+    }
+
+    #[test]
+    fn next_fork_predicate_with_scheduled_fork() {
+        let spec = MainnetEthSpec::default_spec();
+        let local_fork_id = EnrForkId {
+            fork_digest: [0; 4],
+            next_fork_version: [1, 0, 0, 0],
+            next_fork_epoch: Epoch::new(10),
+        };
+        let predicate = next_fork_predicate(local_fork_id.clone(), &spec);
+        assert!(predicate(&enr(Some(&local_fork_id))));
+
+        let other_version = EnrForkId {
+            next_fork_version: [2, 0, 0, 0],
+            ..local_fork_id.clone()
+        };
+        assert!(!predicate(&enr(Some(&other_version))));
+
+        let other_epoch = EnrForkId {
+            next_fork_epoch: Epoch::new(11),
+            ..local_fork_id.clone()
+        };
+        assert!(!predicate(&enr(Some(&other_epoch))));
+
+        let not_scheduled = EnrForkId {
+            next_fork_epoch: spec.far_future_epoch,
+            ..local_fork_id
+        };
+        assert!(!predicate(&enr(Some(&not_scheduled))));
+        assert!(!predicate(&enr(None)));
+    }
+}
@@ -5,6 +5,7 @@
 mod config;
 pub mod service;
 
+pub mod capture;
 pub mod discovery;
 pub mod listen_addr;
 pub mod metrics;
@@ -116,8 +117,10 @@ pub use libp2p::{core::ConnectedPoint, PeerId, Swarm};
 pub use libp2p::{multiaddr, Multiaddr};
 pub use metrics::scrape_discovery_metrics;
 pub use peer_manager::{
+    peer_groups::PeerGroupConfig,
     peerdb::client::Client,
     peerdb::score::{PeerAction, ReportSource},
+    peerdb::stats::{GossipStats, PeerStats, RpcEvent, RpcStats, RpcStatsStore},
     peerdb::PeerDB,
     ConnectionDirection, PeerConnectionStatus, PeerInfo, PeerManager, SyncInfo, SyncStatus,
 };
@@ -101,6 +101,21 @@ pub static PEERS_PER_CUSTODY_SUBNET_COUNT: LazyLock<Result<IntGaugeVec>> = LazyL
     )
 });
 
+pub static PEERS_PER_CUSTODY_SUBNET: LazyLock<Result<IntGaugeVec>> = LazyLock::new(|| {
+    try_create_int_gauge_vec(
+        "peers_per_custody_subnet",
+        "The current count of good peers custodying each data column subnet",
+        &["subnet"],
+    )
+});
+
+pub static CUSTODY_SUBNETS_BELOW_MIN_PEERS: LazyLock<Result<IntGauge>> = LazyLock::new(|| {
+    try_create_int_gauge(
+        "custody_subnets_below_min_peers",
+        "The number of data column subnets custodied by fewer good peers than the minimum",
+    )
+});
+
 pub static FAILED_ATTESTATION_PUBLISHES_PER_SUBNET: LazyLock<Result<IntGaugeVec>> =
     LazyLock::new(|| {
         try_create_int_gauge_vec(
@@ -206,6 +221,36 @@ pub static REPORT_PEER_MSGS: LazyLock<Result<IntCounterVec>> = LazyLock::new(||
     )
 });
 
+pub static NETWORK_CAPTURE_DROPPED_EVENTS: LazyLock<Result<IntCounter>> = LazyLock::new(|| {
+    try_create_int_counter(
+        "libp2p_network_capture_dropped_events_total",
+        "Count of network messages which were not captured because the capture queue was full",
+    )
+});
+
+/*
+ * Bandwidth Budget
+ */
+pub static BANDWIDTH_BUDGET: LazyLock<Result<Gauge>> = LazyLock::new(|| {
+    try_create_float_gauge(
+        "libp2p_bandwidth_budget_mbps",
+        "The configured bandwidth budget for libp2p traffic in Mbps",
+    )
+});
+pub static BANDWIDTH_USAGE: LazyLock<Result<GaugeVec>> = LazyLock::new(|| {
+    try_create_float_gauge_vec(
+        "libp2p_bandwidth_usage_mbps",
+        "The smoothed libp2p traffic in Mbps, compared against the bandwidth budget",
+        &["direction"],
+    )
+});
+pub static BANDWIDTH_BUDGET_LEVEL: LazyLock<Result<IntGauge>> = LazyLock::new(|| {
+    try_create_int_gauge(
+        "libp2p_bandwidth_budget_level",
+        "The current level of the bandwidth budget, from 0 (most constrained) to the configured network load",
+    )
+});
+
 pub fn scrape_discovery_metrics() {
     let metrics =
         discv5::metrics::Metrics::from(discv5::Discv5::<discv5::DefaultProtocolId>::raw_metrics());
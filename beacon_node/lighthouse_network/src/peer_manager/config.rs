@@ -1,3 +1,5 @@
+use super::peer_groups::PeerGroupConfig;
+
 /// The time in seconds between re-status's peers.
 pub const DEFAULT_STATUS_INTERVAL: u64 = 300;
 
@@ -22,6 +24,8 @@ pub struct Config {
     pub quic_enabled: bool,
     /// Target number of peers to connect to.
     pub target_peer_count: usize,
+    /// Static peering groups which are always connected to.
+    pub peer_groups: Vec<PeerGroupConfig>,
 
     /* RPC related configurations */
     /// Time in seconds between status requests sent to peers.
@@ -41,6 +45,7 @@ impl Default for Config {
             metrics_enabled: false,
             quic_enabled: true,
             target_peer_count: DEFAULT_TARGET_PEERS,
+            peer_groups: vec![],
             status_interval: DEFAULT_STATUS_INTERVAL,
             ping_interval_inbound: DEFAULT_PING_INTERVAL_INBOUND,
             ping_interval_outbound: DEFAULT_PING_INTERVAL_OUTBOUND,
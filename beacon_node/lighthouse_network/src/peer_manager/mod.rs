@@ -1,7 +1,7 @@
 //! Implementation of Lighthouse's peer management system.
 
 use crate::discovery::enr_ext::EnrExt;
-use crate::discovery::peer_id_to_node_id;
+use crate::discovery::{next_fork_predicate, peer_id_to_node_id, Eth2Enr};
 use crate::rpc::{GoodbyeReason, MetaData, Protocol, RPCError, RpcErrorResponse};
 use crate::service::TARGET_SUBNET_PEERS;
 use crate::{metrics, Gossipsub, NetworkGlobals, PeerId, Subnet, SubnetDiscovery};
@@ -9,13 +9,15 @@ use delay_map::HashSetDelay;
 use discv5::Enr;
 use libp2p::identify::Info as IdentifyInfo;
 use lru_cache::LRUTimeCache;
+use peer_groups::PeerGroups;
+use peerdb::persisted::PersistedPeers;
 use peerdb::{BanOperation, BanResult, ScoreUpdateResult};
 use rand::seq::SliceRandom;
 use slog::{debug, error, trace, warn};
 use smallvec::SmallVec;
 use std::{
     sync::Arc,
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 use types::{DataColumnSubnetId, EthSpec, SyncSubnetId};
 
@@ -30,6 +32,7 @@ pub use peerdb::peer_info::{
     ConnectionDirection, PeerConnectionStatus, PeerConnectionStatus::*, PeerInfo,
 };
 use peerdb::score::{PeerAction, ReportSource};
+use peerdb::stats::RpcEvent;
 pub use peerdb::sync_status::{SyncInfo, SyncStatus};
 use std::collections::{hash_map::Entry, HashMap, HashSet};
 use std::net::IpAddr;
@@ -37,6 +40,7 @@ use strum::IntoEnumIterator;
 
 pub mod config;
 mod network_behaviour;
+pub mod peer_groups;
 
 /// The heartbeat performs regular updates such as updating reputations and performing discovery
 /// requests. This defines the interval in seconds.
@@ -49,6 +53,13 @@ pub const PEER_RECONNECTION_TIMEOUT: Duration = Duration::from_secs(600);
 /// lower our peer count below this number. Instead we favour a non-uniform distribution of subnet
 /// peers.
 pub const MIN_SYNC_COMMITTEE_PEERS: u64 = 2;
+/// The minimum number of good peers we aim to have custodying each data column subnet. Subnets
+/// below this are searched for in discovery, and peers are not pruned if doing so would lower the
+/// number of peers custodying any of their subnets below it.
+pub const MIN_CUSTODY_SUBNET_PEERS: usize = 2;
+/// Peers discovered for data column subnets lacking custody peers are dialed and kept as priority
+/// peers for this long.
+const CUSTODY_SUBNET_PEER_TTL: Duration = Duration::from_secs(HEARTBEAT_INTERVAL * 2);
 /// A fraction of `PeerManager::target_peers` that we allow to connect to us in excess of
 /// `PeerManager::target_peers`. For clarity, if `PeerManager::target_peers` is 50 and
 /// PEER_EXCESS_FACTOR = 0.1 we allow 10% more nodes, i.e 55.
@@ -76,10 +87,14 @@ pub struct PeerManager<E: EthSpec> {
     outbound_ping_peers: HashSetDelay<PeerId>,
     /// A collection of peers awaiting to be Status'd.
     status_peers: HashSetDelay<PeerId>,
+    /// The time we sent our outstanding ping to each peer, used to measure latency.
+    pings_in_flight: HashMap<PeerId, Instant>,
     /// The target number of peers we would like to connect to.
     target_peers: usize,
     /// Peers queued to be dialed.
     peers_to_dial: Vec<Enr>,
+    /// The static peering groups, which are re-dialed whenever they disconnect.
+    peer_groups: PeerGroups,
     /// The number of temporarily banned peers. This is used to prevent instantaneous
     /// reconnection.
     // NOTE: This just prevents re-connections. The state of the peer is otherwise unaffected. A
@@ -149,6 +164,7 @@ impl<E: EthSpec> PeerManager<E> {
             discovery_enabled,
             metrics_enabled,
             target_peer_count,
+            peer_groups,
             status_interval,
             ping_interval_inbound,
             ping_interval_outbound,
@@ -158,13 +174,23 @@ impl<E: EthSpec> PeerManager<E> {
         // Set up the peer manager heartbeat interval
         let heartbeat = tokio::time::interval(tokio::time::Duration::from_secs(HEARTBEAT_INTERVAL));
 
+        let peer_groups = PeerGroups::new(peer_groups);
+        {
+            let mut peerdb = network_globals.peers.write();
+            for (peer_id, peer_group) in peer_groups.member_groups() {
+                peerdb.set_peer_group(*peer_id, peer_group);
+            }
+        }
+
         Ok(PeerManager {
             network_globals,
             events: SmallVec::new(),
             peers_to_dial: Default::default(),
+            peer_groups,
             inbound_ping_peers: HashSetDelay::new(Duration::from_secs(ping_interval_inbound)),
             outbound_ping_peers: HashSetDelay::new(Duration::from_secs(ping_interval_outbound)),
             status_peers: HashSetDelay::new(Duration::from_secs(status_interval)),
+            pings_in_flight: HashMap::new(),
             target_peers: target_peer_count,
             temporary_banned_peers: LRUTimeCache::new(PEER_RECONNECTION_TIMEOUT),
             sync_committee_subnets: Default::default(),
@@ -322,19 +348,26 @@ impl<E: EthSpec> PeerManager<E> {
         let mut to_dial_peers = 0;
         let results_count = results.len();
         let connected_or_dialing = self.network_globals.connected_or_dialing_peers();
-        for (enr, min_ttl) in results {
+        for (enr, min_ttl, ready_for_next_fork) in self.prioritise_discovered_peers(results) {
             // There are two conditions in deciding whether to dial this peer.
             // 1. If we are less than our max connections. Discovery queries are executed to reach
             //    our target peers, so its fine to dial up to our max peers (which will get pruned
-            //    in the next heartbeat down to our target).
+            //    in the next heartbeat down to our target). Peers that are not ready for our next
+            //    fork are only dialed up to our target peers.
             // 2. If the peer is one our validators require for a specific subnet, then it is
             //    considered a priority. We have pre-allocated some extra priority slots for these
             //    peers as specified by PRIORITY_PEER_EXCESS. Therefore we dial these peers, even
             //    if we are already at our max_peer limit.
+            let max_peers = if ready_for_next_fork {
+                self.max_peers()
+            } else {
+                self.target_peers
+            };
             if !self.peers_to_dial.contains(&enr)
                 && ((min_ttl.is_some()
+                    && ready_for_next_fork
                     && connected_or_dialing + to_dial_peers < self.max_priority_peers())
-                    || connected_or_dialing + to_dial_peers < self.max_peers())
+                    || connected_or_dialing + to_dial_peers < max_peers)
             {
                 // This should be updated with the peer dialing. In fact created once the peer is
                 // dialed
@@ -367,6 +400,47 @@ impl<E: EthSpec> PeerManager<E> {
         }
     }
 
+    /// Orders discovered peers by how useful they are, so that the most useful peers are dialed
+    /// first when we cannot dial all of them.
+    ///
+    /// Peers required for a subnet come first, followed by peers that are ready for our next fork
+    /// and then by the number of data column subnets the peers custody. Each peer is returned with
+    /// whether it is ready for our next fork.
+    fn prioritise_discovered_peers(
+        &self,
+        results: HashMap<Enr, Option<Instant>>,
+    ) -> Vec<(Enr, Option<Instant>, bool)> {
+        let spec = &self.network_globals.spec;
+        let next_fork_predicate = self
+            .network_globals
+            .local_enr()
+            .eth2()
+            .ok()
+            .map(|enr_fork_id| next_fork_predicate(enr_fork_id, spec));
+
+        let mut peers = results
+            .into_iter()
+            .map(|(enr, min_ttl)| {
+                let ready_for_next_fork = next_fork_predicate
+                    .as_ref()
+                    .map_or(true, |predicate| predicate(&enr));
+                let custody_subnet_count = enr.custody_subnet_count::<E>(spec).unwrap_or(0);
+                (enr, min_ttl, ready_for_next_fork, custody_subnet_count)
+            })
+            .collect::<Vec<_>>();
+        peers.sort_by_key(|(_, min_ttl, ready_for_next_fork, custody_subnet_count)| {
+            (
+                min_ttl.is_none(),
+                !ready_for_next_fork,
+                std::cmp::Reverse(*custody_subnet_count),
+            )
+        });
+        peers
+            .into_iter()
+            .map(|(enr, min_ttl, ready_for_next_fork, _)| (enr, min_ttl, ready_for_next_fork))
+            .collect()
+    }
+
     /// A STATUS message has been received from a peer. This resets the status timer.
     pub fn peer_statusd(&mut self, peer_id: &PeerId) {
         self.status_peers.insert(*peer_id);
@@ -387,6 +461,17 @@ impl<E: EthSpec> PeerManager<E> {
         }
     }
 
+    /// The number of peers we aim to be connected to.
+    pub fn target_peers(&self) -> usize {
+        self.target_peers
+    }
+
+    /// Updates the number of peers we aim to be connected to. Excess peers are pruned at the next
+    /// heartbeat.
+    pub fn set_target_peers(&mut self, target_peers: usize) {
+        self.target_peers = target_peers;
+    }
+
     /// The maximum number of peers we allow to connect to us. This is `target_peers` * (1 +
     /// PEER_EXCESS_FACTOR)
     fn max_peers(&self) -> usize {
@@ -418,6 +503,35 @@ impl<E: EthSpec> PeerManager<E> {
             as usize
     }
 
+    /// The number of connected or dialing peers which count towards our peer limits, i.e.
+    /// excluding members of peering groups which occupy reserved slots.
+    fn limited_connected_or_dialing_peers(&self) -> usize {
+        self.network_globals
+            .connected_or_dialing_peers()
+            .saturating_sub(self.reserved_slots_in_use())
+    }
+
+    /// The number of connected peers which count towards our peer limits, i.e. excluding members
+    /// of peering groups which occupy reserved slots.
+    fn limited_connected_peers(&self) -> usize {
+        self.network_globals
+            .connected_peers()
+            .saturating_sub(self.reserved_slots_in_use())
+    }
+
+    /// The number of connected members of peering groups which occupy reserved slots.
+    fn reserved_slots_in_use(&self) -> usize {
+        self.peer_groups
+            .occupied_slots(&self.network_globals.peers.read())
+    }
+
+    /// Returns true if the peer is a member of a peering group which has a free reserved slot for
+    /// a new connection in `direction`.
+    fn has_reserved_slot(&self, peer_id: &PeerId, direction: ConnectionDirection) -> bool {
+        self.peer_groups
+            .has_free_slot(peer_id, direction, &self.network_globals.peers.read())
+    }
+
     /* Notifications from the Swarm */
 
     /// A peer is being dialed.
@@ -436,6 +550,38 @@ impl<E: EthSpec> PeerManager<E> {
         }
     }
 
+    /// Returns the reputation of the known peers, to be persisted across restarts.
+    pub fn persisted_peers(&self) -> PersistedPeers {
+        PersistedPeers {
+            persisted_at: unix_time_secs(),
+            peers: self.network_globals.peers.read().persisted_peers(),
+        }
+    }
+
+    /// Restores the reputation of peers persisted by a previous run.
+    ///
+    /// Peers which remain banned are banned at the swarm level, and the best of the restored
+    /// peers are dialed.
+    pub fn restore_peers(&mut self, persisted: PersistedPeers) {
+        let elapsed = Duration::from_secs(unix_time_secs().saturating_sub(persisted.persisted_at));
+        let (banned_peers, peers_to_dial) = self
+            .network_globals
+            .peers
+            .write()
+            .restore_peers(persisted.peers, elapsed);
+
+        debug!(self.log, "Restored persisted peers";
+            "banned" => banned_peers.len(), "to_dial" => peers_to_dial.len());
+
+        for (peer_id, banned_ips) in banned_peers {
+            self.events
+                .push(PeerManagerEvent::Banned(peer_id, banned_ips));
+        }
+        for enr in peers_to_dial.into_iter().take(self.target_peers) {
+            self.dial_peer(enr);
+        }
+    }
+
     /// Reports if a peer is banned or not.
     ///
     /// This is used to determine if we should accept incoming connections.
@@ -494,6 +640,18 @@ impl<E: EthSpec> PeerManager<E> {
             ],
         );
 
+        // Only the failures of our own requests reflect on the peer.
+        if matches!(direction, ConnectionDirection::Outgoing) {
+            let event = if matches!(err, RPCError::StreamTimeout) {
+                RpcEvent::Timeout
+            } else {
+                RpcEvent::Error
+            };
+            self.network_globals
+                .rpc_stats
+                .record(peer_id, protocol, event, 0);
+        }
+
         // Map this error to a `PeerAction` (if any)
         let peer_action = match err {
             RPCError::IncompleteStream => {
@@ -562,6 +720,9 @@ impl<E: EthSpec> PeerManager<E> {
                     Protocol::BlobsByRoot => PeerAction::MidToleranceError,
                     Protocol::DataColumnsByRoot => PeerAction::MidToleranceError,
                     Protocol::DataColumnsByRange => PeerAction::MidToleranceError,
+                    // State chunks are only requested while checkpoint syncing from peers, which
+                    // spreads its requests across peers close to their quota.
+                    Protocol::CheckpointStateChunks => return,
                     Protocol::Goodbye => PeerAction::LowToleranceError,
                     Protocol::MetaData => PeerAction::LowToleranceError,
                     Protocol::Status => PeerAction::LowToleranceError,
@@ -587,6 +748,8 @@ impl<E: EthSpec> PeerManager<E> {
                     Protocol::LightClientOptimisticUpdate => return,
                     Protocol::LightClientFinalityUpdate => return,
                     Protocol::LightClientUpdatesByRange => return,
+                    // Only Lighthouse supports this protocol.
+                    Protocol::CheckpointStateChunks => return,
                     Protocol::MetaData => PeerAction::Fatal,
                     Protocol::Status => PeerAction::Fatal,
                 }
@@ -609,6 +772,7 @@ impl<E: EthSpec> PeerManager<E> {
                     Protocol::LightClientOptimisticUpdate => return,
                     Protocol::LightClientFinalityUpdate => return,
                     Protocol::LightClientUpdatesByRange => return,
+                    Protocol::CheckpointStateChunks => PeerAction::MidToleranceError,
                     Protocol::Goodbye => return,
                     Protocol::MetaData => return,
                     Protocol::Status => return,
@@ -667,8 +831,11 @@ impl<E: EthSpec> PeerManager<E> {
 
     /// A PONG has been returned from a peer.
     pub fn pong_response(&mut self, peer_id: &PeerId, seq: u64) {
-        if let Some(peer_info) = self.network_globals.peers.read().peer_info(peer_id) {
+        if let Some(peer_info) = self.network_globals.peers.write().peer_info_mut(peer_id) {
             // received a pong
+            if let Some(sent) = self.pings_in_flight.remove(peer_id) {
+                peer_info.update_latency(sent.elapsed());
+            }
 
             // if the sequence number is unknown send update the meta data of the peer.
             if let Some(meta_data) = &peer_info.meta_data() {
@@ -756,6 +923,14 @@ impl<E: EthSpec> PeerManager<E> {
         }
     }
 
+    /// Updates the gossip traffic of connected peers from gossipsub.
+    pub(crate) fn update_gossip_stats(&self, gossipsub: &Gossipsub) {
+        self.network_globals
+            .peers
+            .write()
+            .update_gossip_stats(gossipsub);
+    }
+
     /* Internal functions */
 
     /// Sets a peer as connected as long as their reputation allows it
@@ -802,6 +977,7 @@ impl<E: EthSpec> PeerManager<E> {
         // Remove the ping and status timer for the peer
         self.inbound_ping_peers.remove(peer_id);
         self.outbound_ping_peers.remove(peer_id);
+        self.pings_in_flight.remove(peer_id);
         self.status_peers.remove(peer_id);
         self.events.extend(
             purged_peers
@@ -903,6 +1079,51 @@ impl<E: EthSpec> PeerManager<E> {
         }
     }
 
+    /// Run discovery queries for data column subnets custodied by fewer than
+    /// `MIN_CUSTODY_SUBNET_PEERS` good peers, and update the custody coverage metrics.
+    fn maintain_custody_subnet_peers(&mut self) {
+        let spec = &self.network_globals.spec;
+        if !spec.is_peer_das_scheduled() {
+            return;
+        }
+
+        let min_ttl = Instant::now() + CUSTODY_SUBNET_PEER_TTL;
+        let subnets_to_discover: Vec<SubnetDiscovery> = {
+            let peers = self.network_globals.peers.read();
+            (0..spec.data_column_sidecar_subnet_count)
+                .map(DataColumnSubnetId::new)
+                .filter_map(|subnet| {
+                    let peer_count = peers.good_custody_subnet_peer(subnet).count();
+                    metrics::set_gauge_vec(
+                        &metrics::PEERS_PER_CUSTODY_SUBNET,
+                        &[&subnet.to_string()],
+                        peer_count as i64,
+                    );
+                    (peer_count < MIN_CUSTODY_SUBNET_PEERS).then_some(SubnetDiscovery {
+                        subnet: Subnet::DataColumn(subnet),
+                        min_ttl: Some(min_ttl),
+                    })
+                })
+                .collect()
+        };
+
+        metrics::set_gauge(
+            &metrics::CUSTODY_SUBNETS_BELOW_MIN_PEERS,
+            subnets_to_discover.len() as i64,
+        );
+
+        // request the subnet query from discovery
+        if self.discovery_enabled && !subnets_to_discover.is_empty() {
+            debug!(
+                self.log,
+                "Making subnet queries for maintaining custody subnet peers";
+                "subnets" => ?subnets_to_discover.iter().map(|s| s.subnet).collect::<Vec<_>>()
+            );
+            self.events
+                .push(PeerManagerEvent::DiscoverSubnetPeers(subnets_to_discover));
+        }
+    }
+
     /// This function checks the status of our current peers and optionally requests a discovery
     /// query if we need to find more peers to maintain the current number of peers
     fn maintain_peer_count(&mut self, dialing_peers: usize) {
@@ -960,6 +1181,10 @@ impl<E: EthSpec> PeerManager<E> {
     ///     excess peer limit, all of the following logic is subverted as we will not prune any peers.
     ///     Also, the more trusted peers a user has, the less room Lighthouse has to efficiently manage
     ///     its peers across the subnets.
+    /// - Do not prune members of peering groups. Members occupying reserved slots do not count
+    ///     towards the number of connected peers.
+    /// - Do not prune a peer that would lower the number of good peers custodying any of its data
+    ///     column subnets below MIN_CUSTODY_SUBNET_PEERS, unless it has a negative score.
     ///
     /// Prune peers in the following order:
     /// 1. Remove worst scoring peers
@@ -968,8 +1193,8 @@ impl<E: EthSpec> PeerManager<E> {
     /// 4. Randomly remove peers if all the above are satisfied
     ///
     fn prune_excess_peers(&mut self) {
-        // The current number of connected peers.
-        let connected_peer_count = self.network_globals.connected_peers();
+        // The current number of connected peers, excluding those in reserved slots.
+        let connected_peer_count = self.limited_connected_peers();
         if connected_peer_count <= self.target_peers {
             // No need to prune peers
             return;
@@ -982,8 +1207,37 @@ impl<E: EthSpec> PeerManager<E> {
         // Keep track of the number of outbound peers we are pruning.
         let mut outbound_peers_pruned = 0;
 
+        // The number of good peers custodying each data column subnet, as counted by
+        // `maintain_custody_subnet_peers`, so that we keep enough peers to retrieve every column.
+        let mut custody_subnet_peer_count: HashMap<DataColumnSubnetId, usize> = HashMap::new();
+        for (_, info) in self.network_globals.peers.read().connected_peers() {
+            for subnet in info.good_custody_subnets() {
+                *custody_subnet_peer_count.entry(*subnet).or_default() += 1;
+            }
+        }
+
+        macro_rules! lacks_custody_peers {
+            ($info: expr) => {
+                $info.good_custody_subnets().any(|subnet| {
+                    custody_subnet_peer_count
+                        .get(subnet)
+                        .map_or(true, |count| *count <= MIN_CUSTODY_SUBNET_PEERS)
+                })
+            };
+        }
+
+        macro_rules! remove_custody_peer {
+            ($info: expr) => {
+                for subnet in $info.good_custody_subnets() {
+                    if let Some(count) = custody_subnet_peer_count.get_mut(subnet) {
+                        *count = count.saturating_sub(1);
+                    }
+                }
+            };
+        }
+
         macro_rules! prune_peers {
-            ($filter: expr) => {
+            ($filter: expr, $keep_custody_peers: expr) => {
                 let filter = $filter;
                 for (peer_id, info) in self
                     .network_globals
@@ -992,7 +1246,7 @@ impl<E: EthSpec> PeerManager<E> {
                     .worst_connected_peers()
                     .iter()
                     .filter(|(_, info)| {
-                        !info.has_future_duty() && !info.is_trusted() && filter(*info)
+                        !info.has_future_duty() && !info.is_protected() && filter(*info)
                     })
                 {
                     if peers_to_prune.len()
@@ -1004,6 +1258,10 @@ impl<E: EthSpec> PeerManager<E> {
                     if peers_to_prune.contains(*peer_id) {
                         continue;
                     }
+                    // Keep peers needed to custody data column subnets.
+                    if $keep_custody_peers && lacks_custody_peers!(info) {
+                        continue;
+                    }
                     // Only remove up to the target outbound peer count.
                     if info.is_outbound_only() {
                         if self.target_outbound_peers() + outbound_peers_pruned
@@ -1014,18 +1272,21 @@ impl<E: EthSpec> PeerManager<E> {
                             continue;
                         }
                     }
+                    remove_custody_peer!(info);
                     peers_to_prune.insert(**peer_id);
                 }
             };
         }
 
         // 1. Look through peers that have the worst score (ignoring non-penalized scored peers).
-        prune_peers!(|info: &PeerInfo<E>| { info.score().score() < 0.0 });
+        //    These are pruned even if they custody data column subnets lacking peers, as
+        //    discovery searches for replacements.
+        prune_peers!(|info: &PeerInfo<E>| { info.score().score() < 0.0 }, false);
 
         // 2. Attempt to remove peers that are not subscribed to a subnet, if we still need to
         //    prune more.
         if peers_to_prune.len() < connected_peer_count.saturating_sub(self.target_peers) {
-            prune_peers!(|info: &PeerInfo<E>| { !info.has_long_lived_subnet() });
+            prune_peers!(|info: &PeerInfo<E>| { !info.has_long_lived_subnet() }, true);
         }
 
         // 3. and 4. Remove peers that are too grouped on any given subnet. If all subnets are
@@ -1042,8 +1303,8 @@ impl<E: EthSpec> PeerManager<E> {
             > = HashMap::new();
 
             for (peer_id, info) in self.network_globals.peers.read().connected_peers() {
-                // Ignore peers we trust or that we are already pruning
-                if info.is_trusted() || peers_to_prune.contains(peer_id) {
+                // Ignore protected peers or peers that we are already pruning
+                if info.is_protected() || peers_to_prune.contains(peer_id) {
                     continue;
                 }
 
@@ -1128,6 +1389,11 @@ impl<E: EthSpec> PeerManager<E> {
                                 }
                             }
 
+                            // Check the data column custody subnets
+                            if lacks_custody_peers!(info) {
+                                continue;
+                            }
+
                             if info.is_outbound_only() {
                                 outbound_peers_pruned += 1;
                             }
@@ -1141,7 +1407,8 @@ impl<E: EthSpec> PeerManager<E> {
                         // outbound limit or min_subnet_count. In this case, we remove all
                         // peers from the pruning logic and try another subnet.
                         if let Some(index) = removed_peer_index {
-                            let (candidate_peer, _) = peers_on_subnet.remove(index);
+                            let (candidate_peer, info) = peers_on_subnet.remove(index);
+                            remove_custody_peer!(info);
                             // Remove pruned peers from other subnet counts
                             for subnet_peers in subnet_to_peer.values_mut() {
                                 subnet_peers.retain(|(peer_id, _)| peer_id != &candidate_peer);
@@ -1214,6 +1481,9 @@ impl<E: EthSpec> PeerManager<E> {
         // Maintain minimum count for sync committee peers.
         self.maintain_sync_committee_peers();
 
+        // Maintain minimum count of peers custodying each data column subnet.
+        self.maintain_custody_subnet_peers();
+
         // Prune any excess peers back to our target in such a way that incentivises good scores and
         // a uniform distribution of subnets.
         self.prune_excess_peers();
@@ -1221,6 +1491,14 @@ impl<E: EthSpec> PeerManager<E> {
         // Unban any peers that have served their temporary ban timeout
         self.unban_temporary_banned_peers();
 
+        // Drop the RPC traffic of peers which have been removed from the PeerDB.
+        {
+            let peers = self.network_globals.peers.read();
+            self.network_globals
+                .rpc_stats
+                .retain(|peer_id| peers.peer_info(peer_id).is_some());
+        }
+
         // Maintains memory by shrinking mappings
         self.shrink_mappings();
     }
@@ -1232,6 +1510,7 @@ impl<E: EthSpec> PeerManager<E> {
         self.status_peers.shrink_to(5);
         self.temporary_banned_peers.shrink_to_fit();
         self.sync_committee_subnets.shrink_to_fit();
+        self.peer_groups.shrink_to_fit();
     }
 
     // Update metrics related to peer scoring.
@@ -1438,12 +1717,20 @@ enum ConnectingType {
     },
 }
 
+/// The current time in seconds since the UNIX epoch.
+fn unix_time_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::NetworkConfig;
     use slog::{o, Drain};
-    use types::MainnetEthSpec as E;
+    use types::{EnrForkId, Epoch, MainnetEthSpec as E};
 
     pub fn build_log(level: slog::Level, enabled: bool) -> slog::Logger {
         let decorator = slog_term::TermDecorator::new().build();
@@ -1571,6 +1858,66 @@ mod tests {
         assert_eq!(peer_manager.network_globals.connected_or_dialing_peers(), 3);
     }
 
+    #[tokio::test]
+    async fn test_peer_group_members_are_protected() {
+        let group_peer = PeerId::random();
+        let config = config::Config {
+            target_peer_count: 3,
+            discovery_enabled: false,
+            peer_groups: vec![format!("fleet:1:0:/ip4/10.0.0.1/tcp/9000/p2p/{group_peer}")
+                .parse()
+                .unwrap()],
+            ..Default::default()
+        };
+        let network_config = Arc::new(NetworkConfig {
+            target_peers: 3,
+            ..Default::default()
+        });
+        let log = build_log(slog::Level::Debug, false);
+        let spec = Arc::new(E::default_spec());
+        let globals = NetworkGlobals::new_test_globals(vec![], &log, network_config, spec);
+        let mut peer_manager = PeerManager::new(config, Arc::new(globals), &log).unwrap();
+
+        assert_eq!(
+            peer_manager
+                .network_globals
+                .peers
+                .read()
+                .peer_info(&group_peer)
+                .unwrap()
+                .peer_group(),
+            Some("fleet")
+        );
+
+        for _ in 0..4 {
+            peer_manager.inject_connect_ingoing(
+                &PeerId::random(),
+                "/ip4/0.0.0.0".parse().unwrap(),
+                None,
+            );
+        }
+        assert!(peer_manager.has_reserved_slot(&group_peer, ConnectionDirection::Incoming));
+        assert!(!peer_manager.has_reserved_slot(&group_peer, ConnectionDirection::Outgoing));
+        peer_manager.inject_connect_ingoing(&group_peer, "/ip4/0.0.0.0".parse().unwrap(), None);
+        assert!(!peer_manager.has_reserved_slot(&group_peer, ConnectionDirection::Incoming));
+
+        // The group member is exempt from score-based disconnection.
+        peer_manager.report_peer(
+            &group_peer,
+            PeerAction::Fatal,
+            ReportSource::Gossipsub,
+            None,
+            "test",
+        );
+        assert!(peer_manager.is_connected(&group_peer));
+
+        // The group member occupies a reserved slot, so only one regular peer is pruned.
+        assert_eq!(peer_manager.network_globals.connected_or_dialing_peers(), 5);
+        peer_manager.heartbeat();
+        assert_eq!(peer_manager.network_globals.connected_or_dialing_peers(), 4);
+        assert!(peer_manager.is_connected(&group_peer));
+    }
+
     #[tokio::test]
     async fn test_peer_manager_not_enough_outbound_peers_no_panic_during_heartbeat() {
         let mut peer_manager = build_peer_manager(20).await;
@@ -2141,6 +2488,157 @@ mod tests {
         assert!(!connected_peers.contains(&peers[2]));
     }
 
+    #[tokio::test]
+    /// Test that peers are not pruned if doing so would leave a data column subnet with fewer
+    /// than `MIN_CUSTODY_SUBNET_PEERS` custody peers.
+    async fn test_peer_manager_prune_keeps_custody_subnet_peers() {
+        let target = 3;
+        let mut peer_manager = build_peer_manager(target).await;
+
+        // Create 6 peers to connect to. Peer 0 alone custodies subnet 0 and peers 1 and 2 custody
+        // subnet 1.
+        let mut peers = Vec::new();
+        for x in 0..6 {
+            let peer = PeerId::random();
+            peer_manager.inject_connect_ingoing(&peer, "/ip4/0.0.0.0".parse().unwrap(), None);
+
+            let custody_subnets = match x {
+                0 => HashSet::from([DataColumnSubnetId::new(0)]),
+                1 | 2 => HashSet::from([DataColumnSubnetId::new(1)]),
+                _ => HashSet::new(),
+            };
+            peer_manager
+                .network_globals
+                .peers
+                .write()
+                .peer_info_mut(&peer)
+                .unwrap()
+                .set_custody_subnets(custody_subnets);
+            peers.push(peer);
+        }
+
+        // Perform the heartbeat.
+        peer_manager.heartbeat();
+
+        assert_eq!(
+            peer_manager.network_globals.connected_or_dialing_peers(),
+            target
+        );
+
+        // Check that we kept the custody peers.
+        let connected_peers: std::collections::HashSet<_> = peer_manager
+            .network_globals
+            .peers
+            .read()
+            .connected_or_dialing_peers()
+            .cloned()
+            .collect();
+
+        assert!(connected_peers.contains(&peers[0]));
+        assert!(connected_peers.contains(&peers[1]));
+        assert!(connected_peers.contains(&peers[2]));
+    }
+
+    #[tokio::test]
+    /// Test that a custody peer with a negative score is pruned, even if it is the only peer
+    /// custodying its data column subnet.
+    async fn test_peer_manager_prune_negative_score_custody_peer() {
+        let target = 3;
+        let mut peer_manager = build_peer_manager(target).await;
+
+        // Create 6 peers to connect to. Peer 0 alone custodies subnet 0 but has a negative score.
+        let mut peers = Vec::new();
+        for x in 0..6 {
+            let peer = PeerId::random();
+            peer_manager.inject_connect_ingoing(&peer, "/ip4/0.0.0.0".parse().unwrap(), None);
+
+            if x == 0 {
+                let mut peers = peer_manager.network_globals.peers.write();
+                let info = peers.peer_info_mut(&peer).unwrap();
+                info.set_custody_subnets(HashSet::from([DataColumnSubnetId::new(0)]));
+                info.add_to_score(-1.0);
+            }
+            peers.push(peer);
+        }
+
+        // Perform the heartbeat.
+        peer_manager.heartbeat();
+
+        assert_eq!(
+            peer_manager.network_globals.connected_or_dialing_peers(),
+            target
+        );
+        assert!(!peer_manager
+            .network_globals
+            .peers
+            .read()
+            .is_connected_or_dialing(&peers[0]));
+    }
+
+    /// Builds the ENR of a discovered peer, advertising `enr_fork_id` and
+    /// `custody_subnet_count`.
+    fn discovered_enr(enr_fork_id: &EnrForkId, custody_subnet_count: u64) -> Enr {
+        let enr_key = crate::discovery::CombinedKey::generate_secp256k1();
+        let mut builder = Enr::builder();
+        builder.add_value::<alloy_rlp::bytes::Bytes>(
+            crate::discovery::enr::ETH2_ENR_KEY,
+            &ssz::Encode::as_ssz_bytes(enr_fork_id).into(),
+        );
+        builder.add_value(
+            crate::discovery::enr::PEERDAS_CUSTODY_SUBNET_COUNT_ENR_KEY,
+            &custody_subnet_count,
+        );
+        builder.build(&enr_key).unwrap()
+    }
+
+    #[tokio::test]
+    /// Test that discovered peers are ordered by priority, readiness for our next fork and number
+    /// of custodied subnets.
+    async fn test_prioritise_discovered_peers() {
+        let peer_manager = build_peer_manager(3).await;
+        let spec = peer_manager.network_globals.spec.clone();
+        let local_fork_id = EnrForkId {
+            fork_digest: [0; 4],
+            next_fork_version: [1, 0, 0, 0],
+            next_fork_epoch: Epoch::new(10),
+        };
+        let other_fork_id = EnrForkId {
+            next_fork_version: [2, 0, 0, 0],
+            ..local_fork_id.clone()
+        };
+        *peer_manager.network_globals.local_enr.write() = discovered_enr(&local_fork_id, 0);
+
+        let (min, max) = (
+            spec.custody_requirement,
+            spec.data_column_sidecar_subnet_count,
+        );
+        let priority_not_ready = discovered_enr(&other_fork_id, min);
+        let ready_supernode = discovered_enr(&local_fork_id, max);
+        let ready = discovered_enr(&local_fork_id, min);
+        let not_ready_supernode = discovered_enr(&other_fork_id, max);
+        let results = HashMap::from([
+            (ready.clone(), None),
+            (not_ready_supernode.clone(), None),
+            (ready_supernode.clone(), None),
+            (priority_not_ready.clone(), Some(Instant::now())),
+        ]);
+
+        let prioritised = peer_manager
+            .prioritise_discovered_peers(results)
+            .into_iter()
+            .map(|(enr, _, ready_for_next_fork)| (enr, ready_for_next_fork))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            prioritised,
+            vec![
+                (priority_not_ready, false),
+                (ready_supernode, true),
+                (ready, true),
+                (not_ready_supernode, false),
+            ]
+        );
+    }
+
     /// This test is for reproducing the issue:
     /// https://github.com/sigp/lighthouse/pull/3236#issue-1256432659
     ///
@@ -2307,6 +2805,63 @@ mod tests {
         assert!(connected_peers.contains(&peers[7]));
     }
 
+    #[tokio::test]
+    async fn test_restore_persisted_peers() {
+        let mut peer_manager = build_peer_manager(3).await;
+
+        let enr_key = crate::discovery::CombinedKey::generate_secp256k1();
+        let enr = Enr::builder().build(&enr_key).unwrap();
+        let good_peer = enr.peer_id();
+        let bad_peer = PeerId::random();
+
+        peer_manager.inject_connect_outgoing(
+            &good_peer,
+            "/ip4/1.2.3.4/tcp/9000".parse().unwrap(),
+            Some(enr.clone()),
+        );
+        peer_manager.inject_connect_ingoing(
+            &bad_peer,
+            "/ip4/5.6.7.8/tcp/9000".parse().unwrap(),
+            None,
+        );
+        {
+            let mut peers = peer_manager.network_globals.peers.write();
+            let good_info = peers.peer_info_mut(&good_peer).unwrap();
+            good_info.add_to_score(10.0);
+            good_info.update_latency(Duration::from_millis(50));
+            good_info.update_sync_status(SyncStatus::Synced {
+                info: SyncInfo {
+                    head_slot: Default::default(),
+                    head_root: Default::default(),
+                    finalized_epoch: Default::default(),
+                    finalized_root: Default::default(),
+                },
+            });
+            peers.peer_info_mut(&bad_peer).unwrap().add_to_score(-100.0);
+        }
+
+        let persisted = peer_manager.persisted_peers();
+        assert_eq!(persisted.peers.len(), 2);
+
+        // Restore the peers into a new peer manager, as if the node had restarted.
+        let mut peer_manager = build_peer_manager(3).await;
+        peer_manager.restore_peers(persisted);
+
+        let peers = peer_manager.network_globals.peers.read();
+        let good_info = peers.peer_info(&good_peer).unwrap();
+        assert!(good_info.is_disconnected());
+        assert!(good_info.is_useful_for_sync());
+        assert_eq!(good_info.latency(), Some(Duration::from_millis(50)));
+        assert!(good_info.score().score() > 0.0);
+        assert!(peers.peer_info(&bad_peer).unwrap().is_banned());
+        drop(peers);
+
+        assert!(peer_manager.events.iter().any(
+            |event| matches!(event, PeerManagerEvent::Banned(peer_id, _) if *peer_id == bad_peer)
+        ));
+        assert_eq!(peer_manager.peers_to_dial, vec![enr]);
+    }
+
     // Test properties PeerManager should have using randomly generated input.
     #[cfg(test)]
     mod property_based_tests {
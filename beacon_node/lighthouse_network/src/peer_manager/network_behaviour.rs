@@ -2,6 +2,7 @@
 
 use std::net::IpAddr;
 use std::task::{Context, Poll};
+use std::time::Instant;
 
 use futures::StreamExt;
 use libp2p::core::transport::PortUse;
@@ -20,7 +21,7 @@ use crate::discovery::enr_ext::EnrExt;
 use crate::types::SyncState;
 use crate::{metrics, ClearDialError};
 
-use super::{ConnectingType, PeerManager, PeerManagerEvent};
+use super::{ConnectingType, ConnectionDirection, PeerManager, PeerManagerEvent};
 
 impl<E: EthSpec> NetworkBehaviour for PeerManager<E> {
     type ConnectionHandler = ConnectionHandler;
@@ -48,6 +49,7 @@ impl<E: EthSpec> NetworkBehaviour for PeerManager<E> {
             match self.inbound_ping_peers.poll_next_unpin(cx) {
                 Poll::Ready(Some(Ok(peer_id))) => {
                     self.inbound_ping_peers.insert(peer_id);
+                    self.pings_in_flight.insert(peer_id, Instant::now());
                     self.events.push(PeerManagerEvent::Ping(peer_id));
                 }
                 Poll::Ready(Some(Err(e))) => {
@@ -61,6 +63,7 @@ impl<E: EthSpec> NetworkBehaviour for PeerManager<E> {
             match self.outbound_ping_peers.poll_next_unpin(cx) {
                 Poll::Ready(Some(Ok(peer_id))) => {
                     self.outbound_ping_peers.insert(peer_id);
+                    self.pings_in_flight.insert(peer_id, Instant::now());
                     self.events.push(PeerManagerEvent::Ping(peer_id));
                 }
                 Poll::Ready(Some(Err(e))) => {
@@ -94,6 +97,22 @@ impl<E: EthSpec> NetworkBehaviour for PeerManager<E> {
             self.events.shrink_to_fit();
         }
 
+        // Re-dial members of peering groups which are due.
+        while let Poll::Ready(Some((peer_id, addresses))) = self.peer_groups.poll_redial(cx) {
+            if !self.network_globals.peers.read().should_dial(&peer_id) {
+                continue;
+            }
+            self.inject_peer_connection(&peer_id, ConnectingType::Dialing, None);
+
+            debug!(self.log, "Dialing peer group member"; "peer_id" => %peer_id, "multiaddrs" => ?addresses);
+            return Poll::Ready(ToSwarm::Dial {
+                opts: DialOpts::peer_id(peer_id)
+                    .condition(PeerCondition::DisconnectedAndNotDialing)
+                    .addresses(addresses)
+                    .build(),
+            });
+        }
+
         if let Some(enr) = self.peers_to_dial.pop() {
             self.inject_peer_connection(&enr.peer_id(), ConnectingType::Dialing, Some(enr.clone()));
 
@@ -192,7 +211,8 @@ impl<E: EthSpec> NetworkBehaviour for PeerManager<E> {
         }
 
         // Check the connection limits
-        if self.network_globals.connected_or_dialing_peers() >= self.max_peers()
+        if self.limited_connected_or_dialing_peers() >= self.max_peers()
+            && !self.has_reserved_slot(&peer_id, ConnectionDirection::Incoming)
             && self
                 .network_globals
                 .peers
@@ -231,7 +251,8 @@ impl<E: EthSpec> NetworkBehaviour for PeerManager<E> {
         }
 
         // Check the connection limits
-        if self.network_globals.connected_peers() >= self.max_outbound_dialing_peers()
+        if self.limited_connected_peers() >= self.max_outbound_dialing_peers()
+            && !self.has_reserved_slot(&peer_id, ConnectionDirection::Outgoing)
             && self
                 .network_globals
                 .peers
@@ -269,6 +290,8 @@ impl<E: EthSpec> PeerManager<E> {
 
         // NOTE: We don't register peers that we are disconnecting immediately. The network service
         // does not need to know about these peers.
+        self.peer_groups.on_connected(&peer_id);
+
         match endpoint {
             ConnectedPoint::Listener { send_back_addr, .. } => {
                 self.inject_connect_ingoing(&peer_id, send_back_addr.clone(), None);
@@ -313,6 +336,7 @@ impl<E: EthSpec> PeerManager<E> {
         // here and the peer manager has no knowledge of its connection. We insert it here for
         // reference so that peer manager can track this peer.
         self.inject_disconnect(&peer_id);
+        self.peer_groups.on_disconnected(&peer_id, false);
 
         // Update the prometheus metrics
         if self.metrics_enabled {
@@ -332,6 +356,7 @@ impl<E: EthSpec> PeerManager<E> {
         if let Some(peer_id) = peer_id {
             if !self.network_globals.peers.read().is_connected(&peer_id) {
                 self.inject_disconnect(&peer_id);
+                self.peer_groups.on_disconnected(&peer_id, true);
             }
         }
     }
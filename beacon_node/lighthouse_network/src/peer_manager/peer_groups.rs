@@ -0,0 +1,280 @@
+//! Static peering groups.
+//!
+//! A peering group is a named set of peers which we always want to be connected to, e.g. the
+//! other nodes of an operator's fleet or a partner's relays. Members of a group:
+//!
+//! - Are dialed on startup and re-dialed with an exponential backoff whenever they disconnect.
+//! - May connect in excess of our peer limits, up to the inbound and outbound slots reserved for
+//!   their group. Peers occupying reserved slots do not count towards the peer limits.
+//! - Are never pruned and are exempt from score-based disconnection and banning.
+
+use super::peerdb::PeerDB;
+use super::ConnectionDirection;
+use delay_map::HashSetDelay;
+use futures::StreamExt;
+use libp2p::multiaddr::Protocol;
+use libp2p::{Multiaddr, PeerId};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use types::EthSpec;
+
+/// The delay before re-dialing a group member which has disconnected.
+const MIN_REDIAL_DELAY: Duration = Duration::from_secs(1);
+/// The maximum delay between attempts to re-dial an unreachable group member.
+const MAX_REDIAL_DELAY: Duration = Duration::from_secs(120);
+
+/// The configuration of a static peering group.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerGroupConfig {
+    /// The name of the group, shown in the `PeerInfo` of its members.
+    pub name: String,
+    /// The number of inbound connection slots reserved for members of the group.
+    pub reserved_inbound: usize,
+    /// The number of outbound connection slots reserved for members of the group.
+    pub reserved_outbound: usize,
+    /// The addresses of the members of the group, each ending with a `/p2p/<peer-id>` component.
+    pub peers: Vec<Multiaddr>,
+}
+
+impl PeerGroupConfig {
+    /// The ids of the members of the group.
+    pub fn peer_ids(&self) -> impl Iterator<Item = PeerId> + '_ {
+        self.peers.iter().filter_map(peer_id_of)
+    }
+}
+
+impl FromStr for PeerGroupConfig {
+    type Err = String;
+
+    /// Parses a group of the form `NAME:RESERVED_INBOUND:RESERVED_OUTBOUND:MULTIADDR,MULTIADDR,..`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(4, ':');
+        let (Some(name), Some(inbound), Some(outbound), Some(peers)) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(format!(
+                "Invalid peer group {}, expected NAME:RESERVED_INBOUND:RESERVED_OUTBOUND:MULTIADDRS",
+                s
+            ));
+        };
+        if name.is_empty() {
+            return Err("Peer group names must not be empty".to_string());
+        }
+        let reserved_inbound = inbound
+            .parse()
+            .map_err(|_| format!("Invalid reserved inbound slots for {}: {}", name, inbound))?;
+        let reserved_outbound = outbound
+            .parse()
+            .map_err(|_| format!("Invalid reserved outbound slots for {}: {}", name, outbound))?;
+        let peers = peers
+            .split(',')
+            .map(|multiaddr| {
+                let multiaddr = multiaddr
+                    .parse()
+                    .map_err(|_| format!("Invalid Multiaddr: {}", multiaddr))?;
+                if peer_id_of(&multiaddr).is_none() {
+                    return Err(format!(
+                        "Peer group Multiaddr must end with /p2p/<peer-id>: {}",
+                        multiaddr
+                    ));
+                }
+                Ok(multiaddr)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(PeerGroupConfig {
+            name: name.to_string(),
+            reserved_inbound,
+            reserved_outbound,
+            peers,
+        })
+    }
+}
+
+/// Returns the peer id of the `/p2p/<peer-id>` component which ends `multiaddr`, if any.
+fn peer_id_of(multiaddr: &Multiaddr) -> Option<PeerId> {
+    match multiaddr.iter().last() {
+        Some(Protocol::P2p(peer_id)) => Some(peer_id),
+        _ => None,
+    }
+}
+
+/// A member of a peering group.
+struct Member {
+    /// The index of the member's group.
+    group: usize,
+    /// The addresses to dial the member on, without the `/p2p` component.
+    addresses: Vec<Multiaddr>,
+    /// The number of consecutive failed dials of the member.
+    failed_dials: u32,
+}
+
+/// The state of the static peering groups.
+pub(crate) struct PeerGroups {
+    groups: Vec<PeerGroupConfig>,
+    members: HashMap<PeerId, Member>,
+    /// Members which are waiting to be re-dialed.
+    redial: HashSetDelay<PeerId>,
+}
+
+impl PeerGroups {
+    /// Creates the peering groups, scheduling all of their members to be dialed immediately.
+    pub fn new(groups: Vec<PeerGroupConfig>) -> Self {
+        let mut members: HashMap<PeerId, Member> = HashMap::new();
+        let mut redial = HashSetDelay::new(MIN_REDIAL_DELAY);
+        for (index, group) in groups.iter().enumerate() {
+            for multiaddr in &group.peers {
+                let Some(peer_id) = peer_id_of(multiaddr) else {
+                    continue;
+                };
+                let mut address = multiaddr.clone();
+                address.pop();
+                // A peer listed in multiple groups belongs to the first.
+                let member = members.entry(peer_id).or_insert_with(|| Member {
+                    group: index,
+                    addresses: vec![],
+                    failed_dials: 0,
+                });
+                if member.group == index {
+                    member.addresses.push(address);
+                }
+                redial.insert_at(peer_id, Duration::ZERO);
+            }
+        }
+        PeerGroups {
+            groups,
+            members,
+            redial,
+        }
+    }
+
+    /// The name of each group member's group.
+    pub fn member_groups(&self) -> impl Iterator<Item = (&PeerId, &str)> {
+        self.members
+            .iter()
+            .map(|(peer_id, member)| (peer_id, self.groups[member.group].name.as_str()))
+    }
+
+    /// Returns true if `peer_id` is a member of a group and a slot reserved for its group is free
+    /// for a new connection in `direction`.
+    pub fn has_free_slot<E: EthSpec>(
+        &self,
+        peer_id: &PeerId,
+        direction: ConnectionDirection,
+        peerdb: &PeerDB<E>,
+    ) -> bool {
+        let Some(member) = self.members.get(peer_id) else {
+            return false;
+        };
+        let (inbound, outbound) = self.connected_members(member.group, peerdb);
+        match direction {
+            ConnectionDirection::Incoming => inbound < self.groups[member.group].reserved_inbound,
+            ConnectionDirection::Outgoing => outbound < self.groups[member.group].reserved_outbound,
+        }
+    }
+
+    /// The number of connected peers which occupy reserved slots. These peers do not count
+    /// towards the peer limits.
+    pub fn occupied_slots<E: EthSpec>(&self, peerdb: &PeerDB<E>) -> usize {
+        self.groups
+            .iter()
+            .enumerate()
+            .map(|(index, group)| {
+                let (inbound, outbound) = self.connected_members(index, peerdb);
+                inbound.min(group.reserved_inbound) + outbound.min(group.reserved_outbound)
+            })
+            .sum()
+    }
+
+    /// The number of connected members of a group, by the direction of their connection.
+    fn connected_members<E: EthSpec>(&self, group: usize, peerdb: &PeerDB<E>) -> (usize, usize) {
+        self.members
+            .iter()
+            .filter(|(_, member)| member.group == group)
+            .filter_map(|(peer_id, _)| peerdb.peer_info(peer_id))
+            .filter(|info| info.is_connected())
+            .fold((0, 0), |(inbound, outbound), info| {
+                match info.connection_direction() {
+                    Some(ConnectionDirection::Incoming) => (inbound + 1, outbound),
+                    Some(ConnectionDirection::Outgoing) => (inbound, outbound + 1),
+                    None => (inbound, outbound),
+                }
+            })
+    }
+
+    /// A member has connected, reset its backoff.
+    pub fn on_connected(&mut self, peer_id: &PeerId) {
+        if let Some(member) = self.members.get_mut(peer_id) {
+            member.failed_dials = 0;
+            self.redial.remove(peer_id);
+        }
+    }
+
+    /// A member has disconnected or could not be dialed, schedule it to be re-dialed.
+    pub fn on_disconnected(&mut self, peer_id: &PeerId, dial_failed: bool) {
+        if let Some(member) = self.members.get_mut(peer_id) {
+            if dial_failed {
+                member.failed_dials = member.failed_dials.saturating_add(1);
+            }
+            let delay = MIN_REDIAL_DELAY
+                .saturating_mul(2u32.saturating_pow(member.failed_dials))
+                .min(MAX_REDIAL_DELAY);
+            self.redial.insert_at(*peer_id, delay);
+        }
+    }
+
+    /// Polls for the next member which is due to be re-dialed, along with its addresses.
+    pub fn poll_redial(&mut self, cx: &mut Context<'_>) -> Poll<Option<(PeerId, Vec<Multiaddr>)>> {
+        loop {
+            match self.redial.poll_next_unpin(cx) {
+                Poll::Ready(Some(Ok(peer_id))) => {
+                    if let Some(member) = self.members.get(&peer_id) {
+                        return Poll::Ready(Some((peer_id, member.addresses.clone())));
+                    }
+                }
+                Poll::Ready(Some(Err(_))) => continue,
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.redial.shrink_to(self.members.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_peer_group() {
+        let peer_id = PeerId::random();
+        let group: PeerGroupConfig = format!(
+            "fleet:2:3:/ip4/10.0.0.1/tcp/9000/p2p/{peer_id},/ip6/::1/udp/9001/quic-v1/p2p/{peer_id}"
+        )
+        .parse()
+        .unwrap();
+
+        assert_eq!(group.name, "fleet");
+        assert_eq!(group.reserved_inbound, 2);
+        assert_eq!(group.reserved_outbound, 3);
+        assert_eq!(group.peers.len(), 2);
+        assert_eq!(group.peer_ids().collect::<Vec<_>>(), vec![peer_id, peer_id]);
+
+        // The peer id is required.
+        assert!("fleet:2:3:/ip4/10.0.0.1/tcp/9000"
+            .parse::<PeerGroupConfig>()
+            .is_err());
+        assert!(format!("fleet:2:/ip4/10.0.0.1/tcp/9000/p2p/{peer_id}")
+            .parse::<PeerGroupConfig>()
+            .is_err());
+        assert!(format!(":2:3:/ip4/10.0.0.1/tcp/9000/p2p/{peer_id}")
+            .parse::<PeerGroupConfig>()
+            .is_err());
+    }
+}
@@ -1,12 +1,14 @@
 use crate::discovery::enr::PEERDAS_CUSTODY_SUBNET_COUNT_ENR_KEY;
 use crate::discovery::{peer_id_to_node_id, CombinedKey};
-use crate::{metrics, multiaddr::Multiaddr, types::Subnet, Enr, EnrExt, Gossipsub, PeerId};
+use crate::types::{GossipTopic, Subnet};
+use crate::{metrics, multiaddr::Multiaddr, Enr, EnrExt, Gossipsub, PeerId};
 use itertools::Itertools;
 use peer_info::{ConnectionDirection, PeerConnectionStatus, PeerInfo};
+use persisted::PersistedPeer;
 use score::{PeerAction, ReportSource, Score, ScoreState};
 use slog::{crit, debug, error, trace, warn};
 use std::net::IpAddr;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use std::{cmp::Ordering, fmt::Display};
 use std::{
     collections::{HashMap, HashSet},
@@ -17,7 +19,9 @@ use types::{ChainSpec, DataColumnSubnetId, EthSpec};
 
 pub mod client;
 pub mod peer_info;
+pub mod persisted;
 pub mod score;
+pub mod stats;
 pub mod sync_status;
 
 /// Max number of disconnected nodes to remember.
@@ -255,11 +259,8 @@ impl<E: EthSpec> PeerDB<E> {
     ) -> impl Iterator<Item = &PeerId> {
         self.peers
             .iter()
-            .filter(move |(_, info)| {
-                // The custody_subnets hashset can be populated via enr or metadata
-                let is_custody_subnet_peer = info.is_assigned_to_custody_subnet(&subnet);
-                info.is_connected() && info.is_good_gossipsub_peer() && is_custody_subnet_peer
-            })
+            // The custody_subnets hashset can be populated via enr or metadata
+            .filter(move |(_, info)| info.is_good_custody_subnet_peer(&subnet))
             .map(|(peer_id, _)| peer_id)
     }
 
@@ -511,6 +512,34 @@ impl<E: EthSpec> PeerDB<E> {
         results
     }
 
+    /// Updates the gossip traffic of all connected peers from the traffic accounted by gossipsub
+    /// for their current connection.
+    pub(super) fn update_gossip_stats(&mut self, gossipsub: &Gossipsub) {
+        for (peer_id, info) in self.peers.iter_mut() {
+            if !info.is_connected() {
+                continue;
+            }
+            let Some(traffic) = gossipsub.peer_traffic(peer_id) else {
+                continue;
+            };
+            let gossip = info.gossip_stats_mut();
+            gossip.clear();
+            for (topic, traffic) in traffic {
+                // Topics are grouped by kind, irrespective of the fork digest and encoding.
+                let kind = match GossipTopic::decode(topic.as_str()) {
+                    Ok(topic) => topic.kind().to_string(),
+                    Err(_) => topic.to_string(),
+                };
+                let stats = gossip.entry(kind).or_default();
+                stats.messages_in += traffic.first_deliveries;
+                stats.duplicates_in += traffic.duplicates;
+                stats.bytes_in += traffic.bytes_received;
+                stats.messages_out += traffic.messages_sent;
+                stats.bytes_out += traffic.bytes_sent;
+            }
+        }
+    }
+
     /// Reports a peer for some action.
     ///
     /// The action can only cause a negative effect. This can lead to disconnecting or banning a
@@ -1045,6 +1074,95 @@ impl<E: EthSpec> PeerDB<E> {
         self.update_connection_state(peer_id, NewConnectionState::Disconnecting { to_ban });
     }
 
+    /// Registers a member of a static peering group, exempting it from scoring and pruning.
+    // VISIBILITY: Only the peer manager can configure peering groups.
+    pub(super) fn set_peer_group(&mut self, peer_id: PeerId, peer_group: &str) {
+        self.peers
+            .entry(peer_id)
+            .or_default()
+            .set_peer_group(peer_group.to_string());
+    }
+
+    /// Returns the reputation of all non-protected peers, to be persisted across restarts.
+    pub fn persisted_peers(&self) -> Vec<PersistedPeer> {
+        self.peers
+            .iter()
+            .filter_map(|(peer_id, info)| info.to_persisted(peer_id))
+            .collect()
+    }
+
+    /// Restores peers whose reputation was persisted `elapsed` ago as disconnected peers. Peers
+    /// which are already known are left untouched.
+    ///
+    /// Returns the restored peers that remain banned along with their banned IP addresses, and
+    /// the ENRs of the healthy restored peers ordered from most to least preferable.
+    // VISIBILITY: Only the peer manager can adjust the connection state.
+    pub(super) fn restore_peers(
+        &mut self,
+        persisted_peers: Vec<PersistedPeer>,
+        elapsed: Duration,
+    ) -> (Vec<(PeerId, Vec<IpAddr>)>, Vec<Enr>) {
+        if self.disable_peer_scoring {
+            return (vec![], vec![]);
+        }
+
+        let mut restored = vec![];
+        for persisted in &persisted_peers {
+            let (peer_id, info) = match PeerInfo::from_persisted(persisted, elapsed) {
+                Ok(restored) => restored,
+                Err(e) => {
+                    warn!(self.log, "Unable to restore persisted peer"; "error" => e);
+                    continue;
+                }
+            };
+            if self.peers.contains_key(&peer_id) {
+                continue;
+            }
+            self.peers.insert(peer_id, info);
+            self.disconnected_peers += 1;
+            restored.push(peer_id);
+        }
+
+        let mut banned_peers = vec![];
+        for peer_id in &restored {
+            if let Some(ScoreState::Banned) = self.peers.get(peer_id).map(|i| i.score_state()) {
+                if let Some(BanOperation::ReadyToBan(banned_ips)) =
+                    self.update_connection_state(peer_id, NewConnectionState::Banned)
+                {
+                    banned_peers.push((*peer_id, banned_ips));
+                }
+            }
+        }
+
+        // Respect our limits on disconnected and banned peers. Peers which are dropped here were
+        // never banned at the swarm level, so there is nothing to unban.
+        let _ = self.shrink_to_fit();
+        banned_peers.retain(|(peer_id, _)| self.peers.contains_key(peer_id));
+
+        let mut healthy_peers = restored
+            .iter()
+            .filter_map(|peer_id| self.peers.get(peer_id))
+            .filter(|info| matches!(info.score_state(), ScoreState::Healthy))
+            .filter(|info| info.enr().is_some())
+            .collect::<Vec<_>>();
+        healthy_peers.sort_by(|a, b| {
+            b.is_useful_for_sync()
+                .cmp(&a.is_useful_for_sync())
+                .then_with(|| a.score().total_cmp(b.score(), true))
+                .then_with(|| {
+                    a.latency()
+                        .unwrap_or(Duration::MAX)
+                        .cmp(&b.latency().unwrap_or(Duration::MAX))
+                })
+        });
+        let peers_to_dial = healthy_peers
+            .into_iter()
+            .filter_map(|info| info.enr().cloned())
+            .collect();
+
+        (banned_peers, peers_to_dial)
+    }
+
     /// Removes banned and disconnected peers from the DB if we have reached any of our limits.
     /// Drops the peers with the lowest reputation so that the number of disconnected peers is less
     /// than MAX_DC_PEERS
@@ -1096,7 +1214,7 @@ impl<E: EthSpec> PeerDB<E> {
             if let Some(to_drop) = self
                 .peers
                 .iter()
-                .filter(|(_, info)| info.is_disconnected() && !info.is_trusted())
+                .filter(|(_, info)| info.is_disconnected() && !info.is_protected())
                 .filter_map(|(id, info)| match info.connection_status() {
                     PeerConnectionStatus::Disconnected { since } => Some((id, since)),
                     _ => None,
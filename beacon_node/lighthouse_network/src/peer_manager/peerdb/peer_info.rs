@@ -1,21 +1,28 @@
 use super::client::Client;
+use super::persisted::PersistedPeer;
 use super::score::{PeerAction, Score, ScoreState};
+use super::stats::GossipStats;
 use super::sync_status::SyncStatus;
 use crate::discovery::Eth2Enr;
 use crate::{rpc::MetaData, types::Subnet};
 use discv5::Enr;
 use libp2p::core::multiaddr::{Multiaddr, Protocol};
+use libp2p::PeerId;
 use serde::{
     ser::{SerializeStruct, Serializer},
     Serialize,
 };
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::net::IpAddr;
-use std::time::Instant;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
 use strum::AsRefStr;
 use types::{DataColumnSubnetId, EthSpec};
 use PeerConnectionStatus::*;
 
+/// The weight given to each new ping round trip time when updating a peer's latency.
+const LATENCY_SMOOTHING_FACTOR: f64 = 0.2;
+
 /// Information about a given connected peer.
 #[derive(Clone, Debug, Serialize)]
 #[serde(bound = "E: EthSpec")]
@@ -51,11 +58,22 @@ pub struct PeerInfo<E: EthSpec> {
     min_ttl: Option<Instant>,
     /// Is the peer a trusted peer.
     is_trusted: bool,
+    /// The static peering group the peer is a member of, if any.
+    peer_group: Option<String>,
     /// Direction of the first connection of the last (or current) connected session with this peer.
     /// None if this peer was never connected.
     connection_direction: Option<ConnectionDirection>,
     /// The enr of the peer, if known.
     enr: Option<Enr>,
+    /// The smoothed round trip time of our pings to the peer, if measured.
+    #[serde(skip)]
+    latency: Option<Duration>,
+    /// Whether the peer was synced with or advanced of our chain the last time its sync status
+    /// was known. Unlike `sync_status`, this is retained across connections.
+    useful_for_sync: bool,
+    /// The gossip traffic exchanged with the peer, keyed by topic kind.
+    #[serde(skip)]
+    gossip_stats: BTreeMap<String, GossipStats>,
 }
 
 impl<E: EthSpec> Default for PeerInfo<E> {
@@ -72,8 +90,12 @@ impl<E: EthSpec> Default for PeerInfo<E> {
             meta_data: None,
             min_ttl: None,
             is_trusted: false,
+            peer_group: None,
             connection_direction: None,
             enr: None,
+            latency: None,
+            useful_for_sync: false,
+            gossip_stats: BTreeMap::new(),
         }
     }
 }
@@ -137,6 +159,17 @@ impl<E: EthSpec> PeerInfo<E> {
         self.is_trusted
     }
 
+    /// The static peering group the peer is a member of, if any.
+    pub fn peer_group(&self) -> Option<&str> {
+        self.peer_group.as_deref()
+    }
+
+    /// Returns true if the peer is trusted or a member of a peering group. Such peers are never
+    /// pruned.
+    pub fn is_protected(&self) -> bool {
+        self.is_trusted || self.peer_group.is_some()
+    }
+
     /// The time a peer is expected to be useful until for an attached validator. If this is set to
     /// None, the peer is not required for any upcoming duty.
     pub fn min_ttl(&self) -> Option<&Instant> {
@@ -148,6 +181,21 @@ impl<E: EthSpec> PeerInfo<E> {
         self.enr.as_ref()
     }
 
+    /// The smoothed round trip time of our pings to the peer, if measured.
+    pub fn latency(&self) -> Option<Duration> {
+        self.latency
+    }
+
+    /// Whether the peer was useful for syncing the last time its sync status was known.
+    pub fn is_useful_for_sync(&self) -> bool {
+        self.useful_for_sync
+    }
+
+    /// The gossip traffic exchanged with the peer, keyed by topic kind.
+    pub fn gossip_stats(&self) -> &BTreeMap<String, GossipStats> {
+        &self.gossip_stats
+    }
+
     /// An iterator over all the subnets this peer is subscribed to.
     pub fn subnets(&self) -> impl Iterator<Item = &Subnet> {
         self.subnets.iter()
@@ -214,6 +262,21 @@ impl<E: EthSpec> PeerInfo<E> {
         self.custody_subnets.contains(subnet)
     }
 
+    /// Returns if the peer is a connected, good gossipsub peer assigned to the given
+    /// `DataColumnSubnetId`, so that it can be relied upon for the columns of the subnet.
+    pub fn is_good_custody_subnet_peer(&self, subnet: &DataColumnSubnetId) -> bool {
+        self.is_connected()
+            && self.is_good_gossipsub_peer()
+            && self.is_assigned_to_custody_subnet(subnet)
+    }
+
+    /// Returns the data column subnets the peer is a good custody peer for.
+    pub fn good_custody_subnets(&self) -> impl Iterator<Item = &DataColumnSubnetId> {
+        self.custody_subnets
+            .iter()
+            .filter(|subnet| self.is_good_custody_subnet_peer(subnet))
+    }
+
     /// Returns true if the peer is connected to a long-lived subnet.
     pub fn has_long_lived_subnet(&self) -> bool {
         // Check the meta_data
@@ -336,9 +399,25 @@ impl<E: EthSpec> PeerInfo<E> {
     /// Updates the sync status. Returns true if the status was changed.
     // VISIBILITY: Both the peer manager the network sync is able to update the sync state of a peer
     pub fn update_sync_status(&mut self, sync_status: SyncStatus) -> bool {
+        match sync_status {
+            SyncStatus::Synced { .. } | SyncStatus::Advanced { .. } => self.useful_for_sync = true,
+            SyncStatus::Behind { .. } | SyncStatus::IrrelevantPeer => self.useful_for_sync = false,
+            SyncStatus::Unknown => {}
+        }
         self.sync_status.update(sync_status)
     }
 
+    /// Updates the smoothed latency of the peer with the round trip time of a ping.
+    pub(in crate::peer_manager) fn update_latency(&mut self, round_trip_time: Duration) {
+        self.latency = Some(match self.latency {
+            Some(latency) => {
+                latency.mul_f64(1.0 - LATENCY_SMOOTHING_FACTOR)
+                    + round_trip_time.mul_f64(LATENCY_SMOOTHING_FACTOR)
+            }
+            None => round_trip_time,
+        });
+    }
+
     /// Sets the client of the peer.
     // VISIBILITY: The peer manager is able to set the client
     pub(in crate::peer_manager) fn set_client(&mut self, client: Client) {
@@ -373,6 +452,12 @@ impl<E: EthSpec> PeerInfo<E> {
         self.custody_subnets = custody_subnets
     }
 
+    /// Makes the peer a member of a peering group. Members of peering groups are not scored.
+    pub(super) fn set_peer_group(&mut self, peer_group: String) {
+        self.peer_group = Some(peer_group);
+        self.score = Score::max_score();
+    }
+
     /// Sets the ENR of the peer if one is known.
     pub(super) fn set_enr(&mut self, enr: Enr) {
         self.enr = Some(enr)
@@ -383,6 +468,11 @@ impl<E: EthSpec> PeerInfo<E> {
         self.min_ttl = Some(min_ttl)
     }
 
+    /// Returns the gossip traffic accounting of the peer to be updated.
+    pub(super) fn gossip_stats_mut(&mut self) -> &mut BTreeMap<String, GossipStats> {
+        &mut self.gossip_stats
+    }
+
     /// Adds a known subnet for the peer.
     pub(super) fn insert_subnet(&mut self, subnet: Subnet) {
         self.subnets.insert(subnet);
@@ -398,17 +488,17 @@ impl<E: EthSpec> PeerInfo<E> {
         self.subnets.clear()
     }
 
-    /// Applies decay rates to a non-trusted peer's score.
+    /// Applies decay rates to a non-protected peer's score.
     pub(super) fn score_update(&mut self) {
-        if !self.is_trusted {
+        if !self.is_protected() {
             self.score.update()
         }
     }
 
-    /// Apply peer action to a non-trusted peer's score.
+    /// Apply peer action to a non-protected peer's score.
     // VISIBILITY: The peer manager is able to modify the score of a peer.
     pub(in crate::peer_manager) fn apply_peer_action_to_score(&mut self, peer_action: PeerAction) {
-        if !self.is_trusted {
+        if !self.is_protected() {
             self.score.apply_peer_action(peer_action)
         }
     }
@@ -482,6 +572,82 @@ impl<E: EthSpec> PeerInfo<E> {
         }
     }
 
+    /// Returns the reputation of the peer to be persisted across restarts, or `None` if the peer
+    /// is trusted or a member of a peering group.
+    pub(super) fn to_persisted(&self, peer_id: &PeerId) -> Option<PersistedPeer> {
+        if self.is_protected() {
+            return None;
+        }
+        let (score, decay_delay) = self.score.persisted()?;
+        Some(PersistedPeer {
+            peer_id: peer_id.to_bytes(),
+            score: score.to_bits(),
+            decay_delay_secs: decay_delay.as_secs(),
+            agent_version: self
+                .client
+                .agent_string
+                .as_ref()
+                .map(|agent| agent.as_bytes().to_vec()),
+            protocol_version: self.client.protocol_version.as_bytes().to_vec(),
+            latency_ms: self.latency.map(|latency| latency.as_millis() as u64),
+            useful_for_sync: self.useful_for_sync,
+            enr: self.enr.as_ref().map(|enr| enr.to_base64().into_bytes()),
+            seen_multiaddrs: self
+                .seen_multiaddrs
+                .iter()
+                .map(|addr| addr.to_vec())
+                .collect(),
+        })
+    }
+
+    /// Restores a disconnected peer from its reputation, persisted `elapsed` ago.
+    pub(super) fn from_persisted(
+        persisted: &PersistedPeer,
+        elapsed: Duration,
+    ) -> Result<(PeerId, Self), String> {
+        let peer_id = PeerId::from_bytes(&persisted.peer_id)
+            .map_err(|e| format!("Invalid peer id: {e:?}"))?;
+        let utf8 = |bytes: &[u8]| {
+            String::from_utf8(bytes.to_vec()).map_err(|e| format!("Invalid string: {e:?}"))
+        };
+        let client = match &persisted.agent_version {
+            Some(agent_version) => {
+                Client::from_agent_version(utf8(agent_version)?, utf8(&persisted.protocol_version)?)
+            }
+            None => Client::default(),
+        };
+        let enr = persisted
+            .enr
+            .as_ref()
+            .map(|enr| Enr::from_str(&utf8(enr)?))
+            .transpose()?;
+        let seen_multiaddrs = persisted
+            .seen_multiaddrs
+            .iter()
+            .map(|addr| {
+                Multiaddr::try_from(addr.clone()).map_err(|e| format!("Invalid multiaddr: {e:?}"))
+            })
+            .collect::<Result<_, _>>()?;
+
+        let info = PeerInfo {
+            score: Score::restore(
+                f64::from_bits(persisted.score),
+                Duration::from_secs(persisted.decay_delay_secs),
+                elapsed,
+            ),
+            client,
+            connection_status: PeerConnectionStatus::Disconnected {
+                since: Instant::now(),
+            },
+            seen_multiaddrs,
+            enr,
+            latency: persisted.latency_ms.map(Duration::from_millis),
+            useful_for_sync: persisted.useful_for_sync,
+            ..Default::default()
+        };
+        Ok((peer_id, info))
+    }
+
     #[cfg(test)]
     /// Add an f64 to a non-trusted peer's score abiding by the limits.
     pub fn add_to_score(&mut self, score: f64) {
@@ -0,0 +1,39 @@
+//! The reputation of peers, in a form which can be persisted across restarts.
+//!
+//! This allows a restarted node to immediately prefer peers which were useful to it previously and
+//! to keep misbehaving peers banned.
+use ssz_derive::{Decode, Encode};
+
+/// The reputation of a single peer.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct PersistedPeer {
+    /// The bytes of the peer's `PeerId`.
+    pub peer_id: Vec<u8>,
+    /// The lighthouse component of the peer's score, as the bits of an `f64`.
+    pub score: u64,
+    /// The number of seconds remaining before the score begins to decay, i.e. the remainder of a
+    /// ban.
+    pub decay_delay_secs: u64,
+    /// The identify agent string of the peer, if it was known.
+    pub agent_version: Option<Vec<u8>>,
+    /// The libp2p protocol version of the peer.
+    pub protocol_version: Vec<u8>,
+    /// The smoothed round trip time of our pings to the peer in milliseconds, if measured.
+    pub latency_ms: Option<u64>,
+    /// Whether the peer was useful for syncing the last time its status was known.
+    pub useful_for_sync: bool,
+    /// The base64 encoding of the peer's ENR, if it was known.
+    pub enr: Option<Vec<u8>>,
+    /// The multiaddrs the peer has connected from, used to restore IP bans.
+    pub seen_multiaddrs: Vec<Vec<u8>>,
+}
+
+/// The reputation of all the peers in the `PeerDB` at the time it was persisted.
+#[derive(Debug, Clone, PartialEq, Default, Encode, Decode)]
+pub struct PersistedPeers {
+    /// The time the peers were persisted, in seconds since the UNIX epoch.
+    ///
+    /// Used to account for the time the node was offline when restoring scores.
+    pub persisted_at: u64,
+    pub peers: Vec<PersistedPeer>,
+}
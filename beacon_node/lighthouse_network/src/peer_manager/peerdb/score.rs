@@ -259,6 +259,31 @@ impl RealScore {
     pub fn is_good_gossipsub_peer(&self) -> bool {
         self.gossipsub_score >= 0.0
     }
+
+    /// Restores a score which was persisted `elapsed` ago, when its lighthouse component was
+    /// `lighthouse_score` and it had `decay_delay` remaining before it would begin to decay.
+    ///
+    /// The gossipsub component is not restored, as gossipsub scores are not retained across
+    /// connections.
+    fn restore(lighthouse_score: f64, decay_delay: Duration, elapsed: Duration) -> Self {
+        let now = Instant::now();
+        let mut score = RealScore {
+            lighthouse_score: lighthouse_score.clamp(MIN_SCORE, MAX_SCORE),
+            last_updated: now,
+            ..Default::default()
+        };
+        match decay_delay.checked_sub(elapsed) {
+            // The peer is still serving a ban, resume it where it left off.
+            Some(remaining) => score.last_updated = now + remaining,
+            // Apply the decay which would have occurred whilst we were offline.
+            None => {
+                let secs_since_update = (elapsed - decay_delay).as_secs();
+                score.lighthouse_score *= (*HALFLIFE_DECAY * secs_since_update as f64).exp();
+            }
+        }
+        score.recompute_score();
+        score
+    }
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -309,6 +334,24 @@ impl Score {
         Self::Max
     }
 
+    /// Restores a score from the values returned by `Score::persisted`, `elapsed` after they were
+    /// taken.
+    pub(crate) fn restore(lighthouse_score: f64, decay_delay: Duration, elapsed: Duration) -> Self {
+        Self::Real(RealScore::restore(lighthouse_score, decay_delay, elapsed))
+    }
+
+    /// Returns the lighthouse component of the score and the time remaining before it begins to
+    /// decay (i.e. the remainder of a ban), or `None` for the maximum score.
+    pub(crate) fn persisted(&self) -> Option<(f64, Duration)> {
+        match self {
+            Self::Max => None,
+            Self::Real(score) => Some((
+                score.lighthouse_score,
+                score.last_updated.saturating_duration_since(Instant::now()),
+            )),
+        }
+    }
+
     /// Returns the expected state of the peer given it's score.
     pub(crate) fn state(&self) -> ScoreState {
         match self.score() {
@@ -403,6 +446,36 @@ mod tests {
         assert!(score.score() > MIN_SCORE_BEFORE_BAN);
     }
 
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_restore_score() {
+        // A banned peer remains banned for the remainder of its ban.
+        let mut score = RealScore::default();
+        score.test_add(MIN_SCORE);
+        let Some((lighthouse_score, decay_delay)) = Score::Real(score).persisted() else {
+            panic!("real scores are persisted");
+        };
+        let elapsed = Duration::from_secs(3600);
+        let restored = Score::restore(lighthouse_score, decay_delay, elapsed);
+        assert_eq!(restored.score(), MIN_SCORE);
+        assert_eq!(restored.state(), ScoreState::Banned);
+        let (_, remaining) = restored.persisted().unwrap();
+        assert!(remaining <= BANNED_BEFORE_DECAY - elapsed);
+        assert!(remaining > BANNED_BEFORE_DECAY - elapsed - Duration::from_secs(60));
+
+        // Once the ban has expired, the score decays as if we had been online.
+        let restored = Score::restore(
+            MIN_SCORE,
+            Duration::ZERO,
+            Duration::from_secs(2 * SCORE_HALFLIFE as u64),
+        );
+        assert!((restored.score() - MIN_SCORE / 4.0).abs() < 1e-9);
+        assert_eq!(restored.state(), ScoreState::ForcedDisconnect);
+
+        // The maximum score of a trusted peer is not persisted.
+        assert!(Score::max_score().persisted().is_none());
+    }
+
     #[test]
     fn test_very_negative_gossipsub_score() {
         let mut score = Score::default();
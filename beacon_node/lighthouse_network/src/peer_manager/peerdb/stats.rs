@@ -0,0 +1,279 @@
+//! Accounting of the traffic exchanged with a peer, used to identify peers which are costly or
+//! unreliable.
+use crate::rpc::Protocol;
+use crate::PeerId;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+/// The number of shards of the `RpcStatsStore`.
+const RPC_STATS_SHARDS: usize = 16;
+
+/// An RPC event which is accounted against a peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RpcEvent {
+    /// We sent a request to the peer.
+    RequestSent,
+    /// The peer sent us a request.
+    RequestReceived,
+    /// We sent a response chunk to the peer.
+    ResponseSent,
+    /// The peer sent us a response chunk.
+    ResponseReceived,
+    /// A request we sent to the peer failed.
+    Error,
+    /// A request we sent to the peer timed out.
+    Timeout,
+}
+
+/// The RPC traffic exchanged with a peer over a single protocol.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RpcStats {
+    pub requests_out: u64,
+    pub requests_in: u64,
+    pub responses_out: u64,
+    pub responses_in: u64,
+    /// The bytes of the requests and responses received from the peer, as sent on the wire.
+    pub bytes_in: u64,
+    /// The bytes of the requests and responses sent to the peer, as sent on the wire.
+    pub bytes_out: u64,
+    /// The number of our requests which failed, excluding timeouts.
+    pub errors: u64,
+    /// The number of our requests which timed out.
+    pub timeouts: u64,
+}
+
+impl RpcStats {
+    /// Account an RPC event of `bytes` wire bytes.
+    pub fn record(&mut self, event: RpcEvent, bytes: usize) {
+        let bytes = bytes as u64;
+        match event {
+            RpcEvent::RequestSent => {
+                self.requests_out += 1;
+                self.bytes_out += bytes;
+            }
+            RpcEvent::RequestReceived => {
+                self.requests_in += 1;
+                self.bytes_in += bytes;
+            }
+            RpcEvent::ResponseSent => {
+                self.responses_out += 1;
+                self.bytes_out += bytes;
+            }
+            RpcEvent::ResponseReceived => {
+                self.responses_in += 1;
+                self.bytes_in += bytes;
+            }
+            RpcEvent::Error => self.errors += 1,
+            RpcEvent::Timeout => self.timeouts += 1,
+        }
+    }
+}
+
+/// The gossip traffic exchanged with a peer over a single topic.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GossipStats {
+    /// The messages received from the peer which we had not already seen.
+    pub messages_in: u64,
+    /// The messages received from the peer which we had already seen.
+    pub duplicates_in: u64,
+    pub bytes_in: u64,
+    pub messages_out: u64,
+    pub bytes_out: u64,
+}
+
+impl GossipStats {
+    /// The proportion of the messages received from the peer which we had not already seen, if
+    /// any messages have been received.
+    pub fn useful_ratio(&self) -> Option<f64> {
+        let total = self.messages_in + self.duplicates_in;
+        (total > 0).then(|| self.messages_in as f64 / total as f64)
+    }
+}
+
+/// The traffic exchanged with a peer.
+///
+/// RPC traffic is accumulated whilst the peer is known to the `PeerDB`, whereas gossip traffic
+/// covers the current (or last) connection to the peer.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PeerStats {
+    /// RPC traffic, keyed by protocol.
+    pub rpc: BTreeMap<String, RpcStats>,
+    /// Gossip traffic, keyed by topic kind, e.g. `beacon_block`.
+    pub gossip: BTreeMap<String, GossipStats>,
+}
+
+impl PeerStats {
+    /// The total bytes received from the peer over RPC and gossip.
+    pub fn bytes_in(&self) -> u64 {
+        self.rpc.values().map(|s| s.bytes_in).sum::<u64>()
+            + self.gossip.values().map(|s| s.bytes_in).sum::<u64>()
+    }
+
+    /// The total bytes sent to the peer over RPC and gossip.
+    pub fn bytes_out(&self) -> u64 {
+        self.rpc.values().map(|s| s.bytes_out).sum::<u64>()
+            + self.gossip.values().map(|s| s.bytes_out).sum::<u64>()
+    }
+
+    /// The total number of our RPC requests to the peer which failed or timed out.
+    pub fn rpc_errors(&self) -> u64 {
+        self.rpc.values().map(|s| s.errors + s.timeouts).sum()
+    }
+
+    /// The total number of duplicate gossip messages received from the peer.
+    pub fn duplicates_in(&self) -> u64 {
+        self.gossip.values().map(|s| s.duplicates_in).sum()
+    }
+
+    /// The proportion of the gossip messages received from the peer which we had not already
+    /// seen, across all topics.
+    pub fn useful_gossip_ratio(&self) -> Option<f64> {
+        let total = self
+            .gossip
+            .values()
+            .fold(GossipStats::default(), |acc, s| GossipStats {
+                messages_in: acc.messages_in + s.messages_in,
+                duplicates_in: acc.duplicates_in + s.duplicates_in,
+                ..acc
+            });
+        total.useful_ratio()
+    }
+}
+
+/// The RPC traffic exchanged with each peer.
+///
+/// Traffic is recorded by the RPC codecs of every connection for each chunk sent or received, so
+/// it is kept apart from the `PeerDB` and split into shards by peer, each with its own lock.
+pub struct RpcStatsStore {
+    shards: Vec<Mutex<HashMap<PeerId, HashMap<Protocol, RpcStats>>>>,
+}
+
+impl Default for RpcStatsStore {
+    fn default() -> Self {
+        Self {
+            shards: (0..RPC_STATS_SHARDS).map(|_| Mutex::default()).collect(),
+        }
+    }
+}
+
+impl RpcStatsStore {
+    fn shard(&self, peer_id: &PeerId) -> &Mutex<HashMap<PeerId, HashMap<Protocol, RpcStats>>> {
+        let mut hasher = DefaultHasher::new();
+        peer_id.hash(&mut hasher);
+        &self.shards[hasher.finish() as usize % self.shards.len()]
+    }
+
+    /// Account an RPC event of `bytes` wire bytes against the peer.
+    pub fn record(&self, peer_id: &PeerId, protocol: Protocol, event: RpcEvent, bytes: usize) {
+        self.shard(peer_id)
+            .lock()
+            .entry(*peer_id)
+            .or_default()
+            .entry(protocol)
+            .or_default()
+            .record(event, bytes);
+    }
+
+    /// The RPC traffic exchanged with the peer, keyed by protocol.
+    pub fn peer_stats(&self, peer_id: &PeerId) -> BTreeMap<String, RpcStats> {
+        self.shard(peer_id)
+            .lock()
+            .get(peer_id)
+            .map(|stats| {
+                stats
+                    .iter()
+                    .map(|(protocol, stats)| (protocol.to_string(), *stats))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Drops the traffic of the peers for which `keep` returns false.
+    pub fn retain(&self, mut keep: impl FnMut(&PeerId) -> bool) {
+        for shard in &self.shards {
+            shard.lock().retain(|peer_id, _| keep(peer_id));
+        }
+    }
+}
+
+/// Records the RPC traffic of the substreams of a single peer.
+#[derive(Clone)]
+pub struct RpcStatsRecorder {
+    store: Arc<RpcStatsStore>,
+    peer_id: PeerId,
+}
+
+impl RpcStatsRecorder {
+    pub fn new(store: Arc<RpcStatsStore>, peer_id: PeerId) -> Self {
+        Self { store, peer_id }
+    }
+
+    /// Account an RPC event of `bytes` wire bytes against the peer.
+    pub fn record(&self, protocol: Protocol, event: RpcEvent, bytes: usize) {
+        self.store.record(&self.peer_id, protocol, event, bytes);
+    }
+}
+
+impl std::fmt::Debug for RpcStatsRecorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RpcStatsRecorder")
+            .field("peer_id", &self.peer_id)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peer_stats_totals() {
+        let store = RpcStatsStore::default();
+        let peer_id = PeerId::random();
+        let other_peer_id = PeerId::random();
+        store.record(&peer_id, Protocol::BlocksByRange, RpcEvent::RequestSent, 20);
+        store.record(
+            &peer_id,
+            Protocol::BlocksByRange,
+            RpcEvent::ResponseReceived,
+            1_000,
+        );
+        store.record(&peer_id, Protocol::BlocksByRange, RpcEvent::Timeout, 0);
+        store.record(&peer_id, Protocol::Status, RpcEvent::RequestReceived, 84);
+        store.record(&peer_id, Protocol::Status, RpcEvent::ResponseSent, 84);
+        store.record(&peer_id, Protocol::Status, RpcEvent::Error, 0);
+        store.record(&other_peer_id, Protocol::Ping, RpcEvent::RequestSent, 10);
+
+        let mut stats = PeerStats {
+            rpc: store.peer_stats(&peer_id),
+            gossip: BTreeMap::new(),
+        };
+        stats.gossip.insert(
+            "beacon_block".to_string(),
+            GossipStats {
+                messages_in: 3,
+                duplicates_in: 1,
+                bytes_in: 400,
+                messages_out: 2,
+                bytes_out: 200,
+            },
+        );
+
+        assert_eq!(stats.rpc["blocks_by_range"].requests_out, 1);
+        assert_eq!(stats.rpc["status"].responses_out, 1);
+        assert_eq!(stats.bytes_in(), 1_000 + 84 + 400);
+        assert_eq!(stats.bytes_out(), 20 + 84 + 200);
+        assert_eq!(stats.rpc_errors(), 2);
+        assert_eq!(stats.duplicates_in(), 1);
+        assert_eq!(stats.useful_gossip_ratio(), Some(0.75));
+        assert_eq!(PeerStats::default().useful_gossip_ratio(), None);
+
+        store.retain(|peer| *peer != peer_id);
+        assert!(store.peer_stats(&peer_id).is_empty());
+        assert_eq!(store.peer_stats(&other_peer_id)["ping"].requests_out, 1);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::peer_manager::peerdb::stats::{RpcEvent, RpcStatsRecorder};
 use crate::rpc::methods::*;
 use crate::rpc::protocol::{
     Encoding, ProtocolId, RPCError, SupportedProtocol, ERROR_TYPE_MAX, ERROR_TYPE_MIN,
@@ -35,6 +36,10 @@ pub struct SSZSnappyInboundCodec<E: EthSpec> {
     /// Maximum bytes that can be sent in one req/resp chunked responses.
     max_packet_size: usize,
     fork_context: Arc<ForkContext>,
+    /// Records the wire size of the requests received and the responses sent, if set.
+    stats: Option<RpcStatsRecorder>,
+    /// The bytes of the request being decoded which have been read so far.
+    read_bytes: usize,
     phantom: PhantomData<E>,
 }
 
@@ -55,6 +60,20 @@ impl<E: EthSpec> SSZSnappyInboundCodec<E> {
             phantom: PhantomData,
             fork_context,
             max_packet_size,
+            stats: None,
+            read_bytes: 0,
+        }
+    }
+
+    /// Records the wire size of the traffic of this substream with `stats`.
+    pub fn with_stats(mut self, stats: RpcStatsRecorder) -> Self {
+        self.stats = Some(stats);
+        self
+    }
+
+    fn record(&self, event: RpcEvent, bytes: usize) {
+        if let Some(stats) = &self.stats {
+            stats.record(self.protocol.versioned_protocol.protocol(), event, bytes);
         }
     }
 
@@ -77,6 +96,7 @@ impl<E: EthSpec> SSZSnappyInboundCodec<E> {
                 RpcSuccessResponse::LightClientOptimisticUpdate(res) => res.as_ssz_bytes(),
                 RpcSuccessResponse::LightClientFinalityUpdate(res) => res.as_ssz_bytes(),
                 RpcSuccessResponse::LightClientUpdatesByRange(res) => res.as_ssz_bytes(),
+                RpcSuccessResponse::CheckpointStateChunks(res) => res.as_ssz_bytes(),
                 RpcSuccessResponse::Pong(res) => res.data.as_ssz_bytes(),
                 RpcSuccessResponse::MetaData(res) =>
                 // Encode the correct version of the MetaData response based on the negotiated version.
@@ -124,29 +144,9 @@ impl<E: EthSpec> SSZSnappyInboundCodec<E> {
         dst.extend_from_slice(writer.get_ref());
         Ok(())
     }
-}
-
-// Encoder for inbound streams: Encodes RPC Responses sent to peers.
-impl<E: EthSpec> Encoder<RpcResponse<E>> for SSZSnappyInboundCodec<E> {
-    type Error = RPCError;
-
-    fn encode(&mut self, item: RpcResponse<E>, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        dst.clear();
-        dst.reserve(1);
-        dst.put_u8(
-            item.as_u8()
-                .expect("Should never encode a stream termination"),
-        );
-        self.encode_response(item, dst)
-    }
-}
 
-// Decoder for inbound streams: Decodes RPC requests from peers
-impl<E: EthSpec> Decoder for SSZSnappyInboundCodec<E> {
-    type Item = RequestType<E>;
-    type Error = RPCError;
-
-    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+    /// Decodes an RPC request from a peer.
+    fn decode_request(&mut self, src: &mut BytesMut) -> Result<Option<RequestType<E>>, RPCError> {
         if self.protocol.versioned_protocol == SupportedProtocol::MetaDataV1 {
             return Ok(Some(RequestType::MetaData(MetadataRequest::new_v1())));
         }
@@ -194,6 +194,43 @@ impl<E: EthSpec> Decoder for SSZSnappyInboundCodec<E> {
     }
 }
 
+// Encoder for inbound streams: Encodes RPC Responses sent to peers.
+impl<E: EthSpec> Encoder<RpcResponse<E>> for SSZSnappyInboundCodec<E> {
+    type Error = RPCError;
+
+    fn encode(&mut self, item: RpcResponse<E>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        dst.clear();
+        dst.reserve(1);
+        dst.put_u8(
+            item.as_u8()
+                .expect("Should never encode a stream termination"),
+        );
+        let is_success = matches!(item, RpcResponse::Success(_));
+        self.encode_response(item, dst)?;
+        if is_success {
+            self.record(RpcEvent::ResponseSent, dst.len());
+        }
+        Ok(())
+    }
+}
+
+// Decoder for inbound streams: Decodes RPC requests from peers
+impl<E: EthSpec> Decoder for SSZSnappyInboundCodec<E> {
+    type Item = RequestType<E>;
+    type Error = RPCError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let len_before = src.len();
+        let result = self.decode_request(src);
+        self.read_bytes += len_before - src.len();
+        if let Ok(Some(_)) = result {
+            let read_bytes = std::mem::take(&mut self.read_bytes);
+            self.record(RpcEvent::RequestReceived, read_bytes);
+        }
+        result
+    }
+}
+
 /* Outbound Codec: Codec for initiating RPC requests */
 pub struct SSZSnappyOutboundCodec<E: EthSpec> {
     inner: Uvi<usize>,
@@ -206,6 +243,10 @@ pub struct SSZSnappyOutboundCodec<E: EthSpec> {
     fork_context: Arc<ForkContext>,
     /// Keeps track of the current response code for a chunk.
     current_response_code: Option<u8>,
+    /// Records the wire size of the requests sent and the responses received, if set.
+    stats: Option<RpcStatsRecorder>,
+    /// The bytes of the response chunk being decoded which have been read so far.
+    read_bytes: usize,
     phantom: PhantomData<E>,
 }
 
@@ -228,6 +269,20 @@ impl<E: EthSpec> SSZSnappyOutboundCodec<E> {
             fork_context,
             phantom: PhantomData,
             current_response_code: None,
+            stats: None,
+            read_bytes: 0,
+        }
+    }
+
+    /// Records the wire size of the traffic of this substream with `stats`.
+    pub fn with_stats(mut self, stats: RpcStatsRecorder) -> Self {
+        self.stats = Some(stats);
+        self
+    }
+
+    fn record(&self, event: RpcEvent, bytes: usize) {
+        if let Some(stats) = &self.stats {
+            stats.record(self.protocol.versioned_protocol.protocol(), event, bytes);
         }
     }
 
@@ -319,13 +374,9 @@ impl<E: EthSpec> SSZSnappyOutboundCodec<E> {
             Err(e) => handle_error(e, reader.get_ref().get_ref().position(), max_compressed_len),
         }
     }
-}
 
-// Encoder for outbound streams: Encodes RPC Requests to peers
-impl<E: EthSpec> Encoder<RequestType<E>> for SSZSnappyOutboundCodec<E> {
-    type Error = RPCError;
-
-    fn encode(&mut self, item: RequestType<E>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+    /// Encodes an RPC request to a peer.
+    fn encode_request(&mut self, item: RequestType<E>, dst: &mut BytesMut) -> Result<(), RPCError> {
         let bytes = match item {
             RequestType::Status(req) => req.as_ssz_bytes(),
             RequestType::Goodbye(req) => req.as_ssz_bytes(),
@@ -344,6 +395,7 @@ impl<E: EthSpec> Encoder<RequestType<E>> for SSZSnappyOutboundCodec<E> {
             RequestType::Ping(req) => req.as_ssz_bytes(),
             RequestType::LightClientBootstrap(req) => req.as_ssz_bytes(),
             RequestType::LightClientUpdatesByRange(req) => req.as_ssz_bytes(),
+            RequestType::CheckpointStateChunks(req) => req.as_ssz_bytes(),
             // no metadata to encode
             RequestType::MetaData(_)
             | RequestType::LightClientOptimisticUpdate
@@ -371,18 +423,9 @@ impl<E: EthSpec> Encoder<RequestType<E>> for SSZSnappyOutboundCodec<E> {
         dst.extend_from_slice(writer.get_ref());
         Ok(())
     }
-}
-
-// Decoder for outbound streams: Decodes RPC responses from peers.
-//
-// The majority of the decoding has now been pushed upstream due to the changing specification.
-// We prefer to decode blocks and attestations with extra knowledge about the chain to perform
-// faster verification checks before decoding entire blocks/attestations.
-impl<E: EthSpec> Decoder for SSZSnappyOutboundCodec<E> {
-    type Item = RpcResponse<E>;
-    type Error = RPCError;
 
-    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+    /// Decodes a response chunk from a peer.
+    fn decode_chunk(&mut self, src: &mut BytesMut) -> Result<Option<RpcResponse<E>>, RPCError> {
         // if we have only received the response code, wait for more bytes
         if src.len() <= 1 {
             return Ok(None);
@@ -416,6 +459,41 @@ impl<E: EthSpec> Decoder for SSZSnappyOutboundCodec<E> {
     }
 }
 
+// Encoder for outbound streams: Encodes RPC Requests to peers
+impl<E: EthSpec> Encoder<RequestType<E>> for SSZSnappyOutboundCodec<E> {
+    type Error = RPCError;
+
+    fn encode(&mut self, item: RequestType<E>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let len_before = dst.len();
+        self.encode_request(item, dst)?;
+        self.record(RpcEvent::RequestSent, dst.len() - len_before);
+        Ok(())
+    }
+}
+
+// Decoder for outbound streams: Decodes RPC responses from peers.
+//
+// The majority of the decoding has now been pushed upstream due to the changing specification.
+// We prefer to decode blocks and attestations with extra knowledge about the chain to perform
+// faster verification checks before decoding entire blocks/attestations.
+impl<E: EthSpec> Decoder for SSZSnappyOutboundCodec<E> {
+    type Item = RpcResponse<E>;
+    type Error = RPCError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let len_before = src.len();
+        let result = self.decode_chunk(src);
+        self.read_bytes += len_before - src.len();
+        if let Ok(Some(chunk)) = &result {
+            let read_bytes = std::mem::take(&mut self.read_bytes);
+            if let RpcResponse::Success(_) = chunk {
+                self.record(RpcEvent::ResponseReceived, read_bytes);
+            }
+        }
+        result
+    }
+}
+
 /// Handle errors that we get from decoding an RPC message from the stream.
 /// `num_bytes_read` is the number of bytes the snappy decoder has read from the underlying stream.
 /// `max_compressed_len` is the maximum compressed size for a given uncompressed size.
@@ -512,7 +590,8 @@ fn context_bytes<E: EthSpec>(
                 // These will not pass the has_context_bytes() check
                 RpcSuccessResponse::Status(_)
                 | RpcSuccessResponse::Pong(_)
-                | RpcSuccessResponse::MetaData(_) => {
+                | RpcSuccessResponse::MetaData(_)
+                | RpcSuccessResponse::CheckpointStateChunks(_) => {
                     return None;
                 }
             }
@@ -624,6 +703,9 @@ fn handle_rpc_request<E: EthSpec>(
                 LightClientUpdatesByRangeRequest::from_ssz_bytes(decoded_buffer)?,
             )))
         }
+        SupportedProtocol::CheckpointStateChunksV1 => Ok(Some(RequestType::CheckpointStateChunks(
+            CheckpointStateChunksRequest::from_ssz_bytes(decoded_buffer)?,
+        ))),
         // MetaData requests return early from InboundUpgrade and do not reach the decoder.
         // Handle this case just for completeness.
         SupportedProtocol::MetaDataV3 => {
@@ -774,6 +856,11 @@ fn handle_rpc_response<E: EthSpec>(
         SupportedProtocol::MetaDataV1 => Ok(Some(RpcSuccessResponse::MetaData(MetaData::V1(
             MetaDataV1::from_ssz_bytes(decoded_buffer)?,
         )))),
+        SupportedProtocol::CheckpointStateChunksV1 => {
+            Ok(Some(RpcSuccessResponse::CheckpointStateChunks(Arc::new(
+                CheckpointStateChunk::from_ssz_bytes(decoded_buffer)?,
+            ))))
+        }
         SupportedProtocol::LightClientBootstrapV1 => match fork_name {
             Some(fork_name) => Ok(Some(RpcSuccessResponse::LightClientBootstrap(Arc::new(
                 LightClientBootstrap::from_ssz_bytes(decoded_buffer, fork_name)?,
@@ -931,8 +1018,10 @@ fn context_bytes_to_fork_name(
 mod tests {
 
     use super::*;
+    use crate::peer_manager::peerdb::stats::RpcStatsStore;
     use crate::rpc::protocol::*;
     use crate::types::{EnrAttestationBitfield, EnrSyncCommitteeBitfield};
+    use libp2p::PeerId;
     use types::{
         blob_sidecar::BlobIdentifier, BeaconBlock, BeaconBlockAltair, BeaconBlockBase,
         BeaconBlockBellatrix, DataColumnIdentifier, EmptyBlock, Epoch, FixedBytesExtended,
@@ -1091,6 +1180,24 @@ mod tests {
         Ping { data: 1 }
     }
 
+    fn checkpoint_state_chunks_request() -> CheckpointStateChunksRequest {
+        CheckpointStateChunksRequest {
+            block_root: Hash256::zero(),
+            start_chunk: 0,
+            count: MAX_REQUEST_CHECKPOINT_STATE_CHUNKS,
+        }
+    }
+
+    /// A state chunk with a full size list chunk and proof.
+    fn checkpoint_state_chunk() -> Arc<CheckpointStateChunk> {
+        Arc::new(CheckpointStateChunk {
+            index: 1,
+            total_chunks: 3,
+            proof: VariableList::from(vec![Hash256::repeat_byte(2); 40]),
+            data: VariableList::from(vec![3; CHECKPOINT_STATE_CHUNK_SIZE]),
+        })
+    }
+
     fn metadata() -> MetaData<Spec> {
         MetaData::V1(MetaDataV1 {
             seq_number: 1,
@@ -1256,6 +1363,12 @@ mod tests {
                     RequestType::LightClientUpdatesByRange(light_client_updates_by_range)
                 )
             }
+            RequestType::CheckpointStateChunks(checkpoint_state_chunks) => {
+                assert_eq!(
+                    decoded,
+                    RequestType::CheckpointStateChunks(checkpoint_state_chunks)
+                )
+            }
         }
     }
 
@@ -1471,6 +1584,20 @@ mod tests {
                 empty_data_column_sidecar()
             ))),
         );
+
+        assert_eq!(
+            encode_then_decode_response(
+                SupportedProtocol::CheckpointStateChunksV1,
+                RpcResponse::Success(RpcSuccessResponse::CheckpointStateChunks(
+                    checkpoint_state_chunk()
+                )),
+                ForkName::Base,
+                &chain_spec
+            ),
+            Ok(Some(RpcSuccessResponse::CheckpointStateChunks(
+                checkpoint_state_chunk()
+            ))),
+        );
     }
 
     // Test RPCResponse encoding/decoding for V1 messages
@@ -1849,6 +1976,7 @@ mod tests {
             RequestType::DataColumnsByRange(dcbrange_request()),
             RequestType::DataColumnsByRoot(dcbroot_request(&chain_spec)),
             RequestType::MetaData(MetadataRequest::new_v2()),
+            RequestType::CheckpointStateChunks(checkpoint_state_chunks_request()),
         ];
 
         for req in requests.iter() {
@@ -1858,6 +1986,59 @@ mod tests {
         }
     }
 
+    /// Test that the codecs record the wire size of each request and response chunk, including
+    /// chunks which arrive over several reads.
+    #[test]
+    fn test_stats_record_wire_size() {
+        let chain_spec = Spec::default_spec();
+        let fork_context = Arc::new(fork_context(ForkName::Base));
+        let max_packet_size = max_rpc_size(&fork_context, chain_spec.max_chunk_size as usize);
+        let protocol = ProtocolId::new(SupportedProtocol::BlocksByRangeV2, Encoding::SSZSnappy);
+
+        let store = Arc::new(RpcStatsStore::default());
+        let (local, remote) = (PeerId::random(), PeerId::random());
+        let mut outbound_codec = SSZSnappyOutboundCodec::<Spec>::new(
+            protocol.clone(),
+            max_packet_size,
+            fork_context.clone(),
+        )
+        .with_stats(RpcStatsRecorder::new(store.clone(), remote));
+        let mut inbound_codec =
+            SSZSnappyInboundCodec::<Spec>::new(protocol, max_packet_size, fork_context)
+                .with_stats(RpcStatsRecorder::new(store.clone(), local));
+
+        let mut buf = BytesMut::new();
+        outbound_codec
+            .encode(RequestType::BlocksByRange(bbrange_request_v2()), &mut buf)
+            .unwrap();
+        let request_len = buf.len();
+        let mut rest = buf.split_off(1);
+        assert!(inbound_codec.decode(&mut buf).unwrap().is_none());
+        buf.unsplit(rest);
+        assert!(inbound_codec.decode(&mut buf).unwrap().is_some());
+
+        let response = RpcResponse::Success(RpcSuccessResponse::BlocksByRange(Arc::new(
+            empty_base_block(),
+        )));
+        let mut buf = BytesMut::new();
+        inbound_codec.encode(response, &mut buf).unwrap();
+        let response_len = buf.len();
+        rest = buf.split_off(response_len / 2);
+        assert!(outbound_codec.decode(&mut buf).unwrap().is_none());
+        buf.unsplit(rest);
+        assert!(outbound_codec.decode(&mut buf).unwrap().is_some());
+
+        let protocol = Protocol::BlocksByRange.to_string();
+        let sent = store.peer_stats(&remote)[&protocol];
+        assert_eq!((sent.requests_out, sent.responses_in), (1, 1));
+        assert_eq!(sent.bytes_out, request_len as u64);
+        assert_eq!(sent.bytes_in, response_len as u64);
+        let received = store.peer_stats(&local)[&protocol];
+        assert_eq!((received.requests_in, received.responses_out), (1, 1));
+        assert_eq!(received.bytes_in, request_len as u64);
+        assert_eq!(received.bytes_out, response_len as u64);
+    }
+
     /// Test a malicious snappy encoding for a V1 `Status` message where the attacker
     /// sends a valid message filled with a stream of useless padding before the actual message.
     #[test]
@@ -97,6 +97,7 @@ pub struct RateLimiterConfig {
     pub(super) light_client_optimistic_update_quota: Quota,
     pub(super) light_client_finality_update_quota: Quota,
     pub(super) light_client_updates_by_range_quota: Quota,
+    pub(super) checkpoint_state_chunks_quota: Quota,
 }
 
 impl RateLimiterConfig {
@@ -122,6 +123,8 @@ impl RateLimiterConfig {
     pub const DEFAULT_LIGHT_CLIENT_OPTIMISTIC_UPDATE_QUOTA: Quota = Quota::one_every(10);
     pub const DEFAULT_LIGHT_CLIENT_FINALITY_UPDATE_QUOTA: Quota = Quota::one_every(10);
     pub const DEFAULT_LIGHT_CLIENT_UPDATES_BY_RANGE_QUOTA: Quota = Quota::one_every(10);
+    // Chunks are 1 MiB each, so this allows a peer to download a state at up to ~6 MiB/s.
+    pub const DEFAULT_CHECKPOINT_STATE_CHUNKS_QUOTA: Quota = Quota::n_every(64, 10);
 }
 
 impl Default for RateLimiterConfig {
@@ -142,6 +145,7 @@ impl Default for RateLimiterConfig {
                 Self::DEFAULT_LIGHT_CLIENT_OPTIMISTIC_UPDATE_QUOTA,
             light_client_finality_update_quota: Self::DEFAULT_LIGHT_CLIENT_FINALITY_UPDATE_QUOTA,
             light_client_updates_by_range_quota: Self::DEFAULT_LIGHT_CLIENT_UPDATES_BY_RANGE_QUOTA,
+            checkpoint_state_chunks_quota: Self::DEFAULT_CHECKPOINT_STATE_CHUNKS_QUOTA,
         }
     }
 }
@@ -175,6 +179,10 @@ impl Debug for RateLimiterConfig {
                 "data_columns_by_root",
                 fmt_q!(&self.data_columns_by_root_quota),
             )
+            .field(
+                "checkpoint_state_chunks",
+                fmt_q!(&self.checkpoint_state_chunks_quota),
+            )
             .finish()
     }
 }
@@ -201,6 +209,7 @@ impl FromStr for RateLimiterConfig {
         let mut light_client_optimistic_update_quota = None;
         let mut light_client_finality_update_quota = None;
         let mut light_client_updates_by_range_quota = None;
+        let mut checkpoint_state_chunks_quota = None;
 
         for proto_def in s.split(';') {
             let ProtocolQuota { protocol, quota } = proto_def.parse()?;
@@ -235,6 +244,9 @@ impl FromStr for RateLimiterConfig {
                     light_client_updates_by_range_quota =
                         light_client_updates_by_range_quota.or(quota)
                 }
+                Protocol::CheckpointStateChunks => {
+                    checkpoint_state_chunks_quota = checkpoint_state_chunks_quota.or(quota)
+                }
             }
         }
         Ok(RateLimiterConfig {
@@ -261,6 +273,8 @@ impl FromStr for RateLimiterConfig {
                 .unwrap_or(Self::DEFAULT_LIGHT_CLIENT_FINALITY_UPDATE_QUOTA),
             light_client_updates_by_range_quota: light_client_updates_by_range_quota
                 .unwrap_or(Self::DEFAULT_LIGHT_CLIENT_UPDATES_BY_RANGE_QUOTA),
+            checkpoint_state_chunks_quota: checkpoint_state_chunks_quota
+                .unwrap_or(Self::DEFAULT_CHECKPOINT_STATE_CHUNKS_QUOTA),
         })
     }
 }
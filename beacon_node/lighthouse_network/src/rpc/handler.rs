@@ -788,6 +788,7 @@ where
                         req: req.clone(),
                         fork_context: self.fork_context.clone(),
                         max_rpc_size: self.listen_protocol().upgrade().max_rpc_size,
+                        stats: self.listen_protocol().upgrade().stats.clone(),
                     },
                     (),
                 )
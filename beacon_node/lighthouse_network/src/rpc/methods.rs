@@ -5,7 +5,10 @@ use regex::bytes::Regex;
 use serde::Serialize;
 use ssz::Encode;
 use ssz_derive::{Decode, Encode};
-use ssz_types::{typenum::U256, VariableList};
+use ssz_types::{
+    typenum::{U16, U256, U40, U8388608},
+    VariableList,
+};
 use std::collections::BTreeMap;
 use std::fmt::Display;
 use std::marker::PhantomData;
@@ -506,6 +509,73 @@ impl LightClientUpdatesByRangeRequest {
     }
 }
 
+/// Maximum number of state chunks in a single `CheckpointStateChunks` request.
+pub const MAX_REQUEST_CHECKPOINT_STATE_CHUNKS: u64 = 16;
+
+/// The maximum size in bytes of the elements of a list served in a single state chunk.
+pub const CHECKPOINT_STATE_CHUNK_SIZE: usize = 1 << 20;
+
+/// The maximum size in bytes of the data of a state chunk, including the header chunk.
+pub type CheckpointStateChunkDataLimit = U8388608;
+/// The maximum length of the proof of a state chunk, which is the depth of the deepest list of the
+/// state.
+pub type CheckpointStateChunkProofLimit = U40;
+/// The maximum number of lists of a state that are served in chunks.
+pub type MaxCheckpointStateLists = U16;
+
+/// Request a range of chunks of the post-state of a finalized block.
+///
+/// This is a Lighthouse-specific protocol used to checkpoint sync from peers.
+#[derive(Encode, Decode, Clone, Debug, PartialEq)]
+pub struct CheckpointStateChunksRequest {
+    /// The root of the finalized block whose post-state is requested.
+    pub block_root: Hash256,
+    /// The index of the first chunk requested.
+    pub start_chunk: u64,
+    /// The number of chunks requested from `start_chunk`.
+    pub count: u64,
+}
+
+/// A chunk of the post-state of a finalized block.
+///
+/// The chunk at index 0 is the SSZ encoded `CheckpointStateHeader` of the state, and has no proof.
+/// The following chunks hold the SSZ encoded elements of the lists of the header, in the order of
+/// the lists. Each chunk of a list holds the elements of the largest power of two number of leaves
+/// of the tree of the list that fit in `CHECKPOINT_STATE_CHUNK_SIZE` bytes. The `proof` of such a
+/// chunk is a Merkle proof of the root of its elements against the `elements_root` of its list, so
+/// that each chunk can be verified as it arrives.
+#[derive(Encode, Decode, Clone, Debug, PartialEq)]
+pub struct CheckpointStateChunk {
+    pub index: u64,
+    pub total_chunks: u64,
+    pub proof: VariableList<Hash256, CheckpointStateChunkProofLimit>,
+    pub data: VariableList<u8, CheckpointStateChunkDataLimit>,
+}
+
+/// The first chunk of a state, from which the rest of the state can be verified.
+///
+/// The root of the state is computed from the roots of the fields of `state`, replacing those of
+/// the `lists` with their root as described by `CheckpointStateList`. It can thus be checked
+/// against the state root of the block before downloading the elements of the lists.
+#[derive(Encode, Decode, Clone, Debug, PartialEq)]
+pub struct CheckpointStateHeader {
+    /// The lists of the state which are served in chunks, in field order.
+    pub lists: VariableList<CheckpointStateList, MaxCheckpointStateLists>,
+    /// The SSZ encoded state, with the `lists` left empty.
+    pub state: VariableList<u8, CheckpointStateChunkDataLimit>,
+}
+
+/// A list of the state which is served in chunks of elements.
+#[derive(Encode, Decode, Clone, Debug, PartialEq)]
+pub struct CheckpointStateList {
+    /// The index of the list among the fields of the state.
+    pub field_index: u64,
+    /// The number of elements of the list.
+    pub length: u64,
+    /// The root of the tree of the elements of the list, before the length is mixed in.
+    pub elements_root: Hash256,
+}
+
 /* RPC Handling and Grouping */
 // Collection of enums and structs used by the Codecs to encode/decode RPC messages
 
@@ -545,6 +615,9 @@ pub enum RpcSuccessResponse<E: EthSpec> {
     /// A response to a get DATA_COLUMN_SIDECARS_BY_RANGE request.
     DataColumnsByRange(Arc<DataColumnSidecar<E>>),
 
+    /// A response to a get LIGHTHOUSE_CHECKPOINT_STATE request.
+    CheckpointStateChunks(Arc<CheckpointStateChunk>),
+
     /// A PONG response to a PING request.
     Pong(Ping),
 
@@ -575,6 +648,9 @@ pub enum ResponseTermination {
 
     /// Light client updates by range stream termination.
     LightClientUpdatesByRange,
+
+    /// Checkpoint state chunks stream termination.
+    CheckpointStateChunks,
 }
 
 /// The structured response containing a result/code indicating success or failure
@@ -667,6 +743,7 @@ impl<E: EthSpec> RpcSuccessResponse<E> {
             RpcSuccessResponse::BlobsByRoot(_) => Protocol::BlobsByRoot,
             RpcSuccessResponse::DataColumnsByRoot(_) => Protocol::DataColumnsByRoot,
             RpcSuccessResponse::DataColumnsByRange(_) => Protocol::DataColumnsByRange,
+            RpcSuccessResponse::CheckpointStateChunks(_) => Protocol::CheckpointStateChunks,
             RpcSuccessResponse::Pong(_) => Protocol::Ping,
             RpcSuccessResponse::MetaData(_) => Protocol::MetaData,
             RpcSuccessResponse::LightClientBootstrap(_) => Protocol::LightClientBootstrap,
@@ -725,6 +802,13 @@ impl<E: EthSpec> std::fmt::Display for RpcSuccessResponse<E> {
                     sidecar.slot()
                 )
             }
+            RpcSuccessResponse::CheckpointStateChunks(chunk) => {
+                write!(
+                    f,
+                    "CheckpointStateChunks: Chunk {} of {}",
+                    chunk.index, chunk.total_chunks
+                )
+            }
             RpcSuccessResponse::Pong(ping) => write!(f, "Pong: {}", ping.data),
             RpcSuccessResponse::MetaData(metadata) => {
                 write!(f, "Metadata: {}", metadata.seq_number())
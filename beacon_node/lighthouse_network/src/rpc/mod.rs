@@ -4,6 +4,7 @@
 //! direct peer-to-peer communication primarily for sending/receiving chain information for
 //! syncing.
 
+use crate::peer_manager::peerdb::stats::{RpcStatsRecorder, RpcStatsStore};
 use futures::future::FutureExt;
 use handler::RPCHandler;
 use libp2p::core::transport::PortUse;
@@ -165,6 +166,8 @@ pub struct RPC<Id: ReqId, E: EthSpec> {
     network_params: NetworkParams,
     /// A sequential counter indicating when data gets modified.
     seq_number: u64,
+    /// The RPC traffic exchanged with each peer, recorded by the codecs of each connection.
+    rpc_stats: Arc<RpcStatsStore>,
 }
 
 impl<Id: ReqId, E: EthSpec> RPC<Id, E> {
@@ -176,6 +179,7 @@ impl<Id: ReqId, E: EthSpec> RPC<Id, E> {
         log: slog::Logger,
         network_params: NetworkParams,
         seq_number: u64,
+        rpc_stats: Arc<RpcStatsStore>,
     ) -> Self {
         let log = log.new(o!("service" => "libp2p_rpc"));
 
@@ -198,6 +202,7 @@ impl<Id: ReqId, E: EthSpec> RPC<Id, E> {
             log,
             network_params,
             seq_number,
+            rpc_stats,
         }
     }
 
@@ -251,6 +256,14 @@ impl<Id: ReqId, E: EthSpec> RPC<Id, E> {
         });
     }
 
+    /// Scales the rate at which we serve blocks, blobs, data columns and light client data to
+    /// each peer by `factor`, in `(0, 1]`. Has no effect if inbound rate limiting is disabled.
+    pub fn set_serving_rate_factor(&mut self, factor: f64) {
+        if let Some(limiter) = self.limiter.as_mut() {
+            limiter.set_serving_rate_factor(factor);
+        }
+    }
+
     pub fn update_seq_number(&mut self, seq_number: u64) {
         self.seq_number = seq_number
     }
@@ -287,6 +300,7 @@ where
                 enable_light_client_server: self.enable_light_client_server,
                 phantom: PhantomData,
                 ttfb_timeout: self.network_params.ttfb_timeout,
+                stats: RpcStatsRecorder::new(self.rpc_stats.clone(), peer_id),
             },
             (),
         );
@@ -318,6 +332,7 @@ where
                 enable_light_client_server: self.enable_light_client_server,
                 phantom: PhantomData,
                 ttfb_timeout: self.network_params.ttfb_timeout,
+                stats: RpcStatsRecorder::new(self.rpc_stats.clone(), peer_id),
             },
             (),
         );
@@ -419,6 +434,7 @@ where
                                     | Protocol::BlocksByRoot
                                     | Protocol::BlobsByRoot
                                     | Protocol::DataColumnsByRoot
+                                    | Protocol::CheckpointStateChunks
                             ) {
                                 debug!(self.log, "Request too large to process"; "request" => %r#type, "protocol" => %protocol);
                             } else {
@@ -556,6 +572,9 @@ where
                             ResponseTermination::LightClientUpdatesByRange => {
                                 Protocol::LightClientUpdatesByRange
                             }
+                            ResponseTermination::CheckpointStateChunks => {
+                                Protocol::CheckpointStateChunks
+                            }
                         },
                     ),
                 };
@@ -1,6 +1,7 @@
 use super::protocol::ProtocolId;
 use super::RPCError;
 use super::RequestType;
+use crate::peer_manager::peerdb::stats::RpcStatsRecorder;
 use crate::rpc::codec::SSZSnappyOutboundCodec;
 use crate::rpc::protocol::Encoding;
 use futures::future::BoxFuture;
@@ -23,6 +24,7 @@ pub struct OutboundRequestContainer<E: EthSpec> {
     pub req: RequestType<E>,
     pub fork_context: Arc<ForkContext>,
     pub max_rpc_size: usize,
+    pub stats: RpcStatsRecorder,
 }
 
 impl<E: EthSpec> UpgradeInfo for OutboundRequestContainer<E> {
@@ -56,6 +58,7 @@ where
         let codec = match protocol.encoding {
             Encoding::SSZSnappy => {
                 SSZSnappyOutboundCodec::new(protocol, self.max_rpc_size, self.fork_context.clone())
+                    .with_stats(self.stats.clone())
             }
         };
 
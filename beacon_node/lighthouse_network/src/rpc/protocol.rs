@@ -1,11 +1,12 @@
 use super::methods::*;
+use crate::peer_manager::peerdb::stats::RpcStatsRecorder;
 use crate::rpc::codec::SSZSnappyInboundCodec;
 use futures::future::BoxFuture;
 use futures::prelude::{AsyncRead, AsyncWrite};
 use futures::{FutureExt, StreamExt};
 use libp2p::core::{InboundUpgrade, UpgradeInfo};
 use ssz::Encode;
-use ssz_types::VariableList;
+use ssz_types::{typenum::Unsigned, VariableList};
 use std::io;
 use std::marker::PhantomData;
 use std::sync::{Arc, LazyLock};
@@ -19,7 +20,7 @@ use tokio_util::{
 use types::{
     BeaconBlock, BeaconBlockAltair, BeaconBlockBase, BeaconBlockCapella, BeaconBlockElectra,
     BlobSidecar, ChainSpec, DataColumnSidecar, EmptyBlock, EthSpec, EthSpecId, ForkContext,
-    ForkName, LightClientBootstrap, LightClientBootstrapAltair, LightClientFinalityUpdate,
+    ForkName, Hash256, LightClientBootstrap, LightClientBootstrapAltair, LightClientFinalityUpdate,
     LightClientFinalityUpdateAltair, LightClientOptimisticUpdate,
     LightClientOptimisticUpdateAltair, LightClientUpdate, MainnetEthSpec, MinimalEthSpec,
     Signature, SignedBeaconBlock,
@@ -119,6 +120,22 @@ pub static DATA_COLUMNS_SIDECAR_MIN: LazyLock<usize> = LazyLock::new(|| {
 pub static DATA_COLUMNS_SIDECAR_MAX: LazyLock<usize> =
     LazyLock::new(DataColumnSidecar::<MainnetEthSpec>::max_size);
 
+pub static CHECKPOINT_STATE_CHUNK_MIN: LazyLock<usize> = LazyLock::new(|| {
+    CheckpointStateChunk {
+        index: 0,
+        total_chunks: 0,
+        proof: VariableList::empty(),
+        data: VariableList::empty(),
+    }
+    .as_ssz_bytes()
+    .len()
+});
+pub static CHECKPOINT_STATE_CHUNK_MAX: LazyLock<usize> = LazyLock::new(|| {
+    *CHECKPOINT_STATE_CHUNK_MIN
+        + CheckpointStateChunkProofLimit::to_usize() * <Hash256 as Encode>::ssz_fixed_len()
+        + CheckpointStateChunkDataLimit::to_usize()
+});
+
 pub static ERROR_TYPE_MIN: LazyLock<usize> = LazyLock::new(|| {
     VariableList::<u8, MaxErrorLen>::from(Vec::<u8>::new())
         .as_ssz_bytes()
@@ -331,6 +348,9 @@ pub enum Protocol {
     /// The `LightClientUpdatesByRange` protocol name
     #[strum(serialize = "light_client_updates_by_range")]
     LightClientUpdatesByRange,
+    /// The Lighthouse-specific `CheckpointStateChunks` protocol name.
+    #[strum(serialize = "lighthouse_checkpoint_state")]
+    CheckpointStateChunks,
 }
 
 impl Protocol {
@@ -350,6 +370,7 @@ impl Protocol {
             Protocol::LightClientOptimisticUpdate => None,
             Protocol::LightClientFinalityUpdate => None,
             Protocol::LightClientUpdatesByRange => None,
+            Protocol::CheckpointStateChunks => Some(ResponseTermination::CheckpointStateChunks),
         }
     }
 }
@@ -381,6 +402,7 @@ pub enum SupportedProtocol {
     LightClientOptimisticUpdateV1,
     LightClientFinalityUpdateV1,
     LightClientUpdatesByRangeV1,
+    CheckpointStateChunksV1,
 }
 
 impl SupportedProtocol {
@@ -404,6 +426,7 @@ impl SupportedProtocol {
             SupportedProtocol::LightClientOptimisticUpdateV1 => "1",
             SupportedProtocol::LightClientFinalityUpdateV1 => "1",
             SupportedProtocol::LightClientUpdatesByRangeV1 => "1",
+            SupportedProtocol::CheckpointStateChunksV1 => "1",
         }
     }
 
@@ -429,6 +452,7 @@ impl SupportedProtocol {
             }
             SupportedProtocol::LightClientFinalityUpdateV1 => Protocol::LightClientFinalityUpdate,
             SupportedProtocol::LightClientUpdatesByRangeV1 => Protocol::LightClientUpdatesByRange,
+            SupportedProtocol::CheckpointStateChunksV1 => Protocol::CheckpointStateChunks,
         }
     }
 
@@ -442,6 +466,7 @@ impl SupportedProtocol {
             ProtocolId::new(Self::BlocksByRootV2, Encoding::SSZSnappy),
             ProtocolId::new(Self::BlocksByRootV1, Encoding::SSZSnappy),
             ProtocolId::new(Self::PingV1, Encoding::SSZSnappy),
+            ProtocolId::new(Self::CheckpointStateChunksV1, Encoding::SSZSnappy),
         ];
         if fork_context.spec.is_peer_das_scheduled() {
             supported.extend_from_slice(&[
@@ -488,6 +513,8 @@ pub struct RPCProtocol<E: EthSpec> {
     pub enable_light_client_server: bool,
     pub phantom: PhantomData<E>,
     pub ttfb_timeout: Duration,
+    /// Records the RPC traffic of the peer of the connection.
+    pub stats: RpcStatsRecorder,
 }
 
 impl<E: EthSpec> UpgradeInfo for RPCProtocol<E> {
@@ -595,6 +622,10 @@ impl ProtocolId {
                 LightClientUpdatesByRangeRequest::ssz_min_len(),
                 LightClientUpdatesByRangeRequest::ssz_max_len(),
             ),
+            Protocol::CheckpointStateChunks => RpcLimits::new(
+                <CheckpointStateChunksRequest as Encode>::ssz_fixed_len(),
+                <CheckpointStateChunksRequest as Encode>::ssz_fixed_len(),
+            ),
             Protocol::MetaData => RpcLimits::new(0, 0), // Metadata requests are empty
         }
     }
@@ -633,6 +664,7 @@ impl ProtocolId {
             Protocol::LightClientUpdatesByRange => {
                 rpc_light_client_updates_by_range_limits_by_fork(fork_context.current_fork())
             }
+            Protocol::CheckpointStateChunks => rpc_checkpoint_state_chunk_limits(),
         }
     }
 
@@ -657,7 +689,8 @@ impl ProtocolId {
             | SupportedProtocol::MetaDataV1
             | SupportedProtocol::MetaDataV2
             | SupportedProtocol::MetaDataV3
-            | SupportedProtocol::GoodbyeV1 => false,
+            | SupportedProtocol::GoodbyeV1
+            | SupportedProtocol::CheckpointStateChunksV1 => false,
         }
     }
 }
@@ -696,6 +729,10 @@ pub fn rpc_data_column_limits() -> RpcLimits {
     RpcLimits::new(*DATA_COLUMNS_SIDECAR_MIN, *DATA_COLUMNS_SIDECAR_MAX)
 }
 
+pub fn rpc_checkpoint_state_chunk_limits() -> RpcLimits {
+    RpcLimits::new(*CHECKPOINT_STATE_CHUNK_MIN, *CHECKPOINT_STATE_CHUNK_MAX)
+}
+
 /* Inbound upgrade */
 
 // The inbound protocol reads the request, decodes it and returns the stream to the protocol
@@ -724,7 +761,8 @@ where
                     protocol,
                     self.max_rpc_size,
                     self.fork_context.clone(),
-                ),
+                )
+                .with_stats(self.stats.clone()),
             };
 
             let mut timed_socket = TimeoutStream::new(socket);
@@ -782,6 +820,7 @@ pub enum RequestType<E: EthSpec> {
     LightClientOptimisticUpdate,
     LightClientFinalityUpdate,
     LightClientUpdatesByRange(LightClientUpdatesByRangeRequest),
+    CheckpointStateChunks(CheckpointStateChunksRequest),
     Ping(Ping),
     MetaData(MetadataRequest<E>),
 }
@@ -807,6 +846,7 @@ impl<E: EthSpec> RequestType<E> {
             RequestType::LightClientOptimisticUpdate => 1,
             RequestType::LightClientFinalityUpdate => 1,
             RequestType::LightClientUpdatesByRange(req) => req.count,
+            RequestType::CheckpointStateChunks(req) => req.count,
         }
     }
 
@@ -843,6 +883,7 @@ impl<E: EthSpec> RequestType<E> {
             RequestType::LightClientUpdatesByRange(_) => {
                 SupportedProtocol::LightClientUpdatesByRangeV1
             }
+            RequestType::CheckpointStateChunks(_) => SupportedProtocol::CheckpointStateChunksV1,
         }
     }
 
@@ -858,6 +899,7 @@ impl<E: EthSpec> RequestType<E> {
             RequestType::BlobsByRoot(_) => ResponseTermination::BlobsByRoot,
             RequestType::DataColumnsByRoot(_) => ResponseTermination::DataColumnsByRoot,
             RequestType::DataColumnsByRange(_) => ResponseTermination::DataColumnsByRange,
+            RequestType::CheckpointStateChunks(_) => ResponseTermination::CheckpointStateChunks,
             RequestType::Status(_) => unreachable!(),
             RequestType::Goodbye(_) => unreachable!(),
             RequestType::Ping(_) => unreachable!(),
@@ -929,6 +971,10 @@ impl<E: EthSpec> RequestType<E> {
                 SupportedProtocol::LightClientUpdatesByRangeV1,
                 Encoding::SSZSnappy,
             )],
+            RequestType::CheckpointStateChunks(_) => vec![ProtocolId::new(
+                SupportedProtocol::CheckpointStateChunksV1,
+                Encoding::SSZSnappy,
+            )],
         }
     }
 
@@ -948,6 +994,7 @@ impl<E: EthSpec> RequestType<E> {
             RequestType::LightClientOptimisticUpdate => true,
             RequestType::LightClientFinalityUpdate => true,
             RequestType::LightClientUpdatesByRange(_) => true,
+            RequestType::CheckpointStateChunks(_) => false,
         }
     }
 }
@@ -1069,6 +1116,11 @@ impl<E: EthSpec> std::fmt::Display for RequestType<E> {
             RequestType::LightClientUpdatesByRange(_) => {
                 write!(f, "Light client updates by range request")
             }
+            RequestType::CheckpointStateChunks(req) => write!(
+                f,
+                "Checkpoint state chunks: block root: {}, start chunk: {}, count: {}",
+                req.block_root, req.start_chunk, req.count
+            ),
         }
     }
 }
@@ -17,6 +17,9 @@ use types::EthSpec;
 //       most <init time> + u64::MAX nanosecs, ~500 years. So it is realistic to assume this is fine.
 type Nanosecs = u64;
 
+/// The smallest factor the rate of a `Limiter` can be scaled by.
+const MIN_RATE_FACTOR: f64 = 0.01;
+
 /// User-friendly rate limiting parameters of the GCRA.
 ///
 /// A quota of `max_tokens` tokens every `replenish_all_every` units of time means that:
@@ -109,6 +112,8 @@ pub struct RPCRateLimiter {
     lc_finality_update_rl: Limiter<PeerId>,
     /// LightClientUpdatesByRange rate limiter.
     lc_updates_by_range_rl: Limiter<PeerId>,
+    /// CheckpointStateChunks rate limiter.
+    checkpoint_state_rl: Limiter<PeerId>,
 }
 
 /// Error type for non conformant requests
@@ -151,6 +156,8 @@ pub struct RPCRateLimiterBuilder {
     lc_finality_update_quota: Option<Quota>,
     /// Quota for the LightClientUpdatesByRange protocol.
     lc_updates_by_range_quota: Option<Quota>,
+    /// Quota for the CheckpointStateChunks protocol.
+    checkpoint_state_quota: Option<Quota>,
 }
 
 impl RPCRateLimiterBuilder {
@@ -172,6 +179,7 @@ impl RPCRateLimiterBuilder {
             Protocol::LightClientOptimisticUpdate => self.lc_optimistic_update_quota = q,
             Protocol::LightClientFinalityUpdate => self.lc_finality_update_quota = q,
             Protocol::LightClientUpdatesByRange => self.lc_updates_by_range_quota = q,
+            Protocol::CheckpointStateChunks => self.checkpoint_state_quota = q,
         }
         self
     }
@@ -200,6 +208,9 @@ impl RPCRateLimiterBuilder {
         let lc_updates_by_range_quota = self
             .lc_updates_by_range_quota
             .ok_or("LightClientUpdatesByRange quota not specified")?;
+        let checkpoint_state_quota = self
+            .checkpoint_state_quota
+            .ok_or("CheckpointStateChunks quota not specified")?;
 
         let blbrange_quota = self
             .blbrange_quota
@@ -231,6 +242,7 @@ impl RPCRateLimiterBuilder {
         let lc_optimistic_update_rl = Limiter::from_quota(lc_optimistic_update_quota)?;
         let lc_finality_update_rl = Limiter::from_quota(lc_finality_update_quota)?;
         let lc_updates_by_range_rl = Limiter::from_quota(lc_updates_by_range_quota)?;
+        let checkpoint_state_rl = Limiter::from_quota(checkpoint_state_quota)?;
 
         // check for peers to prune every 30 seconds, starting in 30 seconds
         let prune_every = tokio::time::Duration::from_secs(30);
@@ -252,6 +264,7 @@ impl RPCRateLimiterBuilder {
             lc_optimistic_update_rl,
             lc_finality_update_rl,
             lc_updates_by_range_rl,
+            checkpoint_state_rl,
             init_time: Instant::now(),
         })
     }
@@ -290,6 +303,7 @@ impl RPCRateLimiter {
             light_client_optimistic_update_quota,
             light_client_finality_update_quota,
             light_client_updates_by_range_quota,
+            checkpoint_state_chunks_quota,
         } = config;
 
         Self::builder()
@@ -316,6 +330,10 @@ impl RPCRateLimiter {
                 Protocol::LightClientUpdatesByRange,
                 light_client_updates_by_range_quota,
             )
+            .set_quota(
+                Protocol::CheckpointStateChunks,
+                checkpoint_state_chunks_quota,
+            )
             .build()
     }
 
@@ -349,10 +367,30 @@ impl RPCRateLimiter {
             Protocol::LightClientOptimisticUpdate => &mut self.lc_optimistic_update_rl,
             Protocol::LightClientFinalityUpdate => &mut self.lc_finality_update_rl,
             Protocol::LightClientUpdatesByRange => &mut self.lc_updates_by_range_rl,
+            Protocol::CheckpointStateChunks => &mut self.checkpoint_state_rl,
         };
         check(limiter)
     }
 
+    /// Scales the rate of the limiters of the protocols which serve blocks, blobs, data columns,
+    /// light client data and checkpoint states by `factor`, in `(0, 1]`. Used to reduce the bandwidth spent serving
+    /// peers.
+    pub fn set_serving_rate_factor(&mut self, factor: f64) {
+        for limiter in [
+            &mut self.bbrange_rl,
+            &mut self.bbroots_rl,
+            &mut self.blbrange_rl,
+            &mut self.blbroot_rl,
+            &mut self.dcbroot_rl,
+            &mut self.dcbrange_rl,
+            &mut self.lc_bootstrap_rl,
+            &mut self.lc_updates_by_range_rl,
+            &mut self.checkpoint_state_rl,
+        ] {
+            limiter.set_rate_factor(factor);
+        }
+    }
+
     pub fn prune(&mut self) {
         let time_since_start = self.init_time.elapsed();
         self.ping_rl.prune(time_since_start);
@@ -365,6 +403,7 @@ impl RPCRateLimiter {
         self.blbroot_rl.prune(time_since_start);
         self.dcbrange_rl.prune(time_since_start);
         self.dcbroot_rl.prune(time_since_start);
+        self.checkpoint_state_rl.prune(time_since_start);
     }
 }
 
@@ -387,6 +426,9 @@ pub struct Limiter<Key: Hash + Eq + Clone> {
     tau: Nanosecs,
     /// How often is 1T replenished.
     t: Nanosecs,
+    /// The `tau` and `t` of the quota, before any rate factor is applied.
+    quota_tau: Nanosecs,
+    quota_t: Nanosecs,
     /// Time when the bucket will be full for each peer. TAT (theoretical arrival time) from GCRA.
     tat_per_key: FnvHashMap<Key, Nanosecs>,
 }
@@ -409,10 +451,20 @@ impl<Key: Hash + Eq + Clone> Limiter<Key> {
         Ok(Limiter {
             tau,
             t,
+            quota_tau: tau,
+            quota_t: t,
             tat_per_key: FnvHashMap::default(),
         })
     }
 
+    /// Scales the rate at which tokens are replenished by `factor`, in `(0, 1]`, relative to the
+    /// quota. The maximum size of a batch is unchanged.
+    pub fn set_rate_factor(&mut self, factor: f64) {
+        let factor = factor.clamp(MIN_RATE_FACTOR, 1.0);
+        self.tau = (self.quota_tau as f64 / factor) as Nanosecs;
+        self.t = (self.quota_t as f64 / factor) as Nanosecs;
+    }
+
     pub fn allows(
         &mut self,
         time_since_start: Duration,
@@ -528,4 +580,36 @@ mod tests {
             .allows(Duration::from_secs_f32(0.4), &key, 1)
             .is_err());
     }
+
+    #[test]
+    fn rate_factor() {
+        let mut limiter = Limiter::from_quota(Quota {
+            replenish_all_every: Duration::from_secs(2),
+            max_tokens: 4,
+        })
+        .unwrap();
+        let key = 10;
+        // At half the rate, a token is replenished every second and a full batch is still
+        // allowed.
+        limiter.set_rate_factor(0.5);
+
+        assert!(limiter
+            .allows(Duration::from_secs_f32(0.0), &key, 4)
+            .is_ok());
+        assert!(limiter
+            .allows(Duration::from_secs_f32(0.5), &key, 1)
+            .is_err());
+        assert!(limiter
+            .allows(Duration::from_secs_f32(1.0), &key, 1)
+            .is_ok());
+
+        // Restoring the rate of the quota, the tokens already used are still accounted for.
+        limiter.set_rate_factor(1.0);
+        assert!(limiter
+            .allows(Duration::from_secs_f32(3.0), &key, 1)
+            .is_err());
+        assert!(limiter
+            .allows(Duration::from_secs_f32(3.5), &key, 1)
+            .is_ok());
+    }
 }
@@ -7,7 +7,9 @@ use types::{
 };
 
 use crate::rpc::{
-    methods::{ResponseTermination, RpcResponse, RpcSuccessResponse, StatusMessage},
+    methods::{
+        CheckpointStateChunk, ResponseTermination, RpcResponse, RpcSuccessResponse, StatusMessage,
+    },
     SubstreamId,
 };
 
@@ -119,6 +121,9 @@ pub enum Response<E: EthSpec> {
     LightClientFinalityUpdate(Arc<LightClientFinalityUpdate<E>>),
     /// A response to a LightClientUpdatesByRange request.
     LightClientUpdatesByRange(Option<Arc<LightClientUpdate<E>>>),
+    /// A response to a CheckpointStateChunks request. A None response signals the end of the
+    /// batch.
+    CheckpointStateChunks(Option<Arc<CheckpointStateChunk>>),
 }
 
 impl<E: EthSpec> std::convert::From<Response<E>> for RpcResponse<E> {
@@ -164,6 +169,10 @@ impl<E: EthSpec> std::convert::From<Response<E>> for RpcResponse<E> {
                     RpcResponse::StreamTermination(ResponseTermination::LightClientUpdatesByRange)
                 }
             },
+            Response::CheckpointStateChunks(r) => match r {
+                Some(c) => RpcResponse::Success(RpcSuccessResponse::CheckpointStateChunks(c)),
+                None => RpcResponse::StreamTermination(ResponseTermination::CheckpointStateChunks),
+            },
         }
     }
 }
@@ -0,0 +1,289 @@
+//! Adapts the network stack to keep the traffic of the node under a bandwidth budget.
+//!
+//! The traffic of the substreams of all connections is counted by the transport, so it covers
+//! every libp2p protocol but not the overhead of encryption and multiplexing, nor discovery. It
+//! is sampled periodically and smoothed. When it exceeds the budget the node steps down a level,
+//! and when it is comfortably under the budget the node steps back up. The highest level is the
+//! configured `network_load`, levels `1..=network_load` use the gossipsub parameters of the
+//! corresponding `NetworkLoad` and level `0` additionally sheds optional subnet subscriptions.
+//! Lower levels also:
+//!
+//! - Lower the IDONTWANT message size threshold, so duplicates of smaller messages are avoided,
+//!   down to `MIN_IDONTWANT_MESSAGE_SIZE_THRESHOLD`.
+//! - Lower the target number of peers.
+//! - Lower the rate at which blocks, blobs, data columns and light client data are served to
+//!   peers.
+
+use libp2p::core::{muxing::StreamMuxerBox, transport::Boxed};
+use libp2p::{PeerId, TransportExt};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// The interval at which the traffic is sampled.
+pub(crate) const BANDWIDTH_SAMPLE_INTERVAL: Duration = Duration::from_secs(12);
+/// The weight of the latest sample in the smoothed usage.
+const USAGE_SMOOTHING_FACTOR: f64 = 0.3;
+/// The fraction of the budget above which we step down a level.
+const STEP_DOWN_USAGE: f64 = 0.95;
+/// The fraction of the budget below which we step up a level.
+const STEP_UP_USAGE: f64 = 0.7;
+/// The minimum time between level changes, giving the network time to settle.
+const LEVEL_CHANGE_COOLDOWN: Duration = Duration::from_secs(60);
+/// The fraction of the configured target peers kept at level `0`.
+const MIN_TARGET_PEERS_FACTOR: f64 = 0.5;
+/// The lowest IDONTWANT message size threshold, in bytes. Below this, IDONTWANTs would be sent for
+/// unaggregated attestations and sync committee messages, costing about as much as the duplicates
+/// they avoid.
+const MIN_IDONTWANT_MESSAGE_SIZE_THRESHOLD: usize = 500;
+
+/// The smoothed traffic of the node, in bytes per second.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct Usage {
+    pub bytes_in: f64,
+    pub bytes_out: f64,
+}
+
+impl Usage {
+    pub fn total(&self) -> f64 {
+        self.bytes_in + self.bytes_out
+    }
+}
+
+pub(crate) struct BandwidthBudget {
+    /// The budget, in bytes per second.
+    budget: f64,
+    /// The configured network load, which is the highest level.
+    max_level: u8,
+    level: u8,
+    /// The configured target number of peers.
+    target_peers: usize,
+    /// The configured IDONTWANT message size threshold.
+    idontwant_message_size_threshold: usize,
+    usage: Option<Usage>,
+    /// The cumulative bytes received and sent, and the time of the last sample.
+    last_sample: Option<(u64, u64, Instant)>,
+    last_level_change: Option<Instant>,
+}
+
+impl BandwidthBudget {
+    pub fn new(
+        budget_mbps: f64,
+        network_load: u8,
+        target_peers: usize,
+        idontwant_message_size_threshold: usize,
+    ) -> Self {
+        let max_level = network_load.clamp(1, 5);
+        BandwidthBudget {
+            budget: mbps_to_bytes(budget_mbps),
+            max_level,
+            level: max_level,
+            target_peers,
+            idontwant_message_size_threshold,
+            usage: None,
+            last_sample: None,
+            last_level_change: None,
+        }
+    }
+
+    pub fn budget_mbps(&self) -> f64 {
+        bytes_to_mbps(self.budget)
+    }
+
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+
+    pub fn usage(&self) -> Option<Usage> {
+        self.usage
+    }
+
+    /// Samples the cumulative bytes received and sent by the node. Returns true if the level
+    /// changed.
+    pub fn sample(&mut self, bytes_in: u64, bytes_out: u64, now: Instant) -> bool {
+        let Some((last_in, last_out, last_time)) =
+            self.last_sample.replace((bytes_in, bytes_out, now))
+        else {
+            return false;
+        };
+        let elapsed = now.saturating_duration_since(last_time).as_secs_f64();
+        if elapsed == 0.0 {
+            return false;
+        }
+        let latest = Usage {
+            bytes_in: bytes_in.saturating_sub(last_in) as f64 / elapsed,
+            bytes_out: bytes_out.saturating_sub(last_out) as f64 / elapsed,
+        };
+        let usage = match self.usage {
+            Some(usage) => Usage {
+                bytes_in: smooth(usage.bytes_in, latest.bytes_in),
+                bytes_out: smooth(usage.bytes_out, latest.bytes_out),
+            },
+            None => latest,
+        };
+        self.usage = Some(usage);
+
+        if self
+            .last_level_change
+            .is_some_and(|changed| now.saturating_duration_since(changed) < LEVEL_CHANGE_COOLDOWN)
+        {
+            return false;
+        }
+        let new_level = if usage.total() > self.budget * STEP_DOWN_USAGE {
+            self.level.saturating_sub(1)
+        } else if usage.total() < self.budget * STEP_UP_USAGE {
+            (self.level + 1).min(self.max_level)
+        } else {
+            self.level
+        };
+        if new_level == self.level {
+            return false;
+        }
+        self.level = new_level;
+        self.last_level_change = Some(now);
+        true
+    }
+
+    /// The `NetworkLoad` whose gossipsub parameters are used at the current level.
+    pub fn network_load(&self) -> u8 {
+        self.level.max(1)
+    }
+
+    pub fn idontwant_message_size_threshold(&self) -> usize {
+        let scaled =
+            self.idontwant_message_size_threshold * self.level as usize / self.max_level as usize;
+        // Never raise the threshold above the configured value.
+        scaled.max(MIN_IDONTWANT_MESSAGE_SIZE_THRESHOLD.min(self.idontwant_message_size_threshold))
+    }
+
+    pub fn target_peers(&self) -> usize {
+        let factor = MIN_TARGET_PEERS_FACTOR
+            + (1.0 - MIN_TARGET_PEERS_FACTOR) * self.level as f64 / self.max_level as f64;
+        ((self.target_peers as f64 * factor).ceil() as usize).max(1)
+    }
+
+    /// The factor applied to the rate at which data is served to peers over RPC.
+    pub fn serving_rate_factor(&self) -> f64 {
+        (self.level as f64 + 1.0) / (self.max_level as f64 + 1.0)
+    }
+
+    /// Whether subnet subscriptions which are not required should be dropped.
+    pub fn shed_optional_subnets(&self) -> bool {
+        self.level == 0
+    }
+}
+
+fn smooth(previous: f64, latest: f64) -> f64 {
+    previous * (1.0 - USAGE_SMOOTHING_FACTOR) + latest * USAGE_SMOOTHING_FACTOR
+}
+
+fn mbps_to_bytes(mbps: f64) -> f64 {
+    mbps * 1_000_000.0 / 8.0
+}
+
+pub(crate) fn bytes_to_mbps(bytes: f64) -> f64 {
+    bytes * 8.0 / 1_000_000.0
+}
+
+/// The bytes received and sent over all connections of the transport.
+// The bandwidth sinks of libp2p are deprecated in favour of metrics which are only exposed to a
+// prometheus registry, whereas the budget needs to read the totals.
+#[allow(deprecated)]
+pub(crate) struct TransportTraffic(Arc<libp2p::bandwidth::BandwidthSinks>);
+
+impl TransportTraffic {
+    /// Wraps the transport so that the traffic of all its connections is counted.
+    #[allow(deprecated)]
+    pub fn wrap(
+        transport: Boxed<(PeerId, StreamMuxerBox)>,
+    ) -> (Boxed<(PeerId, StreamMuxerBox)>, Self) {
+        let (transport, sinks) = transport.with_bandwidth_logging();
+        (transport, TransportTraffic(sinks))
+    }
+
+    /// The cumulative bytes received and sent.
+    pub fn totals(&self) -> (u64, u64) {
+        (self.0.total_inbound(), self.0.total_outbound())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The bytes transferred in a sample interval at `mbps`.
+    fn bytes_in_interval(mbps: f64) -> u64 {
+        (mbps_to_bytes(mbps) * BANDWIDTH_SAMPLE_INTERVAL.as_secs_f64()) as u64
+    }
+
+    #[test]
+    fn test_bandwidth_budget_levels() {
+        let mut budget = BandwidthBudget::new(10.0, 3, 100, 1000);
+        let start = Instant::now();
+        let mut now = start;
+        let mut total = 0;
+        let mut sample = |budget: &mut BandwidthBudget, mbps: f64, now: &mut Instant| {
+            total += bytes_in_interval(mbps);
+            *now += BANDWIDTH_SAMPLE_INTERVAL;
+            budget.sample(total, 0, *now)
+        };
+
+        assert_eq!(budget.level(), 3);
+        assert_eq!(budget.target_peers(), 100);
+        assert_eq!(budget.serving_rate_factor(), 1.0);
+        assert!(!budget.sample(0, 0, now));
+
+        // Over budget, step down a level.
+        assert!(sample(&mut budget, 20.0, &mut now));
+        assert_eq!(budget.level(), 2);
+        assert!((budget.usage().unwrap().total() - mbps_to_bytes(20.0)).abs() < 1.0);
+
+        // Level changes wait for the cooldown.
+        for _ in 0..4 {
+            assert!(!sample(&mut budget, 20.0, &mut now));
+        }
+        assert!(sample(&mut budget, 20.0, &mut now));
+        assert_eq!(budget.level(), 1);
+        assert_eq!(budget.idontwant_message_size_threshold(), 500);
+        for _ in 0..5 {
+            sample(&mut budget, 20.0, &mut now);
+        }
+        assert_eq!(budget.level(), 0);
+        assert!(budget.shed_optional_subnets());
+        assert_eq!(budget.network_load(), 1);
+        assert_eq!(
+            budget.idontwant_message_size_threshold(),
+            MIN_IDONTWANT_MESSAGE_SIZE_THRESHOLD
+        );
+        assert_eq!(budget.target_peers(), 50);
+        assert_eq!(budget.serving_rate_factor(), 0.25);
+
+        // The lowest level is kept while over budget.
+        for _ in 0..10 {
+            sample(&mut budget, 20.0, &mut now);
+        }
+        assert_eq!(budget.level(), 0);
+
+        // Comfortably under budget, step back up to the configured load.
+        for _ in 0..30 {
+            sample(&mut budget, 1.0, &mut now);
+        }
+        assert_eq!(budget.level(), 3);
+        assert!(!budget.shed_optional_subnets());
+        assert_eq!(budget.idontwant_message_size_threshold(), 1000);
+    }
+
+    #[test]
+    fn test_idontwant_message_size_threshold_bounds() {
+        let mut budget = BandwidthBudget::new(10.0, 4, 100, 2000);
+        let expected = [(4, 2000), (3, 1500), (2, 1000), (1, 500), (0, 500)];
+        for (level, threshold) in expected {
+            budget.level = level;
+            assert_eq!(budget.idontwant_message_size_threshold(), threshold);
+        }
+
+        // A configured threshold below the minimum is never raised.
+        let mut budget = BandwidthBudget::new(10.0, 4, 100, 100);
+        budget.level = 0;
+        assert_eq!(budget.idontwant_message_size_threshold(), 100);
+    }
+}
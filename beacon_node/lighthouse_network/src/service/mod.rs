@@ -1,4 +1,8 @@
+use self::bandwidth_budget::{
+    bytes_to_mbps, BandwidthBudget, TransportTraffic, BANDWIDTH_SAMPLE_INTERVAL,
+};
 use self::gossip_cache::GossipCache;
+use crate::capture::NetworkCapture;
 use crate::config::{gossipsub_config, GossipsubConfigParams, NetworkLoad};
 use crate::discovery::{
     subnet_predicate, DiscoveredPeers, Discovery, FIND_NODE_QUERY_CLOSEST_PEERS,
@@ -46,6 +50,7 @@ use types::{ChainSpec, ForkName};
 use utils::{build_transport, strip_peer_id, Context as ServiceContext};
 
 pub mod api_types;
+mod bandwidth_budget;
 mod gossip_cache;
 pub mod gossipsub_scoring_parameters;
 pub mod utils;
@@ -159,6 +164,14 @@ pub struct Network<E: EthSpec> {
     /// The interval for updating gossipsub scores
     update_gossipsub_scores: tokio::time::Interval,
     gossip_cache: GossipCache,
+    /// Records inbound messages to disk, if enabled.
+    network_capture: Option<NetworkCapture<E>>,
+    /// Adapts the network to the bandwidth budget, if one is configured.
+    bandwidth_budget: Option<BandwidthBudget>,
+    /// The traffic of all connections, counted if a bandwidth budget is configured.
+    transport_traffic: Option<TransportTraffic>,
+    /// The interval for sampling the traffic against the bandwidth budget.
+    update_bandwidth_budget: tokio::time::Interval,
     /// This node's PeerId.
     pub local_peer_id: PeerId,
     /// Logger for behaviour actions.
@@ -367,6 +380,7 @@ impl<E: EthSpec> Network<E> {
             log.clone(),
             network_params,
             seq_number,
+            network_globals.rpc_stats.clone(),
         );
 
         let discovery = {
@@ -406,26 +420,42 @@ impl<E: EthSpec> Network<E> {
                 quic_enabled: !config.disable_quic_support,
                 metrics_enabled: config.metrics_enabled,
                 target_peer_count: config.target_peers,
+                peer_groups: config.peer_groups.clone(),
                 ..Default::default()
             };
             PeerManager::new(peer_manager_cfg, network_globals.clone(), &log)?
         };
 
         let connection_limits = {
+            // Slots reserved for peering groups are in addition to the regular limits.
+            let (reserved_inbound, reserved_outbound) =
+                config
+                    .peer_groups
+                    .iter()
+                    .fold((0, 0), |(inbound, outbound), group| {
+                        (
+                            inbound + group.reserved_inbound as u32,
+                            outbound + group.reserved_outbound as u32,
+                        )
+                    });
             let limits = libp2p::connection_limits::ConnectionLimits::default()
                 .with_max_pending_incoming(Some(5))
                 .with_max_pending_outgoing(Some(16))
                 .with_max_established_incoming(Some(
                     (config.target_peers as f32
                         * (1.0 + PEER_EXCESS_FACTOR - MIN_OUTBOUND_ONLY_FACTOR))
-                        .ceil() as u32,
+                        .ceil() as u32
+                        + reserved_inbound,
                 ))
                 .with_max_established_outgoing(Some(
-                    (config.target_peers as f32 * (1.0 + PEER_EXCESS_FACTOR)).ceil() as u32,
+                    (config.target_peers as f32 * (1.0 + PEER_EXCESS_FACTOR)).ceil() as u32
+                        + reserved_outbound,
                 ))
                 .with_max_established(Some(
                     (config.target_peers as f32 * (1.0 + PEER_EXCESS_FACTOR + PRIORITY_PEER_EXCESS))
-                        .ceil() as u32,
+                        .ceil() as u32
+                        + reserved_inbound
+                        + reserved_outbound,
                 ))
                 .with_max_established_per_peer(Some(1));
 
@@ -453,6 +483,26 @@ impl<E: EthSpec> Network<E> {
         let transport = build_transport(local_keypair.clone(), !config.disable_quic_support)
             .map_err(|e| format!("Failed to build transport: {:?}", e))?;
 
+        // Count the traffic of all connections if it is limited to a bandwidth budget.
+        let (transport, transport_traffic) = if config.bandwidth_budget_mbps.is_some() {
+            let (transport, traffic) = TransportTraffic::wrap(transport);
+            (transport, Some(traffic))
+        } else {
+            (transport, None)
+        };
+
+        let network_capture = match &config.network_capture {
+            Some(capture_config) => {
+                info!(log, "Capturing network messages"; "dir" => ?capture_config.dir);
+                Some(NetworkCapture::start(
+                    capture_config.clone(),
+                    &executor,
+                    &log,
+                )?)
+            }
+            None => None,
+        };
+
         // use the executor for libp2p
         struct Executor(task_executor::TaskExecutor);
         impl libp2p::swarm::Executor for Executor {
@@ -493,6 +543,17 @@ impl<E: EthSpec> Network<E> {
             }
         };
 
+        let bandwidth_budget = config.bandwidth_budget_mbps.map(|budget_mbps| {
+            info!(log, "Limiting network traffic to bandwidth budget"; "budget_mbps" => budget_mbps);
+            metrics::set_float_gauge(&metrics::BANDWIDTH_BUDGET, budget_mbps);
+            BandwidthBudget::new(
+                budget_mbps,
+                config.network_load,
+                config.target_peers,
+                config.idontwant_message_size_threshold,
+            )
+        });
+
         let mut network = Network {
             swarm,
             network_globals,
@@ -502,6 +563,10 @@ impl<E: EthSpec> Network<E> {
             score_settings,
             update_gossipsub_scores,
             gossip_cache,
+            network_capture,
+            bandwidth_budget,
+            transport_traffic,
+            update_bandwidth_budget: tokio::time::interval(BANDWIDTH_SAMPLE_INTERVAL),
             local_peer_id,
             log,
         };
@@ -889,6 +954,14 @@ impl<E: EthSpec> Network<E> {
         message_id: MessageId,
         validation_result: MessageAcceptance,
     ) {
+        if let Some(capture) = &self.network_capture {
+            capture.record_validation_result(
+                *propagation_source,
+                message_id.clone(),
+                validation_result,
+            );
+        }
+
         if let Some(result) = match validation_result {
             MessageAcceptance::Accept => None,
             MessageAcceptance::Ignore => Some("ignore"),
@@ -959,6 +1032,73 @@ impl<E: EthSpec> Network<E> {
         Ok(())
     }
 
+    /// Whether subnet subscriptions which are not required should be dropped to stay within the
+    /// bandwidth budget.
+    pub fn shed_optional_subnets(&self) -> bool {
+        self.bandwidth_budget
+            .as_ref()
+            .is_some_and(|budget| budget.shed_optional_subnets())
+    }
+
+    /// Samples the traffic of all connections of the node and, if the level of the bandwidth
+    /// budget changed, adapts the network parameters to the new level.
+    fn update_bandwidth_budget(&mut self) {
+        let (Some(budget), Some(traffic)) = (
+            self.bandwidth_budget.as_mut(),
+            self.transport_traffic.as_ref(),
+        ) else {
+            return;
+        };
+        let behaviour = self.swarm.behaviour_mut();
+        let (bytes_in, bytes_out) = traffic.totals();
+        let level_changed = budget.sample(bytes_in, bytes_out, std::time::Instant::now());
+
+        if let Some(usage) = budget.usage() {
+            metrics::set_float_gauge_vec(
+                &metrics::BANDWIDTH_USAGE,
+                &["inbound"],
+                bytes_to_mbps(usage.bytes_in),
+            );
+            metrics::set_float_gauge_vec(
+                &metrics::BANDWIDTH_USAGE,
+                &["outbound"],
+                bytes_to_mbps(usage.bytes_out),
+            );
+        }
+        metrics::set_gauge(&metrics::BANDWIDTH_BUDGET_LEVEL, budget.level() as i64);
+
+        if !level_changed {
+            return;
+        }
+        info!(
+            self.log,
+            "Adapting network to bandwidth budget";
+            "level" => budget.level(),
+            "usage_mbps" => budget.usage().map(|usage| bytes_to_mbps(usage.total())),
+            "budget_mbps" => budget.budget_mbps(),
+        );
+
+        let load = NetworkLoad::from(budget.network_load());
+        if let Err(e) = behaviour.gossipsub.set_mesh_params(
+            load.mesh_n_low,
+            load.mesh_n,
+            load.mesh_n_high,
+            load.outbound_min,
+            load.gossip_lazy,
+        ) {
+            crit!(self.log, "Invalid gossipsub mesh parameters"; "error" => ?e);
+        }
+        behaviour
+            .gossipsub
+            .set_idontwant_message_size_threshold(budget.idontwant_message_size_threshold());
+        behaviour
+            .peer_manager
+            .set_target_peers(budget.target_peers());
+        behaviour
+            .eth2_rpc
+            .set_serving_rate_factor(budget.serving_rate_factor());
+    }
+
     /* Eth2 RPC behaviour functions */
 
     /// Send a request to a peer over RPC.
@@ -986,8 +1126,9 @@ impl<E: EthSpec> Network<E> {
         request_id: rpc::RequestId,
         response: Response<E>,
     ) {
+        let response = response.into();
         self.eth2_rpc_mut()
-            .send_response(peer_id, id, request_id, response.into())
+            .send_response(peer_id, id, request_id, response)
     }
 
     /// Inform the peer that their request produced an error.
@@ -1246,6 +1387,15 @@ impl<E: EthSpec> Network<E> {
                 message_id: id,
                 message: gs_msg,
             } => {
+                if let Some(capture) = &self.network_capture {
+                    capture.record_gossip(
+                        propagation_source,
+                        id.clone(),
+                        gs_msg.topic.clone(),
+                        gs_msg.data.clone(),
+                    );
+                }
+
                 // Note: We are keeping track here of the peer that sent us the message, not the
                 // peer that originally published the message.
                 match PubsubMessage::decode(&gs_msg.topic, &gs_msg.data, &self.fork_context) {
@@ -1590,9 +1740,23 @@ impl<E: EthSpec> Network<E> {
                             request,
                         })
                     }
+                    RequestType::CheckpointStateChunks(_) => {
+                        metrics::inc_counter_vec(
+                            &metrics::TOTAL_RPC_REQUESTS,
+                            &["checkpoint_state_chunks"],
+                        );
+                        Some(NetworkEvent::RequestReceived {
+                            peer_id,
+                            id: (connection_id, request.substream_id),
+                            request,
+                        })
+                    }
                 }
             }
             Ok(RPCReceived::Response(id, resp)) => {
+                if let Some(capture) = &self.network_capture {
+                    capture.record_rpc_response(peer_id, resp.clone());
+                }
                 match resp {
                     /* Behaviour managed protocols */
                     RpcSuccessResponse::Pong(ping) => {
@@ -1648,6 +1812,11 @@ impl<E: EthSpec> Network<E> {
                         peer_id,
                         Response::LightClientUpdatesByRange(Some(update)),
                     ),
+                    RpcSuccessResponse::CheckpointStateChunks(chunk) => self.build_response(
+                        id,
+                        peer_id,
+                        Response::CheckpointStateChunks(Some(chunk)),
+                    ),
                 }
             }
             Ok(RPCReceived::EndOfStream(id, termination)) => {
@@ -1661,6 +1830,9 @@ impl<E: EthSpec> Network<E> {
                     ResponseTermination::LightClientUpdatesByRange => {
                         Response::LightClientUpdatesByRange(None)
                     }
+                    ResponseTermination::CheckpointStateChunks => {
+                        Response::CheckpointStateChunks(None)
+                    }
                 };
                 self.build_response(id, peer_id, response)
             }
@@ -1809,6 +1981,11 @@ impl<E: EthSpec> Network<E> {
                 _ = self.update_gossipsub_scores.tick() => {
                     let this = self.swarm.behaviour_mut();
                     this.peer_manager.update_gossipsub_scores(&this.gossipsub);
+                    this.peer_manager.update_gossip_stats(&this.gossipsub);
+                }
+                // sample the traffic against the bandwidth budget
+                _ = self.update_bandwidth_budget.tick(), if self.bandwidth_budget.is_some() => {
+                    self.update_bandwidth_budget();
                 }
                 // poll the gossipsub cache to clear expired messages
                 Some(result) = self.gossip_cache.next() => {
@@ -1,4 +1,5 @@
 //! A collection of variables that are accessible outside of the network thread itself.
+use crate::peer_manager::peerdb::stats::RpcStatsStore;
 use crate::peer_manager::peerdb::PeerDB;
 use crate::rpc::{MetaData, MetaDataV3};
 use crate::types::{BackFillState, SyncState};
@@ -18,6 +19,8 @@ pub struct NetworkGlobals<E: EthSpec> {
     pub listen_multiaddrs: RwLock<Vec<Multiaddr>>,
     /// The collection of known peers.
     pub peers: RwLock<PeerDB<E>>,
+    /// The RPC traffic exchanged with each peer, recorded by the RPC codecs.
+    pub rpc_stats: Arc<RpcStatsStore>,
     // The local meta data of our node.
     pub local_metadata: RwLock<MetaData<E>>,
     /// The current gossipsub topic subscriptions.
@@ -80,6 +83,7 @@ impl<E: EthSpec> NetworkGlobals<E> {
             listen_multiaddrs: RwLock::new(Vec::new()),
             local_metadata: RwLock::new(local_metadata),
             peers: RwLock::new(PeerDB::new(trusted_peers, disable_peer_scoring, log)),
+            rpc_stats: Arc::default(),
             gossipsub_subscriptions: RwLock::new(HashSet::new()),
             sync_state: RwLock::new(SyncState::Stalled),
             backfill_state: RwLock::new(BackFillState::Paused),
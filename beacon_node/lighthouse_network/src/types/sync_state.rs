@@ -14,7 +14,16 @@ pub enum SyncState {
     /// state. The node first syncs "forward" by downloading blocks up to the current head as
     /// specified by its peers. Once completed, the node enters this sync state and attempts to
     /// download all required historical blocks.
-    BackFillSyncing { completed: usize, remaining: usize },
+    BackFillSyncing {
+        completed: usize,
+        remaining: usize,
+        /// Why the backfill sync is held back by its schedule, if it is.
+        #[serde(default)]
+        paused: Option<BackFillPauseReason>,
+        /// Estimated number of seconds until the backfill sync completes.
+        #[serde(default)]
+        eta_seconds: Option<u64>,
+    },
     /// The node has completed syncing a finalized chain and is in the process of re-evaluating
     /// which sync state to progress to.
     SyncTransition,
@@ -39,6 +48,25 @@ pub enum BackFillState {
     Failed,
 }
 
+/// The reason a backfill sync is held back by its schedule.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackFillPauseReason {
+    /// The current time is outside the hours backfill sync is allowed to run.
+    OutsideWindow,
+    /// A validator connected to this node is about to propose a block.
+    LocalProposal,
+}
+
+impl std::fmt::Display for BackFillPauseReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BackFillPauseReason::OutsideWindow => write!(f, "outside backfill hours"),
+            BackFillPauseReason::LocalProposal => write!(f, "upcoming local block proposal"),
+        }
+    }
+}
+
 impl PartialEq for SyncState {
     fn eq(&self, other: &Self) -> bool {
         matches!(
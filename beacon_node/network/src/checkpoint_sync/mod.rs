@@ -0,0 +1,946 @@
+//! Checkpoint sync from peers, without a trusted HTTP endpoint.
+//!
+//! The block of the weak subjectivity checkpoint is downloaded with `BlocksByRoot` and checked
+//! against the checkpoint root, and its blobs with `BlobsByRoot`. The state is then downloaded in
+//! chunks from the Lighthouse peers that support the `CheckpointStateChunks` protocol. The first
+//! chunk is checked against the state root of the block, and each of the following chunks against
+//! the first one, so that the peers serving bad chunks are identified and banned.
+mod state_chunks;
+
+pub use state_chunks::{CheckpointStateCache, StateAssembly, StateChunks};
+
+use lighthouse_network::rpc::methods::{
+    BlobsByRootRequest, CheckpointStateChunk, CheckpointStateChunksRequest,
+    CHECKPOINT_STATE_CHUNK_SIZE, MAX_REQUEST_CHECKPOINT_STATE_CHUNKS,
+};
+use lighthouse_network::rpc::{
+    BlocksByRootRequest, RPCError, RequestId, RequestType, StatusMessage,
+};
+use lighthouse_network::service::api_types::AppRequestId;
+use lighthouse_network::service::Network;
+use lighthouse_network::{
+    Context, ListenAddr, ListenAddress, NetworkConfig, NetworkEvent, PeerAction, PeerId,
+    PeerRequestId, ReportSource, Response,
+};
+use slog::{debug, info, warn, Logger};
+use slot_clock::{SlotClock, SystemTimeSlotClock};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use task_executor::TaskExecutor;
+use types::blob_sidecar::BlobIdentifier;
+use types::{
+    BeaconState, BlobSidecar, BlobSidecarList, ChainSpec, Checkpoint, EthSpec, ForkContext,
+    SignedBeaconBlock,
+};
+
+/// How often to log the progress of the download.
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The number of times a peer may fail a request before we stop asking it.
+const MAX_PEER_FAILURES: usize = 3;
+
+/// The number of peers to ask for the blobs before starting without them.
+const MAX_BLOB_ATTEMPTS: usize = 3;
+
+/// The name of the directory, within the network directory, holding the key of the short-lived
+/// network used to checkpoint sync.
+const NETWORK_DIR: &str = "checkpoint_sync";
+
+/// Download the block, blobs and state of the finalized `checkpoint` from peers.
+///
+/// A short-lived network is started on ephemeral ports to connect to peers and is shut down
+/// before returning, so that it doesn't conflict with the network service started later.
+pub async fn checkpoint_sync_from_peers<E: EthSpec>(
+    executor: TaskExecutor,
+    config: &NetworkConfig,
+    checkpoint: Checkpoint,
+    genesis_state: &BeaconState<E>,
+    spec: Arc<ChainSpec>,
+    log: &Logger,
+) -> Result<
+    (
+        BeaconState<E>,
+        SignedBeaconBlock<E>,
+        Option<BlobSidecarList<E>>,
+    ),
+    String,
+> {
+    let slot_clock = SystemTimeSlotClock::new(
+        spec.genesis_slot,
+        Duration::from_secs(genesis_state.genesis_time()),
+        Duration::from_secs(spec.seconds_per_slot),
+    );
+    let current_slot = slot_clock.now().unwrap_or(spec.genesis_slot);
+    let genesis_validators_root = genesis_state.genesis_validators_root();
+    let enr_fork_id = spec.enr_fork_id::<E>(current_slot, genesis_validators_root);
+    let fork_context = Arc::new(ForkContext::new::<E>(
+        current_slot,
+        genesis_validators_root,
+        &spec,
+    ));
+
+    let mut config = config.clone();
+    config.network_dir = config.network_dir.join(NETWORK_DIR);
+    config.upnp_enabled = false;
+    config.set_listening_addr(ephemeral_ports(config.listen_addrs().clone()));
+    config.enr_udp4_port = None;
+    config.enr_quic4_port = None;
+    config.enr_tcp4_port = None;
+    config.enr_udp6_port = None;
+    config.enr_quic6_port = None;
+    config.enr_tcp6_port = None;
+
+    let context = Context {
+        config: Arc::new(config),
+        enr_fork_id: enr_fork_id.clone(),
+        fork_context,
+        chain_spec: spec.clone(),
+        libp2p_registry: None,
+    };
+    let (network, _) = Network::new(executor, context, log).await?;
+
+    // Peers check our finalized checkpoint against their chain, and see us as being at the
+    // checkpoint until we have synced.
+    let status = StatusMessage {
+        fork_digest: enr_fork_id.fork_digest,
+        finalized_root: checkpoint.root,
+        finalized_epoch: checkpoint.epoch,
+        head_root: checkpoint.root,
+        head_slot: checkpoint.epoch.start_slot(E::slots_per_epoch()),
+    };
+
+    let mut download = Download {
+        network,
+        checkpoint,
+        status,
+        spec,
+        peers: <_>::default(),
+        block: None,
+        blobs: BlobsDownload::Pending,
+        blob_attempts: 0,
+        state: StateDownload::default(),
+        chunk_size: CHECKPOINT_STATE_CHUNK_SIZE,
+        log: log.clone(),
+    };
+    download.run().await
+}
+
+/// The network through which a `Download` talks to peers.
+trait DownloadNetwork<E: EthSpec> {
+    fn send_request(&mut self, peer_id: PeerId, request: RequestType<E>) -> Result<(), RPCError>;
+
+    fn send_response(
+        &mut self,
+        peer_id: PeerId,
+        id: PeerRequestId,
+        request_id: RequestId,
+        response: Response<E>,
+    );
+
+    fn report_peer(&mut self, peer_id: &PeerId, action: PeerAction, msg: &'static str);
+}
+
+impl<E: EthSpec> DownloadNetwork<E> for Network<E> {
+    fn send_request(&mut self, peer_id: PeerId, request: RequestType<E>) -> Result<(), RPCError> {
+        Network::send_request(self, peer_id, AppRequestId::Router, request)
+            .map_err(|(_, error)| error)
+    }
+
+    fn send_response(
+        &mut self,
+        peer_id: PeerId,
+        id: PeerRequestId,
+        request_id: RequestId,
+        response: Response<E>,
+    ) {
+        Network::send_response(self, peer_id, id, request_id, response)
+    }
+
+    fn report_peer(&mut self, peer_id: &PeerId, action: PeerAction, msg: &'static str) {
+        Network::report_peer(self, peer_id, action, ReportSource::RPC, msg)
+    }
+}
+
+/// Set all the ports of `listen_addr` to 0, to listen on ports picked by the OS.
+fn ephemeral_ports(listen_addr: ListenAddress) -> ListenAddress {
+    fn zero_ports<Ip>(mut addr: ListenAddr<Ip>) -> ListenAddr<Ip> {
+        addr.disc_port = 0;
+        addr.quic_port = 0;
+        addr.tcp_port = 0;
+        addr
+    }
+
+    match listen_addr {
+        ListenAddress::V4(v4) => ListenAddress::V4(zero_ports(v4)),
+        ListenAddress::V6(v6) => ListenAddress::V6(zero_ports(v6)),
+        ListenAddress::DualStack(v4, v6) => {
+            ListenAddress::DualStack(zero_ports(v4), zero_ports(v6))
+        }
+    }
+}
+
+/// A request in flight to a peer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum InFlight {
+    Block,
+    Blobs,
+    StateChunks { start_chunk: u64, count: u64 },
+}
+
+#[derive(Default)]
+struct PeerInfo {
+    /// Whether the peer has finalized the checkpoint, so can serve its block and state.
+    ready: bool,
+    in_flight: Option<InFlight>,
+    /// The number of responses received for the request in flight.
+    responses: usize,
+    failures: usize,
+    /// The peer failed too many requests, or served data we know to be invalid.
+    excluded: bool,
+}
+
+impl PeerInfo {
+    fn is_idle(&self) -> bool {
+        self.ready && !self.excluded && self.in_flight.is_none()
+    }
+}
+
+enum BlobsDownload<E: EthSpec> {
+    /// The blobs are yet to be downloaded.
+    Pending,
+    /// The blobs being received from a peer.
+    Receiving(Vec<Arc<BlobSidecar<E>>>),
+    /// The blobs have been downloaded, or the block has none, or we gave up on them.
+    Done(Option<BlobSidecarList<E>>),
+}
+
+struct StateDownload<E: EthSpec> {
+    /// The state being assembled, once its header chunk has been received and verified.
+    assembly: Option<StateAssembly<E>>,
+    /// The status of each chunk of the state, including the header.
+    chunks: Vec<ChunkStatus>,
+}
+
+impl<E: EthSpec> Default for StateDownload<E> {
+    fn default() -> Self {
+        Self {
+            assembly: None,
+            chunks: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum ChunkStatus {
+    Missing,
+    Requested,
+    Received(Vec<u8>),
+}
+
+struct Download<E: EthSpec, N> {
+    network: N,
+    checkpoint: Checkpoint,
+    status: StatusMessage,
+    spec: Arc<ChainSpec>,
+    peers: HashMap<PeerId, PeerInfo>,
+    block: Option<Arc<SignedBeaconBlock<E>>>,
+    blobs: BlobsDownload<E>,
+    blob_attempts: usize,
+    state: StateDownload<E>,
+    /// The maximum size of the chunks of the state.
+    chunk_size: usize,
+    log: Logger,
+}
+
+impl<E: EthSpec> Download<E, Network<E>> {
+    async fn run(
+        &mut self,
+    ) -> Result<
+        (
+            BeaconState<E>,
+            SignedBeaconBlock<E>,
+            Option<BlobSidecarList<E>>,
+        ),
+        String,
+    > {
+        let mut progress = tokio::time::interval(PROGRESS_INTERVAL);
+        loop {
+            tokio::select! {
+                event = self.network.next_event() => self.on_event(event),
+                _ = progress.tick() => self.log_progress(),
+            }
+
+            if let Some(state) = self.try_assemble_state()? {
+                let block = self.block.take().ok_or("Checkpoint block missing")?;
+                let blobs = match std::mem::replace(&mut self.blobs, BlobsDownload::Done(None)) {
+                    BlobsDownload::Done(blobs) => blobs,
+                    _ => None,
+                };
+                return Ok((state, Arc::unwrap_or_clone(block), blobs));
+            }
+
+            self.send_requests();
+        }
+    }
+}
+
+impl<E: EthSpec, N: DownloadNetwork<E>> Download<E, N> {
+    fn on_event(&mut self, event: NetworkEvent<E>) {
+        match event {
+            NetworkEvent::PeerConnectedOutgoing(peer_id) | NetworkEvent::StatusPeer(peer_id) => {
+                self.send_request(peer_id, RequestType::Status(self.status.clone()));
+            }
+            NetworkEvent::PeerConnectedIncoming(peer_id) => {
+                self.peers.entry(peer_id).or_default();
+            }
+            NetworkEvent::PeerDisconnected(peer_id) => {
+                if let Some(peer) = self.peers.remove(&peer_id) {
+                    self.on_request_ended(peer.in_flight);
+                }
+            }
+            NetworkEvent::RequestReceived {
+                peer_id,
+                id,
+                request,
+            } => {
+                if let RequestType::Status(status) = request.r#type {
+                    self.on_status(peer_id, status);
+                    self.network.send_response(
+                        peer_id,
+                        id,
+                        request.id,
+                        Response::Status(self.status.clone()),
+                    );
+                }
+            }
+            NetworkEvent::ResponseReceived {
+                peer_id, response, ..
+            } => self.on_response(peer_id, response),
+            NetworkEvent::RPCFailed { peer_id, error, .. } => self.on_rpc_failed(peer_id, error),
+            _ => {}
+        }
+    }
+
+    fn on_status(&mut self, peer_id: PeerId, status: StatusMessage) {
+        let peer = self.peers.entry(peer_id).or_default();
+        if status.fork_digest != self.status.fork_digest
+            || (status.finalized_epoch == self.checkpoint.epoch
+                && status.finalized_root != self.checkpoint.root)
+        {
+            debug!(self.log, "Peer is on a different chain"; "peer" => %peer_id);
+            peer.excluded = true;
+            return;
+        }
+        peer.ready = status.finalized_epoch >= self.checkpoint.epoch;
+    }
+
+    fn on_response(&mut self, peer_id: PeerId, response: Response<E>) {
+        match response {
+            Response::Status(status) => self.on_status(peer_id, status),
+            Response::BlocksByRoot(Some(block)) => {
+                self.on_response_chunk(peer_id);
+                self.on_block(peer_id, block);
+            }
+            Response::BlobsByRoot(Some(blob)) => {
+                self.on_response_chunk(peer_id);
+                if let BlobsDownload::Receiving(blobs) = &mut self.blobs {
+                    blobs.push(blob);
+                }
+            }
+            Response::CheckpointStateChunks(Some(chunk)) => {
+                self.on_response_chunk(peer_id);
+                self.on_state_chunk(peer_id, &chunk);
+            }
+            Response::BlocksByRoot(None)
+            | Response::BlobsByRoot(None)
+            | Response::CheckpointStateChunks(None) => self.on_stream_termination(peer_id),
+            _ => {}
+        }
+    }
+
+    fn on_response_chunk(&mut self, peer_id: PeerId) {
+        if let Some(peer) = self.peers.get_mut(&peer_id) {
+            peer.responses += 1;
+        }
+    }
+
+    fn on_stream_termination(&mut self, peer_id: PeerId) {
+        let Some(peer) = self.peers.get_mut(&peer_id) else {
+            return;
+        };
+        let in_flight = peer.in_flight.take();
+        let responses = std::mem::take(&mut peer.responses);
+        if responses == 0 {
+            // The peer doesn't have what we asked for, presumably because it has pruned it.
+            peer.failures += 1;
+            peer.excluded |= peer.failures >= MAX_PEER_FAILURES;
+        }
+
+        if in_flight == Some(InFlight::Blobs) {
+            self.on_blobs_received(peer_id);
+        }
+        self.on_request_ended(in_flight);
+    }
+
+    fn on_rpc_failed(&mut self, peer_id: PeerId, error: RPCError) {
+        let Some(peer) = self.peers.get_mut(&peer_id) else {
+            return;
+        };
+        debug!(self.log, "Checkpoint sync request failed";
+            "peer" => %peer_id,
+            "request" => ?peer.in_flight,
+            "error" => %error,
+        );
+        peer.failures += 1;
+        peer.excluded |=
+            peer.failures >= MAX_PEER_FAILURES || matches!(error, RPCError::UnsupportedProtocol);
+        peer.responses = 0;
+        let in_flight = peer.in_flight.take();
+        self.on_request_ended(in_flight);
+    }
+
+    /// Make the chunks of a request that has ended without them available to request again.
+    fn on_request_ended(&mut self, in_flight: Option<InFlight>) {
+        match in_flight {
+            Some(InFlight::StateChunks { start_chunk, count }) => {
+                for status in self
+                    .state
+                    .chunks
+                    .iter_mut()
+                    .skip(start_chunk as usize)
+                    .take(count as usize)
+                {
+                    if *status == ChunkStatus::Requested {
+                        *status = ChunkStatus::Missing;
+                    }
+                }
+            }
+            Some(InFlight::Blobs) => {
+                if let BlobsDownload::Receiving(_) = self.blobs {
+                    self.blobs = BlobsDownload::Pending;
+                }
+            }
+            Some(InFlight::Block) | None => {}
+        }
+    }
+
+    fn on_block(&mut self, peer_id: PeerId, block: Arc<SignedBeaconBlock<E>>) {
+        if self.block.is_some() {
+            return;
+        }
+        if block.canonical_root() != self.checkpoint.root
+            || block.slot() > self.checkpoint.epoch.start_slot(E::slots_per_epoch())
+        {
+            self.penalize(peer_id, "invalid_checkpoint_block");
+            return;
+        }
+
+        info!(self.log, "Downloaded checkpoint block";
+            "block_slot" => block.slot(),
+            "block_root" => ?self.checkpoint.root,
+            "peer" => %peer_id,
+        );
+        if !block.message().body().has_blobs() {
+            self.blobs = BlobsDownload::Done(None);
+        }
+        self.block = Some(block);
+    }
+
+    fn on_blobs_received(&mut self, peer_id: PeerId) {
+        let BlobsDownload::Receiving(blobs) =
+            std::mem::replace(&mut self.blobs, BlobsDownload::Done(None))
+        else {
+            return;
+        };
+        self.blob_attempts += 1;
+
+        match self.verify_blobs(blobs) {
+            Ok(blobs) => {
+                debug!(self.log, "Downloaded checkpoint blobs"; "peer" => %peer_id);
+                self.blobs = BlobsDownload::Done(Some(blobs));
+            }
+            Err(e) if self.blob_attempts >= MAX_BLOB_ATTEMPTS => {
+                warn!(self.log, "Unable to download checkpoint blobs";
+                    "block_root" => ?self.checkpoint.root,
+                    "error" => e,
+                    "impact" => "db will be slightly corrupt until these blobs are pruned",
+                );
+            }
+            Err(e) => {
+                debug!(self.log, "Invalid checkpoint blobs"; "peer" => %peer_id, "error" => e);
+                self.penalize(peer_id, "invalid_checkpoint_blobs");
+                self.blobs = BlobsDownload::Pending;
+            }
+        }
+    }
+
+    fn verify_blobs(
+        &self,
+        mut blobs: Vec<Arc<BlobSidecar<E>>>,
+    ) -> Result<BlobSidecarList<E>, String> {
+        let block = self.block.as_ref().ok_or("Checkpoint block missing")?;
+        let commitments = block
+            .message()
+            .body()
+            .blob_kzg_commitments()
+            .map_err(|e| format!("{e:?}"))?;
+        if blobs.len() != commitments.len() {
+            return Err(format!(
+                "Expected {} blobs, received {}",
+                commitments.len(),
+                blobs.len()
+            ));
+        }
+
+        blobs.sort_by_key(|blob| blob.index);
+        for (index, (blob, commitment)) in blobs.iter().zip(commitments.iter()).enumerate() {
+            if blob.index != index as u64
+                || blob.block_root() != self.checkpoint.root
+                || blob.kzg_commitment != *commitment
+                || !blob.verify_blob_sidecar_inclusion_proof()
+            {
+                return Err(format!("Invalid blob at index {index}"));
+            }
+        }
+
+        BlobSidecarList::new(blobs).map_err(|e| format!("{e:?}"))
+    }
+
+    fn on_state_chunk(&mut self, peer_id: PeerId, chunk: &CheckpointStateChunk) {
+        let Some(assembly) = &self.state.assembly else {
+            self.on_state_header(peer_id, chunk);
+            return;
+        };
+        let excluded = self.peers.get(&peer_id).map_or(true, |peer| peer.excluded);
+        if chunk.index == 0 || excluded {
+            return;
+        }
+        if let Err(e) = assembly.verify_chunk(chunk) {
+            debug!(self.log, "Invalid checkpoint state chunk"; "peer" => %peer_id, "error" => e);
+            self.penalize(peer_id, "invalid_checkpoint_state_chunk");
+            return;
+        }
+        self.state.chunks[chunk.index as usize] = ChunkStatus::Received(chunk.data.to_vec());
+    }
+
+    /// Check the header of the state against the checkpoint block, from which the number of chunks
+    /// is known and the other chunks can be verified.
+    fn on_state_header(&mut self, peer_id: PeerId, chunk: &CheckpointStateChunk) {
+        let Some(block) = &self.block else {
+            return;
+        };
+        if chunk.index != 0 {
+            // The chunks following a header that has been rejected can't be verified.
+            return;
+        }
+        let assembly = match StateAssembly::from_header(
+            chunk,
+            block.state_root(),
+            self.chunk_size,
+            &self.spec,
+        ) {
+            Ok(assembly) => assembly,
+            Err(e) => {
+                debug!(self.log, "Invalid checkpoint state header"; "peer" => %peer_id, "error" => e);
+                self.penalize(peer_id, "invalid_checkpoint_state_header");
+                return;
+            }
+        };
+
+        debug!(self.log, "Downloading checkpoint state";
+            "total_chunks" => assembly.total_chunks(),
+            "peer" => %peer_id,
+        );
+        self.state.chunks = vec![ChunkStatus::Missing; assembly.total_chunks() as usize];
+        // Don't request again the chunks already requested before the number of chunks was known.
+        for peer in self.peers.values() {
+            if let Some(InFlight::StateChunks { start_chunk, count }) = peer.in_flight {
+                for status in self
+                    .state
+                    .chunks
+                    .iter_mut()
+                    .skip(start_chunk as usize)
+                    .take(count as usize)
+                {
+                    *status = ChunkStatus::Requested;
+                }
+            }
+        }
+        self.state.chunks[0] = ChunkStatus::Received(vec![]);
+        self.state.assembly = Some(assembly);
+    }
+
+    /// Fill the state with the chunks once they have all been received.
+    fn try_assemble_state(&mut self) -> Result<Option<BeaconState<E>>, String> {
+        if !matches!(self.blobs, BlobsDownload::Done(_)) {
+            return Ok(None);
+        }
+        let Some(block) = &self.block else {
+            return Ok(None);
+        };
+        if self.state.assembly.is_none()
+            || self
+                .state
+                .chunks
+                .iter()
+                .any(|status| !matches!(status, ChunkStatus::Received(_)))
+        {
+            return Ok(None);
+        }
+
+        let StateDownload { assembly, chunks } = std::mem::take(&mut self.state);
+        let assembly = assembly.ok_or("Checkpoint state header missing")?;
+        let data = chunks.into_iter().skip(1).map(|status| match status {
+            ChunkStatus::Received(data) => data,
+            _ => vec![],
+        });
+        let mut state = assembly.assemble(data)?;
+
+        // Every chunk has been verified, so this only guards against bugs.
+        let state_root = block.state_root();
+        let root = state.canonical_root().map_err(|e| format!("{e:?}"))?;
+        if root != state_root {
+            return Err(format!(
+                "Assembled state root {root:?} does not match {state_root:?}"
+            ));
+        }
+        Ok(Some(state))
+    }
+
+    /// Send requests for what is left to download to the idle peers.
+    fn send_requests(&mut self) {
+        let idle_peers = self
+            .peers
+            .iter()
+            .filter(|(_, peer)| peer.is_idle())
+            .map(|(peer_id, _)| *peer_id)
+            .collect::<Vec<_>>();
+
+        for peer_id in idle_peers {
+            if self.block.is_none() {
+                let already_requested = self
+                    .peers
+                    .values()
+                    .any(|peer| peer.in_flight == Some(InFlight::Block));
+                if !already_requested {
+                    let request = BlocksByRootRequest::new(vec![self.checkpoint.root], &self.spec);
+                    self.send_data_request(
+                        peer_id,
+                        InFlight::Block,
+                        RequestType::BlocksByRoot(request),
+                    );
+                }
+                continue;
+            }
+
+            if let BlobsDownload::Pending = self.blobs {
+                if let Some(block) = &self.block {
+                    let blob_ids = (0..block.num_expected_blobs() as u64)
+                        .map(|index| BlobIdentifier {
+                            block_root: self.checkpoint.root,
+                            index,
+                        })
+                        .collect();
+                    let request = BlobsByRootRequest::new(blob_ids, &self.spec);
+                    self.blobs = BlobsDownload::Receiving(vec![]);
+                    self.send_data_request(
+                        peer_id,
+                        InFlight::Blobs,
+                        RequestType::BlobsByRoot(request),
+                    );
+                    continue;
+                }
+            }
+
+            let (start_chunk, count) = if self.state.assembly.is_none() {
+                // Until the header has been received, the number of chunks is unknown.
+                let already_requested = self
+                    .peers
+                    .values()
+                    .any(|peer| matches!(peer.in_flight, Some(InFlight::StateChunks { .. })));
+                if already_requested {
+                    continue;
+                }
+                (0, MAX_REQUEST_CHECKPOINT_STATE_CHUNKS)
+            } else {
+                let Some(start_chunk) = self
+                    .state
+                    .chunks
+                    .iter()
+                    .position(|status| *status == ChunkStatus::Missing)
+                else {
+                    break;
+                };
+                let count = self.state.chunks[start_chunk..]
+                    .iter()
+                    .take(MAX_REQUEST_CHECKPOINT_STATE_CHUNKS as usize)
+                    .take_while(|status| **status == ChunkStatus::Missing)
+                    .count();
+                for status in &mut self.state.chunks[start_chunk..start_chunk + count] {
+                    *status = ChunkStatus::Requested;
+                }
+                (start_chunk as u64, count as u64)
+            };
+            let request = CheckpointStateChunksRequest {
+                block_root: self.checkpoint.root,
+                start_chunk,
+                count,
+            };
+            self.send_data_request(
+                peer_id,
+                InFlight::StateChunks { start_chunk, count },
+                RequestType::CheckpointStateChunks(request),
+            );
+        }
+    }
+
+    fn send_data_request(&mut self, peer_id: PeerId, in_flight: InFlight, request: RequestType<E>) {
+        if let Some(peer) = self.peers.get_mut(&peer_id) {
+            peer.in_flight = Some(in_flight);
+            peer.responses = 0;
+        }
+        self.send_request(peer_id, request);
+    }
+
+    fn send_request(&mut self, peer_id: PeerId, request: RequestType<E>) {
+        if let Err(error) = self.network.send_request(peer_id, request) {
+            self.on_rpc_failed(peer_id, error);
+        }
+    }
+
+    fn penalize(&mut self, peer_id: PeerId, msg: &'static str) {
+        if let Some(peer) = self.peers.get_mut(&peer_id) {
+            peer.excluded = true;
+        }
+        self.network
+            .report_peer(&peer_id, PeerAction::LowToleranceError, msg);
+    }
+
+    fn log_progress(&self) {
+        let ready_peers = self
+            .peers
+            .values()
+            .filter(|peer| peer.ready && !peer.excluded)
+            .count();
+        let received_chunks = self
+            .state
+            .chunks
+            .iter()
+            .filter(|status| matches!(status, ChunkStatus::Received(_)))
+            .count();
+        info!(self.log, "Checkpoint sync from peers";
+            "peers" => ready_peers,
+            "block" => if self.block.is_some() { "downloaded" } else { "pending" },
+            "state_chunks" => format!("{}/{}", received_chunks, self.state.chunks.len()),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use beacon_chain::test_utils::{BeaconChainHarness, EphemeralHarnessType};
+    use logging::test_logger;
+    use types::{Epoch, MinimalEthSpec};
+
+    type E = MinimalEthSpec;
+
+    /// Small enough for the state to span many chunks.
+    const CHUNK_SIZE: usize = 256;
+
+    /// Records the requests sent and the peers reported, for the test to respond to.
+    #[derive(Default)]
+    struct MockNetwork {
+        requests: Vec<(PeerId, RequestType<E>)>,
+        reports: Vec<(PeerId, &'static str)>,
+    }
+
+    impl DownloadNetwork<E> for MockNetwork {
+        fn send_request(
+            &mut self,
+            peer_id: PeerId,
+            request: RequestType<E>,
+        ) -> Result<(), RPCError> {
+            self.requests.push((peer_id, request));
+            Ok(())
+        }
+
+        fn send_response(&mut self, _: PeerId, _: PeerRequestId, _: RequestId, _: Response<E>) {}
+
+        fn report_peer(&mut self, peer_id: &PeerId, _: PeerAction, msg: &'static str) {
+            self.reports.push((*peer_id, msg));
+        }
+    }
+
+    /// How a peer tampers with the chunks it serves.
+    #[derive(Clone, Copy, PartialEq)]
+    enum Serve {
+        Honest,
+        BadHeader,
+        BadChunks,
+    }
+
+    struct TestRig {
+        download: Download<E, MockNetwork>,
+        block: Arc<SignedBeaconBlock<E>>,
+        chunks: StateChunks,
+        state_root: types::Hash256,
+        peers: HashMap<PeerId, Serve>,
+    }
+
+    impl TestRig {
+        fn new() -> Self {
+            let harness: BeaconChainHarness<EphemeralHarnessType<E>> =
+                BeaconChainHarness::builder(MinimalEthSpec)
+                    .default_spec()
+                    .deterministic_keypairs(64)
+                    .fresh_ephemeral_store()
+                    .build();
+            let head = harness.chain.head_snapshot();
+            let checkpoint = Checkpoint {
+                epoch: Epoch::new(0),
+                root: head.beacon_block_root,
+            };
+            let mut state = head.beacon_state.clone();
+            let state_root = state.canonical_root().unwrap();
+            let chunks = StateChunks::with_chunk_size(checkpoint.root, &state, CHUNK_SIZE).unwrap();
+            let status = StatusMessage {
+                fork_digest: [0; 4],
+                finalized_root: checkpoint.root,
+                finalized_epoch: checkpoint.epoch,
+                head_root: checkpoint.root,
+                head_slot: checkpoint.epoch.start_slot(E::slots_per_epoch()),
+            };
+
+            Self {
+                download: Download {
+                    network: MockNetwork::default(),
+                    checkpoint,
+                    status,
+                    spec: harness.spec.clone(),
+                    peers: <_>::default(),
+                    block: None,
+                    blobs: BlobsDownload::Pending,
+                    blob_attempts: 0,
+                    state: StateDownload::default(),
+                    chunk_size: CHUNK_SIZE,
+                    log: test_logger(),
+                },
+                block: head.beacon_block.clone(),
+                chunks,
+                state_root,
+                peers: <_>::default(),
+            }
+        }
+
+        fn add_peer(&mut self, serve: Serve) -> PeerId {
+            let peer_id = PeerId::random();
+            self.peers.insert(peer_id, serve);
+            self.respond(peer_id, Response::Status(self.download.status.clone()));
+            peer_id
+        }
+
+        fn respond(&mut self, peer_id: PeerId, response: Response<E>) {
+            self.download.on_event(NetworkEvent::ResponseReceived {
+                peer_id,
+                id: AppRequestId::Router,
+                response,
+            });
+        }
+
+        fn chunk(&self, serve: Serve, index: u64) -> Option<CheckpointStateChunk> {
+            let mut chunk = self.chunks.chunk(index)?;
+            match serve {
+                Serve::BadHeader if index == 0 => chunk.data[100] ^= 1,
+                Serve::BadChunks if index > 0 => chunk.data[0] ^= 1,
+                _ => {}
+            }
+            Some(chunk)
+        }
+
+        /// Serve the requests sent by the download, until the state is assembled or no more
+        /// requests are sent.
+        fn run(&mut self) -> Option<BeaconState<E>> {
+            loop {
+                if let Some(state) = self.download.try_assemble_state().unwrap() {
+                    return Some(state);
+                }
+                self.download.send_requests();
+                let requests = std::mem::take(&mut self.download.network.requests);
+                if requests.is_empty() {
+                    return None;
+                }
+                for (peer_id, request) in requests {
+                    let serve = self.peers[&peer_id];
+                    match request {
+                        RequestType::BlocksByRoot(_) => {
+                            self.respond(peer_id, Response::BlocksByRoot(Some(self.block.clone())));
+                            self.respond(peer_id, Response::BlocksByRoot(None));
+                        }
+                        RequestType::CheckpointStateChunks(request) => {
+                            for index in request.start_chunk..request.start_chunk + request.count {
+                                if let Some(chunk) = self.chunk(serve, index) {
+                                    let response =
+                                        Response::CheckpointStateChunks(Some(Arc::new(chunk)));
+                                    self.respond(peer_id, response);
+                                }
+                            }
+                            self.respond(peer_id, Response::CheckpointStateChunks(None));
+                        }
+                        _ => panic!("Unexpected request {request:?}"),
+                    }
+                }
+            }
+        }
+
+        fn assert_assembled(&mut self, state: Option<BeaconState<E>>) {
+            let mut state = state.expect("state should be assembled");
+            assert_eq!(state.canonical_root().unwrap(), self.state_root);
+        }
+    }
+
+    #[test]
+    fn download_from_honest_peers() {
+        let mut rig = TestRig::new();
+        rig.add_peer(Serve::Honest);
+        rig.add_peer(Serve::Honest);
+        let state = rig.run();
+        rig.assert_assembled(state);
+        assert!(rig.download.network.reports.is_empty());
+    }
+
+    #[test]
+    fn peer_serving_bad_header_is_reported() {
+        let mut rig = TestRig::new();
+        let bad_peer = rig.add_peer(Serve::BadHeader);
+        assert!(rig.run().is_none());
+        assert_eq!(
+            rig.download.network.reports,
+            vec![(bad_peer, "invalid_checkpoint_state_header")]
+        );
+
+        rig.add_peer(Serve::Honest);
+        let state = rig.run();
+        rig.assert_assembled(state);
+        assert_eq!(rig.download.network.reports.len(), 1);
+    }
+
+    #[test]
+    fn peer_serving_bad_chunks_is_reported() {
+        let mut rig = TestRig::new();
+        let bad_peer = rig.add_peer(Serve::BadChunks);
+        assert!(rig.run().is_none());
+        // The header is valid, and the first of the following chunks gets the peer excluded.
+        assert_eq!(
+            rig.download.network.reports,
+            vec![(bad_peer, "invalid_checkpoint_state_chunk")]
+        );
+        assert!(rig.download.state.assembly.is_some());
+
+        rig.add_peer(Serve::Honest);
+        let state = rig.run();
+        rig.assert_assembled(state);
+        assert_eq!(rig.download.network.reports.len(), 1);
+    }
+}
@@ -0,0 +1,623 @@
+//! Splits a state into chunks that can be verified individually against the state root of its
+//! block.
+//!
+//! The first chunk is a `CheckpointStateHeader`: the state with its large lists left empty, along
+//! with the length and the root of the elements of each of these lists. The header is verified by
+//! recomputing the state root from it. The elements of the lists are then served in chunks
+//! aligned to subtrees of the tree hash of their list, each with a proof against the root of the
+//! elements of its list, so that a bad chunk is detected as soon as it arrives and the peer
+//! serving it can be identified.
+use ethereum_hashing::{hash32_concat, ZERO_HASHES};
+use lighthouse_network::rpc::methods::{
+    CheckpointStateChunk, CheckpointStateHeader, CheckpointStateList, CHECKPOINT_STATE_CHUNK_SIZE,
+};
+use merkle_proof::{verify_merkle_proof, MerkleTree};
+use parking_lot::Mutex;
+use ssz::{Decode, Encode};
+use ssz_types::VariableList;
+use std::ops::Range;
+use std::sync::Arc;
+use tree_hash::{TreeHash, TreeHashType};
+use types::{
+    milhouse, BeaconState, ChainSpec, EthSpec, FixedBytesExtended, Hash256, List, Unsigned,
+};
+
+/// The chunks of the last state served to peers, shared by the workers handling requests.
+pub type CheckpointStateCache = Arc<Mutex<Option<Arc<StateChunks>>>>;
+
+/// Computes the root of the subtree of a chunk of SSZ encoded elements, with the given number of
+/// leaves.
+type ChunkRootFn = fn(&[u8], usize) -> Result<Hash256, String>;
+
+fn chunk_root<T: Encode + Decode + TreeHash>(
+    data: &[u8],
+    leaves: usize,
+) -> Result<Hash256, String> {
+    if matches!(T::tree_hash_type(), TreeHashType::Basic) {
+        // The SSZ encoding of basic values is their packed tree hash encoding.
+        return Ok(tree_hash::merkle_root(data, leaves));
+    }
+    let mut roots = Vec::with_capacity(leaves * 32);
+    for bytes in data.chunks(<T as Encode>::ssz_fixed_len()) {
+        let element = T::from_ssz_bytes(bytes).map_err(|e| format!("{e:?}"))?;
+        roots.extend_from_slice(element.tree_hash_root().as_slice());
+    }
+    Ok(tree_hash::merkle_root(&roots, leaves))
+}
+
+/// A list of the state which is served in chunks of elements.
+trait ChunkedList {
+    fn length(&self) -> usize;
+    fn max_length(&self) -> usize;
+    /// The size of the SSZ encoding of each element.
+    fn element_size(&self) -> usize;
+    /// The number of elements packed into each leaf of the tree of the list.
+    fn elements_per_leaf(&self) -> usize;
+    fn chunk_root_fn(&self) -> ChunkRootFn;
+    /// The depth of the tree of the list, before the length is mixed in.
+    fn tree_depth(&self) -> usize {
+        self.max_length()
+            .div_ceil(self.elements_per_leaf())
+            .next_power_of_two()
+            .ilog2() as usize
+    }
+    /// Returns the SSZ encoded elements of the list and leaves it empty.
+    fn take_elements(&mut self) -> Vec<u8>;
+    /// Replace the elements of the list with the SSZ encoded `data`.
+    fn set_elements(&mut self, data: &[u8]) -> Result<(), String>;
+}
+
+impl<T: milhouse::Value + Encode + Decode, N: Unsigned> ChunkedList for List<T, N> {
+    fn length(&self) -> usize {
+        self.len()
+    }
+
+    fn max_length(&self) -> usize {
+        N::to_usize()
+    }
+
+    fn element_size(&self) -> usize {
+        <T as Encode>::ssz_fixed_len()
+    }
+
+    fn elements_per_leaf(&self) -> usize {
+        match T::tree_hash_type() {
+            TreeHashType::Basic => T::tree_hash_packing_factor(),
+            _ => 1,
+        }
+    }
+
+    fn chunk_root_fn(&self) -> ChunkRootFn {
+        chunk_root::<T>
+    }
+
+    fn take_elements(&mut self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.len() * self.element_size());
+        for element in self.iter() {
+            element.ssz_append(&mut data);
+        }
+        *self = List::empty();
+        data
+    }
+
+    fn set_elements(&mut self, data: &[u8]) -> Result<(), String> {
+        let elements = data
+            .chunks(self.element_size())
+            .map(T::from_ssz_bytes)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("{e:?}"))?;
+        *self = List::new(elements).map_err(|e| format!("{e:?}"))?;
+        Ok(())
+    }
+}
+
+/// Call `f` with the field index and the value of each list of `state` that is served in chunks,
+/// in field order.
+///
+/// These are the lists which grow with the number of validators or over time, and whose elements
+/// have a fixed size. The indices of the fields of the state never change across forks.
+fn for_each_chunked_list<E: EthSpec>(
+    state: &mut BeaconState<E>,
+    mut f: impl FnMut(usize, &mut dyn ChunkedList) -> Result<(), String>,
+) -> Result<(), String> {
+    f(7, state.historical_roots_mut())?;
+    f(9, state.eth1_data_votes_mut())?;
+    f(11, state.validators_mut())?;
+    f(12, state.balances_mut())?;
+    if let Ok(list) = state.previous_epoch_participation_mut() {
+        f(15, list)?;
+    }
+    if let Ok(list) = state.current_epoch_participation_mut() {
+        f(16, list)?;
+    }
+    if let Ok(list) = state.inactivity_scores_mut() {
+        f(21, list)?;
+    }
+    if let Ok(list) = state.historical_summaries_mut() {
+        f(27, list)?;
+    }
+    if let Ok(list) = state.pending_balance_deposits_mut() {
+        f(34, list)?;
+    }
+    if let Ok(list) = state.pending_partial_withdrawals_mut() {
+        f(35, list)?;
+    }
+    if let Ok(list) = state.pending_consolidations_mut() {
+        f(36, list)?;
+    }
+    Ok(())
+}
+
+/// How the elements of a list are split into chunks.
+struct ListLayout {
+    field_index: usize,
+    length: usize,
+    element_size: usize,
+    elements_per_chunk: usize,
+    /// The height of the subtree of each chunk in the tree of the list.
+    chunk_height: usize,
+    /// The depth of the tree of the list above the subtrees of the chunks.
+    depth: usize,
+    elements_root: Hash256,
+    /// The index of the first chunk of the list among the chunks of the state.
+    first_chunk: u64,
+    chunk_root: ChunkRootFn,
+}
+
+impl ListLayout {
+    /// The layout of `list`, of `length` elements, in chunks of at most `chunk_size` bytes.
+    ///
+    /// Each chunk holds the largest power of two number of leaves of the tree of the list that
+    /// fits in `chunk_size`, so that all the nodes serving a state split it in the same way.
+    fn new(
+        field_index: usize,
+        list: &dyn ChunkedList,
+        length: u64,
+        elements_root: Hash256,
+        first_chunk: u64,
+        chunk_size: usize,
+    ) -> Result<Self, String> {
+        let length = usize::try_from(length).map_err(|e| e.to_string())?;
+        if length > list.max_length() {
+            return Err(format!("List {field_index} has too many elements"));
+        }
+        let leaves = std::cmp::max(
+            chunk_size / list.element_size() / list.elements_per_leaf(),
+            1,
+        );
+        let chunk_height = std::cmp::min(leaves.ilog2() as usize, list.tree_depth());
+
+        Ok(Self {
+            field_index,
+            length,
+            element_size: list.element_size(),
+            elements_per_chunk: (1 << chunk_height) * list.elements_per_leaf(),
+            chunk_height,
+            depth: list.tree_depth() - chunk_height,
+            elements_root,
+            first_chunk,
+            chunk_root: list.chunk_root_fn(),
+        })
+    }
+
+    fn contains(&self, chunk_index: u64) -> bool {
+        (self.first_chunk..self.first_chunk + self.num_chunks()).contains(&chunk_index)
+    }
+
+    fn num_chunks(&self) -> u64 {
+        self.length.div_ceil(self.elements_per_chunk) as u64
+    }
+
+    /// The byte range of the SSZ encoded elements of the list held by the chunk at `index` in the
+    /// list.
+    fn chunk_range(&self, index: usize) -> Range<usize> {
+        let start = index * self.elements_per_chunk;
+        let end = std::cmp::min(start + self.elements_per_chunk, self.length);
+        start * self.element_size..end * self.element_size
+    }
+
+    /// The root of the field of the list in the state.
+    fn field_root(&self) -> Hash256 {
+        Hash256::from(hash32_concat(
+            self.elements_root.as_slice(),
+            Hash256::from_low_u64_le(self.length as u64).as_slice(),
+        ))
+    }
+}
+
+/// The root of an empty subtree of the given `height`.
+fn zero_hash(height: usize) -> Hash256 {
+    Hash256::from_slice(&ZERO_HASHES[height])
+}
+
+/// The state of a checkpoint block, split into chunks.
+pub struct StateChunks {
+    block_root: Hash256,
+    header: Vec<u8>,
+    lists: Vec<ListChunks>,
+    total_chunks: u64,
+}
+
+/// The elements of a list of the state, with the tree above the subtrees of its chunks.
+struct ListChunks {
+    layout: ListLayout,
+    data: Vec<u8>,
+    /// The nodes of the tree of the list at each height above the chunks, bottom-up, omitting
+    /// empty subtrees.
+    levels: Vec<Vec<Hash256>>,
+}
+
+impl ListChunks {
+    fn proof(&self, index: usize) -> Vec<Hash256> {
+        (0..self.layout.depth)
+            .map(|height| {
+                self.levels[height]
+                    .get((index >> height) ^ 1)
+                    .copied()
+                    .unwrap_or_else(|| zero_hash(self.layout.chunk_height + height))
+            })
+            .collect()
+    }
+}
+
+impl StateChunks {
+    /// Split the `state` of the block with `block_root` into chunks.
+    pub fn new<E: EthSpec>(block_root: Hash256, state: &BeaconState<E>) -> Result<Self, String> {
+        Self::with_chunk_size(block_root, state, CHECKPOINT_STATE_CHUNK_SIZE)
+    }
+
+    /// Split `state` into chunks of at most `chunk_size` bytes of list elements.
+    pub fn with_chunk_size<E: EthSpec>(
+        block_root: Hash256,
+        state: &BeaconState<E>,
+        chunk_size: usize,
+    ) -> Result<Self, String> {
+        let mut header_state = state.clone();
+        let mut lists = vec![];
+        let mut total_chunks = 1;
+        for_each_chunked_list(&mut header_state, |field_index, list| {
+            let mut layout = ListLayout::new(
+                field_index,
+                list,
+                list.length() as u64,
+                Hash256::zero(),
+                total_chunks,
+                chunk_size,
+            )?;
+
+            let data = list.take_elements();
+            let chunk_leaves = 1 << layout.chunk_height;
+            let mut level = (0..layout.num_chunks() as usize)
+                .map(|index| (layout.chunk_root)(&data[layout.chunk_range(index)], chunk_leaves))
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut levels = vec![];
+            for height in 0..layout.depth {
+                let next = level
+                    .chunks(2)
+                    .map(|pair| {
+                        let right = pair
+                            .get(1)
+                            .copied()
+                            .unwrap_or_else(|| zero_hash(layout.chunk_height + height));
+                        Hash256::from(hash32_concat(pair[0].as_slice(), right.as_slice()))
+                    })
+                    .collect();
+                levels.push(std::mem::replace(&mut level, next));
+            }
+            layout.elements_root = level
+                .first()
+                .copied()
+                .unwrap_or_else(|| zero_hash(layout.chunk_height + layout.depth));
+
+            total_chunks += layout.num_chunks();
+            lists.push(ListChunks {
+                layout,
+                data,
+                levels,
+            });
+            Ok(())
+        })?;
+
+        let header = CheckpointStateHeader {
+            lists: VariableList::new(
+                lists
+                    .iter()
+                    .map(|list| CheckpointStateList {
+                        field_index: list.layout.field_index as u64,
+                        length: list.layout.length as u64,
+                        elements_root: list.layout.elements_root,
+                    })
+                    .collect(),
+            )
+            .map_err(|e| format!("{e:?}"))?,
+            state: VariableList::new(header_state.as_ssz_bytes())
+                .map_err(|e| format!("Header state too large: {e:?}"))?,
+        };
+
+        Ok(Self {
+            block_root,
+            header: header.as_ssz_bytes(),
+            lists,
+            total_chunks,
+        })
+    }
+
+    pub fn block_root(&self) -> Hash256 {
+        self.block_root
+    }
+
+    pub fn total_chunks(&self) -> u64 {
+        self.total_chunks
+    }
+
+    /// Returns the chunk at `index` with its proof, or `None` if the state has no such chunk.
+    pub fn chunk(&self, index: u64) -> Option<CheckpointStateChunk> {
+        let (data, proof) = if index == 0 {
+            (self.header.clone(), vec![])
+        } else {
+            let list = self.lists.iter().find(|list| list.layout.contains(index))?;
+            let index = (index - list.layout.first_chunk) as usize;
+            (
+                list.data[list.layout.chunk_range(index)].to_vec(),
+                list.proof(index),
+            )
+        };
+
+        Some(CheckpointStateChunk {
+            index,
+            total_chunks: self.total_chunks,
+            proof: VariableList::new(proof).ok()?,
+            data: VariableList::new(data).ok()?,
+        })
+    }
+}
+
+/// A state being downloaded in chunks, from its verified header.
+pub struct StateAssembly<E: EthSpec> {
+    /// The state decoded from the header, with the chunked lists left empty.
+    state: BeaconState<E>,
+    lists: Vec<ListLayout>,
+    total_chunks: u64,
+}
+
+impl<E: EthSpec> StateAssembly<E> {
+    /// Decode the header chunk of a state, and check it against the `state_root` of the block.
+    ///
+    /// The state is expected to be split into chunks of at most `chunk_size` bytes.
+    pub fn from_header(
+        chunk: &CheckpointStateChunk,
+        state_root: Hash256,
+        chunk_size: usize,
+        spec: &ChainSpec,
+    ) -> Result<Self, String> {
+        if chunk.index != 0 || !chunk.proof.is_empty() {
+            return Err("Not a header chunk".to_string());
+        }
+        let header =
+            CheckpointStateHeader::from_ssz_bytes(&chunk.data).map_err(|e| format!("{e:?}"))?;
+        let mut state =
+            BeaconState::from_ssz_bytes(&header.state, spec).map_err(|e| format!("{e:?}"))?;
+        let mut field_roots = state
+            .compute_field_proofs()
+            .map_err(|e| format!("{e:?}"))?
+            .into_iter()
+            .map(|(root, _)| root)
+            .collect::<Vec<_>>();
+
+        let mut entries = header.lists.iter();
+        let mut lists = vec![];
+        let mut total_chunks = 1;
+        for_each_chunked_list(&mut state, |field_index, list| {
+            let entry = entries
+                .next()
+                .filter(|entry| entry.field_index == field_index as u64)
+                .ok_or_else(|| format!("Missing list {field_index}"))?;
+            if list.length() != 0 {
+                return Err(format!("List {field_index} is not empty"));
+            }
+            let layout = ListLayout::new(
+                field_index,
+                list,
+                entry.length,
+                entry.elements_root,
+                total_chunks,
+                chunk_size,
+            )?;
+            field_roots[field_index] = layout.field_root();
+            total_chunks += layout.num_chunks();
+            lists.push(layout);
+            Ok(())
+        })?;
+        if entries.next().is_some() {
+            return Err("Unexpected list".to_string());
+        }
+
+        let depth = state.num_fields_pow2().ilog2() as usize;
+        let root = MerkleTree::create(&field_roots, depth).hash();
+        if root != state_root {
+            return Err(format!("State root {root:?} does not match {state_root:?}"));
+        }
+
+        Ok(Self {
+            state,
+            lists,
+            total_chunks,
+        })
+    }
+
+    pub fn total_chunks(&self) -> u64 {
+        self.total_chunks
+    }
+
+    /// Check a chunk of list elements against the root of the elements of its list.
+    pub fn verify_chunk(&self, chunk: &CheckpointStateChunk) -> Result<(), String> {
+        if chunk.total_chunks != self.total_chunks {
+            return Err(format!(
+                "Expected {} chunks, chunk claims {}",
+                self.total_chunks, chunk.total_chunks
+            ));
+        }
+        let layout = self
+            .lists
+            .iter()
+            .find(|layout| layout.contains(chunk.index))
+            .ok_or_else(|| format!("Unexpected chunk {}", chunk.index))?;
+        let index = (chunk.index - layout.first_chunk) as usize;
+        if chunk.data.len() != layout.chunk_range(index).len() {
+            return Err(format!(
+                "Chunk {} has {} bytes, expected {}",
+                chunk.index,
+                chunk.data.len(),
+                layout.chunk_range(index).len()
+            ));
+        }
+
+        let root = (layout.chunk_root)(&chunk.data, 1 << layout.chunk_height)?;
+        if chunk.proof.len() != layout.depth
+            || !verify_merkle_proof(
+                root,
+                &chunk.proof,
+                layout.depth,
+                index,
+                layout.elements_root,
+            )
+        {
+            return Err(format!("Invalid proof for chunk {}", chunk.index));
+        }
+        Ok(())
+    }
+
+    /// Fill the lists of the state with the data of all the verified chunks but the header, in
+    /// order.
+    pub fn assemble(
+        mut self,
+        chunks: impl IntoIterator<Item = Vec<u8>>,
+    ) -> Result<BeaconState<E>, String> {
+        let mut chunks = chunks.into_iter();
+        let mut layouts = self.lists.iter();
+        for_each_chunked_list(&mut self.state, |field_index, list| {
+            let layout = layouts
+                .next()
+                .filter(|layout| layout.field_index == field_index)
+                .ok_or_else(|| format!("Missing list {field_index}"))?;
+            let data = chunks
+                .by_ref()
+                .take(layout.num_chunks() as usize)
+                .flatten()
+                .collect::<Vec<_>>();
+            list.set_elements(&data)
+        })?;
+        Ok(self.state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use beacon_chain::test_utils::{BeaconChainHarness, EphemeralHarnessType};
+    use types::MinimalEthSpec;
+
+    type E = MinimalEthSpec;
+
+    /// Small enough for the validators and balances to span several chunks.
+    const CHUNK_SIZE: usize = 256;
+
+    fn harness() -> BeaconChainHarness<EphemeralHarnessType<E>> {
+        BeaconChainHarness::builder(MinimalEthSpec)
+            .default_spec()
+            .deterministic_keypairs(64)
+            .fresh_ephemeral_store()
+            .build()
+    }
+
+    fn state_chunks(
+        harness: &BeaconChainHarness<EphemeralHarnessType<E>>,
+    ) -> (StateChunks, Hash256) {
+        let mut state = harness.get_current_state();
+        let state_root = state.canonical_root().unwrap();
+        let chunks = StateChunks::with_chunk_size(Hash256::repeat_byte(1), &state, CHUNK_SIZE);
+        (chunks.unwrap(), state_root)
+    }
+
+    fn from_header(
+        chunk: &CheckpointStateChunk,
+        state_root: Hash256,
+        harness: &BeaconChainHarness<EphemeralHarnessType<E>>,
+    ) -> Result<StateAssembly<E>, String> {
+        StateAssembly::from_header(chunk, state_root, CHUNK_SIZE, &harness.spec)
+    }
+
+    #[test]
+    fn all_chunks_verify() {
+        let harness = harness();
+        let (chunks, state_root) = state_chunks(&harness);
+        let assembly = from_header(&chunks.chunk(0).unwrap(), state_root, &harness).unwrap();
+        assert_eq!(assembly.total_chunks(), chunks.total_chunks());
+        // 64 validators of 121 bytes, two per chunk, and 64 balances, 32 per chunk.
+        assert!(chunks.total_chunks() > 32 + 2);
+
+        let data = (1..chunks.total_chunks())
+            .map(|index| {
+                let chunk = chunks.chunk(index).unwrap();
+                assembly.verify_chunk(&chunk).unwrap();
+                chunk.data.to_vec()
+            })
+            .collect::<Vec<_>>();
+        assert!(chunks.chunk(chunks.total_chunks()).is_none());
+
+        let mut state = assembly.assemble(data).unwrap();
+        assert_eq!(state.canonical_root().unwrap(), state_root);
+    }
+
+    #[test]
+    fn header_must_match_state_root() {
+        let harness = harness();
+        let (chunks, state_root) = state_chunks(&harness);
+        let header = chunks.chunk(0).unwrap();
+
+        assert!(from_header(&header, Hash256::repeat_byte(2), &harness).is_err());
+
+        let mut tampered = CheckpointStateHeader::from_ssz_bytes(&header.data).unwrap();
+        tampered.lists[2].length -= 1;
+        let mut chunk = header.clone();
+        chunk.data = VariableList::new(tampered.as_ssz_bytes()).unwrap();
+        assert!(from_header(&chunk, state_root, &harness).is_err());
+
+        let mut tampered = CheckpointStateHeader::from_ssz_bytes(&header.data).unwrap();
+        tampered.lists[2].elements_root = Hash256::repeat_byte(2);
+        let mut chunk = header.clone();
+        chunk.data = VariableList::new(tampered.as_ssz_bytes()).unwrap();
+        assert!(from_header(&chunk, state_root, &harness).is_err());
+
+        let mut chunk = header.clone();
+        chunk.data[100] ^= 1;
+        assert!(from_header(&chunk, state_root, &harness).is_err());
+    }
+
+    #[test]
+    fn tampered_chunks_do_not_verify() {
+        let harness = harness();
+        let (chunks, state_root) = state_chunks(&harness);
+        let assembly = from_header(&chunks.chunk(0).unwrap(), state_root, &harness).unwrap();
+
+        let mut chunk = chunks.chunk(3).unwrap();
+        chunk.data[7] ^= 1;
+        assert!(assembly.verify_chunk(&chunk).is_err());
+
+        let mut chunk = chunks.chunk(3).unwrap();
+        chunk.proof[0] = Hash256::repeat_byte(3);
+        assert!(assembly.verify_chunk(&chunk).is_err());
+
+        let mut chunk = chunks.chunk(3).unwrap();
+        chunk.index = 4;
+        assert!(assembly.verify_chunk(&chunk).is_err());
+
+        let mut chunk = chunks.chunk(3).unwrap();
+        chunk.total_chunks += 1;
+        assert!(assembly.verify_chunk(&chunk).is_err());
+
+        let mut chunk = chunks.chunk(3).unwrap();
+        chunk.index = 0;
+        assert!(assembly.verify_chunk(&chunk).is_err());
+    }
+}
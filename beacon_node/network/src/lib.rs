@@ -1,4 +1,6 @@
 /// This crate provides the network server for Lighthouse.
+pub mod checkpoint_sync;
+pub mod replay;
 pub mod service;
 
 mod metrics;
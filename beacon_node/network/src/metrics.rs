@@ -403,6 +403,41 @@ pub static SYNCING_CHAIN_BATCH_AWAITING_PROCESSING: LazyLock<Result<Histogram>>
             ]),
         )
     });
+pub static SYNC_RANGE_DOWNLOADED_BATCHES: LazyLock<Result<IntCounter>> = LazyLock::new(|| {
+    try_create_int_counter(
+        "sync_range_downloaded_batches_total",
+        "Total count of range sync batches downloaded",
+    )
+});
+pub static SYNC_RANGE_DOWNLOADED_BLOCKS: LazyLock<Result<IntCounter>> = LazyLock::new(|| {
+    try_create_int_counter(
+        "sync_range_downloaded_blocks_total",
+        "Total count of blocks downloaded by range sync",
+    )
+});
+pub static SYNC_RANGE_BATCH_DOWNLOAD_RATE: LazyLock<Result<Histogram>> = LazyLock::new(|| {
+    try_create_histogram_with_buckets(
+        "sync_range_batch_download_rate",
+        "Blocks per second of each range sync batch download",
+        Ok(vec![
+            1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0, 200.0, 500.0, 1000.0,
+        ]),
+    )
+});
+pub static SYNC_RANGE_TOP_PEER_BLOCKS_SHARE: LazyLock<Result<GaugeVec>> = LazyLock::new(|| {
+    try_create_float_gauge_vec(
+        "sync_range_top_peer_blocks_share",
+        "Share of the range sync blocks from connected peers served by the peer at each rank",
+        &["rank"],
+    )
+});
+pub static SYNC_RANGE_HEDGED_BATCHES: LazyLock<Result<IntCounterVec>> = LazyLock::new(|| {
+    try_create_int_counter_vec(
+        "sync_range_hedged_batches_total",
+        "Total count of slow range sync batches requested from a second peer, by outcome",
+        &["outcome"],
+    )
+});
 pub static SYNC_SINGLE_BLOCK_LOOKUPS: LazyLock<Result<IntGauge>> = LazyLock::new(|| {
     try_create_int_gauge(
         "sync_single_block_lookups",
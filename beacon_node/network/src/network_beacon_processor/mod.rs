@@ -1,3 +1,4 @@
+use crate::checkpoint_sync::CheckpointStateCache;
 use crate::sync::manager::BlockProcessType;
 use crate::sync::SamplingId;
 use crate::{service::NetworkMessage, sync::manager::SyncMessage};
@@ -19,8 +20,8 @@ use beacon_processor::{
 };
 use lighthouse_network::discovery::ConnectionId;
 use lighthouse_network::rpc::methods::{
-    BlobsByRangeRequest, BlobsByRootRequest, DataColumnsByRangeRequest, DataColumnsByRootRequest,
-    LightClientUpdatesByRangeRequest,
+    BlobsByRangeRequest, BlobsByRootRequest, CheckpointStateChunksRequest,
+    DataColumnsByRangeRequest, DataColumnsByRootRequest, LightClientUpdatesByRangeRequest,
 };
 use lighthouse_network::rpc::{RequestId, SubstreamId};
 use lighthouse_network::{
@@ -70,6 +71,8 @@ pub struct NetworkBeaconProcessor<T: BeaconChainTypes> {
     pub reprocess_tx: mpsc::Sender<ReprocessQueueMessage>,
     pub network_globals: Arc<NetworkGlobals<T::EthSpec>>,
     pub invalid_block_storage: InvalidBlockStorage,
+    /// The chunked SSZ encoding of the last state served to a peer checkpoint syncing from us.
+    pub checkpoint_state_cache: CheckpointStateCache,
     pub executor: TaskExecutor,
     pub log: Logger,
 }
@@ -447,6 +450,143 @@ impl<T: BeaconChainTypes> NetworkBeaconProcessor<T> {
         })
     }
 
+    /// Create a new `Work` event for any gossip message.
+    ///
+    /// Note: `should_process` is currently only useful for the `Attestation` variant.
+    /// if `should_process` is `false`, we only propagate the message on successful verification,
+    /// else, we propagate **and** import into the beacon chain.
+    pub fn send_gossip_message(
+        self: &Arc<Self>,
+        message_id: MessageId,
+        peer_id: PeerId,
+        peer_client: Client,
+        gossip_message: PubsubMessage<T::EthSpec>,
+        should_process: bool,
+        seen_timestamp: Duration,
+    ) -> Result<(), Error<T::EthSpec>> {
+        match gossip_message {
+            PubsubMessage::AggregateAndProofAttestation(aggregate_and_proof) => self
+                .send_aggregated_attestation(
+                    message_id,
+                    peer_id,
+                    *aggregate_and_proof,
+                    seen_timestamp,
+                ),
+            PubsubMessage::Attestation(subnet_attestation) => self.send_unaggregated_attestation(
+                message_id,
+                peer_id,
+                subnet_attestation.1,
+                subnet_attestation.0,
+                should_process,
+                seen_timestamp,
+            ),
+            PubsubMessage::BeaconBlock(block) => self.send_gossip_beacon_block(
+                message_id,
+                peer_id,
+                peer_client,
+                block,
+                seen_timestamp,
+            ),
+            PubsubMessage::BlobSidecar(data) => {
+                let (blob_index, blob_sidecar) = *data;
+                self.send_gossip_blob_sidecar(
+                    message_id,
+                    peer_id,
+                    peer_client,
+                    blob_index,
+                    blob_sidecar,
+                    seen_timestamp,
+                )
+            }
+            PubsubMessage::DataColumnSidecar(data) => {
+                let (subnet_id, column_sidecar) = *data;
+                self.send_gossip_data_column_sidecar(
+                    message_id,
+                    peer_id,
+                    peer_client,
+                    subnet_id,
+                    column_sidecar,
+                    seen_timestamp,
+                )
+            }
+            PubsubMessage::VoluntaryExit(exit) => {
+                debug!(self.log, "Received a voluntary exit"; "peer_id" => %peer_id);
+                self.send_gossip_voluntary_exit(message_id, peer_id, exit)
+            }
+            PubsubMessage::ProposerSlashing(proposer_slashing) => {
+                debug!(
+                    self.log,
+                    "Received a proposer slashing";
+                    "peer_id" => %peer_id
+                );
+                self.send_gossip_proposer_slashing(message_id, peer_id, proposer_slashing)
+            }
+            PubsubMessage::AttesterSlashing(attester_slashing) => {
+                debug!(
+                    self.log,
+                    "Received a attester slashing";
+                    "peer_id" => %peer_id
+                );
+                self.send_gossip_attester_slashing(message_id, peer_id, attester_slashing)
+            }
+            PubsubMessage::SignedContributionAndProof(contribution_and_proof) => {
+                trace!(
+                    self.log,
+                    "Received sync committee aggregate";
+                    "peer_id" => %peer_id
+                );
+                self.send_gossip_sync_contribution(
+                    message_id,
+                    peer_id,
+                    *contribution_and_proof,
+                    seen_timestamp,
+                )
+            }
+            PubsubMessage::SyncCommitteeMessage(sync_committtee_msg) => {
+                trace!(
+                    self.log,
+                    "Received sync committee signature";
+                    "peer_id" => %peer_id
+                );
+                self.send_gossip_sync_signature(
+                    message_id,
+                    peer_id,
+                    sync_committtee_msg.1,
+                    sync_committtee_msg.0,
+                    seen_timestamp,
+                )
+            }
+            PubsubMessage::LightClientFinalityUpdate(light_client_finality_update) => {
+                trace!(
+                    self.log,
+                    "Received light client finality update";
+                    "peer_id" => %peer_id
+                );
+                self.send_gossip_light_client_finality_update(
+                    message_id,
+                    peer_id,
+                    *light_client_finality_update,
+                    seen_timestamp,
+                )
+            }
+            PubsubMessage::LightClientOptimisticUpdate(light_client_optimistic_update) => {
+                trace!(
+                    self.log,
+                    "Received light client optimistic update";
+                    "peer_id" => %peer_id
+                );
+                self.send_gossip_light_client_optimistic_update(
+                    message_id,
+                    peer_id,
+                    *light_client_optimistic_update,
+                    seen_timestamp,
+                )
+            }
+            PubsubMessage::BlsToExecutionChange(bls_to_execution_change) => self
+                .send_gossip_bls_to_execution_change(message_id, peer_id, bls_to_execution_change),
+        }
+    }
+
     /// Create a new `Work` event for some block, where the result from computation (if any) is
     /// sent to the other side of `result_tx`.
     pub fn send_rpc_beacon_block(
@@ -868,6 +1008,32 @@ impl<T: BeaconChainTypes> NetworkBeaconProcessor<T> {
         })
     }
 
+    /// Create a new work event to process a `CheckpointStateChunks` request from the RPC network.
+    pub fn send_checkpoint_state_chunks_request(
+        self: &Arc<Self>,
+        peer_id: PeerId,
+        connection_id: ConnectionId,
+        substream_id: SubstreamId,
+        request_id: RequestId,
+        request: CheckpointStateChunksRequest,
+    ) -> Result<(), Error<T::EthSpec>> {
+        let processor = self.clone();
+        let process_fn = move || {
+            processor.handle_checkpoint_state_chunks_request(
+                peer_id,
+                connection_id,
+                substream_id,
+                request_id,
+                request,
+            )
+        };
+
+        self.try_send(BeaconWorkEvent {
+            drop_during_sync: true,
+            work: Work::CheckpointStateChunksRequest(Box::new(process_fn)),
+        })
+    }
+
     /// Send a message to `sync_tx`.
     ///
     /// Creates a log if there is an internal error.
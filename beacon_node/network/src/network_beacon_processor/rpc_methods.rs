@@ -1,3 +1,4 @@
+use crate::checkpoint_sync::StateChunks;
 use crate::network_beacon_processor::{NetworkBeaconProcessor, FUTURE_SLOT_TOLERANCE};
 use crate::service::NetworkMessage;
 use crate::status::ToStatusMessage;
@@ -6,7 +7,8 @@ use beacon_chain::{BeaconChainError, BeaconChainTypes, WhenSlotSkipped};
 use itertools::process_results;
 use lighthouse_network::discovery::ConnectionId;
 use lighthouse_network::rpc::methods::{
-    BlobsByRangeRequest, BlobsByRootRequest, DataColumnsByRangeRequest, DataColumnsByRootRequest,
+    BlobsByRangeRequest, BlobsByRootRequest, CheckpointStateChunksRequest,
+    DataColumnsByRangeRequest, DataColumnsByRootRequest, MAX_REQUEST_CHECKPOINT_STATE_CHUNKS,
 };
 use lighthouse_network::rpc::*;
 use lighthouse_network::{PeerId, PeerRequestId, ReportSource, Response, SyncInfo};
@@ -1255,6 +1257,186 @@ impl<T: BeaconChainTypes> NetworkBeaconProcessor<T> {
         Ok(())
     }
 
+    /// Handle a `CheckpointStateChunks` request from the peer.
+    pub fn handle_checkpoint_state_chunks_request(
+        &self,
+        peer_id: PeerId,
+        connection_id: ConnectionId,
+        substream_id: SubstreamId,
+        request_id: RequestId,
+        req: CheckpointStateChunksRequest,
+    ) {
+        self.terminate_response_stream(
+            peer_id,
+            connection_id,
+            substream_id,
+            request_id,
+            self.handle_checkpoint_state_chunks_request_inner(
+                peer_id,
+                connection_id,
+                substream_id,
+                request_id,
+                req,
+            ),
+            Response::CheckpointStateChunks,
+        );
+    }
+
+    /// Handle a `CheckpointStateChunks` request from the peer.
+    pub fn handle_checkpoint_state_chunks_request_inner(
+        &self,
+        peer_id: PeerId,
+        connection_id: ConnectionId,
+        substream_id: SubstreamId,
+        request_id: RequestId,
+        req: CheckpointStateChunksRequest,
+    ) -> Result<(), (RpcErrorResponse, &'static str)> {
+        debug!(self.log, "Received CheckpointStateChunks Request";
+            "peer_id" => %peer_id,
+            "block_root" => ?req.block_root,
+            "start_chunk" => req.start_chunk,
+            "count" => req.count,
+        );
+
+        if req.count == 0 || req.count > MAX_REQUEST_CHECKPOINT_STATE_CHUNKS {
+            return Err((
+                RpcErrorResponse::InvalidRequest,
+                "Request exceeded `MAX_REQUEST_CHECKPOINT_STATE_CHUNKS`",
+            ));
+        }
+
+        // The cached state is always that of a finalized checkpoint, which remains so.
+        let cached = self
+            .checkpoint_state_cache
+            .lock()
+            .as_ref()
+            .filter(|state_chunks| state_chunks.block_root() == req.block_root)
+            .cloned();
+        let state_chunks = match cached {
+            Some(state_chunks) => state_chunks,
+            None => {
+                // Chunk the state without holding the lock, so that requests for the cached
+                // state aren't blocked. Concurrent requests may chunk the state more than once
+                // when a new checkpoint is requested, which is rare and harmless.
+                let state_chunks = Arc::new(self.load_checkpoint_state_chunks(req.block_root)?);
+                *self.checkpoint_state_cache.lock() = Some(state_chunks.clone());
+                state_chunks
+            }
+        };
+
+        let end_chunk = std::cmp::min(
+            req.start_chunk.saturating_add(req.count),
+            state_chunks.total_chunks(),
+        );
+        let mut chunks_sent = 0;
+        for index in req.start_chunk..end_chunk {
+            let Some(chunk) = state_chunks.chunk(index) else {
+                error!(self.log, "Unable to build checkpoint state chunk";
+                    "block_root" => ?req.block_root,
+                    "index" => index,
+                );
+                return Err((RpcErrorResponse::ServerError, "Failed building state chunk"));
+            };
+            chunks_sent += 1;
+            self.send_network_message(NetworkMessage::SendResponse {
+                peer_id,
+                request_id,
+                response: Response::CheckpointStateChunks(Some(Arc::new(chunk))),
+                id: (connection_id, substream_id),
+            });
+        }
+
+        debug!(self.log, "CheckpointStateChunks outgoing response processed";
+            "peer" => %peer_id,
+            "block_root" => ?req.block_root,
+            "start_chunk" => req.start_chunk,
+            "sent" => chunks_sent,
+            "total_chunks" => state_chunks.total_chunks(),
+        );
+
+        Ok(())
+    }
+
+    /// Load and chunk the state of the block with `block_root`, which must be the block of a
+    /// finalized checkpoint.
+    ///
+    /// Only checkpoint states are served, so that peers can't make us load and chunk arbitrary
+    /// states. Checkpoints prior to the latest finalized checkpoint are served as long as their
+    /// state is held, as the checkpoint a node syncs from is usually behind the finality of its
+    /// peers, and finality may advance during the download.
+    fn load_checkpoint_state_chunks(
+        &self,
+        block_root: Hash256,
+    ) -> Result<StateChunks, (RpcErrorResponse, &'static str)> {
+        let block = match self.chain.get_blinded_block(&block_root) {
+            Ok(Some(block)) => block,
+            Ok(None) => {
+                return Err((RpcErrorResponse::ResourceUnavailable, "Unknown block root"));
+            }
+            Err(e) => {
+                error!(self.log, "Error loading checkpoint block";
+                    "block_root" => ?block_root,
+                    "error" => ?e,
+                );
+                return Err((RpcErrorResponse::ServerError, "Failed loading block"));
+            }
+        };
+
+        // The block of a checkpoint is the latest block at or before the first slot of its epoch.
+        let slots_per_epoch = T::EthSpec::slots_per_epoch();
+        let block_epoch = block.slot().epoch(slots_per_epoch);
+        let checkpoint_epoch = if block.slot() == block_epoch.start_slot(slots_per_epoch) {
+            block_epoch
+        } else {
+            block_epoch + 1
+        };
+        let finalized_epoch = self
+            .chain
+            .canonical_head
+            .cached_head()
+            .finalized_checkpoint()
+            .epoch;
+        if checkpoint_epoch > finalized_epoch
+            || !matches!(
+                self.chain.block_root_at_slot(
+                    checkpoint_epoch.start_slot(slots_per_epoch),
+                    WhenSlotSkipped::Prev
+                ),
+                Ok(Some(root)) if root == block_root
+            )
+        {
+            return Err((
+                RpcErrorResponse::ResourceUnavailable,
+                "Block is not a finalized checkpoint",
+            ));
+        }
+
+        let state = match self
+            .chain
+            .get_state(&block.state_root(), Some(block.slot()))
+        {
+            Ok(Some(state)) => state,
+            Ok(None) => {
+                return Err((RpcErrorResponse::ResourceUnavailable, "State not available"));
+            }
+            Err(e) => {
+                error!(self.log, "Error loading checkpoint state";
+                    "block_root" => ?block_root,
+                    "error" => ?e,
+                );
+                return Err((RpcErrorResponse::ServerError, "Failed loading state"));
+            }
+        };
+
+        StateChunks::new(block_root, &state).map_err(|e| {
+            error!(self.log, "Unable to chunk checkpoint state";
+                "block_root" => ?block_root,
+                "error" => e,
+            );
+            (RpcErrorResponse::ServerError, "Failed chunking state")
+        })
+    }
+
     /// Helper function to ensure single item protocol always end with either a single chunk or an
     /// error
     fn terminate_response_single_item<R, F: Fn(R) -> Response<T::EthSpec>>(
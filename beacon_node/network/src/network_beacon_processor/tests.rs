@@ -2,6 +2,7 @@
 #![cfg(test)]
 
 use crate::{
+    checkpoint_sync::StateAssembly,
     network_beacon_processor::{
         ChainSegmentProcessId, DuplicateCache, InvalidBlockStorage, NetworkBeaconProcessor,
     },
@@ -15,7 +16,10 @@ use beacon_chain::test_utils::{
 use beacon_chain::{BeaconChain, WhenSlotSkipped};
 use beacon_processor::{work_reprocessing_queue::*, *};
 use lighthouse_network::discovery::ConnectionId;
-use lighthouse_network::rpc::methods::BlobsByRangeRequest;
+use lighthouse_network::rpc::methods::{
+    BlobsByRangeRequest, CheckpointStateChunksRequest, RpcErrorResponse,
+    CHECKPOINT_STATE_CHUNK_SIZE, MAX_REQUEST_CHECKPOINT_STATE_CHUNKS,
+};
 use lighthouse_network::rpc::{RequestId, SubstreamId};
 use lighthouse_network::{
     discv5::enr::{self, CombinedKey},
@@ -230,6 +234,7 @@ impl TestRig {
             reprocess_tx: work_reprocessing_tx.clone(),
             network_globals: network_globals.clone(),
             invalid_block_storage: InvalidBlockStorage::Disabled,
+            checkpoint_state_cache: <_>::default(),
             executor: executor.clone(),
             log: log.clone(),
         };
@@ -371,6 +376,27 @@ impl TestRig {
             .unwrap();
     }
 
+    pub fn enqueue_checkpoint_state_chunks_request(
+        &self,
+        block_root: Hash256,
+        start_chunk: u64,
+        count: u64,
+    ) {
+        self.network_beacon_processor
+            .send_checkpoint_state_chunks_request(
+                PeerId::random(),
+                ConnectionId::new_unchecked(42),
+                SubstreamId::new(24),
+                RequestId::new_unchecked(0),
+                CheckpointStateChunksRequest {
+                    block_root,
+                    start_chunk,
+                    count,
+                },
+            )
+            .unwrap();
+    }
+
     pub fn enqueue_backfill_batch(&self) {
         self.network_beacon_processor
             .send_chain_segment(
@@ -1153,3 +1179,110 @@ async fn test_blobs_by_range() {
     }
     assert_eq!(blob_count, actual_count);
 }
+
+/// Request all the state chunks of the checkpoint with `block_root`, returning the verified
+/// assembly of the chunks, or `None` if the request was rejected.
+async fn request_checkpoint_state(
+    rig: &mut TestRig,
+    block_root: Hash256,
+) -> Option<StateAssembly<E>> {
+    let block = rig.chain.get_blinded_block(&block_root).unwrap().unwrap();
+    rig.enqueue_checkpoint_state_chunks_request(block_root, 0, MAX_REQUEST_CHECKPOINT_STATE_CHUNKS);
+
+    let mut assembly = None;
+    while let Some(next) = rig._network_rx.recv().await {
+        match next {
+            NetworkMessage::SendResponse {
+                response: Response::CheckpointStateChunks(chunk),
+                ..
+            } => {
+                let Some(chunk) = chunk else {
+                    break;
+                };
+                match &assembly {
+                    None => {
+                        assembly = Some(
+                            StateAssembly::<E>::from_header(
+                                &chunk,
+                                block.state_root(),
+                                CHECKPOINT_STATE_CHUNK_SIZE,
+                                &rig.chain.spec,
+                            )
+                            .unwrap(),
+                        );
+                    }
+                    Some(assembly) => assembly.verify_chunk(&chunk).unwrap(),
+                }
+            }
+            NetworkMessage::SendErrorResponse {
+                error: RpcErrorResponse::ResourceUnavailable,
+                ..
+            } => return None,
+            next => panic!("unexpected message {:?}", next),
+        }
+    }
+    assert!(assembly.is_some(), "header should be served");
+    assembly
+}
+
+#[tokio::test]
+async fn test_checkpoint_state_chunks() {
+    let mut rig = TestRig::new(SLOTS_PER_EPOCH * 4).await;
+    let finalized_checkpoint = rig.chain.head().finalized_checkpoint();
+    assert!(
+        finalized_checkpoint.epoch > 0,
+        "precondition: chain has finalized"
+    );
+    assert!(
+        request_checkpoint_state(&mut rig, finalized_checkpoint.root)
+            .await
+            .is_some()
+    );
+}
+
+#[tokio::test]
+async fn test_checkpoint_state_chunks_prior_checkpoint() {
+    let mut rig = TestRig::new(SLOTS_PER_EPOCH * 6).await;
+    let finalized_checkpoint = rig.chain.head().finalized_checkpoint();
+    assert!(
+        finalized_checkpoint.epoch > 1,
+        "precondition: chain has finalized past the requested checkpoint"
+    );
+
+    // The finality of the server is past the requested checkpoint.
+    let checkpoint_slot = (finalized_checkpoint.epoch - 1).start_slot(SLOTS_PER_EPOCH);
+    let checkpoint_root = rig
+        .chain
+        .block_root_at_slot(checkpoint_slot, WhenSlotSkipped::Prev)
+        .unwrap()
+        .unwrap();
+    assert_ne!(checkpoint_root, finalized_checkpoint.root);
+    assert!(request_checkpoint_state(&mut rig, checkpoint_root)
+        .await
+        .is_some());
+
+    // Finalized blocks which are not checkpoints are not served.
+    let block_root = rig
+        .chain
+        .block_root_at_slot(checkpoint_slot + 1, WhenSlotSkipped::None)
+        .unwrap()
+        .unwrap();
+    assert!(request_checkpoint_state(&mut rig, block_root)
+        .await
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_checkpoint_state_chunks_not_finalized() {
+    let mut rig = TestRig::new(SLOTS_PER_EPOCH * 4).await;
+    let head_root = rig.chain.head().head_block_root();
+    rig.enqueue_checkpoint_state_chunks_request(head_root, 0, 1);
+
+    match rig._network_rx.recv().await {
+        Some(NetworkMessage::SendErrorResponse {
+            error: RpcErrorResponse::ResourceUnavailable,
+            ..
+        }) => {}
+        next => panic!("unexpected message {:?}", next),
+    }
+}
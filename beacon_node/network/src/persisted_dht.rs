@@ -1,4 +1,6 @@
+use lighthouse_network::peer_manager::peerdb::persisted::PersistedPeers;
 use lighthouse_network::Enr;
+use ssz::{Decode, Encode};
 use std::sync::Arc;
 use store::{DBColumn, Error as StoreError, HotColdDB, ItemStore, StoreItem};
 use types::{EthSpec, Hash256};
@@ -6,6 +8,9 @@ use types::{EthSpec, Hash256};
 /// 32-byte key for accessing the `DhtEnrs`. All zero because `DhtEnrs` has its own column.
 pub const DHT_DB_KEY: Hash256 = Hash256::ZERO;
 
+/// 32-byte key for accessing the `PeerDb`. All zero because `PeerDb` has its own column.
+pub const PEER_DB_KEY: Hash256 = Hash256::ZERO;
+
 pub fn load_dht<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
     store: Arc<HotColdDB<E, Hot, Cold>>,
 ) -> Vec<Enr> {
@@ -34,6 +39,23 @@ pub fn clear_dht<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
     store.hot_db.delete::<PersistedDht>(&DHT_DB_KEY)
 }
 
+/// Load the reputation of the peers known to the previous run, if any.
+pub fn load_peer_db<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
+    store: Arc<HotColdDB<E, Hot, Cold>>,
+) -> Result<Option<PersistedPeers>, store::Error> {
+    Ok(store
+        .get_item::<PersistedPeerDb>(&PEER_DB_KEY)?
+        .map(|p| p.peers))
+}
+
+/// Attempt to persist the reputation of known peers to `self.store`.
+pub fn persist_peer_db<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
+    store: Arc<HotColdDB<E, Hot, Cold>>,
+    peers: PersistedPeers,
+) -> Result<(), store::Error> {
+    store.put_item(&PEER_DB_KEY, &PersistedPeerDb { peers })
+}
+
 /// Wrapper around DHT for persistence to disk.
 pub struct PersistedDht {
     pub enrs: Vec<Enr>,
@@ -66,9 +88,31 @@ impl StoreItem for PersistedDht {
     }
 }
 
+/// Wrapper around the reputation of peers for persistence to disk.
+pub struct PersistedPeerDb {
+    pub peers: PersistedPeers,
+}
+
+impl StoreItem for PersistedPeerDb {
+    fn db_column() -> DBColumn {
+        DBColumn::PeerDb
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.peers.as_ssz_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, StoreError> {
+        Ok(PersistedPeerDb {
+            peers: PersistedPeers::from_ssz_bytes(bytes)?,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use lighthouse_network::peer_manager::peerdb::persisted::PersistedPeer;
     use sloggers::{null::NullLoggerBuilder, Build};
     use std::str::FromStr;
     use store::config::StoreConfig;
@@ -90,4 +134,34 @@ mod tests {
         let dht: PersistedDht = store.get_item(&DHT_DB_KEY).unwrap().unwrap();
         assert_eq!(dht.enrs, enrs);
     }
+
+    #[test]
+    fn test_persisted_peer_db() {
+        let log = NullLoggerBuilder.build().unwrap();
+        let store: HotColdDB<
+            MinimalEthSpec,
+            MemoryStore<MinimalEthSpec>,
+            MemoryStore<MinimalEthSpec>,
+        > = HotColdDB::open_ephemeral(StoreConfig::default(), ChainSpec::minimal().into(), log)
+            .unwrap();
+        let store = Arc::new(store);
+        assert_eq!(load_peer_db(store.clone()).unwrap(), None);
+
+        let peers = PersistedPeers {
+            persisted_at: 1_700_000_000,
+            peers: vec![PersistedPeer {
+                peer_id: lighthouse_network::PeerId::random().to_bytes(),
+                score: (-75.5f64).to_bits(),
+                decay_delay_secs: 3600,
+                agent_version: Some(b"Lighthouse/v6.0.0/x86_64-linux".to_vec()),
+                protocol_version: b"eth2/1.0.0".to_vec(),
+                latency_ms: Some(42),
+                useful_for_sync: true,
+                enr: None,
+                seen_multiaddrs: vec![],
+            }],
+        };
+        persist_peer_db(store.clone(), peers.clone()).unwrap();
+        assert_eq!(load_peer_db(store).unwrap(), Some(peers));
+    }
 }
@@ -0,0 +1,570 @@
+//! Replays a network capture recorded by `lighthouse_network::capture` into a
+//! `NetworkBeaconProcessor`.
+//!
+//! Gossip messages are submitted one at a time, with the slot clock set to the time at which each
+//! message arrived. After submitting a message the replay waits (up to a timeout) for its
+//! validation result before moving on, which makes the outcomes independent of the scheduling of
+//! the beacon processor workers. The outcomes are compared against the validation results which
+//! were recorded in the capture.
+//!
+//! Blocks and blobs received via RPC are submitted for processing in the same way as they would be
+//! by single block lookups. Other RPC responses are skipped.
+
+use crate::network_beacon_processor::{InvalidBlockStorage, NetworkBeaconProcessor};
+use crate::service::NetworkMessage;
+use crate::sync::manager::{BlockProcessType, SyncMessage};
+use beacon_chain::block_verification_types::RpcBlock;
+use beacon_chain::{BeaconChain, BeaconChainTypes};
+use beacon_processor::{
+    BeaconProcessor, BeaconProcessorChannels, BeaconProcessorConfig, BeaconProcessorQueueLengths,
+    DuplicateCache,
+};
+use lighthouse_network::capture::{CaptureRecord, GossipRecord, RpcResponseRecord};
+use lighthouse_network::rpc::Protocol;
+use lighthouse_network::types::SyncState;
+use lighthouse_network::{
+    Client, MessageAcceptance, MessageId, NetworkConfig, NetworkGlobals, PubsubMessage,
+};
+use slog::{debug, info, warn, Logger};
+use slot_clock::ManualSlotClock;
+use ssz::Decode;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use task_executor::TaskExecutor;
+use tokio::sync::mpsc;
+use tokio::time::{timeout_at, Instant};
+use types::blob_sidecar::FixedBlobSidecarList;
+use types::{BlobSidecar, ForkContext, SignedBeaconBlock};
+
+/// The default time to wait for the result of each message before moving on to the next.
+pub const DEFAULT_MESSAGE_TIMEOUT: Duration = Duration::from_secs(1);
+/// The default time to wait for results after the last message, which allows messages queued for
+/// reprocessing (e.g. attestations to unknown blocks) to complete.
+pub const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone)]
+pub struct ReplayConfig {
+    pub message_timeout: Duration,
+    pub drain_timeout: Duration,
+    pub beacon_processor: BeaconProcessorConfig,
+}
+
+impl Default for ReplayConfig {
+    fn default() -> Self {
+        Self {
+            message_timeout: DEFAULT_MESSAGE_TIMEOUT,
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+            beacon_processor: BeaconProcessorConfig::default(),
+        }
+    }
+}
+
+/// The outcome of replaying a single gossip message.
+#[derive(Debug, Clone)]
+pub struct GossipOutcome {
+    pub arrival: Duration,
+    pub message_id: MessageId,
+    pub topic: String,
+    /// The validation result recorded in the capture, if any.
+    pub captured: Option<MessageAcceptance>,
+    /// The validation result produced by the replay, if any.
+    pub replayed: Option<MessageAcceptance>,
+}
+
+impl GossipOutcome {
+    pub fn is_match(&self) -> bool {
+        self.captured == self.replayed
+    }
+}
+
+/// The outcome of replaying a single RPC response.
+#[derive(Debug, Clone)]
+pub struct RpcOutcome {
+    pub arrival: Duration,
+    pub protocol: String,
+    /// The `Debug` representation of the processing result, or `None` if the response was not
+    /// processed within the timeout.
+    pub result: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct ReplaySummary {
+    pub gossip: Vec<GossipOutcome>,
+    pub rpc: Vec<RpcOutcome>,
+    /// The number of RPC responses which were not replayed because of their protocol.
+    pub rpc_skipped: usize,
+    /// Records which could not be read or decoded.
+    pub errors: Vec<String>,
+}
+
+impl ReplaySummary {
+    /// Returns the gossip messages with a replayed result which differs from the captured result.
+    pub fn mismatches(&self) -> impl Iterator<Item = &GossipOutcome> {
+        self.gossip.iter().filter(|outcome| !outcome.is_match())
+    }
+}
+
+/// Drives a `NetworkBeaconProcessor` with the records of a capture.
+pub struct Replay<T: BeaconChainTypes<SlotClock = ManualSlotClock>> {
+    chain: Arc<BeaconChain<T>>,
+    network_beacon_processor: Arc<NetworkBeaconProcessor<T>>,
+    network_rx: mpsc::UnboundedReceiver<NetworkMessage<T::EthSpec>>,
+    sync_rx: mpsc::UnboundedReceiver<SyncMessage<T::EthSpec>>,
+    fork_context: ForkContext,
+    config: ReplayConfig,
+    /// The replayed validation results which have been received, by message id.
+    results: HashMap<MessageId, MessageAcceptance>,
+    /// The RPC processing results which have been received, by request id.
+    rpc_results: HashMap<u32, String>,
+    next_rpc_id: u32,
+    log: Logger,
+}
+
+impl<T: BeaconChainTypes<SlotClock = ManualSlotClock>> Replay<T> {
+    /// Start a beacon processor for `chain`.
+    ///
+    /// Messages are processed as though the node is synced, so that no messages are dropped.
+    pub fn new(
+        chain: Arc<BeaconChain<T>>,
+        config: ReplayConfig,
+        executor: TaskExecutor,
+        log: Logger,
+    ) -> Result<Self, String> {
+        let (network_tx, network_rx) = mpsc::unbounded_channel();
+        let (sync_tx, sync_rx) = mpsc::unbounded_channel();
+
+        let BeaconProcessorChannels {
+            beacon_processor_tx,
+            beacon_processor_rx,
+            work_reprocessing_tx,
+            work_reprocessing_rx,
+        } = BeaconProcessorChannels::new(&config.beacon_processor);
+
+        let network_globals = Arc::new(NetworkGlobals::new_test_globals(
+            vec![],
+            &log,
+            Arc::new(NetworkConfig::default()),
+            chain.spec.clone(),
+        ));
+        network_globals.set_sync_state(SyncState::Synced);
+
+        let network_beacon_processor = Arc::new(NetworkBeaconProcessor {
+            beacon_processor_send: beacon_processor_tx,
+            duplicate_cache: DuplicateCache::default(),
+            chain: chain.clone(),
+            network_tx,
+            sync_tx,
+            reprocess_tx: work_reprocessing_tx.clone(),
+            network_globals: network_globals.clone(),
+            invalid_block_storage: InvalidBlockStorage::Disabled,
+            checkpoint_state_cache: <_>::default(),
+            executor: executor.clone(),
+            log: log.clone(),
+        });
+
+        let queue_lengths = BeaconProcessorQueueLengths::from_state(
+            &chain.canonical_head.cached_head().snapshot.beacon_state,
+            &chain.spec,
+        )
+        .map_err(|e| format!("Unable to compute queue lengths: {e:?}"))?;
+
+        BeaconProcessor {
+            network_globals,
+            executor,
+            current_workers: 0,
+            config: config.beacon_processor.clone(),
+            log: log.clone(),
+        }
+        .spawn_manager(
+            beacon_processor_rx,
+            work_reprocessing_tx,
+            work_reprocessing_rx,
+            None,
+            chain.slot_clock.clone(),
+            chain.spec.maximum_gossip_clock_disparity(),
+            queue_lengths,
+        )?;
+
+        let current_slot = chain
+            .slot()
+            .map_err(|e| format!("Unable to read slot: {e:?}"))?;
+        let fork_context = ForkContext::new::<T::EthSpec>(
+            current_slot,
+            chain.genesis_validators_root,
+            &chain.spec,
+        );
+
+        Ok(Self {
+            chain,
+            network_beacon_processor,
+            network_rx,
+            sync_rx,
+            fork_context,
+            config,
+            results: HashMap::new(),
+            rpc_results: HashMap::new(),
+            next_rpc_id: 0,
+            log,
+        })
+    }
+
+    /// Replay `records` in order.
+    pub async fn run(
+        mut self,
+        records: impl Iterator<Item = Result<CaptureRecord, String>>,
+    ) -> ReplaySummary {
+        let mut summary = ReplaySummary::default();
+        let mut captured = HashMap::new();
+
+        for record in records {
+            let record = match record {
+                Ok(record) => record,
+                Err(e) => {
+                    warn!(self.log, "Unable to read capture record"; "error" => &e);
+                    summary.errors.push(e);
+                    continue;
+                }
+            };
+            self.chain.slot_clock.set_current_time(record.timestamp());
+
+            let result = match record {
+                CaptureRecord::Gossip(record) => self
+                    .replay_gossip(&record)
+                    .await
+                    .map(|outcome| summary.gossip.push(outcome)),
+                CaptureRecord::Validation(record) => {
+                    // Only the first result is relevant, gossipsub ignores any others.
+                    if let Some(acceptance) = record.acceptance() {
+                        captured.entry(record.message_id()).or_insert(acceptance);
+                    }
+                    Ok(())
+                }
+                CaptureRecord::RpcResponse(record) => {
+                    self.replay_rpc_response(&record).await.map(|outcome| {
+                        if let Some(outcome) = outcome {
+                            summary.rpc.push(outcome);
+                        } else {
+                            summary.rpc_skipped += 1;
+                        }
+                    })
+                }
+            };
+            if let Err(e) = result {
+                debug!(self.log, "Unable to replay capture record"; "error" => &e);
+                summary.errors.push(e);
+            }
+        }
+
+        // Allow time for messages which were queued for reprocessing.
+        let deadline = Instant::now() + self.config.drain_timeout;
+        while summary
+            .gossip
+            .iter()
+            .any(|outcome| !self.results.contains_key(&outcome.message_id))
+        {
+            if !self.recv_result(deadline).await {
+                break;
+            }
+        }
+
+        for outcome in &mut summary.gossip {
+            outcome.captured = captured.get(&outcome.message_id).copied();
+            outcome.replayed = self.results.get(&outcome.message_id).copied();
+        }
+        let rpc_results = std::mem::take(&mut self.rpc_results);
+        for (id, outcome) in summary.rpc.iter_mut().enumerate() {
+            if outcome.result.is_none() {
+                outcome.result = rpc_results.get(&(id as u32)).cloned();
+            }
+        }
+
+        info!(
+            self.log,
+            "Finished replaying capture";
+            "gossip_messages" => summary.gossip.len(),
+            "mismatches" => summary.mismatches().count(),
+            "rpc_responses" => summary.rpc.len(),
+            "errors" => summary.errors.len(),
+        );
+        summary
+    }
+
+    async fn replay_gossip(&mut self, record: &GossipRecord) -> Result<GossipOutcome, String> {
+        let message_id = record.message_id();
+        let topic = record.topic()?;
+        let data = record.decompressed_data()?;
+        let message = PubsubMessage::decode(&topic, &data, &self.fork_context)?;
+
+        self.network_beacon_processor
+            .send_gossip_message(
+                message_id.clone(),
+                record.peer_id()?,
+                Client::default(),
+                message,
+                true,
+                record_time(record.arrival_ms),
+            )
+            .map_err(|e| format!("Unable to send to beacon processor: {e}"))?;
+
+        let deadline = Instant::now() + self.config.message_timeout;
+        while !self.results.contains_key(&message_id) {
+            if !self.recv_result(deadline).await {
+                break;
+            }
+        }
+
+        Ok(GossipOutcome {
+            arrival: record_time(record.arrival_ms),
+            message_id,
+            topic: topic.into_string(),
+            captured: None,
+            replayed: None,
+        })
+    }
+
+    /// Replay blocks and blobs, returning `None` for responses of any other protocol.
+    ///
+    /// The RPC outcomes are indexed by the order of the replayed responses, which is also used as
+    /// the id of their requests.
+    async fn replay_rpc_response(
+        &mut self,
+        record: &RpcResponseRecord,
+    ) -> Result<Option<RpcOutcome>, String> {
+        let protocol = record.protocol()?;
+        let data = record.decompressed_data()?;
+        let arrival = record_time(record.arrival_ms);
+        let id = self.next_rpc_id;
+
+        let sent = if protocol == Protocol::BlocksByRange.as_ref()
+            || protocol == Protocol::BlocksByRoot.as_ref()
+        {
+            let block = Arc::new(
+                SignedBeaconBlock::from_ssz_bytes(&data, &self.chain.spec)
+                    .map_err(|e| format!("Unable to decode block: {e:?}"))?,
+            );
+            let block_root = block.canonical_root();
+            self.network_beacon_processor.send_rpc_beacon_block(
+                block_root,
+                RpcBlock::new_without_blobs(Some(block_root), block),
+                arrival,
+                BlockProcessType::SingleBlock { id },
+            )
+        } else if protocol == Protocol::BlobsByRange.as_ref()
+            || protocol == Protocol::BlobsByRoot.as_ref()
+        {
+            let blob = Arc::new(
+                BlobSidecar::from_ssz_bytes(&data)
+                    .map_err(|e| format!("Unable to decode blob: {e:?}"))?,
+            );
+            let block_root = blob.block_root();
+            let mut blobs = FixedBlobSidecarList::default();
+            *blobs
+                .get_mut(blob.index as usize)
+                .ok_or_else(|| format!("Invalid blob index: {}", blob.index))? = Some(blob);
+            self.network_beacon_processor.send_rpc_blobs(
+                block_root,
+                blobs,
+                arrival,
+                BlockProcessType::SingleBlob { id },
+            )
+        } else {
+            return Ok(None);
+        };
+        sent.map_err(|e| format!("Unable to send to beacon processor: {e}"))?;
+        self.next_rpc_id += 1;
+
+        let deadline = Instant::now() + self.config.message_timeout;
+        while !self.rpc_results.contains_key(&id) {
+            if !self.recv_result(deadline).await {
+                break;
+            }
+        }
+
+        Ok(Some(RpcOutcome {
+            arrival,
+            protocol,
+            result: self.rpc_results.get(&id).cloned(),
+        }))
+    }
+
+    /// Wait for a single result from the beacon processor, returning `false` once `deadline` has
+    /// passed.
+    async fn recv_result(&mut self, deadline: Instant) -> bool {
+        let network_rx = &mut self.network_rx;
+        let sync_rx = &mut self.sync_rx;
+        let received = timeout_at(deadline, async {
+            tokio::select! {
+                Some(message) = network_rx.recv() => Some(Ok(message)),
+                Some(message) = sync_rx.recv() => Some(Err(message)),
+                else => None,
+            }
+        })
+        .await;
+
+        match received {
+            Ok(Some(Ok(NetworkMessage::ValidationResult {
+                message_id,
+                validation_result,
+                ..
+            }))) => {
+                self.results.entry(message_id).or_insert(validation_result);
+            }
+            Ok(Some(Err(SyncMessage::BlockComponentProcessed {
+                process_type,
+                result,
+            }))) => {
+                self.rpc_results
+                    .insert(process_type.id(), format!("{:?}", result));
+            }
+            // Other messages (e.g. peer reports, or lookups of unknown blocks) are not replayed.
+            Ok(Some(_)) => {}
+            Ok(None) | Err(_) => return false,
+        }
+        true
+    }
+}
+
+fn record_time(millis: u64) -> Duration {
+    Duration::from_millis(millis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use beacon_chain::test_utils::{
+        AttestationStrategy, BeaconChainHarness, BlockStrategy, EphemeralHarnessType,
+    };
+    use lighthouse_network::capture::{acceptance_to_u8, ValidationRecord};
+    use lighthouse_network::types::GossipEncoding;
+    use lighthouse_network::{PeerId, Topic, TopicHash};
+    use slot_clock::SlotClock;
+    use ssz::Encode;
+    use types::{MainnetEthSpec, Slot};
+
+    type E = MainnetEthSpec;
+
+    const VALIDATOR_COUNT: usize = 32;
+
+    /// The time 1s into `slot`, in milliseconds since the UNIX epoch.
+    fn arrival_ms(harness: &BeaconChainHarness<EphemeralHarnessType<E>>, slot: Slot) -> u64 {
+        let start = harness.chain.slot_clock.start_of(slot).unwrap();
+        (start + Duration::from_secs(1)).as_millis() as u64
+    }
+
+    #[tokio::test]
+    async fn replay_rpc_block_and_gossip_block() {
+        let harness = BeaconChainHarness::builder(MainnetEthSpec)
+            .default_spec()
+            .deterministic_keypairs(VALIDATOR_COUNT)
+            .fresh_ephemeral_store()
+            .build();
+        harness.advance_slot();
+        harness
+            .extend_chain(
+                1,
+                BlockStrategy::OnCanonicalHead,
+                AttestationStrategy::AllValidators,
+            )
+            .await;
+        harness.advance_slot();
+
+        // Two blocks which have not been imported. The first is replayed from an RPC response and
+        // the second, its child, from gossip.
+        let rpc_slot = harness.get_current_slot();
+        let ((rpc_block, _), state) = harness
+            .make_block(harness.get_current_state(), rpc_slot)
+            .await;
+        let ((gossip_block, _), _) = harness.make_block(state, rpc_slot + 1).await;
+
+        let replay = Replay::new(
+            harness.chain.clone(),
+            ReplayConfig {
+                message_timeout: Duration::from_secs(10),
+                drain_timeout: Duration::from_secs(1),
+                beacon_processor: BeaconProcessorConfig::default(),
+            },
+            harness.runtime.task_executor.clone(),
+            harness.logger().clone(),
+        )
+        .unwrap();
+
+        let peer_id = PeerId::random();
+        let fork_digest = replay
+            .fork_context
+            .to_context_bytes(replay.fork_context.current_fork())
+            .unwrap();
+        let message = PubsubMessage::BeaconBlock(gossip_block.clone());
+        let topic = Topic::from(
+            message
+                .topics(GossipEncoding::default(), fork_digest)
+                .remove(0),
+        )
+        .hash();
+        let message_id = MessageId::new(&[1]);
+        let gossip_ms = arrival_ms(&harness, rpc_slot + 1);
+
+        let records = vec![
+            RpcResponseRecord::new(
+                arrival_ms(&harness, rpc_slot),
+                peer_id,
+                Protocol::BlocksByRoot.as_ref(),
+                &rpc_block.as_ssz_bytes(),
+            )
+            .map(CaptureRecord::RpcResponse),
+            // Responses without blocks or blobs are skipped.
+            RpcResponseRecord::new(
+                arrival_ms(&harness, rpc_slot),
+                peer_id,
+                Protocol::LightClientBootstrap.as_ref(),
+                &[],
+            )
+            .map(CaptureRecord::RpcResponse),
+            GossipRecord::new(
+                gossip_ms,
+                peer_id,
+                message_id.clone(),
+                topic,
+                &message.encode(GossipEncoding::default()),
+            )
+            .map(CaptureRecord::Gossip),
+            Ok(CaptureRecord::Validation(ValidationRecord {
+                reported_ms: gossip_ms,
+                peer_id: peer_id.to_bytes(),
+                message_id: message_id.0.clone(),
+                acceptance: acceptance_to_u8(MessageAcceptance::Accept),
+            })),
+            // A gossip message on an unknown topic cannot be replayed.
+            GossipRecord::new(
+                gossip_ms,
+                peer_id,
+                MessageId::new(&[2]),
+                TopicHash::from_raw("unknown"),
+                &[],
+            )
+            .map(CaptureRecord::Gossip),
+            Err("unreadable record".to_string()),
+        ];
+
+        let summary = replay.run(records.into_iter()).await;
+
+        assert_eq!(summary.rpc.len(), 1);
+        assert_eq!(summary.rpc_skipped, 1);
+        let rpc_result = summary.rpc[0].result.as_ref().expect("RPC block processed");
+        assert!(rpc_result.contains("Imported"), "{rpc_result}");
+
+        assert_eq!(summary.gossip.len(), 1);
+        let outcome = &summary.gossip[0];
+        assert_eq!(outcome.message_id, message_id);
+        assert_eq!(outcome.captured, Some(MessageAcceptance::Accept));
+        assert_eq!(outcome.replayed, Some(MessageAcceptance::Accept));
+        assert_eq!(summary.mismatches().count(), 0);
+
+        assert_eq!(summary.errors.len(), 2);
+        assert!(harness
+            .chain
+            .canonical_head
+            .fork_choice_read_lock()
+            .contains_block(&rpc_block.canonical_root()));
+    }
+}
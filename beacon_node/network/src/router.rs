@@ -110,6 +110,7 @@ impl<T: BeaconChainTypes> Router<T> {
             reprocess_tx: beacon_processor_reprocess_tx,
             network_globals: network_globals.clone(),
             invalid_block_storage,
+            checkpoint_state_cache: <_>::default(),
             executor: executor.clone(),
             log: log.clone(),
         };
@@ -321,6 +322,17 @@ impl<T: BeaconChainTypes> Router<T> {
                             request,
                         ),
                 ),
+            RequestType::CheckpointStateChunks(request) => self
+                .handle_beacon_processor_send_result(
+                    self.network_beacon_processor
+                        .send_checkpoint_state_chunks_request(
+                            peer_id,
+                            request_id.0,
+                            request_id.1,
+                            rpc_request.id,
+                            request,
+                        ),
+                ),
             _ => {}
         }
     }
@@ -358,11 +370,13 @@ impl<T: BeaconChainTypes> Router<T> {
             Response::DataColumnsByRange(data_column) => {
                 self.on_data_columns_by_range_response(peer_id, request_id, data_column);
             }
-            // Light client responses should not be received
+            // Light client responses should not be received, and state chunks are only requested
+            // while checkpoint syncing, before the router is started.
             Response::LightClientBootstrap(_)
             | Response::LightClientOptimisticUpdate(_)
             | Response::LightClientFinalityUpdate(_)
-            | Response::LightClientUpdatesByRange(_) => unreachable!(),
+            | Response::LightClientUpdatesByRange(_)
+            | Response::CheckpointStateChunks(_) => unreachable!(),
         }
     }
 
@@ -377,171 +391,14 @@ impl<T: BeaconChainTypes> Router<T> {
         gossip_message: PubsubMessage<T::EthSpec>,
         should_process: bool,
     ) {
-        match gossip_message {
-            PubsubMessage::AggregateAndProofAttestation(aggregate_and_proof) => self
-                .handle_beacon_processor_send_result(
-                    self.network_beacon_processor.send_aggregated_attestation(
-                        message_id,
-                        peer_id,
-                        *aggregate_and_proof,
-                        timestamp_now(),
-                    ),
-                ),
-            PubsubMessage::Attestation(subnet_attestation) => self
-                .handle_beacon_processor_send_result(
-                    self.network_beacon_processor.send_unaggregated_attestation(
-                        message_id,
-                        peer_id,
-                        subnet_attestation.1,
-                        subnet_attestation.0,
-                        should_process,
-                        timestamp_now(),
-                    ),
-                ),
-            PubsubMessage::BeaconBlock(block) => self.handle_beacon_processor_send_result(
-                self.network_beacon_processor.send_gossip_beacon_block(
-                    message_id,
-                    peer_id,
-                    self.network_globals.client(&peer_id),
-                    block,
-                    timestamp_now(),
-                ),
-            ),
-            PubsubMessage::BlobSidecar(data) => {
-                let (blob_index, blob_sidecar) = *data;
-                self.handle_beacon_processor_send_result(
-                    self.network_beacon_processor.send_gossip_blob_sidecar(
-                        message_id,
-                        peer_id,
-                        self.network_globals.client(&peer_id),
-                        blob_index,
-                        blob_sidecar,
-                        timestamp_now(),
-                    ),
-                )
-            }
-            PubsubMessage::DataColumnSidecar(data) => {
-                let (subnet_id, column_sidecar) = *data;
-                self.handle_beacon_processor_send_result(
-                    self.network_beacon_processor
-                        .send_gossip_data_column_sidecar(
-                            message_id,
-                            peer_id,
-                            self.network_globals.client(&peer_id),
-                            subnet_id,
-                            column_sidecar,
-                            timestamp_now(),
-                        ),
-                )
-            }
-            PubsubMessage::VoluntaryExit(exit) => {
-                debug!(self.log, "Received a voluntary exit"; "peer_id" => %peer_id);
-                self.handle_beacon_processor_send_result(
-                    self.network_beacon_processor
-                        .send_gossip_voluntary_exit(message_id, peer_id, exit),
-                )
-            }
-            PubsubMessage::ProposerSlashing(proposer_slashing) => {
-                debug!(
-                    self.log,
-                    "Received a proposer slashing";
-                    "peer_id" => %peer_id
-                );
-                self.handle_beacon_processor_send_result(
-                    self.network_beacon_processor.send_gossip_proposer_slashing(
-                        message_id,
-                        peer_id,
-                        proposer_slashing,
-                    ),
-                )
-            }
-            PubsubMessage::AttesterSlashing(attester_slashing) => {
-                debug!(
-                    self.log,
-                    "Received a attester slashing";
-                    "peer_id" => %peer_id
-                );
-                self.handle_beacon_processor_send_result(
-                    self.network_beacon_processor.send_gossip_attester_slashing(
-                        message_id,
-                        peer_id,
-                        attester_slashing,
-                    ),
-                )
-            }
-            PubsubMessage::SignedContributionAndProof(contribution_and_proof) => {
-                trace!(
-                    self.log,
-                    "Received sync committee aggregate";
-                    "peer_id" => %peer_id
-                );
-                self.handle_beacon_processor_send_result(
-                    self.network_beacon_processor.send_gossip_sync_contribution(
-                        message_id,
-                        peer_id,
-                        *contribution_and_proof,
-                        timestamp_now(),
-                    ),
-                )
-            }
-            PubsubMessage::SyncCommitteeMessage(sync_committtee_msg) => {
-                trace!(
-                    self.log,
-                    "Received sync committee signature";
-                    "peer_id" => %peer_id
-                );
-                self.handle_beacon_processor_send_result(
-                    self.network_beacon_processor.send_gossip_sync_signature(
-                        message_id,
-                        peer_id,
-                        sync_committtee_msg.1,
-                        sync_committtee_msg.0,
-                        timestamp_now(),
-                    ),
-                )
-            }
-            PubsubMessage::LightClientFinalityUpdate(light_client_finality_update) => {
-                trace!(
-                    self.log,
-                    "Received light client finality update";
-                    "peer_id" => %peer_id
-                );
-                self.handle_beacon_processor_send_result(
-                    self.network_beacon_processor
-                        .send_gossip_light_client_finality_update(
-                            message_id,
-                            peer_id,
-                            *light_client_finality_update,
-                            timestamp_now(),
-                        ),
-                )
-            }
-            PubsubMessage::LightClientOptimisticUpdate(light_client_optimistic_update) => {
-                trace!(
-                    self.log,
-                    "Received light client optimistic update";
-                    "peer_id" => %peer_id
-                );
-                self.handle_beacon_processor_send_result(
-                    self.network_beacon_processor
-                        .send_gossip_light_client_optimistic_update(
-                            message_id,
-                            peer_id,
-                            *light_client_optimistic_update,
-                            timestamp_now(),
-                        ),
-                )
-            }
-            PubsubMessage::BlsToExecutionChange(bls_to_execution_change) => self
-                .handle_beacon_processor_send_result(
-                    self.network_beacon_processor
-                        .send_gossip_bls_to_execution_change(
-                            message_id,
-                            peer_id,
-                            bls_to_execution_change,
-                        ),
-                ),
-        }
+        self.handle_beacon_processor_send_result(self.network_beacon_processor.send_gossip_message(
+            message_id,
+            peer_id,
+            self.network_globals.client(&peer_id),
+            gossip_message,
+            should_process,
+            timestamp_now(),
+        ))
     }
 
     fn send_status(&mut self, peer_id: PeerId) {
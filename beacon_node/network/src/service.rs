@@ -1,12 +1,14 @@
 use crate::metrics;
 use crate::nat;
 use crate::network_beacon_processor::InvalidBlockStorage;
-use crate::persisted_dht::{clear_dht, load_dht, persist_dht};
+use crate::persisted_dht::{clear_dht, load_dht, load_peer_db, persist_dht, persist_peer_db};
 use crate::router::{Router, RouterMessage};
 use crate::subnet_service::{SubnetService, SubnetServiceMessage, Subscription};
 use crate::NetworkConfig;
 use beacon_chain::{BeaconChain, BeaconChainTypes};
 use beacon_processor::{work_reprocessing_queue::ReprocessQueueMessage, BeaconProcessorSend};
+use eth2::lighthouse::{PeerConnectionEvent, SsePeerConnection};
+use eth2::types::{EventKind, PeerDirection};
 use futures::channel::mpsc::Sender;
 use futures::future::OptionFuture;
 use futures::prelude::*;
@@ -295,6 +297,19 @@ impl<T: BeaconChainTypes> NetworkService<T> {
             }
         }
 
+        // Restore the reputation of the peers known to the previous run.
+        match load_peer_db::<T::EthSpec, T::HotStore, T::ColdStore>(store.clone()) {
+            Ok(Some(persisted_peers)) => {
+                debug!(
+                    network_log,
+                    "Restoring peer reputations"; "peers" => persisted_peers.peers.len()
+                );
+                libp2p.peer_manager_mut().restore_peers(persisted_peers);
+            }
+            Ok(None) => {}
+            Err(e) => warn!(network_log, "Unable to load peer reputations"; "error" => ?e),
+        }
+
         let invalid_block_storage = config
             .invalid_block_storage
             .clone()
@@ -429,6 +444,34 @@ impl<T: BeaconChainTypes> NetworkService<T> {
         }
     }
 
+    /// Publish a peer connection or disconnection to the `lighthouse_peer_connection` event topic.
+    fn register_peer_connection_event(
+        &self,
+        peer_id: &PeerId,
+        event: PeerConnectionEvent,
+        direction: Option<PeerDirection>,
+    ) {
+        let Some(event_handler) = self.beacon_chain.event_handler.as_ref() else {
+            return;
+        };
+        if !event_handler.has_peer_connection_subscribers() {
+            return;
+        }
+
+        let client = self
+            .network_globals
+            .peers
+            .read()
+            .peer_info(peer_id)
+            .map(|info| info.client().kind.to_string());
+        event_handler.register(EventKind::LighthousePeerConnection(SsePeerConnection {
+            peer_id: peer_id.to_string(),
+            event,
+            direction,
+            client,
+        }));
+    }
+
     fn spawn_service(mut self, executor: task_executor::TaskExecutor) {
         let mut shutdown_sender = executor.shutdown_sender();
 
@@ -494,12 +537,26 @@ impl<T: BeaconChainTypes> NetworkService<T> {
     ) {
         match ev {
             NetworkEvent::PeerConnectedOutgoing(peer_id) => {
+                self.register_peer_connection_event(
+                    &peer_id,
+                    PeerConnectionEvent::Connected,
+                    Some(PeerDirection::Outbound),
+                );
                 self.send_to_router(RouterMessage::StatusPeer(peer_id));
             }
-            NetworkEvent::PeerConnectedIncoming(_) => {
-                // No action required for this event.
+            NetworkEvent::PeerConnectedIncoming(peer_id) => {
+                self.register_peer_connection_event(
+                    &peer_id,
+                    PeerConnectionEvent::Connected,
+                    Some(PeerDirection::Inbound),
+                );
             }
             NetworkEvent::PeerDisconnected(peer_id) => {
+                self.register_peer_connection_event(
+                    &peer_id,
+                    PeerConnectionEvent::Disconnected,
+                    None,
+                );
                 self.send_to_router(RouterMessage::PeerDisconnected(peer_id));
             }
             NetworkEvent::RequestReceived {
@@ -863,6 +920,8 @@ impl<T: BeaconChainTypes> NetworkService<T> {
                 );
             }
         }
+        self.subnet_service
+            .set_shed_optional_subnets(self.libp2p.shed_optional_subnets());
     }
 
     fn on_subnet_service_msg(&mut self, msg: SubnetServiceMessage) {
@@ -884,9 +943,8 @@ impl<T: BeaconChainTypes> NetworkService<T> {
             SubnetServiceMessage::EnrAdd(subnet) => {
                 self.libp2p.update_enr_subnet(subnet, true);
             }
-            SubnetServiceMessage::EnrRemove(sync_subnet_id) => {
-                self.libp2p
-                    .update_enr_subnet(Subnet::SyncCommittee(sync_subnet_id), false);
+            SubnetServiceMessage::EnrRemove(subnet) => {
+                self.libp2p.update_enr_subnet(subnet, false);
             }
             SubnetServiceMessage::DiscoverPeers(subnets_to_discover) => {
                 self.libp2p.discover_subnet_peers(subnets_to_discover);
@@ -995,6 +1053,19 @@ impl<T: BeaconChainTypes> Drop for NetworkService<T> {
                 "Saved DHT state";
             ),
         }
+
+        let persisted_peers = self.libp2p.peer_manager().persisted_peers();
+        debug!(
+            self.log,
+            "Persisting peer reputations to store";
+            "Number of peers" => persisted_peers.peers.len(),
+        );
+        if let Err(e) = persist_peer_db::<T::EthSpec, T::HotStore, T::ColdStore>(
+            self.store.clone(),
+            persisted_peers,
+        ) {
+            error!(self.log, "Failed to persist peer reputations on drop"; "error" => ?e);
+        }
         info!(self.log, "Network service shutdown");
     }
 }
@@ -16,8 +16,9 @@ use futures::prelude::*;
 use lighthouse_network::{discv5::enr::NodeId, NetworkConfig, Subnet, SubnetDiscovery};
 use slog::{debug, error, o, warn};
 use slot_clock::SlotClock;
+use types::consts::altair::SYNC_COMMITTEE_SUBNET_COUNT;
 use types::{
-    Attestation, EthSpec, Slot, SubnetId, SyncCommitteeSubscription, SyncSubnetId,
+    Attestation, ChainSpec, EthSpec, Slot, SubnetId, SyncCommitteeSubscription, SyncSubnetId,
     ValidatorSubscription,
 };
 
@@ -32,8 +33,8 @@ pub enum SubnetServiceMessage {
     Unsubscribe(Subnet),
     /// Add the `SubnetId` to the ENR bitfield.
     EnrAdd(Subnet),
-    /// Remove a sync committee subnet from the ENR.
-    EnrRemove(SyncSubnetId),
+    /// Remove the `SubnetId` from the ENR bitfield.
+    EnrRemove(Subnet),
     /// Discover peers for a list of `SubnetDiscovery`.
     DiscoverPeers(Vec<SubnetDiscovery>),
 }
@@ -105,6 +106,13 @@ pub struct SubnetService<T: BeaconChainTypes> {
     /// We are always subscribed to all subnets.
     subscribe_all_subnets: bool,
 
+    /// We are configured to subscribe to all subnets, but may temporarily only subscribe to the
+    /// subnets required by our node id to stay within a bandwidth budget.
+    subscribe_all_subnets_configured: bool,
+
+    /// Our node id, which determines the attestation subnets we are required to subscribe to.
+    node_id: NodeId,
+
     /// Whether this node is a block proposer-only node.
     proposer_only: bool,
 
@@ -142,11 +150,8 @@ impl<T: BeaconChainTypes> SubnetService<T> {
         } else {
             // Not subscribed to all subnets, so just calculate the required subnets from the node
             // id.
-            for subnet_id in
-                SubnetId::compute_attestation_subnets(node_id.raw(), &beacon_chain.spec)
-            {
-                permanent_attestation_subscriptions.insert(Subnet::Attestation(subnet_id));
-            }
+            permanent_attestation_subscriptions =
+                required_attestation_subnets(node_id, &beacon_chain.spec);
         }
 
         // Set up the sync committee subscriptions
@@ -193,6 +198,8 @@ impl<T: BeaconChainTypes> SubnetService<T> {
             waker: None,
             discovery_disabled: config.disable_discovery,
             subscribe_all_subnets: config.subscribe_all_subnets,
+            subscribe_all_subnets_configured: config.subscribe_all_subnets,
+            node_id,
             proposer_only: config.proposer_only,
             log,
         }
@@ -594,19 +601,75 @@ impl<T: BeaconChainTypes> SubnetService<T> {
 
     // Unsubscribes from a subnet that was removed.
     fn handle_removed_subnet(&mut self, subnet: Subnet) {
-        if !self.subscriptions.contains_key(&subnet) {
+        // Short lived subscriptions can outlive shedding optional subnets, in which case we are
+        // subscribed to all subnets again.
+        if !self.subscriptions.contains_key(&subnet) && !self.subscribe_all_subnets {
             // Subscription no longer exists as short lived subnet
             debug!(self.log, "Unsubscribing from subnet"; "subnet" => ?subnet);
             self.queue_event(SubnetServiceMessage::Unsubscribe(subnet));
 
             // If this is a sync subnet, we need to remove it from our ENR.
-            if let Subnet::SyncCommittee(sync_subnet_id) = subnet {
-                self.queue_event(SubnetServiceMessage::EnrRemove(sync_subnet_id));
+            if let Subnet::SyncCommittee(_) = subnet {
+                self.queue_event(SubnetServiceMessage::EnrRemove(subnet));
+            }
+        }
+    }
+
+    /// When configured to subscribe to all subnets, temporarily drops the subscriptions to the
+    /// subnets which are not required by our node id if `shed` is true, and restores them
+    /// otherwise.
+    ///
+    /// Used to stay within a bandwidth budget. While the optional subscriptions are dropped,
+    /// subnets required by validator duties are subscribed to as usual.
+    pub fn set_shed_optional_subnets(&mut self, shed: bool) {
+        if !self.subscribe_all_subnets_configured || shed != self.subscribe_all_subnets {
+            return;
+        }
+        let spec = &self.beacon_chain.spec;
+        let all_subnets = (0..spec.attestation_subnet_count)
+            .map(|index| Subnet::Attestation(SubnetId::from(index)))
+            .chain(
+                (0..SYNC_COMMITTEE_SUBNET_COUNT)
+                    .map(|index| Subnet::SyncCommittee(SyncSubnetId::from(index))),
+            )
+            .collect::<Vec<_>>();
+
+        if shed {
+            slog::info!(self.log, "Unsubscribing from optional subnets");
+            self.permanent_attestation_subscriptions =
+                required_attestation_subnets(self.node_id, spec);
+            self.subscribe_all_subnets = false;
+            for subnet in all_subnets {
+                if !self.permanent_attestation_subscriptions.contains(&subnet)
+                    && !self.subscriptions.contains_key(&subnet)
+                {
+                    self.queue_event(SubnetServiceMessage::Unsubscribe(subnet));
+                    self.queue_event(SubnetServiceMessage::EnrRemove(subnet));
+                }
+            }
+        } else {
+            slog::info!(self.log, "Subscribing to all subnets");
+            self.subscribe_all_subnets = true;
+            for subnet in all_subnets {
+                if !self.permanent_attestation_subscriptions.contains(&subnet) {
+                    self.queue_event(SubnetServiceMessage::Subscribe(subnet));
+                    self.queue_event(SubnetServiceMessage::EnrAdd(subnet));
+                }
+                if let Subnet::Attestation(_) = subnet {
+                    self.permanent_attestation_subscriptions.insert(subnet);
+                }
             }
         }
     }
 }
 
+/// The attestation subnets our node id requires us to subscribe to.
+fn required_attestation_subnets(node_id: NodeId, spec: &ChainSpec) -> HashSet<Subnet> {
+    SubnetId::compute_attestation_subnets(node_id.raw(), spec)
+        .map(Subnet::Attestation)
+        .collect()
+}
+
 impl<T: BeaconChainTypes> Stream for SubnetService<T> {
     type Item = SubnetServiceMessage;
 
@@ -433,6 +433,69 @@ mod test {
         // test completed successfully
     }
 
+    #[tokio::test]
+    async fn shed_optional_subnets() {
+        let config = NetworkConfig {
+            subscribe_all_subnets: true,
+            disable_discovery: true,
+            ..NetworkConfig::default()
+        };
+        let mut subnet_service = SubnetService::new(
+            CHAIN.chain.clone(),
+            NodeId::random(),
+            &config,
+            &test_logger(),
+        );
+        let attestation_subnet_count = subnet_service.beacon_chain.spec.attestation_subnet_count;
+        let subnets_per_node = subnet_service.beacon_chain.spec.subnets_per_node as u64;
+        let optional_subnet_count =
+            (attestation_subnet_count - subnets_per_node + SYNC_COMMITTEE_SUBNET_COUNT) as usize;
+
+        // Subscribed to all attestation subnets on startup.
+        let events = get_events(
+            &mut subnet_service,
+            Some(attestation_subnet_count as usize * 2),
+            1,
+        )
+        .await;
+        assert_eq!(events.len(), attestation_subnet_count as usize * 2);
+
+        // Shedding unsubscribes from all but the subnets required by our node id.
+        subnet_service.set_shed_optional_subnets(true);
+        assert_eq!(
+            subnet_service.permanent_subscriptions().count(),
+            subnets_per_node as usize
+        );
+        let events = get_events(&mut subnet_service, Some(optional_subnet_count * 2), 1).await;
+        let unsubscribe_count = events
+            .iter()
+            .filter(|event| matches!(event, SubnetServiceMessage::Unsubscribe(_)))
+            .count();
+        let enr_remove_count = events
+            .iter()
+            .filter(|event| matches!(event, SubnetServiceMessage::EnrRemove(_)))
+            .count();
+        assert_eq!(unsubscribe_count, optional_subnet_count);
+        assert_eq!(enr_remove_count, optional_subnet_count);
+
+        // Shedding again has no effect.
+        subnet_service.set_shed_optional_subnets(true);
+        assert!(get_events(&mut subnet_service, None, 1).await.is_empty());
+
+        // Restoring subscribes to all subnets again.
+        subnet_service.set_shed_optional_subnets(false);
+        assert_eq!(
+            subnet_service.permanent_subscriptions().count(),
+            attestation_subnet_count as usize
+        );
+        let events = get_events(&mut subnet_service, Some(optional_subnet_count * 2), 1).await;
+        let subscribe_count = events
+            .iter()
+            .filter(|event| matches!(event, SubnetServiceMessage::Subscribe(_)))
+            .count();
+        assert_eq!(subscribe_count, optional_subnet_count);
+    }
+
     #[tokio::test]
     async fn subscribe_correct_number_of_subnets() {
         let attestation_subnet_count = MainnetEthSpec::default_spec().attestation_subnet_count;
@@ -18,7 +18,7 @@ use crate::sync::range_sync::{
 use beacon_chain::block_verification_types::RpcBlock;
 use beacon_chain::{BeaconChain, BeaconChainTypes};
 use lighthouse_network::service::api_types::Id;
-use lighthouse_network::types::{BackFillState, NetworkGlobals};
+use lighthouse_network::types::{BackFillPauseReason, BackFillState, NetworkGlobals};
 use lighthouse_network::{PeerAction, PeerId};
 use rand::seq::SliceRandom;
 use slog::{crit, debug, error, info, warn};
@@ -27,6 +27,7 @@ use std::collections::{
     HashMap, HashSet,
 };
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use types::{Epoch, EthSpec};
 
 /// Blocks are downloaded in batches from peers. This constant specifies how many epochs worth of
@@ -74,6 +75,10 @@ pub enum SyncStart {
         completed: usize,
         /// The number of slots still to be processed.
         remaining: usize,
+        /// Why the sync is held back by its schedule, if it is.
+        paused: Option<BackFillPauseReason>,
+        /// The estimated time until the sync completes.
+        eta: Option<Duration>,
     },
     /// The chain didn't start syncing.
     NotSyncing,
@@ -140,6 +145,21 @@ pub struct BackFillSync<T: BeaconChainTypes> {
     /// This signifies that we are able to attempt to restart a failed chain.
     restart_failed_sync: bool,
 
+    /// Set while the backfill schedule (allowed hours, local block proposals) does not allow the
+    /// sync to run.
+    scheduled_pause: Option<BackFillPauseReason>,
+
+    /// When the sync first started in this process, and the batches validated by then. Used to
+    /// estimate the time until the sync completes.
+    progress_start: Option<(Instant, u64)>,
+
+    /// When the batch being processed was sent to the processor.
+    processing_started: Option<Instant>,
+
+    /// With a processing budget, the next batch isn't processed before this time, so that the sync
+    /// stays within its share of CPU and IO.
+    next_processing_at: Option<Instant>,
+
     /// Reference to the beacon chain to obtain initial starting points for the backfill sync.
     beacon_chain: Arc<BeaconChain<T>>,
 
@@ -185,6 +205,10 @@ impl<T: BeaconChainTypes> BackFillSync<T> {
             validated_batches: 0,
             participating_peers: HashSet::new(),
             restart_failed_sync: false,
+            scheduled_pause: None,
+            progress_start: None,
+            processing_started: None,
+            next_processing_at: None,
             beacon_chain,
             log,
         };
@@ -202,6 +226,26 @@ impl<T: BeaconChainTypes> BackFillSync<T> {
         }
     }
 
+    /// Holds the sync back while its schedule does not allow it to run, pausing it if it is
+    /// running. Returns `true` if the schedule changed, in which case the global sync state should
+    /// be updated to resume the sync or report the pause.
+    pub fn set_scheduled_pause(&mut self, reason: Option<BackFillPauseReason>) -> bool {
+        if self.scheduled_pause == reason {
+            return false;
+        }
+        match reason {
+            Some(reason) => {
+                debug!(self.log, "Backfill sync held back by schedule"; "reason" => %reason)
+            }
+            None => debug!(self.log, "Backfill sync allowed by schedule"),
+        }
+        self.scheduled_pause = reason;
+        if reason.is_some() {
+            self.pause();
+        }
+        true
+    }
+
     /// Starts or resumes syncing.
     ///
     /// If resuming is successful, reports back the current syncing metrics.
@@ -212,6 +256,8 @@ impl<T: BeaconChainTypes> BackFillSync<T> {
     ) -> Result<SyncStart, BackFillError> {
         match self.state() {
             BackFillState::Syncing => {} // already syncing ignore.
+            // Wait for the schedule to allow the sync to resume.
+            BackFillState::Paused if self.scheduled_pause.is_some() => {}
             BackFillState::Paused => {
                 if self
                     .network_globals
@@ -240,7 +286,7 @@ impl<T: BeaconChainTypes> BackFillSync<T> {
                 // cleared already for a fresh start.
                 // We only attempt to restart a failed backfill sync if a new synced peer has been
                 // added.
-                if !self.restart_failed_sync {
+                if !self.restart_failed_sync || self.scheduled_pause.is_some() {
                     return Ok(SyncStart::NotSyncing);
                 }
 
@@ -266,18 +312,51 @@ impl<T: BeaconChainTypes> BackFillSync<T> {
             BackFillState::Completed => return Ok(SyncStart::NotSyncing),
         }
 
+        if self.progress_start.is_none() && self.scheduled_pause.is_none() {
+            self.progress_start = Some((Instant::now(), self.validated_batches));
+        }
+
+        let remaining = self
+            .current_start
+            .start_slot(T::EthSpec::slots_per_epoch())
+            .saturating_sub(self.beacon_chain.genesis_backfill_slot)
+            .as_usize();
         Ok(SyncStart::Syncing {
             completed: (self.validated_batches
                 * BACKFILL_EPOCHS_PER_BATCH
                 * T::EthSpec::slots_per_epoch()) as usize,
-            remaining: self
-                .current_start
-                .start_slot(T::EthSpec::slots_per_epoch())
-                .saturating_sub(self.beacon_chain.genesis_backfill_slot)
-                .as_usize(),
+            remaining,
+            paused: self.scheduled_pause,
+            eta: self.estimated_time_remaining(remaining),
         })
     }
 
+    /// Estimates the time to backfill `remaining` slots from the average speed since the sync
+    /// started, including any time it spent paused.
+    fn estimated_time_remaining(&self, remaining: usize) -> Option<Duration> {
+        let (started, validated_at_start) = self.progress_start?;
+        let validated_slots = self.validated_batches.saturating_sub(validated_at_start)
+            * BACKFILL_EPOCHS_PER_BATCH
+            * T::EthSpec::slots_per_epoch();
+        estimate_time_remaining(started.elapsed(), validated_slots, remaining as u64)
+    }
+
+    /// Processes the next batch once the processing budget allows it. Called periodically, as
+    /// nothing else triggers processing while the sync waits on its budget.
+    #[must_use = "A failure here indicates the backfill sync has failed and the global sync state should be updated"]
+    pub fn resume_processing(
+        &mut self,
+        network: &mut SyncNetworkContext<T>,
+    ) -> Result<ProcessResult, BackFillError> {
+        match self.next_processing_at {
+            Some(next_processing_at) if next_processing_at <= Instant::now() => {
+                self.next_processing_at = None;
+                self.process_completed_batches(network)
+            }
+            _ => Ok(ProcessResult::Successful),
+        }
+    }
+
     /// A fully synced peer has joined us.
     /// If we are in a failed state, update a local variable to indicate we are able to restart
     /// the failed sync on the next attempt.
@@ -497,6 +576,13 @@ impl<T: BeaconChainTypes> BackFillSync<T> {
         if self.state() != BackFillState::Syncing || self.current_processing_batch.is_some() {
             return Ok(ProcessResult::Successful);
         }
+        // Wait for the processing budget, `resume_processing` picks the batch up later.
+        if self
+            .next_processing_at
+            .is_some_and(|next_processing_at| next_processing_at > Instant::now())
+        {
+            return Ok(ProcessResult::Successful);
+        }
 
         let Some(batch) = self.batches.get_mut(&batch_id) else {
             return self
@@ -522,6 +608,7 @@ impl<T: BeaconChainTypes> BackFillSync<T> {
 
         let process_id = ChainSegmentProcessId::BackSyncBatchId(batch_id);
         self.current_processing_batch = Some(batch_id);
+        self.processing_started = Some(Instant::now());
 
         if let Err(e) = network
             .beacon_processor()
@@ -566,6 +653,13 @@ impl<T: BeaconChainTypes> BackFillSync<T> {
             _ => {
                 // batch_id matches, continue
                 self.current_processing_batch = None;
+                if let (Some(started), Some(budget)) = (
+                    self.processing_started.take(),
+                    self.beacon_chain.config.backfill_processing_budget,
+                ) {
+                    self.next_processing_at =
+                        Some(Instant::now() + processing_budget_delay(started.elapsed(), budget));
+                }
 
                 match self.batches.get_mut(&batch_id) {
                     Some(batch) => batch,
@@ -1166,8 +1260,233 @@ impl<T: BeaconChainTypes> BackFillSync<T> {
     }
 }
 
+/// Estimates the time to backfill `remaining` slots, given `validated` slots took `elapsed`.
+fn estimate_time_remaining(elapsed: Duration, validated: u64, remaining: u64) -> Option<Duration> {
+    if validated == 0 {
+        return None;
+    }
+    let seconds_per_slot = elapsed.as_secs_f64() / validated as f64;
+    Some(Duration::from_secs_f64(seconds_per_slot * remaining as f64))
+}
+
+/// Returns how long to wait after processing a batch for `processing_time`, so that processing
+/// takes at most `budget` percent of the time.
+fn processing_budget_delay(processing_time: Duration, budget: u8) -> Duration {
+    let budget = u32::from(budget.clamp(1, 100));
+    processing_time * (100 - budget) / budget
+}
+
+/// Returns `true` if `hour` is within the backfill `window` of `(start, end)` hours, with the end
+/// exclusive. The window wraps around midnight if `end` is not after `start`.
+pub fn in_backfill_window(hour: u8, (start, end): (u8, u8)) -> bool {
+    if start < end {
+        (start..end).contains(&hour)
+    } else {
+        hour >= start || hour < end
+    }
+}
+
 /// Error kind for attempting to restart the sync from beacon chain parameters.
 enum ResetEpochError {
     /// The chain has already completed.
     SyncCompleted,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network_beacon_processor::NetworkBeaconProcessor;
+    use crate::sync::SyncMessage;
+    use crate::NetworkMessage;
+    use beacon_chain::builder::Witness;
+    use beacon_chain::eth1_chain::CachingEth1Backend;
+    use beacon_chain::test_utils::{BeaconChainHarness, EphemeralHarnessType};
+    use lighthouse_network::{NetworkConfig, SyncInfo, SyncStatus};
+    use logging::test_logger;
+    use slot_clock::ManualSlotClock;
+    use store::MemoryStore;
+    use tokio::sync::mpsc;
+    use types::{FixedBytesExtended, Hash256, MinimalEthSpec as E, Slot};
+
+    type TestBeaconChainType =
+        Witness<ManualSlotClock, CachingEth1Backend<E>, E, MemoryStore<E>, MemoryStore<E>>;
+
+    struct TestRig {
+        backfill: BackFillSync<TestBeaconChainType>,
+        cx: SyncNetworkContext<TestBeaconChainType>,
+        network_rx: mpsc::UnboundedReceiver<NetworkMessage<E>>,
+        globals: Arc<NetworkGlobals<E>>,
+        _harness: BeaconChainHarness<EphemeralHarnessType<E>>,
+    }
+
+    impl TestRig {
+        /// A backfill sync paused with epochs left to download.
+        fn new() -> Self {
+            let log = test_logger();
+            let harness = BeaconChainHarness::<EphemeralHarnessType<E>>::builder(E)
+                .default_spec()
+                .logger(log.clone())
+                .deterministic_keypairs(1)
+                .fresh_ephemeral_store()
+                .build();
+            let chain = harness.chain.clone();
+            let globals = Arc::new(NetworkGlobals::new_test_globals(
+                Vec::new(),
+                &log,
+                Arc::new(NetworkConfig::default()),
+                chain.spec.clone(),
+            ));
+            let (network_tx, network_rx) = mpsc::unbounded_channel();
+            let (sync_tx, _sync_rx) = mpsc::unbounded_channel::<SyncMessage<E>>();
+            let (network_beacon_processor, _) = NetworkBeaconProcessor::null_for_testing(
+                globals.clone(),
+                sync_tx,
+                chain.clone(),
+                harness.runtime.task_executor.clone(),
+                log.clone(),
+            );
+            let cx = SyncNetworkContext::new(
+                network_tx,
+                Arc::new(network_beacon_processor),
+                chain.clone(),
+                log.clone(),
+            );
+
+            let mut backfill = BackFillSync::new(chain, globals.clone(), log);
+            let start = Epoch::new(4);
+            backfill.current_start = start;
+            backfill.processing_target = start;
+            backfill.to_be_downloaded = start;
+            backfill.set_state(BackFillState::Paused);
+
+            Self {
+                backfill,
+                cx,
+                network_rx,
+                globals,
+                _harness: harness,
+            }
+        }
+
+        fn add_synced_peer(&mut self) {
+            let mut peers = self.globals.peers.write();
+            let peer_id = peers.__add_connected_peer_testing_only(false, &self.cx.chain.spec);
+            let info = SyncInfo {
+                head_slot: Slot::new(0),
+                head_root: Hash256::zero(),
+                finalized_epoch: Epoch::new(0),
+                finalized_root: Hash256::zero(),
+            };
+            peers.update_sync_status(&peer_id, SyncStatus::Synced { info });
+        }
+
+        fn start(&mut self) -> Option<Option<BackFillPauseReason>> {
+            match self.backfill.start(&mut self.cx).unwrap() {
+                SyncStart::Syncing { paused, .. } => Some(paused),
+                SyncStart::NotSyncing => None,
+            }
+        }
+
+        fn expect_batch_request(&mut self) {
+            assert!(
+                matches!(
+                    self.network_rx.try_recv(),
+                    Ok(NetworkMessage::SendRequest { .. })
+                ),
+                "expected a batch request"
+            );
+        }
+    }
+
+    #[test]
+    fn backfill_window() {
+        // Within the day.
+        assert!(!in_backfill_window(7, (8, 17)));
+        assert!(in_backfill_window(8, (8, 17)));
+        assert!(in_backfill_window(16, (8, 17)));
+        assert!(!in_backfill_window(17, (8, 17)));
+
+        // Wrapping around midnight.
+        assert!(in_backfill_window(22, (22, 6)));
+        assert!(in_backfill_window(23, (22, 6)));
+        assert!(in_backfill_window(0, (22, 6)));
+        assert!(in_backfill_window(5, (22, 6)));
+        assert!(!in_backfill_window(6, (22, 6)));
+        assert!(!in_backfill_window(21, (22, 6)));
+    }
+
+    #[test]
+    fn time_remaining() {
+        assert_eq!(
+            estimate_time_remaining(Duration::from_secs(10), 0, 100),
+            None
+        );
+        assert_eq!(
+            estimate_time_remaining(Duration::from_secs(50), 100, 200),
+            Some(Duration::from_secs(100))
+        );
+        assert_eq!(
+            estimate_time_remaining(Duration::from_secs(50), 100, 0),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn processing_budget() {
+        let processing_time = Duration::from_secs(1);
+        assert_eq!(
+            processing_budget_delay(processing_time, 100),
+            Duration::ZERO
+        );
+        assert_eq!(
+            processing_budget_delay(processing_time, 50),
+            Duration::from_secs(1)
+        );
+        assert_eq!(
+            processing_budget_delay(processing_time, 25),
+            Duration::from_secs(3)
+        );
+        assert_eq!(
+            processing_budget_delay(processing_time, 0),
+            Duration::from_secs(99)
+        );
+    }
+
+    #[test]
+    fn scheduled_pause_and_resume() {
+        let mut rig = TestRig::new();
+        rig.add_synced_peer();
+
+        // Held back by the schedule, the sync reports the pause without resuming.
+        assert!(rig
+            .backfill
+            .set_scheduled_pause(Some(BackFillPauseReason::OutsideWindow)));
+        assert!(!rig
+            .backfill
+            .set_scheduled_pause(Some(BackFillPauseReason::OutsideWindow)));
+        assert_eq!(rig.start(), Some(Some(BackFillPauseReason::OutsideWindow)));
+        assert_eq!(rig.backfill.state(), BackFillState::Paused);
+        assert!(rig.network_rx.try_recv().is_err());
+
+        // Allowed by the schedule, the sync resumes.
+        assert!(rig.backfill.set_scheduled_pause(None));
+        assert_eq!(rig.start(), Some(None));
+        assert_eq!(rig.backfill.state(), BackFillState::Syncing);
+        rig.expect_batch_request();
+
+        // A local proposal pauses the running sync.
+        assert!(rig
+            .backfill
+            .set_scheduled_pause(Some(BackFillPauseReason::LocalProposal)));
+        assert_eq!(rig.backfill.state(), BackFillState::Paused);
+        assert_eq!(rig.start(), Some(Some(BackFillPauseReason::LocalProposal)));
+        assert_eq!(rig.backfill.state(), BackFillState::Paused);
+    }
+
+    #[test]
+    fn paused_without_peers() {
+        let mut rig = TestRig::new();
+        assert_eq!(rig.start(), None);
+        assert_eq!(rig.backfill.state(), BackFillState::Paused);
+    }
+}
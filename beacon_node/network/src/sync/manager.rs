@@ -33,7 +33,7 @@
 //! needs to be searched for (i.e if an attestation references an unknown block) this manager can
 //! search for the block and subsequently search for parents if needed.
 
-use super::backfill_sync::{BackFillSync, ProcessResult, SyncStart};
+use super::backfill_sync::{in_backfill_window, BackFillSync, ProcessResult, SyncStart};
 use super::block_lookups::BlockLookups;
 use super::network_context::{
     BlockOrBlob, CustodyByRootResult, RangeRequestId, RpcEvent, SyncNetworkContext,
@@ -54,13 +54,15 @@ use beacon_chain::validator_monitor::timestamp_now;
 use beacon_chain::{
     AvailabilityProcessingStatus, BeaconChain, BeaconChainTypes, BlockError, EngineState,
 };
+use eth2::lighthouse::SseSyncState;
+use eth2::types::EventKind;
 use futures::StreamExt;
 use lighthouse_network::rpc::RPCError;
 use lighthouse_network::service::api_types::{
     CustodyRequester, DataColumnsByRootRequestId, DataColumnsByRootRequester, Id, SamplingId,
     SamplingRequester, SingleLookupReqId, SyncRequestId,
 };
-use lighthouse_network::types::{NetworkGlobals, SyncState};
+use lighthouse_network::types::{BackFillPauseReason, NetworkGlobals, SyncState};
 use lighthouse_network::SyncInfo;
 use lighthouse_network::{PeerAction, PeerId};
 use lru_cache::LRUTimeCache;
@@ -645,10 +647,14 @@ impl<T: BeaconChainTypes> SyncManager<T> {
                             Ok(SyncStart::Syncing {
                                 completed,
                                 remaining,
+                                paused,
+                                eta,
                             }) => {
                                 sync_state = SyncState::BackFillSyncing {
                                     completed,
                                     remaining,
+                                    paused,
+                                    eta_seconds: eta.map(|eta| eta.as_secs()),
                                 };
                             }
                             Ok(SyncStart::NotSyncing) => {} // Ignore updating the state if the backfill sync state didn't start.
@@ -699,9 +705,45 @@ impl<T: BeaconChainTypes> SyncManager<T> {
             {
                 self.network.subscribe_core_topics();
             }
+
+            if let Some(event_handler) = self.chain.event_handler.as_ref() {
+                if event_handler.has_sync_state_subscribers() {
+                    let execution_optimistic = self
+                        .chain
+                        .is_optimistic_or_invalid_head()
+                        .unwrap_or_default();
+                    event_handler.register(EventKind::LighthouseSyncState(SseSyncState {
+                        previous: old_state,
+                        current: new_state,
+                        execution_optimistic,
+                    }));
+                }
+            }
         }
     }
 
+    /// Returns why the backfill schedule does not currently allow backfill sync to run, if it
+    /// does not.
+    async fn backfill_pause_reason(&self) -> Option<BackFillPauseReason> {
+        if let Some(window) = self.chain.config.backfill_window_utc_hours {
+            let hour = (timestamp_now().as_secs() / 3600 % 24) as u8;
+            if !in_backfill_window(hour, window) {
+                return Some(BackFillPauseReason::OutsideWindow);
+            }
+        }
+
+        if let Some(pause_slots) = self.chain.config.backfill_proposal_pause_slots {
+            let current_slot = self.chain.slot().ok()?;
+            for slot in current_slot.as_u64()..=current_slot.as_u64().saturating_add(pause_slots) {
+                if self.chain.has_local_proposer_at_slot(Slot::new(slot)).await {
+                    return Some(BackFillPauseReason::LocalProposal);
+                }
+            }
+        }
+
+        None
+    }
+
     /// The main driving future for the sync manager.
     async fn main(&mut self) {
         let check_ee = self.chain.execution_layer.is_some();
@@ -725,6 +767,18 @@ impl<T: BeaconChainTypes> SyncManager<T> {
 
         let mut register_metrics_interval = tokio::time::interval(Duration::from_secs(5));
 
+        // Range sync batches are hedged after at least 5 seconds, so checking every 2 seconds
+        // keeps the delay to detect a slow batch small.
+        let mut hedge_batches_interval = tokio::time::interval(Duration::from_secs(2));
+
+        // Check the backfill schedule three times per slot, so that backfill pauses well before a
+        // local proposal.
+        let mut backfill_schedule_interval =
+            tokio::time::interval(Duration::from_secs(self.chain.spec.seconds_per_slot) / 3);
+
+        // Backfill batches held back by the processing budget are picked up on this interval.
+        let mut backfill_budget_interval = tokio::time::interval(Duration::from_millis(500));
+
         // process any inbound messages
         loop {
             tokio::select! {
@@ -743,6 +797,25 @@ impl<T: BeaconChainTypes> SyncManager<T> {
                 _ = register_metrics_interval.tick() => {
                     self.network.register_metrics();
                 }
+                _ = hedge_batches_interval.tick() => {
+                    self.range_sync.hedge_slow_batches(&mut self.network);
+                }
+                _ = backfill_schedule_interval.tick() => {
+                    let pause_reason = self.backfill_pause_reason().await;
+                    if self.backfill_sync.set_scheduled_pause(pause_reason) {
+                        self.update_sync_state();
+                    }
+                }
+                _ = backfill_budget_interval.tick() => {
+                    match self.backfill_sync.resume_processing(&mut self.network) {
+                        Ok(ProcessResult::Successful) => {}
+                        Ok(ProcessResult::SyncCompleted) => self.update_sync_state(),
+                        Err(error) => {
+                            error!(self.log, "Backfill sync failed"; "error" => ?error);
+                            self.update_sync_state();
+                        }
+                    }
+                }
             }
         }
     }
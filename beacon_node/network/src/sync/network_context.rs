@@ -5,7 +5,7 @@ use self::custody::{ActiveCustodyRequest, Error as CustodyRequestError};
 pub use self::requests::{BlocksByRootSingleRequest, DataColumnsByRootSingleBlockRequest};
 use super::block_sidecar_coupling::RangeBlockComponentsRequest;
 use super::manager::BlockProcessType;
-use super::range_sync::{BatchId, ByRangeRequestType, ChainId};
+use super::range_sync::{BatchId, ByRangeRequestType, ChainId, SyncPeerScores};
 use super::SyncMessage;
 use crate::metrics;
 use crate::network_beacon_processor::NetworkBeaconProcessor;
@@ -197,6 +197,9 @@ pub struct SyncNetworkContext<T: BeaconChainTypes> {
     range_block_components_requests:
         FnvHashMap<Id, (RangeRequestId, RangeBlockComponentsRequest<T::EthSpec>)>,
 
+    /// How well each peer has served range sync batches, shared by all syncing chains.
+    pub range_peer_scores: SyncPeerScores,
+
     /// Whether the ee is online. If it's not, we don't allow access to the
     /// `beacon_processor_send`.
     execution_engine_state: EngineState,
@@ -245,6 +248,7 @@ impl<T: BeaconChainTypes> SyncNetworkContext<T> {
             data_columns_by_root_requests: ActiveRequests::new("data_columns_by_root"),
             custody_by_root_requests: <_>::default(),
             range_block_components_requests: FnvHashMap::default(),
+            range_peer_scores: <_>::default(),
             network_beacon_processor,
             chain,
             log,
@@ -1146,6 +1150,7 @@ impl<T: BeaconChainTypes> SyncNetworkContext<T> {
             &["range_blocks"],
             self.range_block_components_requests.len() as i64,
         );
+        self.range_peer_scores.register_metrics();
     }
 }
 
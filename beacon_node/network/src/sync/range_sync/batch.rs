@@ -79,6 +79,7 @@ impl BatchConfig for RangeSyncBatchConfig {
 
 /// Error type of a batch in a wrong state.
 // Such errors should never be encountered.
+#[derive(Debug)]
 pub struct WrongState(pub(crate) String);
 
 /// After batch operations, we use this to communicate whether a batch can continue or not
@@ -109,6 +110,11 @@ pub struct BatchInfo<E: EthSpec, B: BatchConfig = RangeSyncBatchConfig> {
     state: BatchState<E>,
     /// Whether this batch contains all blocks or all blocks and blobs.
     batch_type: ByRangeRequestType,
+    /// When the current download request was sent.
+    download_started: Option<Instant>,
+    /// A second download request sent because the current one was taking too long. Whichever
+    /// request completes first is used.
+    hedge: Option<(PeerId, Id, Instant)>,
     /// Pin the generic
     marker: std::marker::PhantomData<B>,
 }
@@ -168,6 +174,8 @@ impl<E: EthSpec, B: BatchConfig> BatchInfo<E, B> {
             non_faulty_processing_attempts: 0,
             state: BatchState::AwaitingDownload,
             batch_type,
+            download_started: None,
+            hedge: None,
             marker: std::marker::PhantomData,
         }
     }
@@ -202,11 +210,96 @@ impl<E: EthSpec, B: BatchConfig> BatchInfo<E, B> {
     /// Verifies if an incoming block belongs to this batch.
     pub fn is_expecting_block(&self, request_id: &Id) -> bool {
         if let BatchState::Downloading(_, expected_id) = &self.state {
-            return expected_id == request_id;
+            return expected_id == request_id
+                || self
+                    .hedge
+                    .as_ref()
+                    .is_some_and(|(_, hedge_id, _)| hedge_id == request_id);
         }
         false
     }
 
+    /// Returns how long the current download request has been in flight.
+    pub fn download_elapsed(&self) -> Option<Duration> {
+        match self.state {
+            BatchState::Downloading(..) => self.download_started.map(|started| started.elapsed()),
+            _ => None,
+        }
+    }
+
+    /// Returns the peer of the second download request of the batch, if any.
+    pub fn hedged_peer(&self) -> Option<&PeerId> {
+        self.hedge.as_ref().map(|(peer, _, _)| peer)
+    }
+
+    /// Registers a second download request for a batch that is taking too long to download.
+    pub fn start_hedge(&mut self, peer: PeerId, request_id: Id) -> Result<(), WrongState> {
+        match &self.state {
+            BatchState::Downloading(current_peer, _)
+                if self.hedge.is_none() && *current_peer != peer =>
+            {
+                self.hedge = Some((peer, request_id, Instant::now()));
+                Ok(())
+            }
+            _ => Err(WrongState(format!(
+                "Hedging download for batch in wrong state {:?}",
+                self.state
+            ))),
+        }
+    }
+
+    /// Settles a hedged download once one of its requests, `request_id`, has completed.
+    ///
+    /// The completed request becomes the current request of the batch, so that the download can
+    /// be completed as usual. Returns the peer of the request that is no longer needed, if the
+    /// batch was hedged.
+    pub fn settle_hedged_download(&mut self, request_id: &Id) -> Option<PeerId> {
+        let (hedge_peer, hedge_id, hedge_started) = self.hedge.take()?;
+        match &mut self.state {
+            BatchState::Downloading(peer, id) if hedge_id == *request_id => {
+                let abandoned_peer = std::mem::replace(peer, hedge_peer);
+                *id = hedge_id;
+                self.download_started = Some(hedge_started);
+                Some(abandoned_peer)
+            }
+            _ => Some(hedge_peer),
+        }
+    }
+
+    /// Drops the request to `peer` of a hedged download, keeping the other request in flight.
+    ///
+    /// Returns `false` if the batch is not hedged or `peer` is not downloading it, in which case
+    /// the failure must be handled with `download_failed`.
+    pub fn drop_hedged_request(&mut self, peer: &PeerId, mark_failed: bool) -> bool {
+        let Some((hedge_peer, hedge_id, hedge_started)) = self.hedge.take() else {
+            return false;
+        };
+        let BatchState::Downloading(current_peer, current_id) = &mut self.state else {
+            return false;
+        };
+        if *current_peer == *peer {
+            // the hedged request takes over
+            *current_peer = hedge_peer;
+            *current_id = hedge_id;
+            self.download_started = Some(hedge_started);
+        } else if hedge_peer != *peer {
+            self.hedge = Some((hedge_peer, hedge_id, hedge_started));
+            return false;
+        }
+        if mark_failed {
+            self.failed_download_attempts.push(*peer);
+        }
+        true
+    }
+
+    /// Moves the start of the current download request back by `elapsed`.
+    #[cfg(test)]
+    pub(crate) fn backdate_download(&mut self, elapsed: Duration) {
+        self.download_started = self
+            .download_started
+            .and_then(|started| started.checked_sub(elapsed));
+    }
+
     /// Returns the peer that is currently responsible for progressing the state of the batch.
     pub fn current_peer(&self) -> Option<&PeerId> {
         match &self.state {
@@ -275,6 +368,7 @@ impl<E: EthSpec, B: BatchConfig> BatchInfo<E, B> {
         usize, /* Received blocks */
         Result<(Slot, Slot, BatchOperationOutcome), WrongState>,
     > {
+        self.hedge = None;
         match self.state.poison() {
             BatchState::Downloading(peer, _request_id) => {
                 // verify that blocks are in range
@@ -332,6 +426,7 @@ impl<E: EthSpec, B: BatchConfig> BatchInfo<E, B> {
         &mut self,
         mark_failed: bool,
     ) -> Result<BatchOperationOutcome, WrongState> {
+        self.hedge = None;
         match self.state.poison() {
             BatchState::Downloading(peer, _request_id) => {
                 // register the attempt and check if the batch can be tried again
@@ -367,6 +462,7 @@ impl<E: EthSpec, B: BatchConfig> BatchInfo<E, B> {
         match self.state.poison() {
             BatchState::AwaitingDownload => {
                 self.state = BatchState::Downloading(peer, request_id);
+                self.download_started = Some(Instant::now());
                 Ok(())
             }
             BatchState::Poisoned => unreachable!("Poisoned batch"),
@@ -561,3 +657,103 @@ impl<E: EthSpec> BatchState<E> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::MinimalEthSpec as E;
+
+    fn downloading_batch(peer: PeerId, request_id: Id) -> BatchInfo<E> {
+        let mut batch = BatchInfo::new(&Epoch::new(1), 1, ByRangeRequestType::Blocks);
+        batch.start_downloading_from_peer(peer, request_id).unwrap();
+        batch
+    }
+
+    #[test]
+    fn start_hedge() {
+        let (peer, hedge_peer) = (PeerId::random(), PeerId::random());
+
+        let mut batch = BatchInfo::<E>::new(&Epoch::new(1), 1, ByRangeRequestType::Blocks);
+        assert!(batch.start_hedge(hedge_peer, 2).is_err(), "not downloading");
+
+        let mut batch = downloading_batch(peer, 1);
+        assert!(batch.start_hedge(peer, 2).is_err(), "same peer");
+        batch.start_hedge(hedge_peer, 2).unwrap();
+        assert_eq!(batch.hedged_peer(), Some(&hedge_peer));
+        assert!(batch.is_expecting_block(&1));
+        assert!(batch.is_expecting_block(&2));
+        assert!(!batch.is_expecting_block(&3));
+        assert!(
+            batch.start_hedge(PeerId::random(), 3).is_err(),
+            "already hedged"
+        );
+    }
+
+    #[test]
+    fn settle_hedged_download_by_hedge() {
+        let (peer, hedge_peer) = (PeerId::random(), PeerId::random());
+        let mut batch = downloading_batch(peer, 1);
+        batch.start_hedge(hedge_peer, 2).unwrap();
+
+        assert_eq!(batch.settle_hedged_download(&2), Some(peer));
+        assert_eq!(batch.current_peer(), Some(&hedge_peer));
+        assert_eq!(batch.hedged_peer(), None);
+        assert!(batch.is_expecting_block(&2));
+        assert!(!batch.is_expecting_block(&1));
+        assert!(matches!(batch.download_completed(vec![]), Ok(0)));
+        assert!(batch.failed_peers().is_empty());
+    }
+
+    #[test]
+    fn settle_hedged_download_by_original() {
+        let (peer, hedge_peer) = (PeerId::random(), PeerId::random());
+        let mut batch = downloading_batch(peer, 1);
+        assert_eq!(batch.settle_hedged_download(&1), None, "not hedged");
+
+        batch.start_hedge(hedge_peer, 2).unwrap();
+        assert_eq!(batch.settle_hedged_download(&1), Some(hedge_peer));
+        assert_eq!(batch.current_peer(), Some(&peer));
+        assert_eq!(batch.hedged_peer(), None);
+        assert!(!batch.is_expecting_block(&2));
+    }
+
+    #[test]
+    fn drop_hedged_request() {
+        let (peer, hedge_peer) = (PeerId::random(), PeerId::random());
+        let mut batch = downloading_batch(peer, 1);
+        assert!(!batch.drop_hedged_request(&peer, true), "not hedged");
+
+        // A peer that isn't downloading the batch leaves it untouched.
+        batch.start_hedge(hedge_peer, 2).unwrap();
+        assert!(!batch.drop_hedged_request(&PeerId::random(), true));
+        assert_eq!(batch.hedged_peer(), Some(&hedge_peer));
+
+        // Dropping the hedged request keeps the original one.
+        assert!(batch.drop_hedged_request(&hedge_peer, true));
+        assert_eq!(batch.current_peer(), Some(&peer));
+        assert_eq!(batch.hedged_peer(), None);
+        assert_eq!(batch.failed_peers(), HashSet::from([hedge_peer]));
+
+        // Dropping the original request lets the hedged one take over.
+        let other_peer = PeerId::random();
+        batch.start_hedge(other_peer, 3).unwrap();
+        assert!(batch.drop_hedged_request(&peer, false));
+        assert_eq!(batch.current_peer(), Some(&other_peer));
+        assert!(batch.is_expecting_block(&3));
+        assert!(!batch.is_expecting_block(&1));
+        assert_eq!(batch.failed_peers(), HashSet::from([hedge_peer]));
+    }
+
+    #[test]
+    fn download_failure_clears_hedge() {
+        let (peer, hedge_peer) = (PeerId::random(), PeerId::random());
+        let mut batch = downloading_batch(peer, 1);
+        batch.start_hedge(hedge_peer, 2).unwrap();
+        assert!(matches!(
+            batch.download_failed(true),
+            Ok(BatchOperationOutcome::Continue)
+        ));
+        assert_eq!(batch.hedged_peer(), None);
+        assert!(!batch.is_expecting_block(&2));
+    }
+}
@@ -11,7 +11,6 @@ use fnv::FnvHashMap;
 use lighthouse_network::service::api_types::Id;
 use lighthouse_network::{PeerAction, PeerId};
 use metrics::set_int_gauge;
-use rand::seq::SliceRandom;
 use rand::Rng;
 use slog::{crit, debug, o, warn};
 use std::collections::{btree_map::Entry, BTreeMap, HashSet};
@@ -206,6 +205,10 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
             // fail the batches.
             for id in batch_ids {
                 if let Some(batch) = self.batches.get_mut(&id) {
+                    // if the batch was hedged, the other request is still in flight
+                    if batch.drop_hedged_request(peer_id, true) {
+                        continue;
+                    }
                     if let BatchOperationOutcome::Failed { blacklist } =
                         batch.download_failed(true)?
                     {
@@ -275,8 +278,29 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
                 .get_mut(peer_id)
                 .map(|active_requests| active_requests.remove(&batch_id));
 
+            // If the batch was hedged, the other request is no longer needed.
+            let hedged_peer = batch.hedged_peer().copied();
+            if let Some(abandoned_peer) = batch.settle_hedged_download(&request_id) {
+                self.peers
+                    .get_mut(&abandoned_peer)
+                    .map(|active_requests| active_requests.remove(&batch_id));
+                let outcome = if Some(abandoned_peer) == hedged_peer {
+                    "original_won"
+                } else {
+                    "hedge_won"
+                };
+                metrics::inc_counter_vec(&metrics::SYNC_RANGE_HEDGED_BATCHES, &[outcome]);
+            }
+            let download_peer = batch.current_peer().copied();
+            let download_elapsed = batch.download_elapsed();
+
             match batch.download_completed(blocks) {
                 Ok(received) => {
+                    if let (Some(peer), Some(elapsed)) = (download_peer, download_elapsed) {
+                        network
+                            .range_peer_scores
+                            .on_download_success(peer, elapsed, received);
+                    }
                     let awaiting_batches = batch_id
                         .saturating_sub(self.optimistic_start.unwrap_or(self.processing_target))
                         / EPOCHS_PER_BATCH;
@@ -290,6 +314,9 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
                     let (expected_boundary, received_boundary, outcome) = result?;
                     warn!(self.log, "Batch received out of range blocks"; "expected_boundary" => expected_boundary, "received_boundary" => received_boundary,
                         "peer_id" => %peer_id, batch);
+                    if let Some(peer) = download_peer {
+                        network.range_peer_scores.on_download_failure(peer);
+                    }
 
                     if let BatchOperationOutcome::Failed { blacklist } = outcome {
                         return Err(RemoveChain::ChainFailed {
@@ -702,9 +729,11 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
                     }
                 }
                 BatchState::Downloading(peer, ..) => {
-                    // remove this batch from the peer's active requests
-                    if let Some(active_batches) = self.peers.get_mut(peer) {
-                        active_batches.remove(&id);
+                    // remove this batch from the active requests of its peers
+                    for peer in std::iter::once(peer).chain(batch.hedged_peer()) {
+                        if let Some(active_batches) = self.peers.get_mut(peer) {
+                            active_batches.remove(&id);
+                        }
                     }
                 }
                 BatchState::Failed | BatchState::Poisoned | BatchState::AwaitingDownload => crit!(
@@ -898,6 +927,11 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
             if let Some(active_requests) = self.peers.get_mut(peer_id) {
                 active_requests.remove(&batch_id);
             }
+            network.range_peer_scores.on_download_failure(*peer_id);
+            // if the batch was hedged, the other request is still in flight
+            if batch.drop_hedged_request(peer_id, true) {
+                return Ok(KeepChain);
+            }
             if let BatchOperationOutcome::Failed { blacklist } = batch.download_failed(true)? {
                 return Err(RemoveChain::ChainFailed {
                     blacklist,
@@ -938,12 +972,14 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
             .map(|(peer, requests)| {
                 (
                     failed_peers.contains(peer),
-                    requests.len(),
+                    network
+                        .range_peer_scores
+                        .expected_completion(peer, requests.len()),
                     rand::thread_rng().gen::<u32>(),
                     *peer,
                 )
             })
-            // Sort peers prioritizing unrelated peers with less active requests.
+            // Sort peers prioritizing unrelated peers expected to complete the batch sooner.
             .min()
             .map(|(_, _, _, peer)| peer);
 
@@ -1057,21 +1093,6 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
 
         // find the next pending batch and request it from the peer
 
-        // randomize the peers for load balancing
-        let mut rng = rand::thread_rng();
-        let mut idle_peers = self
-            .peers
-            .iter()
-            .filter_map(|(peer, requests)| {
-                if requests.is_empty() {
-                    Some(*peer)
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<_>>();
-        idle_peers.shuffle(&mut rng);
-
         // check if we have the batch for our optimistic start. If not, request it first.
         // We wait for this batch before requesting any other batches.
         if let Some(epoch) = self.optimistic_start {
@@ -1083,18 +1104,18 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
                 return Ok(KeepChain);
             }
 
-            if let Entry::Vacant(entry) = self.batches.entry(epoch) {
-                if let Some(peer) = idle_peers.pop() {
+            if !self.batches.contains_key(&epoch) {
+                if let Some(peer) = self.next_available_peer(network, |_| true) {
                     let batch_type = network.batch_type(epoch);
                     let optimistic_batch = BatchInfo::new(&epoch, EPOCHS_PER_BATCH, batch_type);
-                    entry.insert(optimistic_batch);
+                    self.batches.insert(epoch, optimistic_batch);
                     self.send_batch(network, epoch, peer)?;
                 }
             }
             return Ok(KeepChain);
         }
 
-        while let Some(peer) = idle_peers.pop() {
+        while let Some(peer) = self.next_available_peer(network, |_| true) {
             if let Some(batch_id) = self.include_next_batch(network) {
                 // send the batch
                 self.send_batch(network, batch_id, peer)?;
@@ -1107,6 +1128,96 @@ impl<T: BeaconChainTypes> SyncingChain<T> {
         Ok(KeepChain)
     }
 
+    /// Returns the peer with spare capacity that is expected to complete a new batch the soonest,
+    /// among those accepted by `filter`.
+    fn next_available_peer(
+        &self,
+        network: &SyncNetworkContext<T>,
+        filter: impl Fn(&PeerId) -> bool,
+    ) -> Option<PeerId> {
+        let scores = &network.range_peer_scores;
+        self.peers
+            .iter()
+            .filter(|(peer, requests)| requests.len() < scores.max_batches(peer) && filter(peer))
+            .map(|(peer, requests)| {
+                (
+                    scores.expected_completion(peer, requests.len()),
+                    rand::thread_rng().gen::<u32>(),
+                    *peer,
+                )
+            })
+            .min()
+            .map(|(_, _, peer)| peer)
+    }
+
+    /// Moves the start of the batch downloads in flight back by `elapsed`.
+    #[cfg(test)]
+    pub(crate) fn backdate_downloads(&mut self, elapsed: std::time::Duration) {
+        for batch in self.batches.values_mut() {
+            batch.backdate_download(elapsed);
+        }
+    }
+
+    /// Requests the first batch still downloading from a second peer if it is taking much longer
+    /// than its peer usually needs, as it is likely holding up processing.
+    pub fn hedge_slow_batches(&mut self, network: &mut SyncNetworkContext<T>) -> ProcessingResult {
+        if !self.is_syncing() {
+            return Ok(KeepChain);
+        }
+
+        let Some((&batch_id, batch)) = self
+            .batches
+            .iter()
+            .find(|(_, batch)| matches!(batch.state(), BatchState::Downloading(..)))
+        else {
+            return Ok(KeepChain);
+        };
+        let (Some(&peer), Some(elapsed)) = (batch.current_peer(), batch.download_elapsed()) else {
+            return Ok(KeepChain);
+        };
+        if batch.hedged_peer().is_some() || !network.range_peer_scores.should_hedge(&peer, elapsed)
+        {
+            return Ok(KeepChain);
+        }
+
+        let failed_peers = batch.failed_peers();
+        let Some(hedge_peer) = self.next_available_peer(network, |candidate| {
+            *candidate != peer && !failed_peers.contains(candidate)
+        }) else {
+            return Ok(KeepChain);
+        };
+
+        let (request, batch_type) = batch.to_blocks_by_range_request();
+        match network.block_components_by_range_request(
+            hedge_peer,
+            batch_type,
+            request,
+            RangeRequestId::RangeSync {
+                chain_id: self.id,
+                batch_id,
+            },
+        ) {
+            Ok(request_id) => {
+                let batch = self.batches.get_mut(&batch_id).ok_or_else(|| {
+                    RemoveChain::WrongChainState(format!("Hedged batch not found: {}", batch_id))
+                })?;
+                batch.start_hedge(hedge_peer, request_id)?;
+                debug!(self.log, "Requesting slow batch from a second peer"; "epoch" => batch_id,
+                    "elapsed" => ?elapsed, "hedge_peer" => %hedge_peer, &batch);
+                metrics::inc_counter_vec(&metrics::SYNC_RANGE_HEDGED_BATCHES, &["requested"]);
+                if let Some(requests) = self.peers.get_mut(&hedge_peer) {
+                    requests.insert(batch_id);
+                }
+            }
+            Err(e) => {
+                // The original request is still in flight, so there is nothing to recover.
+                debug!(self.log, "Could not send hedged batch request";
+                    "batch_id" => batch_id, "error" => ?e);
+            }
+        }
+        Ok(KeepChain)
+    }
+
     /// Checks all sampling column subnets for peers. Returns `true` if there is at least one peer in
     /// every sampling column subnet.
     fn good_peers_on_sampling_subnets(
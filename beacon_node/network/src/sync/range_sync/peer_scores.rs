@@ -0,0 +1,274 @@
+//! Tracks how well each peer serves range sync batches.
+//!
+//! Range sync downloads batches from every peer of a chain in parallel. Peers differ widely in
+//! bandwidth and reliability, so rather than spreading batches evenly, batches are assigned to the
+//! peer expected to complete them soonest. Fast peers are given several batches at once, and a
+//! batch that is taking much longer than its peer usually needs is requested from a second peer,
+//! so a single slow peer does not hold up processing.
+//!
+//! Peers are rated by the blocks per second they serve, so that a peer answering a near-empty batch
+//! quickly is not rated above a peer streaming full batches.
+//!
+//! Sizing batches per peer is deliberately not done here: batches keep a fixed size of
+//! `EPOCHS_PER_BATCH`, as batch ids are their start epoch and the chain assumes consecutive batch
+//! ids are `EPOCHS_PER_BATCH` apart. Larger batches for fast peers would need batch ids that are
+//! not aligned to `EPOCHS_PER_BATCH` and are left to a separate change. Until then, fast peers get
+//! more batches in flight rather than larger ones.
+//!
+//! The scores are shared by all syncing chains, as the same peer may serve several of them.
+use crate::metrics;
+use fnv::FnvHashMap;
+use lighthouse_network::PeerId;
+use std::time::Duration;
+
+/// Weight of the latest download in the moving averages of the download rates and batch sizes.
+const RATE_WEIGHT: f64 = 0.3;
+
+/// The maximum number of batches downloaded from a single peer at the same time.
+pub const MAX_BATCHES_PER_PEER: usize = 3;
+
+/// The number of top peers whose share of the downloaded blocks is reported in the metrics.
+const METRICS_TOP_PEERS: usize = 5;
+
+/// Expected duration of a batch download for peers we have no measurements for.
+const DEFAULT_BATCH_DURATION: Duration = Duration::from_secs(5);
+
+/// A batch is hedged once it has been downloading for this many times its expected duration.
+const HEDGE_FACTOR: u32 = 3;
+
+/// A batch is never hedged before it has been downloading for this long.
+const MIN_HEDGE_DELAY: Duration = Duration::from_secs(5);
+
+/// Range sync statistics of a single peer.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PeerSyncStats {
+    /// Moving average of the blocks per second downloaded from the peer.
+    rate: Option<f64>,
+    /// Recent failed downloads. Halved on every successful download, so that the peer recovers
+    /// from past failures.
+    failures: u32,
+    /// Number of batches downloaded from the peer.
+    batches_downloaded: u64,
+    /// Number of blocks downloaded from the peer.
+    blocks_downloaded: u64,
+}
+
+/// Range sync statistics of all the peers syncing chains.
+#[derive(Default)]
+pub struct SyncPeerScores {
+    peers: FnvHashMap<PeerId, PeerSyncStats>,
+    /// Moving average of the blocks per downloaded batch, across all peers.
+    batch_blocks: Option<f64>,
+}
+
+impl SyncPeerScores {
+    /// Registers a batch with `blocks` downloaded from `peer_id` in `duration`.
+    pub fn on_download_success(&mut self, peer_id: PeerId, duration: Duration, blocks: usize) {
+        // Empty batches still take a round trip, count them as a single block. Avoid dividing by
+        // zero.
+        let rate = blocks.max(1) as f64 / duration.as_secs_f64().max(0.001);
+        metrics::observe(&metrics::SYNC_RANGE_BATCH_DOWNLOAD_RATE, rate);
+        metrics::inc_counter(&metrics::SYNC_RANGE_DOWNLOADED_BATCHES);
+        metrics::inc_counter_by(&metrics::SYNC_RANGE_DOWNLOADED_BLOCKS, blocks as u64);
+
+        self.batch_blocks = Some(moving_average(self.batch_blocks, blocks.max(1) as f64));
+        let stats = self.peers.entry(peer_id).or_default();
+        stats.rate = Some(moving_average(stats.rate, rate));
+        stats.failures /= 2;
+        stats.batches_downloaded += 1;
+        stats.blocks_downloaded += blocks as u64;
+    }
+
+    /// Registers a failed or invalid batch download from `peer_id`.
+    pub fn on_download_failure(&mut self, peer_id: PeerId) {
+        let stats = self.peers.entry(peer_id).or_default();
+        stats.failures = stats.failures.saturating_add(1);
+    }
+
+    /// Forgets the statistics of a disconnected peer.
+    pub fn remove_peer(&mut self, peer_id: &PeerId) {
+        self.peers.remove(peer_id);
+    }
+
+    pub fn get(&self, peer_id: &PeerId) -> Option<&PeerSyncStats> {
+        self.peers.get(peer_id)
+    }
+
+    /// Median download rate of the peers with measurements.
+    fn median_rate(&self) -> Option<f64> {
+        let mut rates = self
+            .peers
+            .values()
+            .filter_map(|stats| stats.rate)
+            .collect::<Vec<_>>();
+        if rates.is_empty() {
+            return None;
+        }
+        rates.sort_by(f64::total_cmp);
+        Some(rates[(rates.len() - 1) / 2])
+    }
+
+    /// The time `peer_id` is expected to take to download a batch.
+    ///
+    /// This is the average number of blocks per batch over the peer's download rate. Peers without
+    /// measurements are assumed to be as fast as the median peer. Recent failures make a peer look
+    /// proportionally slower.
+    pub fn expected_duration(&self, peer_id: &PeerId) -> Duration {
+        let stats = self.peers.get(peer_id);
+        let duration = stats
+            .and_then(|stats| stats.rate)
+            .or_else(|| self.median_rate())
+            .zip(self.batch_blocks)
+            .map(|(rate, batch_blocks)| Duration::from_secs_f64(batch_blocks / rate))
+            .unwrap_or(DEFAULT_BATCH_DURATION);
+        duration * (1 + stats.map_or(0, |stats| stats.failures))
+    }
+
+    /// The number of batches that can be downloaded from `peer_id` at the same time.
+    ///
+    /// Peers are given one batch per multiple of the median download rate they achieve, up to
+    /// `MAX_BATCHES_PER_PEER`. Peers that have failed recently, or that we have no measurements
+    /// for, are given a single batch.
+    pub fn max_batches(&self, peer_id: &PeerId) -> usize {
+        let Some(stats) = self.peers.get(peer_id) else {
+            return 1;
+        };
+        if stats.failures > 0 {
+            return 1;
+        }
+        match (stats.rate, self.median_rate()) {
+            (Some(rate), Some(median)) => {
+                ((rate / median).round() as usize).clamp(1, MAX_BATCHES_PER_PEER)
+            }
+            _ => 1,
+        }
+    }
+
+    /// The time until `peer_id` is expected to complete a new batch, given it is already
+    /// downloading `active_batches`.
+    pub fn expected_completion(&self, peer_id: &PeerId, active_batches: usize) -> Duration {
+        self.expected_duration(peer_id) * (active_batches as u32 + 1)
+    }
+
+    /// Returns `true` if a batch that has been downloading from `peer_id` for `elapsed` should be
+    /// requested from a second peer.
+    pub fn should_hedge(&self, peer_id: &PeerId, elapsed: Duration) -> bool {
+        elapsed
+            > std::cmp::max(
+                MIN_HEDGE_DELAY,
+                self.expected_duration(peer_id) * HEDGE_FACTOR,
+            )
+    }
+
+    /// Updates the share of the downloaded blocks served by the top peers.
+    ///
+    /// Only the ranks are used as labels, to keep the cardinality of the metric bounded.
+    pub fn register_metrics(&self) {
+        let mut blocks = self
+            .peers
+            .values()
+            .map(|stats| stats.blocks_downloaded)
+            .collect::<Vec<_>>();
+        blocks.sort_unstable_by(|a, b| b.cmp(a));
+        let total = blocks.iter().sum::<u64>().max(1) as f64;
+        for rank in 0..METRICS_TOP_PEERS {
+            let share = blocks.get(rank).copied().unwrap_or(0) as f64 / total;
+            metrics::set_float_gauge_vec(
+                &metrics::SYNC_RANGE_TOP_PEER_BLOCKS_SHARE,
+                &[&(rank + 1).to_string()],
+                share,
+            );
+        }
+    }
+}
+
+fn moving_average(previous: Option<f64>, latest: f64) -> f64 {
+    match previous {
+        Some(previous) => RATE_WEIGHT * latest + (1.0 - RATE_WEIGHT) * previous,
+        None => latest,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_peers_get_a_single_batch() {
+        let scores = SyncPeerScores::default();
+        let peer = PeerId::random();
+        assert_eq!(scores.max_batches(&peer), 1);
+        assert_eq!(scores.expected_duration(&peer), DEFAULT_BATCH_DURATION);
+    }
+
+    #[test]
+    fn fast_peers_get_more_batches() {
+        let mut scores = SyncPeerScores::default();
+        let fast = PeerId::random();
+        let median = PeerId::random();
+        let slow = PeerId::random();
+        scores.on_download_success(fast, Duration::from_millis(250), 32);
+        scores.on_download_success(median, Duration::from_secs(1), 32);
+        scores.on_download_success(slow, Duration::from_secs(4), 32);
+
+        assert_eq!(scores.max_batches(&fast), MAX_BATCHES_PER_PEER);
+        assert_eq!(scores.max_batches(&median), 1);
+        assert_eq!(scores.max_batches(&slow), 1);
+        assert!(
+            scores.expected_completion(&fast, 2) < scores.expected_completion(&median, 0),
+            "a busy fast peer should still beat an idle median peer"
+        );
+        assert!(scores.expected_completion(&median, 0) < scores.expected_completion(&slow, 0));
+        // Unknown peers are expected to perform like the median peer.
+        assert_eq!(
+            scores.expected_duration(&PeerId::random()),
+            scores.expected_duration(&median)
+        );
+    }
+
+    #[test]
+    fn failures_are_penalised_and_forgotten() {
+        let mut scores = SyncPeerScores::default();
+        let peer = PeerId::random();
+        scores.on_download_success(peer, Duration::from_millis(250), 32);
+        scores.on_download_success(PeerId::random(), Duration::from_secs(1), 32);
+        let expected = scores.expected_duration(&peer);
+
+        scores.on_download_failure(peer);
+        scores.on_download_failure(peer);
+        assert_eq!(scores.max_batches(&peer), 1);
+        assert_eq!(scores.expected_duration(&peer), expected * 3);
+
+        scores.on_download_success(peer, Duration::from_millis(250), 32);
+        scores.on_download_success(peer, Duration::from_millis(250), 32);
+        assert_eq!(scores.get(&peer).unwrap().failures, 0);
+        assert_eq!(scores.get(&peer).unwrap().batches_downloaded, 3);
+        assert_eq!(scores.get(&peer).unwrap().blocks_downloaded, 96);
+        assert!(scores.max_batches(&peer) > 1);
+    }
+
+    #[test]
+    fn rates_account_for_batch_size() {
+        let mut scores = SyncPeerScores::default();
+        let quick_empty = PeerId::random();
+        let full = PeerId::random();
+        // A near-empty batch answered quickly is less throughput than a full batch.
+        scores.on_download_success(quick_empty, Duration::from_millis(100), 1);
+        scores.on_download_success(full, Duration::from_millis(500), 32);
+
+        assert!(scores.get(&full).unwrap().rate > scores.get(&quick_empty).unwrap().rate);
+        assert!(scores.expected_duration(&full) < scores.expected_duration(&quick_empty));
+        assert!(scores.max_batches(&full) >= scores.max_batches(&quick_empty));
+    }
+
+    #[test]
+    fn slow_batches_are_hedged() {
+        let mut scores = SyncPeerScores::default();
+        let peer = PeerId::random();
+        assert!(!scores.should_hedge(&peer, MIN_HEDGE_DELAY));
+
+        scores.on_download_success(peer, Duration::from_secs(4), 32);
+        assert!(!scores.should_hedge(&peer, Duration::from_secs(11)));
+        assert!(scores.should_hedge(&peer, Duration::from_secs(13)));
+    }
+}
@@ -271,6 +271,7 @@ where
 
         // remove the peer from any peer pool, failing its batches
         self.remove_peer(network, peer_id);
+        network.range_peer_scores.remove_peer(peer_id);
     }
 
     /// When a peer gets removed, both the head and finalized chains need to be searched to check
@@ -381,6 +382,23 @@ where
             );
         }
     }
+
+    /// Requests slow batches from a second peer, so that a single slow peer does not stall the
+    /// syncing chains.
+    pub fn hedge_slow_batches(&mut self, network: &mut SyncNetworkContext<T>) {
+        for (removed_chain, sync_type, remove_reason) in self
+            .chains
+            .call_all(|chain| chain.hedge_slow_batches(network))
+        {
+            self.on_chain_removed(
+                removed_chain,
+                sync_type,
+                remove_reason,
+                network,
+                "batch hedged",
+            );
+        }
+    }
 }
 
 #[cfg(test)]
@@ -391,6 +409,7 @@ mod tests {
 
     use super::*;
     use crate::sync::network_context::{BlockOrBlob, RangeRequestId};
+    use crate::sync::range_sync::chain::KeepChain;
     use beacon_chain::builder::Witness;
     use beacon_chain::eth1_chain::CachingEth1Backend;
     use beacon_chain::parking_lot::RwLock;
@@ -404,6 +423,7 @@ mod tests {
     use slog::{o, Drain};
     use slot_clock::TestingSlotClock;
     use std::collections::HashSet;
+    use std::time::Duration;
     use store::MemoryStore;
     use tokio::sync::mpsc;
     use types::{FixedBytesExtended, ForkName, MinimalEthSpec as E};
@@ -843,4 +863,43 @@ mod tests {
         rig.expect_chain_segment();
         rig.expect_chain_segment();
     }
+
+    #[test]
+    fn slow_batch_is_hedged() {
+        let (mut rig, mut range) = range(false);
+        let fork = rig
+            .cx
+            .chain
+            .spec
+            .fork_name_at_epoch(rig.cx.chain.epoch().unwrap());
+
+        // A head chain one slot ahead has a single batch, downloading from the first peer.
+        let (peer1, local_info, head_info) = rig.head_peer();
+        range.add_peer(&mut rig.cx, local_info.clone(), peer1, head_info.clone());
+        let _ = rig.grab_request(&peer1, fork);
+        let peer2 = PeerId::random();
+        range.add_peer(&mut rig.cx, local_info, peer2, head_info);
+        assert!(rig.network_rx.try_recv().is_err(), "no other batch");
+
+        // The batch isn't hedged before it gets slow.
+        range.hedge_slow_batches(&mut rig.cx);
+        assert!(rig.network_rx.try_recv().is_err(), "batch not yet slow");
+
+        range.chains.call_all(|chain| {
+            chain.backdate_downloads(Duration::from_secs(60));
+            Ok(KeepChain)
+        });
+        range.hedge_slow_batches(&mut rig.cx);
+        let (block_req, blob_req_opt) = rig.grab_request(&peer2, fork);
+
+        // Hedging is done once per batch.
+        range.hedge_slow_batches(&mut rig.cx);
+        assert!(rig.network_rx.try_recv().is_err(), "batch already hedged");
+
+        // The hedged request completes the batch.
+        let (chain_id, batch_id, id) =
+            rig.complete_range_block_and_blobs_response(block_req, blob_req_opt);
+        range.blocks_by_range_response(&mut rig.cx, peer2, chain_id, batch_id, id, vec![]);
+        rig.expect_chain_segment();
+    }
 }
@@ -0,0 +1,220 @@
+//! Compares the reward and running time of packing post-Electra attestations into on-chain
+//! aggregates against the previous strategy, which combined the attestation with the most votes
+//! for each committee into a single on-chain aggregate per `AttestationData` and left the rest to
+//! the greedy maximum cover.
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use operation_pool::{pack_attestations, total_reward, PackingItem};
+use rand::Rng;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::Duration;
+use types::test_utils::{SeedableRng, XorShiftRng};
+use types::{AttestationData, Checkpoint, Hash256, Slot};
+
+/// The `MAX_ATTESTATIONS_ELECTRA` of mainnet.
+const LIMIT: usize = 8;
+
+struct Scenario {
+    name: &'static str,
+    slots: u64,
+    committees_per_slot: u64,
+    committee_size: u64,
+    /// The number of different head votes at each slot.
+    heads: u8,
+    /// The number of overlapping aggregates for each committee and head vote.
+    aggregates: usize,
+}
+
+/// The items of a scenario, and the number of votes in each item (including those which have
+/// already been included on chain).
+fn generate(scenario: &Scenario) -> (Vec<PackingItem>, Vec<usize>) {
+    let mut rng = XorShiftRng::from_seed([42; 16]);
+    let mut items = vec![];
+    let mut num_votes = vec![];
+
+    for slot in 0..scenario.slots {
+        for committee_index in 0..scenario.committees_per_slot {
+            let first_validator =
+                (slot * scenario.committees_per_slot + committee_index) * scenario.committee_size;
+            let mut voters_by_head = BTreeMap::<u8, Vec<(u64, Option<u64>)>>::new();
+            for validator_index in first_validator..first_validator + scenario.committee_size {
+                // Most validators vote for the canonical head.
+                let head = if rng.gen_bool(0.8) {
+                    0
+                } else {
+                    rng.gen_range(0..scenario.heads)
+                };
+                // Votes which have already been included on chain earn no reward.
+                let reward = (!rng.gen_bool(0.3)).then(|| rng.gen_range(1..=4) * 1_000);
+                voters_by_head
+                    .entry(head)
+                    .or_default()
+                    .push((validator_index, reward));
+            }
+
+            for (head, voters) in voters_by_head {
+                let data = AttestationData {
+                    slot: Slot::new(slot),
+                    index: 0,
+                    beacon_block_root: Hash256::repeat_byte(head),
+                    source: Checkpoint::default(),
+                    target: Checkpoint::default(),
+                };
+                for _ in 0..scenario.aggregates {
+                    let coverage = rng.gen_range(0.2..1.0);
+                    let votes = voters
+                        .iter()
+                        .filter(|_| rng.gen_bool(coverage))
+                        .collect::<Vec<_>>();
+                    if votes.is_empty() {
+                        continue;
+                    }
+                    num_votes.push(votes.len());
+                    items.push(PackingItem {
+                        data: data.clone(),
+                        committee_index: Some(committee_index),
+                        rewards: votes
+                            .iter()
+                            .filter_map(|(validator_index, reward)| {
+                                reward.map(|reward| (*validator_index, reward))
+                            })
+                            .collect(),
+                    });
+                }
+            }
+        }
+    }
+
+    (items, num_votes)
+}
+
+/// Pack the `items` using the previous strategy.
+fn pack_previous(items: &[PackingItem], num_votes: &[usize]) -> Vec<Vec<usize>> {
+    // Combine the item with the most votes for each committee.
+    let mut best_by_data = HashMap::<&AttestationData, BTreeMap<u64, usize>>::new();
+    for (index, item) in items.iter().enumerate() {
+        let Some(committee_index) = item.committee_index else {
+            continue;
+        };
+        let best = best_by_data
+            .entry(&item.data)
+            .or_default()
+            .entry(committee_index)
+            .or_insert(index);
+        if num_votes[index] > num_votes[*best] {
+            *best = index;
+        }
+    }
+    let mut candidates = best_by_data
+        .into_values()
+        .map(|best| best.into_values().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let combined = candidates.iter().flatten().copied().collect::<HashSet<_>>();
+    candidates.extend(
+        (0..items.len())
+            .filter(|index| !combined.contains(index))
+            .map(|index| vec![index]),
+    );
+
+    // Greedy maximum cover of the candidates.
+    let mut covered = HashSet::<(Slot, u64)>::new();
+    let mut solution = vec![];
+    for _ in 0..LIMIT {
+        let reward = |candidate: &Vec<usize>| -> u64 {
+            candidate
+                .iter()
+                .flat_map(|&index| &items[index].rewards)
+                .filter(|(validator_index, _)| {
+                    !covered.contains(&(items[candidate[0]].data.slot, **validator_index))
+                })
+                .map(|(_, reward)| reward)
+                .sum()
+        };
+        let Some(position) = (0..candidates.len())
+            .filter(|&position| reward(&candidates[position]) > 0)
+            .max_by_key(|&position| reward(&candidates[position]))
+        else {
+            break;
+        };
+        let best = candidates.swap_remove(position);
+        let slot = items[best[0]].data.slot;
+        covered.extend(
+            best.iter()
+                .flat_map(|&index| items[index].rewards.keys())
+                .map(|validator_index| (slot, *validator_index)),
+        );
+        solution.push(best);
+    }
+    solution
+}
+
+fn all_benches(c: &mut Criterion) {
+    let scenarios = [
+        Scenario {
+            name: "single_head",
+            slots: 2,
+            committees_per_slot: 64,
+            committee_size: 64,
+            heads: 1,
+            aggregates: 4,
+        },
+        Scenario {
+            name: "competing_heads",
+            slots: 4,
+            committees_per_slot: 64,
+            committee_size: 64,
+            heads: 3,
+            aggregates: 4,
+        },
+        Scenario {
+            name: "many_slots",
+            slots: 32,
+            committees_per_slot: 16,
+            committee_size: 32,
+            heads: 2,
+            aggregates: 2,
+        },
+    ];
+    let search_timeout = Duration::from_millis(50);
+
+    for scenario in &scenarios {
+        let (items, num_votes) = generate(scenario);
+
+        let previous_reward = total_reward(&items, &pack_previous(&items, &num_votes));
+        let greedy = pack_attestations(&items, LIMIT, None);
+        let searched = pack_attestations(&items, LIMIT, Some(search_timeout));
+        let complete = searched.search.is_some_and(|search| search.complete);
+        println!(
+            "{}: {} items, previous reward {}, greedy reward {} ({:+.2}%), searched reward {} \
+             ({:+.2}%, complete: {})",
+            scenario.name,
+            items.len(),
+            previous_reward,
+            greedy.reward,
+            change(previous_reward, greedy.reward),
+            searched.reward,
+            change(previous_reward, searched.reward),
+            complete,
+        );
+
+        let mut group = c.benchmark_group(scenario.name);
+        group.sample_size(10);
+        group.bench_function("previous", |b| {
+            b.iter(|| black_box(pack_previous(&items, &num_votes)))
+        });
+        group.bench_function("greedy", |b| {
+            b.iter(|| black_box(pack_attestations(&items, LIMIT, None)))
+        });
+        group.bench_function("search", |b| {
+            b.iter(|| black_box(pack_attestations(&items, LIMIT, Some(search_timeout))))
+        });
+        group.finish();
+    }
+}
+
+/// The percentage change from `previous` to `reward`.
+fn change(previous: u64, reward: u64) -> f64 {
+    (reward as f64 - previous as f64) * 100.0 / previous.max(1) as f64
+}
+
+criterion_group!(benches, all_benches);
+criterion_main!(benches);
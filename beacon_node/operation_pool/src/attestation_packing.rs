@@ -0,0 +1,547 @@
+//! Packing of post-Electra attestations into on-chain aggregates.
+//!
+//! From Electra, an attestation included in a block may aggregate the votes of every committee at
+//! a slot for the same `AttestationData`, but a block may only include `MAX_ATTESTATIONS_ELECTRA`
+//! attestations. The pool holds one or more aggregates for each committee, and two aggregates for
+//! the same committee can't be combined on chain unless they are disjoint (in which case they were
+//! already combined when inserted into the pool).
+//!
+//! Packing happens in two stages:
+//!
+//! 1. Candidate on-chain aggregates are built in layers for each `AttestationData`. The first
+//!    layer combines the most rewarding aggregate of each committee, the second layer combines the
+//!    most rewarding of the remaining aggregates given the votes covered by the first layer, and
+//!    so on.
+//! 2. At most `limit` candidates are chosen to maximise the total reward. This is a weighted
+//!    maximum coverage problem, which we approximate greedily using `maximum_cover`. If time
+//!    allows, the greedy solution is then improved upon by a branch and bound search, which finds
+//!    the optimal set of candidates if it completes within its time limit.
+use crate::max_cover::{maximum_cover, MaxCover};
+use crate::packing_report::ExclusionReason;
+use std::collections::{btree_map::Entry, BTreeMap, HashMap, HashSet};
+use std::time::{Duration, Instant};
+use types::{AttestationData, Slot};
+
+/// An attestation from the pool, which may be packed into an on-chain aggregate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackingItem {
+    pub data: AttestationData,
+    /// The committee which signed the attestation.
+    ///
+    /// This is `None` for attestations which already span multiple committees, which can't be
+    /// combined with any other attestation.
+    pub committee_index: Option<u64>,
+    /// The reward for including each validator's vote for the first time.
+    pub rewards: HashMap<u64, u64>,
+}
+
+/// An item which was left out of the packed attestations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExcludedPackingItem {
+    /// The position of the item in the packed items.
+    pub index: usize,
+    /// The rewards of the validators whose votes are not covered by the packed attestations.
+    pub fresh_validators_rewards: HashMap<u64, u64>,
+    pub reason: ExclusionReason,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PackingSearchStats {
+    /// The number of partial solutions visited.
+    pub nodes: usize,
+    /// Whether the search completed within its time limit, in which case no other combination
+    /// of the candidate aggregates is more rewarding.
+    pub complete: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackingSolution {
+    /// The on-chain aggregates in decreasing order of reward, each given by the positions of its
+    /// items in increasing committee order.
+    pub aggregates: Vec<Vec<usize>>,
+    /// The total reward of the `aggregates`.
+    pub reward: u64,
+    /// The total reward of the greedy solution.
+    pub greedy_reward: u64,
+    /// The statistics of the search, if one was run.
+    pub search: Option<PackingSearchStats>,
+    pub excluded: Vec<ExcludedPackingItem>,
+}
+
+/// Pack `items` into at most `limit` on-chain aggregates.
+///
+/// If a `search_timeout` is provided, up to that much time is spent searching for a more
+/// rewarding solution than the greedy solution.
+pub fn pack_attestations(
+    items: &[PackingItem],
+    limit: usize,
+    search_timeout: Option<Duration>,
+) -> PackingSolution {
+    let candidates = build_candidates(items, limit);
+
+    let greedy = maximum_cover(candidates.clone(), limit, "on_chain_aggregates");
+    let greedy_reward = greedy
+        .included
+        .iter()
+        .map(|candidate| candidate.score() as u64)
+        .sum();
+    let mut aggregates = greedy
+        .included
+        .into_iter()
+        .map(|candidate| candidate.items.items)
+        .collect::<Vec<_>>();
+    let mut reward = greedy_reward;
+
+    let search = search_timeout.map(|timeout| {
+        let mut search = Search::new(candidates, greedy_reward, Instant::now() + timeout);
+        search.search(0, limit, 0);
+        if let Some(best) = search.best.take() {
+            aggregates = search.order_by_reward(&best);
+            reward = search.best_reward;
+        }
+        PackingSearchStats {
+            nodes: search.nodes,
+            complete: !search.timed_out,
+        }
+    });
+
+    let excluded = excluded_items(items, &aggregates);
+    PackingSolution {
+        aggregates,
+        reward,
+        greedy_reward,
+        search,
+        excluded,
+    }
+}
+
+/// The total reward of including the `aggregates` of `items` in a block.
+pub fn total_reward(items: &[PackingItem], aggregates: &[Vec<usize>]) -> u64 {
+    aggregates
+        .iter()
+        .flatten()
+        .flat_map(|&index| votes(&items[index]))
+        .collect::<HashMap<_, _>>()
+        .values()
+        .sum()
+}
+
+/// The rewards of an item keyed by `(slot, validator_index)`, which identifies a vote across
+/// both epochs.
+fn votes(item: &PackingItem) -> impl Iterator<Item = ((Slot, u64), u64)> + '_ {
+    item.rewards
+        .iter()
+        .map(|(&validator_index, &reward)| ((item.data.slot, validator_index), reward))
+}
+
+/// The positions of the items in a candidate on-chain aggregate.
+#[derive(Debug, Clone)]
+struct CandidateItems {
+    slot: Slot,
+    items: Vec<usize>,
+}
+
+#[derive(Debug, Clone)]
+struct Candidate {
+    items: CandidateItems,
+    /// The rewards of the votes which the candidate would include.
+    rewards: HashMap<u64, u64>,
+}
+
+impl MaxCover for Candidate {
+    type Object = Vec<usize>;
+    type Intermediate = CandidateItems;
+    type Set = HashMap<u64, u64>;
+
+    fn intermediate(&self) -> &CandidateItems {
+        &self.items
+    }
+
+    fn convert_to_object(items: &CandidateItems) -> Vec<usize> {
+        items.items.clone()
+    }
+
+    fn covering_set(&self) -> &HashMap<u64, u64> {
+        &self.rewards
+    }
+
+    /// A validator is only a member of one committee per epoch, so only the candidates for the
+    /// same slot can include the same votes.
+    fn update_covering_set(&mut self, best: &CandidateItems, covered: &HashMap<u64, u64>) {
+        if self.items.slot == best.slot {
+            self.rewards
+                .retain(|validator_index, _| !covered.contains_key(validator_index));
+        }
+    }
+
+    fn score(&self) -> usize {
+        self.rewards.values().sum::<u64>() as usize
+    }
+}
+
+/// Build up to `limit` layers of candidate on-chain aggregates for each `AttestationData`.
+///
+/// Items which span multiple committees are candidates by themselves.
+fn build_candidates(items: &[PackingItem], limit: usize) -> Vec<Candidate> {
+    let mut candidates = vec![];
+    let mut items_by_data = HashMap::<&AttestationData, Vec<usize>>::new();
+    for (index, item) in items.iter().enumerate() {
+        if item.committee_index.is_some() {
+            items_by_data.entry(&item.data).or_default().push(index);
+        } else {
+            candidates.push(Candidate {
+                items: CandidateItems {
+                    slot: item.data.slot,
+                    items: vec![index],
+                },
+                rewards: item.rewards.clone(),
+            });
+        }
+    }
+
+    for (data, mut remaining) in items_by_data {
+        let mut covered = HashSet::<u64>::new();
+        for _ in 0..limit {
+            // The most rewarding remaining item of each committee, given the votes covered by the
+            // previous layers.
+            let mut best_by_committee = BTreeMap::<u64, (u64, usize)>::new();
+            for &index in &remaining {
+                let item = &items[index];
+                let reward = item
+                    .rewards
+                    .iter()
+                    .filter(|(validator_index, _)| !covered.contains(*validator_index))
+                    .map(|(_, reward)| reward)
+                    .sum::<u64>();
+                let Some(committee_index) = item.committee_index.filter(|_| reward > 0) else {
+                    continue;
+                };
+                match best_by_committee.entry(committee_index) {
+                    Entry::Vacant(entry) => {
+                        entry.insert((reward, index));
+                    }
+                    Entry::Occupied(mut entry) if entry.get().0 < reward => {
+                        entry.insert((reward, index));
+                    }
+                    Entry::Occupied(_) => {}
+                }
+            }
+            if best_by_committee.is_empty() {
+                break;
+            }
+
+            let layer = best_by_committee
+                .into_values()
+                .map(|(_, index)| index)
+                .collect::<Vec<_>>();
+            let rewards = layer
+                .iter()
+                .flat_map(|&index| &items[index].rewards)
+                .filter(|(validator_index, _)| !covered.contains(*validator_index))
+                .map(|(&validator_index, &reward)| (validator_index, reward))
+                .collect::<HashMap<_, _>>();
+            covered.extend(rewards.keys().copied());
+            remaining.retain(|index| !layer.contains(index));
+            candidates.push(Candidate {
+                items: CandidateItems {
+                    slot: data.slot,
+                    items: layer,
+                },
+                rewards,
+            });
+        }
+    }
+
+    candidates
+}
+
+/// Describe the items which are not part of the `aggregates`.
+fn excluded_items(items: &[PackingItem], aggregates: &[Vec<usize>]) -> Vec<ExcludedPackingItem> {
+    let included = aggregates.iter().flatten().copied().collect::<HashSet<_>>();
+    let covered = included
+        .iter()
+        .flat_map(|&index| votes(&items[index]).map(|(vote, _)| vote))
+        .collect::<HashSet<_>>();
+
+    items
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| !included.contains(index))
+        .map(|(index, item)| {
+            let fresh_validators_rewards = votes(item)
+                .filter(|(vote, _)| !covered.contains(vote))
+                .map(|((_, validator_index), reward)| (validator_index, reward))
+                .collect::<HashMap<_, _>>();
+            let reason = if fresh_validators_rewards.is_empty() {
+                ExclusionReason::Superseded
+            } else {
+                ExclusionReason::MaxCoverLost
+            };
+            ExcludedPackingItem {
+                index,
+                fresh_validators_rewards,
+                reason,
+            }
+        })
+        .collect()
+}
+
+struct SearchCandidate {
+    items: Vec<usize>,
+    votes: Vec<((Slot, u64), u64)>,
+}
+
+/// A depth-first branch and bound search for the most rewarding set of candidates.
+struct Search {
+    /// The candidates in decreasing order of reward.
+    candidates: Vec<SearchCandidate>,
+    /// The total reward of the first `i` candidates, for each `i`.
+    prefix_rewards: Vec<u64>,
+    /// The votes covered by the `chosen` candidates.
+    covered: HashSet<(Slot, u64)>,
+    chosen: Vec<usize>,
+    /// The most rewarding set of candidates found, if it is better than the greedy solution.
+    best: Option<Vec<usize>>,
+    best_reward: u64,
+    deadline: Instant,
+    nodes: usize,
+    timed_out: bool,
+}
+
+impl Search {
+    fn new(candidates: Vec<Candidate>, greedy_reward: u64, deadline: Instant) -> Self {
+        let mut candidates = candidates
+            .into_iter()
+            .map(|candidate| SearchCandidate {
+                votes: candidate
+                    .rewards
+                    .iter()
+                    .map(|(&validator_index, &reward)| {
+                        ((candidate.items.slot, validator_index), reward)
+                    })
+                    .collect(),
+                items: candidate.items.items,
+            })
+            .filter(|candidate| !candidate.votes.is_empty())
+            .collect::<Vec<_>>();
+        candidates.sort_unstable_by_key(|candidate| std::cmp::Reverse(candidate.reward()));
+
+        let mut prefix_rewards = vec![0];
+        for candidate in &candidates {
+            let total = prefix_rewards.last().copied().unwrap_or(0);
+            prefix_rewards.push(total + candidate.reward());
+        }
+
+        Self {
+            candidates,
+            prefix_rewards,
+            covered: HashSet::new(),
+            chosen: vec![],
+            best: None,
+            best_reward: greedy_reward,
+            deadline,
+            nodes: 0,
+            timed_out: false,
+        }
+    }
+
+    /// Extend the `chosen` candidates with up to `remaining` candidates from `start` onwards.
+    fn search(&mut self, start: usize, remaining: usize, reward: u64) {
+        if reward > self.best_reward {
+            self.best_reward = reward;
+            self.best = Some(self.chosen.clone());
+        }
+        if remaining == 0 {
+            return;
+        }
+
+        for index in start..self.candidates.len() {
+            // No candidate can add more than its own reward, and the candidates are sorted, so
+            // neither this candidate nor any later candidate can improve on the best solution.
+            let end = std::cmp::min(index + remaining, self.candidates.len());
+            let bound = self.prefix_rewards[end] - self.prefix_rewards[index];
+            if self.timed_out || reward + bound <= self.best_reward {
+                return;
+            }
+
+            // Visiting a node hashes every vote of the candidate, which dwarfs the cost of reading
+            // the clock, so the deadline is checked at every node.
+            self.nodes += 1;
+            if Instant::now() >= self.deadline {
+                self.timed_out = true;
+                return;
+            }
+
+            let newly_covered = self.candidates[index]
+                .votes
+                .iter()
+                .filter(|(vote, _)| !self.covered.contains(vote))
+                .copied()
+                .collect::<Vec<_>>();
+            if newly_covered.is_empty() {
+                continue;
+            }
+            let gain = newly_covered.iter().map(|(_, reward)| reward).sum::<u64>();
+
+            self.covered
+                .extend(newly_covered.iter().map(|(vote, _)| *vote));
+            self.chosen.push(index);
+            self.search(index + 1, remaining - 1, reward + gain);
+            self.chosen.pop();
+            for (vote, _) in &newly_covered {
+                self.covered.remove(vote);
+            }
+        }
+    }
+
+    /// Order the `chosen` candidates so that each adds the most reward to those before it.
+    fn order_by_reward(&self, chosen: &[usize]) -> Vec<Vec<usize>> {
+        let mut remaining = chosen.to_vec();
+        let mut covered = HashSet::<(Slot, u64)>::new();
+        let mut ordered = vec![];
+        while let Some((position, _)) = remaining
+            .iter()
+            .map(|&index| {
+                self.candidates[index]
+                    .votes
+                    .iter()
+                    .filter(|(vote, _)| !covered.contains(vote))
+                    .map(|(_, reward)| reward)
+                    .sum::<u64>()
+            })
+            .enumerate()
+            .max_by_key(|&(_, gain)| gain)
+        {
+            let candidate = &self.candidates[remaining.swap_remove(position)];
+            covered.extend(candidate.votes.iter().map(|(vote, _)| *vote));
+            ordered.push(candidate.items.clone());
+        }
+        ordered
+    }
+}
+
+impl SearchCandidate {
+    fn reward(&self) -> u64 {
+        self.votes.iter().map(|(_, reward)| reward).sum()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use types::{Checkpoint, Hash256};
+
+    fn data(slot: u64, head: u8) -> AttestationData {
+        AttestationData {
+            slot: Slot::new(slot),
+            index: 0,
+            beacon_block_root: Hash256::repeat_byte(head),
+            source: Checkpoint::default(),
+            target: Checkpoint::default(),
+        }
+    }
+
+    fn item(data: AttestationData, committee_index: u64, rewards: &[(u64, u64)]) -> PackingItem {
+        PackingItem {
+            data,
+            committee_index: Some(committee_index),
+            rewards: rewards.iter().copied().collect(),
+        }
+    }
+
+    #[test]
+    fn aggregates_across_committees_by_reward() {
+        let items = vec![
+            // The attestation of committee 0 with the most votes is less rewarding.
+            item(data(1, 1), 0, &[(0, 1), (1, 1), (2, 1)]),
+            item(data(1, 1), 0, &[(0, 1), (3, 5)]),
+            item(data(1, 1), 2, &[(10, 1)]),
+            item(data(1, 1), 1, &[(20, 2)]),
+            // Attestations for a different head can't be aggregated with the others.
+            item(data(1, 2), 3, &[(30, 1)]),
+        ];
+
+        // The leftover attestation of committee 0 forms a second layer.
+        let solution = pack_attestations(&items, 2, None);
+        assert_eq!(solution.aggregates, vec![vec![1, 3, 2], vec![0]]);
+        assert_eq!(solution.reward, 11);
+        assert_eq!(solution.reward, total_reward(&items, &solution.aggregates));
+        assert_eq!(
+            solution.excluded,
+            vec![ExcludedPackingItem {
+                index: 4,
+                fresh_validators_rewards: [(30, 1)].into_iter().collect(),
+                reason: ExclusionReason::MaxCoverLost,
+            }]
+        );
+
+        let solution = pack_attestations(&items, 3, None);
+        assert_eq!(solution.aggregates.len(), 3);
+        assert_eq!(solution.reward, 12);
+        assert!(solution.excluded.is_empty());
+    }
+
+    #[test]
+    fn search_improves_on_greedy() {
+        // Conflicting votes at the same slot, for which the greedy solution is sub-optimal.
+        let items = vec![
+            item(data(1, 1), 0, &[(1, 1), (2, 1), (3, 1), (4, 1)]),
+            item(data(1, 2), 0, &[(1, 1), (2, 1), (5, 1)]),
+            item(data(1, 3), 0, &[(3, 1), (4, 1), (6, 1)]),
+        ];
+
+        let greedy = pack_attestations(&items, 2, None);
+        assert_eq!(greedy.aggregates[0], vec![0]);
+        assert_eq!(greedy.reward, 5);
+        assert_eq!(greedy.search, None);
+
+        let solution = pack_attestations(&items, 2, Some(Duration::from_secs(60)));
+        assert_eq!(solution.greedy_reward, 5);
+        assert_eq!(solution.reward, 6);
+        assert_eq!(solution.reward, total_reward(&items, &solution.aggregates));
+        assert!(solution.search.unwrap().complete);
+        assert_eq!(
+            solution.excluded,
+            vec![ExcludedPackingItem {
+                index: 0,
+                fresh_validators_rewards: HashMap::new(),
+                reason: ExclusionReason::Superseded,
+            }]
+        );
+    }
+
+    #[test]
+    fn search_stops_at_deadline() {
+        let items = vec![
+            item(data(1, 1), 0, &[(1, 1), (2, 1), (3, 1), (4, 1)]),
+            item(data(1, 2), 0, &[(1, 1), (2, 1), (5, 1)]),
+            item(data(1, 3), 0, &[(3, 1), (4, 1), (6, 1)]),
+        ];
+
+        // The search gives up at its first node, leaving the greedy solution in place.
+        let greedy = pack_attestations(&items, 2, None);
+        let solution = pack_attestations(&items, 2, Some(Duration::ZERO));
+        assert_eq!(
+            solution.search,
+            Some(PackingSearchStats {
+                nodes: 1,
+                complete: false,
+            })
+        );
+        assert_eq!(solution.aggregates, greedy.aggregates);
+        assert_eq!(solution.reward, greedy.reward);
+    }
+
+    #[test]
+    fn votes_in_different_slots_do_not_conflict() {
+        // The same validator votes in both epochs.
+        let items = vec![
+            item(data(1, 1), 0, &[(1, 3)]),
+            item(data(33, 1), 0, &[(1, 2)]),
+        ];
+
+        let solution = pack_attestations(&items, 2, Some(Duration::from_secs(60)));
+        assert_eq!(solution.aggregates, vec![vec![0], vec![1]]);
+        assert_eq!(solution.reward, 5);
+    }
+}
@@ -1,6 +1,6 @@
 use crate::AttestationStats;
 use itertools::Itertools;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::HashMap;
 use types::{
     attestation::{AttestationBase, AttestationElectra},
     superstruct, AggregateSignature, Attestation, AttestationData, BeaconState, BitList, BitVector,
@@ -13,7 +13,7 @@ pub struct CheckpointKey {
     pub target_epoch: Epoch,
 }
 
-#[derive(Debug, PartialEq, Eq, Hash)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub struct CompactAttestationData {
     pub slot: Slot,
     pub index: u64,
@@ -21,8 +21,11 @@ pub struct CompactAttestationData {
     pub target_root: Hash256,
 }
 
-#[superstruct(variants(Base, Electra), variant_attributes(derive(Debug, PartialEq,)))]
-#[derive(Debug, PartialEq)]
+#[superstruct(
+    variants(Base, Electra),
+    variant_attributes(derive(Debug, PartialEq, Clone))
+)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct CompactIndexedAttestation<E: EthSpec> {
     pub attesting_indices: Vec<u64>,
     #[superstruct(only(Base), partial_getter(rename = "aggregation_bits_base"))]
@@ -119,6 +122,14 @@ impl<E: EthSpec> CompactAttestationRef<'_, E> {
         }
     }
 
+    pub fn clone_as_split_attestation(&self) -> SplitAttestation<E> {
+        SplitAttestation {
+            checkpoint: *self.checkpoint,
+            data: self.data.clone(),
+            indexed: self.indexed.clone(),
+        }
+    }
+
     pub fn clone_as_attestation(&self) -> Attestation<E> {
         match self.indexed {
             CompactIndexedAttestation::Base(indexed_att) => Attestation::Base(AttestationBase {
@@ -136,6 +147,35 @@ impl<E: EthSpec> CompactAttestationRef<'_, E> {
             }
         }
     }
+
+    /// Aggregate `attestations` for the same data signed by different committees into a single
+    /// on-chain attestation.
+    ///
+    /// The attestations must each be signed by a single committee and be given in increasing
+    /// committee order, otherwise `None` is returned.
+    pub fn aggregate_across_committees(attestations: &[Self]) -> Option<Attestation<E>> {
+        let (first, rest) = attestations.split_first()?;
+        if rest.is_empty() {
+            return Some(first.clone_as_attestation());
+        }
+        let CompactIndexedAttestation::Electra(first_indexed) = first.indexed else {
+            return None;
+        };
+        let mut aggregate = first_indexed.clone();
+        for attestation in rest {
+            let CompactIndexedAttestation::Electra(indexed) = attestation.indexed else {
+                return None;
+            };
+            aggregate.aggregate_with_disjoint_committees(indexed)?;
+        }
+        let aggregate = CompactIndexedAttestation::Electra(aggregate);
+        let aggregate_ref = CompactAttestationRef {
+            checkpoint: first.checkpoint,
+            data: first.data,
+            indexed: &aggregate,
+        };
+        Some(aggregate_ref.clone_as_attestation())
+    }
 }
 
 impl CheckpointKey {
@@ -328,88 +368,6 @@ impl<E: EthSpec> AttestationMap<E> {
         }
     }
 
-    /// Aggregate Electra attestations for the same attestation data signed by different
-    /// committees.
-    ///
-    /// Non-Electra attestations are left as-is.
-    pub fn aggregate_across_committees(&mut self, checkpoint_key: CheckpointKey) {
-        let Some(attestation_map) = self.checkpoint_map.get_mut(&checkpoint_key) else {
-            return;
-        };
-        for compact_indexed_attestations in attestation_map.attestations.values_mut() {
-            let unaggregated_attestations = std::mem::take(compact_indexed_attestations);
-            let mut aggregated_attestations: Vec<CompactIndexedAttestation<E>> = vec![];
-
-            // Aggregate the best attestations for each committee and leave the rest.
-            let mut best_attestations_by_committee: BTreeMap<
-                u64,
-                CompactIndexedAttestationElectra<E>,
-            > = BTreeMap::new();
-
-            for committee_attestation in unaggregated_attestations {
-                let mut electra_attestation = match committee_attestation {
-                    CompactIndexedAttestation::Electra(att)
-                        if att.committee_bits.num_set_bits() == 1 =>
-                    {
-                        att
-                    }
-                    CompactIndexedAttestation::Electra(att) => {
-                        // Aggregate already covers multiple committees, leave it as-is.
-                        aggregated_attestations.push(CompactIndexedAttestation::Electra(att));
-                        continue;
-                    }
-                    CompactIndexedAttestation::Base(att) => {
-                        // Leave as-is.
-                        aggregated_attestations.push(CompactIndexedAttestation::Base(att));
-                        continue;
-                    }
-                };
-                if let Some(committee_index) = electra_attestation.committee_index() {
-                    if let Some(existing_attestation) =
-                        best_attestations_by_committee.get_mut(&committee_index)
-                    {
-                        // Search for the best (most aggregation bits) attestation for this committee
-                        // index.
-                        if electra_attestation.aggregation_bits.num_set_bits()
-                            > existing_attestation.aggregation_bits.num_set_bits()
-                        {
-                            // New attestation is better than the previously known one for this
-                            // committee. Replace it.
-                            std::mem::swap(existing_attestation, &mut electra_attestation);
-                        }
-                        // Put the inferior attestation into the list of aggregated attestations
-                        // without performing any cross-committee aggregation.
-                        aggregated_attestations
-                            .push(CompactIndexedAttestation::Electra(electra_attestation));
-                    } else {
-                        // First attestation seen for this committee. Place it in the map
-                        // provisionally.
-                        best_attestations_by_committee.insert(committee_index, electra_attestation);
-                    }
-                }
-            }
-
-            if let Some(on_chain_aggregate) =
-                Self::compute_on_chain_aggregate(best_attestations_by_committee)
-            {
-                aggregated_attestations
-                    .push(CompactIndexedAttestation::Electra(on_chain_aggregate));
-            }
-
-            *compact_indexed_attestations = aggregated_attestations;
-        }
-    }
-
-    pub fn compute_on_chain_aggregate(
-        mut attestations_by_committee: BTreeMap<u64, CompactIndexedAttestationElectra<E>>,
-    ) -> Option<CompactIndexedAttestationElectra<E>> {
-        let (_, mut on_chain_aggregate) = attestations_by_committee.pop_first()?;
-        for (_, attestation) in attestations_by_committee {
-            on_chain_aggregate.aggregate_with_disjoint_committees(&attestation);
-        }
-        Some(on_chain_aggregate)
-    }
-
     /// Iterate all attestations matching the given `checkpoint_key`.
     pub fn get_attestations<'a>(
         &'a self,
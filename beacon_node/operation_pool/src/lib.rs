@@ -1,17 +1,26 @@
 mod attestation;
+mod attestation_packing;
 mod attestation_storage;
 mod attester_slashing;
 mod bls_to_execution_changes;
 mod max_cover;
 mod metrics;
+mod packing_report;
 mod persistence;
 mod reward_cache;
 mod sync_aggregate_id;
 
 pub use crate::bls_to_execution_changes::ReceivedPreCapella;
 pub use attestation::{earliest_attestation_validators, AttMaxCover};
+pub use attestation_packing::{
+    pack_attestations, total_reward, ExcludedPackingItem, PackingItem, PackingSearchStats,
+    PackingSolution,
+};
 pub use attestation_storage::{CompactAttestationRef, SplitAttestation};
 pub use max_cover::MaxCover;
+pub use packing_report::{
+    ExcludedAttestation, ExcludedOperation, ExclusionReason, PackingReport, PackingReportCache,
+};
 pub use persistence::{
     PersistedOperationPool, PersistedOperationPoolV15, PersistedOperationPoolV20,
 };
@@ -19,11 +28,11 @@ pub use reward_cache::RewardCache;
 use state_processing::epoch_cache::is_epoch_cache_initialized;
 use types::EpochCacheError;
 
-use crate::attestation_storage::{AttestationMap, CheckpointKey};
+use crate::attestation_storage::{AttestationMap, CheckpointKey, CompactIndexedAttestation};
 use crate::bls_to_execution_changes::BlsToExecutionChanges;
 use crate::sync_aggregate_id::SyncAggregateId;
 use attester_slashing::AttesterSlashingMaxCover;
-use max_cover::maximum_cover;
+use max_cover::{maximum_cover, MaxCoverSolution};
 use parking_lot::{RwLock, RwLockWriteGuard};
 use rand::seq::SliceRandom;
 use rand::thread_rng;
@@ -35,11 +44,13 @@ use state_processing::{SigVerifiedOp, VerifyOperation};
 use std::collections::{hash_map::Entry, HashMap, HashSet};
 use std::marker::PhantomData;
 use std::ptr;
+use std::time::Duration;
 use types::{
     sync_aggregate::Error as SyncAggregateError, typenum::Unsigned, AbstractExecPayload,
-    Attestation, AttestationData, AttesterSlashing, BeaconState, BeaconStateError, ChainSpec,
-    Epoch, EthSpec, ProposerSlashing, SignedBeaconBlock, SignedBlsToExecutionChange,
-    SignedVoluntaryExit, Slot, SyncAggregate, SyncCommitteeContribution, Validator,
+    Attestation, AttestationData, AttesterSlashing, AttesterSlashingRef, BeaconState,
+    BeaconStateError, ChainSpec, Epoch, EthSpec, ProposerSlashing, SignedBeaconBlock,
+    SignedBlsToExecutionChange, SignedVoluntaryExit, Slot, SyncAggregate,
+    SyncCommitteeContribution, Validator,
 };
 
 type SyncContributions<E> = RwLock<HashMap<SyncAggregateId, Vec<SyncCommitteeContribution<E>>>>;
@@ -60,6 +71,8 @@ pub struct OperationPool<E: EthSpec + Default> {
     bls_to_execution_changes: RwLock<BlsToExecutionChanges<E>>,
     /// Reward cache for accelerating attestation packing.
     reward_cache: RwLock<RewardCache>,
+    /// The operations excluded from recently packed blocks.
+    packing_reports: RwLock<PackingReportCache>,
     _phantom: PhantomData<E>,
 }
 
@@ -217,7 +230,9 @@ impl<E: EthSpec> OperationPool<E> {
         self.attestations.read().stats()
     }
 
-    /// Return all valid attestations for the given epoch, for use in max cover.
+    /// Return all attestations for the given epoch, for use in max cover.
+    ///
+    /// Attestations which are invalid for inclusion in a block are returned as errors.
     #[allow(clippy::too_many_arguments)]
     fn get_valid_attestations_for_epoch<'a>(
         &'a self,
@@ -226,18 +241,20 @@ impl<E: EthSpec> OperationPool<E> {
         state: &'a BeaconState<E>,
         reward_cache: &'a RewardCache,
         total_active_balance: u64,
-        validity_filter: impl FnMut(&CompactAttestationRef<'a, E>) -> bool + Send,
+        mut validity_filter: impl FnMut(&CompactAttestationRef<'a, E>) -> bool + Send,
         spec: &'a ChainSpec,
-    ) -> impl Iterator<Item = AttMaxCover<'a, E>> + Send {
+    ) -> impl Iterator<Item = Result<AttMaxCover<'a, E>, CompactAttestationRef<'a, E>>> + Send {
         all_attestations
             .get_attestations(checkpoint_key)
-            .filter(|att| {
-                att.data.slot + spec.min_attestation_inclusion_delay <= state.slot()
+            .map(move |att| {
+                let valid = att.data.slot + spec.min_attestation_inclusion_delay <= state.slot()
                     && state.slot() <= att.data.slot + E::slots_per_epoch()
-            })
-            .filter(validity_filter)
-            .filter_map(move |att| {
-                AttMaxCover::new(att, state, reward_cache, total_active_balance, spec)
+                    && validity_filter(&att);
+                if !valid {
+                    return Err(att);
+                }
+                AttMaxCover::new(att.clone(), state, reward_cache, total_active_balance, spec)
+                    .ok_or(att)
             })
     }
 
@@ -247,11 +264,15 @@ impl<E: EthSpec> OperationPool<E> {
     /// before an approximately optimal bundle is constructed. We use it to provide access
     /// to the fork choice data from the `BeaconChain` struct that doesn't logically belong
     /// in the operation pool.
+    ///
+    /// Post-Electra, up to `search_timeout` is spent searching for a more rewarding bundle than
+    /// the greedy one.
     pub fn get_attestations(
         &self,
         state: &BeaconState<E>,
         prev_epoch_validity_filter: impl for<'a> FnMut(&CompactAttestationRef<'a, E>) -> bool + Send,
         curr_epoch_validity_filter: impl for<'a> FnMut(&CompactAttestationRef<'a, E>) -> bool + Send,
+        search_timeout: Option<Duration>,
         spec: &ChainSpec,
     ) -> Result<Vec<Attestation<E>>, OpPoolError> {
         let fork_name = state.fork_name_unchecked();
@@ -277,20 +298,12 @@ impl<E: EthSpec> OperationPool<E> {
         let reward_cache = RwLockWriteGuard::downgrade(reward_cache);
         drop(reward_timer);
 
-        // Split attestations for the previous & current epochs, so that we
+        // Split attestations for the previous & current epochs, so that prior to Electra we
         // can optimise them individually in parallel.
         let mut num_prev_valid = 0_i64;
         let mut num_curr_valid = 0_i64;
 
-        // TODO(electra): Work out how to do this more elegantly. This is a bit of a hack.
-        let mut all_attestations = self.attestations.write();
-
-        if fork_name.electra_enabled() {
-            all_attestations.aggregate_across_committees(prev_epoch_key);
-            all_attestations.aggregate_across_committees(curr_epoch_key);
-        }
-
-        let all_attestations = parking_lot::RwLockWriteGuard::downgrade(all_attestations);
+        let all_attestations = self.attestations.read();
 
         let prev_epoch_att = self
             .get_valid_attestations_for_epoch(
@@ -302,7 +315,11 @@ impl<E: EthSpec> OperationPool<E> {
                 prev_epoch_validity_filter,
                 spec,
             )
-            .inspect(|_| num_prev_valid += 1);
+            .inspect(|att| {
+                if att.is_ok() {
+                    num_prev_valid += 1
+                }
+            });
         let curr_epoch_att = self
             .get_valid_attestations_for_epoch(
                 &curr_epoch_key,
@@ -313,7 +330,11 @@ impl<E: EthSpec> OperationPool<E> {
                 curr_epoch_validity_filter,
                 spec,
             )
-            .inspect(|_| num_curr_valid += 1);
+            .inspect(|att| {
+                if att.is_ok() {
+                    num_curr_valid += 1
+                }
+            });
 
         let curr_epoch_limit = if fork_name.electra_enabled() {
             E::MaxAttestationsElectra::to_usize()
@@ -330,30 +351,81 @@ impl<E: EthSpec> OperationPool<E> {
             curr_epoch_limit
         };
 
-        let (prev_cover, curr_cover) = rayon::join(
-            move || {
-                let _timer = metrics::start_timer(&metrics::ATTESTATION_PREV_EPOCH_PACKING_TIME);
-                // If we're in the genesis epoch, just use the current epoch attestations.
-                if prev_epoch_key == curr_epoch_key {
-                    vec![]
-                } else {
-                    maximum_cover(prev_epoch_att, prev_epoch_limit, "prev_epoch_attestations")
-                }
-            },
-            move || {
-                let _timer = metrics::start_timer(&metrics::ATTESTATION_CURR_EPOCH_PACKING_TIME);
-                maximum_cover(curr_epoch_att, curr_epoch_limit, "curr_epoch_attestations")
-            },
-        );
+        let (attestations, excluded) = if fork_name.electra_enabled() {
+            // Attestations from both epochs are packed together, as they share the same limit.
+            let _timer = metrics::start_timer(&metrics::ATTESTATION_PACKING_TIME);
+            // If we're in the genesis epoch, just use the current epoch attestations.
+            let prev_epoch_att = (prev_epoch_key != curr_epoch_key)
+                .then_some(prev_epoch_att)
+                .into_iter()
+                .flatten();
+            let candidates = PackingCandidates::new(prev_epoch_att.chain(curr_epoch_att));
+            // Release the pool before packing, as the search may take up to `search_timeout`.
+            drop(all_attestations);
+            candidates.pack_on_chain_aggregates(curr_epoch_limit, search_timeout)
+        } else {
+            let ((prev_cover, prev_invalid), (curr_cover, curr_invalid)) = rayon::join(
+                move || {
+                    let _timer =
+                        metrics::start_timer(&metrics::ATTESTATION_PREV_EPOCH_PACKING_TIME);
+                    // If we're in the genesis epoch, just use the current epoch attestations.
+                    if prev_epoch_key == curr_epoch_key {
+                        let empty = MaxCoverSolution {
+                            included: vec![],
+                            excluded: vec![],
+                        };
+                        (empty, vec![])
+                    } else {
+                        maximum_cover_attestations(
+                            prev_epoch_att,
+                            prev_epoch_limit,
+                            "prev_epoch_attestations",
+                        )
+                    }
+                },
+                move || {
+                    let _timer =
+                        metrics::start_timer(&metrics::ATTESTATION_CURR_EPOCH_PACKING_TIME);
+                    maximum_cover_attestations(
+                        curr_epoch_att,
+                        curr_epoch_limit,
+                        "curr_epoch_attestations",
+                    )
+                },
+            );
+
+            let (attestations, over_limit) = max_cover::merge_solutions(
+                curr_cover.included,
+                prev_cover.included,
+                curr_epoch_limit,
+            );
+
+            let mut excluded = prev_invalid
+                .into_iter()
+                .chain(curr_invalid)
+                .map(|att| excluded_attestation(&att, None, ExclusionReason::InvalidAgainstHead))
+                .collect::<Vec<_>>();
+            let uncovered = prev_cover
+                .excluded
+                .into_iter()
+                .chain(curr_cover.excluded)
+                .chain(
+                    over_limit
+                        .into_iter()
+                        .map(|cover| (cover, ExclusionReason::LimitReached)),
+                );
+            excluded.extend(uncovered.map(|(cover, reason)| {
+                excluded_attestation(&cover.att, Some(&cover.fresh_validators_rewards), reason)
+            }));
+            (attestations, excluded)
+        };
 
         metrics::set_gauge(&metrics::NUM_PREV_EPOCH_ATTESTATIONS, num_prev_valid);
         metrics::set_gauge(&metrics::NUM_CURR_EPOCH_ATTESTATIONS, num_curr_valid);
 
-        Ok(max_cover::merge_solutions(
-            curr_cover,
-            prev_cover,
-            curr_epoch_limit,
-        ))
+        self.update_packing_report(state, |report| report.attestations = excluded);
+
+        Ok(attestations)
     }
 
     /// Remove attestations which are too old to be included in a block.
@@ -394,7 +466,7 @@ impl<E: EthSpec> OperationPool<E> {
         Vec<AttesterSlashing<E>>,
         Vec<SignedVoluntaryExit>,
     ) {
-        let proposer_slashings = filter_limit_operations(
+        let (proposer_slashings, excluded) = filter_limit_operations(
             self.proposer_slashings.read().values(),
             |slashing| {
                 slashing.signature_is_still_valid(&state.fork())
@@ -406,6 +478,13 @@ impl<E: EthSpec> OperationPool<E> {
             |slashing| slashing.as_inner().clone(),
             E::MaxProposerSlashings::to_usize(),
         );
+        let excluded_proposer_slashings = excluded
+            .into_iter()
+            .map(|(slashing, reason)| ExcludedOperation {
+                validator_indices: vec![slashing.as_inner().proposer_index()],
+                reason,
+            })
+            .collect();
 
         // Set of validators to be slashed, so we don't attempt to construct invalid attester
         // slashings.
@@ -414,46 +493,80 @@ impl<E: EthSpec> OperationPool<E> {
             .map(|s| s.proposer_index())
             .collect();
 
-        let attester_slashings = self.get_attester_slashings(state, &mut to_be_slashed);
+        let (attester_slashings, excluded_attester_slashings) =
+            self.get_attester_slashings(state, &mut to_be_slashed);
 
-        let voluntary_exits = self.get_voluntary_exits(
+        let (voluntary_exits, excluded_voluntary_exits) = self.get_voluntary_exits(
             state,
             |exit| !to_be_slashed.contains(&exit.message.validator_index),
             spec,
         );
 
+        self.update_packing_report(state, |report| {
+            report.proposer_slashings = excluded_proposer_slashings;
+            report.attester_slashings = excluded_attester_slashings;
+            report.voluntary_exits = excluded_voluntary_exits;
+        });
+
         (proposer_slashings, attester_slashings, voluntary_exits)
     }
 
-    /// Get attester slashings taking into account already slashed validators.
+    /// Get attester slashings taking into account already slashed validators, along with the
+    /// slashings which were excluded.
     ///
     /// This function *must* remain private.
     fn get_attester_slashings(
         &self,
         state: &BeaconState<E>,
         to_be_slashed: &mut HashSet<u64>,
-    ) -> Vec<AttesterSlashing<E>> {
+    ) -> (Vec<AttesterSlashing<E>>, Vec<ExcludedOperation>) {
         let reader = self.attester_slashings.read();
+        let mut excluded = vec![];
 
         let relevant_attester_slashings = reader.iter().flat_map(|slashing| {
-            if slashing.signature_is_still_valid(&state.fork()) {
+            let cover = if slashing.signature_is_still_valid(&state.fork()) {
                 AttesterSlashingMaxCover::new(slashing.as_inner().to_ref(), to_be_slashed, state)
             } else {
                 None
+            };
+            if cover.is_none() {
+                excluded.push((
+                    slashing.as_inner().to_ref(),
+                    ExclusionReason::InvalidAgainstHead,
+                ));
             }
+            cover
         });
 
-        maximum_cover(
+        let cover = maximum_cover(
             relevant_attester_slashings,
             E::MaxAttesterSlashings::to_usize(),
             "attester_slashings",
-        )
-        .into_iter()
-        .map(|cover| {
-            to_be_slashed.extend(cover.covering_set().keys());
-            AttesterSlashingMaxCover::convert_to_object(cover.intermediate())
-        })
-        .collect()
+        );
+
+        excluded.extend(
+            cover
+                .excluded
+                .iter()
+                .map(|(cover, reason)| (*cover.intermediate(), *reason)),
+        );
+        let excluded = excluded
+            .into_iter()
+            .map(|(slashing, reason)| ExcludedOperation {
+                validator_indices: attester_slashing_indices(slashing),
+                reason,
+            })
+            .collect();
+
+        let slashings = cover
+            .included
+            .into_iter()
+            .map(|cover| {
+                to_be_slashed.extend(cover.covering_set().keys());
+                AttesterSlashingMaxCover::convert_to_object(cover.intermediate())
+            })
+            .collect();
+        (slashings, excluded)
     }
 
     /// Prune proposer slashings for validators which are exited in the finalized epoch.
@@ -507,27 +620,49 @@ impl<E: EthSpec> OperationPool<E> {
             .insert(exit.as_inner().message.validator_index, exit);
     }
 
-    /// Get a list of voluntary exits for inclusion in a block.
+    /// Get a list of voluntary exits for inclusion in a block, along with the exits which were
+    /// excluded.
+    ///
+    /// Exits which don't pass the `filter` are superseded, e.g. by a slashing of the same
+    /// validator.
     fn get_voluntary_exits<F>(
         &self,
         state: &BeaconState<E>,
         filter: F,
         spec: &ChainSpec,
-    ) -> Vec<SignedVoluntaryExit>
+    ) -> (Vec<SignedVoluntaryExit>, Vec<ExcludedOperation>)
     where
         F: Fn(&SignedVoluntaryExit) -> bool,
     {
-        filter_limit_operations(
-            self.voluntary_exits.read().values(),
+        let reader = self.voluntary_exits.read();
+        let (candidates, superseded): (Vec<_>, Vec<_>) =
+            reader.values().partition(|exit| filter(exit.as_inner()));
+
+        let (exits, mut excluded) = filter_limit_operations(
+            candidates,
             |exit| {
-                filter(exit.as_inner())
-                    && exit.signature_is_still_valid(&state.fork())
+                exit.signature_is_still_valid(&state.fork())
                     && verify_exit(state, None, exit.as_inner(), VerifySignatures::False, spec)
                         .is_ok()
             },
             |exit| exit.as_inner().clone(),
             E::MaxVoluntaryExits::to_usize(),
-        )
+        );
+
+        excluded.extend(
+            superseded
+                .into_iter()
+                .map(|exit| (exit, ExclusionReason::Superseded)),
+        );
+        let excluded = excluded
+            .into_iter()
+            .map(|(exit, reason)| ExcludedOperation {
+                validator_indices: vec![exit.as_inner().message.validator_index],
+                reason,
+            })
+            .collect();
+
+        (exits, excluded)
     }
 
     /// Prune if validator has already exited at or before the finalized checkpoint of the head.
@@ -577,7 +712,7 @@ impl<E: EthSpec> OperationPool<E> {
         state: &BeaconState<E>,
         spec: &ChainSpec,
     ) -> Vec<SignedBlsToExecutionChange> {
-        filter_limit_operations(
+        let (changes, excluded) = filter_limit_operations(
             self.bls_to_execution_changes.read().iter_lifo(),
             |address_change| {
                 address_change.signature_is_still_valid(&state.fork())
@@ -589,7 +724,18 @@ impl<E: EthSpec> OperationPool<E> {
             },
             |address_change| address_change.as_inner().clone(),
             E::MaxBlsToExecutionChanges::to_usize(),
-        )
+        );
+
+        let excluded = excluded
+            .into_iter()
+            .map(|(address_change, reason)| ExcludedOperation {
+                validator_indices: vec![address_change.as_inner().message.validator_index],
+                reason,
+            })
+            .collect();
+        self.update_packing_report(state, |report| report.bls_to_execution_changes = excluded);
+
+        changes
     }
 
     /// Get a list of execution changes to be broadcast at the Capella fork.
@@ -601,7 +747,7 @@ impl<E: EthSpec> OperationPool<E> {
         state: &BeaconState<E>,
         spec: &ChainSpec,
     ) -> Vec<SignedBlsToExecutionChange> {
-        let mut changes = filter_limit_operations(
+        let (mut changes, _) = filter_limit_operations(
             self.bls_to_execution_changes
                 .read()
                 .iter_received_pre_capella(),
@@ -654,6 +800,7 @@ impl<E: EthSpec> OperationPool<E> {
         self.prune_attester_slashings(head_state);
         self.prune_voluntary_exits(head_state);
         self.prune_bls_to_execution_changes(head_block, head_state, spec);
+        self.prune_packing_reports(current_epoch.start_slot(E::slots_per_epoch()));
     }
 
     /// Total number of voluntary exits in the pool.
@@ -730,26 +877,222 @@ impl<E: EthSpec> OperationPool<E> {
             .map(|address_change| address_change.as_inner().clone())
             .collect()
     }
+
+    /// Record the operations excluded by one packing pass for the block built on `state`.
+    fn update_packing_report(
+        &self,
+        state: &BeaconState<E>,
+        update: impl FnOnce(&mut PackingReport),
+    ) {
+        // The state has been advanced to the slot of the block, so the latest block root is the
+        // root of the parent.
+        let parent_root = state
+            .get_block_root(state.slot().saturating_sub(1u64))
+            .copied()
+            .unwrap_or_default();
+        self.packing_reports
+            .write()
+            .update(state.slot(), parent_root, update);
+    }
+
+    /// Returns the operations which were excluded from the block packed for `slot`, if any.
+    pub fn get_packing_report(&self, slot: Slot) -> Option<PackingReport> {
+        self.packing_reports.read().get(slot).cloned()
+    }
+
+    /// Returns the reports of all recently packed blocks, ordered by slot.
+    pub fn get_packing_reports(&self) -> Vec<PackingReport> {
+        self.packing_reports
+            .read()
+            .reports()
+            .into_iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Remove packing reports for blocks which are more than 2 epochs old.
+    pub fn prune_packing_reports(&self, current_slot: Slot) {
+        self.packing_reports.write().prune(current_slot);
+    }
 }
 
 /// Filter up to a maximum number of operations out of an iterator.
+///
+/// The operations which were left out are also returned: operations which fail the `filter` are
+/// invalid against the head, and valid operations beyond the `limit` didn't fit.
 fn filter_limit_operations<'a, T, V: 'a, I, F, G>(
     operations: I,
     filter: F,
     mapping: G,
     limit: usize,
-) -> Vec<V>
+) -> (Vec<V>, Vec<(&'a T, ExclusionReason)>)
 where
     I: IntoIterator<Item = &'a T>,
     F: Fn(&T) -> bool,
     G: Fn(&T) -> V,
     T: Clone + 'a,
 {
-    operations
-        .into_iter()
-        .filter(|x| filter(*x))
-        .take(limit)
-        .map(mapping)
+    let mut included = vec![];
+    let mut excluded = vec![];
+    for operation in operations {
+        if !filter(operation) {
+            excluded.push((operation, ExclusionReason::InvalidAgainstHead));
+        } else if included.len() < limit {
+            included.push(mapping(operation));
+        } else {
+            excluded.push((operation, ExclusionReason::LimitReached));
+        }
+    }
+    (included, excluded)
+}
+
+/// Compute a maximum cover of the valid `attestations`, also returning the invalid attestations.
+#[allow(clippy::type_complexity)]
+fn maximum_cover_attestations<'a, E: EthSpec>(
+    attestations: impl Iterator<Item = Result<AttMaxCover<'a, E>, CompactAttestationRef<'a, E>>>,
+    limit: usize,
+    label: &str,
+) -> (
+    MaxCoverSolution<AttMaxCover<'a, E>>,
+    Vec<CompactAttestationRef<'a, E>>,
+) {
+    let mut invalid = vec![];
+    let valid = attestations.filter_map(|att| att.map_err(|att| invalid.push(att)).ok());
+    let cover = maximum_cover(valid, limit, label);
+    (cover, invalid)
+}
+
+/// The attestations to pack into on-chain aggregates, copied out of the pool so that it isn't
+/// locked while they are packed.
+struct PackingCandidates<E: EthSpec> {
+    /// The valid attestations, in the same order as their `items`.
+    atts: Vec<SplitAttestation<E>>,
+    items: Vec<PackingItem>,
+    /// The attestations which are invalid against the head.
+    excluded: Vec<ExcludedAttestation>,
+}
+
+impl<E: EthSpec> PackingCandidates<E> {
+    fn new<'a>(
+        attestations: impl Iterator<Item = Result<AttMaxCover<'a, E>, CompactAttestationRef<'a, E>>>,
+    ) -> Self {
+        let mut candidates = PackingCandidates {
+            atts: vec![],
+            items: vec![],
+            excluded: vec![],
+        };
+        for att in attestations {
+            match att {
+                Ok(cover) => {
+                    let committee_index = match cover.att.indexed {
+                        CompactIndexedAttestation::Electra(indexed)
+                            if indexed.committee_bits.num_set_bits() == 1 =>
+                        {
+                            indexed.committee_index()
+                        }
+                        _ => None,
+                    };
+                    candidates.items.push(PackingItem {
+                        data: cover.att.attestation_data(),
+                        committee_index,
+                        rewards: cover.fresh_validators_rewards,
+                    });
+                    candidates.atts.push(cover.att.clone_as_split_attestation());
+                }
+                Err(att) => candidates.excluded.push(excluded_attestation(
+                    &att,
+                    None,
+                    ExclusionReason::InvalidAgainstHead,
+                )),
+            }
+        }
+        candidates
+    }
+
+    /// Pack the attestations into at most `limit` on-chain aggregates, also returning the
+    /// attestations which were excluded.
+    fn pack_on_chain_aggregates(
+        self,
+        limit: usize,
+        search_timeout: Option<Duration>,
+    ) -> (Vec<Attestation<E>>, Vec<ExcludedAttestation>) {
+        let PackingCandidates {
+            atts,
+            items,
+            mut excluded,
+        } = self;
+
+        let solution = pack_attestations(&items, limit, search_timeout);
+        if let Some(search) = solution.search {
+            if solution.reward > solution.greedy_reward {
+                metrics::inc_counter(&metrics::ATTESTATION_PACKING_SEARCH_IMPROVEMENTS);
+            }
+            if !search.complete {
+                metrics::inc_counter(&metrics::ATTESTATION_PACKING_SEARCH_TIMEOUTS);
+            }
+        }
+
+        excluded.extend(solution.excluded.iter().map(|item| {
+            excluded_attestation(
+                &atts[item.index].as_ref(),
+                Some(&item.fresh_validators_rewards),
+                item.reason,
+            )
+        }));
+        let attestations = solution
+            .aggregates
+            .iter()
+            .filter_map(|aggregate| {
+                let parts = aggregate
+                    .iter()
+                    .map(|&index| atts[index].as_ref())
+                    .collect::<Vec<_>>();
+                let attestation = CompactAttestationRef::aggregate_across_committees(&parts);
+                if attestation.is_none() {
+                    // The packing only combines attestations which can be aggregated, so this
+                    // indicates a bug.
+                    metrics::inc_counter(&metrics::ATTESTATION_PACKING_AGGREGATION_FAILURES);
+                }
+                attestation
+            })
+            .collect();
+        (attestations, excluded)
+    }
+}
+
+/// Describe an attestation excluded from a block.
+///
+/// The `fresh_validators` are those whose votes were not already covered, if known.
+fn excluded_attestation<E: EthSpec>(
+    att: &CompactAttestationRef<E>,
+    fresh_validators: Option<&HashMap<u64, u64>>,
+    reason: ExclusionReason,
+) -> ExcludedAttestation {
+    let attesting_indices = att.indexed.attesting_indices();
+    let mut fresh_validator_indices = match fresh_validators {
+        Some(fresh_validators) => fresh_validators.keys().copied().collect(),
+        None => attesting_indices.clone(),
+    };
+    fresh_validator_indices.sort_unstable();
+    ExcludedAttestation {
+        data: att.attestation_data(),
+        num_attesting_indices: attesting_indices.len(),
+        fresh_validator_indices,
+        reason,
+    }
+}
+
+/// The validators which attested in both attestations of an attester slashing.
+fn attester_slashing_indices<E: EthSpec>(slashing: AttesterSlashingRef<E>) -> Vec<u64> {
+    let attestation_1 = slashing.attestation_1();
+    let attestation_2 = slashing.attestation_2();
+    let indices_2 = attestation_2
+        .attesting_indices_iter()
+        .collect::<HashSet<_>>();
+    attestation_1
+        .attesting_indices_iter()
+        .filter(|index| indices_2.contains(index))
+        .copied()
         .collect()
 }
 
@@ -798,7 +1141,10 @@ mod release_tests {
     };
     use maplit::hashset;
     use state_processing::epoch_cache::initialize_epoch_cache;
-    use state_processing::{common::get_attesting_indices_from_state, VerifyOperation};
+    use state_processing::per_block_processing::verify_attestation_for_block_inclusion;
+    use state_processing::{
+        common::get_attesting_indices_from_state, ConsensusContext, VerifyOperation,
+    };
     use std::collections::BTreeSet;
     use std::sync::{Arc, LazyLock};
     use types::consts::altair::SYNC_COMMITTEE_SUBNET_COUNT;
@@ -1003,7 +1349,7 @@ mod release_tests {
         // Before the min attestation inclusion delay, get_attestations shouldn't return anything.
         assert_eq!(
             op_pool
-                .get_attestations(&state, |_| true, |_| true, spec)
+                .get_attestations(&state, |_| true, |_| true, None, spec)
                 .expect("should have attestations")
                 .len(),
             0
@@ -1013,7 +1359,7 @@ mod release_tests {
         *state.slot_mut() += spec.min_attestation_inclusion_delay;
 
         let block_attestations = op_pool
-            .get_attestations(&state, |_| true, |_| true, spec)
+            .get_attestations(&state, |_| true, |_| true, None, spec)
             .expect("Should have block attestations");
         assert_eq!(block_attestations.len(), committees.len());
 
@@ -1264,8 +1610,10 @@ mod release_tests {
 
         *state.slot_mut() += spec.min_attestation_inclusion_delay;
         let best_attestations = op_pool
-            .get_attestations(&state, |_| true, |_| true, spec)
+            .get_attestations(&state, |_| true, |_| true, None, spec)
             .expect("should have best attestations");
+        // Packing doesn't modify the attestations in the pool.
+        assert_eq!(op_pool.num_attestations(), stats.num_attestations);
         match fork_name {
             ForkName::Electra => {
                 assert_eq!(best_attestations.len(), 8);
@@ -1286,6 +1634,113 @@ mod release_tests {
                 }
             };
         }
+
+        // The excluded attestations list the validators whose votes are missing from the block.
+        let included = best_attestations
+            .iter()
+            .flat_map(|att| get_attesting_indices_from_state(&state, att.to_ref()).unwrap())
+            .collect::<HashSet<_>>();
+        let report = op_pool.get_packing_report(state.slot()).unwrap();
+        assert!(!report.attestations.is_empty());
+        for excluded in &report.attestations {
+            let fresh = &excluded.fresh_validator_indices;
+            assert!(fresh.windows(2).all(|pair| pair[0] < pair[1]));
+            assert!(fresh.len() <= excluded.num_attesting_indices);
+            assert!(fresh.iter().all(|index| !included.contains(index)));
+            assert_eq!(
+                fresh.is_empty(),
+                excluded.reason == ExclusionReason::Superseded
+            );
+        }
+    }
+
+    /// Pack overlapping attestations with a search timeout, and check that the packed
+    /// attestations are valid for inclusion in a block and cover at least as many validators as
+    /// the greedy packing.
+    #[test]
+    fn attestation_get_with_search_timeout() {
+        let num_committees = 4;
+        let step_size = 2;
+
+        let (harness, ref spec) = attestation_test_state::<MainnetEthSpec>(num_committees);
+        let mut state = get_current_state_initialize_epoch_cache(&harness, &spec);
+        let op_pool = OperationPool::<MainnetEthSpec>::new();
+
+        let slot = state.slot();
+        let num_validators = num_committees
+            * MainnetEthSpec::slots_per_epoch() as usize
+            * spec.target_committee_size;
+        let attestations = harness.make_attestations(
+            (0..num_validators).collect::<Vec<_>>().as_slice(),
+            &state,
+            Hash256::zero(),
+            SignedBeaconBlockHash::from(Hash256::zero()),
+            slot,
+        );
+
+        // Chunk each committee's attestations twice, offset by one validator, so that the pool
+        // holds overlapping aggregates which can't all be included.
+        for (atts, _) in attestations {
+            let aggregates = [&atts[..], &atts[1..]]
+                .into_iter()
+                .flat_map(|atts| atts.chunks(step_size))
+                .map(|chunk| {
+                    let (first, rest) = chunk.split_first().unwrap();
+                    let mut aggregate = first.0.clone();
+                    for (att, _) in rest {
+                        aggregate.aggregate(att.to_ref());
+                    }
+                    aggregate
+                })
+                .collect::<Vec<_>>();
+            for att in aggregates {
+                let attesting_indices =
+                    get_attesting_indices_from_state(&state, att.to_ref()).unwrap();
+                op_pool.insert_attestation(att, attesting_indices).unwrap();
+            }
+        }
+
+        *state.slot_mut() += spec.min_attestation_inclusion_delay;
+        let limit = if state.fork_name_unchecked().electra_enabled() {
+            <MainnetEthSpec as EthSpec>::MaxAttestationsElectra::to_usize()
+        } else {
+            <MainnetEthSpec as EthSpec>::MaxAttestations::to_usize()
+        };
+        let covered_validators = |attestations: &[Attestation<MainnetEthSpec>]| {
+            attestations
+                .iter()
+                .flat_map(|att| get_attesting_indices_from_state(&state, att.to_ref()).unwrap())
+                .collect::<HashSet<_>>()
+                .len()
+        };
+
+        let greedy = op_pool
+            .get_attestations(&state, |_| true, |_| true, None, spec)
+            .expect("should pack attestations greedily");
+        let searched = op_pool
+            .get_attestations(
+                &state,
+                |_| true,
+                |_| true,
+                Some(Duration::from_millis(100)),
+                spec,
+            )
+            .expect("should pack attestations with a search");
+
+        assert!(!searched.is_empty());
+        assert!(searched.len() <= limit);
+        assert!(covered_validators(&searched) >= covered_validators(&greedy));
+        for att in &searched {
+            let mut ctxt = ConsensusContext::new(state.slot());
+            verify_attestation_for_block_inclusion(
+                &state,
+                att.to_ref(),
+                &mut ctxt,
+                VerifySignatures::True,
+                spec,
+            )
+            .expect("packed attestation should be valid for inclusion");
+        }
     }
 
     #[test]
@@ -1385,7 +1840,7 @@ mod release_tests {
 
         *state.slot_mut() += spec.min_attestation_inclusion_delay;
         let best_attestations = op_pool
-            .get_attestations(&state, |_| true, |_| true, spec)
+            .get_attestations(&state, |_| true, |_| true, None, spec)
             .expect("should have valid best attestations");
 
         match fork_name {
@@ -1559,6 +2014,41 @@ mod release_tests {
         assert_eq!(best_slashings.1, vec![a_slashing_1, a_slashing_3]);
     }
 
+    // Slashings left out of the block are recorded in the packing report for its slot
+    #[test]
+    fn packing_report_attester_slashings() {
+        let harness = get_harness(32, None);
+        let spec = &harness.spec;
+        let state = harness.get_current_state();
+        let op_pool = OperationPool::<MainnetEthSpec>::new();
+
+        let p_slashing = harness.make_proposer_slashing(1);
+        let a_slashing_1 = harness.make_attester_slashing(vec![1, 2, 3, 4]);
+        let a_slashing_2 = harness.make_attester_slashing(vec![1, 3, 4]);
+        let a_slashing_3 = harness.make_attester_slashing(vec![5, 6]);
+
+        op_pool.insert_proposer_slashing(p_slashing.clone().validate(&state, spec).unwrap());
+        op_pool.insert_attester_slashing(a_slashing_1.clone().validate(&state, spec).unwrap());
+        op_pool.insert_attester_slashing(a_slashing_2.clone().validate(&state, spec).unwrap());
+        op_pool.insert_attester_slashing(a_slashing_3.clone().validate(&state, spec).unwrap());
+
+        assert!(op_pool.get_packing_report(state.slot()).is_none());
+        op_pool.get_slashings_and_exits(&state, &harness.spec);
+
+        // The second attester slashing only slashes validators slashed by the others.
+        let report = op_pool.get_packing_report(state.slot()).unwrap();
+        assert!(report.proposer_slashings.is_empty());
+        assert_eq!(
+            report.attester_slashings,
+            vec![ExcludedOperation {
+                validator_indices: vec![1, 3, 4],
+                reason: ExclusionReason::Superseded,
+            }]
+        );
+        assert!(report.voluntary_exits.is_empty());
+        assert_eq!(op_pool.get_packing_reports(), vec![report]);
+    }
+
     //Max coverage checking that non overlapping indices are still recognized for their value
     #[test]
     fn max_coverage_different_indices_set() {
@@ -1916,7 +2406,7 @@ mod release_tests {
             .validate(&altair_head.beacon_state, &harness.chain.spec)
             .unwrap();
         op_pool.insert_voluntary_exit(verified_exit1);
-        let exits =
+        let (exits, _) =
             op_pool.get_voluntary_exits(&altair_head.beacon_state, |_| true, &harness.chain.spec);
         assert!(exits.contains(&exit1));
         assert_eq!(exits.len(), 1);
@@ -1958,7 +2448,7 @@ mod release_tests {
         // Attempting to fetch exit1 now should fail, despite it still being in the pool.
         // exit2 should still be valid, because it was signed with the Altair fork domain.
         assert_eq!(op_pool.voluntary_exits.read().len(), 2);
-        let exits =
+        let (exits, _) =
             op_pool.get_voluntary_exits(&bellatrix_head.beacon_state, |_| true, &harness.spec);
         assert_eq!(&exits, &[exit2]);
     }
@@ -2126,7 +2616,7 @@ mod release_tests {
         // Seeing as we can only extract 2 at time we'll just pretend that validator 0 is already
         // slashed.
         let mut to_be_slashed = hashset! {0};
-        let attester_slashings =
+        let (attester_slashings, _) =
             op_pool.get_attester_slashings(&altair_head.beacon_state, &mut to_be_slashed);
         assert!(attester_slashings.contains(&slashing2));
         assert!(attester_slashings.contains(&slashing3));
@@ -1,4 +1,5 @@
 use crate::metrics;
+use crate::packing_report::ExclusionReason;
 use itertools::Itertools;
 
 /// Trait for types that we can compute a maximum cover for.
@@ -46,20 +47,34 @@ impl<T> MaxCoverItem<T> {
     }
 }
 
+/// The items selected by a maximum cover, and the items left out alongside the reason why.
+pub struct MaxCoverSolution<T> {
+    pub included: Vec<T>,
+    pub excluded: Vec<(T, ExclusionReason)>,
+}
+
 /// Compute an approximate maximum cover using a greedy algorithm.
 ///
 /// * Time complexity: `O(limit * items_iter.len())`
 /// * Space complexity: `O(item_iter.len())`
-pub fn maximum_cover<I, T>(items_iter: I, limit: usize, label: &str) -> Vec<T>
+pub fn maximum_cover<I, T>(items_iter: I, limit: usize, label: &str) -> MaxCoverSolution<T>
 where
     I: IntoIterator<Item = T>,
     T: MaxCover,
 {
-    // Construct an initial vec of all items, marked available.
+    // Construct an initial vec of all items, marked available. Items which cover nothing are
+    // superseded before we start.
+    let mut excluded = vec![];
     let mut all_items: Vec<_> = items_iter
         .into_iter()
-        .map(MaxCoverItem::new)
-        .filter(|x| x.item.score() != 0)
+        .filter_map(|item| {
+            if item.score() == 0 {
+                excluded.push((item, ExclusionReason::Superseded));
+                None
+            } else {
+                Some(MaxCoverItem::new(item))
+            }
+        })
         .collect();
 
     metrics::set_int_gauge(
@@ -81,7 +96,7 @@ where
                 x.available = false;
                 x.item.clone()
             }
-            None => return result,
+            None => break,
         };
 
         // Update the covering sets of the other items, for the inclusion of the selected item.
@@ -97,22 +112,40 @@ where
         result.push(best);
     }
 
-    result
+    // Items which still cover something were outscored by the items in the solution.
+    excluded.extend(all_items.into_iter().filter(|x| x.available).map(|x| {
+        let reason = if x.item.score() == 0 {
+            ExclusionReason::Superseded
+        } else {
+            ExclusionReason::MaxCoverLost
+        };
+        (x.item, reason)
+    }));
+
+    MaxCoverSolution {
+        included: result,
+        excluded,
+    }
 }
 
 /// Perform a greedy merge of two max cover solutions, preferring higher-score values.
-pub fn merge_solutions<I1, I2, T>(cover1: I1, cover2: I2, limit: usize) -> Vec<T::Object>
+///
+/// Returns the merged objects and the items which did not fit within the `limit`.
+pub fn merge_solutions<I1, I2, T>(cover1: I1, cover2: I2, limit: usize) -> (Vec<T::Object>, Vec<T>)
 where
     I1: IntoIterator<Item = T>,
     I2: IntoIterator<Item = T>,
     T: MaxCover,
 {
-    cover1
+    let mut merged = cover1
         .into_iter()
-        .merge_by(cover2, |item1, item2| item1.score() >= item2.score())
+        .merge_by(cover2, |item1, item2| item1.score() >= item2.score());
+    let objects = merged
+        .by_ref()
         .take(limit)
         .map(|item| T::convert_to_object(item.intermediate()))
-        .collect()
+        .collect();
+    (objects, merged.collect())
 }
 
 #[cfg(test)]
@@ -162,14 +195,14 @@ mod test {
 
     #[test]
     fn zero_limit() {
-        let cover = maximum_cover(example_system(), 0, "test");
+        let cover = maximum_cover(example_system(), 0, "test").included;
         assert_eq!(cover.len(), 0);
     }
 
     #[test]
     fn one_limit() {
         let sets = example_system();
-        let cover = maximum_cover(sets.clone(), 1, "test");
+        let cover = maximum_cover(sets.clone(), 1, "test").included;
         assert_eq!(cover.len(), 1);
         assert_eq!(cover[0], sets[1]);
     }
@@ -179,13 +212,33 @@ mod test {
     fn exclude_zero_score() {
         let sets = example_system();
         for k in 2..10 {
-            let cover = maximum_cover(sets.clone(), k, "test");
+            let cover = maximum_cover(sets.clone(), k, "test").included;
             assert_eq!(cover.len(), 2);
             assert_eq!(cover[0], sets[1]);
             assert_eq!(cover[1], sets[0]);
         }
     }
 
+    #[test]
+    fn exclusion_reasons() {
+        let sets = example_system();
+        let solution = maximum_cover(sets.clone(), 1, "test");
+        assert_eq!(solution.included, vec![sets[1].clone()]);
+
+        // Only the set covering element 3 still covers something once sets[1] is included.
+        let mut excluded = solution.excluded;
+        excluded.sort_by_key(|(_, reason)| *reason == ExclusionReason::Superseded);
+        assert_eq!(
+            excluded,
+            vec![
+                (sets[0].clone(), ExclusionReason::MaxCoverLost),
+                (HashSet::new(), ExclusionReason::Superseded),
+                (HashSet::new(), ExclusionReason::Superseded),
+                (HashSet::new(), ExclusionReason::Superseded),
+            ]
+        );
+    }
+
     fn quality<T: Eq + Hash>(solution: &[HashSet<T>]) -> usize {
         solution.iter().map(HashSet::len).sum()
     }
@@ -203,7 +256,7 @@ mod test {
             HashSet::from_iter(vec![5, 6, 7, 8]),      // 4, 4*
             HashSet::from_iter(vec![0, 1, 2, 3, 4]),   // 5*
         ];
-        let cover = maximum_cover(sets, 3, "test");
+        let cover = maximum_cover(sets, 3, "test").included;
         assert_eq!(quality(&cover), 11);
     }
 
@@ -218,7 +271,7 @@ mod test {
             HashSet::from_iter(vec![1, 5, 6, 8]),
             HashSet::from_iter(vec![1, 7, 11, 19]),
         ];
-        let cover = maximum_cover(sets, 5, "test");
+        let cover = maximum_cover(sets, 5, "test").included;
         assert_eq!(quality(&cover), 19);
         assert_eq!(cover.len(), 5);
     }
@@ -19,6 +19,33 @@ pub static ATTESTATION_CURR_EPOCH_PACKING_TIME: LazyLock<Result<Histogram>> = La
         "Time to pack current epoch attestations",
     )
 });
+pub static ATTESTATION_PACKING_TIME: LazyLock<Result<Histogram>> = LazyLock::new(|| {
+    try_create_histogram(
+        "op_pool_attestation_packing_time",
+        "Time to pack attestations from both epochs into on-chain aggregates",
+    )
+});
+pub static ATTESTATION_PACKING_SEARCH_IMPROVEMENTS: LazyLock<Result<IntCounter>> =
+    LazyLock::new(|| {
+        try_create_int_counter(
+            "op_pool_attestation_packing_search_improvements_total",
+            "Number of times the attestation packing search beat the greedy solution",
+        )
+    });
+pub static ATTESTATION_PACKING_SEARCH_TIMEOUTS: LazyLock<Result<IntCounter>> =
+    LazyLock::new(|| {
+        try_create_int_counter(
+            "op_pool_attestation_packing_search_timeouts_total",
+            "Number of times the attestation packing search ran out of time",
+        )
+    });
+pub static ATTESTATION_PACKING_AGGREGATION_FAILURES: LazyLock<Result<IntCounter>> =
+    LazyLock::new(|| {
+        try_create_int_counter(
+            "op_pool_attestation_packing_aggregation_failures_total",
+            "Number of packed on-chain aggregates dropped because they failed to aggregate",
+        )
+    });
 pub static NUM_PREV_EPOCH_ATTESTATIONS: LazyLock<Result<IntGauge>> = LazyLock::new(|| {
     try_create_int_gauge(
         "op_pool_prev_epoch_attestations",
@@ -0,0 +1,170 @@
+//! This module provides the `PackingReportCache`, which records the operations that were left out
+//! of each block packed by the `OperationPool`, and why.
+//!
+//! This allows us to determine why a slashing, exit or attestation known to the pool was not
+//! included in one of our proposals:
+//! - It was invalid against the state the block was built on.
+//! - It was superseded, e.g. its votes were already included or its validator is being slashed.
+//! - It lost out to operations with a higher reward in the maximum cover.
+//! - The block had already reached the maximum number of operations of its kind.
+
+use std::collections::HashMap;
+use types::{AttestationData, Hash256, Slot};
+
+/// The number of slots for which reports are retained.
+pub const PACKING_REPORT_RETENTION_SLOTS: u64 = 64;
+
+/// The reason that a candidate operation was excluded from a block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExclusionReason {
+    /// The operation is invalid against the state the block was built on.
+    InvalidAgainstHead,
+    /// The operation adds nothing to the block, because the same votes or validators are already
+    /// covered by the chain or by other operations in the block.
+    Superseded,
+    /// The operation would add to the block, but was outscored by the operations selected by the
+    /// maximum cover.
+    MaxCoverLost,
+    /// The operation is valid, but the block already contains the maximum number of operations of
+    /// its kind.
+    LimitReached,
+}
+
+/// An attestation excluded from a block.
+///
+/// Only the validators whose votes are missing from the block are listed, as a block may exclude
+/// thousands of attestations whose votes are mostly already covered.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExcludedAttestation {
+    pub data: AttestationData,
+    pub num_attesting_indices: usize,
+    /// The validators whose votes would have been included for the first time, in increasing
+    /// order.
+    ///
+    /// This is empty for superseded attestations, and lists all attesting validators for
+    /// attestations which are invalid against the head.
+    pub fresh_validator_indices: Vec<u64>,
+    pub reason: ExclusionReason,
+}
+
+/// A slashing, exit or BLS to execution change excluded from a block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExcludedOperation {
+    /// The validators which the operation applies to.
+    pub validator_indices: Vec<u64>,
+    pub reason: ExclusionReason,
+}
+
+/// The operations excluded from the block proposed at `slot`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PackingReport {
+    pub slot: Slot,
+    pub parent_root: Hash256,
+    pub attestations: Vec<ExcludedAttestation>,
+    pub proposer_slashings: Vec<ExcludedOperation>,
+    pub attester_slashings: Vec<ExcludedOperation>,
+    pub voluntary_exits: Vec<ExcludedOperation>,
+    pub bls_to_execution_changes: Vec<ExcludedOperation>,
+}
+
+impl PackingReport {
+    fn new(slot: Slot, parent_root: Hash256) -> Self {
+        PackingReport {
+            slot,
+            parent_root,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct PackingReportCache {
+    reports: HashMap<Slot, PackingReport>,
+}
+
+impl PackingReportCache {
+    /// Apply `update` to the report for the block at `slot`.
+    ///
+    /// The operations of a block are packed in several passes, so each pass fills in its own
+    /// fields. A block built on a different parent replaces any existing report for the slot.
+    pub fn update(
+        &mut self,
+        slot: Slot,
+        parent_root: Hash256,
+        update: impl FnOnce(&mut PackingReport),
+    ) {
+        let report = self
+            .reports
+            .entry(slot)
+            .or_insert_with(|| PackingReport::new(slot, parent_root));
+        if report.parent_root != parent_root {
+            *report = PackingReport::new(slot, parent_root);
+        }
+        update(report);
+    }
+
+    pub fn get(&self, slot: Slot) -> Option<&PackingReport> {
+        self.reports.get(&slot)
+    }
+
+    /// Returns all reports, ordered by slot.
+    pub fn reports(&self) -> Vec<&PackingReport> {
+        let mut reports = self.reports.values().collect::<Vec<_>>();
+        reports.sort_unstable_by_key(|report| report.slot);
+        reports
+    }
+
+    // Prune the cache to only store the most recent 2 epochs.
+    pub fn prune(&mut self, current_slot: Slot) {
+        self.reports
+            .retain(|slot, _| *slot > current_slot.saturating_sub(PACKING_REPORT_RETENTION_SLOTS));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn exit(validator_index: u64, reason: ExclusionReason) -> ExcludedOperation {
+        ExcludedOperation {
+            validator_indices: vec![validator_index],
+            reason,
+        }
+    }
+
+    #[test]
+    fn reports_are_replaced_on_new_parent_and_pruned() {
+        let mut cache = PackingReportCache::default();
+        let slot = Slot::new(100);
+        let parent_1 = Hash256::repeat_byte(1);
+        let parent_2 = Hash256::repeat_byte(2);
+
+        cache.update(slot, parent_1, |report| {
+            report.voluntary_exits = vec![exit(1, ExclusionReason::LimitReached)];
+        });
+        cache.update(slot, parent_1, |report| {
+            report.bls_to_execution_changes = vec![exit(2, ExclusionReason::InvalidAgainstHead)];
+        });
+        let report = cache.get(slot).unwrap();
+        assert_eq!(report.voluntary_exits.len(), 1);
+        assert_eq!(report.bls_to_execution_changes.len(), 1);
+
+        // A block built on another parent starts a new report.
+        cache.update(slot, parent_2, |report| {
+            report.voluntary_exits = vec![exit(3, ExclusionReason::Superseded)];
+        });
+        let report = cache.get(slot).unwrap();
+        assert_eq!(report.parent_root, parent_2);
+        assert_eq!(
+            report.voluntary_exits,
+            vec![exit(3, ExclusionReason::Superseded)]
+        );
+        assert!(report.bls_to_execution_changes.is_empty());
+
+        cache.update(slot + PACKING_REPORT_RETENTION_SLOTS, parent_1, |_| {});
+        assert_eq!(cache.reports().len(), 2);
+        cache.prune(slot + PACKING_REPORT_RETENTION_SLOTS);
+        assert!(cache.get(slot).is_none());
+        assert_eq!(cache.reports().len(), 1);
+    }
+}
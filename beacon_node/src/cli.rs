@@ -231,6 +231,18 @@ pub fn cli_app() -> Command {
                 .action(ArgAction::Set)
                 .display_order(0)
         )
+        .arg(
+            Arg::new("bandwidth-budget")
+                .long("bandwidth-budget")
+                .value_name("MBPS")
+                .help("Keep the libp2p traffic of the node under this bandwidth in Mbps. \
+                    The gossipsub mesh sizes, IDONTWANT threshold, optional subnet subscriptions, \
+                    target number of peers and rate at which data is served to peers are reduced \
+                    when the traffic exceeds the budget, and restored up to --network-load when \
+                    it is back under the budget.")
+                .action(ArgAction::Set)
+                .display_order(0)
+        )
         .arg(
             Arg::new("boot-nodes")
                 .long("boot-nodes")
@@ -394,6 +406,20 @@ pub fn cli_app() -> Command {
                 .hide(true)
                 .display_order(0)
         )
+        .arg(
+            Arg::new("peer-group")
+                .long("peer-group")
+                .value_name("NAME:IN:OUT:MULTIADDRS")
+                .help("A static peering group of the form \
+                      NAME:RESERVED_INBOUND:RESERVED_OUTBOUND:MULTIADDR,MULTIADDR,... where each \
+                      multiaddr ends with /p2p/<peer-id>. Members of the group are always \
+                      re-dialed when disconnected, are never pruned or disconnected due to their \
+                      score, and may connect using the given number of inbound and outbound \
+                      slots which are reserved in addition to the target peer count. This flag \
+                      may be provided multiple times.")
+                .action(ArgAction::Append)
+                .display_order(0)
+        )
         .arg(
             Arg::new("trusted-peers")
                 .long("trusted-peers")
@@ -493,6 +519,47 @@ pub fn cli_app() -> Command {
                 .help_heading(FLAG_HEADER)
                 .display_order(0)
         )
+        .arg(
+            Arg::new("backfill-schedule")
+                .long("backfill-schedule")
+                .value_name("PERCENTAGES")
+                .help("Comma-separated points within each slot, as percentages of the slot \
+                    duration, at which a rate-limited backfill batch may be processed. \
+                    [default: 50,60,80]")
+                .conflicts_with("disable-backfill-rate-limiting")
+                .action(ArgAction::Set)
+                .display_order(0)
+        )
+        .arg(
+            Arg::new("backfill-hours")
+                .long("backfill-hours")
+                .value_name("START-END")
+                .help("Only run backfill sync between these UTC hours, e.g. 22-6 to backfill \
+                    overnight. The end hour is exclusive. Backfill sync runs at all hours by \
+                    default.")
+                .action(ArgAction::Set)
+                .display_order(0)
+        )
+        .arg(
+            Arg::new("backfill-proposal-pause-slots")
+                .long("backfill-proposal-pause-slots")
+                .value_name("SLOTS")
+                .help("Pause backfill sync while a validator attached to this node is due to \
+                    propose a block within this many slots. At most the number of slots in an \
+                    epoch.")
+                .action(ArgAction::Set)
+                .display_order(0)
+        )
+        .arg(
+            Arg::new("backfill-processing-budget")
+                .long("backfill-processing-budget")
+                .value_name("PERCENTAGE")
+                .help("Limit backfill sync to importing blocks for at most this percentage of \
+                    the time, to bound the CPU and disk IO it uses. Backfill sync is only limited \
+                    by --backfill-schedule by default.")
+                .action(ArgAction::Set)
+                .display_order(0)
+        )
         /* REST API related arguments */
         .arg(
             Arg::new("http")
@@ -574,6 +641,32 @@ pub fn cli_app() -> Command {
                        Increasing this value can prevent messages from being dropped.")
                 .display_order(0)
         )
+        .arg(
+            Arg::new("http-sse-replay-buffer-size")
+                .long("http-sse-replay-buffer-size")
+                .requires("enable_http")
+                .action(ArgAction::Set)
+                .default_value_if("enable_http", ArgPredicate::IsPresent, "0")
+                .value_name("N")
+                .help("Number of recent server-sent events (SSE) to retain for each topic, so that \
+                       they can be replayed to clients which reconnect with a Last-Event-ID \
+                       header. Events are produced for every topic when this is non-zero, even \
+                       without subscribers, which may increase resource usage. Set to 0 to \
+                       disable replay.")
+                .display_order(0)
+        )
+        .arg(
+            Arg::new("http-access-control-file")
+                .long("http-access-control-file")
+                .requires("enable_http")
+                .value_name("PATH")
+                .help("Path to a YAML file of bearer tokens which are permitted to access the \
+                       HTTP API. Each token has a list of allowed routes and an optional rate \
+                       limit. If this is not provided, the HTTP API does not require \
+                       authentication.")
+                .action(ArgAction::Set)
+                .display_order(0)
+        )
         .arg(
             Arg::new("http-duplicate-block-status")
                 .long("http-duplicate-block-status")
@@ -836,7 +929,9 @@ pub fn cli_app() -> Command {
                 .alias("execution-endpoints")
                 .help("Server endpoint for an execution layer JWT-authenticated HTTP \
                        JSON-RPC connection. Uses the same endpoint to populate the \
-                       deposit cache.")
+                       deposit cache. An IPC socket on the same host can be used instead \
+                       with an ipc:// endpoint (e.g. ipc:///path/to/engine.ipc), which \
+                       does not accept a JWT secret.")
                 .required(true)
                 .action(ArgAction::Set)
                 .display_order(0)
@@ -888,6 +983,41 @@ pub fn cli_app() -> Command {
                 .action(ArgAction::Set)
                 .display_order(0)
         )
+        .arg(
+            Arg::new("execution-secondary-endpoints")
+                .long("execution-secondary-endpoints")
+                .value_name("EXECUTION-ENDPOINTS")
+                .help("Comma-separated list of additional execution engine endpoints. Payloads \
+                       and fork choice updates are sent to all engines, and the beacon node \
+                       fails over to a secondary engine while the primary engine is offline \
+                       or syncing.")
+                .requires("execution-endpoint")
+                .action(ArgAction::Set)
+                .display_order(0)
+        )
+        .arg(
+            Arg::new("execution-secondary-jwts")
+                .long("execution-secondary-jwts")
+                .value_name("EXECUTION-JWTS")
+                .help("Comma-separated list of file paths containing the hex-encoded JWT secrets \
+                       of the engines provided in the --execution-secondary-endpoints flag, in \
+                       the same order. Engines without a JWT file use the JWT secret of the \
+                       primary execution endpoint.")
+                .requires("execution-secondary-endpoints")
+                .action(ArgAction::Set)
+                .display_order(0)
+        )
+        .arg(
+            Arg::new("execution-require-agreement")
+                .long("execution-require-agreement")
+                .help("Only treat a payload as valid if all execution engines respond in time \
+                       and agree that it is valid. Payloads that an engine does not confirm are \
+                       treated as optimistic.")
+                .requires("execution-secondary-endpoints")
+                .action(ArgAction::SetTrue)
+                .help_heading(FLAG_HEADER)
+                .display_order(0)
+        )
         .arg(
             Arg::new("suggested-fee-recipient")
                 .long("suggested-fee-recipient")
@@ -904,7 +1034,9 @@ pub fn cli_app() -> Command {
                 .long("builder")
                 .alias("payload-builder")
                 .alias("payload-builders")
-                .help("The URL of a service compatible with the MEV-boost API.")
+                .help("Comma-separated list of URLs of services compatible with the MEV-boost \
+                       API. Payload headers are requested from all of them and the most \
+                       valuable valid bid is used.")
                 .requires("execution-endpoint")
                 .action(ArgAction::Set)
                 .display_order(0)
@@ -918,6 +1050,37 @@ pub fn cli_app() -> Command {
                 .action(ArgAction::Set)
                 .display_order(0)
         )
+        .arg(
+            Arg::new("execution-record-dir")
+                .long("execution-record-dir")
+                .value_name("PATH")
+                .help("Record every engine API request sent to the execution engines, with its \
+                       latency and response, to files in this directory. The recording can be \
+                       replayed against another execution engine with `lcli replay-engine-api`. \
+                       This feature is only recommended for developers.")
+                .requires("execution-endpoint")
+                .action(ArgAction::Set)
+                .display_order(0)
+        )
+        .arg(
+            Arg::new("execution-record-max-file-size")
+                .long("execution-record-max-file-size")
+                .value_name("MiB")
+                .help("The size at which a new engine API recording file is started. Default: 256")
+                .requires("execution-record-dir")
+                .action(ArgAction::Set)
+                .display_order(0)
+        )
+        .arg(
+            Arg::new("execution-record-max-files")
+                .long("execution-record-max-files")
+                .value_name("INTEGER")
+                .help("The number of engine API recording files to retain. The oldest file is \
+                       deleted when this is exceeded. Default: 8")
+                .requires("execution-record-dir")
+                .action(ArgAction::Set)
+                .display_order(0)
+        )
         .arg(
             Arg::new("builder-header-timeout")
                 .long("builder-header-timeout")
@@ -1190,7 +1353,7 @@ pub fn cli_app() -> Command {
                     "Specify a weak subjectivity checkpoint in `block_root:epoch` format to verify \
                      the node's sync against. The block root should be 0x-prefixed. Note that this \
                      flag is for verification only, to perform a checkpoint sync from a recent \
-                     state use --checkpoint-sync-url."
+                     state use --checkpoint-sync-url or --checkpoint-sync-p2p."
                 )
                 .value_name("WSS_CHECKPOINT")
                 .action(ArgAction::Set)
@@ -1244,6 +1407,20 @@ pub fn cli_app() -> Command {
                 .default_value("180")
                 .display_order(0)
         )
+        .arg(
+            Arg::new("checkpoint-sync-p2p")
+                .long("checkpoint-sync-p2p")
+                .help("Checkpoint sync by downloading the block and state of the checkpoint given \
+                    with --wss-checkpoint from peers, without a trusted HTTP endpoint. The state \
+                    is downloaded in Merkle-verified chunks from Lighthouse peers and checked \
+                    against the state root of the checkpoint block.")
+                .requires("wss-checkpoint")
+                .conflicts_with("checkpoint-sync-url")
+                .conflicts_with("checkpoint-state")
+                .action(ArgAction::SetTrue)
+                .help_heading(FLAG_HEADER)
+                .display_order(0)
+        )
         .arg(
             Arg::new("allow-insecure-genesis-sync")
                 .long("allow-insecure-genesis-sync")
@@ -1252,6 +1429,7 @@ pub fn cli_app() -> Command {
                     Only use this flag when testing. DO NOT use on mainnet!")
                 .conflicts_with("checkpoint-sync-url")
                 .conflicts_with("checkpoint-state")
+                .conflicts_with("checkpoint-sync-p2p")
                 .action(ArgAction::SetTrue)
                 .help_heading(FLAG_HEADER)
                 .display_order(0)
@@ -1403,6 +1581,18 @@ pub fn cli_app() -> Command {
                 .action(ArgAction::Set)
                 .display_order(0)
         )
+        .arg(
+            Arg::new("attestation-packing-search-timeout")
+                .long("attestation-packing-search-timeout")
+                .value_name("MILLISECONDS")
+                .help("Set the maximum number of milliseconds to spend searching for a more \
+                       rewarding set of attestations when proposing a block after the Electra \
+                       fork. The search starts from the greedy solution, so the attestations \
+                       included are never less rewarding. At most 300 milliseconds, as the \
+                       search delays the proposal. Disabled by default.")
+                .action(ArgAction::Set)
+                .display_order(0)
+        )
         .arg(
             Arg::new("paranoid-block-proposal")
                 .long("paranoid-block-proposal")
@@ -1530,6 +1720,36 @@ pub fn cli_app() -> Command {
                     filling up their disks.")
             .display_order(0)
         )
+        .arg(
+            Arg::new("network-capture-dir")
+                .long("network-capture-dir")
+                .value_name("PATH")
+                .help("Record all inbound gossip messages, their validation results and all \
+                       inbound RPC responses to files in this directory. The capture can be \
+                       replayed against a database snapshot with `lcli replay-gossip`. This \
+                       feature is only recommended for developers.")
+                .action(ArgAction::Set)
+                .display_order(0)
+        )
+        .arg(
+            Arg::new("network-capture-max-file-size")
+                .long("network-capture-max-file-size")
+                .value_name("MiB")
+                .help("The size at which a new network capture file is started. Default: 256")
+                .requires("network-capture-dir")
+                .action(ArgAction::Set)
+                .display_order(0)
+        )
+        .arg(
+            Arg::new("network-capture-max-files")
+                .long("network-capture-max-files")
+                .value_name("INTEGER")
+                .help("The number of network capture files to retain. The oldest file is \
+                       deleted when this is exceeded. Default: 16")
+                .requires("network-capture-dir")
+                .action(ArgAction::Set)
+                .display_order(0)
+        )
         .arg(
             Arg::new("beacon-processor-max-workers")
                 .long("beacon-processor-max-workers")
@@ -2,7 +2,7 @@ use account_utils::{read_input_from_user, STDIN_INPUTS_FLAG};
 use beacon_chain::chain_config::{
     DisallowedReOrgOffsets, ReOrgThreshold, DEFAULT_PREPARE_PAYLOAD_LOOKAHEAD_FACTOR,
     DEFAULT_RE_ORG_HEAD_THRESHOLD, DEFAULT_RE_ORG_MAX_EPOCHS_SINCE_FINALIZATION,
-    DEFAULT_RE_ORG_PARENT_THRESHOLD,
+    DEFAULT_RE_ORG_PARENT_THRESHOLD, MAX_ATTESTATION_PACKING_SEARCH_TIMEOUT,
 };
 use beacon_chain::graffiti_calculator::GraffitiOrigin;
 use beacon_chain::TrustedSetup;
@@ -12,14 +12,19 @@ use clap_utils::{parse_flag, parse_required};
 use client::{ClientConfig, ClientGenesis};
 use directory::{DEFAULT_BEACON_NODE_DIR, DEFAULT_NETWORK_DIR, DEFAULT_ROOT_DIR};
 use environment::RuntimeContext;
+use execution_layer::recorder::RecorderConfig;
 use execution_layer::DEFAULT_JWT_FILE;
 use genesis::Eth1Endpoint;
 use http_api::TlsConfig;
+use lighthouse_network::capture::CaptureConfig;
 use lighthouse_network::ListenAddress;
-use lighthouse_network::{multiaddr::Protocol, Enr, Multiaddr, NetworkConfig, PeerIdSerialized};
+use lighthouse_network::{
+    multiaddr::Protocol, Enr, Multiaddr, NetworkConfig, PeerGroupConfig, PeerIdSerialized,
+};
 use sensitive_url::SensitiveUrl;
 use slog::{info, warn, Logger};
 use std::cmp::max;
+use std::collections::HashSet;
 use std::fmt::Debug;
 use std::fs;
 use std::io::IsTerminal;
@@ -169,6 +174,12 @@ pub fn get_config<E: EthSpec>(
         client_config.http_api.sse_capacity_multiplier =
             parse_required(cli_args, "http-sse-capacity-multiplier")?;
 
+        client_config.http_api.sse_replay_buffer_size =
+            parse_required(cli_args, "http-sse-replay-buffer-size")?;
+
+        client_config.http_api.access_control_file =
+            clap_utils::parse_optional(cli_args, "http-access-control-file")?;
+
         client_config.http_api.enable_beacon_processor =
             parse_required(cli_args, "http-enable-beacon-processor")?;
 
@@ -298,22 +309,42 @@ pub fn get_config<E: EthSpec>(
         log,
     )?;
 
-    // JWTs are required if `--execution-endpoint` is supplied. They can be either passed via
-    // file_path or directly as string.
+    // JWTs are required if `--execution-endpoint` is supplied, unless it is an IPC endpoint which
+    // is protected by the permissions of the socket file. They can be either passed via file_path
+    // or directly as string.
+    let execution_endpoint_is_ipc = execution_layer::http::ipc_socket_path(&execution_endpoint)
+        .map_err(|e| format!("Invalid --execution-endpoint: {:?}", e))?
+        .is_some();
+    if execution_endpoint_is_ipc
+        && (cli_args.get_one::<String>("execution-jwt").is_some()
+            || cli_args
+                .get_one::<String>("execution-jwt-secret-key")
+                .is_some())
+    {
+        return Err(
+            "--execution-jwt and --execution-jwt-secret-key cannot be used with an IPC \
+            --execution-endpoint, which is protected by the permissions of the socket file instead"
+                .to_string(),
+        );
+    }
 
-    let secret_file: PathBuf;
+    let secret_file: Option<PathBuf>;
     // Parse a single JWT secret from a given file_path, logging warnings if multiple are supplied.
     if let Some(secret_files) = cli_args.get_one::<String>("execution-jwt") {
-        secret_file =
-            parse_only_one_value(secret_files, PathBuf::from_str, "--execution-jwt", log)?;
+        secret_file = Some(parse_only_one_value(
+            secret_files,
+            PathBuf::from_str,
+            "--execution-jwt",
+            log,
+        )?);
 
     // Check if the JWT secret key is passed directly via cli flag and persist it to the default
     // file location.
     } else if let Some(jwt_secret_key) = cli_args.get_one::<String>("execution-jwt-secret-key") {
         use std::fs::File;
         use std::io::Write;
-        secret_file = client_config.data_dir().join(DEFAULT_JWT_FILE);
-        let mut jwt_secret_key_file = File::create(secret_file.clone())
+        let jwt_path = client_config.data_dir().join(DEFAULT_JWT_FILE);
+        let mut jwt_secret_key_file = File::create(&jwt_path)
             .map_err(|e| format!("Error while creating jwt_secret_key file: {:?}", e))?;
         jwt_secret_key_file
             .write_all(jwt_secret_key.as_bytes())
@@ -323,15 +354,57 @@ pub fn get_config<E: EthSpec>(
                     e
                 )
             })?;
+        secret_file = Some(jwt_path);
+    } else if execution_endpoint_is_ipc {
+        secret_file = None;
     } else {
         return Err("Error! Please set either --execution-jwt file_path or --execution-jwt-secret-key directly via cli when using --execution-endpoint".to_string());
     }
 
-    // Parse and set the payload builder, if any.
-    if let Some(endpoint) = cli_args.get_one::<String>("builder") {
-        let payload_builder =
-            parse_only_one_value(endpoint, SensitiveUrl::parse, "--builder", log)?;
-        el_config.builder_url = Some(payload_builder);
+    // Parse the secondary execution engines, if any.
+    if let Some(endpoints) = cli_args.get_one::<String>("execution-secondary-endpoints") {
+        el_config.secondary_execution_endpoints = endpoints
+            .split(',')
+            .map(|endpoint| SensitiveUrl::parse(endpoint.trim()))
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("Invalid --execution-secondary-endpoints: {:?}", e))?;
+    }
+    if let Some(secret_files) = cli_args.get_one::<String>("execution-secondary-jwts") {
+        el_config.secondary_secret_files = secret_files
+            .split(',')
+            .map(|path| PathBuf::from(path.trim()))
+            .collect();
+        if el_config.secondary_secret_files.len() > el_config.secondary_execution_endpoints.len() {
+            return Err(
+                "--execution-secondary-jwts has more entries than --execution-secondary-endpoints"
+                    .to_string(),
+            );
+        }
+        for endpoint in el_config
+            .secondary_execution_endpoints
+            .iter()
+            .take(el_config.secondary_secret_files.len())
+        {
+            if execution_layer::http::ipc_socket_path(endpoint)
+                .map_err(|e| format!("Invalid --execution-secondary-endpoints: {:?}", e))?
+                .is_some()
+            {
+                return Err(format!(
+                    "--execution-secondary-jwts cannot set a JWT secret for the IPC endpoint {}",
+                    endpoint
+                ));
+            }
+        }
+    }
+    el_config.require_engine_agreement = cli_args.get_flag("execution-require-agreement");
+
+    // Parse and set the payload builders, if any.
+    if let Some(endpoints) = cli_args.get_one::<String>("builder") {
+        el_config.builder_urls = endpoints
+            .split(',')
+            .map(|endpoint| SensitiveUrl::parse(endpoint.trim()))
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("Invalid --builder: {:?}", e))?;
 
         el_config.builder_user_agent = clap_utils::parse_optional(cli_args, "builder-user-agent")?;
 
@@ -341,7 +414,7 @@ pub fn get_config<E: EthSpec>(
     }
 
     // Set config values from parse values.
-    el_config.secret_file = Some(secret_file.clone());
+    el_config.secret_file.clone_from(&secret_file);
     el_config.execution_endpoint = Some(execution_endpoint.clone());
     el_config.suggested_fee_recipient =
         clap_utils::parse_optional(cli_args, "suggested-fee-recipient")?;
@@ -354,11 +427,28 @@ pub fn get_config<E: EthSpec>(
         clap_utils::parse_required(cli_args, "execution-timeout-multiplier")?;
     el_config.execution_timeout_multiplier = Some(execution_timeout_multiplier);
 
-    client_config.eth1.endpoint = Eth1Endpoint::Auth {
-        endpoint: execution_endpoint,
-        jwt_path: secret_file,
-        jwt_id: el_config.jwt_id.clone(),
-        jwt_version: el_config.jwt_version.clone(),
+    if let Some(dir) = clap_utils::parse_optional(cli_args, "execution-record-dir")? {
+        let mut recorder_config = RecorderConfig::new(dir);
+        if let Some(max_file_size) =
+            parse_file_size_mib(cli_args, "execution-record-max-file-size")?
+        {
+            recorder_config.max_file_size = max_file_size;
+        }
+        if let Some(max_files) = clap_utils::parse_optional(cli_args, "execution-record-max-files")?
+        {
+            recorder_config.max_files = max_files;
+        }
+        el_config.engine_api_recorder = Some(recorder_config);
+    }
+
+    client_config.eth1.endpoint = match secret_file {
+        Some(jwt_path) => Eth1Endpoint::Auth {
+            endpoint: execution_endpoint,
+            jwt_path,
+            jwt_id: el_config.jwt_id.clone(),
+            jwt_version: el_config.jwt_version.clone(),
+        },
+        None => Eth1Endpoint::NoAuth(execution_endpoint),
     };
 
     // Store the EL config in the client config.
@@ -574,11 +664,16 @@ pub fn get_config<E: EthSpec>(
                 .map_err(|e| format!("Invalid checkpoint sync URL: {:?}", e))?;
 
             ClientGenesis::CheckpointSyncUrl { url }
+        } else if cli_args.get_flag("checkpoint-sync-p2p") {
+            ClientGenesis::CheckpointSyncP2p
         } else {
             ClientGenesis::GenesisState
         }
     } else {
-        if parse_flag(cli_args, "checkpoint-state") || parse_flag(cli_args, "checkpoint-sync-url") {
+        if parse_flag(cli_args, "checkpoint-state")
+            || parse_flag(cli_args, "checkpoint-sync-url")
+            || cli_args.get_flag("checkpoint-sync-p2p")
+        {
             return Err(
                 "Checkpoint sync is not available for this network as no genesis state is known"
                     .to_string(),
@@ -820,6 +915,19 @@ pub fn get_config<E: EthSpec>(
         client_config.chain.fork_choice_before_proposal_timeout_ms = timeout;
     }
 
+    if let Some(timeout) =
+        clap_utils::parse_optional::<u64>(cli_args, "attestation-packing-search-timeout")?
+    {
+        if timeout > MAX_ATTESTATION_PACKING_SEARCH_TIMEOUT {
+            return Err(format!(
+                "Invalid --attestation-packing-search-timeout value {}, expected at most {} ms",
+                timeout, MAX_ATTESTATION_PACKING_SEARCH_TIMEOUT
+            ));
+        }
+        client_config.chain.attestation_packing_search_timeout =
+            Some(Duration::from_millis(timeout));
+    }
+
     client_config.chain.always_reset_payload_statuses = cli_args.get_flag("reset-payload-statuses");
 
     client_config.chain.paranoid_block_proposal = cli_args.get_flag("paranoid-block-proposal");
@@ -856,11 +964,85 @@ pub fn get_config<E: EthSpec>(
     client_config.beacon_processor.enable_backfill_rate_limiting =
         !cli_args.get_flag("disable-backfill-rate-limiting");
 
+    if let Some(schedule) = cli_args.get_one::<String>("backfill-schedule") {
+        client_config.beacon_processor.backfill_schedule_in_slot = schedule
+            .split(',')
+            .map(|percentage| {
+                percentage
+                    .trim()
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|percentage| (1..100).contains(percentage))
+                    .map(|percentage| (percentage, 100))
+                    .ok_or_else(|| {
+                        format!(
+                            "Invalid --backfill-schedule value {:?}, expected a percentage \
+                            between 1 and 99",
+                            percentage
+                        )
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+    }
+
+    if let Some(hours) = cli_args.get_one::<String>("backfill-hours") {
+        let window = hours
+            .split_once('-')
+            .and_then(|(start, end)| Some((start.parse::<u8>().ok()?, end.parse::<u8>().ok()?)))
+            .filter(|(start, end)| *start < 24 && *end < 24 && start != end)
+            .ok_or_else(|| {
+                format!(
+                    "Invalid --backfill-hours value {:?}, expected START-END with distinct \
+                    UTC hours between 0 and 23",
+                    hours
+                )
+            })?;
+        client_config.chain.backfill_window_utc_hours = Some(window);
+    }
+
+    if let Some(pause_slots) =
+        clap_utils::parse_optional::<u64>(cli_args, "backfill-proposal-pause-slots")?
+    {
+        if pause_slots > E::slots_per_epoch() {
+            return Err(format!(
+                "Invalid --backfill-proposal-pause-slots value {}, expected at most {} slots",
+                pause_slots,
+                E::slots_per_epoch()
+            ));
+        }
+        client_config.chain.backfill_proposal_pause_slots = Some(pause_slots);
+    }
+
+    if let Some(budget) = clap_utils::parse_optional::<u8>(cli_args, "backfill-processing-budget")?
+    {
+        if !(1..=100).contains(&budget) {
+            return Err(format!(
+                "Invalid --backfill-processing-budget value {}, expected a percentage between 1 \
+                and 100",
+                budget
+            ));
+        }
+        client_config.chain.backfill_processing_budget = Some(budget);
+    }
+
     if let Some(path) = clap_utils::parse_optional(cli_args, "invalid-gossip-verified-blocks-path")?
     {
         client_config.network.invalid_block_storage = Some(path);
     }
 
+    if let Some(dir) = clap_utils::parse_optional(cli_args, "network-capture-dir")? {
+        let mut capture_config = CaptureConfig::new(dir);
+        if let Some(max_file_size) = parse_file_size_mib(cli_args, "network-capture-max-file-size")?
+        {
+            capture_config.max_file_size = max_file_size;
+        }
+        if let Some(max_files) = clap_utils::parse_optional(cli_args, "network-capture-max-files")?
+        {
+            capture_config.max_files = max_files;
+        }
+        client_config.network.network_capture = Some(capture_config);
+    }
+
     if let Some(max_workers) = clap_utils::parse_optional(cli_args, "beacon-processor-max-workers")?
     {
         client_config.beacon_processor.max_workers = max_workers;
@@ -1156,6 +1338,16 @@ pub fn set_network_config(
         config.network_load = network_load;
     }
 
+    if let Some(value) = cli_args.get_one::<String>("bandwidth-budget") {
+        let budget_mbps = value
+            .parse::<f64>()
+            .map_err(|_| format!("Invalid bandwidth budget: {}", value))?;
+        if !(budget_mbps.is_finite() && budget_mbps > 0.0) {
+            return Err(format!("Bandwidth budget must be positive: {}", value));
+        }
+        config.bandwidth_budget_mbps = Some(budget_mbps);
+    }
+
     if let Some(boot_enr_str) = cli_args.get_one::<String>("boot-nodes") {
         let mut enrs: Vec<Enr> = vec![];
         let mut multiaddrs: Vec<Multiaddr> = vec![];
@@ -1192,6 +1384,20 @@ pub fn set_network_config(
             .collect::<Result<Vec<Multiaddr>, _>>()?;
     }
 
+    if let Some(peer_groups) = cli_args.get_many::<String>("peer-group") {
+        config.peer_groups = peer_groups
+            .map(|peer_group| peer_group.parse())
+            .collect::<Result<Vec<PeerGroupConfig>, _>>()?;
+        let mut names = HashSet::new();
+        if let Some(duplicate) = config
+            .peer_groups
+            .iter()
+            .find(|group| !names.insert(group.name.as_str()))
+        {
+            return Err(format!("Duplicate peer group name: {}", duplicate.name));
+        }
+    }
+
     if parse_flag(cli_args, "disable-peer-scoring") {
         config.disable_peer_scoring = true;
     }
@@ -1518,6 +1724,22 @@ where
         .ok_or(format!("Must provide at least one value to {}", flag_name))
 }
 
+/// Parses the size in MiB given to the `flag_name` flag, returning the size in bytes.
+///
+/// Returns an error if the size is zero or too large.
+fn parse_file_size_mib(cli_args: &ArgMatches, flag_name: &str) -> Result<Option<u64>, String> {
+    clap_utils::parse_optional::<u64>(cli_args, flag_name)?
+        .map(|size_mib| {
+            if size_mib == 0 {
+                return Err(format!("--{} must be greater than zero", flag_name));
+            }
+            size_mib
+                .checked_mul(1024 * 1024)
+                .ok_or_else(|| format!("--{} is too large", flag_name))
+        })
+        .transpose()
+}
+
 /// Remove chain, freezer and blobs db.
 fn purge_db(chain_db: PathBuf, freezer_db: PathBuf, blobs_db: PathBuf) -> Result<(), String> {
     // Remove the chain_db.
@@ -0,0 +1,299 @@
+use crate::Error;
+use ssz::{Decode, Encode};
+use ssz_derive::{Decode, Encode};
+use state_processing::per_epoch_processing::process_epoch;
+use std::io::{Read, Write};
+use std::sync::Arc;
+use types::{
+    BeaconState, BeaconStateError, ChainSpec, CommitteeCache, Domain, Epoch, EthSpec, Hash256,
+    RelativeEpoch,
+};
+use zstd::{Decoder, Encoder};
+
+/// The data required to compute the committees and proposers of a finalized epoch.
+///
+/// This is stored in the freezer for every epoch at migration time, so that committee and duty
+/// queries for historic epochs don't need to load a full `BeaconState`. You MUST NOT change the
+/// fields of this struct without implementing a schema migration.
+#[derive(Debug, PartialEq, Clone, Encode, Decode)]
+pub struct CompactEpochShuffling {
+    pub epoch: Epoch,
+    /// The seed used to shuffle the attester committees of `epoch`.
+    pub attester_seed: Hash256,
+    /// The root of the block which decided the attester shuffling for `epoch`.
+    pub attester_shuffling_decision_root: Hash256,
+    /// The root of the block which decided the proposer shuffling for `epoch`.
+    pub proposer_shuffling_decision_root: Hash256,
+    /// The indices of the validators which are active in `epoch`, in ascending order.
+    pub active_validator_indices: Vec<u64>,
+    /// The index of the proposer of each slot in `epoch`.
+    pub proposer_indices: Vec<u64>,
+}
+
+impl CompactEpochShuffling {
+    /// Compute the shuffling for the current epoch of `state`.
+    ///
+    /// The `latest_block_root` should be the root of the latest block applied to `state`, and is
+    /// only used when the genesis block decides its own shuffling.
+    pub fn from_state<E: EthSpec>(
+        state: &BeaconState<E>,
+        latest_block_root: Hash256,
+        spec: &ChainSpec,
+    ) -> Result<Self, BeaconStateError> {
+        let epoch = state.current_epoch();
+        let to_u64 = |indices: Vec<usize>| indices.into_iter().map(|i| i as u64).collect();
+
+        Ok(Self {
+            epoch,
+            attester_seed: state.get_seed(epoch, Domain::BeaconAttester, spec)?,
+            attester_shuffling_decision_root: state
+                .attester_shuffling_decision_root(latest_block_root, RelativeEpoch::Current)?,
+            proposer_shuffling_decision_root: state
+                .proposer_shuffling_decision_root(latest_block_root)?,
+            active_validator_indices: to_u64(state.get_active_validator_indices(epoch, spec)?),
+            proposer_indices: to_u64(state.get_beacon_proposer_indices(spec)?),
+        })
+    }
+
+    /// Build the committee cache for `self.epoch`.
+    pub fn committee_cache<E: EthSpec>(
+        &self,
+        spec: &ChainSpec,
+    ) -> Result<Arc<CommitteeCache>, BeaconStateError> {
+        let validator_count = self
+            .active_validator_indices
+            .last()
+            .map_or(0, |index| *index as usize + 1);
+        CommitteeCache::from_seed_and_active_indices::<E>(
+            self.epoch,
+            self.attester_seed,
+            self.active_validator_indices
+                .iter()
+                .map(|index| *index as usize)
+                .collect(),
+            validator_count,
+            spec,
+        )
+    }
+
+    /// Encode as compressed bytes for storage in the freezer.
+    ///
+    /// The active validator indices are delta-encoded prior to compression, which reduces them to
+    /// a handful of bytes when there are few inactive validators.
+    pub fn as_compressed_bytes(&self, compression_level: i32) -> Result<Vec<u8>, Error> {
+        let mut previous = 0u64;
+        let deltas = self
+            .active_validator_indices
+            .iter()
+            .map(|&index| {
+                let delta = index.saturating_sub(previous);
+                previous = index;
+                delta
+            })
+            .collect();
+        let ssz_bytes = Self {
+            active_validator_indices: deltas,
+            ..self.clone()
+        }
+        .as_ssz_bytes();
+        compress(&ssz_bytes, compression_level)
+    }
+
+    /// Decode from the bytes produced by `Self::as_compressed_bytes`.
+    pub fn from_compressed_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let mut shuffling = Self::from_ssz_bytes(&decompress(bytes)?)?;
+        let mut previous = 0u64;
+        for index in shuffling.active_validator_indices.iter_mut() {
+            previous = previous.saturating_add(*index);
+            *index = previous;
+        }
+        Ok(shuffling)
+    }
+}
+
+/// The participation of each validator in a finalized epoch, as reported by the
+/// `validator_inclusion` API endpoints.
+///
+/// This is computed from the state at the end of the epoch at migration time, so that inclusion
+/// queries for historic epochs don't need to load a full `BeaconState`. You MUST NOT change the
+/// fields of this struct without implementing a schema migration.
+#[derive(Debug, PartialEq, Clone, Encode, Decode)]
+pub struct CompactEpochInclusion {
+    pub epoch: Epoch,
+    pub current_epoch_active_gwei: u64,
+    pub current_epoch_target_attesting_gwei: u64,
+    pub previous_epoch_target_attesting_gwei: u64,
+    pub previous_epoch_head_attesting_gwei: u64,
+    /// The `ValidatorInclusionFlags` of each validator in the registry.
+    pub validator_flags: Vec<u8>,
+    /// The effective balance of each validator in the registry, prior to epoch processing.
+    pub effective_balances: Vec<u64>,
+}
+
+/// The inclusion of a single validator in a `CompactEpochInclusion`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ValidatorInclusionFlags(u8);
+
+impl ValidatorInclusionFlags {
+    const SLASHED: u8 = 1 << 0;
+    const WITHDRAWABLE_IN_CURRENT_EPOCH: u8 = 1 << 1;
+    const ACTIVE_UNSLASHED_IN_CURRENT_EPOCH: u8 = 1 << 2;
+    const ACTIVE_UNSLASHED_IN_PREVIOUS_EPOCH: u8 = 1 << 3;
+    const CURRENT_EPOCH_TARGET_ATTESTER: u8 = 1 << 4;
+    const PREVIOUS_EPOCH_TARGET_ATTESTER: u8 = 1 << 5;
+    const PREVIOUS_EPOCH_HEAD_ATTESTER: u8 = 1 << 6;
+
+    fn has(self, flag: u8) -> bool {
+        self.0 & flag != 0
+    }
+
+    pub fn is_slashed(self) -> bool {
+        self.has(Self::SLASHED)
+    }
+
+    pub fn is_withdrawable_in_current_epoch(self) -> bool {
+        self.has(Self::WITHDRAWABLE_IN_CURRENT_EPOCH)
+    }
+
+    pub fn is_active_unslashed_in_current_epoch(self) -> bool {
+        self.has(Self::ACTIVE_UNSLASHED_IN_CURRENT_EPOCH)
+    }
+
+    pub fn is_active_unslashed_in_previous_epoch(self) -> bool {
+        self.has(Self::ACTIVE_UNSLASHED_IN_PREVIOUS_EPOCH)
+    }
+
+    pub fn is_current_epoch_target_attester(self) -> bool {
+        self.has(Self::CURRENT_EPOCH_TARGET_ATTESTER)
+    }
+
+    pub fn is_previous_epoch_target_attester(self) -> bool {
+        self.has(Self::PREVIOUS_EPOCH_TARGET_ATTESTER)
+    }
+
+    pub fn is_previous_epoch_head_attester(self) -> bool {
+        self.has(Self::PREVIOUS_EPOCH_HEAD_ATTESTER)
+    }
+}
+
+impl CompactEpochInclusion {
+    /// Compute the inclusion of validators in the current epoch of `state`, which must be at the
+    /// last slot of its epoch.
+    ///
+    /// Epoch processing is applied to a copy of the state, exactly as the `validator_inclusion`
+    /// endpoints would do for a historic state.
+    pub fn from_state<E: EthSpec>(state: &BeaconState<E>, spec: &ChainSpec) -> Result<Self, Error> {
+        let epoch = state.current_epoch();
+        let summary = process_epoch(&mut state.clone(), spec).map_err(Error::EpochProcessing)?;
+
+        let mut validator_flags = Vec::with_capacity(state.validators().len());
+        let mut effective_balances = Vec::with_capacity(state.validators().len());
+        for (index, validator) in state.validators().iter().enumerate() {
+            let flags = [
+                (validator.slashed, ValidatorInclusionFlags::SLASHED),
+                (
+                    validator.is_withdrawable_at(epoch),
+                    ValidatorInclusionFlags::WITHDRAWABLE_IN_CURRENT_EPOCH,
+                ),
+                (
+                    summary.is_active_unslashed_in_current_epoch(index),
+                    ValidatorInclusionFlags::ACTIVE_UNSLASHED_IN_CURRENT_EPOCH,
+                ),
+                (
+                    summary.is_active_unslashed_in_previous_epoch(index),
+                    ValidatorInclusionFlags::ACTIVE_UNSLASHED_IN_PREVIOUS_EPOCH,
+                ),
+                (
+                    summary.is_current_epoch_target_attester(index)?,
+                    ValidatorInclusionFlags::CURRENT_EPOCH_TARGET_ATTESTER,
+                ),
+                (
+                    summary.is_previous_epoch_target_attester(index)?,
+                    ValidatorInclusionFlags::PREVIOUS_EPOCH_TARGET_ATTESTER,
+                ),
+                (
+                    summary.is_previous_epoch_head_attester(index)?,
+                    ValidatorInclusionFlags::PREVIOUS_EPOCH_HEAD_ATTESTER,
+                ),
+            ];
+            validator_flags.push(
+                flags
+                    .into_iter()
+                    .filter(|(set, _)| *set)
+                    .fold(0, |acc, (_, flag)| acc | flag),
+            );
+            effective_balances.push(validator.effective_balance);
+        }
+
+        Ok(Self {
+            epoch,
+            current_epoch_active_gwei: summary.current_epoch_total_active_balance(),
+            current_epoch_target_attesting_gwei: summary
+                .current_epoch_target_attesting_balance()?,
+            previous_epoch_target_attesting_gwei: summary
+                .previous_epoch_target_attesting_balance()?,
+            previous_epoch_head_attesting_gwei: summary.previous_epoch_head_attesting_balance()?,
+            validator_flags,
+            effective_balances,
+        })
+    }
+
+    /// Returns the flags and effective balance of the validator at `index`, if it existed at the
+    /// end of the epoch.
+    pub fn validator(&self, index: usize) -> Option<(ValidatorInclusionFlags, u64)> {
+        let flags = *self.validator_flags.get(index)?;
+        let effective_balance = *self.effective_balances.get(index)?;
+        Some((ValidatorInclusionFlags(flags), effective_balance))
+    }
+
+    /// Encode as compressed bytes for storage in the freezer.
+    pub fn as_compressed_bytes(&self, compression_level: i32) -> Result<Vec<u8>, Error> {
+        compress(&self.as_ssz_bytes(), compression_level)
+    }
+
+    /// Decode from the bytes produced by `Self::as_compressed_bytes`.
+    pub fn from_compressed_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(Self::from_ssz_bytes(&decompress(bytes)?)?)
+    }
+}
+
+fn compress(ssz_bytes: &[u8], compression_level: i32) -> Result<Vec<u8>, Error> {
+    let mut out = Vec::with_capacity(ssz_bytes.len() / 8);
+    let mut encoder = Encoder::new(&mut out, compression_level).map_err(Error::Compression)?;
+    encoder.write_all(ssz_bytes).map_err(Error::Compression)?;
+    encoder.finish().map_err(Error::Compression)?;
+    Ok(out)
+}
+
+fn decompress(bytes: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut ssz_bytes = vec![];
+    let mut decoder = Decoder::new(bytes).map_err(Error::Compression)?;
+    decoder
+        .read_to_end(&mut ssz_bytes)
+        .map_err(Error::Compression)?;
+    Ok(ssz_bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn compressed_bytes_roundtrip() {
+        let shuffling = CompactEpochShuffling {
+            epoch: Epoch::new(100),
+            attester_seed: Hash256::repeat_byte(1),
+            attester_shuffling_decision_root: Hash256::repeat_byte(2),
+            proposer_shuffling_decision_root: Hash256::repeat_byte(3),
+            active_validator_indices: (0..100_000).filter(|i| i % 97 != 13).collect(),
+            proposer_indices: (0..32).map(|i| i * 1000).collect(),
+        };
+
+        let bytes = shuffling.as_compressed_bytes(1).unwrap();
+        assert!(bytes.len() < shuffling.active_validator_indices.len());
+        assert_eq!(
+            CompactEpochShuffling::from_compressed_bytes(&bytes).unwrap(),
+            shuffling
+        );
+    }
+}
@@ -3,6 +3,7 @@ use crate::config::StoreConfigError;
 use crate::hot_cold_store::HotColdDBError;
 use crate::{hdiff, DBColumn};
 use ssz::DecodeError;
+use state_processing::per_epoch_processing::errors::EpochProcessingError;
 use state_processing::BlockReplayError;
 use types::{milhouse, BeaconStateError, EpochCacheError, Hash256, InconsistentFork, Slot};
 
@@ -48,6 +49,7 @@ pub enum Error {
     MissingGenesisState,
     MissingSnapshot(Slot),
     BlockReplayError(BlockReplayError),
+    EpochProcessing(EpochProcessingError),
     MilhouseError(milhouse::Error),
     Compression(std::io::Error),
     FinalizedStateDecreasingSlot,
@@ -1,3 +1,4 @@
+use crate::compact_shuffling::{CompactEpochInclusion, CompactEpochShuffling};
 use crate::config::{OnDiskStoreConfig, StoreConfig};
 use crate::forwards_iter::{HybridForwardsBlockRootsIterator, HybridForwardsStateRootsIterator};
 use crate::hdiff::{HDiff, HDiffBuffer, HierarchyModuli, StorageStrategy};
@@ -1617,6 +1618,71 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
         Ok(())
     }
 
+    /// Store the compact shuffling for the current epoch of `state`, which must lie on an epoch
+    /// boundary.
+    pub fn store_cold_epoch_shuffling(
+        &self,
+        state: &BeaconState<E>,
+        latest_block_root: Hash256,
+        ops: &mut Vec<KeyValueStoreOp>,
+    ) -> Result<(), Error> {
+        let shuffling = CompactEpochShuffling::from_state(state, latest_block_root, &self.spec)?;
+        ops.push(KeyValueStoreOp::PutKeyValue(
+            get_key_for_col(
+                DBColumn::BeaconEpochShuffling.into(),
+                &shuffling.epoch.as_u64().to_be_bytes(),
+            ),
+            shuffling.as_compressed_bytes(self.config.compression_level)?,
+        ));
+        Ok(())
+    }
+
+    /// Load the compact shuffling for a finalized `epoch` from the freezer database.
+    pub fn get_cold_epoch_shuffling(
+        &self,
+        epoch: Epoch,
+    ) -> Result<Option<CompactEpochShuffling>, Error> {
+        self.cold_db
+            .get_bytes(
+                DBColumn::BeaconEpochShuffling.into(),
+                &epoch.as_u64().to_be_bytes(),
+            )?
+            .map(|bytes| CompactEpochShuffling::from_compressed_bytes(&bytes))
+            .transpose()
+    }
+
+    /// Store the validator inclusion for the current epoch of `state`, which must lie in the last
+    /// slot of its epoch.
+    pub fn store_cold_epoch_inclusion(
+        &self,
+        state: &BeaconState<E>,
+        ops: &mut Vec<KeyValueStoreOp>,
+    ) -> Result<(), Error> {
+        let inclusion = CompactEpochInclusion::from_state(state, &self.spec)?;
+        ops.push(KeyValueStoreOp::PutKeyValue(
+            get_key_for_col(
+                DBColumn::BeaconEpochInclusion.into(),
+                &inclusion.epoch.as_u64().to_be_bytes(),
+            ),
+            inclusion.as_compressed_bytes(self.config.compression_level)?,
+        ));
+        Ok(())
+    }
+
+    /// Load the validator inclusion for a finalized `epoch` from the freezer database.
+    pub fn get_cold_epoch_inclusion(
+        &self,
+        epoch: Epoch,
+    ) -> Result<Option<CompactEpochInclusion>, Error> {
+        self.cold_db
+            .get_bytes(
+                DBColumn::BeaconEpochInclusion.into(),
+                &epoch.as_u64().to_be_bytes(),
+            )?
+            .map(|bytes| CompactEpochInclusion::from_compressed_bytes(&bytes))
+            .transpose()
+    }
+
     /// Store a pre-finalization state in the freezer database.
     pub fn store_cold_state(
         &self,
@@ -3094,6 +3160,57 @@ pub fn migrate_database<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
             non_checkpoint_block_roots.remove(&block_root);
         }
 
+        // Store the compact shuffling for the epoch so that historic committees and duties can be
+        // served without loading a full state. This is done regardless of whether the state
+        // itself is retained in the freezer.
+        //
+        // Epoch boundary states are stored in full in the hot database, and are likely to still be
+        // in the state cache, so they are cheap to load.
+        if slot % E::slots_per_epoch() == 0 {
+            let cached_state = store.state_cache.lock().get_by_state_root(state_root);
+            let state = match cached_state {
+                Some(state) => Some(state),
+                None => get_full_state(&store.hot_db, &state_root, &store.spec)?,
+            };
+            if let Some(state) = state {
+                store.store_cold_epoch_shuffling(&state, block_root, &mut cold_db_block_ops)?;
+            } else {
+                debug!(
+                    store.log,
+                    "Missing state for epoch shuffling";
+                    "slot" => slot,
+                    "state_root" => ?state_root,
+                );
+            }
+        }
+
+        // Likewise store the validator inclusion for the epoch, which requires the state in the
+        // last slot of the epoch. That state is not stored in full, so it may need to be replayed
+        // from the epoch boundary. A failure here only degrades the `validator_inclusion` API, so
+        // it must not prevent the migration.
+        if (slot + 1) % E::slots_per_epoch() == 0 {
+            let cached_state = store.state_cache.lock().get_by_state_root(state_root);
+            let state = match cached_state {
+                Some(state) => Ok(Some(state)),
+                None => store
+                    .load_hot_state(&state_root)
+                    .map(|opt| opt.map(|(state, _)| state)),
+            };
+            let result = state.and_then(|state| match state {
+                Some(state) => store.store_cold_epoch_inclusion(&state, &mut cold_db_block_ops),
+                None => Err(HotColdDBError::MissingHotStateSummary(state_root).into()),
+            });
+            if let Err(e) = result {
+                debug!(
+                    store.log,
+                    "Unable to store epoch inclusion";
+                    "slot" => slot,
+                    "state_root" => ?state_root,
+                    "error" => ?e,
+                );
+            }
+        }
+
         // Delete the old summary, and the full state if we lie on an epoch boundary.
         hot_db_ops.push(StoreOp::DeleteState(state_root, Some(slot)));
 
@@ -9,6 +9,7 @@
 //! tests for implementation examples.
 pub mod chunked_iter;
 pub mod chunked_vector;
+pub mod compact_shuffling;
 pub mod config;
 pub mod consensus_context;
 pub mod errors;
@@ -28,6 +29,9 @@ pub mod state_cache;
 
 pub mod iter;
 
+pub use self::compact_shuffling::{
+    CompactEpochInclusion, CompactEpochShuffling, ValidatorInclusionFlags,
+};
 pub use self::config::StoreConfig;
 pub use self::consensus_context::OnDiskConsensusContext;
 pub use self::hot_cold_store::{HotColdDB, HotStateSummary, Split};
@@ -279,6 +283,12 @@ pub enum DBColumn {
     /// Mapping from state root to `ColdStateSummary` in the cold DB.
     #[strum(serialize = "bcs")]
     BeaconColdStateSummary,
+    /// Mapping from epoch to `CompactEpochShuffling` in the freezer DB.
+    #[strum(serialize = "bes")]
+    BeaconEpochShuffling,
+    /// Mapping from epoch to `CompactEpochInclusion` in the freezer DB.
+    #[strum(serialize = "bei")]
+    BeaconEpochInclusion,
     /// For the list of temporary states stored during block import,
     /// and then made non-temporary by the deletion of their state root from this column.
     #[strum(serialize = "bst")]
@@ -332,6 +342,9 @@ pub enum DBColumn {
     BeaconRandaoMixes,
     #[strum(serialize = "dht")]
     DhtEnrs,
+    /// For persisting the reputation of peers across restarts.
+    #[strum(serialize = "pdb")]
+    PeerDb,
     /// For Optimistically Imported Merge Transition Blocks
     #[strum(serialize = "otb")]
     OptimisticTransitionBlock,
@@ -387,6 +400,7 @@ impl DBColumn {
             | Self::PubkeyCache
             | Self::BeaconRestorePoint
             | Self::DhtEnrs
+            | Self::PeerDb
             | Self::OptimisticTransitionBlock => 32,
             Self::BeaconBlockRoots
             | Self::BeaconBlockRootsChunked
@@ -397,6 +411,8 @@ impl DBColumn {
             | Self::BeaconRandaoMixes
             | Self::BeaconStateSnapshot
             | Self::BeaconStateDiff
+            | Self::BeaconEpochShuffling
+            | Self::BeaconEpochInclusion
             | Self::SyncCommittee
             | Self::SyncCommitteeBranch
             | Self::LightClientUpdate => 8,
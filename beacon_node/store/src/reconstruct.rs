@@ -110,6 +110,14 @@ where
                 // Stage state for storage in freezer DB.
                 self.store_cold_state(&state_root, &state, &mut io_batch)?;
 
+                if slot % E::slots_per_epoch() == 0 {
+                    self.store_cold_epoch_shuffling(&state, block_root, &mut io_batch)?;
+                }
+
+                if (slot + 1) % E::slots_per_epoch() == 0 {
+                    self.store_cold_epoch_inclusion(&state, &mut io_batch)?;
+                }
+
                 let batch_complete =
                     num_blocks.map_or(false, |n_blocks| slot == lower_limit_slot + n_blocks as u64);
                 let reconstruction_complete = slot + 1 == upper_limit_slot;
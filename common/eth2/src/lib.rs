@@ -30,9 +30,11 @@ pub use reqwest::{StatusCode, Url};
 pub use sensitive_url::{SensitiveError, SensitiveUrl};
 use serde::{de::DeserializeOwned, Serialize};
 use ssz::Encode;
+use std::collections::VecDeque;
 use std::fmt;
 use std::future::Future;
 use std::path::PathBuf;
+use std::pin::Pin;
 use std::time::Duration;
 use store::fork_versioned_response::ExecutionOptimisticFinalizedForkVersionedResponse;
 
@@ -44,6 +46,7 @@ pub const CONSENSUS_VERSION_HEADER: &str = "Eth-Consensus-Version";
 pub const EXECUTION_PAYLOAD_BLINDED_HEADER: &str = "Eth-Execution-Payload-Blinded";
 pub const EXECUTION_PAYLOAD_VALUE_HEADER: &str = "Eth-Execution-Payload-Value";
 pub const CONSENSUS_BLOCK_VALUE_HEADER: &str = "Eth-Consensus-Block-Value";
+pub const LAST_EVENT_ID_HEADER: &str = "Last-Event-ID";
 
 pub const CONTENT_TYPE_HEADER: &str = "Content-Type";
 pub const SSZ_CONTENT_TYPE_HEADER: &str = "application/octet-stream";
@@ -2604,6 +2607,49 @@ impl BeaconNodeHttpClient {
             }))
     }
 
+    /// `GET events?topics`, reconnecting whenever the connection is lost.
+    ///
+    /// On reconnection the identifier of the last event received is sent in the `Last-Event-ID`
+    /// header, so that a beacon node which retains recent events can replay any which were missed.
+    /// A stream from a previous session may be resumed by providing its `last_event_id`.
+    ///
+    /// Connection errors are yielded by the stream but do not terminate it; a new connection is
+    /// attempted after `reconnect_delay`.
+    pub fn get_events_resuming<E: EthSpec>(
+        &self,
+        topic: &[EventTopic],
+        last_event_id: Option<u64>,
+        reconnect_delay: Duration,
+    ) -> Result<impl Stream<Item = Result<SseEvent<E>, Error>>, Error> {
+        let mut path = self.eth_path(V1)?;
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("events");
+
+        let topic_string = topic
+            .iter()
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        path.query_pairs_mut().append_pair("topics", &topic_string);
+
+        let state = ResumingEventStream::<E> {
+            client: self.client.clone(),
+            path,
+            reconnect_delay,
+            last_event_id,
+            connection: None,
+            buffer: Vec::new(),
+            pending: VecDeque::new(),
+            reconnect: false,
+        };
+
+        Ok(futures::stream::unfold(state, |mut state| async move {
+            let next = state.next_event().await;
+            Some((next, state))
+        }))
+    }
+
     /// `POST validator/duties/sync/{epoch}`
     pub async fn post_validator_duties_sync(
         &self,
@@ -2628,6 +2674,79 @@ impl BeaconNodeHttpClient {
     }
 }
 
+/// State of the stream returned by `BeaconNodeHttpClient::get_events_resuming`.
+struct ResumingEventStream<E: EthSpec> {
+    client: reqwest::Client,
+    path: Url,
+    reconnect_delay: Duration,
+    last_event_id: Option<u64>,
+    connection: Option<Pin<Box<dyn Stream<Item = reqwest::Result<bytes::Bytes>> + Send>>>,
+    /// Bytes received which do not yet form a complete event.
+    buffer: Vec<u8>,
+    /// Events which have been parsed but not yet yielded.
+    pending: VecDeque<SseEvent<E>>,
+    /// `true` if a connection has previously been attempted.
+    reconnect: bool,
+}
+
+impl<E: EthSpec> ResumingEventStream<E> {
+    async fn next_event(&mut self) -> Result<SseEvent<E>, Error> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(event);
+            }
+
+            let Some(connection) = self.connection.as_mut() else {
+                self.connect().await?;
+                continue;
+            };
+
+            match connection.next().await {
+                Some(Ok(bytes)) => {
+                    self.buffer.extend_from_slice(&bytes);
+                    self.parse_buffer()?;
+                }
+                Some(Err(e)) => {
+                    self.connection = None;
+                    return Err(Error::HttpClient(e.into()));
+                }
+                None => self.connection = None,
+            }
+        }
+    }
+
+    async fn connect(&mut self) -> Result<(), Error> {
+        if self.reconnect {
+            tokio::time::sleep(self.reconnect_delay).await;
+        }
+        self.reconnect = true;
+
+        let mut request = self.client.get(self.path.clone());
+        if let Some(last_event_id) = self.last_event_id {
+            request = request.header(LAST_EVENT_ID_HEADER, last_event_id.to_string());
+        }
+        let response = ok_or_error(request.send().await?).await?;
+
+        self.buffer.clear();
+        self.connection = Some(Box::pin(response.bytes_stream()));
+        Ok(())
+    }
+
+    /// Parse all complete events from the buffer, which are terminated by a blank line.
+    fn parse_buffer(&mut self) -> Result<(), Error> {
+        while let Some(end) = self.buffer.windows(2).position(|window| window == b"\n\n") {
+            let message = self.buffer.drain(..end + 2).collect::<Vec<_>>();
+            if let Some(event) = SseEvent::from_sse_bytes(&message[..end])? {
+                if event.id.is_some() {
+                    self.last_event_id = event.id;
+                }
+                self.pending.push_back(event);
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Returns `Ok(response)` if the response is a `200 OK` response. Otherwise, creates an
 /// appropriate error message.
 pub async fn ok_or_error(response: Response) -> Result<Response, Error> {
@@ -3,21 +3,36 @@
 mod attestation_performance;
 pub mod attestation_rewards;
 mod block_packing_efficiency;
+pub mod block_range;
 mod block_rewards;
+mod block_simulation;
+mod data_columns;
+mod packing_report;
+mod peer_stats;
+mod sse_events;
 mod standard_block_rewards;
+mod state_fields;
 mod sync_committee_rewards;
+mod validator_income;
 
 use crate::{
+    ok_or_error,
     types::{
-        DepositTreeSnapshot, Epoch, EthSpec, FinalizedExecutionBlock, GenericResponse, ValidatorId,
+        BlockId, DepositTreeSnapshot, Epoch, EthSpec, ExecutionOptimisticFinalizedResponse,
+        FinalizedExecutionBlock, GenericResponse, StateId, ValidatorId,
     },
-    BeaconNodeHttpClient, DepositData, Error, Eth1Data, Hash256, Slot,
+    BeaconNodeHttpClient, DepositData, Error, Eth1Data, Hash256, Slot, CONTENT_TYPE_HEADER,
+    EXECUTION_PAYLOAD_BLINDED_HEADER, SSZ_CONTENT_TYPE_HEADER,
 };
+use futures::{Stream, StreamExt};
 use proto_array::core::ProtoArray;
-use serde::{Deserialize, Serialize};
-use ssz::four_byte_option_impl;
+use reqwest::Response;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use ssz::{four_byte_option_impl, Encode};
 use ssz_derive::{Decode, Encode};
+use std::sync::Arc;
 use store::{AnchorInfo, BlobInfo, Split, StoreConfig};
+use types::{AbstractExecPayload, BeaconBlock, BlockType, ChainSpec};
 
 pub use attestation_performance::{
     AttestationPerformance, AttestationPerformanceQuery, AttestationPerformanceStatistics,
@@ -26,10 +41,26 @@ pub use attestation_rewards::StandardAttestationRewards;
 pub use block_packing_efficiency::{
     BlockPackingEfficiency, BlockPackingEfficiencyQuery, ProposerInfo, UniqueAttestation,
 };
+pub use block_range::{BlockRangeItem, BlockRangeQuery};
 pub use block_rewards::{AttestationRewards, BlockReward, BlockRewardMeta, BlockRewardsQuery};
+pub use block_simulation::{BlockSimulation, SimulatedPayloadStatus};
+pub use data_columns::{DataColumnCustody, StoredDataColumns};
 pub use lighthouse_network::{types::SyncState, PeerInfo};
+pub use packing_report::{
+    ExcludedAttestation, ExcludedOperation, ExclusionCounts, ExclusionReason, PackingReport,
+    PackingReportSummary,
+};
+pub use peer_stats::{
+    PeerStatsQuery, PeerStatsResponse, PeerStatsSortKey, PeerStatsSummary, DEFAULT_PEER_STATS_LIMIT,
+};
+pub use sse_events::{
+    EngineStatus, PeerConnectionEvent, SseDataColumnSidecar, SseEngineState, SsePeerConnection,
+    SseSyncState,
+};
 pub use standard_block_rewards::StandardBlockReward;
+pub use state_fields::{StateFieldProof, StateFields, StateFieldsRequest};
 pub use sync_committee_rewards::SyncCommitteeReward;
+pub use validator_income::{ValidatorIncome, ValidatorIncomeEpoch, ValidatorIncomeRequest};
 
 // Define "legacy" implementations of `Option<T>` which use four bytes for encoding the union
 // selector.
@@ -309,6 +340,36 @@ pub struct Eth1SyncStatusData {
     pub lighthouse_is_cached_and_ready: bool,
 }
 
+/// The state of an execution engine, primary engine first.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionEngineStatus {
+    /// The position of the engine, `0` for the primary engine.
+    pub index: usize,
+    /// The engine endpoint, with any credentials redacted.
+    pub endpoint: String,
+    pub status: EngineStatus,
+    /// `true` for the engine that requests are currently sent to.
+    pub preferred: bool,
+}
+
+/// The request statistics of a builder relay, in the order of `--builder`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BuilderRelayStatus {
+    /// The position of the relay in the list of relays.
+    pub index: usize,
+    /// The relay endpoint, with any credentials redacted.
+    pub endpoint: String,
+    pub requests: u64,
+    /// Requests that failed or timed out.
+    pub failures: u64,
+    /// Payload headers offered by the relay.
+    pub bids: u64,
+    /// Payload headers offered by the relay that were proposed with.
+    pub bids_selected: u64,
+    /// Moving average of the response time of successful requests.
+    pub latency_ms: Option<u64>,
+}
+
 /// A fully parsed eth1 deposit contract log.
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize, Encode, Decode)]
 pub struct DepositLog {
@@ -398,6 +459,49 @@ impl BeaconNodeHttpClient {
      * fairly simply achieved, if desired.
      */
 
+    /// `GET lighthouse/peers/{peer_id}/stats`
+    ///
+    /// Returns `Ok(None)` on a 404 error.
+    pub async fn get_lighthouse_peer_stats(
+        &self,
+        peer_id: &str,
+    ) -> Result<Option<GenericResponse<PeerStatsResponse>>, Error> {
+        let mut path = self.server.full.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("peers")
+            .push(peer_id)
+            .push("stats");
+
+        self.get_opt(path).await
+    }
+
+    /// `GET lighthouse/peers/stats?sort_by,limit`
+    pub async fn get_lighthouse_peers_stats(
+        &self,
+        sort_by: PeerStatsSortKey,
+        limit: Option<usize>,
+    ) -> Result<GenericResponse<Vec<PeerStatsSummary>>, Error> {
+        let mut path = self.server.full.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("peers")
+            .push("stats");
+
+        path.query_pairs_mut()
+            .append_pair("sort_by", &sort_by.to_string());
+        if let Some(limit) = limit {
+            path.query_pairs_mut()
+                .append_pair("limit", &limit.to_string());
+        }
+
+        self.get(path).await
+    }
+
     /// `GET lighthouse/proto_array`
     pub async fn get_lighthouse_proto_array(&self) -> Result<GenericResponse<ProtoArray>, Error> {
         let mut path = self.server.full.clone();
@@ -460,6 +564,36 @@ impl BeaconNodeHttpClient {
         self.get(path).await
     }
 
+    /// `GET lighthouse/eth1/engines`
+    pub async fn get_lighthouse_eth1_engines(
+        &self,
+    ) -> Result<GenericResponse<Vec<ExecutionEngineStatus>>, Error> {
+        let mut path = self.server.full.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("eth1")
+            .push("engines");
+
+        self.get(path).await
+    }
+
+    /// `GET lighthouse/eth1/relays`
+    pub async fn get_lighthouse_eth1_relays(
+        &self,
+    ) -> Result<GenericResponse<Vec<BuilderRelayStatus>>, Error> {
+        let mut path = self.server.full.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("eth1")
+            .push("relays");
+
+        self.get(path).await
+    }
+
     /// `GET lighthouse/eth1/block_cache`
     pub async fn get_lighthouse_eth1_block_cache(
         &self,
@@ -515,6 +649,40 @@ impl BeaconNodeHttpClient {
         self.get(path).await
     }
 
+    /// `GET lighthouse/operation_pool/packing_reports`
+    pub async fn get_lighthouse_operation_pool_packing_reports(
+        &self,
+    ) -> Result<GenericResponse<Vec<PackingReportSummary>>, Error> {
+        let mut path = self.server.full.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("operation_pool")
+            .push("packing_reports");
+
+        self.get(path).await
+    }
+
+    /// `GET lighthouse/operation_pool/packing_reports/{slot}`
+    ///
+    /// Returns `Ok(None)` on a 404 error.
+    pub async fn get_lighthouse_operation_pool_packing_report(
+        &self,
+        slot: Slot,
+    ) -> Result<Option<GenericResponse<PackingReport>>, Error> {
+        let mut path = self.server.full.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("operation_pool")
+            .push("packing_reports")
+            .push(&slot.to_string());
+
+        self.get_opt(path).await
+    }
+
     /// `POST lighthouse/database/reconstruct`
     pub async fn post_lighthouse_database_reconstruct(&self) -> Result<String, Error> {
         let mut path = self.server.full.clone();
@@ -528,6 +696,145 @@ impl BeaconNodeHttpClient {
         self.post_with_response(path, &()).await
     }
 
+    fn post_lighthouse_beacon_blocks_simulate_request<
+        E: EthSpec,
+        Payload: AbstractExecPayload<E>,
+    >(
+        &self,
+    ) -> Result<reqwest::RequestBuilder, Error> {
+        let mut path = self.server.full.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("beacon")
+            .push("blocks")
+            .push("simulate");
+
+        let blinded = matches!(Payload::block_type(), BlockType::Blinded);
+        Ok(self
+            .client
+            .post(path)
+            .header(EXECUTION_PAYLOAD_BLINDED_HEADER, blinded.to_string()))
+    }
+
+    /// `POST lighthouse/beacon/blocks/simulate`
+    ///
+    /// Apply an unsigned block to its parent state without importing it.
+    pub async fn post_lighthouse_beacon_blocks_simulate<
+        E: EthSpec,
+        Payload: AbstractExecPayload<E>,
+    >(
+        &self,
+        block: &BeaconBlock<E, Payload>,
+    ) -> Result<BlockSimulation, Error> {
+        let response = self
+            .post_lighthouse_beacon_blocks_simulate_request::<E, Payload>()?
+            .json(block)
+            .send()
+            .await?;
+
+        Ok(ok_or_error(response).await?.json().await?)
+    }
+
+    /// `POST lighthouse/beacon/blocks/simulate`
+    ///
+    /// Apply an unsigned block to its parent state without importing it, sending the block as SSZ.
+    pub async fn post_lighthouse_beacon_blocks_simulate_ssz<
+        E: EthSpec,
+        Payload: AbstractExecPayload<E>,
+    >(
+        &self,
+        block: &BeaconBlock<E, Payload>,
+    ) -> Result<BlockSimulation, Error> {
+        let response = self
+            .post_lighthouse_beacon_blocks_simulate_request::<E, Payload>()?
+            .header(CONTENT_TYPE_HEADER, SSZ_CONTENT_TYPE_HEADER)
+            .body(block.as_ssz_bytes())
+            .send()
+            .await?;
+
+        Ok(ok_or_error(response).await?.json().await?)
+    }
+
+    /// `GET lighthouse/beacon/blocks/range`
+    ///
+    /// The server streams every canonical block in `start_slot..=end_slot` as length-prefixed SSZ,
+    /// which is decoded as it arrives. Blinded blocks are requested if `Payload` is blinded.
+    pub async fn get_lighthouse_beacon_blocks_range<E: EthSpec, Payload: AbstractExecPayload<E>>(
+        &self,
+        start_slot: Slot,
+        end_slot: Slot,
+        blobs: bool,
+        spec: &ChainSpec,
+    ) -> Result<impl Stream<Item = Result<BlockRangeItem<E, Payload>, Error>>, Error> {
+        let mut path = self.server.full.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("beacon")
+            .push("blocks")
+            .push("range");
+
+        let blinded = matches!(Payload::block_type(), BlockType::Blinded);
+        path.query_pairs_mut()
+            .append_pair("start_slot", &start_slot.to_string())
+            .append_pair("end_slot", &end_slot.to_string())
+            .append_pair("blinded", &blinded.to_string())
+            .append_pair("blobs", &blobs.to_string());
+
+        let response = self.get_response(path, |builder| builder).await?;
+
+        Ok(block_range_stream(response, blobs, spec.clone()))
+    }
+
+    /// `GET lighthouse/beacon/data_columns/{block_id}`
+    ///
+    /// Returns `Ok(None)` on a 404 error.
+    pub async fn get_lighthouse_beacon_data_columns<E: EthSpec>(
+        &self,
+        block_id: BlockId,
+    ) -> Result<Option<ExecutionOptimisticFinalizedResponse<StoredDataColumns<E>>>, Error> {
+        let mut path = self.server.full.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("beacon")
+            .push("data_columns")
+            .push(&block_id.to_string());
+
+        self.get_opt(path).await
+    }
+
+    /// `POST lighthouse/beacon/states/{state_id}/fields`
+    ///
+    /// Returns `Ok(None)` on a 404 error.
+    pub async fn post_lighthouse_beacon_states_fields(
+        &self,
+        state_id: StateId,
+        paths: &[String],
+    ) -> Result<Option<ExecutionOptimisticFinalizedResponse<StateFields>>, Error> {
+        let mut path = self.server.full.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("beacon")
+            .push("states")
+            .push(&state_id.to_string())
+            .push("fields");
+
+        self.post_with_opt_response(
+            path,
+            &StateFieldsRequest {
+                paths: paths.to_vec(),
+            },
+        )
+        .await
+    }
+
     /*
      Analysis endpoints.
     */
@@ -596,4 +903,119 @@ impl BeaconNodeHttpClient {
 
         self.get(path).await
     }
+
+    /// `POST` lighthouse/analysis/validator_income
+    ///
+    /// The server streams one `ValidatorIncomeEpoch` per epoch as newline-delimited JSON, which
+    /// is decoded as it arrives.
+    pub async fn post_lighthouse_analysis_validator_income(
+        &self,
+        request: &ValidatorIncomeRequest,
+    ) -> Result<impl Stream<Item = Result<ValidatorIncomeEpoch, Error>>, Error> {
+        let mut path = self.server.full.clone();
+
+        path.path_segments_mut()
+            .map_err(|()| Error::InvalidUrl(self.server.clone()))?
+            .push("lighthouse")
+            .push("analysis")
+            .push("validator_income");
+
+        let response = self.post_generic(path, request, None).await?;
+
+        Ok(json_lines_stream(response))
+    }
+}
+
+/// Decode a response body of length-prefixed SSZ frames into a stream of blocks.
+fn block_range_stream<E: EthSpec, Payload: AbstractExecPayload<E>>(
+    response: Response,
+    blobs: bool,
+    spec: ChainSpec,
+) -> impl Stream<Item = Result<BlockRangeItem<E, Payload>, Error>> {
+    let frames_per_item = BlockRangeItem::<E, Payload>::frames_per_item(blobs);
+    let spec = Arc::new(spec);
+    futures::stream::unfold(
+        (Box::pin(response.bytes_stream()), Vec::new(), false),
+        move |(mut bytes_stream, mut buffer, done)| {
+            let spec = spec.clone();
+            async move {
+                if done {
+                    return None;
+                }
+                let mut frames = Vec::with_capacity(frames_per_item);
+                loop {
+                    while frames.len() < frames_per_item {
+                        match block_range::take_frame(&mut buffer) {
+                            Some(frame) => frames.push(frame),
+                            None => break,
+                        }
+                    }
+                    if frames.len() == frames_per_item {
+                        let item =
+                            BlockRangeItem::from_frames(&frames, &spec).map_err(Error::InvalidSsz);
+                        return Some((item, (bytes_stream, buffer, false)));
+                    }
+
+                    match bytes_stream.next().await {
+                        Some(Ok(bytes)) => buffer.extend_from_slice(&bytes),
+                        Some(Err(e)) => {
+                            return Some((
+                                Err(Error::HttpClient(e.into())),
+                                (bytes_stream, buffer, true),
+                            ))
+                        }
+                        None if frames.is_empty() && buffer.is_empty() => return None,
+                        None => {
+                            let error = ssz::DecodeError::BytesInvalid(
+                                "response ended with an incomplete frame".to_string(),
+                            );
+                            return Some((
+                                Err(Error::InvalidSsz(error)),
+                                (bytes_stream, Vec::new(), true),
+                            ));
+                        }
+                    }
+                }
+            }
+        },
+    )
+}
+
+/// Decode a response body of newline-delimited JSON into a stream of items.
+fn json_lines_stream<T: DeserializeOwned>(
+    response: Response,
+) -> impl Stream<Item = Result<T, Error>> {
+    futures::stream::unfold(
+        (Box::pin(response.bytes_stream()), Vec::new(), false),
+        |(mut bytes_stream, mut buffer, done)| async move {
+            if done {
+                return None;
+            }
+            loop {
+                if let Some(position) = buffer.iter().position(|byte| *byte == b'\n') {
+                    let line = buffer.drain(..=position).collect::<Vec<u8>>();
+                    if line.iter().all(u8::is_ascii_whitespace) {
+                        continue;
+                    }
+                    let item = serde_json::from_slice(&line).map_err(Error::InvalidJson);
+                    return Some((item, (bytes_stream, buffer, false)));
+                }
+
+                match bytes_stream.next().await {
+                    Some(Ok(bytes)) => buffer.extend_from_slice(&bytes),
+                    Some(Err(e)) => {
+                        return Some((
+                            Err(Error::HttpClient(e.into())),
+                            (bytes_stream, buffer, true),
+                        ))
+                    }
+                    None if buffer.iter().all(u8::is_ascii_whitespace) => return None,
+                    None => {
+                        let item = serde_json::from_slice(&buffer).map_err(Error::InvalidJson);
+                        return Some((item, (bytes_stream, Vec::new(), true)));
+                    }
+                }
+            }
+        },
+    )
 }
@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+use ssz::{Decode, DecodeError};
+use std::sync::Arc;
+use types::{
+    AbstractExecPayload, BlobSidecarList, ChainSpec, EthSpec, FullPayload, SignedBeaconBlock, Slot,
+};
+
+/// The number of bytes used to encode the length of each frame.
+pub const FRAME_LENGTH_BYTES: usize = 4;
+
+/// Query parameters for `lighthouse/beacon/blocks/range`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BlockRangeQuery {
+    pub start_slot: Slot,
+    /// The last slot of the range (inclusive).
+    pub end_slot: Slot,
+    /// Return blinded blocks instead of full blocks.
+    #[serde(default)]
+    pub blinded: bool,
+    /// Follow each block with a frame containing its blob sidecars.
+    #[serde(default)]
+    pub blobs: bool,
+}
+
+/// A block streamed by `lighthouse/beacon/blocks/range`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockRangeItem<E: EthSpec, Payload: AbstractExecPayload<E> = FullPayload<E>> {
+    pub block: Arc<SignedBeaconBlock<E, Payload>>,
+    /// The blob sidecars of the block, if they were requested.
+    ///
+    /// This is empty for blocks prior to Deneb.
+    pub blobs: Option<BlobSidecarList<E>>,
+}
+
+/// Append `bytes` to `buffer` as a single frame, prefixed by its length as a little-endian `u32`.
+pub fn encode_frame(bytes: &[u8], buffer: &mut Vec<u8>) {
+    buffer.reserve(FRAME_LENGTH_BYTES + bytes.len());
+    buffer.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    buffer.extend_from_slice(bytes);
+}
+
+/// Remove and return the first complete frame in `buffer`, if any.
+pub fn take_frame(buffer: &mut Vec<u8>) -> Option<Vec<u8>> {
+    let length_bytes = buffer.get(..FRAME_LENGTH_BYTES)?;
+    let mut length = [0; FRAME_LENGTH_BYTES];
+    length.copy_from_slice(length_bytes);
+    let end = FRAME_LENGTH_BYTES + u32::from_le_bytes(length) as usize;
+    if buffer.len() < end {
+        return None;
+    }
+    let frame = buffer[FRAME_LENGTH_BYTES..end].to_vec();
+    buffer.drain(..end);
+    Some(frame)
+}
+
+impl<E: EthSpec, Payload: AbstractExecPayload<E>> BlockRangeItem<E, Payload> {
+    /// The number of frames used to encode each item.
+    pub fn frames_per_item(blobs: bool) -> usize {
+        if blobs {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// Decode an item from its frames, as produced by `frames_per_item`.
+    pub fn from_frames(frames: &[Vec<u8>], spec: &ChainSpec) -> Result<Self, DecodeError> {
+        match frames {
+            [block] => Ok(Self {
+                block: Arc::new(SignedBeaconBlock::from_ssz_bytes(block, spec)?),
+                blobs: None,
+            }),
+            [block, blobs] => Ok(Self {
+                block: Arc::new(SignedBeaconBlock::from_ssz_bytes(block, spec)?),
+                blobs: Some(BlobSidecarList::from_ssz_bytes(blobs)?),
+            }),
+            _ => Err(DecodeError::BytesInvalid(format!(
+                "invalid number of frames: {}",
+                frames.len()
+            ))),
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use crate::lighthouse::StandardBlockReward;
+use serde::{Deserialize, Serialize};
+use types::{ExecutionBlockHash, Hash256, Slot};
+
+/// The outcome of verifying the execution payload of a simulated block.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum SimulatedPayloadStatus {
+    /// The block does not contain an execution payload.
+    NotApplicable,
+    /// The block is blinded, so the payload is not available for verification.
+    Unavailable,
+    /// The payload was not sent to the execution engine, either because the block is invalid or
+    /// because no execution engine is configured.
+    NotChecked,
+    /// The payload's block hash does not match its contents.
+    InvalidBlockHash {
+        validation_error: Option<String>,
+    },
+    Valid,
+    Invalid {
+        latest_valid_hash: Option<ExecutionBlockHash>,
+        validation_error: Option<String>,
+    },
+    Syncing,
+    Accepted,
+    /// The execution engine could not be reached or returned an error.
+    Error {
+        error: String,
+    },
+}
+
+/// Response for the `/lighthouse/beacon/blocks/simulate` endpoint.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct BlockSimulation {
+    pub slot: Slot,
+    pub parent_root: Hash256,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub proposer_index: u64,
+    /// `true` if the block passed all consensus checks and the execution payload was not found
+    /// to be invalid.
+    pub valid: bool,
+    /// Reasons the block is invalid, in the order they were encountered.
+    pub errors: Vec<String>,
+    /// The root of the state after applying the block, if it could be applied.
+    pub post_state_root: Option<Hash256>,
+    pub proposer_reward: Option<StandardBlockReward>,
+    pub execution_payload: SimulatedPayloadStatus,
+}
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use types::{ColumnIndex, DataColumnSidecar, EthSpec, Hash256, Slot};
+
+/// The data columns stored for a block, returned by `lighthouse/beacon/data_columns/{block_id}`.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(bound = "E: EthSpec")]
+pub struct StoredDataColumns<E: EthSpec> {
+    pub block_root: Hash256,
+    pub slot: Slot,
+    pub custody: DataColumnCustody,
+    /// The stored data columns, ordered by column index.
+    pub data_columns: Vec<Arc<DataColumnSidecar<E>>>,
+}
+
+/// Information about which data columns are custodied by the node and stored for a block.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct DataColumnCustody {
+    /// The number of data columns of each block.
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub number_of_columns: u64,
+    /// The columns which the node currently custodies and samples.
+    #[serde(with = "serde_utils::quoted_u64_vec")]
+    pub custody_columns: Vec<ColumnIndex>,
+    /// The columns which are stored for the block.
+    ///
+    /// These may differ from `custody_columns` if the node's custody has changed, or if columns
+    /// are missing for the block.
+    #[serde(with = "serde_utils::quoted_u64_vec")]
+    pub stored_columns: Vec<ColumnIndex>,
+    /// True if enough columns are stored to reconstruct the blobs of the block.
+    pub can_reconstruct_blobs: bool,
+}
@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+use types::{AttestationData, Hash256, Slot};
+
+type ValidatorIndex = u64;
+
+/// The reason that the operation pool left a candidate operation out of a block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExclusionReason {
+    InvalidAgainstHead,
+    Superseded,
+    MaxCoverLost,
+    LimitReached,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExcludedAttestation {
+    pub data: AttestationData,
+    pub num_attesting_indices: usize,
+    /// The validators whose votes would have been included for the first time.
+    pub fresh_validator_indices: Vec<ValidatorIndex>,
+    pub reason: ExclusionReason,
+}
+
+/// A slashing, exit or BLS to execution change left out of a block.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExcludedOperation {
+    pub validator_indices: Vec<ValidatorIndex>,
+    pub reason: ExclusionReason,
+}
+
+/// The operations left out of the block packed for `slot`, returned by
+/// `lighthouse/operation_pool/packing_reports/{slot}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackingReport {
+    pub slot: Slot,
+    pub parent_root: Hash256,
+    pub attestations: Vec<ExcludedAttestation>,
+    pub proposer_slashings: Vec<ExcludedOperation>,
+    pub attester_slashings: Vec<ExcludedOperation>,
+    pub voluntary_exits: Vec<ExcludedOperation>,
+    pub bls_to_execution_changes: Vec<ExcludedOperation>,
+}
+
+/// The number of operations of one kind left out of a block for each reason.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExclusionCounts {
+    pub invalid_against_head: usize,
+    pub superseded: usize,
+    pub max_cover_lost: usize,
+    pub limit_reached: usize,
+}
+
+impl ExclusionCounts {
+    pub fn from_reasons(reasons: impl IntoIterator<Item = ExclusionReason>) -> Self {
+        let mut counts = Self::default();
+        for reason in reasons {
+            match reason {
+                ExclusionReason::InvalidAgainstHead => counts.invalid_against_head += 1,
+                ExclusionReason::Superseded => counts.superseded += 1,
+                ExclusionReason::MaxCoverLost => counts.max_cover_lost += 1,
+                ExclusionReason::LimitReached => counts.limit_reached += 1,
+            }
+        }
+        counts
+    }
+}
+
+/// A summary of a `PackingReport`, returned by `lighthouse/operation_pool/packing_reports`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackingReportSummary {
+    pub slot: Slot,
+    pub parent_root: Hash256,
+    pub attestations: ExclusionCounts,
+    pub proposer_slashings: ExclusionCounts,
+    pub attester_slashings: ExclusionCounts,
+    pub voluntary_exits: ExclusionCounts,
+    pub bls_to_execution_changes: ExclusionCounts,
+}
+
+impl From<&PackingReport> for PackingReportSummary {
+    fn from(report: &PackingReport) -> Self {
+        let counts = |operations: &[ExcludedOperation]| {
+            ExclusionCounts::from_reasons(operations.iter().map(|operation| operation.reason))
+        };
+        PackingReportSummary {
+            slot: report.slot,
+            parent_root: report.parent_root,
+            attestations: ExclusionCounts::from_reasons(
+                report.attestations.iter().map(|att| att.reason),
+            ),
+            proposer_slashings: counts(&report.proposer_slashings),
+            attester_slashings: counts(&report.attester_slashings),
+            voluntary_exits: counts(&report.voluntary_exits),
+            bls_to_execution_changes: counts(&report.bls_to_execution_changes),
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use lighthouse_network::PeerStats;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// The default number of peers returned by `lighthouse/peers/stats`.
+pub const DEFAULT_PEER_STATS_LIMIT: usize = 10;
+
+/// The traffic exchanged with a peer, returned by `lighthouse/peers/{peer_id}/stats`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerStatsResponse {
+    pub peer_id: String,
+    /// The kind of client the peer is running, if known.
+    pub client: String,
+    /// The proportion of the gossip messages received from the peer which we had not already
+    /// seen, if any have been received.
+    pub useful_gossip_ratio: Option<f64>,
+    pub stats: PeerStats,
+}
+
+/// The totals of the traffic exchanged with a peer, returned by `lighthouse/peers/stats`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerStatsSummary {
+    pub peer_id: String,
+    pub client: String,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    /// The number of our RPC requests to the peer which failed or timed out.
+    pub rpc_errors: u64,
+    /// The number of duplicate gossip messages received from the peer.
+    pub duplicates_in: u64,
+    pub useful_gossip_ratio: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PeerStatsSortKey {
+    #[default]
+    BytesIn,
+    BytesOut,
+    RpcErrors,
+    Duplicates,
+}
+
+impl PeerStatsSortKey {
+    /// The value of `summary` which is sorted by, in descending order.
+    pub fn key(&self, summary: &PeerStatsSummary) -> u64 {
+        match self {
+            PeerStatsSortKey::BytesIn => summary.bytes_in,
+            PeerStatsSortKey::BytesOut => summary.bytes_out,
+            PeerStatsSortKey::RpcErrors => summary.rpc_errors,
+            PeerStatsSortKey::Duplicates => summary.duplicates_in,
+        }
+    }
+}
+
+impl FromStr for PeerStatsSortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bytes_in" => Ok(PeerStatsSortKey::BytesIn),
+            "bytes_out" => Ok(PeerStatsSortKey::BytesOut),
+            "rpc_errors" => Ok(PeerStatsSortKey::RpcErrors),
+            "duplicates" => Ok(PeerStatsSortKey::Duplicates),
+            _ => Err(format!("{} cannot be parsed as a peer stats sort key", s)),
+        }
+    }
+}
+
+impl fmt::Display for PeerStatsSortKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PeerStatsSortKey::BytesIn => write!(f, "bytes_in"),
+            PeerStatsSortKey::BytesOut => write!(f, "bytes_out"),
+            PeerStatsSortKey::RpcErrors => write!(f, "rpc_errors"),
+            PeerStatsSortKey::Duplicates => write!(f, "duplicates"),
+        }
+    }
+}
+
+/// Query parameters for `lighthouse/peers/stats`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PeerStatsQuery {
+    #[serde(default)]
+    pub sort_by: PeerStatsSortKey,
+    pub limit: Option<usize>,
+}
@@ -0,0 +1,79 @@
+//! Payloads of the Lighthouse-specific server-sent event topics.
+//!
+//! The names of these topics are prefixed with `lighthouse_` to distinguish them from the topics
+//! defined by the standard beacon node API.
+
+use crate::types::PeerDirection;
+use lighthouse_network::types::SyncState;
+use serde::{Deserialize, Serialize};
+use types::{DataColumnSidecar, EthSpec, Hash256, KzgCommitment, Slot};
+
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone)]
+pub struct SseDataColumnSidecar {
+    pub block_root: Hash256,
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub index: u64,
+    pub slot: Slot,
+    pub kzg_commitments: Vec<KzgCommitment>,
+}
+
+impl SseDataColumnSidecar {
+    pub fn from_data_column_sidecar<E: EthSpec>(
+        data_column_sidecar: &DataColumnSidecar<E>,
+    ) -> SseDataColumnSidecar {
+        SseDataColumnSidecar {
+            block_root: data_column_sidecar.block_root(),
+            index: data_column_sidecar.index,
+            slot: data_column_sidecar.slot(),
+            kzg_commitments: data_column_sidecar.kzg_commitments.to_vec(),
+        }
+    }
+}
+
+/// Emitted when the node moves between sync states, e.g. from syncing a finalized chain to
+/// syncing head chains, or from syncing to synced.
+///
+/// Progress updates within a single sync state are not emitted.
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone)]
+pub struct SseSyncState {
+    pub previous: SyncState,
+    pub current: SyncState,
+    /// `true` if the head block has not yet been fully verified by the execution engine.
+    pub execution_optimistic: bool,
+}
+
+/// The state of the connection to the execution engine.
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum EngineStatus {
+    Synced,
+    Syncing,
+    Offline,
+    AuthFailed,
+}
+
+/// Emitted when the state of the connection to the execution engine changes.
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone)]
+pub struct SseEngineState {
+    /// The previous state, or `None` if this is the first state observed since startup.
+    pub previous: Option<EngineStatus>,
+    pub current: EngineStatus,
+}
+
+#[derive(PartialEq, Eq, Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum PeerConnectionEvent {
+    Connected,
+    Disconnected,
+}
+
+/// Emitted when a peer connects or disconnects.
+#[derive(PartialEq, Debug, Serialize, Deserialize, Clone)]
+pub struct SsePeerConnection {
+    pub peer_id: String,
+    pub event: PeerConnectionEvent,
+    /// The direction of the connection. Only present for connection events.
+    pub direction: Option<PeerDirection>,
+    /// The client the peer identifies as, if known.
+    pub client: Option<String>,
+}
@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use types::{Hash256, Slot};
+
+/// The request body of `POST lighthouse/beacon/states/{state_id}/fields`.
+///
+/// Each path names a top-level field of the `BeaconState`, optionally followed by an element
+/// index or half-open range for lists and vectors, and a field name for containers, e.g.
+/// `finalized_checkpoint`, `balances[0..1000]` or `validators[123].effective_balance`.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct StateFieldsRequest {
+    pub paths: Vec<String>,
+}
+
+/// The values selected by a `StateFieldsRequest`, with proofs against `state_root`.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct StateFields {
+    pub slot: Slot,
+    pub state_root: Hash256,
+    /// One entry per selected value, in request order. Ranges are expanded to one entry per
+    /// element.
+    pub fields: Vec<StateFieldProof>,
+}
+
+/// A single value of a `BeaconState` along with a Merkle proof of its inclusion.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct StateFieldProof {
+    /// The path of the value, with ranges resolved to a single index.
+    pub path: String,
+    pub value: Value,
+    /// The tree hash chunk containing the value.
+    ///
+    /// For elements of lists of basic types like `balances` this chunk packs several elements
+    /// together, and the value is found at offset `(index % elements_per_chunk) * element_size`.
+    pub leaf: Hash256,
+    /// The generalized index of `leaf` in the tree rooted at `state_root`.
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub gindex: u64,
+    /// The sibling nodes on the path from `leaf` to `state_root`, in bottom-up order.
+    pub proof: Vec<Hash256>,
+}
@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+use types::Epoch;
+
+// Details about the balance changes of a single validator over a single epoch.
+// All values in GWei.
+//
+// The `delta` of a validator is always equal to the sum of the income components, minus
+// `withdrawals`, plus `other`.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct ValidatorIncome {
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub validator_index: u64,
+    // balance at the end of the epoch, after the epoch transition was applied
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub balance: u64,
+    // total balance change over the epoch
+    #[serde(with = "serde_utils::quoted_i64")]
+    pub delta: i64,
+    // attestation reward for head votes
+    #[serde(with = "serde_utils::quoted_i64")]
+    pub head: i64,
+    // attestation reward for target votes
+    #[serde(with = "serde_utils::quoted_i64")]
+    pub target: i64,
+    // attestation reward for source votes
+    #[serde(with = "serde_utils::quoted_i64")]
+    pub source: i64,
+    // attestation inclusion delay reward (phase0 only)
+    #[serde(with = "serde_utils::quoted_i64")]
+    pub inclusion_delay: i64,
+    // rewards and penalties for sync committee participation
+    #[serde(with = "serde_utils::quoted_i64")]
+    pub sync_committee: i64,
+    // rewards for proposing blocks
+    #[serde(with = "serde_utils::quoted_i64")]
+    pub proposal: i64,
+    // inactivity leak penalties
+    #[serde(with = "serde_utils::quoted_i64")]
+    pub inactivity: i64,
+    // initial and correlation slashing penalties
+    #[serde(with = "serde_utils::quoted_i64")]
+    pub slashing: i64,
+    // amount withdrawn to the execution layer
+    #[serde(with = "serde_utils::quoted_u64")]
+    pub withdrawals: u64,
+    // balance changes which are not income, e.g. deposits
+    #[serde(with = "serde_utils::quoted_i64")]
+    pub other: i64,
+}
+
+/// A single item of the stream returned by `/lighthouse/analysis/validator_income`.
+///
+/// Balance changes are attributed to the epoch in which they were applied. Attestation rewards
+/// are applied at the end of the epoch following the one in which the attestations were made.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct ValidatorIncomeEpoch {
+    pub epoch: Epoch,
+    pub validators: Vec<ValidatorIncome>,
+}
+
+/// Request body for the `/lighthouse/analysis/validator_income` endpoint.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ValidatorIncomeRequest {
+    pub start_epoch: Epoch,
+    pub end_epoch: Epoch,
+    #[serde(with = "serde_utils::quoted_u64_vec")]
+    pub validators: Vec<u64>,
+}
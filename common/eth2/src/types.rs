@@ -20,7 +20,9 @@ use types::beacon_block_body::KzgCommitments;
 pub use types::*;
 
 #[cfg(feature = "lighthouse")]
-use crate::lighthouse::BlockReward;
+use crate::lighthouse::{
+    BlockReward, SseDataColumnSidecar, SseEngineState, SsePeerConnection, SseSyncState,
+};
 
 /// An API error serializable to JSON.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -1127,6 +1129,16 @@ pub enum EventKind<E: EthSpec> {
     AttesterSlashing(Box<AttesterSlashing<E>>),
     BlsToExecutionChange(Box<SignedBlsToExecutionChange>),
     BlockGossip(Box<BlockGossip>),
+    #[cfg(feature = "lighthouse")]
+    LighthouseDataColumnSidecar(SseDataColumnSidecar),
+    #[cfg(feature = "lighthouse")]
+    LighthouseSyncCommitteeMessage(Box<SyncCommitteeMessage>),
+    #[cfg(feature = "lighthouse")]
+    LighthouseSyncState(SseSyncState),
+    #[cfg(feature = "lighthouse")]
+    LighthouseEngineState(SseEngineState),
+    #[cfg(feature = "lighthouse")]
+    LighthousePeerConnection(SsePeerConnection),
 }
 
 impl<E: EthSpec> EventKind<E> {
@@ -1150,6 +1162,16 @@ impl<E: EthSpec> EventKind<E> {
             EventKind::AttesterSlashing(_) => "attester_slashing",
             EventKind::BlsToExecutionChange(_) => "bls_to_execution_change",
             EventKind::BlockGossip(_) => "block_gossip",
+            #[cfg(feature = "lighthouse")]
+            EventKind::LighthouseDataColumnSidecar(_) => "lighthouse_data_column_sidecar",
+            #[cfg(feature = "lighthouse")]
+            EventKind::LighthouseSyncCommitteeMessage(_) => "lighthouse_sync_committee_message",
+            #[cfg(feature = "lighthouse")]
+            EventKind::LighthouseSyncState(_) => "lighthouse_sync_state",
+            #[cfg(feature = "lighthouse")]
+            EventKind::LighthouseEngineState(_) => "lighthouse_engine_state",
+            #[cfg(feature = "lighthouse")]
+            EventKind::LighthousePeerConnection(_) => "lighthouse_peer_connection",
         }
     }
 
@@ -1171,6 +1193,11 @@ impl<E: EthSpec> EventKind<E> {
             })?
             .trim_start_matches("data:");
 
+        Self::from_sse_fields(event, data)
+    }
+
+    /// Parse an event from the values of the `event` and `data` fields of a server-sent event.
+    pub fn from_sse_fields(event: &str, data: &str) -> Result<Self, ServerError> {
         match event {
             "attestation" => Ok(EventKind::Attestation(serde_json::from_str(data).map_err(
                 |e| ServerError::InvalidServerSentEvent(format!("Attestation: {:?}", e)),
@@ -1248,6 +1275,36 @@ impl<E: EthSpec> EventKind<E> {
             "block_gossip" => Ok(EventKind::BlockGossip(serde_json::from_str(data).map_err(
                 |e| ServerError::InvalidServerSentEvent(format!("Block Gossip: {:?}", e)),
             )?)),
+            #[cfg(feature = "lighthouse")]
+            "lighthouse_data_column_sidecar" => Ok(EventKind::LighthouseDataColumnSidecar(
+                serde_json::from_str(data).map_err(|e| {
+                    ServerError::InvalidServerSentEvent(format!("Data Column Sidecar: {:?}", e))
+                })?,
+            )),
+            #[cfg(feature = "lighthouse")]
+            "lighthouse_sync_committee_message" => Ok(EventKind::LighthouseSyncCommitteeMessage(
+                serde_json::from_str(data).map_err(|e| {
+                    ServerError::InvalidServerSentEvent(format!("Sync Committee Message: {:?}", e))
+                })?,
+            )),
+            #[cfg(feature = "lighthouse")]
+            "lighthouse_sync_state" => Ok(EventKind::LighthouseSyncState(
+                serde_json::from_str(data).map_err(|e| {
+                    ServerError::InvalidServerSentEvent(format!("Sync State: {:?}", e))
+                })?,
+            )),
+            #[cfg(feature = "lighthouse")]
+            "lighthouse_engine_state" => Ok(EventKind::LighthouseEngineState(
+                serde_json::from_str(data).map_err(|e| {
+                    ServerError::InvalidServerSentEvent(format!("Engine State: {:?}", e))
+                })?,
+            )),
+            #[cfg(feature = "lighthouse")]
+            "lighthouse_peer_connection" => Ok(EventKind::LighthousePeerConnection(
+                serde_json::from_str(data).map_err(|e| {
+                    ServerError::InvalidServerSentEvent(format!("Peer Connection: {:?}", e))
+                })?,
+            )),
             _ => Err(ServerError::InvalidServerSentEvent(
                 "Could not parse event tag".to_string(),
             )),
@@ -1255,6 +1312,66 @@ impl<E: EthSpec> EventKind<E> {
     }
 }
 
+/// A server-sent event, along with the identifier assigned to it by the beacon node.
+///
+/// The identifier is only present if the beacon node supports resuming event streams with the
+/// `Last-Event-ID` header.
+#[derive(PartialEq, Debug, Clone)]
+pub struct SseEvent<E: EthSpec> {
+    pub id: Option<u64>,
+    pub kind: EventKind<E>,
+}
+
+impl<E: EthSpec> SseEvent<E> {
+    /// Parse a single server-sent event message, excluding the trailing blank line.
+    ///
+    /// Returns `Ok(None)` if the message contains no event, e.g. a keep-alive comment.
+    pub fn from_sse_bytes(message: &[u8]) -> Result<Option<Self>, ServerError> {
+        let s = from_utf8(message)
+            .map_err(|e| ServerError::InvalidServerSentEvent(format!("{:?}", e)))?;
+
+        let mut event = None;
+        let mut data: Option<String> = None;
+        let mut id = None;
+        for line in s.lines() {
+            // Lines beginning with a colon are comments.
+            if line.starts_with(':') {
+                continue;
+            }
+            let (field, value) = line.split_once(':').unwrap_or((line, ""));
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match field {
+                "event" => event = Some(value),
+                // Multiple data fields are joined with newlines.
+                "data" => match &mut data {
+                    Some(data) => {
+                        data.push('\n');
+                        data.push_str(value);
+                    }
+                    None => data = Some(value.to_string()),
+                },
+                "id" => {
+                    id = Some(value.parse::<u64>().map_err(|e| {
+                        ServerError::InvalidServerSentEvent(format!("Invalid id: {:?}", e))
+                    })?)
+                }
+                _ => (),
+            }
+        }
+
+        match (event, data) {
+            (Some(event), Some(data)) => Ok(Some(Self {
+                id,
+                kind: EventKind::from_sse_fields(event, &data)?,
+            })),
+            (None, None) => Ok(None),
+            _ => Err(ServerError::InvalidServerSentEvent(
+                "Event must contain both event and data fields".to_string(),
+            )),
+        }
+    }
+}
+
 #[derive(Clone, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct EventQuery {
@@ -1283,6 +1400,16 @@ pub enum EventTopic {
     ProposerSlashing,
     BlsToExecutionChange,
     BlockGossip,
+    #[cfg(feature = "lighthouse")]
+    LighthouseDataColumnSidecar,
+    #[cfg(feature = "lighthouse")]
+    LighthouseSyncCommitteeMessage,
+    #[cfg(feature = "lighthouse")]
+    LighthouseSyncState,
+    #[cfg(feature = "lighthouse")]
+    LighthouseEngineState,
+    #[cfg(feature = "lighthouse")]
+    LighthousePeerConnection,
 }
 
 impl FromStr for EventTopic {
@@ -1308,6 +1435,16 @@ impl FromStr for EventTopic {
             "proposer_slashing" => Ok(EventTopic::ProposerSlashing),
             "bls_to_execution_change" => Ok(EventTopic::BlsToExecutionChange),
             "block_gossip" => Ok(EventTopic::BlockGossip),
+            #[cfg(feature = "lighthouse")]
+            "lighthouse_data_column_sidecar" => Ok(EventTopic::LighthouseDataColumnSidecar),
+            #[cfg(feature = "lighthouse")]
+            "lighthouse_sync_committee_message" => Ok(EventTopic::LighthouseSyncCommitteeMessage),
+            #[cfg(feature = "lighthouse")]
+            "lighthouse_sync_state" => Ok(EventTopic::LighthouseSyncState),
+            #[cfg(feature = "lighthouse")]
+            "lighthouse_engine_state" => Ok(EventTopic::LighthouseEngineState),
+            #[cfg(feature = "lighthouse")]
+            "lighthouse_peer_connection" => Ok(EventTopic::LighthousePeerConnection),
             _ => Err("event topic cannot be parsed.".to_string()),
         }
     }
@@ -1334,6 +1471,20 @@ impl fmt::Display for EventTopic {
             EventTopic::ProposerSlashing => write!(f, "proposer_slashing"),
             EventTopic::BlsToExecutionChange => write!(f, "bls_to_execution_change"),
             EventTopic::BlockGossip => write!(f, "block_gossip"),
+            #[cfg(feature = "lighthouse")]
+            EventTopic::LighthouseDataColumnSidecar => {
+                write!(f, "lighthouse_data_column_sidecar")
+            }
+            #[cfg(feature = "lighthouse")]
+            EventTopic::LighthouseSyncCommitteeMessage => {
+                write!(f, "lighthouse_sync_committee_message")
+            }
+            #[cfg(feature = "lighthouse")]
+            EventTopic::LighthouseSyncState => write!(f, "lighthouse_sync_state"),
+            #[cfg(feature = "lighthouse")]
+            EventTopic::LighthouseEngineState => write!(f, "lighthouse_engine_state"),
+            #[cfg(feature = "lighthouse")]
+            EventTopic::LighthousePeerConnection => write!(f, "lighthouse_peer_connection"),
         }
     }
 }
@@ -1597,6 +1748,34 @@ mod tests {
         .expect("should decode BlockAndBlobSidecars");
         assert!(matches!(decoded, PublishBlockRequest::BlockContents(_)));
     }
+
+    #[test]
+    fn sse_event_multi_line_data() {
+        type E = MainnetEthSpec;
+        let checkpoint = SseFinalizedCheckpoint {
+            block: Hash256::repeat_byte(1),
+            state: Hash256::repeat_byte(2),
+            epoch: Epoch::new(3),
+            execution_optimistic: false,
+        };
+        let data = serde_json::to_string_pretty(&checkpoint).unwrap();
+        let message = format!(
+            "event: finalized_checkpoint\n{}\nid: 7",
+            data.lines()
+                .map(|line| format!("data: {}", line))
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+        assert!(data.lines().count() > 1);
+
+        assert_eq!(
+            SseEvent::<E>::from_sse_bytes(message.as_bytes()).unwrap(),
+            Some(SseEvent {
+                id: Some(7),
+                kind: EventKind::FinalizedCheckpoint(checkpoint),
+            })
+        );
+    }
 }
 
 #[derive(Debug, Encode, Serialize, Deserialize)]
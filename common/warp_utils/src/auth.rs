@@ -0,0 +1,516 @@
+//! Optional bearer-token authentication for HTTP servers.
+//!
+//! Each token is given a name, a list of routes which it may access and an optional rate limit.
+//! Tokens are loaded from a YAML file, for example:
+//!
+//! ```yaml
+//! tokens:
+//!   - name: explorer
+//!     token: "0x4e7a..."
+//!     allow:
+//!       - path: /eth/v1/beacon/*
+//!         methods: [GET]
+//!       - path: /eth/v1/node/*
+//!     rate_limit:
+//!       requests_per_second: 10
+//!       burst: 50
+//!   - name: admin
+//!     token: "0x9f12..."
+//!     allow:
+//!       - path: /*
+//! ```
+//!
+//! Paths ending in `*` match any path with the preceding prefix, provided that the prefix ends on
+//! a path segment boundary, e.g. `/eth/v1/beacon*` matches `/eth/v1/beacon/genesis` but not
+//! `/eth/v1/beacons`. Other paths must match exactly. Rules without `methods` match all methods.
+use crate::metrics::{
+    HTTP_AUTH_FORBIDDEN_TOTAL, HTTP_AUTH_RATE_LIMITED_TOTAL, HTTP_AUTH_REQUESTS_TOTAL,
+    HTTP_AUTH_UNAUTHENTICATED_TOTAL,
+};
+use crate::reject::{invalid_auth, rate_limited, route_forbidden, unauthorized};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
+use warp::filters::path::FullPath;
+use warp::http::Method;
+use warp::Filter;
+
+const BEARER_PREFIX: &str = "Bearer ";
+
+/// The contents of an access control file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AccessControlConfig {
+    pub tokens: Vec<TokenConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TokenConfig {
+    /// A name for the token, used in metrics.
+    pub name: String,
+    /// The secret which must be provided in an `Authorization: Bearer <token>` header.
+    pub token: String,
+    /// The routes which may be accessed with this token.
+    pub allow: Vec<RouteRule>,
+    #[serde(default)]
+    pub rate_limit: Option<RateLimit>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RouteRule {
+    pub path: String,
+    /// The methods which may be used, e.g. `GET`. All methods are allowed if `None`.
+    #[serde(default)]
+    pub methods: Option<Vec<String>>,
+}
+
+impl RouteRule {
+    fn matches(&self, method: &Method, path: &str) -> bool {
+        let path_matches = match self.path.strip_suffix('*') {
+            Some(prefix) => path.strip_prefix(prefix).is_some_and(|rest| {
+                prefix.ends_with('/') || rest.is_empty() || rest.starts_with('/')
+            }),
+            None => path == self.path,
+        };
+        let method_matches = self.methods.as_ref().map_or(true, |methods| {
+            methods
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(method.as_str()))
+        });
+        path_matches && method_matches
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimit {
+    /// The sustained number of requests permitted per second.
+    pub requests_per_second: f64,
+    /// The number of requests which may be made in a burst, above the sustained rate.
+    pub burst: u32,
+}
+
+/// A token bucket which is refilled continuously at the sustained rate.
+#[derive(Debug)]
+struct TokenBucket {
+    limit: RateLimit,
+    available: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            available: limit.burst.max(1) as f64,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Returns `true` if a request is permitted, consuming one unit of the bucket.
+    fn try_acquire(&mut self, now: Instant) -> bool {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.last_refill = now;
+        self.available = (self.available + elapsed * self.limit.requests_per_second)
+            .min(self.limit.burst.max(1) as f64);
+        if self.available >= 1.0 {
+            self.available -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Client {
+    token: String,
+    name: String,
+    allow: Vec<RouteRule>,
+    bucket: Option<Mutex<TokenBucket>>,
+}
+
+/// The outcome of checking a request against the access control list.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AccessDecision {
+    Allowed,
+    /// No `Authorization` header was provided.
+    Unauthenticated,
+    /// The token is not known.
+    InvalidToken,
+    /// The token is not permitted to access the route.
+    Forbidden {
+        name: String,
+    },
+    /// The token has exceeded its rate limit.
+    RateLimited {
+        name: String,
+    },
+}
+
+/// The set of tokens permitted to access an HTTP server.
+#[derive(Debug)]
+pub struct AccessControl {
+    clients: Vec<Client>,
+}
+
+impl AccessControl {
+    pub fn new(config: AccessControlConfig) -> Result<Self, String> {
+        let mut clients: Vec<Client> = Vec::with_capacity(config.tokens.len());
+        for token in config.tokens {
+            if token.token.is_empty() {
+                return Err(format!("token {} is empty", token.name));
+            }
+            if let Some(limit) = token.rate_limit {
+                if limit.requests_per_second.is_nan() || limit.requests_per_second <= 0.0 {
+                    return Err(format!(
+                        "rate limit of token {} must be greater than zero",
+                        token.name
+                    ));
+                }
+            }
+            if clients.iter().any(|client| client.token == token.token) {
+                return Err(format!("token {} is duplicated", token.name));
+            }
+            clients.push(Client {
+                token: token.token,
+                name: token.name,
+                allow: token.allow,
+                bucket: token
+                    .rate_limit
+                    .map(|limit| Mutex::new(TokenBucket::new(limit))),
+            });
+        }
+        Ok(Self { clients })
+    }
+
+    /// Load the access control list from a YAML file.
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let file = File::open(path)
+            .map_err(|e| format!("Unable to open access control file {:?}: {:?}", path, e))?;
+        let config: AccessControlConfig = serde_yaml::from_reader(file)
+            .map_err(|e| format!("Unable to parse access control file {:?}: {:?}", path, e))?;
+        Self::new(config)
+    }
+
+    /// Check whether a request with the given `Authorization` header is permitted.
+    pub fn check(
+        &self,
+        authorization: Option<&str>,
+        method: &Method,
+        path: &str,
+    ) -> AccessDecision {
+        let Some(authorization) = authorization else {
+            return AccessDecision::Unauthenticated;
+        };
+        let Some(client) = authorization
+            .strip_prefix(BEARER_PREFIX)
+            .and_then(|token| self.find_client(token.trim()))
+        else {
+            return AccessDecision::InvalidToken;
+        };
+
+        if !client.allow.iter().any(|rule| rule.matches(method, path)) {
+            return AccessDecision::Forbidden {
+                name: client.name.clone(),
+            };
+        }
+
+        if let Some(bucket) = &client.bucket {
+            if !bucket.lock().try_acquire(Instant::now()) {
+                return AccessDecision::RateLimited {
+                    name: client.name.clone(),
+                };
+            }
+        }
+
+        metrics::inc_counter_vec(&HTTP_AUTH_REQUESTS_TOTAL, &[&client.name]);
+        AccessDecision::Allowed
+    }
+
+    /// Find the client with the given `token`.
+    ///
+    /// Every token is compared in constant time, so that the time taken does not reveal how much
+    /// of a token was guessed correctly.
+    fn find_client(&self, token: &str) -> Option<&Client> {
+        let mut found = None;
+        for client in &self.clients {
+            if constant_time_eq(client.token.as_bytes(), token.as_bytes()) {
+                found = Some(client);
+            }
+        }
+        found
+    }
+}
+
+/// Compare two byte strings in a time which depends only on their lengths.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let difference = a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y));
+    std::hint::black_box(difference) == 0
+}
+
+/// A filter which rejects requests that are not permitted by `access_control`.
+///
+/// All requests are permitted if `access_control` is `None`.
+pub fn access_control_filter(
+    access_control: Option<Arc<AccessControl>>,
+) -> warp::filters::BoxedFilter<()> {
+    warp::any()
+        .map(move || access_control.clone())
+        .and(warp::method())
+        .and(warp::path::full())
+        .and(warp::header::optional::<String>("Authorization"))
+        .and_then(
+            |access_control: Option<Arc<AccessControl>>,
+             method: Method,
+             path: FullPath,
+             authorization: Option<String>| async move {
+                let Some(access_control) = access_control else {
+                    return Ok(());
+                };
+                match access_control.check(authorization.as_deref(), &method, path.as_str()) {
+                    AccessDecision::Allowed => Ok(()),
+                    AccessDecision::Unauthenticated => {
+                        metrics::inc_counter(&HTTP_AUTH_UNAUTHENTICATED_TOTAL);
+                        Err(unauthorized("missing Authorization header".to_string()))
+                    }
+                    AccessDecision::InvalidToken => {
+                        metrics::inc_counter(&HTTP_AUTH_UNAUTHENTICATED_TOTAL);
+                        Err(invalid_auth("unknown token".to_string()))
+                    }
+                    AccessDecision::Forbidden { name } => {
+                        metrics::inc_counter_vec(&HTTP_AUTH_FORBIDDEN_TOTAL, &[&name]);
+                        Err(route_forbidden(format!(
+                            "token {} may not {} {}",
+                            name,
+                            method,
+                            path.as_str()
+                        )))
+                    }
+                    AccessDecision::RateLimited { name } => {
+                        metrics::inc_counter_vec(&HTTP_AUTH_RATE_LIMITED_TOTAL, &[&name]);
+                        Err(rate_limited(format!(
+                            "token {} exceeded its rate limit",
+                            name
+                        )))
+                    }
+                }
+            },
+        )
+        .untuple_one()
+        .boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reject::handle_rejection;
+    use std::time::Duration;
+    use warp::http::StatusCode;
+
+    fn access_control() -> AccessControl {
+        AccessControl::new(AccessControlConfig {
+            tokens: vec![
+                TokenConfig {
+                    name: "reader".to_string(),
+                    token: "read".to_string(),
+                    allow: vec![RouteRule {
+                        path: "/eth/v1/beacon/*".to_string(),
+                        methods: Some(vec!["GET".to_string()]),
+                    }],
+                    rate_limit: Some(RateLimit {
+                        requests_per_second: 1.0,
+                        burst: 2,
+                    }),
+                },
+                TokenConfig {
+                    name: "admin".to_string(),
+                    token: "admin".to_string(),
+                    allow: vec![RouteRule {
+                        path: "/*".to_string(),
+                        methods: None,
+                    }],
+                    rate_limit: None,
+                },
+            ],
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn route_allowlist() {
+        let access_control = access_control();
+        let check = |auth, method, path| access_control.check(auth, &method, path);
+
+        assert_eq!(
+            check(
+                Some("Bearer admin"),
+                Method::POST,
+                "/lighthouse/database/reconstruct"
+            ),
+            AccessDecision::Allowed
+        );
+        assert_eq!(
+            check(Some("Bearer read"), Method::GET, "/eth/v1/beacon/genesis"),
+            AccessDecision::Allowed
+        );
+        assert_eq!(
+            check(
+                Some("Bearer read"),
+                Method::POST,
+                "/eth/v1/beacon/pool/attestations"
+            ),
+            AccessDecision::Forbidden {
+                name: "reader".to_string()
+            }
+        );
+        assert_eq!(
+            check(Some("Bearer read"), Method::GET, "/lighthouse/health"),
+            AccessDecision::Forbidden {
+                name: "reader".to_string()
+            }
+        );
+        assert_eq!(
+            check(
+                Some("Bearer unknown"),
+                Method::GET,
+                "/eth/v1/beacon/genesis"
+            ),
+            AccessDecision::InvalidToken
+        );
+        assert_eq!(
+            check(Some("admin"), Method::GET, "/eth/v1/beacon/genesis"),
+            AccessDecision::InvalidToken
+        );
+        assert_eq!(
+            check(None, Method::GET, "/eth/v1/beacon/genesis"),
+            AccessDecision::Unauthenticated
+        );
+    }
+
+    #[test]
+    fn prefix_matches_whole_segments() {
+        let rule = RouteRule {
+            path: "/eth/v1/beacon*".to_string(),
+            methods: None,
+        };
+        assert!(rule.matches(&Method::GET, "/eth/v1/beacon"));
+        assert!(rule.matches(&Method::GET, "/eth/v1/beacon/genesis"));
+        assert!(!rule.matches(&Method::GET, "/eth/v1/beacons"));
+        assert!(!rule.matches(&Method::GET, "/eth/v1/beacon_admin/genesis"));
+
+        let rule = RouteRule {
+            path: "/eth/v1/beacon/*".to_string(),
+            methods: None,
+        };
+        assert!(rule.matches(&Method::GET, "/eth/v1/beacon/genesis"));
+        assert!(!rule.matches(&Method::GET, "/eth/v1/beacon"));
+    }
+
+    #[test]
+    fn constant_time_comparison() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+        assert!(!constant_time_eq(b"", b"secret"));
+    }
+
+    async fn request_status(
+        access_control: &Arc<AccessControl>,
+        authorization: Option<&str>,
+        path: &str,
+    ) -> StatusCode {
+        let filter = access_control_filter(Some(access_control.clone()))
+            .map(|| "ok")
+            .recover(handle_rejection);
+        let mut request = warp::test::request().method("GET").path(path);
+        if let Some(authorization) = authorization {
+            request = request.header("Authorization", authorization);
+        }
+        request.reply(&filter).await.status()
+    }
+
+    #[tokio::test]
+    async fn http_responses() {
+        let access_control = Arc::new(access_control());
+        let path = "/eth/v1/beacon/genesis";
+
+        assert_eq!(
+            request_status(&access_control, None, path).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            request_status(&access_control, Some("Bearer unknown"), path).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            request_status(&access_control, Some("Bearer read"), "/lighthouse/health").await,
+            StatusCode::FORBIDDEN
+        );
+
+        // The reader may make a burst of two requests before it is rate limited.
+        for _ in 0..2 {
+            assert_eq!(
+                request_status(&access_control, Some("Bearer read"), path).await,
+                StatusCode::OK
+            );
+        }
+        assert_eq!(
+            request_status(&access_control, Some("Bearer read"), path).await,
+            StatusCode::TOO_MANY_REQUESTS
+        );
+
+        // Other tokens are not affected by the rate limit.
+        assert_eq!(
+            request_status(&access_control, Some("Bearer admin"), path).await,
+            StatusCode::OK
+        );
+    }
+
+    #[test]
+    fn token_bucket() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(RateLimit {
+            requests_per_second: 2.0,
+            burst: 3,
+        });
+        bucket.last_refill = start;
+
+        // The burst may be used immediately.
+        assert!((0..3).all(|_| bucket.try_acquire(start)));
+        assert!(!bucket.try_acquire(start));
+
+        // The bucket refills at the sustained rate.
+        assert!(bucket.try_acquire(start + Duration::from_millis(500)));
+        assert!(!bucket.try_acquire(start + Duration::from_millis(500)));
+
+        // The bucket never holds more than the burst.
+        let later = start + Duration::from_secs(60);
+        assert!((0..3).all(|_| bucket.try_acquire(later)));
+        assert!(!bucket.try_acquire(later));
+    }
+
+    #[test]
+    fn rejects_duplicate_tokens() {
+        let token = TokenConfig {
+            name: "a".to_string(),
+            token: "secret".to_string(),
+            allow: vec![],
+            rate_limit: None,
+        };
+        assert!(AccessControl::new(AccessControlConfig {
+            tokens: vec![token.clone(), token],
+        })
+        .is_err());
+    }
+}
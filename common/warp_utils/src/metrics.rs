@@ -121,6 +121,34 @@ pub static BOOT_TIME: LazyLock<Result<IntGauge>> = LazyLock::new(|| {
     )
 });
 
+pub static HTTP_AUTH_REQUESTS_TOTAL: LazyLock<Result<IntCounterVec>> = LazyLock::new(|| {
+    try_create_int_counter_vec(
+        "http_api_auth_requests_total",
+        "Count of authenticated HTTP requests permitted, by token name",
+        &["token"],
+    )
+});
+pub static HTTP_AUTH_FORBIDDEN_TOTAL: LazyLock<Result<IntCounterVec>> = LazyLock::new(|| {
+    try_create_int_counter_vec(
+        "http_api_auth_forbidden_total",
+        "Count of HTTP requests rejected because the route is not allowed for the token",
+        &["token"],
+    )
+});
+pub static HTTP_AUTH_RATE_LIMITED_TOTAL: LazyLock<Result<IntCounterVec>> = LazyLock::new(|| {
+    try_create_int_counter_vec(
+        "http_api_auth_rate_limited_total",
+        "Count of HTTP requests rejected because the token exceeded its rate limit",
+        &["token"],
+    )
+});
+pub static HTTP_AUTH_UNAUTHENTICATED_TOTAL: LazyLock<Result<IntCounter>> = LazyLock::new(|| {
+    try_create_int_counter(
+        "http_api_auth_unauthenticated_total",
+        "Count of HTTP requests rejected due to a missing or unknown token",
+    )
+});
+
 pub fn scrape_health_metrics() {
     scrape_process_health_metrics();
     scrape_system_health_metrics();
@@ -136,6 +136,33 @@ pub fn invalid_auth(msg: String) -> warp::reject::Rejection {
     warp::reject::custom(InvalidAuthorization(msg))
 }
 
+#[derive(Debug)]
+pub struct Unauthorized(pub String);
+
+impl Reject for Unauthorized {}
+
+pub fn unauthorized(msg: String) -> warp::reject::Rejection {
+    warp::reject::custom(Unauthorized(msg))
+}
+
+#[derive(Debug)]
+pub struct RouteForbidden(pub String);
+
+impl Reject for RouteForbidden {}
+
+pub fn route_forbidden(msg: String) -> warp::reject::Rejection {
+    warp::reject::custom(RouteForbidden(msg))
+}
+
+#[derive(Debug)]
+pub struct RateLimited(pub String);
+
+impl Reject for RateLimited {}
+
+pub fn rate_limited(msg: String) -> warp::reject::Rejection {
+    warp::reject::custom(RateLimited(msg))
+}
+
 #[derive(Debug)]
 pub struct UnsupportedMediaType(pub String);
 
@@ -228,6 +255,15 @@ pub async fn handle_rejection(err: warp::Rejection) -> Result<impl warp::Reply,
     } else if let Some(e) = err.find::<crate::reject::InvalidAuthorization>() {
         code = StatusCode::FORBIDDEN;
         message = format!("FORBIDDEN: Invalid auth token: {}", e.0);
+    } else if let Some(e) = err.find::<crate::reject::Unauthorized>() {
+        code = StatusCode::UNAUTHORIZED;
+        message = format!("UNAUTHORIZED: {}", e.0);
+    } else if let Some(e) = err.find::<crate::reject::RouteForbidden>() {
+        code = StatusCode::FORBIDDEN;
+        message = format!("FORBIDDEN: {}", e.0);
+    } else if let Some(e) = err.find::<crate::reject::RateLimited>() {
+        code = StatusCode::TOO_MANY_REQUESTS;
+        message = format!("TOO_MANY_REQUESTS: {}", e.0);
     } else if let Some(e) = err.find::<warp::reject::MissingHeader>() {
         if e.name().eq("Authorization") {
             code = StatusCode::UNAUTHORIZED;
@@ -1,5 +1,7 @@
 use ethereum_hashing::{hash, hash32_concat, ZERO_HASHES};
 use safe_arith::ArithError;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 use std::sync::LazyLock;
 
 type H256 = fixed_bytes::Hash256;
@@ -349,6 +351,94 @@ impl MerkleTree {
     }
 }
 
+/// Return the leaves at `indices` of the Merkle tree of `leaves` with the given `depth`, along with
+/// a proof of the inclusion of each.
+///
+/// The leaves are hashed in a single pass without building the tree, retaining only the nodes
+/// required by the proofs. Memory use is therefore independent of the number of leaves, which
+/// makes this suitable for proofs into large lists. Leaves beyond the end of `leaves` are zero.
+///
+/// Proofs are in "bottom-up" order, like those of `MerkleTree::generate_proof`.
+pub fn generate_proofs(
+    leaves: impl IntoIterator<Item = H256>,
+    depth: usize,
+    indices: Range<usize>,
+) -> Result<Vec<(H256, Vec<H256>)>, MerkleTreeError> {
+    if depth >= ZERO_HASHES.len() || indices.end > 1 << depth {
+        return Err(MerkleTreeError::Invalid);
+    }
+
+    // The nodes of each proof, identified by their height above the leaves and their index
+    // within that level.
+    let mut required = HashSet::new();
+    for index in indices.clone() {
+        required.insert((0, index));
+        for height in 0..depth {
+            required.insert((height, (index >> height) ^ 1));
+        }
+    }
+    let mut nodes = HashMap::with_capacity(required.len());
+    let mut visit = |height: usize, index: usize, node: H256| {
+        if required.contains(&(height, index)) {
+            nodes.insert((height, index), node);
+        }
+    };
+    let parent = |left: &H256, right: &[u8]| H256::from(hash32_concat(left.as_slice(), right));
+
+    // The roots of the complete subtrees which are yet to be paired with a right sibling, in
+    // descending order of height.
+    let mut pending: Vec<(usize, H256)> = vec![];
+    for (index, leaf) in leaves.into_iter().enumerate() {
+        if index >= 1 << depth {
+            return Err(MerkleTreeError::MerkleTreeFull);
+        }
+        visit(0, index, leaf);
+
+        let (mut height, mut node) = (0, leaf);
+        while let Some(&(left_height, left)) = pending.last() {
+            if left_height != height {
+                break;
+            }
+            pending.pop();
+            node = parent(&left, node.as_slice());
+            height += 1;
+            visit(height, index >> height, node);
+        }
+        pending.push((height, node));
+    }
+
+    // Complete the rightmost subtree at each height with zero leaves.
+    let num_leaves = pending.iter().map(|(height, _)| 1 << height).sum::<usize>();
+    if let Some((mut height, mut node)) = pending.pop() {
+        while height < depth {
+            node = match pending.last() {
+                Some(&(left_height, left)) if left_height == height => {
+                    pending.pop();
+                    parent(&left, node.as_slice())
+                }
+                _ => parent(&node, &ZERO_HASHES[height]),
+            };
+            height += 1;
+            visit(height, (num_leaves - 1) >> height, node);
+        }
+    }
+
+    let node = |height: usize, index: usize| {
+        nodes
+            .get(&(height, index))
+            .copied()
+            .unwrap_or_else(|| H256::from_slice(&ZERO_HASHES[height]))
+    };
+    Ok(indices
+        .map(|index| {
+            let proof = (0..depth)
+                .map(|height| node(height, (index >> height) ^ 1))
+                .collect();
+            (node(0, index), proof)
+        })
+        .collect())
+}
+
 /// Verify a proof that `leaf` exists at `index` in a Merkle tree rooted at `root`.
 ///
 /// The `branch` argument is the main component of the proof: it should be a list of internal
@@ -448,6 +538,42 @@ mod tests {
         TestResult::from_bool(proofs_ok)
     }
 
+    /// Check that the proofs generated without building the tree match those of `MerkleTree`.
+    #[quickcheck]
+    fn quickcheck_generate_proofs(int_leaves: Vec<u64>, depth: usize, start: usize) -> TestResult {
+        if depth > MAX_TREE_DEPTH || int_leaves.len() > 2usize.pow(depth as u32) {
+            return TestResult::discard();
+        }
+
+        let leaves: Vec<_> = int_leaves.into_iter().map(H256::from_low_u64_be).collect();
+        let merkle_tree = MerkleTree::create(&leaves, depth);
+
+        // Include indices beyond the last leaf, whose leaves are zero.
+        let end = std::cmp::min(leaves.len() + 2, 1 << depth);
+        let indices = std::cmp::min(start, end)..end;
+        let proofs = generate_proofs(leaves.iter().copied(), depth, indices.clone()).unwrap();
+
+        let proofs_ok = indices.zip(proofs).all(|(i, (leaf, branch))| {
+            (leaf, branch) == merkle_tree.generate_proof(i, depth).unwrap()
+                && verify_merkle_proof(leaf, &branch, depth, i, merkle_tree.hash())
+        });
+
+        TestResult::from_bool(proofs_ok)
+    }
+
+    #[test]
+    fn generate_proofs_rejects_out_of_range() {
+        let leaves = vec![H256::from([0xAA; 32]); 5];
+        assert_eq!(
+            generate_proofs(leaves.iter().copied(), 2, 0..1),
+            Err(MerkleTreeError::MerkleTreeFull)
+        );
+        assert_eq!(
+            generate_proofs(leaves.iter().copied(), 3, 0..9),
+            Err(MerkleTreeError::Invalid)
+        );
+    }
+
     #[test]
     fn sparse_zero_correct() {
         let depth = 2;
@@ -2551,6 +2551,19 @@ impl<E: EthSpec> BeaconState<E> {
         Ok(proof)
     }
 
+    /// Compute the tree hash root of each top-level field, along with a Merkle proof of the field
+    /// against the state root.
+    ///
+    /// Proofs are in bottom-up order, and are returned in field order.
+    pub fn compute_field_proofs(&self) -> Result<Vec<(Hash256, Vec<Hash256>)>, Error> {
+        let leaves = self.get_beacon_state_leaves();
+        let depth = self.num_fields_pow2().ilog2() as usize;
+        let tree = merkle_proof::MerkleTree::create(&leaves, depth);
+        (0..leaves.len())
+            .map(|field_index| Ok(tree.generate_proof(field_index, depth)?))
+            .collect()
+    }
+
     fn generate_proof(
         &self,
         field_index: usize,
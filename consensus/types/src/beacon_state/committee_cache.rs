@@ -80,18 +80,40 @@ impl CommitteeCache {
             return Err(Error::EpochOutOfBounds);
         }
 
+        let active_validator_indices = get_active_validator_indices(state.validators(), epoch);
+        let seed = state.get_seed(epoch, Domain::BeaconAttester, spec)?;
+
+        Self::from_seed_and_active_indices::<E>(
+            epoch,
+            seed,
+            active_validator_indices,
+            state.validators().len(),
+            spec,
+        )
+    }
+
+    /// Return a new, fully initialized cache from the attester `seed` and the ascending
+    /// `active_validator_indices` of `epoch`.
+    ///
+    /// This allows the committees of historic epochs to be computed without a `BeaconState`.
+    /// The `validator_count` must be greater than the highest active validator index.
+    pub fn from_seed_and_active_indices<E: EthSpec>(
+        epoch: Epoch,
+        seed: Hash256,
+        active_validator_indices: Vec<usize>,
+        validator_count: usize,
+        spec: &ChainSpec,
+    ) -> Result<Arc<CommitteeCache>, Error> {
         // May cause divide-by-zero errors.
         if E::slots_per_epoch() == 0 {
             return Err(Error::ZeroSlotsPerEpoch);
         }
 
         // The use of `NonZeroUsize` reduces the maximum number of possible validators by one.
-        if state.validators().len() == usize::MAX {
+        if validator_count == usize::MAX {
             return Err(Error::TooManyValidators);
         }
 
-        let active_validator_indices = get_active_validator_indices(state.validators(), epoch);
-
         if active_validator_indices.is_empty() {
             return Err(Error::InsufficientValidators);
         }
@@ -99,8 +121,6 @@ impl CommitteeCache {
         let committees_per_slot =
             E::get_committee_count_per_slot(active_validator_indices.len(), spec)? as u64;
 
-        let seed = state.get_seed(epoch, Domain::BeaconAttester, spec)?;
-
         let shuffling = shuffle_list(
             active_validator_indices,
             spec.shuffle_round_count,
@@ -109,7 +129,7 @@ impl CommitteeCache {
         )
         .ok_or(Error::UnableToShuffle)?;
 
-        let mut shuffling_positions = vec![<_>::default(); state.validators().len()];
+        let mut shuffling_positions = vec![<_>::default(); validator_count];
         for (i, &v) in shuffling.iter().enumerate() {
             *shuffling_positions
                 .get_mut(v)
@@ -143,6 +143,32 @@ async fn shuffles_for_the_right_epoch() {
     );
 }
 
+#[tokio::test]
+async fn from_seed_and_active_indices_matches_initialized() {
+    let num_validators = MinimalEthSpec::minimum_validator_count() * 2;
+    let epoch = Epoch::new(2);
+    let slot = epoch.start_slot(MinimalEthSpec::slots_per_epoch());
+
+    let state = new_state::<MinimalEthSpec>(num_validators, slot).await;
+    let spec = &MinimalEthSpec::default_spec();
+
+    for e in (0..=epoch.as_u64()).map(Epoch::new) {
+        let seed = state.get_seed(e, Domain::BeaconAttester, spec).unwrap();
+        let active_validator_indices = state.get_active_validator_indices(e, spec).unwrap();
+        let validator_count = active_validator_indices.last().unwrap() + 1;
+
+        let cache = CommitteeCache::from_seed_and_active_indices::<MinimalEthSpec>(
+            e,
+            seed,
+            active_validator_indices,
+            validator_count,
+            spec,
+        )
+        .unwrap();
+        assert_eq!(cache, CommitteeCache::initialized(&state, e, spec).unwrap());
+    }
+}
+
 #[tokio::test]
 async fn min_randao_epoch_correct() {
     let num_validators = MinimalEthSpec::minimum_validator_count() * 2;
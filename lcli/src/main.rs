@@ -6,6 +6,8 @@ mod indexed_attestations;
 mod mnemonic_validators;
 mod mock_el;
 mod parse_ssz;
+mod replay_engine_api;
+mod replay_gossip;
 mod skip_slots;
 mod state_root;
 mod transition_blocks;
@@ -621,6 +623,143 @@ fn main() {
                         .display_order(0)
                 )
         )
+        .subcommand(
+            Command::new("replay-gossip")
+                .about("Replays a network capture recorded with --network-capture-dir against a \
+                    copy of a beacon node database, and compares the outcome of each gossip \
+                    message with the outcome recorded in the capture. The database is modified, \
+                    so this should never be run against the database of a running node.")
+                .arg(
+                    Arg::new("datadir")
+                        .long("datadir")
+                        .value_name("PATH")
+                        .action(ArgAction::Set)
+                        .help("Path to the beacon directory containing the database, e.g. \
+                            ~/.lighthouse/mainnet/beacon.")
+                        .required(true)
+                        .display_order(0)
+                )
+                .arg(
+                    Arg::new("capture-dir")
+                        .long("capture-dir")
+                        .value_name("PATH")
+                        .action(ArgAction::Set)
+                        .help("Path to the directory containing the capture files.")
+                        .required(true)
+                        .display_order(0)
+                )
+                .arg(
+                    Arg::new("output-path")
+                        .long("output-path")
+                        .value_name("PATH")
+                        .action(ArgAction::Set)
+                        .help("Path to write the outcome of every gossip message to, as CSV.")
+                        .display_order(0)
+                )
+                .arg(
+                    Arg::new("message-timeout-ms")
+                        .long("message-timeout-ms")
+                        .value_name("MILLIS")
+                        .action(ArgAction::Set)
+                        .help("The time to wait for the outcome of each message before replaying \
+                            the next.")
+                        .default_value("1000")
+                        .display_order(0)
+                )
+                .arg(
+                    Arg::new("drain-timeout-ms")
+                        .long("drain-timeout-ms")
+                        .value_name("MILLIS")
+                        .action(ArgAction::Set)
+                        .help("The time to wait for outstanding outcomes after the last message \
+                            has been replayed.")
+                        .default_value("15000")
+                        .display_order(0)
+                )
+        )
+        .subcommand(
+            Command::new("replay-engine-api")
+                .about("Replays an engine API recording made with --execution-record-dir against \
+                    an execution engine, and compares each response with the response recorded \
+                    from the original engine. The engine should be synced to the same point as \
+                    the original engine at the start of the recording.")
+                .arg(
+                    Arg::new("recording-dir")
+                        .long("recording-dir")
+                        .value_name("PATH")
+                        .action(ArgAction::Set)
+                        .help("Path to the directory containing the recording files.")
+                        .required(true)
+                        .display_order(0)
+                )
+                .arg(
+                    Arg::new("execution-endpoint")
+                        .long("execution-endpoint")
+                        .value_name("URL")
+                        .action(ArgAction::Set)
+                        .help("Engine API endpoint of the execution engine to replay against. An \
+                            ipc:// endpoint may be used for an IPC socket.")
+                        .required(true)
+                        .display_order(0)
+                )
+                .arg(
+                    Arg::new("execution-jwt")
+                        .long("execution-jwt")
+                        .value_name("PATH")
+                        .action(ArgAction::Set)
+                        .help("File containing the hex-encoded JWT secret of the execution \
+                            engine. Required unless the endpoint is an IPC socket.")
+                        .display_order(0)
+                )
+                .arg(
+                    Arg::new("endpoint")
+                        .long("endpoint")
+                        .value_name("URL")
+                        .action(ArgAction::Set)
+                        .help("Only replay the requests recorded from the engine with this \
+                            endpoint, as it appears in the recording. By default, the requests \
+                            recorded from the primary engine of the beacon node are replayed.")
+                        .display_order(0)
+                )
+                .arg(
+                    Arg::new("methods")
+                        .long("methods")
+                        .value_name("METHODS")
+                        .action(ArgAction::Set)
+                        .help("Comma-separated list of the methods to replay, e.g. \
+                            engine_newPayloadV3. All methods are replayed by default.")
+                        .display_order(0)
+                )
+                .arg(
+                    Arg::new("ignore-fields")
+                        .long("ignore-fields")
+                        .value_name("FIELDS")
+                        .action(ArgAction::Set)
+                        .help("Comma-separated list of response fields which are not compared, \
+                            at any depth. Validation errors are ignored by default, as their \
+                            messages are specific to each execution client.")
+                        .default_value("validationError")
+                        .display_order(0)
+                )
+                .arg(
+                    Arg::new("timeout-ms")
+                        .long("timeout-ms")
+                        .value_name("MILLIS")
+                        .action(ArgAction::Set)
+                        .help("The time to wait for the response to each request.")
+                        .default_value("12000")
+                        .display_order(0)
+                )
+                .arg(
+                    Arg::new("output-path")
+                        .long("output-path")
+                        .value_name("PATH")
+                        .action(ArgAction::Set)
+                        .help("Path to write the latencies and number of differences of every \
+                            request to, as CSV.")
+                        .display_order(0)
+                )
+        )
         .get_matches();
 
     let result = matches
@@ -732,6 +871,13 @@ fn run<E: EthSpec>(env_builder: EnvironmentBuilder<E>, matches: &ArgMatches) ->
             http_sync::run::<E>(env, network_config, matches)
                 .map_err(|e| format!("Failed to run http-sync command: {}", e))
         }
+        Some(("replay-gossip", matches)) => {
+            let network_config = get_network_config()?;
+            replay_gossip::run::<E>(env, network_config, matches)
+                .map_err(|e| format!("Failed to run replay-gossip command: {}", e))
+        }
+        Some(("replay-engine-api", matches)) => replay_engine_api::run::<E>(env, matches)
+            .map_err(|e| format!("Failed to run replay-engine-api command: {}", e)),
         Some((other, _)) => Err(format!("Unknown subcommand {}. See --help.", other)),
         _ => Err("No subcommand provided. See --help.".to_string()),
     }
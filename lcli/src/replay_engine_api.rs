@@ -0,0 +1,549 @@
+//! # Replay-Engine-API
+//!
+//! Use this tool to replay an engine API recording made by a beacon node running with
+//! `--execution-record-dir` against another execution engine, and compare its responses with the
+//! responses recorded from the original engine.
+//!
+//! Only the requests recorded from a single engine are replayed, by default those of the primary
+//! engine, as the beacon node sends many of its requests to each of its engines.
+//!
+//! Requests are sent one at a time in the order they were recorded. Payload IDs returned by the
+//! engine are substituted for the recorded payload IDs, so that payloads built by the engine can
+//! be retrieved. The engine should be synced to the same point as the original engine at the start
+//! of the recording, otherwise most responses will differ.
+//!
+//! ## Example
+//!
+//! ```ignore
+//! lcli replay-engine-api \
+//!     --recording-dir ~/.lighthouse/mainnet/beacon/engine-api \
+//!     --execution-endpoint http://localhost:8551 \
+//!     --execution-jwt /secrets/jwt.hex \
+//!     --methods engine_newPayloadV3,engine_forkchoiceUpdatedV3
+//! ```
+use clap::ArgMatches;
+use clap_utils::{parse_optional, parse_required};
+use environment::Environment;
+use eth2::SensitiveUrl;
+use execution_layer::auth::Auth;
+use execution_layer::http::{ipc_socket_path, IPC_SCHEME, JSONRPC_VERSION};
+use execution_layer::json_structures::{JsonRequestBody, JsonResponseBody};
+use execution_layer::recorder::{EngineApiRecord, RecordingReader};
+use execution_layer::HttpJsonRpc;
+use log::info;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use types::EthSpec;
+
+/// The maximum length of a value printed in a difference.
+const MAX_VALUE_LENGTH: usize = 120;
+
+/// The outcome of replaying a single request.
+struct Outcome {
+    sent_ms: u64,
+    method: String,
+    recorded_latency: Duration,
+    replayed_latency: Duration,
+    /// The differences between the recorded and replayed responses.
+    differences: Vec<String>,
+}
+
+pub fn run<E: EthSpec>(env: Environment<E>, matches: &ArgMatches) -> Result<(), String> {
+    let recording_dir: PathBuf = parse_required(matches, "recording-dir")?;
+    let endpoint: SensitiveUrl = parse_required(matches, "execution-endpoint")?;
+    let jwt_path: Option<PathBuf> = parse_optional(matches, "execution-jwt")?;
+    let recorded_endpoint: Option<String> = parse_optional(matches, "endpoint")?;
+    let methods: Option<String> = parse_optional(matches, "methods")?;
+    let ignore_fields: String = parse_required(matches, "ignore-fields")?;
+    let timeout_ms: u64 = parse_required(matches, "timeout-ms")?;
+    let output_path: Option<PathBuf> = parse_optional(matches, "output-path")?;
+
+    let methods = methods.map(|methods| split_list(&methods));
+    let ignore_fields = split_list(&ignore_fields);
+    let timeout = Duration::from_millis(timeout_ms);
+
+    let is_ipc = ipc_socket_path(&endpoint)
+        .map_err(|e| format!("Invalid endpoint: {e:?}"))?
+        .is_some();
+    let engine = match jwt_path {
+        Some(jwt_path) => {
+            let auth = Auth::new_with_path(jwt_path, None, None)
+                .map_err(|e| format!("Unable to load JWT secret: {e:?}"))?;
+            HttpJsonRpc::new_with_auth(endpoint, auth, None)
+        }
+        None if is_ipc => HttpJsonRpc::new(endpoint, None),
+        None => return Err("--execution-jwt is required for HTTP endpoints".to_string()),
+    }
+    .map_err(|e| format!("Unable to create engine API client: {e:?}"))?;
+
+    let records = RecordingReader::open(&recording_dir)?;
+    let handle = env
+        .core_context()
+        .executor
+        .handle()
+        .ok_or("shutdown in progress")?;
+    // HTTP endpoints are recorded in their redacted form, e.g. with a trailing slash.
+    let recorded_endpoint =
+        recorded_endpoint.map(|endpoint| match SensitiveUrl::parse(&endpoint) {
+            Ok(url) if url.full.scheme() != IPC_SCHEME => url.to_string(),
+            _ => endpoint,
+        });
+    let filter = RecordFilter {
+        endpoint: recorded_endpoint,
+        methods,
+    };
+    let replay = replay_all(&engine, records, &filter, &ignore_fields, timeout);
+    let (outcomes, skipped) = handle.block_on(replay)?;
+
+    if let Some(output_path) = output_path {
+        let mut output = BufWriter::new(
+            File::create(&output_path)
+                .map_err(|e| format!("Unable to create {}: {e}", output_path.display()))?,
+        );
+        let write_err = |e| format!("Unable to write output: {e:?}");
+        writeln!(
+            output,
+            "sent_ms,method,recorded_latency_us,replayed_latency_us,differences"
+        )
+        .map_err(write_err)?;
+        for outcome in &outcomes {
+            writeln!(
+                output,
+                "{},{},{},{},{}",
+                outcome.sent_ms,
+                outcome.method,
+                outcome.recorded_latency.as_micros(),
+                outcome.replayed_latency.as_micros(),
+                outcome.differences.len(),
+            )
+            .map_err(write_err)?;
+        }
+        output.flush().map_err(write_err)?;
+    }
+
+    let mismatches = outcomes
+        .iter()
+        .filter(|outcome| !outcome.differences.is_empty())
+        .count();
+    info!(
+        "Replayed {} requests ({} skipped), {} mismatches",
+        outcomes.len(),
+        skipped,
+        mismatches,
+    );
+    for (method, (count, recorded, replayed)) in latencies(&outcomes) {
+        info!(
+            "Mean latency of {}: recorded {:?}, replayed {:?} ({} requests)",
+            method,
+            recorded / count,
+            replayed / count,
+            count,
+        );
+    }
+
+    if mismatches > 0 {
+        Err(format!(
+            "{} of {} responses were different",
+            mismatches,
+            outcomes.len()
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Selects the recorded requests to replay.
+#[derive(Default)]
+struct RecordFilter {
+    /// The endpoint of the engine whose requests are replayed, or the primary engine if `None`.
+    endpoint: Option<String>,
+    /// The methods to replay, or all methods if `None`.
+    methods: Option<HashSet<String>>,
+}
+
+impl RecordFilter {
+    fn matches(&self, record: &EngineApiRecord) -> bool {
+        let engine_matches = match &self.endpoint {
+            Some(endpoint) => record.endpoint == *endpoint,
+            None => record.engine == 0,
+        };
+        engine_matches
+            && self
+                .methods
+                .as_ref()
+                .map_or(true, |methods| methods.contains(&record.method))
+    }
+}
+
+/// Replays the recorded requests selected by `filter`, returning the outcome of each request and
+/// the number of requests skipped.
+async fn replay_all(
+    engine: &HttpJsonRpc,
+    records: RecordingReader,
+    filter: &RecordFilter,
+    ignore_fields: &HashSet<String>,
+    timeout: Duration,
+) -> Result<(Vec<Outcome>, usize), String> {
+    let mut outcomes = vec![];
+    let mut skipped = 0;
+    // Recorded payload IDs, mapped to the payload IDs of the replayed builds.
+    let mut payload_ids = HashMap::new();
+
+    for record in records {
+        let mut record = record?;
+        if !filter.matches(&record) {
+            skipped += 1;
+            continue;
+        }
+        // Requests that did not receive a response cannot be compared.
+        let Some(recorded) = record.response.take() else {
+            skipped += 1;
+            continue;
+        };
+
+        let outcome = replay(
+            engine,
+            record,
+            recorded,
+            timeout,
+            &mut payload_ids,
+            ignore_fields,
+        )
+        .await;
+        for difference in &outcome.differences {
+            info!(
+                "Mismatch: sent_ms: {}, method: {}, {}",
+                outcome.sent_ms, outcome.method, difference
+            );
+        }
+        outcomes.push(outcome);
+    }
+    Ok((outcomes, skipped))
+}
+
+/// Sends the recorded request to `engine` and compares the response with `recorded`.
+async fn replay(
+    engine: &HttpJsonRpc,
+    record: EngineApiRecord,
+    recorded: JsonResponseBody,
+    timeout: Duration,
+    payload_ids: &mut HashMap<String, String>,
+    ignore_fields: &HashSet<String>,
+) -> Outcome {
+    let mut params = record.params;
+    substitute_payload_id(&record.method, &mut params, payload_ids);
+
+    let body = JsonRequestBody {
+        jsonrpc: JSONRPC_VERSION,
+        method: &record.method,
+        params,
+        id: recorded.id.clone(),
+    };
+    let start = Instant::now();
+    let response = engine.send_rpc_request(&body, timeout).await;
+    let replayed_latency = start.elapsed();
+
+    let mut differences = vec![];
+    match response {
+        Ok(replayed) => {
+            if let (Some(recorded_id), Some(replayed_id)) =
+                (payload_id(&recorded.result), payload_id(&replayed.result))
+            {
+                payload_ids.insert(recorded_id.to_string(), replayed_id.to_string());
+            }
+            let recorded_error = recorded.error.as_ref().map(|error| error.code);
+            let replayed_error = replayed.error.as_ref().map(|error| error.code);
+            if recorded_error != replayed_error {
+                differences.push(format!(
+                    "error: recorded {:?}, replayed {:?}",
+                    recorded.error, replayed.error
+                ));
+            }
+            diff(
+                "result",
+                &recorded.result,
+                &replayed.result,
+                ignore_fields,
+                &mut differences,
+            );
+        }
+        Err(e) => differences.push(format!("no response: {e:?}")),
+    }
+
+    Outcome {
+        sent_ms: record.sent_ms,
+        method: record.method,
+        recorded_latency: Duration::from_micros(record.latency_us),
+        replayed_latency,
+        differences,
+    }
+}
+
+/// Appends a description of each difference between `recorded` and `replayed` to `differences`.
+fn diff(
+    path: &str,
+    recorded: &Value,
+    replayed: &Value,
+    ignore_fields: &HashSet<String>,
+    differences: &mut Vec<String>,
+) {
+    match (recorded, replayed) {
+        (Value::Object(recorded), Value::Object(replayed)) => {
+            let fields = recorded
+                .keys()
+                .chain(replayed.keys().filter(|key| !recorded.contains_key(*key)));
+            for field in fields.filter(|field| !ignore_fields.contains(*field)) {
+                diff(
+                    &format!("{path}.{field}"),
+                    recorded.get(field).unwrap_or(&Value::Null),
+                    replayed.get(field).unwrap_or(&Value::Null),
+                    ignore_fields,
+                    differences,
+                );
+            }
+        }
+        (Value::Array(recorded_items), Value::Array(replayed_items))
+            if recorded_items.len() == replayed_items.len() =>
+        {
+            for (index, (recorded, replayed)) in
+                recorded_items.iter().zip(replayed_items).enumerate()
+            {
+                diff(
+                    &format!("{path}[{index}]"),
+                    recorded,
+                    replayed,
+                    ignore_fields,
+                    differences,
+                );
+            }
+        }
+        (recorded, replayed) if recorded != replayed => differences.push(format!(
+            "{path}: recorded {}, replayed {}",
+            truncate(recorded),
+            truncate(replayed)
+        )),
+        _ => {}
+    }
+}
+
+fn truncate(value: &Value) -> String {
+    let mut value = value.to_string();
+    if value.len() > MAX_VALUE_LENGTH {
+        let mut end = MAX_VALUE_LENGTH;
+        while !value.is_char_boundary(end) {
+            end -= 1;
+        }
+        value.truncate(end);
+        value.push_str("...");
+    }
+    value
+}
+
+/// Whether `method` retrieves a payload by its payload ID, e.g. `engine_getPayloadV4`.
+fn is_get_payload(method: &str) -> bool {
+    method
+        .strip_prefix("engine_getPayloadV")
+        .is_some_and(|version| version.parse::<u8>().is_ok())
+}
+
+/// Replaces the recorded payload ID in the `params` of a `getPayload` request with the payload ID
+/// returned by the engine for the same build, if any.
+fn substitute_payload_id(method: &str, params: &mut Value, payload_ids: &HashMap<String, String>) {
+    if !is_get_payload(method) {
+        return;
+    }
+    if let Some(payload_id) = params.get_mut(0) {
+        if let Some(replayed_id) = payload_id.as_str().and_then(|id| payload_ids.get(id)) {
+            *payload_id = Value::String(replayed_id.clone());
+        }
+    }
+}
+
+/// The payload ID in the result of a forkchoice update, if any.
+fn payload_id(result: &Value) -> Option<&str> {
+    result.get("payloadId").and_then(Value::as_str)
+}
+
+/// Returns the number of requests and the total recorded and replayed latencies of each method.
+fn latencies(outcomes: &[Outcome]) -> Vec<(&str, (u32, Duration, Duration))> {
+    let mut latencies = HashMap::<&str, (u32, Duration, Duration)>::new();
+    for outcome in outcomes {
+        let entry = latencies.entry(outcome.method.as_str()).or_default();
+        entry.0 += 1;
+        entry.1 += outcome.recorded_latency;
+        entry.2 += outcome.replayed_latency;
+    }
+    let mut latencies = latencies.into_iter().collect::<Vec<_>>();
+    latencies.sort_unstable_by_key(|(method, _)| *method);
+    latencies
+}
+
+fn split_list(list: &str) -> HashSet<String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn differences(recorded: Value, replayed: Value, ignore_fields: &[&str]) -> Vec<String> {
+        let ignore_fields = ignore_fields
+            .iter()
+            .map(|field| field.to_string())
+            .collect();
+        let mut differences = vec![];
+        diff(
+            "result",
+            &recorded,
+            &replayed,
+            &ignore_fields,
+            &mut differences,
+        );
+        // The order of the fields depends on the features of `serde_json`.
+        differences.sort();
+        differences
+    }
+
+    #[test]
+    fn diff_equal_values() {
+        let value = json!({"status": "VALID", "hashes": ["0x01", "0x02"], "extra": null});
+        assert!(differences(value.clone(), value, &[]).is_empty());
+    }
+
+    #[test]
+    fn diff_reports_each_difference() {
+        let recorded = json!({
+            "status": "VALID",
+            "latestValidHash": "0x01",
+            "withdrawals": [{"index": "0x1"}, {"index": "0x2"}],
+            "transactions": ["0x01"],
+        });
+        let replayed = json!({
+            "status": "SYNCING",
+            "withdrawals": [{"index": "0x1"}, {"index": "0x3"}],
+            "transactions": ["0x01", "0x02"],
+            "validationError": "error",
+        });
+        assert_eq!(
+            differences(recorded, replayed, &[]),
+            vec![
+                r#"result.latestValidHash: recorded "0x01", replayed null"#,
+                r#"result.status: recorded "VALID", replayed "SYNCING""#,
+                r#"result.transactions: recorded ["0x01"], replayed ["0x01","0x02"]"#,
+                r#"result.validationError: recorded null, replayed "error""#,
+                r#"result.withdrawals[1].index: recorded "0x2", replayed "0x3""#,
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_truncates_long_values() {
+        let recorded = json!("0".repeat(2 * MAX_VALUE_LENGTH));
+        let differences = differences(recorded, json!("1"), &[]);
+        assert_eq!(differences.len(), 1);
+        assert!(differences[0].ends_with(r#"..., replayed "1""#));
+        assert!(differences[0].len() < 2 * MAX_VALUE_LENGTH);
+    }
+
+    #[test]
+    fn diff_ignores_fields() {
+        let recorded = json!({
+            "status": "VALID",
+            "payload": {"blockHash": "0x01", "timestamp": "0x1"},
+        });
+        let replayed = json!({
+            "status": "VALID",
+            "payload": {"blockHash": "0x02", "timestamp": "0x1"},
+        });
+        assert_eq!(
+            differences(recorded.clone(), replayed.clone(), &["timestamp"]),
+            vec![r#"result.payload.blockHash: recorded "0x01", replayed "0x02""#]
+        );
+        assert!(differences(recorded, replayed, &["blockHash"]).is_empty());
+    }
+
+    fn record(endpoint: &str, engine: usize, method: &str) -> EngineApiRecord {
+        EngineApiRecord {
+            sent_ms: 0,
+            endpoint: endpoint.to_string(),
+            engine,
+            method: method.to_string(),
+            params: json!([]),
+            latency_us: 0,
+            response: None,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn filters_records_by_engine_and_method() {
+        let primary = record("http://localhost:8551/", 0, "engine_newPayloadV3");
+        let secondary = record("http://localhost:8552/", 1, "engine_newPayloadV3");
+        let upcheck = record("http://localhost:8551/", 0, "eth_syncing");
+
+        // Only the requests of the primary engine are replayed by default.
+        let filter = RecordFilter::default();
+        assert!(filter.matches(&primary));
+        assert!(!filter.matches(&secondary));
+        assert!(filter.matches(&upcheck));
+
+        let filter = RecordFilter {
+            endpoint: Some("http://localhost:8552/".to_string()),
+            methods: None,
+        };
+        assert!(!filter.matches(&primary));
+        assert!(filter.matches(&secondary));
+
+        let filter = RecordFilter {
+            endpoint: None,
+            methods: Some(split_list("engine_newPayloadV3")),
+        };
+        assert!(filter.matches(&primary));
+        assert!(!filter.matches(&secondary));
+        assert!(!filter.matches(&upcheck));
+    }
+
+    #[test]
+    fn get_payload_methods() {
+        assert!(is_get_payload("engine_getPayloadV1"));
+        assert!(is_get_payload("engine_getPayloadV4"));
+        assert!(!is_get_payload("engine_getPayloadV"));
+        assert!(!is_get_payload("engine_getPayloadBodiesByHashV1"));
+        assert!(!is_get_payload("engine_forkchoiceUpdatedV3"));
+    }
+
+    #[test]
+    fn substitutes_replayed_payload_ids() {
+        let recorded = json!({"payloadStatus": {"status": "VALID"}, "payloadId": "0x01"});
+        let replayed = json!({"payloadStatus": {"status": "VALID"}, "payloadId": "0x02"});
+        let payload_ids = HashMap::from([(
+            payload_id(&recorded).unwrap().to_string(),
+            payload_id(&replayed).unwrap().to_string(),
+        )]);
+
+        let mut params = json!(["0x01"]);
+        substitute_payload_id("engine_getPayloadV3", &mut params, &payload_ids);
+        assert_eq!(params, json!(["0x02"]));
+
+        // Payload IDs of builds that were not replayed are kept.
+        let mut params = json!(["0x03"]);
+        substitute_payload_id("engine_getPayloadV3", &mut params, &payload_ids);
+        assert_eq!(params, json!(["0x03"]));
+
+        // Only the params of `getPayload` requests are changed.
+        let mut params = json!(["0x01"]);
+        substitute_payload_id("engine_getBlobsV1", &mut params, &payload_ids);
+        assert_eq!(params, json!(["0x01"]));
+
+        assert_eq!(payload_id(&json!({"payloadId": null})), None);
+    }
+}
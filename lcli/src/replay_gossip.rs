@@ -0,0 +1,149 @@
+//! # Replay-Gossip
+//!
+//! Use this tool to replay a network capture recorded by a beacon node running with
+//! `--network-capture-dir` against a snapshot of its database. The validation result of each
+//! gossip message is compared with the result recorded in the capture.
+//!
+//! The slot clock is set to the arrival time of each message as it is replayed, and the execution
+//! layer is mocked to consider all payloads valid.
+//!
+//! The database is modified by the replay (e.g. blocks are imported), so it should be run against
+//! a copy of the database rather than the database of a running node.
+//!
+//! ## Example
+//!
+//! ```ignore
+//! lcli replay-gossip \
+//!     --datadir /tmp/snapshot/beacon \
+//!     --capture-dir ~/.lighthouse/mainnet/beacon/capture \
+//!     --output-path /tmp/replay.csv
+//! ```
+use beacon_chain::test_utils::{BeaconChainHarness, DiskHarnessType};
+use clap::ArgMatches;
+use clap_utils::{parse_optional, parse_required};
+use environment::Environment;
+use eth2_network_config::Eth2NetworkConfig;
+use lighthouse_network::capture::CaptureReader;
+use log::info;
+use network::replay::{Replay, ReplayConfig};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use store::{HotColdDB, LevelDB, StoreConfig};
+use types::EthSpec;
+
+pub fn run<E: EthSpec>(
+    env: Environment<E>,
+    network_config: Eth2NetworkConfig,
+    matches: &ArgMatches,
+) -> Result<(), String> {
+    let spec = Arc::new(network_config.chain_spec::<E>()?);
+    let executor = env.core_context().executor;
+    let log = env.core_context().log().clone();
+
+    let datadir: PathBuf = parse_required(matches, "datadir")?;
+    let capture_dir: PathBuf = parse_required(matches, "capture-dir")?;
+    let output_path: Option<PathBuf> = parse_optional(matches, "output-path")?;
+    let message_timeout_ms: u64 = parse_required(matches, "message-timeout-ms")?;
+    let drain_timeout_ms: u64 = parse_required(matches, "drain-timeout-ms")?;
+
+    let store = HotColdDB::<E, LevelDB<E>, LevelDB<E>>::open(
+        &datadir.join("chain_db"),
+        &datadir.join("freezer_db"),
+        &datadir.join("blobs_db"),
+        |_, from, to| {
+            Err(store::Error::SchemaMigrationError(format!(
+                "database must be migrated from {from:?} to {to:?} by the beacon node"
+            )))
+        },
+        StoreConfig::default(),
+        spec.clone(),
+        log.clone(),
+    )
+    .map_err(|e| format!("Unable to open database: {e:?}"))?;
+
+    let records = CaptureReader::open(&capture_dir)?;
+    let config = ReplayConfig {
+        message_timeout: Duration::from_millis(message_timeout_ms),
+        drain_timeout: Duration::from_millis(drain_timeout_ms),
+        ..ReplayConfig::default()
+    };
+
+    let summary = executor
+        .handle()
+        .ok_or("shutdown in progress")?
+        .block_on(async move {
+            let harness = BeaconChainHarness::<DiskHarnessType<E>>::builder(E::default())
+                .spec(spec)
+                .keypairs(vec![])
+                .logger(log.clone())
+                .resumed_disk_store(store)
+                .mock_execution_layer()
+                .mock_execution_layer_all_payloads_valid()
+                .build();
+            let replay = Replay::new(
+                harness.chain.clone(),
+                config,
+                harness.runtime.task_executor.clone(),
+                log,
+            )?;
+            Ok::<_, String>(replay.run(records).await)
+        })?;
+
+    if let Some(output_path) = output_path {
+        let mut output = BufWriter::new(
+            File::create(&output_path)
+                .map_err(|e| format!("Unable to create {}: {e}", output_path.display()))?,
+        );
+        let write_err = |e| format!("Unable to write output: {e:?}");
+        writeln!(output, "arrival_ms,message_id,topic,captured,replayed").map_err(write_err)?;
+        for outcome in &summary.gossip {
+            writeln!(
+                output,
+                "{},{},{},{:?},{:?}",
+                outcome.arrival.as_millis(),
+                hex::encode(&outcome.message_id.0),
+                outcome.topic,
+                outcome.captured,
+                outcome.replayed,
+            )
+            .map_err(write_err)?;
+        }
+        output.flush().map_err(write_err)?;
+    }
+
+    for outcome in summary.mismatches() {
+        info!(
+            "Mismatch: arrival_ms: {}, message_id: {}, topic: {}, captured: {:?}, replayed: {:?}",
+            outcome.arrival.as_millis(),
+            hex::encode(&outcome.message_id.0),
+            outcome.topic,
+            outcome.captured,
+            outcome.replayed,
+        );
+    }
+    for error in &summary.errors {
+        info!("Unable to replay record: {}", error);
+    }
+
+    let mismatches = summary.mismatches().count();
+    info!(
+        "Replayed {} gossip messages and {} RPC responses ({} skipped), {} mismatches",
+        summary.gossip.len(),
+        summary.rpc.len(),
+        summary.rpc_skipped,
+        mismatches,
+    );
+
+    if mismatches > 0 {
+        Err(format!(
+            "{} of {} gossip messages had a different outcome",
+            mismatches,
+            summary.gossip.len()
+        ))
+    } else {
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
 use beacon_node::ClientConfig as Config;
+use beacon_node::ClientGenesis;
 
 use crate::exec::{CommandLineTestExec, CompletedTest};
 use beacon_node::beacon_chain::chain_config::{
@@ -8,6 +9,7 @@ use beacon_node::beacon_chain::chain_config::{
 use beacon_node::beacon_chain::graffiti_calculator::GraffitiOrigin;
 use beacon_processor::BeaconProcessorConfig;
 use eth1::Eth1Endpoint;
+use lighthouse_network::capture::CaptureConfig;
 use lighthouse_network::PeerId;
 use lighthouse_version;
 use std::fs::File;
@@ -213,6 +215,34 @@ fn fork_choice_before_proposal_timeout_zero() {
         .with_config(|config| assert_eq!(config.chain.fork_choice_before_proposal_timeout_ms, 0));
 }
 
+#[test]
+fn attestation_packing_search_timeout_default() {
+    CommandLineTest::new()
+        .run_with_zero_port()
+        .with_config(|config| assert_eq!(config.chain.attestation_packing_search_timeout, None));
+}
+
+#[test]
+fn attestation_packing_search_timeout_flag() {
+    CommandLineTest::new()
+        .flag("attestation-packing-search-timeout", Some("50"))
+        .run_with_zero_port()
+        .with_config(|config| {
+            assert_eq!(
+                config.chain.attestation_packing_search_timeout,
+                Some(Duration::from_millis(50))
+            )
+        });
+}
+
+#[test]
+#[should_panic]
+fn attestation_packing_search_timeout_flag_too_long() {
+    CommandLineTest::new()
+        .flag("attestation-packing-search-timeout", Some("1000"))
+        .run_with_zero_port();
+}
+
 #[test]
 fn checkpoint_sync_url_timeout_flag() {
     CommandLineTest::new()
@@ -232,6 +262,28 @@ fn checkpoint_sync_url_timeout_default() {
         });
 }
 
+#[test]
+fn checkpoint_sync_p2p_flag() {
+    CommandLineTest::new()
+        .flag("checkpoint-sync-p2p", None)
+        .flag(
+            "wss-checkpoint",
+            Some("0xdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeefdeadbeef:1010"),
+        )
+        .run_with_zero_port_and_no_genesis_sync()
+        .with_config(|config| {
+            assert!(matches!(config.genesis, ClientGenesis::CheckpointSyncP2p));
+        });
+}
+
+#[test]
+#[should_panic]
+fn checkpoint_sync_p2p_requires_wss_checkpoint() {
+    CommandLineTest::new()
+        .flag("checkpoint-sync-p2p", None)
+        .run_with_zero_port_and_no_genesis_sync();
+}
+
 #[test]
 fn prepare_payload_lookahead_default() {
     CommandLineTest::new()
@@ -378,6 +430,42 @@ fn trusted_peers_flag() {
         });
 }
 
+#[test]
+fn peer_group_flag() {
+    let peers = vec![PeerId::random(), PeerId::random()];
+    CommandLineTest::new()
+        .flag(
+            "peer-group",
+            Some(format!("fleet:2:1:/ip4/10.0.0.1/tcp/9000/p2p/{}", peers[0]).as_str()),
+        )
+        .flag(
+            "peer-group",
+            Some(format!("relays:0:4:/ip4/10.0.0.2/tcp/9000/p2p/{}", peers[1]).as_str()),
+        )
+        .run_with_zero_port()
+        .with_config(|config| {
+            let groups = &config.network.peer_groups;
+            assert_eq!(groups.len(), 2);
+            assert_eq!(groups[0].name, "fleet");
+            assert_eq!(groups[0].reserved_inbound, 2);
+            assert_eq!(groups[0].reserved_outbound, 1);
+            assert_eq!(groups[0].peer_ids().collect::<Vec<_>>(), vec![peers[0]]);
+            assert_eq!(groups[1].name, "relays");
+            assert_eq!(groups[1].peer_ids().collect::<Vec<_>>(), vec![peers[1]]);
+        });
+}
+
+#[test]
+#[should_panic]
+fn peer_group_flag_duplicate_name() {
+    let peer_id = PeerId::random();
+    let group = format!("fleet:1:1:/ip4/10.0.0.1/tcp/9000/p2p/{}", peer_id);
+    CommandLineTest::new()
+        .flag("peer-group", Some(group.as_str()))
+        .flag("peer-group", Some(group.as_str()))
+        .run_with_zero_port();
+}
+
 #[test]
 fn genesis_backfill_flag() {
     CommandLineTest::new()
@@ -529,6 +617,164 @@ fn execution_timeout_multiplier_flag() {
             assert_eq!(config.execution_timeout_multiplier, Some(3));
         });
 }
+#[cfg(unix)]
+#[test]
+fn execution_endpoint_ipc_without_jwt() {
+    use sensitive_url::SensitiveUrl;
+    let url = SensitiveUrl::parse("ipc:///tmp/engine.ipc").unwrap();
+    CommandLineTest::new_with_no_execution_endpoint()
+        .flag("execution-endpoint", Some("ipc:///tmp/engine.ipc"))
+        .run_with_zero_port()
+        .with_config(|config| {
+            let el_config = config.execution_layer.as_ref().unwrap();
+            assert_eq!(el_config.execution_endpoint, Some(url.clone()));
+            assert_eq!(el_config.secret_file, None);
+            assert_eq!(config.eth1.endpoint, Eth1Endpoint::NoAuth(url));
+        });
+}
+#[cfg(unix)]
+#[test]
+#[should_panic]
+fn execution_endpoint_ipc_rejects_jwt() {
+    let dir = TempDir::new().expect("Unable to create temporary directory");
+    CommandLineTest::new_with_no_execution_endpoint()
+        .flag("execution-endpoint", Some("ipc:///tmp/engine.ipc"))
+        .flag(
+            "execution-jwt",
+            dir.path().join("jwt-file").as_os_str().to_str(),
+        )
+        .run_with_zero_port();
+}
+#[cfg(not(unix))]
+#[test]
+#[should_panic]
+fn execution_endpoint_ipc_unsupported() {
+    CommandLineTest::new_with_no_execution_endpoint()
+        .flag("execution-endpoint", Some("ipc:///tmp/engine.ipc"))
+        .run_with_zero_port();
+}
+#[test]
+#[should_panic]
+fn execution_endpoint_http_requires_jwt() {
+    CommandLineTest::new_with_no_execution_endpoint()
+        .flag("execution-endpoint", Some("http://localhost:8551/"))
+        .run_with_zero_port();
+}
+#[test]
+fn execution_record_default() {
+    CommandLineTest::new()
+        .run_with_zero_port()
+        .with_config(|config| {
+            let el_config = config.execution_layer.as_ref().unwrap();
+            assert!(el_config.engine_api_recorder.is_none());
+        });
+}
+#[test]
+fn execution_record_dir() {
+    let path = "/tmp/engine-api-recording";
+    CommandLineTest::new()
+        .flag("execution-record-dir", Some(path))
+        .run_with_zero_port()
+        .with_config(|config| {
+            let el_config = config.execution_layer.as_ref().unwrap();
+            let recorder_config = el_config.engine_api_recorder.as_ref().unwrap();
+            assert_eq!(recorder_config.dir, PathBuf::from(path));
+            assert_eq!(recorder_config.max_file_size, 256 * 1024 * 1024);
+            assert_eq!(recorder_config.max_files, 8);
+        });
+}
+#[test]
+fn execution_record_limits() {
+    CommandLineTest::new()
+        .flag("execution-record-dir", Some("/tmp/engine-api-recording"))
+        .flag("execution-record-max-file-size", Some("4"))
+        .flag("execution-record-max-files", Some("2"))
+        .run_with_zero_port()
+        .with_config(|config| {
+            let el_config = config.execution_layer.as_ref().unwrap();
+            let recorder_config = el_config.engine_api_recorder.as_ref().unwrap();
+            assert_eq!(recorder_config.max_file_size, 4 * 1024 * 1024);
+            assert_eq!(recorder_config.max_files, 2);
+        });
+}
+#[test]
+#[should_panic]
+fn execution_record_zero_max_file_size() {
+    CommandLineTest::new()
+        .flag("execution-record-dir", Some("/tmp/engine-api-recording"))
+        .flag("execution-record-max-file-size", Some("0"))
+        .run_with_zero_port();
+}
+#[test]
+#[should_panic]
+fn execution_record_max_file_size_overflow() {
+    CommandLineTest::new()
+        .flag("execution-record-dir", Some("/tmp/engine-api-recording"))
+        .flag(
+            "execution-record-max-file-size",
+            Some(&u64::MAX.to_string()),
+        )
+        .run_with_zero_port();
+}
+#[test]
+fn execution_secondary_endpoints_flag() {
+    use sensitive_url::SensitiveUrl;
+    let dir = TempDir::new().expect("Unable to create temporary directory");
+    let jwt = dir.path().join("jwt-secondary");
+    CommandLineTest::new_with_no_execution_endpoint()
+        .flag("execution-endpoint", Some("http://localhost:8551/"))
+        .flag(
+            "execution-jwt",
+            dir.path().join("jwt-file").as_os_str().to_str(),
+        )
+        .flag(
+            "execution-secondary-endpoints",
+            Some("http://localhost:8552/,http://localhost:8553/"),
+        )
+        .flag("execution-secondary-jwts", jwt.as_os_str().to_str())
+        .flag("execution-require-agreement", None)
+        .run_with_zero_port()
+        .with_config(|config| {
+            let config = config.execution_layer.as_ref().unwrap();
+            assert_eq!(
+                config.secondary_execution_endpoints,
+                vec![
+                    SensitiveUrl::parse("http://localhost:8552/").unwrap(),
+                    SensitiveUrl::parse("http://localhost:8553/").unwrap(),
+                ]
+            );
+            assert_eq!(config.secondary_secret_files, vec![jwt.clone()]);
+            assert!(config.require_engine_agreement);
+        });
+}
+#[test]
+fn execution_secondary_endpoints_default() {
+    CommandLineTest::new()
+        .run_with_zero_port()
+        .with_config(|config| {
+            let config = config.execution_layer.as_ref().unwrap();
+            assert!(config.secondary_execution_endpoints.is_empty());
+            assert!(config.secondary_secret_files.is_empty());
+            assert!(!config.require_engine_agreement);
+        });
+}
+#[test]
+#[should_panic]
+fn execution_secondary_jwts_more_than_endpoints() {
+    let dir = TempDir::new().expect("Unable to create temporary directory");
+    CommandLineTest::new_with_no_execution_endpoint()
+        .flag("execution-endpoint", Some("http://localhost:8551/"))
+        .flag(
+            "execution-jwt",
+            dir.path().join("jwt-file").as_os_str().to_str(),
+        )
+        .flag(
+            "execution-secondary-endpoints",
+            Some("http://localhost:8552/"),
+        )
+        .flag("execution-secondary-jwts", Some("/tmp/jwt-a,/tmp/jwt-b"))
+        .run_with_zero_port();
+}
 #[test]
 fn bellatrix_execution_endpoints_flag() {
     run_bellatrix_execution_endpoints_flag_test("execution-endpoints")
@@ -593,9 +839,7 @@ fn run_payload_builder_flag_test(flag: &str, builders: &str) {
         .collect();
     run_payload_builder_flag_test_with_config(flag, builders, None, None, |config| {
         let config = config.execution_layer.as_ref().unwrap();
-        // Only first provided endpoint is parsed as we don't support
-        // redundancy.
-        assert_eq!(config.builder_url, all_builders.get(0).cloned());
+        assert_eq!(config.builder_urls, all_builders);
     })
 }
 fn run_payload_builder_flag_test_with_config<F: Fn(&Config)>(
@@ -1239,6 +1483,77 @@ fn default_backfill_rate_limiting_flag() {
         .with_config(|config| assert!(config.beacon_processor.enable_backfill_rate_limiting));
 }
 #[test]
+fn backfill_schedule_flag() {
+    CommandLineTest::new()
+        .flag("backfill-schedule", Some("25,50,90"))
+        .run_with_zero_port()
+        .with_config(|config| {
+            assert_eq!(
+                config.beacon_processor.backfill_schedule_in_slot,
+                vec![(25, 100), (50, 100), (90, 100)]
+            )
+        });
+}
+#[test]
+#[should_panic]
+fn backfill_schedule_flag_invalid_percentage() {
+    CommandLineTest::new()
+        .flag("backfill-schedule", Some("50,100"))
+        .run_with_zero_port();
+}
+#[test]
+fn backfill_hours_flag() {
+    CommandLineTest::new()
+        .flag("backfill-hours", Some("22-6"))
+        .run_with_zero_port()
+        .with_config(|config| assert_eq!(config.chain.backfill_window_utc_hours, Some((22, 6))));
+}
+#[test]
+#[should_panic]
+fn backfill_hours_flag_invalid() {
+    CommandLineTest::new()
+        .flag("backfill-hours", Some("22-24"))
+        .run_with_zero_port();
+}
+#[test]
+fn backfill_proposal_pause_slots_flag() {
+    CommandLineTest::new()
+        .flag("backfill-proposal-pause-slots", Some("4"))
+        .run_with_zero_port()
+        .with_config(|config| assert_eq!(config.chain.backfill_proposal_pause_slots, Some(4)));
+}
+#[test]
+#[should_panic]
+fn backfill_proposal_pause_slots_flag_over_epoch() {
+    CommandLineTest::new()
+        .flag("backfill-proposal-pause-slots", Some("33"))
+        .run_with_zero_port();
+}
+#[test]
+fn backfill_processing_budget_flag() {
+    CommandLineTest::new()
+        .flag("backfill-processing-budget", Some("20"))
+        .run_with_zero_port()
+        .with_config(|config| assert_eq!(config.chain.backfill_processing_budget, Some(20)));
+}
+#[test]
+#[should_panic]
+fn backfill_processing_budget_flag_zero() {
+    CommandLineTest::new()
+        .flag("backfill-processing-budget", Some("0"))
+        .run_with_zero_port();
+}
+#[test]
+fn backfill_schedule_defaults() {
+    CommandLineTest::new()
+        .run_with_zero_port()
+        .with_config(|config| {
+            assert_eq!(config.chain.backfill_window_utc_hours, None);
+            assert_eq!(config.chain.backfill_proposal_pause_slots, None);
+            assert_eq!(config.chain.backfill_processing_budget, None);
+        });
+}
+#[test]
 fn default_boot_nodes() {
     let number_of_boot_nodes = 15;
 
@@ -1316,6 +1631,30 @@ fn network_load_flag() {
             assert_eq!(config.network.network_load, 4);
         });
 }
+#[test]
+fn bandwidth_budget_flag() {
+    CommandLineTest::new()
+        .flag("bandwidth-budget", Some("12.5"))
+        .run_with_zero_port()
+        .with_config(|config| {
+            assert_eq!(config.network.bandwidth_budget_mbps, Some(12.5));
+        });
+}
+#[test]
+fn bandwidth_budget_default() {
+    CommandLineTest::new()
+        .run_with_zero_port()
+        .with_config(|config| {
+            assert_eq!(config.network.bandwidth_budget_mbps, None);
+        });
+}
+#[test]
+#[should_panic]
+fn bandwidth_budget_flag_zero() {
+    CommandLineTest::new()
+        .flag("bandwidth-budget", Some("0"))
+        .run_with_zero_port();
+}
 
 // Tests for ENR flags.
 #[test]
@@ -2595,6 +2934,49 @@ fn invalid_gossip_verified_blocks_path() {
         });
 }
 
+#[test]
+fn network_capture_default() {
+    CommandLineTest::new()
+        .run_with_zero_port()
+        .with_config(|config| assert_eq!(config.network.network_capture, None));
+}
+
+#[test]
+fn network_capture_dir() {
+    let path = "/tmp/network-capture";
+    CommandLineTest::new()
+        .flag("network-capture-dir", Some(path))
+        .run_with_zero_port()
+        .with_config(|config| {
+            assert_eq!(
+                config.network.network_capture,
+                Some(CaptureConfig::new(PathBuf::from(path)))
+            )
+        });
+}
+
+#[test]
+fn network_capture_limits() {
+    CommandLineTest::new()
+        .flag("network-capture-dir", Some("/tmp/network-capture"))
+        .flag("network-capture-max-file-size", Some("4"))
+        .flag("network-capture-max-files", Some("2"))
+        .run_with_zero_port()
+        .with_config(|config| {
+            let capture_config = config.network.network_capture.as_ref().unwrap();
+            assert_eq!(capture_config.max_file_size, 4 * 1024 * 1024);
+            assert_eq!(capture_config.max_files, 2);
+        });
+}
+#[test]
+#[should_panic]
+fn network_capture_zero_max_file_size() {
+    CommandLineTest::new()
+        .flag("network-capture-dir", Some("/tmp/network-capture"))
+        .flag("network-capture-max-file-size", Some("0"))
+        .run_with_zero_port();
+}
+
 #[test]
 fn beacon_processor() {
     CommandLineTest::new()
@@ -2618,7 +3000,9 @@ fn beacon_processor() {
                     max_scheduled_work_queue_len: 3,
                     max_gossip_attestation_batch_size: 4,
                     max_gossip_aggregate_batch_size: 5,
-                    enable_backfill_rate_limiting: false
+                    enable_backfill_rate_limiting: false,
+                    backfill_schedule_in_slot: BeaconProcessorConfig::default()
+                        .backfill_schedule_in_slot,
                 }
             )
         });
@@ -2648,6 +3032,43 @@ fn http_sse_capacity_multiplier_override() {
         .with_config(|config| assert_eq!(config.http_api.sse_capacity_multiplier, 10));
 }
 
+#[test]
+fn http_sse_replay_buffer_size_default() {
+    CommandLineTest::new()
+        .run_with_zero_port()
+        .with_config(|config| assert_eq!(config.http_api.sse_replay_buffer_size, 0));
+}
+
+#[test]
+fn http_sse_replay_buffer_size_override() {
+    CommandLineTest::new()
+        .flag("http", None)
+        .flag("http-sse-replay-buffer-size", Some("64"))
+        .run_with_zero_port()
+        .with_config(|config| assert_eq!(config.http_api.sse_replay_buffer_size, 64));
+}
+
+#[test]
+fn http_access_control_file_default() {
+    CommandLineTest::new()
+        .run_with_zero_port()
+        .with_config(|config| assert_eq!(config.http_api.access_control_file, None));
+}
+
+#[test]
+fn http_access_control_file_flag() {
+    CommandLineTest::new()
+        .flag("http", None)
+        .flag("http-access-control-file", Some("/tmp/access.yaml"))
+        .run_with_zero_port()
+        .with_config(|config| {
+            assert_eq!(
+                config.http_api.access_control_file,
+                Some(PathBuf::from("/tmp/access.yaml"))
+            )
+        });
+}
+
 #[test]
 fn http_duplicate_block_status_default() {
     CommandLineTest::new()